@@ -36,7 +36,7 @@ pub fn get_gc_stats() -> GcStats {
     typthon_runtime::gc::get_stats()
 }
 
-/// Get current allocator statistics
+/// Get heap statistics aggregated across all thread-local allocators
 pub fn get_heap_stats() -> HeapStats {
     typthon_runtime::allocator::get_stats()
 }
@@ -3,6 +3,7 @@
 //! Main library entry point that exposes the typhon Python package.
 
 // Internal module structure (for organization)
+#[doc(hidden)]
 pub mod internal {
     pub mod core;
     pub mod compiler;
@@ -7,32 +7,231 @@
 use crate::{
     TypeChecker, Type, TypeContext,
     parse_module,
-    compiler::analysis::{EffectAnalyzer, checker::TypeError as CheckerTypeError},
+    compiler::analysis::{EffectAnalyzer, CheckerOptions, RefinementAnalyzer, checker::TypeError as CheckerTypeError},
+    compiler::types::{OverrideOrigin, SignatureOverride, SignatureOverrides, overrides::parse_member_path},
+    compiler::errors::SourceLocation,
+    compiler::ast::LineIndex,
 };
 
 use std::path::Path as StdPath;
+use std::sync::{Arc, Mutex, OnceLock};
 
 /// High-level API for type checking a Python file
 pub fn check_file<P: AsRef<StdPath>>(path: P) -> Result<Vec<CheckerTypeError>, String> {
+    check_file_with(path, CheckerOptions::default())
+}
+
+/// Type check a Python file with explicit settings
+pub fn check_file_with<P: AsRef<StdPath>>(path: P, options: CheckerOptions) -> Result<Vec<CheckerTypeError>, String> {
     let source = std::fs::read_to_string(path.as_ref())
         .map_err(|e| e.to_string())?;
 
     let ast = parse_module(&source)
         .map_err(|e| e.to_string())?;
 
-    let mut checker = TypeChecker::new();
+    let mut checker = session_checker(options);
     Ok(checker.check(&ast))
 }
 
+/// A problem found in a checked module, with the extent of the code it's
+/// about. Lines are 1-based and columns 0-based; a problem about the module
+/// as a whole is at line 0
+#[cfg_attr(feature = "python", pyclass(get_all))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub col: usize,
+    pub end_line: usize,
+    pub end_col: usize,
+    /// Error code such as `TYP001`, or lint code such as `S001`
+    pub code: Option<String>,
+    /// `"error"` or `"warning"`
+    pub severity: String,
+    pub message: String,
+    /// Likely fixes, e.g. the names a misspelled one may have meant
+    pub suggestions: Vec<String>,
+}
+
+/// Type check and lint the Python file at `path`, with positions
+pub fn check_file_detailed<P: AsRef<StdPath>>(path: P) -> Result<Vec<Diagnostic>, String> {
+    let source = std::fs::read_to_string(path.as_ref())
+        .map_err(|e| e.to_string())?;
+    check_source_detailed_with(&source, Some(&path.as_ref().display().to_string()), CheckerOptions::default())
+}
+
+/// Type check and lint `source`, with positions; `filename`, when given,
+/// names the module in messages and anchors its relative imports
+pub fn check_source_detailed(source: &str, filename: Option<&str>) -> Result<Vec<Diagnostic>, String> {
+    check_source_detailed_with(source, filename, CheckerOptions::default())
+}
+
+/// `check_source_detailed` with explicit settings
+pub fn check_source_detailed_with(source: &str, filename: Option<&str>, options: CheckerOptions) -> Result<Vec<Diagnostic>, String> {
+    let ast = parse_module(source)
+        .map_err(|e| e.to_string())?;
+
+    let mut checker = session_checker(options);
+    match filename {
+        Some(filename) => {
+            checker.set_source_name(filename);
+            checker.set_source_file(StdPath::new(filename), source);
+        }
+        None => checker.set_source(source),
+    }
+    let errors = checker.check(&ast);
+    let lints = checker.lint(&ast);
+    let index = LineIndex::new(source);
+
+    let mut diagnostics: Vec<Diagnostic> = errors.into_iter()
+        .map(|e| {
+            let (end_line, end_col) = e.range.as_ref().map_or((e.line, e.col), |range| index.offset_to_position(range.end));
+            Diagnostic {
+                line: e.line,
+                col: e.col,
+                end_line,
+                end_col,
+                code: e.code.map(str::to_string),
+                severity: e.severity.to_string(),
+                message: e.message,
+                suggestions: e.suggestions,
+            }
+        })
+        .collect();
+    diagnostics.extend(lints.into_iter().map(|lint| {
+        let location = lint.location(&index);
+        Diagnostic {
+            line: location.line,
+            col: location.col,
+            end_line: location.end_line,
+            end_col: location.end_col,
+            code: Some(lint.code.to_string()),
+            severity: lint.severity().to_string(),
+            message: lint.message,
+            suggestions: lint.fix.map(|fix| vec![fix.title]).unwrap_or_default(),
+        }
+    }));
+    Ok(diagnostics)
+}
+
 /// High-level API for type inference on source code
 pub fn infer_types(source: &str) -> Result<Type, String> {
+    infer_types_with(source, CheckerOptions::default())
+}
+
+/// Infer types with explicit settings
+pub fn infer_types_with(source: &str, options: CheckerOptions) -> Result<Type, String> {
     let ast = parse_module(source)
         .map_err(|e| e.to_string())?;
 
-    let mut checker = TypeChecker::new();
+    let mut checker = session_checker(options);
     Ok(checker.infer(&ast))
 }
 
+/// Every top-level and function-local binding in `source` with its
+/// inferred type and where it's first bound (see `TypeChecker::inferred_bindings`)
+pub fn infer_module(source: &str) -> Result<Vec<(String, Type, SourceLocation)>, String> {
+    infer_module_with(source, CheckerOptions::default())
+}
+
+/// Infer a module's bindings with explicit settings
+pub fn infer_module_with(source: &str, options: CheckerOptions) -> Result<Vec<(String, Type, SourceLocation)>, String> {
+    let ast = parse_module(source)
+        .map_err(|e| e.to_string())?;
+
+    let mut checker = session_checker(options);
+    checker.set_source(source);
+    checker.check(&ast);
+    Ok(checker.inferred_bindings())
+}
+
+/// Option names accepted by `typthon.configure()`
+pub const CONFIG_OPTIONS: &[&str] = &[
+    "allow_untyped_defs", "disabled_plugins", "disallow_any", "disallow_untyped_defs", "erase_unknown_decorators",
+    "max_errors", "python_version", "strict", "strict_optional", "stub_paths", "warn_unused_ignores",
+    "warn_unused_variables",
+];
+
+/// Process-wide checker settings for the Python bindings
+///
+/// `typthon.configure()` may change them until the first check reads them;
+/// from then on they are frozen so every check in the process agrees.
+pub struct BindingsConfig {
+    state: Mutex<(CheckerOptions, bool)>,
+}
+
+impl BindingsConfig {
+    pub fn global() -> &'static Self {
+        static CONFIG: OnceLock<BindingsConfig> = OnceLock::new();
+        CONFIG.get_or_init(|| Self { state: Mutex::new((CheckerOptions::default(), false)) })
+    }
+
+    /// Apply `update` to the settings; fails once they are frozen
+    pub fn update(&self, update: impl FnOnce(&mut CheckerOptions)) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let (options, frozen) = &mut *state;
+        if *frozen {
+            return Err("typthon.configure() must be called before the first check; \
+                        settings are fixed once checking has started".to_string());
+        }
+
+        update(options);
+        Ok(())
+    }
+
+    /// Settings for a check; freezes the configuration
+    pub fn options_for_check(&self) -> CheckerOptions {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.1 = true;
+        state.0.clone()
+    }
+
+    /// Current settings, without freezing them
+    pub fn snapshot(&self) -> CheckerOptions {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).0.clone()
+    }
+}
+
+/// Signature overrides shared by every check in the process
+pub fn session_overrides() -> &'static Arc<SignatureOverrides> {
+    static OVERRIDES: OnceLock<Arc<SignatureOverrides>> = OnceLock::new();
+    OVERRIDES.get_or_init(|| Arc::new(SignatureOverrides::new()))
+}
+
+/// A checker whose context sees the session's signature overrides
+fn session_checker(options: CheckerOptions) -> TypeChecker {
+    let ctx = Arc::new(TypeContext::with_overrides(session_overrides().clone()));
+    TypeChecker::with_context(ctx).with_options(options)
+}
+
+/// Shadow the member at dotted `path` with `annotation` for the rest of the session
+pub fn patch_signature(path: &str, annotation: &str) -> Result<(), String> {
+    let (class, member) = parse_member_path(path)?;
+    let ty = TypeChecker::new().parse_annotation(annotation)?;
+    session_overrides().set(&class, &member, ty, OverrideOrigin::Session);
+    Ok(())
+}
+
+/// Active session overrides, ordered by class then member
+pub fn signature_patches() -> Vec<SignatureOverride> {
+    session_overrides().list()
+}
+
+/// Remove the override at `path`, or all of them; returns whether any were removed
+pub fn clear_signature_patches(path: Option<&str>) -> Result<bool, String> {
+    let overrides = session_overrides();
+    match path {
+        Some(path) => {
+            let (class, member) = parse_member_path(path)?;
+            Ok(overrides.remove(&class, &member))
+        }
+        None => {
+            let had_any = !overrides.is_empty();
+            overrides.clear();
+            Ok(had_any)
+        }
+    }
+}
+
 /// High-level API for effect analysis
 pub fn analyze_effects(source: &str) -> Result<std::collections::HashMap<String, String>, String> {
     let ast = parse_module(source)
@@ -54,6 +253,17 @@ pub fn analyze_effects(source: &str) -> Result<std::collections::HashMap<String,
         .collect())
 }
 
+/// High-level API for refinement validation: whether the JSON `value`
+/// satisfies `predicate`, e.g. `value > 0 and value < 100`
+pub fn validate_refinement(value: &str, predicate: &str) -> Result<bool, String> {
+    let value: serde_json::Value = serde_json::from_str(value)
+        .map_err(|e| format!("Invalid JSON value: {}", e))?;
+
+    let analyzer = RefinementAnalyzer::new();
+    let predicate = analyzer.parse_predicate(predicate)?;
+    Ok(analyzer.validate(&value, &predicate))
+}
+
 // Python bindings
 #[cfg(feature = "python")]
 use pyo3::prelude::*;
@@ -61,31 +271,75 @@ use pyo3::prelude::*;
 #[cfg(feature = "python")]
 #[pyfunction]
 fn check_file_py(path: String) -> PyResult<Vec<String>> {
-    check_file(&path)
+    check_file_with(&path, BindingsConfig::global().options_for_check())
         .map(|errors| errors.iter().map(|e| e.to_string()).collect())
         .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(e))
 }
 
+/// `typthon.check_file_detailed(path)`: the file's problems as `Diagnostic`s
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(name = "check_file_detailed")]
+fn check_file_detailed_py(path: String) -> PyResult<Vec<Diagnostic>> {
+    let source = std::fs::read_to_string(&path)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(e.to_string()))?;
+    check_source_detailed_with(&source, Some(&path), BindingsConfig::global().options_for_check())
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PySyntaxError, _>(e))
+}
+
+/// `typthon.check_source_detailed(source, filename=None)`: like
+/// `check_file_detailed`, for source text
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(name = "check_source_detailed", signature = (source, filename=None))]
+fn check_source_detailed_py(source: &str, filename: Option<&str>) -> PyResult<Vec<Diagnostic>> {
+    check_source_detailed_with(source, filename, BindingsConfig::global().options_for_check())
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PySyntaxError, _>(e))
+}
+
+#[cfg(feature = "python")]
+#[pymethods]
+impl Diagnostic {
+    fn __repr__(&self) -> String {
+        format!(
+            "Diagnostic(line={}, col={}, end_line={}, end_col={}, code={:?}, severity={:?}, message={:?})",
+            self.line, self.col, self.end_line, self.end_col, self.code, self.severity, self.message
+        )
+    }
+}
+
 #[cfg(feature = "python")]
 #[pyfunction]
 fn infer_types_py(source: String) -> PyResult<String> {
-    infer_types(&source)
+    infer_types_with(&source, BindingsConfig::global().options_for_check())
         .map(|ty| format!("{}", ty))
         .map_err(|e| PyErr::new::<pyo3::exceptions::PySyntaxError, _>(e))
 }
 
+/// `typthon.infer_module(source)`: `{"x": "int", "greet": "(str) -> str", ...}`,
+/// with names local to a function qualified by it, e.g. `greet.name`
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(name = "infer_module")]
+fn infer_module_py(source: String) -> PyResult<std::collections::BTreeMap<String, String>> {
+    infer_module_with(&source, BindingsConfig::global().options_for_check())
+        .map(|bindings| bindings.into_iter().map(|(name, ty, _)| (name, ty.to_string())).collect())
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PySyntaxError, _>(e))
+}
+
 #[cfg(feature = "python")]
 #[pyfunction]
 fn analyze_effects_py(source: String) -> PyResult<std::collections::HashMap<String, String>> {
+    BindingsConfig::global().options_for_check();
     analyze_effects(&source)
         .map_err(|e| PyErr::new::<pyo3::exceptions::PySyntaxError, _>(e))
 }
 
 #[cfg(feature = "python")]
 #[pyfunction]
-fn validate_refinement_py(_value: String, _predicate: String) -> PyResult<bool> {
-    // RefinementAnalyzer integration pending
-    Ok(true)
+fn validate_refinement_py(value: String, predicate: String) -> PyResult<bool> {
+    validate_refinement(&value, &predicate)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e))
 }
 
 #[cfg(feature = "python")]
@@ -99,7 +353,7 @@ pub struct TypeValidator {
 impl TypeValidator {
     #[new]
     fn new() -> Self {
-        Self { checker: TypeChecker::new() }
+        Self { checker: session_checker(BindingsConfig::global().options_for_check()) }
     }
 
     fn validate(&mut self, source: String) -> PyResult<bool> {
@@ -118,6 +372,13 @@ impl TypeValidator {
         Ok(format!("{:?}", ty))
     }
 
+    /// Types of the bindings in the source last given to `validate`, by name
+    fn get_all_types(&self) -> std::collections::BTreeMap<String, String> {
+        self.checker.inferred_bindings().into_iter()
+            .map(|(name, ty, _)| (name, ty.to_string()))
+            .collect()
+    }
+
     fn get_function_effects(&self, name: String) -> PyResult<Vec<String>> {
         if let Some(effects) = self.checker.get_function_effects(&name) {
             if effects.is_pure() {
@@ -137,6 +398,145 @@ impl TypeValidator {
                 format!("Function '{}' not found", name)
             ))
     }
+
+    fn validate_refinement_value(&self, value: String, predicate: String) -> PyResult<bool> {
+        validate_refinement_py(value, predicate)
+    }
+}
+
+/// `typthon.configure(**options)`: set checker options for every later check
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (**options))]
+fn configure(options: Option<&pyo3::types::PyDict>) -> PyResult<()> {
+    use pyo3::exceptions::{PyRuntimeError, PyTypeError, PyValueError};
+
+    let Some(options) = options else { return Ok(()) };
+
+    // Convert every value up front so a bad one leaves the settings untouched
+    let mut setters: Vec<Box<dyn FnOnce(&mut CheckerOptions)>> = Vec::new();
+    for (key, value) in options.iter() {
+        let key: String = key.extract()?;
+        let setter: Box<dyn FnOnce(&mut CheckerOptions)> = match key.as_str() {
+            "strict" => {
+                let strict: bool = value.extract()?;
+                Box::new(move |options| options.strict = strict)
+            }
+            "allow_untyped_defs" => {
+                let allow: bool = value.extract()?;
+                Box::new(move |options| options.allow_untyped_defs = allow)
+            }
+            "disallow_untyped_defs" => {
+                let disallow: bool = value.extract()?;
+                Box::new(move |options| options.disallow_untyped_defs = disallow)
+            }
+            "disallow_any" => {
+                let disallow: bool = value.extract()?;
+                Box::new(move |options| options.disallow_any = disallow)
+            }
+            "erase_unknown_decorators" => {
+                let erase: bool = value.extract()?;
+                Box::new(move |options| options.erase_unknown_decorators = erase)
+            }
+            "strict_optional" => {
+                let strict_optional: bool = value.extract()?;
+                Box::new(move |options| options.strict_optional = strict_optional)
+            }
+            "warn_unused_ignores" => {
+                let warn: bool = value.extract()?;
+                Box::new(move |options| options.warn_unused_ignores = warn)
+            }
+            "warn_unused_variables" => {
+                let warn: bool = value.extract()?;
+                Box::new(move |options| options.warn_unused_variables = warn)
+            }
+            "python_version" => {
+                let version = CheckerOptions::parse_python_version(value.extract()?)
+                    .map_err(PyValueError::new_err)?;
+                Box::new(move |options| options.python_version = version)
+            }
+            "stub_paths" => {
+                let paths: Vec<String> = value.extract()?;
+                Box::new(move |options| options.stub_paths = paths.into_iter().map(Into::into).collect())
+            }
+            "max_errors" => {
+                let max: Option<usize> = value.extract()?;
+                Box::new(move |options| options.max_errors = max)
+            }
+            "disabled_plugins" => {
+                let plugins: Vec<String> = value.extract()?;
+                Box::new(move |options| options.disabled_plugins = plugins)
+            }
+            _ => {
+                return Err(PyTypeError::new_err(format!(
+                    "configure() got an unexpected option '{}'; valid options are: {}",
+                    key, CONFIG_OPTIONS.join(", ")
+                )));
+            }
+        };
+        setters.push(setter);
+    }
+
+    BindingsConfig::global()
+        .update(|options| {
+            for setter in setters {
+                setter(options);
+            }
+        })
+        .map_err(PyRuntimeError::new_err)
+}
+
+/// `typthon.get_configuration()`: the effective settings as a dict
+#[cfg(feature = "python")]
+#[pyfunction]
+fn get_configuration(py: Python) -> PyResult<PyObject> {
+    let options = BindingsConfig::global().snapshot();
+    let (major, minor) = options.python_version;
+
+    let dict = pyo3::types::PyDict::new(py);
+    dict.set_item("strict", options.strict)?;
+    dict.set_item("allow_untyped_defs", options.allow_untyped_defs)?;
+    dict.set_item("disallow_untyped_defs", options.disallow_untyped_defs)?;
+    dict.set_item("disallow_any", options.disallow_any)?;
+    dict.set_item("erase_unknown_decorators", options.erase_unknown_decorators)?;
+    dict.set_item("strict_optional", options.strict_optional)?;
+    dict.set_item("warn_unused_ignores", options.warn_unused_ignores)?;
+    dict.set_item("warn_unused_variables", options.warn_unused_variables)?;
+    dict.set_item("python_version", format!("{}.{}", major, minor))?;
+    dict.set_item(
+        "stub_paths",
+        options.stub_paths.iter().map(|p| p.display().to_string()).collect::<Vec<_>>(),
+    )?;
+    dict.set_item("max_errors", options.max_errors)?;
+    dict.set_item("disabled_plugins", options.disabled_plugins)?;
+    Ok(dict.into())
+}
+
+/// `typthon.patch_signature(path, annotation)`: override a member signature,
+/// e.g. `patch_signature("builtins.str.find", "Callable[[str], Optional[int]]")`
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(name = "patch_signature")]
+fn patch_signature_py(path: &str, annotation: &str) -> PyResult<()> {
+    patch_signature(path, annotation).map_err(pyo3::exceptions::PyValueError::new_err)
+}
+
+/// `typthon.list_signature_patches()`: `{"str.find": "(str) -> int | None", ...}`
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(name = "list_signature_patches")]
+fn list_signature_patches_py() -> std::collections::BTreeMap<String, String> {
+    signature_patches().into_iter()
+        .map(|patch| (format!("{}.{}", patch.class, patch.member), patch.ty.to_string()))
+        .collect()
+}
+
+/// `typthon.clear_signature_patches(path=None)`: drop one override or all of them
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(name = "clear_signature_patches", signature = (path=None))]
+fn clear_signature_patches_py(path: Option<&str>) -> PyResult<bool> {
+    clear_signature_patches(path).map_err(pyo3::exceptions::PyValueError::new_err)
 }
 
 #[cfg(feature = "python")]
@@ -147,6 +547,14 @@ pub struct RuntimeStats {
     #[pyo3(get)]
     pub heap_allocated: usize,
     #[pyo3(get)]
+    pub heap_in_use: usize,
+    #[pyo3(get)]
+    pub heap_peak: usize,
+    #[pyo3(get)]
+    pub arena_count: usize,
+    #[pyo3(get)]
+    pub allocation_count: usize,
+    #[pyo3(get)]
     pub cache_hits: usize,
     #[pyo3(get)]
     pub cache_misses: usize,
@@ -163,7 +571,6 @@ fn get_runtime_stats() -> RuntimeStats {
     // Static metrics tracking
     use std::sync::atomic::{AtomicUsize, Ordering};
     static GC_COUNT: AtomicUsize = AtomicUsize::new(0);
-    static HEAP_ALLOCATED: AtomicUsize = AtomicUsize::new(0);
     static CACHE_HITS: AtomicUsize = AtomicUsize::new(0);
     static CACHE_MISSES: AtomicUsize = AtomicUsize::new(0);
 
@@ -172,9 +579,15 @@ fn get_runtime_stats() -> RuntimeStats {
     let start = START_TIME.get_or_init(SystemTime::now);
     let uptime = SystemTime::now().duration_since(*start).unwrap_or_default();
 
+    let heap = typthon_runtime::allocator::get_stats();
+
     RuntimeStats {
         gc_collections: GC_COUNT.load(Ordering::Relaxed),
-        heap_allocated: HEAP_ALLOCATED.load(Ordering::Relaxed),
+        heap_allocated: heap.total_allocated,
+        heap_in_use: heap.bytes_in_use,
+        heap_peak: heap.peak_usage,
+        arena_count: heap.arena_count,
+        allocation_count: heap.allocation_count,
         cache_hits: CACHE_HITS.load(Ordering::Relaxed),
         cache_misses: CACHE_MISSES.load(Ordering::Relaxed),
         uptime_secs: uptime.as_secs(),
@@ -210,12 +623,10 @@ fn clear_cache_py() -> PyResult<String> {
     // Reset static counters
     use std::sync::atomic::{AtomicUsize, Ordering};
     static GC_COUNT: AtomicUsize = AtomicUsize::new(0);
-    static HEAP_ALLOCATED: AtomicUsize = AtomicUsize::new(0);
     static CACHE_HITS: AtomicUsize = AtomicUsize::new(0);
     static CACHE_MISSES: AtomicUsize = AtomicUsize::new(0);
 
     GC_COUNT.store(0, Ordering::Relaxed);
-    HEAP_ALLOCATED.store(0, Ordering::Relaxed);
     CACHE_HITS.store(0, Ordering::Relaxed);
     CACHE_MISSES.store(0, Ordering::Relaxed);
 
@@ -227,7 +638,6 @@ fn clear_cache_py() -> PyResult<String> {
 fn get_metrics_py() -> PyResult<std::collections::HashMap<String, String>> {
     use std::sync::atomic::{AtomicUsize, Ordering};
     static GC_COUNT: AtomicUsize = AtomicUsize::new(0);
-    static HEAP_ALLOCATED: AtomicUsize = AtomicUsize::new(0);
     static CACHE_HITS: AtomicUsize = AtomicUsize::new(0);
     static CACHE_MISSES: AtomicUsize = AtomicUsize::new(0);
 
@@ -240,7 +650,10 @@ fn get_metrics_py() -> PyResult<std::collections::HashMap<String, String>> {
     result.insert("uptime".to_string(), uptime.as_secs().to_string());
     result.insert("uptime_secs".to_string(), uptime.as_secs().to_string());  // Keep both for compatibility
     result.insert("gc_collections".to_string(), GC_COUNT.load(Ordering::Relaxed).to_string());
-    result.insert("heap_allocated".to_string(), HEAP_ALLOCATED.load(Ordering::Relaxed).to_string());
+    let heap = typthon_runtime::allocator::get_stats();
+    result.insert("heap_allocated".to_string(), heap.total_allocated.to_string());
+    result.insert("heap_in_use".to_string(), heap.bytes_in_use.to_string());
+    result.insert("heap_peak".to_string(), heap.peak_usage.to_string());
     result.insert("cache_hits".to_string(), CACHE_HITS.load(Ordering::Relaxed).to_string());
     result.insert("cache_misses".to_string(), CACHE_MISSES.load(Ordering::Relaxed).to_string());
 
@@ -252,10 +665,21 @@ fn get_metrics_py() -> PyResult<std::collections::HashMap<String, String>> {
 fn typthon(_py: Python, m: &PyModule) -> PyResult<()> {
     // Type checking and inference
     m.add_function(wrap_pyfunction!(check_file_py, m)?)?;
+    m.add_function(wrap_pyfunction!(check_file_detailed_py, m)?)?;
+    m.add_function(wrap_pyfunction!(check_source_detailed_py, m)?)?;
     m.add_function(wrap_pyfunction!(infer_types_py, m)?)?;
+    m.add_function(wrap_pyfunction!(infer_module_py, m)?)?;
     m.add_function(wrap_pyfunction!(analyze_effects_py, m)?)?;
     m.add_function(wrap_pyfunction!(validate_refinement_py, m)?)?;
     m.add_class::<TypeValidator>()?;
+    m.add_class::<Diagnostic>()?;
+
+    // Configuration
+    m.add_function(wrap_pyfunction!(configure, m)?)?;
+    m.add_function(wrap_pyfunction!(get_configuration, m)?)?;
+    m.add_function(wrap_pyfunction!(patch_signature_py, m)?)?;
+    m.add_function(wrap_pyfunction!(list_signature_patches_py, m)?)?;
+    m.add_function(wrap_pyfunction!(clear_signature_patches_py, m)?)?;
 
     // Runtime management
     m.add_function(wrap_pyfunction!(init_runtime_py, m)?)?;
@@ -75,6 +75,15 @@ def add(x: int, y: int):
 """
         assert validator.validate(code)
 
+    def test_inferred_return_in_function_type(self, validator):
+        """Test that an unannotated function's type shows what it returns."""
+        code = """
+def double(x: int):
+    return x * 2
+"""
+        assert validator.validate(code)
+        assert validator.get_function_type("double") == "(int) -> int"
+
     def test_string_return_inference(self, validator):
         """Test inference of string return."""
         code = """
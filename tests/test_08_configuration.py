@@ -0,0 +1,77 @@
+"""Test global checker configuration through typthon.configure().
+
+Settings are process-wide and freeze on the first check, so each scenario
+runs in a fresh interpreter.
+"""
+
+import subprocess
+import sys
+import textwrap
+
+import pytest
+
+
+def run_isolated(script: str) -> subprocess.CompletedProcess:
+    """Run a snippet in a new interpreter so configuration starts clean."""
+    return subprocess.run(
+        [sys.executable, "-c", textwrap.dedent(script)],
+        capture_output=True,
+        text=True,
+    )
+
+
+@pytest.mark.requires_typhon
+@pytest.mark.unit
+class TestConfigure:
+    """Test typthon.configure() and typthon.get_configuration()."""
+
+    def test_strict_mode_rejects_untyped_defs(self):
+        """A snippet that passes by default fails once strict mode is on."""
+        result = run_isolated("""
+            import typthon
+
+            code = "def add(x, y):\\n    return x + y\\n"
+            typthon.configure(strict=True, python_version="3.11", max_errors=10)
+            assert typthon.get_configuration()["strict"] is True
+            assert typthon.get_configuration()["python_version"] == "3.11"
+            assert not typthon.TypeValidator().validate(code)
+        """)
+        assert result.returncode == 0, result.stderr
+
+        default = run_isolated("""
+            import typthon
+            assert typthon.TypeValidator().validate("def add(x, y):\\n    return x + y\\n")
+        """)
+        assert default.returncode == 0, default.stderr
+
+    def test_reconfigure_after_first_check_raises(self):
+        """Settings are frozen once a check has run."""
+        result = run_isolated("""
+            import typthon
+
+            typthon.configure(strict=False)
+            typthon.TypeValidator().validate("x: int = 1")
+            try:
+                typthon.configure(strict=True)
+            except RuntimeError as e:
+                assert "before the first check" in str(e)
+            else:
+                raise AssertionError("late configure() did not raise")
+            assert typthon.get_configuration()["strict"] is False
+        """)
+        assert result.returncode == 0, result.stderr
+
+    def test_unknown_option_lists_valid_options(self):
+        """Unknown keys raise TypeError naming the accepted options."""
+        result = run_isolated("""
+            import typthon
+
+            try:
+                typthon.configure(strictness=True)
+            except TypeError as e:
+                assert "strictness" in str(e)
+                assert "python_version" in str(e)
+            else:
+                raise AssertionError("unknown option did not raise")
+        """)
+        assert result.returncode == 0, result.stderr
@@ -0,0 +1,71 @@
+"""Test session-scoped signature overrides through typthon.patch_signature().
+
+Patches are process-wide, so each scenario runs in a fresh interpreter.
+"""
+
+import subprocess
+import sys
+import textwrap
+
+import pytest
+
+
+def run_isolated(script: str) -> subprocess.CompletedProcess:
+    """Run a snippet in a new interpreter so no patches leak between tests."""
+    return subprocess.run(
+        [sys.executable, "-c", textwrap.dedent(script)],
+        capture_output=True,
+        text=True,
+    )
+
+
+@pytest.mark.requires_typhon
+@pytest.mark.unit
+class TestPatchSignature:
+    """Test typthon.patch_signature() and its list/clear companions."""
+
+    def test_patch_adds_diagnostics_until_cleared(self):
+        """Overriding str.find to return Optional[int] breaks an int-returning caller."""
+        result = run_isolated("""
+            import os
+            import tempfile
+            import typthon
+
+            code = 'def first_dot(s: str) -> int:\\n    return s.find(".")\\n'
+            validator = typthon.TypeValidator()
+            assert validator.validate(code)
+
+            typthon.patch_signature("builtins.str.find", "Callable[[str], Optional[int]]")
+            assert list(typthon.list_signature_patches()) == ["str.find"]
+            assert not validator.validate(code)
+
+            with tempfile.NamedTemporaryFile("w", suffix=".py", delete=False) as f:
+                f.write(code)
+            try:
+                errors = typthon.check_file_py(f.name)
+                assert any("note: signature overridden for this session (str.find)" in e for e in errors), errors
+
+                assert typthon.clear_signature_patches("str.find") is True
+                assert typthon.list_signature_patches() == {}
+                assert typthon.check_file_py(f.name) == []
+                assert validator.validate(code)
+            finally:
+                os.unlink(f.name)
+        """)
+        assert result.returncode == 0, result.stderr
+
+    def test_invalid_patches_raise_value_error(self):
+        """Bad paths and unparsable annotations are rejected."""
+        result = run_isolated("""
+            import typthon
+
+            for path, annotation in [("find", "int"), ("str.find", "Callable[[")]:
+                try:
+                    typthon.patch_signature(path, annotation)
+                except ValueError:
+                    pass
+                else:
+                    raise AssertionError(f"{path!r} = {annotation!r} did not raise")
+            assert typthon.clear_signature_patches() is False
+        """)
+        assert result.returncode == 0, result.stderr
@@ -59,6 +59,55 @@ class TestRustExtensionIntegration:
         result = infer_types_py("x = 5")
         assert isinstance(result, str)
 
+    def test_check_file_detailed(self):
+        """Test check_file_detailed returns positioned Diagnostic objects."""
+        from typthon.typthon import Diagnostic, check_file_detailed
+
+        test_file = Path(__file__).parent / "temp_detailed.py"
+        test_file.write_text('count: int = "three"\n')
+
+        try:
+            diagnostics = check_file_detailed(str(test_file))
+            assert len(diagnostics) >= 1
+            diagnostic = diagnostics[0]
+            assert isinstance(diagnostic, Diagnostic)
+            assert (diagnostic.line, diagnostic.col) == (1, 13)
+            assert (diagnostic.end_line, diagnostic.end_col) == (1, 20)
+            assert diagnostic.code == "TYP001"
+            assert diagnostic.severity == "error"
+            assert "str" in diagnostic.message
+            assert diagnostic.suggestions == []
+        finally:
+            test_file.unlink(missing_ok=True)
+
+    def test_check_source_detailed(self):
+        """Test check_source_detailed carries suggestions."""
+        from typthon.typthon import check_source_detailed
+
+        diagnostics = check_source_detailed('"x".uper()\n', filename="example.py")
+        assert [d.suggestions for d in diagnostics] == [["upper"]]
+        assert diagnostics[0].code == "TYP012"
+
+        with pytest.raises(SyntaxError):
+            check_source_detailed("def broken(:\n")
+
+    def test_infer_module(self):
+        """Test infer_module maps each binding to its type."""
+        from typthon.typthon import infer_module
+
+        source = "x = 5\n\ndef greet(name: str) -> str:\n    return 'Hello, ' + name\n"
+        types = infer_module(source)
+        assert {"x": "int", "greet": "(str) -> str"}.items() <= types.items()
+        assert types["greet.name"] == "str"
+
+    def test_type_validator_get_all_types(self):
+        """Test TypeValidator.get_all_types after validating a module."""
+        from typthon.typthon import TypeValidator
+
+        validator = TypeValidator()
+        assert validator.validate("x = 5\ny = 'a'\n")
+        assert validator.get_all_types() == {"x": "int", "y": "str"}
+
     def test_analyze_effects_py(self):
         """Test analyze_effects_py function."""
         from typthon.typthon import analyze_effects_py
@@ -70,8 +119,11 @@ class TestRustExtensionIntegration:
         """Test validate_refinement_py function."""
         from typthon.typthon import validate_refinement_py
 
-        result = validate_refinement_py("5", "x > 0")
-        assert isinstance(result, bool)
+        assert validate_refinement_py("5", "value > 0 and value < 10") is True
+        assert validate_refinement_py('"ab"', "len(value) >= 3") is False
+
+        with pytest.raises(ValueError, match="position 0"):
+            validate_refinement_py("5", "x > 0")
 
 
 class TestRuntimeFunctions:
@@ -100,6 +152,8 @@ class TestRuntimeFunctions:
 
         assert isinstance(stats.gc_collections, int)
         assert isinstance(stats.heap_allocated, int)
+        assert stats.heap_in_use <= stats.heap_allocated
+        assert stats.heap_peak >= stats.heap_in_use
         assert stats.uptime_secs >= 0
 
     def test_force_gc(self):
@@ -152,6 +206,7 @@ class TestTopLevelAPI:
 
         assert hasattr(typthon, "check_file_py")
         assert hasattr(typthon, "infer_types_py")
+        assert hasattr(typthon, "infer_module")
         assert hasattr(typthon, "analyze_effects_py")
         assert hasattr(typthon, "init_runtime_py")
         assert hasattr(typthon, "get_runtime_stats")
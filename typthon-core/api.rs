@@ -0,0 +1,149 @@
+//! Supported API for embedding the checker
+//!
+//! Build tools and other language servers should depend on this module only.
+//! Everything else the crate exports is internal: it is reachable for the
+//! CLI, the language server and the bindings, but changes without notice.
+//! The items here keep their signatures across minor versions; the exported
+//! list is locked by `tests/test_public_api.rs`.
+//!
+//! ```
+//! use typthon::api::{Config, Session, Severity};
+//!
+//! let mut session = Session::new(Config::default(), ".").unwrap();
+//! let diagnostics = session.check_source("example.py", "x: int = 'a'\n");
+//! assert_eq!(diagnostics[0].severity, Severity::Error);
+//! ```
+
+use crate::compiler::analysis::{ArchitectureRules, CheckerOptions, TypeChecker};
+use crate::compiler::ast::LineIndex;
+use crate::compiler::frontend::parse_module;
+use crate::compiler::types::{OverrideOrigin, TypeContext};
+use serde::Serialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+pub use crate::compiler::analysis::LintLevel;
+pub use crate::compiler::frontend::config::Config;
+pub use crate::compiler::types::Type;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A problem found in a checked file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub file: String,
+    /// 1-based; 0 when the checker has no location for the problem
+    pub line: usize,
+    pub col: usize,
+    pub severity: Severity,
+    /// Lint code such as `S001`; `None` for type and syntax errors
+    pub code: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{}:{}:{}: {}", self.file, self.line, self.col, severity)?;
+        if let Some(code) = &self.code {
+            write!(f, "[{}]", code)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// A project being checked: its configuration, and the checker state the
+/// files checked in it share (classes and signatures seen so far)
+pub struct Session {
+    config: Config,
+    checker: TypeChecker,
+}
+
+impl Session {
+    /// Session for a project rooted at `root`, where layering rules are resolved
+    ///
+    /// Fails when the configuration's architecture rules or signature
+    /// overrides are invalid.
+    pub fn new(config: Config, root: impl Into<PathBuf>) -> Result<Self, String> {
+        let rules = ArchitectureRules::new(&config.architecture, root.into())
+            .map_err(|e| format!("Invalid [architecture] config: {}", e))?;
+        let mut checker = TypeChecker::with_context(Arc::new(TypeContext::new()))
+            .with_options(CheckerOptions::from_config(&config))
+            .with_architecture(Arc::new(rules));
+        for (path, annotation) in &config.signatures {
+            checker.override_signature(path, annotation, OverrideOrigin::Config)
+                .map_err(|e| format!("Invalid signature override for '{}': {}", path, e))?;
+        }
+        Ok(Self { config, checker })
+    }
+
+    /// Session for the project containing `start`, using the nearest
+    /// `typthon.toml` or `pyproject.toml` above it
+    ///
+    /// Fails when that file can't be loaded, as well as when `new` would.
+    pub fn discover(start: &Path) -> Result<Self, String> {
+        let (config, root) = Config::try_discover_from(start)?;
+        Self::new(config, root)
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Type check and lint `source`, reporting it as `file`
+    pub fn check_source(&mut self, file: &str, source: &str) -> Vec<Diagnostic> {
+        let diagnostic = |line, col, severity, code: Option<&str>, message: String| Diagnostic {
+            file: file.to_string(),
+            line,
+            col,
+            severity,
+            code: code.map(str::to_string),
+            message,
+        };
+        let module = match parse_module(source) {
+            Ok(module) => module,
+            Err(e) => return vec![diagnostic(0, 0, Severity::Error, None, format!("Parse error: {}", e))],
+        };
+
+        self.checker.set_source_name(file);
+        self.checker.set_source_file(Path::new(file), source);
+        let errors = self.checker.check(&module);
+        let lints = self.checker.lint(&module);
+        let index = LineIndex::new(source);
+
+        let mut diagnostics: Vec<Diagnostic> = errors.into_iter()
+            .map(|e| diagnostic(e.line, e.col, Severity::Error, e.code, e.message))
+            .collect();
+        diagnostics.extend(lints.into_iter().map(|lint| {
+            let location = lint.location(&index);
+            let severity = if lint.level == LintLevel::Error { Severity::Error } else { Severity::Warning };
+            diagnostic(location.line, location.col, severity, Some(lint.code), lint.message)
+        }));
+        diagnostics
+    }
+
+    /// Read and check the file at `path`
+    pub fn check_file(&mut self, path: &Path) -> std::io::Result<Vec<Diagnostic>> {
+        let source = std::fs::read_to_string(path)?;
+        Ok(self.check_source(&path.display().to_string(), &source))
+    }
+
+    /// Type of a module-level name in the last checked file
+    pub fn type_of(&self, name: &str) -> Option<Type> {
+        self.checker.get_type(name)
+    }
+
+    /// Parse an annotation such as `"list[int] | None"` into a `Type`
+    pub fn parse_type(&mut self, annotation: &str) -> Result<Type, String> {
+        self.checker.parse_annotation(annotation)
+    }
+}
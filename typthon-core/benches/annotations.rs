@@ -0,0 +1,78 @@
+//! Annotation-heavy checking benchmarks
+//!
+//! Measures checking a module of thousands of annotated functions and
+//! variables, and reports the bytes one check allocates and the most it
+//! holds at once.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use typthon::compiler::analysis::TypeChecker;
+use typthon::parse_module;
+
+const FUNCTIONS: usize = 2000;
+
+/// Tracks the bytes allocated and held
+struct Tracking;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static HELD: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Tracking {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        let held = HELD.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        PEAK.fetch_max(held, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        HELD.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Tracking = Tracking;
+
+/// Functions and module-level variables with annotations on every name
+fn annotated_module() -> String {
+    let mut source = String::new();
+    for i in 0..FUNCTIONS {
+        source.push_str(&format!("\
+def f{i}(a: int, b: str, c: list[int], d: dict[str, float] | None = None) -> tuple[int, str]:
+    total: int = a + len(c)
+    label: str = b
+    return (total, label)
+
+v{i}: list[tuple[int, str]] = [f{i}(1, \"x\", [1])]
+"));
+    }
+    source
+}
+
+fn check(source: &str, module: &rustpython_parser::ast::Mod) {
+    let mut checker = TypeChecker::new();
+    checker.set_source(source);
+    black_box(checker.check(module));
+}
+
+fn bench_annotations(c: &mut Criterion) {
+    let source = annotated_module();
+    let module = parse_module(&source).unwrap();
+
+    let (allocated, held) = (ALLOCATED.load(Ordering::Relaxed), HELD.load(Ordering::Relaxed));
+    PEAK.store(held, Ordering::Relaxed);
+    check(&source, &module);
+    println!(
+        "one check: {} KiB allocated, {} KiB peak",
+        (ALLOCATED.load(Ordering::Relaxed) - allocated) / 1024,
+        (PEAK.load(Ordering::Relaxed) - held) / 1024,
+    );
+
+    c.bench_function("check_2k_annotated_functions", |b| b.iter(|| check(&source, &module)));
+}
+
+criterion_group!(benches, bench_annotations);
+criterion_main!(benches);
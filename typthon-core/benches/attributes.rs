@@ -0,0 +1,63 @@
+//! Attribute resolution benchmarks
+//!
+//! Measures class schema lookups on a method-heavy class.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use typthon::{Type, TypeContext};
+use typthon::compiler::types::ClassSchema;
+
+const METHODS: usize = 50;
+const LOOKUPS: usize = 100_000;
+
+fn setup_context() -> TypeContext {
+    let ctx = TypeContext::new();
+
+    let base = ClassSchema::new("Base".to_string());
+    base.add_method("inherited".to_string(), Type::Function(vec![], Box::new(Type::Int)));
+    ctx.register_class(base);
+
+    let schema = ClassSchema::new("Service".to_string()).with_bases(vec!["Base".to_string()]);
+    for i in 0..METHODS {
+        schema.add_method(
+            format!("method_{}", i),
+            Type::Function(vec![Type::Int, Type::Str], Box::new(Type::Bool)),
+        );
+    }
+    ctx.register_class(schema);
+
+    ctx
+}
+
+fn bench_attribute_lookup(c: &mut Criterion) {
+    let ctx = setup_context();
+    let ty = Type::Class("Service".to_string());
+    let names: Vec<String> = (0..METHODS).map(|i| format!("method_{}", i)).collect();
+
+    c.bench_function("has_attribute_100k", |b| {
+        b.iter(|| {
+            for i in 0..LOOKUPS {
+                black_box(ctx.has_attribute(&ty, &names[i % METHODS]));
+            }
+        });
+    });
+
+    c.bench_function("inherited_attribute_100k", |b| {
+        b.iter(|| {
+            for _ in 0..LOOKUPS {
+                black_box(ctx.has_attribute(&ty, "inherited"));
+            }
+        });
+    });
+
+    c.bench_function("get_class_100k", |b| {
+        b.iter(|| {
+            for i in 0..LOOKUPS {
+                let schema = ctx.get_class("Service").unwrap();
+                black_box(schema.get_member(&names[i % METHODS]));
+            }
+        });
+    });
+}
+
+criterion_group!(benches, bench_attribute_lookup);
+criterion_main!(benches);
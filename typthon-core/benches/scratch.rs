@@ -0,0 +1,58 @@
+//! Checker scratch space benchmarks
+//!
+//! Measures checking a 10k-line module with a fresh checker against one
+//! reusing the scratch space of a checker that checked it before.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use typthon::compiler::analysis::TypeChecker;
+use typthon::infrastructure::ScratchPool;
+use typthon::parse_module;
+
+const BLOCKS: usize = 910;
+
+/// About 10k lines of classes, functions and module-level calls
+fn synthetic_module() -> String {
+    let mut source = String::new();
+    for i in 0..BLOCKS {
+        source.push_str(&format!("\
+class Shape{i}:
+    def __init__(self, side: int) -> None:
+        self.side = side
+
+    def area(self) -> int:
+        return self.side * self.side
+
+
+def grow{i}(shape: Shape{i}, by: int) -> int:
+    return shape.area() + by
+total{i}: int = grow{i}(Shape{i}({i}), 2)
+"));
+    }
+    source
+}
+
+fn bench_check_10k_lines(c: &mut Criterion) {
+    let source = synthetic_module();
+    let module = parse_module(&source).unwrap();
+
+    c.bench_function("check_10k_lines_fresh", |b| {
+        b.iter(|| {
+            let mut checker = TypeChecker::new();
+            checker.set_source(&source);
+            black_box(checker.check(&module));
+        });
+    });
+
+    let pool = ScratchPool::new(1);
+    c.bench_function("check_10k_lines_pooled", |b| {
+        b.iter(|| {
+            let mut checker = TypeChecker::new().with_scratch(pool.acquire());
+            checker.set_source(&source);
+            black_box(checker.check(&module));
+            pool.release(checker.into_scratch());
+        });
+    });
+}
+
+criterion_group!(benches, bench_check_10k_lines);
+criterion_main!(benches);
@@ -36,6 +36,60 @@ fn check_file(path: String) -> PyResult<Vec<String>> {
     Ok(errors.iter().map(|e| e.to_string()).collect())
 }
 
+/// A type error with the extent of the code it's about; lines are 1-based
+/// and columns 0-based
+#[cfg(feature = "python")]
+#[pyclass(get_all)]
+#[derive(Clone)]
+struct Diagnostic {
+    line: usize,
+    col: usize,
+    end_line: usize,
+    end_col: usize,
+    code: Option<String>,
+    severity: String,
+    message: String,
+    suggestions: Vec<String>,
+}
+
+#[cfg(feature = "python")]
+#[pyfunction]
+fn check_file_detailed(path: String) -> PyResult<Vec<Diagnostic>> {
+    let source = std::fs::read_to_string(&path)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(e.to_string()))?;
+
+    check_source_detailed(&source, Some(&path))
+}
+
+#[cfg(feature = "python")]
+#[pyfunction]
+#[pyo3(signature = (source, filename=None))]
+fn check_source_detailed(source: &str, filename: Option<&str>) -> PyResult<Vec<Diagnostic>> {
+    let ast = parse_module(source)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PySyntaxError, _>(e.to_string()))?;
+
+    let mut checker = TypeChecker::new();
+    if let Some(filename) = filename {
+        checker.set_source_name(filename);
+    }
+    checker.set_source(source);
+    let index = crate::compiler::ast::LineIndex::new(source);
+
+    Ok(checker.check(&ast).into_iter().map(|e| {
+        let (end_line, end_col) = e.range.as_ref().map_or((e.line, e.col), |range| index.offset_to_position(range.end));
+        Diagnostic {
+            line: e.line,
+            col: e.col,
+            end_line,
+            end_col,
+            code: e.code.map(str::to_string),
+            severity: e.severity.to_string(),
+            message: e.message,
+            suggestions: e.suggestions,
+        }
+    }).collect())
+}
+
 #[cfg(feature = "python")]
 #[pyfunction]
 fn infer_types(source: String) -> PyResult<String> {
@@ -45,7 +99,20 @@ fn infer_types(source: String) -> PyResult<String> {
     let mut checker = TypeChecker::new();
     let result = checker.infer(&ast);
 
-    Ok(format!("{:?}", result))
+    Ok(format!("{}", result))
+}
+
+/// Inferred type of every top-level and function-local binding, by name
+#[cfg(feature = "python")]
+#[pyfunction]
+fn infer_module(source: String) -> PyResult<std::collections::BTreeMap<String, String>> {
+    let ast = parse_module(&source)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PySyntaxError, _>(e.to_string()))?;
+
+    let mut checker = TypeChecker::new();
+    checker.check(&ast);
+
+    Ok(checker.inferred_bindings().into_iter().map(|(name, ty, _)| (name, ty.to_string())).collect())
 }
 
 #[cfg(feature = "python")]
@@ -143,6 +210,12 @@ impl TypeValidator {
         Ok(format!("{:?}", ty))
     }
 
+    fn get_all_types(&self) -> std::collections::BTreeMap<String, String> {
+        self.checker.inferred_bindings().into_iter()
+            .map(|(name, ty, _)| (name, ty.to_string()))
+            .collect()
+    }
+
     fn get_function_effects(&self, name: String) -> PyResult<Vec<String>> {
         if let Some(effects) = self.checker.get_function_effects(&name) {
             if effects.is_pure() {
@@ -167,10 +240,8 @@ impl TypeValidator {
         }
     }
 
-    fn validate_refinement_value(&self, _value: String, _type_str: String) -> PyResult<bool> {
-        // Parse type_str to get Type; for now, just check basic types
-        // Full implementation would parse the type annotation
-        Ok(true)
+    fn validate_refinement_value(&self, value: String, predicate: String) -> PyResult<bool> {
+        validate_refinement(value, predicate)
     }
 }
 
@@ -178,7 +249,11 @@ impl TypeValidator {
 #[pymodule]
 fn _core(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(check_file, m)?)?;
+    m.add_function(wrap_pyfunction!(check_file_detailed, m)?)?;
+    m.add_function(wrap_pyfunction!(check_source_detailed, m)?)?;
+    m.add_class::<Diagnostic>()?;
     m.add_function(wrap_pyfunction!(infer_types, m)?)?;
+    m.add_function(wrap_pyfunction!(infer_module, m)?)?;
     m.add_function(wrap_pyfunction!(check_effects, m)?)?;
     m.add_function(wrap_pyfunction!(get_function_type_with_effects, m)?)?;
     m.add_function(wrap_pyfunction!(validate_refinement, m)?)?;
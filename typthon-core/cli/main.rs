@@ -1,16 +1,106 @@
-use typthon::{TypeChecker, TypeContext, parse_module, init_dev_logging, LogConfig, LogFormat, LogOutput};
+mod report;
+mod sources;
+mod watch;
+
+use typthon::{TypeChecker, TypeContext, PerformanceMetrics, parse_module, init_dev_logging};
+use typthon::{DependencyGraph, IncrementalEngine, ResultCache};
+use typthon::compiler::analysis::{ArchitectureRules, CheckerOptions, LintDiagnostic, ModuleResolver};
+use typthon::compiler::analysis::architecture::{import_statements, ImportResolver};
+use typthon::compiler::ast::LineIndex;
+use typthon::compiler::errors::{render, Severity, SourceLocation};
+use typthon::compiler::types::OverrideOrigin;
+use typthon::compiler::frontend::config::{Config as ProjectConfig, ErrorFormat};
+use typthon::infrastructure::find_case_conflicts;
+use typthon::infrastructure::incremental::ModuleId;
+use typthon::infrastructure::parallel::{AnalysisResult, AnalysisTask, CheckerFactory, ParallelAnalyzer, CACHE_VERSION};
+use typthon::infrastructure::cache::{CacheEntry, CacheKey};
+use typthon::infrastructure::incremental::ContentHash;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::sync::Arc;
 use std::fs;
-use std::path::PathBuf;
-use tracing::{debug, error, info, Level};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use tracing::{debug, error, info};
+use report::Record;
+use sources::{collect_sources, collect_stubs, SourceFilter};
+
+/// When to emit ANSI colors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColorMode {
+    Auto,
+    Always,
+    Never,
+}
+
+/// How diagnostics are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Text,
+    /// Source excerpts with the offending spans underlined
+    Pretty,
+    Json,
+    Sarif,
+}
+
+/// What the invocation does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    /// Type check the given files
+    Check,
+    /// Print the project import graph, or validate it against the layering rules
+    Deps { check_rules: bool },
+}
 
 #[derive(Debug)]
 struct Config {
+    command: Command,
     files: Vec<PathBuf>,
     strict: bool,
-    no_color: bool,
+    /// `--strict-optional`
+    strict_optional: bool,
+    verbose: bool,
+    color: Option<ColorMode>,
+    format: Option<OutputFormat>,
+    stats: Stats,
+    /// Codes from `--disable`
+    disabled_codes: BTreeSet<String>,
+    /// Codes from `--enable-only`
+    enabled_codes: Option<BTreeSet<String>>,
+    /// `--include` / `--exclude` patterns for directories
+    filter: SourceFilter,
+    /// Threads checking files; 0 for one per core, and the project's
+    /// `workers` when unset
+    jobs: Option<usize>,
+    /// `--no-cache`
+    no_cache: bool,
+    /// `--cache-dir`
+    cache_dir: Option<PathBuf>,
+    /// `--stubs-path` directories, searched for `.pyi` stubs in order
+    stub_paths: Vec<PathBuf>,
+    /// `--warnings-as-errors`: warnings fail the run too
+    warnings_as_errors: bool,
+    /// `--watch`: check again whenever files change
+    watch: bool,
+    /// `--profile`: print phase timings, file counts and cache hit rates
+    profile: bool,
+    /// `--profile-json`: where to write the same as JSON
+    profile_json: Option<PathBuf>,
+}
+
+/// How much timing detail to report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Stats {
+    Off,
+    Phases,
+    Detailed,
 }
 
+/// Files and offenders listed in the `--stats` report
+const STATS_TOP: usize = 5;
+
+/// Size the result cache is kept under
+const CACHE_SIZE_MB: usize = 256;
+
 impl Config {
     fn from_args() -> Result<Self, String> {
         let args: Vec<String> = std::env::args().collect();
@@ -19,125 +109,692 @@ impl Config {
             return Err(Self::usage(&args[0]));
         }
 
+        let mut command = Command::Check;
+        let mut args_start = 1;
+        if args[1] == "deps" {
+            command = Command::Deps { check_rules: false };
+            args_start = 2;
+        }
+
         let mut files = Vec::new();
         let mut strict = false;
-        let mut no_color = false;
+        let mut strict_optional = false;
+        let mut verbose = false;
+        let mut color = None;
+        let mut format = None;
+        let mut stats = Stats::Off;
+        let mut disabled_codes = BTreeSet::new();
+        let mut enabled_codes: Option<BTreeSet<String>> = None;
+        let mut filter = SourceFilter::default();
+        let mut jobs = None;
+        let mut no_cache = false;
+        let mut cache_dir = None;
+        let mut stub_paths = Vec::new();
+        let mut warnings_as_errors = false;
+        let mut watch = false;
+        let mut profile = false;
+        let mut profile_json = None;
+
+        let mut iter = args[args_start..].iter();
+        while let Some(arg) = iter.next() {
+            // Accept both `--flag value` and `--flag=value`
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if arg.starts_with("--") => (flag, Some(value.to_string())),
+                _ => (arg.as_str(), None),
+            };
+            let mut value = |name: &str| {
+                inline.clone()
+                    .or_else(|| iter.next().cloned())
+                    .ok_or_else(|| format!("Missing value for {}", name))
+            };
 
-        for arg in &args[1..] {
-            match arg.as_str() {
+            match flag {
                 "--help" | "-h" => return Err(Self::usage(&args[0])),
                 "--strict" => strict = true,
-                "--no-color" => no_color = false,
+                "--strict-optional" => strict_optional = true,
+                "--warnings-as-errors" => warnings_as_errors = true,
+                "--watch" | "-w" => watch = true,
+                "--verbose" | "-v" => verbose = true,
+                "--no-color" => color = Some(ColorMode::Never),
+                "--no-cache" => no_cache = true,
+                "--cache-dir" => cache_dir = Some(PathBuf::from(value("--cache-dir")?)),
+                "--stubs-path" => stub_paths.push(PathBuf::from(value("--stubs-path")?)),
+                "--stats" => stats = stats.max(Stats::Phases),
+                "--stats-detailed" => stats = Stats::Detailed,
+                "--profile" => profile = true,
+                "--profile-json" => profile_json = Some(PathBuf::from(value("--profile-json")?)),
+                "--check-rules" if matches!(command, Command::Deps { .. }) => {
+                    command = Command::Deps { check_rules: true };
+                }
+                "--color" => {
+                    color = Some(match value("--color")?.as_str() {
+                        "auto" => ColorMode::Auto,
+                        "always" => ColorMode::Always,
+                        "never" => ColorMode::Never,
+                        other => return Err(format!("Invalid value for --color: {}", other)),
+                    })
+                }
+                "--disable" => disabled_codes.extend(Self::code_list(&value("--disable")?)),
+                "--enable-only" => {
+                    enabled_codes.get_or_insert_with(BTreeSet::new).extend(Self::code_list(&value("--enable-only")?));
+                }
+                "--jobs" | "-j" => {
+                    let value = value(flag)?;
+                    jobs = Some(value.parse().map_err(|_| format!("Invalid value for {}: {}", flag, value))?);
+                }
+                "--include" => filter.include(&value("--include")?)?,
+                "--exclude" => filter.exclude(&value("--exclude")?)?,
+                "--output" | "--output-format" => {
+                    format = Some(match value(flag)?.as_str() {
+                        "text" => OutputFormat::Text,
+                        "pretty" => OutputFormat::Pretty,
+                        "json" => OutputFormat::Json,
+                        "sarif" => OutputFormat::Sarif,
+                        other => return Err(format!("Invalid value for {}: {}", flag, other)),
+                    })
+                }
                 path if !path.starts_with("--") => files.push(PathBuf::from(path)),
                 opt => return Err(format!("Unknown option: {}\n\n{}", opt, Self::usage(&args[0]))),
             }
         }
 
-        if files.is_empty() {
-            return Err("No files specified".to_string());
+        // The import graph covers the whole project by default; a check
+        // falls back to the project's `python_files`
+        if files.is_empty() && matches!(command, Command::Deps { .. }) {
+            files.push(PathBuf::from("."));
         }
 
-        Ok(Self { files, strict, no_color })
+        Ok(Self { command, files, strict, strict_optional, verbose, color, format, stats, disabled_codes, enabled_codes, filter, jobs, no_cache, cache_dir, stub_paths, warnings_as_errors, watch, profile, profile_json })
+    }
+
+    /// Codes of a comma-separated flag value like `TYP005,TYP007`
+    fn code_list(value: &str) -> impl Iterator<Item = String> + '_ {
+        value.split(',').map(str::trim).filter(|code| !code.is_empty()).map(str::to_uppercase)
     }
 
     fn usage(prog: &str) -> String {
         format!(
             "Typthon - Advanced Type Checker for Python\n\n\
-            USAGE:\n    {} [OPTIONS] <files...>\n\n\
+            USAGE:\n    {} [OPTIONS] <files or directories...>\n    \
+            {} deps [--check-rules] [paths...]\n\n\
             OPTIONS:\n    \
-            -h, --help      Print help information\n    \
-            --strict        Enable strict type checking\n    \
-            --no-color      Disable colored output\n\n\
+            -h, --help                  Print help information\n    \
+            -v, --verbose               Emit debug logs to stderr\n    \
+            --strict                    Enable strict type checking\n    \
+            --strict-optional           Report uses of values that may be None\n    \
+            --warnings-as-errors        Fail on warnings as well as errors\n    \
+            --color <auto|always|never> When to use colored output\n    \
+            --no-color                  Disable colored output\n    \
+            --output <format>           Diagnostic output: text, pretty, json or sarif\n    \
+            --disable <codes>           Don't report these codes, e.g. TYP005,S001\n    \
+            --enable-only <codes>       Report only these codes\n    \
+            --include <glob>            Only check matching files under directories\n    \
+            --exclude <glob>            Skip matching files and directories\n    \
+            -j, --jobs <n>              Files checked in parallel; 0 (default) for one per core\n    \
+            --no-cache                  Check every file, ignoring results from earlier runs\n    \
+            --cache-dir <dir>           Where results are kept between runs\n    \
+            --stubs-path <dir>          Also look for .pyi stubs here; repeatable\n    \
+            -w, --watch                 Check again whenever files change\n    \
+            --stats                     Report check time per phase and file\n    \
+            --stats-detailed            Also time individual rules\n    \
+            --profile                   Report phase times, file counts and cache hit rates\n    \
+            --profile-json <path>       Write the same report as JSON\n    \
+            --check-rules               (deps) Validate imports against [architecture] layers\n\n\
             EXAMPLES:\n    \
             {} script.py\n    \
-            {} --strict src/**/*.py\n    \
-            {} --no-color myfile.py",
-            prog, prog, prog, prog
+            {} --strict src/ --exclude 'tests'\n    \
+            {} --watch --output pretty src/\n    \
+            {} --no-color myfile.py\n    \
+            {} --disable=TYP005,TYP007 main.py\n    \
+            {} --output=sarif main.py > typthon.sarif\n    \
+            {} deps --check-rules",
+            prog, prog, prog, prog, prog, prog, prog, prog, prog
         )
     }
-}
 
-fn print_errors(errors: &[String], file: &PathBuf, config: &Config) {
-    if errors.is_empty() {
-        return;
+    /// Put the flags over the project config, so they win for modules its
+    /// overrides cover too
+    fn apply_flags(&self, project: &mut ProjectConfig) {
+        if self.strict {
+            project.check.strict = true;
+            for entry in &mut project.module_overrides {
+                entry.strict = None;
+            }
+        }
+        if self.strict_optional {
+            project.check.strict_optional = true;
+        }
+        project.errors.disable.extend(self.disabled_codes.iter().cloned());
+        if let Some(codes) = &self.enabled_codes {
+            project.errors.enable = Some(codes.iter().cloned().collect());
+            for entry in &mut project.module_overrides {
+                entry.enable = None;
+            }
+        }
     }
 
-    let file_display = file.display();
+    /// Fill in what the flags leave unset from the project config rooted at `root`
+    fn fill_from_project(&mut self, project: &ProjectConfig, root: &Path) -> Result<(), String> {
+        self.warnings_as_errors |= project.errors.warnings_as_errors;
+        self.jobs = self.jobs.or(project.check.workers);
+        if self.files.is_empty() {
+            if project.paths.roots.is_empty() {
+                return Err("No files specified".to_string());
+            }
+            // Shown relative to the current directory where they can be
+            let cwd = std::env::current_dir().unwrap_or_default();
+            self.files = project.paths.roots.iter()
+                .map(|dir| root.join(dir))
+                .map(|dir| dir.strip_prefix(&cwd).map(Path::to_path_buf).unwrap_or(dir))
+                .map(|dir| if dir.as_os_str().is_empty() { PathBuf::from(".") } else { dir })
+                .collect();
+        }
+        Ok(())
+    }
 
-    for error in errors {
-        if config.no_color {
-            eprintln!("{}:{}", file_display, error);
+    /// Whether a diagnostic of `severity` fails the run
+    fn fails_on(&self, severity: Severity) -> bool {
+        severity == Severity::Error || (self.warnings_as_errors && severity == Severity::Warning)
+    }
+
+    /// Resolve colors from flags, then the project config, then the terminal
+    fn use_color(&self, project: &ProjectConfig) -> bool {
+        let mode = self.color.unwrap_or(if project.errors.color {
+            ColorMode::Auto
         } else {
-            eprintln!("\x1b[31m{}:{}\x1b[0m", file_display, error);
+            ColorMode::Never
+        });
+
+        match mode {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto => std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
         }
     }
+
+    /// Resolve the cache directory from flags, then `TYPTHON_CACHE_DIR`, then
+    /// the project config; `None` when caching is off
+    fn cache_dir(&self, project: &ProjectConfig, root: &Path) -> Option<PathBuf> {
+        if self.no_cache {
+            return None;
+        }
+        self.cache_dir.clone()
+            .or_else(|| std::env::var_os("TYPTHON_CACHE_DIR").map(PathBuf::from))
+            .or_else(|| project.cache.enabled.then(|| project.cache_dir(root)))
+    }
+
+    /// Resolve the output format from flags, then the project config
+    fn output_format(&self, project: &ProjectConfig) -> OutputFormat {
+        self.format.unwrap_or(match project.errors.format {
+            ErrorFormat::Json => OutputFormat::Json,
+            ErrorFormat::Sarif => OutputFormat::Sarif,
+            ErrorFormat::Verbose => OutputFormat::Pretty,
+            _ => OutputFormat::Text,
+        })
+    }
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Initialize logging early
-    let _guard = init_dev_logging();
+/// Print `errors` under `file`; errors in red, warnings in yellow and notes in cyan
+fn print_errors(errors: &[(Severity, String)], file: &PathBuf, color: bool) {
+    if errors.is_empty() {
+        return;
+    }
 
-    info!("Typthon CLI starting");
+    let file_display = file.display();
 
-    let config = match Config::from_args() {
-        Ok(c) => c,
-        Err(e) => {
-            error!(error = %e, "Failed to parse CLI arguments");
-            eprintln!("{}", e);
-            std::process::exit(1);
+    for (severity, error) in errors {
+        if color {
+            let code = match severity {
+                Severity::Error => 31,
+                Severity::Warning => 33,
+                Severity::Note => 36,
+            };
+            eprintln!("\x1b[{}m{}:{}\x1b[0m", code, file_display, error);
+        } else {
+            eprintln!("{}:{}", file_display, error);
         }
-    };
-
-    debug!(files = ?config.files, strict = config.strict, "Configuration loaded");
+    }
+}
 
-    let ctx = Arc::new(TypeContext::new());
-    let mut checker = TypeChecker::with_context(ctx.clone());
+/// Warn about sources that would collide on a case-insensitive filesystem
+fn warn_case_conflicts(files: &[PathBuf]) {
+    for (first, second) in find_case_conflicts(files) {
+        eprintln!(
+            "warning: {} and {} differ only by case; this project can't be checked out on a case-insensitive filesystem",
+            first.display(), second.display(),
+        );
+    }
+}
 
-    let mut total_errors = 0;
+/// `typthon deps`: resolve imports without type checking; returns the exit code
+fn run_deps(config: &Config, project: &ProjectConfig, rules: &ArchitectureRules, check_rules: bool) -> i32 {
+    let color = config.use_color(project);
+    if check_rules && rules.is_empty() {
+        eprintln!("No layers configured; add an [architecture] section to .typyrc");
+        return 1;
+    }
 
-    for file in &config.files {
-        info!(file = %file.display(), "Processing file");
+    let files = collect_sources(&config.files, project, &config.filter);
+    warn_case_conflicts(&files);
+    let resolver = rules.resolver();
+    let mut violations = 0;
 
+    for file in &files {
         let source = match fs::read_to_string(file) {
-            Ok(s) => s,
+            Ok(source) => source,
             Err(e) => {
-                error!(file = %file.display(), error = %e, "Failed to read file");
                 eprintln!("Error reading {}: {}", file.display(), e);
                 continue;
             }
         };
-
         let ast = match parse_module(&source) {
             Ok(ast) => ast,
             Err(e) => {
-                error!(file = %file.display(), error = %e, "Parse error");
                 eprintln!("Parse error in {}: {}", file.display(), e);
-                total_errors += 1;
+                violations += 1;
                 continue;
             }
         };
 
-        let errors = checker.check(&ast);
-        let error_strs: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        if check_rules {
+            let line_index = LineIndex::new(&source);
+            let found: Vec<(Severity, String)> = rules.check_module(file, &ast).iter()
+                .map(|violation| {
+                    let (line, col) = line_index.offset_to_position(violation.range.start);
+                    (Severity::Error, format!("Line {}, Col {}: {}", line, col, violation.message))
+                })
+                .collect();
+            violations += found.len();
+            print_errors(&found, file, color);
+        } else {
+            let module = resolver.module_name(&resolver.absolute(file));
+            let mut targets: Vec<String> = import_statements(&ast).into_iter()
+                .flat_map(|stmt| resolver.targets(file, stmt))
+                .map(|target| resolver.module_name(&target))
+                .collect();
+            targets.sort();
+            targets.dedup();
+            for target in targets {
+                println!("{} -> {}", module, target);
+            }
+        }
+    }
+
+    if violations > 0 {
+        eprintln!("\nFound {} architecture violation(s)", violations);
+        1
+    } else {
+        if check_rules {
+            println!("✓ No architecture violations in {} module(s)", files.len());
+        }
+        0
+    }
+}
+
+/// Check `tasks` on `jobs` threads, scheduling modules after the project
+/// modules they import. With a cache directory, files whose content,
+/// configuration (`fingerprint`) and imported modules are unchanged since an
+/// earlier run aren't parsed or checked again
+fn check_tasks(
+    tasks: &[AnalysisTask],
+    factory: CheckerFactory,
+    metrics: Arc<PerformanceMetrics>,
+    fingerprint: String,
+    cache_dir: Option<PathBuf>,
+    jobs: usize,
+    resolver: &ImportResolver,
+    modules: &ModuleResolver,
+) -> Vec<AnalysisResult> {
+    let cache = cache_dir.and_then(|dir| match ResultCache::for_version(dir.clone(), CACHE_SIZE_MB, CACHE_VERSION) {
+        Ok(cache) => Some(Arc::new(cache)),
+        Err(e) => {
+            eprintln!("warning: can't use cache directory {}: {}", dir.display(), e);
+            None
+        }
+    });
 
-        debug!(file = %file.display(), error_count = errors.len(), "Type checking complete");
-        total_errors += error_strs.len();
-        print_errors(&error_strs, file, &config);
+    let incremental = Arc::new(IncrementalEngine::new(Arc::new(DependencyGraph::new())));
+    let checked: HashSet<ModuleId> = tasks.iter().map(|task| task.id).collect();
+    // Where an import resolves depends on which files exist
+    let file_set: String = tasks.iter().map(|task| format!("{}\n", task.path.display())).collect();
+    for task in tasks {
+        let key = CacheKey::new(task.id, ContentHash::with_fingerprint(
+            &task.content,
+            &format!("{} imports\n{}", CACHE_VERSION, file_set),
+        ));
+        let imports = match cache.as_ref().and_then(|cache| cache.get(&key)) {
+            Some(entry) => entry.imports.clone(),
+            None => {
+                // Imports outside the checked files don't affect scheduling
+                let imports: Vec<PathBuf> = project_imports(task, resolver, modules).into_iter()
+                    .filter(|target| checked.contains(&ModuleId::from_path(target)))
+                    .collect();
+                if let Some(cache) = &cache {
+                    let _ = cache.set(key.clone(), CacheEntry {
+                        module: task.id,
+                        hash: key.hash,
+                        types: Vec::new(),
+                        errors: Vec::new(),
+                        lints: Vec::new(),
+                        parse_error: None,
+                        imports: imports.clone(),
+                        timestamp: 0,
+                        size_bytes: task.path.as_os_str().len(),
+                    });
+                }
+                imports
+            }
+        };
+        incremental.register_module(resolver.absolute(&task.path), &task.content, imports);
+    }
+
+    let context = Arc::new(TypeContext::new());
+    let analyzer = match cache {
+        Some(cache) => ParallelAnalyzer::new(context, cache, incremental, jobs),
+        None => ParallelAnalyzer::uncached(context, incremental, jobs),
+    };
+    analyzer
+        .with_checker_factory(factory, fingerprint)
+        .with_metrics(metrics)
+        .analyze_modules(tasks.to_vec())
+}
+
+/// Project files `task` imports, as absolute paths; none if it doesn't parse
+fn project_imports(task: &AnalysisTask, resolver: &ImportResolver, modules: &ModuleResolver) -> Vec<PathBuf> {
+    parse_module(&task.content)
+        .map(|ast| import_statements(&ast).into_iter()
+            .flat_map(|stmt| {
+                let mut targets = resolver.targets(&task.path, stmt);
+                targets.extend(modules.targets(&task.path, stmt));
+                targets
+            })
+            .collect())
+        .unwrap_or_default()
+}
+
+/// Read `files` to be checked; unreadable files are reported and left out
+fn read_tasks(files: &[PathBuf], resolver: &ImportResolver) -> Vec<AnalysisTask> {
+    files.iter()
+        .filter_map(|file| match fs::read_to_string(file) {
+            Ok(content) => Some(AnalysisTask { id: ModuleId::from_path(&resolver.absolute(file)), path: file.clone(), content }),
+            Err(e) => {
+                error!(file = %file.display(), error = %e, "Failed to read file");
+                eprintln!("Error reading {}: {}", file.display(), e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// What checking a set of files takes, the same for every run in watch mode
+struct Checking<'a> {
+    config: &'a Config,
+    factory: CheckerFactory,
+    /// What every run's checkers recorded, merged
+    metrics: Arc<PerformanceMetrics>,
+    fingerprint: String,
+    cache_dir: Option<PathBuf>,
+    resolver: &'a ImportResolver,
+    modules: Arc<ModuleResolver>,
+    format: OutputFormat,
+    color: bool,
+}
+
+impl Checking<'_> {
+    /// Results of checking `tasks`, by module
+    fn check(&self, tasks: &[AnalysisTask]) -> HashMap<ModuleId, AnalysisResult> {
+        check_tasks(tasks, self.factory.clone(), self.metrics.clone(), self.fingerprint.clone(), self.cache_dir.clone(), self.config.jobs.unwrap_or(0), self.resolver, &self.modules)
+            .into_iter()
+            .map(|result| (result.id, result))
+            .collect()
+    }
+
+    /// Print the diagnostics of one checked file, or add them to `records`
+    /// for a structured format; returns how many fail the run
+    fn report(&self, task: &AnalysisTask, result: &AnalysisResult, records: &mut Vec<Record>) -> usize {
+        let (format, color) = (self.format, self.color);
+        let file = &task.path;
+        let file_name = file.display().to_string();
+
+        if let Some(e) = &result.parse_error {
+            error!(file = %file.display(), error = %e, "Parse error");
+            eprintln!("Parse error in {}: {}", file.display(), e);
+            return 1;
+        }
+
+        let (errors, lints) = (&result.diagnostics, &result.lints);
+        let line_index = LineIndex::new(&task.content);
+
+        debug!(file = %file.display(), error_count = errors.len(), lint_count = lints.len(), "Type checking complete");
+        let severities = errors.iter().map(|e| e.severity).chain(lints.iter().map(LintDiagnostic::severity));
+        let failing = severities.filter(|&severity| self.config.fails_on(severity)).count();
+
+        match format {
+            OutputFormat::Json | OutputFormat::Sarif => {
+                records.extend(errors.iter().map(|e| Record::from_error(&file_name, e, &line_index)));
+                records.extend(lints.iter().map(|lint| Record::from_lint(&file_name, lint, &line_index)));
+            }
+            OutputFormat::Text => {
+                let mut error_strs: Vec<(Severity, String)> = errors.iter().map(|e| (e.severity, e.to_string())).collect();
+                error_strs.extend(lints.iter().map(|lint| {
+                    let location = lint.location(&line_index);
+                    let severity = lint.severity();
+                    (severity, format!("Line {}, Col {}: {}[{}]: {}", location.line, location.col, severity, lint.code, lint.message))
+                }));
+                print_errors(&error_strs, file, color);
+            }
+            OutputFormat::Pretty => {
+                let checker_suggestions: Vec<Vec<String>> = errors.iter()
+                    .map(|e| e.suggestions.iter().map(|name| format!("did you mean '{}'?", name)).collect())
+                    .collect();
+                let lint_suggestions: Vec<Vec<String>> = lints.iter()
+                    .map(|lint| lint.fix.iter().map(|fix| fix.title.clone()).collect())
+                    .collect();
+                let rendered = errors.iter().zip(&checker_suggestions)
+                    .map(|(e, suggestions)| render::Diagnostic {
+                        file: &file_name,
+                        severity: e.severity,
+                        code: e.code,
+                        message: &e.message,
+                        location: e.range.as_ref().map_or_else(
+                            || SourceLocation::new(e.line, e.col, e.line, e.col),
+                            |range| SourceLocation::from_range((e.line, e.col), line_index.offset_to_position(range.end)),
+                        ),
+                        suggestions,
+                    })
+                    .chain(lints.iter().zip(&lint_suggestions).map(|(lint, suggestions)| render::Diagnostic {
+                        file: &file_name,
+                        severity: lint.severity(),
+                        code: Some(lint.code),
+                        message: &lint.message,
+                        location: lint.location(&line_index),
+                        suggestions,
+                    }));
+                for diagnostic in rendered {
+                    eprintln!("{}", render::render(&diagnostic, &task.content, color));
+                }
+            }
+        }
+        failing
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut config = match Config::from_args() {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    // Logs go to stderr, so they stay off unless asked for
+    let _guard = if config.verbose { init_dev_logging() } else { None };
+
+    info!("Typthon CLI starting");
+
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let (mut project, root) = match ProjectConfig::try_discover_from(&cwd) {
+        Ok(found) => found,
+        Err(e) => {
+            eprintln!("Invalid config: {}", e);
+            std::process::exit(1);
+        }
+    };
+    config.apply_flags(&mut project);
+    if let Err(e) = config.fill_from_project(&project, &root) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+    let rules = match ArchitectureRules::new(&project.architecture, root.clone()) {
+        Ok(rules) => Arc::new(rules),
+        Err(e) => {
+            eprintln!("Invalid [architecture] config: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if let Command::Deps { check_rules } = config.command {
+        std::process::exit(run_deps(&config, &project, &rules, check_rules));
+    }
+
+    let strict = project.check.strict;
+    let color = config.use_color(&project);
+    let format = config.output_format(&project);
+
+    debug!(paths = ?config.files, strict, color, ?format, "Configuration loaded");
+
+    if config.watch && !matches!(format, OutputFormat::Text | OutputFormat::Pretty) {
+        eprintln!("--watch prints diagnostics as text; use --output text or pretty");
+        std::process::exit(1);
+    }
+
+    let metrics = Arc::new(PerformanceMetrics::new());
+    let options = CheckerOptions {
+        stub_paths: config.stub_paths.clone(),
+        ..CheckerOptions::from_config(&project)
+    };
+
+    let mut probe = TypeChecker::new();
+    for (path, annotation) in &project.signatures {
+        if let Err(e) = probe.override_signature(path, annotation, OverrideOrigin::Config) {
+            eprintln!("Invalid signature override for '{}': {}", path, e);
+            std::process::exit(1);
+        }
+    }
+
+    // Everything the checkers are configured with, so cached results from
+    // another configuration aren't reused
+    let stubs: Vec<(PathBuf, String)> = collect_stubs(&config.stub_paths).into_iter()
+        .filter_map(|stub| fs::read_to_string(&stub).ok().map(|source| (stub, source)))
+        .collect();
+    let fingerprint = serde_json::to_string(&(&options, &project.module_overrides, &project.signatures, &project.architecture, &root, &stubs))?;
+
+    // Imports resolve against the project root and the directories named on
+    // the command line; the exports of modules checked once are shared
+    let modules = Arc::new(ModuleResolver::new(
+        std::iter::once(root.clone()).chain(config.files.iter().filter(|path| path.is_dir()).cloned()),
+    ));
+
+    // Every file gets its own checker and bindings, so results don't depend
+    // on which files were checked before it or alongside it
+    let factory: CheckerFactory = {
+        let (rules, signatures, modules) = (rules.clone(), project.signatures.clone(), modules.clone());
+        let (project, stub_paths) = (project.clone(), config.stub_paths.clone());
+        let detailed = config.stats == Stats::Detailed;
+        Arc::new(move |path: &Path| {
+            let options = CheckerOptions {
+                stub_paths: stub_paths.clone(),
+                ..CheckerOptions::from_config(&project.for_module(&modules.module_name(path)))
+            };
+            let mut checker = TypeChecker::with_context(Arc::new(TypeContext::new()))
+                .with_options(options)
+                .with_architecture(rules.clone())
+                .with_modules(modules.clone())
+                // Merged into the run's metrics once the file is checked
+                .with_metrics(Arc::new(PerformanceMetrics::new()), detailed);
+            for (path, annotation) in &signatures {
+                // Already validated above
+                let _ = checker.override_signature(path, annotation, OverrideOrigin::Config);
+            }
+            checker
+        })
+    };
+
+    let files = collect_sources(&config.files, &project, &config.filter);
+    warn_case_conflicts(&files);
+
+    let checking = Checking {
+        config: &config,
+        factory,
+        metrics: metrics.clone(),
+        fingerprint,
+        cache_dir: config.cache_dir(&project, &root),
+        resolver: rules.resolver(),
+        modules,
+        format,
+        color,
+    };
+    if config.watch {
+        return watch::run(&checking, || collect_sources(&config.files, &project, &config.filter), files);
+    }
+
+    let tasks = read_tasks(&files, checking.resolver);
+    let mut results = checking.check(&tasks);
+    let mut total_errors = 0;
+    let mut records = Vec::new();
+
+    // Grouped by file in discovery order, however the checks were scheduled
+    for task in &tasks {
+        let Some(result) = results.remove(&task.id) else { continue };
+        total_errors += checking.report(task, &result, &mut records);
+    }
+
+    let breakdown = (config.stats != Stats::Off).then(|| metrics.phase_breakdown());
+
+    match (format, &breakdown) {
+        (OutputFormat::Json, Some(breakdown)) => {
+            let document = serde_json::json!({ "diagnostics": records, "stats": breakdown });
+            println!("{}", serde_json::to_string_pretty(&document)?);
+        }
+        (OutputFormat::Json, None) => println!("{}", serde_json::to_string_pretty(&records)?),
+        (OutputFormat::Sarif, _) => println!("{}", serde_json::to_string_pretty(&report::sarif(&records))?),
+        (OutputFormat::Text, Some(breakdown)) => eprintln!("\n{}", breakdown.report(STATS_TOP)),
+        (OutputFormat::Pretty, Some(breakdown)) => eprintln!("{}", breakdown.report(STATS_TOP)),
+        (OutputFormat::Text | OutputFormat::Pretty, None) => {}
+    }
+
+    if config.profile || config.profile_json.is_some() {
+        let profile = metrics.profile();
+        if config.profile {
+            eprintln!("\n{}", profile.report());
+        }
+        if let Some(path) = &config.profile_json {
+            fs::write(path, serde_json::to_string_pretty(&profile)?)?;
+        }
     }
 
     if total_errors > 0 {
         error!(total_errors, "Type checking failed");
-        eprintln!("\nFound {} error(s)", total_errors);
+        // Structured formats keep stdout to the document, so the summary always goes to stderr.
+        // Pretty diagnostics already end with a blank line
+        if format == OutputFormat::Text {
+            eprintln!("\nFound {} error(s)", total_errors);
+        } else {
+            eprintln!("Found {} error(s)", total_errors);
+        }
         std::process::exit(1);
     } else {
         info!("All type checks passed");
-        if !config.no_color {
-            println!("\x1b[32m✓ All checks passed\x1b[0m");
-        } else {
-            println!("✓ All checks passed");
+        // In JSON and SARIF mode the document is the whole of stdout
+        if matches!(format, OutputFormat::Text | OutputFormat::Pretty) {
+            if color {
+                println!("\x1b[32m✓ All checks passed\x1b[0m");
+            } else {
+                println!("✓ All checks passed");
+            }
         }
     }
 
     Ok(())
 }
-
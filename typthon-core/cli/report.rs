@@ -0,0 +1,118 @@
+//! Structured diagnostic output: `--output json` and `--output sarif`
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use typthon::compiler::analysis::checker::TypeError;
+use typthon::compiler::analysis::LintDiagnostic;
+use typthon::compiler::ast::LineIndex;
+use typthon::compiler::errors::codes;
+
+/// One diagnostic of the `--output json` array. Fields are only ever added,
+/// so tooling can rely on the existing ones
+#[derive(Debug, Clone, Serialize)]
+pub struct Record {
+    pub file: String,
+    /// 1-based; 0 for diagnostics about the module as a whole
+    pub line: usize,
+    /// 0-based
+    pub col: usize,
+    pub end_line: usize,
+    pub end_col: usize,
+    /// `error`, `warning` or `note`
+    pub severity: &'static str,
+    pub code: Option<&'static str>,
+    /// Name of a `TYP` code, e.g. `type-mismatch`
+    pub kind: Option<&'static str>,
+    pub message: String,
+    pub suggestions: Vec<String>,
+}
+
+impl Record {
+    pub fn from_error(file: &str, error: &TypeError, index: &LineIndex) -> Self {
+        let (end_line, end_col) = error.range.as_ref()
+            .map_or((error.line, error.col), |range| index.offset_to_position(range.end));
+        Self {
+            file: file.to_string(),
+            line: error.line,
+            col: error.col,
+            end_line,
+            end_col,
+            severity: error.severity.as_str(),
+            code: error.code,
+            kind: error.code.and_then(codes::name),
+            message: error.message.clone(),
+            suggestions: error.suggestions.clone(),
+        }
+    }
+
+    pub fn from_lint(file: &str, lint: &LintDiagnostic, index: &LineIndex) -> Self {
+        let location = lint.location(index);
+        Self {
+            file: file.to_string(),
+            line: location.line,
+            col: location.col,
+            end_line: location.end_line,
+            end_col: location.end_col,
+            severity: lint.severity().as_str(),
+            code: Some(lint.code),
+            kind: codes::name(lint.code),
+            message: lint.message.clone(),
+            suggestions: lint.fix.iter().map(|fix| fix.title.clone()).collect(),
+        }
+    }
+}
+
+/// A SARIF 2.1.0 log with one run; every code seen becomes a rule that its
+/// results point back to
+pub fn sarif(records: &[Record]) -> Value {
+    let mut rules: Vec<&'static str> = records.iter().filter_map(|record| record.code).collect();
+    rules.sort_unstable();
+    rules.dedup();
+
+    let results: Vec<Value> = records.iter().map(|record| {
+        let mut location = json!({ "artifactLocation": { "uri": record.file } });
+        // SARIF lines and columns are 1-based; module-level diagnostics have no region
+        if record.line > 0 {
+            location["region"] = json!({
+                "startLine": record.line,
+                "startColumn": record.col + 1,
+                "endLine": record.end_line,
+                "endColumn": record.end_col + 1,
+            });
+        }
+
+        let mut result = json!({
+            "level": record.severity,
+            "message": { "text": record.message },
+            "locations": [{ "physicalLocation": location }],
+        });
+        if let Some(code) = record.code {
+            result["ruleId"] = json!(code);
+            result["ruleIndex"] = json!(rules.binary_search(&code).unwrap_or_default());
+        }
+        result
+    }).collect();
+
+    let rules: Vec<Value> = rules.iter().map(|code| {
+        let mut rule = json!({ "id": code });
+        if let Some(name) = codes::name(code) {
+            rule["name"] = json!(name);
+        }
+        rule
+    }).collect();
+
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "typthon",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }]
+    })
+}
@@ -0,0 +1,140 @@
+//! Finding the Python files under the paths given on the command line
+
+use glob::{MatchOptions, Pattern};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use typthon::compiler::frontend::config::Config as ProjectConfig;
+
+/// Directories never descended into, on top of hidden ones and virtualenvs
+const SKIPPED_DIRS: &[&str] = &["__pycache__", "node_modules"];
+
+/// `--include` / `--exclude` patterns, matched against paths relative to the
+/// directory being walked. A pattern without a `/` matches any single file or
+/// directory name, so `--exclude tests` skips every `tests` directory
+#[derive(Debug, Default)]
+pub struct SourceFilter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+impl SourceFilter {
+    pub fn include(&mut self, pattern: &str) -> Result<(), String> {
+        self.include.push(Self::pattern("--include", pattern)?);
+        Ok(())
+    }
+
+    pub fn exclude(&mut self, pattern: &str) -> Result<(), String> {
+        self.exclude.push(Self::pattern("--exclude", pattern)?);
+        Ok(())
+    }
+
+    fn pattern(flag: &str, pattern: &str) -> Result<Pattern, String> {
+        Pattern::new(pattern.trim_end_matches('/'))
+            .map_err(|e| format!("Invalid pattern for {}: {}: {}", flag, pattern, e))
+    }
+
+    fn matches(pattern: &Pattern, relative: &Path) -> bool {
+        let options = MatchOptions { require_literal_separator: true, ..Default::default() };
+        if pattern.as_str().contains('/') {
+            pattern.matches_path_with(relative, options)
+        } else {
+            relative.file_name().is_some_and(|name| pattern.matches_with(&name.to_string_lossy(), options))
+        }
+    }
+
+    fn excludes(&self, relative: &Path) -> bool {
+        self.exclude.iter().any(|pattern| Self::matches(pattern, relative))
+    }
+
+    fn includes(&self, relative: &Path) -> bool {
+        self.include.is_empty() || self.include.iter().any(|pattern| Self::matches(pattern, relative))
+    }
+}
+
+/// Python files under `paths` the project config and `filter` include,
+/// sorted and without duplicates. Files named directly are always kept;
+/// directories are walked recursively, skipping hidden directories,
+/// `__pycache__` and virtualenvs, and following each symlinked directory once
+pub fn collect_sources(paths: &[PathBuf], project: &ProjectConfig, filter: &SourceFilter) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            let mut visited = HashSet::new();
+            walk(path, path, project, filter, &mut visited, &mut files);
+        } else {
+            files.push(path.clone());
+        }
+    }
+    files.sort();
+    files.dedup();
+    files
+}
+
+fn walk(
+    root: &Path,
+    dir: &Path,
+    project: &ProjectConfig,
+    filter: &SourceFilter,
+    visited: &mut HashSet<PathBuf>,
+    out: &mut Vec<PathBuf>,
+) {
+    // A symlink back up the tree leads to a directory already walked
+    let Ok(canonical) = dir.canonicalize() else { return };
+    if !visited.insert(canonical) {
+        return;
+    }
+    let Ok(entries) = fs::read_dir(dir) else { return };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        let relative = path.strip_prefix(root).unwrap_or(&path);
+        if name.starts_with('.') || filter.excludes(relative) {
+            continue;
+        }
+
+        if path.is_dir() {
+            if !SKIPPED_DIRS.contains(&name.as_ref()) && !is_virtualenv(&path) {
+                walk(root, &path, project, filter, visited, out);
+            }
+        } else if path.extension().is_some_and(|ext| ext == "py")
+            && filter.includes(relative)
+            && project.should_check(&path)
+        {
+            out.push(path.strip_prefix(".").map(Path::to_path_buf).unwrap_or(path));
+        }
+    }
+}
+
+/// Virtualenvs are marked by `pyvenv.cfg`, whatever the directory is called
+fn is_virtualenv(dir: &Path) -> bool {
+    dir.join("pyvenv.cfg").is_file()
+}
+
+/// `.pyi` files under the `--stubs-path` directories, sorted
+pub fn collect_stubs(dirs: &[PathBuf]) -> Vec<PathBuf> {
+    fn walk(dir: &Path, visited: &mut HashSet<PathBuf>, out: &mut Vec<PathBuf>) {
+        let Ok(canonical) = dir.canonicalize() else { return };
+        if !visited.insert(canonical) {
+            return;
+        }
+        let Ok(entries) = fs::read_dir(dir) else { return };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.is_dir() {
+                walk(&path, visited, out);
+            } else if path.extension().is_some_and(|ext| ext == "pyi") {
+                out.push(path);
+            }
+        }
+    }
+
+    let mut stubs = Vec::new();
+    let mut visited = HashSet::new();
+    for dir in dirs {
+        walk(dir, &mut visited, &mut stubs);
+    }
+    stubs.sort();
+    stubs
+}
@@ -0,0 +1,87 @@
+//! `--watch`: check once, then again whenever the checked files change
+//!
+//! Only the changed files and the files importing them are checked again,
+//! and only their diagnostics are printed. Each run ends with a summary
+//! line giving the files checked, the time taken and the errors now in the
+//! whole project.
+
+use super::{project_imports, read_tasks, Checking};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Instant;
+use typthon::infrastructure::concurrency::FileChange;
+use typthon::infrastructure::WatchSession;
+
+/// Check `files`, then watch them and the files `discover` finds until
+/// interrupted
+pub fn run(checking: &Checking, discover: impl Fn() -> Vec<PathBuf>, files: Vec<PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_time().build()?;
+    let session = WatchSession::new();
+    // The session sees absolute paths; diagnostics use the paths as found
+    let mut shown: HashMap<PathBuf, PathBuf> = HashMap::new();
+    let mut failing: HashMap<PathBuf, usize> = HashMap::new();
+
+    for file in &files {
+        shown.insert(checking.resolver.absolute(file), file.clone());
+    }
+    check(checking, &session, &files, &mut failing);
+    eprintln!("Watching for changes; press Ctrl-C to stop");
+
+    loop {
+        let mut found = Vec::new();
+        let changes = runtime.block_on(session.next_changes(|| {
+            found = discover();
+            found.iter().map(|file| checking.resolver.absolute(file)).collect()
+        }))?;
+        for file in found {
+            shown.entry(checking.resolver.absolute(&file)).or_insert(file);
+        }
+
+        let recheck = session.recheck_set(&changes);
+        let display = |path: &PathBuf| shown.get(path).unwrap_or(path).display().to_string();
+        let summary: Vec<String> = changes.iter()
+            .map(|change| match change {
+                FileChange::Created(path) => format!("{} created", display(path)),
+                FileChange::Modified(path) => format!("{} changed", display(path)),
+                FileChange::Removed(path) => format!("{} removed", display(path)),
+            })
+            .collect();
+        eprintln!("\n[watch] {}", summary.join(", "));
+
+        for change in &changes {
+            checking.modules.forget(change.path());
+            if let FileChange::Removed(path) = change {
+                session.untrack(path);
+                failing.remove(path);
+            }
+        }
+        for path in &recheck {
+            checking.modules.forget(path);
+        }
+        let files: Vec<PathBuf> = recheck.iter().map(|path| shown.get(path).unwrap_or(path).clone()).collect();
+        check(checking, &session, &files, &mut failing);
+    }
+}
+
+/// Check `files` and print their diagnostics, then the summary line
+fn check(checking: &Checking, session: &WatchSession, files: &[PathBuf], failing: &mut HashMap<PathBuf, usize>) {
+    let started = Instant::now();
+    let tasks = read_tasks(files, checking.resolver);
+    let mut results = checking.check(&tasks);
+    let mut records = Vec::new();
+
+    for task in &tasks {
+        let absolute = checking.resolver.absolute(&task.path);
+        session.track(&absolute, &task.content, &project_imports(task, checking.resolver, &checking.modules));
+        if let Some(result) = results.remove(&task.id) {
+            failing.insert(absolute, checking.report(task, &result, &mut records));
+        }
+    }
+
+    eprintln!(
+        "checked {} file(s) in {}ms, {} error(s)",
+        tasks.len(),
+        started.elapsed().as_millis(),
+        failing.values().sum::<usize>(),
+    );
+}
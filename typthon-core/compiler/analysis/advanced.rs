@@ -67,11 +67,18 @@ impl AdvancedTypeAnalyzer {
         }
     }
 
+    /// Whether every use of `rec_name` in `ty` is under a constructor; one
+    /// standing alone as a union member, as in `rec X. int | X`, unfolds
+    /// to itself and says nothing about the values it holds
     fn has_guard(&self, ty: &Type, rec_name: &str) -> bool {
         match ty {
             Type::Class(name) if name == rec_name => false,
-            Type::List(_) | Type::Tuple(_) | Type::Dict(_, _) => true,
-            Type::Union(types) => types.iter().any(|t| self.has_guard(t, rec_name)),
+            Type::List(_) | Type::Set(_) | Type::Tuple(_) | Type::VarTuple(_) | Type::Dict(_, _) | Type::Function(_, _) | Type::Generic(_, _) => true,
+            Type::Union(types) | Type::Intersection(types) => types.iter().all(|t| self.has_guard(t, rec_name)),
+            Type::Refinement(inner, _) | Type::Effect(inner, _) => self.has_guard(inner, rec_name),
+            // An inner binding of the same name shadows this one
+            Type::Recursive(inner, _) if inner == rec_name => true,
+            Type::Recursive(inner, body) => self.has_guard(body, rec_name) && self.has_guard(body, inner),
             _ => true,
         }
     }
@@ -101,18 +108,15 @@ impl AdvancedTypeAnalyzer {
         }
     }
 
-    /// Evaluate a conditional type
+    /// Evaluate a conditional type, reduced to the branch its condition picks
+    /// unless that depends on type variables
     pub fn eval_conditional(&self, condition: &TypeCondition, then_type: &Type, else_type: &Type) -> Type {
-        if self.eval_condition(condition) { then_type.clone() } else { else_type.clone() }
-    }
-
-    fn eval_condition(&self, condition: &TypeCondition) -> bool {
-        match condition {
-            TypeCondition::Extends(sub, sup) => sub.is_subtype(sup),
-            TypeCondition::Equal(a, b) => a == b,
-            TypeCondition::HasProperty(ty, _) => matches!(ty, Type::Class(_)), // Would need class definition lookup
-            TypeCondition::Custom(_) => false,
-        }
+        let conditional = Type::Conditional {
+            condition: Box::new(condition.clone()),
+            then_type: Box::new(then_type.clone()),
+            else_type: Box::new(else_type.clone()),
+        };
+        conditional.reduce_conditionals(&|sub: &str, sup: &str| sub == sup)
     }
 
     /// Create a dependent length type for collections
@@ -197,6 +201,18 @@ mod tests {
         assert!(analyzer.is_productive(&list_ty));
     }
 
+    #[test]
+    fn test_unguarded_recursion_is_not_productive() {
+        let analyzer = AdvancedTypeAnalyzer::new();
+        let var = || Type::Class("X".to_string());
+        let rec = |body: Type| Type::Recursive("X".to_string(), Box::new(body));
+
+        assert!(!analyzer.is_productive(&rec(var())));
+        assert!(!analyzer.is_productive(&rec(Type::Union(vec![Type::Int, var()]))));
+        assert!(analyzer.is_productive(&rec(Type::Union(vec![Type::Int, Type::List(Box::new(var()))]))));
+        assert!(analyzer.is_productive(&recursive::json_type()));
+    }
+
     #[test]
     fn test_json_type() {
         let json_ty = recursive::json_type();
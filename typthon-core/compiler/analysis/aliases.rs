@@ -0,0 +1,137 @@
+//! Type aliases
+//!
+//! `Vector = list[float]` at module level, an assignment annotated
+//! `TypeAlias`, or a `type Vector = list[float]` statement names a type that
+//! annotations can use in its place. A plain assignment only counts when its
+//! value reads as a type: a builtin or class name, a subscripted generic, or
+//! a `|` union of those. Aliases are collected before the module is checked,
+//! so annotations, quoted forward references among them, can name ones
+//! defined further down.
+
+use crate::compiler::types::Type;
+use rustpython_parser::ast::*;
+use std::collections::HashSet;
+
+/// Names that are types without being classes of the module
+const BUILTIN_TYPES: &[&str] = &["int", "float", "str", "bool", "bytes", "object", "complex", "Any"];
+
+/// Names that make a type when subscripted
+const GENERIC_TYPES: &[&str] = &[
+    "list", "List", "dict", "Dict", "set", "Set", "frozenset", "FrozenSet", "tuple", "Tuple", "type", "Type",
+    "Union", "Optional", "Callable", "Literal", "Annotated", "Sequence", "MutableSequence", "Mapping",
+    "MutableMapping", "Iterable", "Iterator", "Generator", "Awaitable", "Coroutine",
+];
+
+/// Module-level aliases in `body`, by name, with the statement defining each
+pub fn collect_aliases(body: &[Stmt]) -> Vec<(String, &Stmt, &Expr)> {
+    let mut known: HashSet<String> = body.iter()
+        .filter_map(|stmt| match stmt {
+            Stmt::ClassDef(class_def) => Some(class_def.name.to_string()),
+            _ => None,
+        })
+        .collect();
+    let mut aliases = Vec::new();
+    for stmt in body {
+        if let Some((name, value)) = alias_definition(stmt, &known) {
+            known.insert(name.clone());
+            aliases.push((name, stmt, value));
+        }
+    }
+    aliases
+}
+
+/// Name and value of the alias `stmt` defines, when `known` holds the class
+/// and alias names it may use
+pub fn alias_definition<'a>(stmt: &'a Stmt, known: &HashSet<String>) -> Option<(String, &'a Expr)> {
+    match stmt {
+        Stmt::TypeAlias(alias) => match &*alias.name {
+            Expr::Name(name) => Some((name.id.to_string(), &alias.value)),
+            _ => None,
+        },
+        Stmt::AnnAssign(ann_assign) if is_type_alias(&ann_assign.annotation) => match (&*ann_assign.target, &ann_assign.value) {
+            (Expr::Name(name), Some(value)) => Some((name.id.to_string(), &**value)),
+            _ => None,
+        },
+        Stmt::Assign(assign) => match assign.targets.as_slice() {
+            [Expr::Name(name)] if is_type_expr(&assign.value, known) => Some((name.id.to_string(), &*assign.value)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Whether `expr` reads as a type rather than a value
+pub fn is_type_expr(expr: &Expr, known: &HashSet<String>) -> bool {
+    match expr {
+        Expr::Name(name) => BUILTIN_TYPES.contains(&name.id.as_str()) || known.contains(name.id.as_str()),
+        Expr::Subscript(subscript) => match &*subscript.value {
+            Expr::Name(name) => GENERIC_TYPES.contains(&name.id.as_str()) || known.contains(name.id.as_str()),
+            value => is_typing(value),
+        },
+        Expr::BinOp(binop) if matches!(binop.op, Operator::BitOr) => {
+            let side = |expr: &Expr| matches!(expr, Expr::Constant(c) if matches!(c.value, Constant::None)) || is_type_expr(expr, known);
+            side(&binop.left) && side(&binop.right)
+        }
+        expr => is_typing(expr),
+    }
+}
+
+/// Whether the recursive alias `name` refers to itself other than inside a
+/// container, which would make it an infinite type
+pub fn is_unguarded(ty: &Type, name: &str) -> bool {
+    match ty {
+        Type::Class(class_name) => class_name == name,
+        Type::Union(members) => members.iter().any(|member| is_unguarded(member, name)),
+        _ => false,
+    }
+}
+
+fn is_type_alias(annotation: &Expr) -> bool {
+    match annotation {
+        Expr::Name(name) => name.id.as_str() == "TypeAlias",
+        Expr::Attribute(attr) => attr.attr.as_str() == "TypeAlias" && is_typing(annotation),
+        _ => false,
+    }
+}
+
+/// `typing.X` or `typing_extensions.X`
+fn is_typing(expr: &Expr) -> bool {
+    matches!(expr, Expr::Attribute(attr)
+        if matches!(&*attr.value, Expr::Name(module) if matches!(module.id.as_str(), "typing" | "typing_extensions")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::frontend::parse_module;
+
+    fn aliases(source: &str) -> Vec<String> {
+        let Mod::Module(module) = parse_module(source).unwrap() else { unreachable!() };
+        collect_aliases(&module.body).into_iter().map(|(name, _, _)| name).collect()
+    }
+
+    #[test]
+    fn test_collect_aliases() {
+        let source = "\
+Vector = list[float]
+Number = int | float
+MaybeNode = Node | None
+Pair: TypeAlias = 'tuple[int, int]'
+type Matrix = list[Vector]
+Also = Vector
+count = 0
+first = items[0]
+other = some_value
+class Node: pass
+";
+        assert_eq!(aliases(source), vec!["Vector", "Number", "MaybeNode", "Pair", "Matrix", "Also"]);
+    }
+
+    #[test]
+    fn test_unguarded_recursion() {
+        let name = "Tree";
+        let tree = || Type::Class(name.to_string());
+        assert!(is_unguarded(&Type::Union(vec![tree(), Type::Int]), name));
+        assert!(!is_unguarded(&Type::Union(vec![Type::List(Box::new(tree())), Type::Int]), name));
+    }
+}
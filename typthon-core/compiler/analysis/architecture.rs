@@ -0,0 +1,395 @@
+//! Architecture rules - layering constraints on the import graph
+//!
+//! Project files are assigned to layers by path globs (see
+//! `ArchitectureConfig`). Every import that resolves to a project file is an
+//! edge between two layers, and edges the rules don't allow are reported at
+//! the import statement. Names re-exported through a package `__init__` count
+//! as imports of the module that defines them.
+
+use rustpython_parser::ast::*;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use crate::compiler::frontend::config::{ArchitectureConfig, UnknownLayerPolicy};
+use crate::compiler::frontend::parse_module;
+use crate::infrastructure::paths::ModulePath;
+
+/// An import crossing layers in a direction the rules forbid
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayerViolation {
+    /// Rule broken, e.g. `ui → db`
+    pub rule: String,
+    pub message: String,
+    /// Byte range of the import statement
+    pub range: Range<usize>,
+}
+
+/// A name a module binds by importing it: `from <level><module> import <name>`,
+/// or the module itself when `name` is `None`
+#[derive(Debug, Clone)]
+struct Reexport {
+    level: u32,
+    module: Option<String>,
+    name: Option<String>,
+}
+
+/// Maps import statements to the project files they load
+pub struct ImportResolver {
+    root: PathBuf,
+    /// Names bound by imports at the top of each file, parsed on demand
+    reexports: Mutex<HashMap<ModulePath, Arc<HashMap<String, Reexport>>>>,
+}
+
+impl ImportResolver {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into(), reexports: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Absolute form of a path given relative to the project root
+    pub fn absolute(&self, file: &Path) -> PathBuf {
+        if file.is_absolute() { file.to_path_buf() } else { self.root.join(file) }
+    }
+
+    /// Dotted module name of a project file (`app/db/__init__.py` is `app.db`)
+    pub fn module_name(&self, file: &Path) -> String {
+        let relative = file.strip_prefix(&self.root).unwrap_or(file).with_extension("");
+        let mut parts: Vec<String> = relative.components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        if parts.last().is_some_and(|last| last == "__init__") {
+            parts.pop();
+        }
+        parts.join(".")
+    }
+
+    /// Project files imported by `stmt` in `file`; each is the file that
+    /// defines the imported name, after following re-exports
+    pub fn targets(&self, file: &Path, stmt: &Stmt) -> Vec<PathBuf> {
+        let file = self.absolute(file);
+        match stmt {
+            Stmt::Import(import) => import.names.iter()
+                .filter_map(|alias| self.resolve_module(&self.root, alias.name.as_str()))
+                .collect(),
+            Stmt::ImportFrom(import) => {
+                let level = import.level.map_or(0, |level| level.to_u32());
+                let module = import.module.as_ref().map(|m| m.as_str());
+                import.names.iter()
+                    .filter_map(|alias| {
+                        self.resolve_from(&file, level, module, alias.name.as_str(), &mut HashSet::new())
+                    })
+                    .collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Module a `from ... import` statement names, e.g. `app.services`
+    /// for `from app.services import Customer`
+    fn named_module(&self, file: &Path, stmt: &Stmt) -> Option<String> {
+        let Stmt::ImportFrom(import) = stmt else { return None };
+        let level = import.level.map_or(0, |level| level.to_u32());
+        let module = import.module.as_ref().map(|m| m.as_str());
+        let package = self.resolve_from(&self.absolute(file), level, module, "*", &mut HashSet::new())?;
+        Some(self.module_name(&package))
+    }
+
+    /// File loaded by `module` (`a.b` as `a/b.py` or `a/b/__init__.py`) under `base`
+    fn resolve_module(&self, base: &Path, module: &str) -> Option<PathBuf> {
+        let path = module.split('.').filter(|part| !part.is_empty()).fold(base.to_path_buf(), |path, part| path.join(part));
+        module_file(&path)
+    }
+
+    /// File defining `name` in `from <level><module> import <name>` inside `file`
+    fn resolve_from(
+        &self,
+        file: &Path,
+        level: u32,
+        module: Option<&str>,
+        name: &str,
+        seen: &mut HashSet<(PathBuf, String)>,
+    ) -> Option<PathBuf> {
+        let base = if level == 0 {
+            self.root.clone()
+        } else {
+            let mut dir = file.parent()?.to_path_buf();
+            for _ in 1..level {
+                dir = dir.parent()?.to_path_buf();
+            }
+            dir
+        };
+        let package = module.unwrap_or("").split('.').filter(|part| !part.is_empty()).fold(base, |path, part| path.join(part));
+
+        if name != "*" {
+            // `from pkg import submodule`
+            if let Some(submodule) = module_file(&package.join(name)) {
+                return Some(submodule);
+            }
+        }
+        let defining = module_file(&package)?;
+        if name == "*" {
+            return Some(defining);
+        }
+        Some(self.origin(defining, name, seen))
+    }
+
+    /// Follow `name` through re-exports to the file that defines it
+    fn origin(&self, file: PathBuf, name: &str, seen: &mut HashSet<(PathBuf, String)>) -> PathBuf {
+        if !seen.insert((file.clone(), name.to_string())) {
+            return file;
+        }
+        let reexports = self.reexports(&file);
+        let target = reexports.get(name).and_then(|reexport| match &reexport.name {
+            Some(original) => self.resolve_from(&file, reexport.level, reexport.module.as_deref(), original, seen),
+            None => self.resolve_module(&self.root, reexport.module.as_deref()?),
+        });
+        // Names from outside the project belong to the module re-exporting them
+        target.unwrap_or(file)
+    }
+
+    fn reexports(&self, file: &Path) -> Arc<HashMap<String, Reexport>> {
+        let mut cache = self.reexports.lock().unwrap();
+        cache.entry(ModulePath::new(file))
+            .or_insert_with(|| Arc::new(collect_reexports(file)))
+            .clone()
+    }
+}
+
+/// `path.py` or the package `path/__init__.py`, if either exists
+pub(crate) fn module_file(path: &Path) -> Option<PathBuf> {
+    let package = path.join("__init__.py");
+    if package.is_file() {
+        return Some(package);
+    }
+    let module = path.with_file_name(format!("{}.py", path.file_name()?.to_string_lossy()));
+    module.is_file().then_some(module)
+}
+
+fn collect_reexports(file: &Path) -> HashMap<String, Reexport> {
+    let Some(Mod::Module(ModModule { body, .. })) = fs::read_to_string(file).ok()
+        .and_then(|source| parse_module(&source).ok()) else {
+        return HashMap::new();
+    };
+
+    let mut reexports = HashMap::new();
+    for stmt in &body {
+        match stmt {
+            Stmt::ImportFrom(import) => {
+                for alias in import.names.iter().filter(|alias| alias.name.as_str() != "*") {
+                    let bound = alias.asname.as_ref().unwrap_or(&alias.name);
+                    reexports.insert(bound.to_string(), Reexport {
+                        level: import.level.map_or(0, |level| level.to_u32()),
+                        module: import.module.as_ref().map(|m| m.to_string()),
+                        name: Some(alias.name.to_string()),
+                    });
+                }
+            }
+            // Only `import a.b as c` binds the module itself
+            Stmt::Import(import) => {
+                for alias in &import.names {
+                    if let Some(bound) = &alias.asname {
+                        reexports.insert(bound.to_string(), Reexport {
+                            level: 0,
+                            module: Some(alias.name.to_string()),
+                            name: None,
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    reexports
+}
+
+struct Layer {
+    name: String,
+    patterns: Vec<glob::Pattern>,
+}
+
+/// Layers and the dependencies allowed between them
+pub struct ArchitectureRules {
+    layers: Vec<Layer>,
+    /// Layers each layer may import by index; `None` orders layers top-down
+    allow: Option<Vec<HashSet<usize>>>,
+    unknown: UnknownLayerPolicy,
+    resolver: ImportResolver,
+}
+
+impl ArchitectureRules {
+    /// Build rules from config; globs are relative to `root`
+    pub fn new(config: &ArchitectureConfig, root: impl Into<PathBuf>) -> Result<Self, String> {
+        let mut layers = Vec::new();
+        for layer in &config.layers {
+            let patterns = layer.paths.iter()
+                .map(|path| glob::Pattern::new(path)
+                    .map_err(|e| format!("Invalid path '{}' for layer '{}': {}", path, layer.name, e)))
+                .collect::<Result<_, _>>()?;
+            layers.push(Layer { name: layer.name.clone(), patterns });
+        }
+
+        let index = |name: &str| layers.iter().position(|layer| layer.name == name)
+            .ok_or_else(|| format!("Unknown layer '{}' in architecture.allow", name));
+        let allow = if config.allow.is_empty() {
+            None
+        } else {
+            let mut allow = vec![HashSet::new(); layers.len()];
+            for (from, targets) in &config.allow {
+                let from = index(from)?;
+                for to in targets {
+                    allow[from].insert(index(to)?);
+                }
+            }
+            Some(allow)
+        };
+
+        Ok(Self { layers, allow, unknown: config.unknown, resolver: ImportResolver::new(root) })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    pub fn resolver(&self) -> &ImportResolver {
+        &self.resolver
+    }
+
+    /// Name of the layer `file` belongs to; the first matching layer wins
+    pub fn layer_of(&self, file: &Path) -> Option<&str> {
+        self.layer_index(&self.resolver.absolute(file)).map(|i| self.layers[i].name.as_str())
+    }
+
+    fn layer_index(&self, file: &Path) -> Option<usize> {
+        let relative = file.strip_prefix(self.resolver.root()).ok()?;
+        let options = glob::MatchOptions { require_literal_separator: true, ..Default::default() };
+        self.layers.iter().position(|layer| {
+            layer.patterns.iter().any(|pattern| pattern.matches_path_with(relative, options))
+        })
+    }
+
+    fn allows(&self, from: usize, to: usize) -> bool {
+        from == to || match &self.allow {
+            Some(allow) => allow[from].contains(&to),
+            None => to > from,
+        }
+    }
+
+    /// Violations among the imports of one statement in `file`
+    pub fn check_import(&self, file: &Path, stmt: &Stmt) -> Vec<LayerViolation> {
+        let file = self.resolver.absolute(file);
+        // Code outside every layer is unconstrained
+        let Some(from) = self.layer_index(&file) else { return Vec::new() };
+        let from_name = &self.layers[from].name;
+        let named = self.resolver.named_module(&file, stmt);
+
+        let mut violations = Vec::new();
+        for target in self.resolver.targets(&file, stmt) {
+            let rule = match self.layer_index(&target) {
+                Some(to) if self.allows(from, to) => continue,
+                Some(to) => format!("{} → {}", from_name, self.layers[to].name),
+                None if self.unknown == UnknownLayerPolicy::Forbidden => format!("{} → unlayered", from_name),
+                None => continue,
+            };
+            let target_name = self.resolver.module_name(&target);
+            let via = match &named {
+                Some(named) if target_name != *named && !target_name.starts_with(&format!("{}.", named)) => {
+                    format!(" (re-exported by '{}')", named)
+                }
+                _ => String::new(),
+            };
+            let violation = LayerViolation {
+                message: format!(
+                    "import of '{}'{} from layer '{}' violates architecture rule {}",
+                    target_name, via, from_name, rule
+                ),
+                rule,
+                range: stmt.start().to_usize()..stmt.end().to_usize(),
+            };
+            if !violations.contains(&violation) {
+                violations.push(violation);
+            }
+        }
+        violations
+    }
+
+    /// Violations among every import in `module`, including nested ones
+    pub fn check_module(&self, file: &Path, module: &Mod) -> Vec<LayerViolation> {
+        import_statements(module).into_iter()
+            .flat_map(|stmt| self.check_import(file, stmt))
+            .collect()
+    }
+}
+
+/// Every `import` and `from ... import` in `module`, including those nested
+/// in functions, classes and conditionals
+pub fn import_statements(module: &Mod) -> Vec<&Stmt> {
+    fn walk<'a>(body: &'a [Stmt], out: &mut Vec<&'a Stmt>) {
+        for stmt in body {
+            match stmt {
+                Stmt::Import(_) | Stmt::ImportFrom(_) => out.push(stmt),
+                Stmt::FunctionDef(def) => walk(&def.body, out),
+                Stmt::AsyncFunctionDef(def) => walk(&def.body, out),
+                Stmt::ClassDef(class) => walk(&class.body, out),
+                Stmt::If(if_stmt) => {
+                    walk(&if_stmt.body, out);
+                    walk(&if_stmt.orelse, out);
+                }
+                Stmt::Try(try_stmt) => {
+                    walk(&try_stmt.body, out);
+                    for ExceptHandler::ExceptHandler(handler) in &try_stmt.handlers {
+                        walk(&handler.body, out);
+                    }
+                    walk(&try_stmt.orelse, out);
+                    walk(&try_stmt.finalbody, out);
+                }
+                Stmt::With(with) => walk(&with.body, out),
+                _ => {}
+            }
+        }
+    }
+
+    let mut statements = Vec::new();
+    if let Mod::Module(ModModule { body, .. }) = module {
+        walk(body, &mut statements);
+    }
+    statements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::frontend::config::LayerConfig;
+
+    fn layers(names: &[(&str, &str)]) -> ArchitectureConfig {
+        ArchitectureConfig {
+            layers: names.iter()
+                .map(|(name, path)| LayerConfig { name: name.to_string(), paths: vec![path.to_string()] })
+                .collect(),
+            ..ArchitectureConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_layer_order_and_allow_matrix() {
+        let config = layers(&[("ui", "ui/**"), ("services", "services/**"), ("db", "db/**")]);
+        let ordered = ArchitectureRules::new(&config, "/project").unwrap();
+        assert_eq!(ordered.layer_of(Path::new("ui/views.py")), Some("ui"));
+        assert_eq!(ordered.layer_of(Path::new("main.py")), None);
+        assert!(ordered.allows(0, 2));
+        assert!(!ordered.allows(2, 0));
+
+        let mut config = config;
+        config.allow.insert("ui".to_string(), vec!["services".to_string()]);
+        let matrix = ArchitectureRules::new(&config, "/project").unwrap();
+        assert!(matrix.allows(0, 1));
+        assert!(!matrix.allows(0, 2));
+
+        config.allow.insert("ui".to_string(), vec!["api".to_string()]);
+        assert!(ArchitectureRules::new(&config, "/project").is_err());
+    }
+}
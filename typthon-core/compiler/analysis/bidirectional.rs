@@ -1,5 +1,5 @@
-use crate::compiler::types::{Type, TypeContext};
-use crate::compiler::errors::{TypeError, ErrorCollector, SourceLocation};
+use crate::compiler::types::{KeywordSlot, Type, TypeContext};
+use crate::compiler::errors::{TypeError, ErrorCollector, ErrorKind, SourceLocation};
 use crate::compiler::analysis::inference::InferenceEngine;
 use crate::compiler::ast::{LineIndex, SourceLocationExt};
 use rustpython_parser::ast::{Expr, Constant, Operator, Comprehension};
@@ -61,9 +61,21 @@ impl BiInfer {
             Expr::Slice(s) => self.synth_slice(s),
             Expr::NamedExpr(n) => self.synthesize(&n.value),
             Expr::Starred(s) => self.synthesize(&s.value),
-            Expr::Await(a) => self.synthesize(&a.value),
-            Expr::Yield(y) => y.value.as_ref().map_or(Type::None, |v| self.synthesize(v)),
-            Expr::YieldFrom(y) => self.synthesize(&y.value),
+            Expr::Await(a) => {
+                let awaitable = self.synthesize(&a.value);
+                awaitable.awaited().unwrap_or(Type::Any)
+            }
+            // A yield evaluates to whatever is sent in, not the value it yields
+            Expr::Yield(y) => {
+                if let Some(v) = &y.value {
+                    self.synthesize(v);
+                }
+                Type::Any
+            }
+            Expr::YieldFrom(y) => {
+                self.synthesize(&y.value);
+                Type::Any
+            }
             Expr::FormattedValue(f) => {
                 self.synthesize(&f.value);
                 Type::Str
@@ -75,7 +87,7 @@ impl BiInfer {
     /// Check type (top-down): verify expression has expected type
     pub fn check(&mut self, expr: &Expr, expected: &Type) -> bool {
         let synthesized = self.synthesize(expr);
-        if !synthesized.is_subtype(expected) {
+        if !self.ctx.is_subtype(&synthesized, expected) {
             self.errors.add(TypeError::type_mismatch(
                 expected.clone(),
                 synthesized,
@@ -154,35 +166,96 @@ impl BiInfer {
     }
 
     fn synth_call(&mut self, call: &rustpython_parser::ast::ExprCall) -> Type {
-        let func_ty = self.synthesize(&call.func);
+        // The callee's effects don't change how it's called
+        let func_ty = match self.synthesize(&call.func) {
+            Type::Effect(inner, _) => *inner,
+            ty => ty,
+        };
 
         match func_ty {
             Type::Function(params, ret) => {
-                // Check argument count
-                if params.len() != call.args.len() {
-                    self.errors.add(TypeError::invalid_arg_count(
-                        params.len(),
-                        call.args.len(),
-                        SourceLocation::new(0, 0, 0, 0),
-                    ));
+                // Arguments from a `*iterable` onwards have no known position
+                let positional = call.args.iter().position(|arg| matches!(arg, Expr::Starred(_))).unwrap_or(call.args.len());
+                let unpacked = positional < call.args.len();
+                let sig = match &*call.func {
+                    Expr::Name(n) => self.ctx.get_signature(&n.id).filter(|sig| sig.params.len() == params.len()),
+                    _ => None,
+                };
+
+                let mut keyword_types: Vec<Option<Type>> = vec![None; call.keywords.len()];
+                match &sig {
+                    Some(sig) => {
+                        let names: Vec<Option<&str>> = call.keywords.iter().map(|kw| kw.arg.as_ref().map(|arg| arg.as_str())).collect();
+                        let binding = sig.bind(positional, unpacked, &names);
+                        if let Some(max) = binding.too_many {
+                            self.errors.add(TypeError::invalid_arg_count(max, positional, call.source_location(&self.line_index)));
+                        }
+                        for name in binding.missing {
+                            self.errors.add(TypeError::new(ErrorKind::MissingArgument { name }, call.source_location(&self.line_index)));
+                        }
+                        for ((keyword, slot), expected) in call.keywords.iter().zip(binding.keywords).zip(&mut keyword_types) {
+                            match slot {
+                                Ok(KeywordSlot::Param(i)) => *expected = Some(params[i].clone()),
+                                Ok(KeywordSlot::KwOnly(i)) => *expected = Some(sig.kwonly[i].1.clone()),
+                                Ok(KeywordSlot::Kwargs) => *expected = sig.kwargs.clone(),
+                                Ok(_) => {}
+                                Err(reason) => {
+                                    let name = keyword.arg.as_ref().map_or_else(String::new, |arg| arg.to_string());
+                                    self.errors.add(TypeError::new(
+                                        ErrorKind::InvalidKeyword { name, reason },
+                                        keyword.source_location(&self.line_index),
+                                    ));
+                                }
+                            }
+                        }
+                    }
+                    // Keywords and unpacking could fill any parameter
+                    None if call.keywords.is_empty() && !unpacked && params.len() != call.args.len() => {
+                        self.errors.add(TypeError::invalid_arg_count(
+                            params.len(),
+                            call.args.len(),
+                            call.source_location(&self.line_index),
+                        ));
+                    }
+                    None => {}
                 }
 
                 // Check argument types
-                for (i, (arg, param_ty)) in call.args.iter().zip(params.iter()).enumerate() {
+                for (i, arg) in call.args.iter().enumerate() {
                     let arg_ty = self.synthesize(arg);
-                    if !arg_ty.is_subtype(param_ty) {
-                        self.errors.add(TypeError::invalid_arg_type(
-                            format!("arg{}", i),
-                            param_ty.clone(),
-                            arg_ty,
-                            SourceLocation::new(0, 0, 0, 0),
-                        ));
+                    let expected = match &sig {
+                        Some(sig) => sig.positional_type(&params, i),
+                        None => params.get(i),
+                    };
+                    match expected {
+                        Some(param_ty) if i < positional && !self.ctx.is_subtype(&arg_ty, param_ty) => {
+                            self.errors.add(TypeError::invalid_arg_type(
+                                format!("arg{}", i),
+                                param_ty.clone(),
+                                arg_ty,
+                                arg.source_location(&self.line_index),
+                            ));
+                        }
+                        _ => {}
+                    }
+                }
+                for (keyword, expected) in call.keywords.iter().zip(keyword_types) {
+                    let arg_ty = self.synthesize(&keyword.value);
+                    if let (Some(name), Some(param_ty)) = (&keyword.arg, expected) {
+                        if !self.ctx.is_subtype(&arg_ty, &param_ty) {
+                            self.errors.add(TypeError::invalid_arg_type(
+                                name.to_string(),
+                                param_ty,
+                                arg_ty,
+                                keyword.source_location(&self.line_index),
+                            ));
+                        }
                     }
                 }
 
                 *ret
             }
-            Type::Class(name) => Type::Class(name), // Constructor call
+            Type::ClassObject(name) => Type::Class(name), // Constructor call
             _ => {
                 // Try to infer from builtins
                 if let Expr::Name(n) = &*call.func {
@@ -280,9 +353,10 @@ impl BiInfer {
             Type::List(elem) => *elem.clone(),
             Type::Set(elem) => *elem.clone(),
             Type::Tuple(elems) => Type::union(elems.clone()),
+            Type::VarTuple(elem) => *elem.clone(),
             Type::Dict(key, _) => *key.clone(), // Iterating dict yields keys
             Type::Str => Type::Str, // Iterating string yields strings
-            Type::Generic(name, args) if name == "Iterator" || name == "Iterable" => {
+            Type::Generic(name, args) if matches!(name.as_str(), "Generator" | "Iterator" | "Iterable") => {
                 args.first().cloned().unwrap_or_else(|| self.ctx.fresh_var())
             }
             _ => self.ctx.fresh_var(),
@@ -337,7 +411,7 @@ impl BiInfer {
         let _ = self.synthesize(&subscript.slice);
 
         match value_ty {
-            Type::List(elem) => *elem,
+            Type::List(elem) | Type::VarTuple(elem) => *elem,
             Type::Tuple(elems) => {
                 if let Expr::Constant(c) = &*subscript.slice { // Try to extract constant index
                     if let Constant::Int(idx) = &c.value {
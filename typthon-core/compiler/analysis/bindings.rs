@@ -0,0 +1,253 @@
+//! Binding sites
+//!
+//! The names a block of statements binds in its own scope, with where each
+//! is bound, for reporting what the checker inferred for them. Bodies of
+//! nested functions and classes are their own scopes and aren't entered;
+//! their names are bound where they're defined.
+
+use crate::compiler::errors::SourceLocation;
+use crate::compiler::types::Type;
+use rustpython_parser::ast::*;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+/// Inferred types of bound names, under their qualified names (`f.x` for
+/// `x` local to `f`), in the order they were first bound
+#[derive(Debug, Clone, Default)]
+pub struct InferredBindings {
+    entries: Vec<(String, Type, SourceLocation)>,
+    index: HashMap<String, usize>,
+}
+
+impl InferredBindings {
+    /// Record that `name` now has type `ty`; a name bound before keeps the
+    /// location it was first bound at
+    pub fn record(&mut self, name: String, ty: Type, location: SourceLocation) {
+        match self.index.get(&name) {
+            Some(&i) => self.entries[i].1 = ty,
+            None => {
+                self.index.insert(name.clone(), self.entries.len());
+                self.entries.push((name, ty, location));
+            }
+        }
+    }
+
+    /// Type `name` was last recorded with
+    pub fn get(&self, name: &str) -> Option<&Type> {
+        self.index.get(name).map(|&i| &self.entries[i].1)
+    }
+
+    pub fn entries(&self) -> &[(String, Type, SourceLocation)] {
+        &self.entries
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.index.clear();
+    }
+}
+
+/// Names `body` binds, in order, with the byte range of each binding. A
+/// name bound more than once appears once per binding
+pub fn binding_sites(body: &[Stmt]) -> Vec<(String, Range<usize>)> {
+    let mut sites = Vec::new();
+    for stmt in body {
+        stmt_sites(stmt, &mut sites);
+    }
+    sites
+}
+
+/// A function's parameters, with the byte range of each
+pub fn parameter_sites(args: &Arguments) -> Vec<(String, Range<usize>)> {
+    let positional = args.posonlyargs.iter().chain(&args.args).chain(&args.kwonlyargs).map(|arg| &arg.def);
+    positional.chain(args.vararg.as_deref()).chain(args.kwarg.as_deref())
+        .map(|arg| (arg.arg.to_string(), range(arg)))
+        .collect()
+}
+
+/// Names a comprehension's `for` clauses bind in its own scope, with the
+/// byte range of each
+pub fn comprehension_sites(generators: &[Comprehension]) -> Vec<(String, Range<usize>)> {
+    let mut sites = Vec::new();
+    for generator in generators {
+        target_sites(&generator.target, &mut sites);
+    }
+    sites
+}
+
+/// Names `body` declares `global` and those it declares `nonlocal`. Like
+/// bindings, declarations in nested functions and classes are theirs
+pub fn declarations(body: &[Stmt]) -> (HashSet<String>, HashSet<String>) {
+    let mut globals = HashSet::new();
+    let mut nonlocals = HashSet::new();
+    let mut pending: Vec<&Stmt> = body.iter().collect();
+    while let Some(stmt) = pending.pop() {
+        match stmt {
+            Stmt::Global(global) => globals.extend(global.names.iter().map(|name| name.to_string())),
+            Stmt::Nonlocal(nonlocal) => nonlocals.extend(nonlocal.names.iter().map(|name| name.to_string())),
+            Stmt::For(for_stmt) => pending.extend(for_stmt.body.iter().chain(&for_stmt.orelse)),
+            Stmt::AsyncFor(for_stmt) => pending.extend(for_stmt.body.iter().chain(&for_stmt.orelse)),
+            Stmt::While(while_stmt) => pending.extend(while_stmt.body.iter().chain(&while_stmt.orelse)),
+            Stmt::If(if_stmt) => pending.extend(if_stmt.body.iter().chain(&if_stmt.orelse)),
+            Stmt::With(with) => pending.extend(&with.body),
+            Stmt::AsyncWith(with) => pending.extend(&with.body),
+            Stmt::Try(try_stmt) => {
+                pending.extend(try_stmt.body.iter().chain(&try_stmt.orelse).chain(&try_stmt.finalbody));
+                for ExceptHandler::ExceptHandler(handler) in &try_stmt.handlers {
+                    pending.extend(&handler.body);
+                }
+            }
+            Stmt::Match(match_stmt) => pending.extend(match_stmt.cases.iter().flat_map(|case| &case.body)),
+            _ => {}
+        }
+    }
+    (globals, nonlocals)
+}
+
+fn stmt_sites(stmt: &Stmt, sites: &mut Vec<(String, Range<usize>)>) {
+    let block = |body: &[Stmt], sites: &mut Vec<(String, Range<usize>)>| {
+        for stmt in body {
+            stmt_sites(stmt, sites);
+        }
+    };
+    match stmt {
+        Stmt::FunctionDef(def) => sites.push((def.name.to_string(), range(stmt))),
+        Stmt::AsyncFunctionDef(def) => sites.push((def.name.to_string(), range(stmt))),
+        Stmt::ClassDef(class) => sites.push((class.name.to_string(), range(stmt))),
+        Stmt::Assign(assign) => assign.targets.iter().for_each(|target| target_sites(target, sites)),
+        Stmt::AnnAssign(ann_assign) => target_sites(&ann_assign.target, sites),
+        Stmt::AugAssign(aug_assign) => target_sites(&aug_assign.target, sites),
+        Stmt::Import(import) => {
+            for alias in &import.names {
+                let bound = alias.asname.as_ref().map_or_else(|| alias.name.split('.').next().unwrap_or_default(), |asname| asname.as_str());
+                sites.push((bound.to_string(), range(alias)));
+            }
+        }
+        Stmt::ImportFrom(import) => {
+            for alias in import.names.iter().filter(|alias| alias.name.as_str() != "*") {
+                sites.push((alias.asname.as_ref().unwrap_or(&alias.name).to_string(), range(alias)));
+            }
+        }
+        Stmt::For(for_stmt) => {
+            target_sites(&for_stmt.target, sites);
+            block(&for_stmt.body, sites);
+            block(&for_stmt.orelse, sites);
+        }
+        Stmt::AsyncFor(for_stmt) => {
+            target_sites(&for_stmt.target, sites);
+            block(&for_stmt.body, sites);
+            block(&for_stmt.orelse, sites);
+        }
+        Stmt::While(while_stmt) => {
+            block(&while_stmt.body, sites);
+            block(&while_stmt.orelse, sites);
+        }
+        Stmt::If(if_stmt) => {
+            block(&if_stmt.body, sites);
+            block(&if_stmt.orelse, sites);
+        }
+        Stmt::With(with) => {
+            with.items.iter().filter_map(|item| item.optional_vars.as_deref()).for_each(|vars| target_sites(vars, sites));
+            block(&with.body, sites);
+        }
+        Stmt::AsyncWith(with) => {
+            with.items.iter().filter_map(|item| item.optional_vars.as_deref()).for_each(|vars| target_sites(vars, sites));
+            block(&with.body, sites);
+        }
+        Stmt::Try(try_stmt) => {
+            block(&try_stmt.body, sites);
+            for ExceptHandler::ExceptHandler(handler) in &try_stmt.handlers {
+                if let Some(name) = &handler.name {
+                    sites.push((name.to_string(), range(handler)));
+                }
+                block(&handler.body, sites);
+            }
+            block(&try_stmt.orelse, sites);
+            block(&try_stmt.finalbody, sites);
+        }
+        Stmt::Match(match_stmt) => {
+            for case in &match_stmt.cases {
+                capture_sites(&case.pattern, sites);
+                block(&case.body, sites);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Names an assignment target binds; attributes and subscripts bind none
+fn target_sites(target: &Expr, sites: &mut Vec<(String, Range<usize>)>) {
+    match target {
+        Expr::Name(name) => sites.push((name.id.to_string(), range(name))),
+        Expr::Tuple(tuple) => tuple.elts.iter().for_each(|elt| target_sites(elt, sites)),
+        Expr::List(list) => list.elts.iter().for_each(|elt| target_sites(elt, sites)),
+        Expr::Starred(starred) => target_sites(&starred.value, sites),
+        _ => {}
+    }
+}
+
+/// Names a match pattern captures, with the byte range of each. A mapping
+/// pattern's `**rest` has no node of its own, so its range is the whole
+/// mapping pattern's
+pub fn pattern_sites(pattern: &Pattern) -> Vec<(String, Range<usize>)> {
+    let mut sites = Vec::new();
+    capture_sites(pattern, &mut sites);
+    sites
+}
+
+fn capture_sites(pattern: &Pattern, sites: &mut Vec<(String, Range<usize>)>) {
+    let captured = |name: &Identifier, pattern: &dyn Ranged| {
+        let end = pattern.end().to_usize();
+        (name.to_string(), end - name.len()..end)
+    };
+    match pattern {
+        Pattern::MatchAs(p) => {
+            sites.extend(p.name.as_ref().map(|name| captured(name, p)));
+            if let Some(inner) = &p.pattern {
+                capture_sites(inner, sites);
+            }
+        }
+        Pattern::MatchStar(p) => sites.extend(p.name.as_ref().map(|name| captured(name, p))),
+        Pattern::MatchMapping(p) => {
+            p.patterns.iter().for_each(|inner| capture_sites(inner, sites));
+            sites.extend(p.rest.as_ref().map(|name| (name.to_string(), range(p))));
+        }
+        Pattern::MatchSequence(p) => p.patterns.iter().for_each(|inner| capture_sites(inner, sites)),
+        Pattern::MatchClass(p) => p.patterns.iter().chain(&p.kwd_patterns).for_each(|inner| capture_sites(inner, sites)),
+        Pattern::MatchOr(p) => p.patterns.iter().for_each(|inner| capture_sites(inner, sites)),
+        Pattern::MatchValue(_) | Pattern::MatchSingleton(_) => {}
+    }
+}
+
+fn range(node: &impl Ranged) -> Range<usize> {
+    node.start().to_usize()..node.end().to_usize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::frontend::parse_module;
+
+    fn names(source: &str) -> Vec<String> {
+        let Mod::Module(module) = parse_module(source).unwrap() else { unreachable!() };
+        binding_sites(&module.body).into_iter().map(|(name, _)| name).collect()
+    }
+
+    #[test]
+    fn test_nested_scopes_are_not_entered() {
+        let source = "\
+import os.path
+a, (b, *c) = 1, (2, 3)
+def f(x):
+    y = x
+class C:
+    z = 1
+for i in range(3):
+    if i:
+        j = i
+with open('f') as fh:
+    pass
+";
+        assert_eq!(names(source), vec!["os", "a", "b", "c", "f", "C", "i", "j", "fh"]);
+    }
+}
@@ -1,23 +1,91 @@
-use crate::compiler::types::{Type, TypeContext};
+use crate::compiler::types::{Type, TypeContext, TypeRef, TypeCondition, OverrideOrigin, SignatureOverride, DependentConstraint};
+use crate::compiler::types::{FunctionSig, KeywordMismatch, KeywordSlot, Param};
+use crate::compiler::types::overrides::parse_member_path;
 use crate::compiler::analysis::{
     AdvancedTypeAnalyzer, EffectAnalyzer, RefinementAnalyzer,
-    BiInfer, ConstraintSolver, VarianceAnalyzer, Constraint
+    BiInfer, ConstraintSolver, VarianceAnalyzer, Constraint, ConditionAnalyzer, Narrowing,
+    CheckerPlugin, CheckerOptions, LengthBound, LintDiagnostic, LintLevel, ArchitectureRules,
+    ProtocolChecker, ModuleExports, ModuleResolver,
 };
-use rustpython_parser::ast::{Mod, ModModule, Stmt, Expr, ExprConstant, Constant, Operator};
-use num_traits::ToPrimitive;
+use crate::compiler::analysis::imports::{erase_sig_vars, erase_vars, load_time_imports, top_level_names, ModuleOrigin};
+use crate::compiler::analysis::protocols::ProtocolLibrary;
+use crate::compiler::analysis::lints::{self, Fix, OperandTypes, TextEdit};
+use crate::compiler::analysis::consteval::{self, ConstEnv};
+use crate::compiler::analysis::dataclasses::{self, Dataclass, DataclassOptions};
+use crate::compiler::analysis::constraints::{GenericType, TypeParameter};
+use crate::compiler::analysis::generics;
+use crate::compiler::analysis::aliases;
+use crate::compiler::analysis::bindings::{self, InferredBindings};
+use crate::compiler::analysis::decorators;
+use crate::compiler::analysis::effects;
+use crate::compiler::analysis::formatting::{self, FieldRef, ValueKind};
+use crate::compiler::analysis::narrowing::{self, type_guard};
+use crate::compiler::analysis::variance::Variance;
+use crate::compiler::analysis::items::item_range;
+use crate::compiler::analysis::reachability;
+use crate::compiler::analysis::refinement::Interval;
+use crate::compiler::analysis::scopes::ScopeKind;
+use crate::compiler::analysis::suppressions::Suppressions;
+use crate::compiler::analysis::undefined::{undefined_names, UndefinedName};
+use crate::compiler::analysis::unused_imports::unused_imports;
+use crate::compiler::analysis::unused_variables::unused_variables;
+use crate::compiler::errors::{codes, ErrorKind, Severity, SourceLocation};
+use crate::compiler::ast::{Docstring, LineIndex};
+use crate::compiler::analysis::lengths::{const_int, slice_indices};
+use crate::compiler::frontend::parse_module;
+use crate::infrastructure::{CancellationToken, CheckerScratch, PerformanceMetrics};
+use crate::infrastructure::metrics::counters;
+use crate::compiler::types::{ClassSchema, Effect, EffectSet, MemberKind, TypedDictKeys};
+use rustpython_parser::ast::{Mod, ModModule, Stmt, Expr, Comprehension, ExprAttribute, ExprBinOp, ExprCall, ExprCompare, ExprConstant, ExprDict, ExprLambda, ExprSlice, ExprSubscript, Pattern, StmtAsyncFunctionDef, StmtAugAssign, StmtClassDef, StmtFunctionDef, StmtIf, StmtMatch, WithItem, ExceptHandler, Keyword, CmpOp, Constant, ConversionFlag, Operator, Ranged};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tracing::{debug, error, info, instrument, warn};
 
 #[derive(Debug, Clone)]
 pub struct TypeError {
     pub message: String,
+    /// Stable category from `errors::codes`, e.g. `TYP001`; `None` for
+    /// errors outside the categories, such as a cancelled check
+    pub code: Option<&'static str>,
+    /// `Error` unless the check reporting it is only advisory
+    pub severity: Severity,
+    /// 1-based; 0 when the error has no node or no source text was given
+    /// (`set_source`)
     pub line: usize,
     pub col: usize,
+    /// Byte range of the offending node
+    pub range: Option<Range<usize>>,
+    /// Likely intended names, e.g. for a misspelled attribute
+    pub suggestions: Vec<String>,
+}
+
+impl TypeError {
+    /// An error about the module as a whole, without a location
+    pub fn new(message: impl Into<String>) -> Self {
+        Self { message: message.into(), code: None, severity: Severity::Error, line: 0, col: 0, range: None, suggestions: Vec::new() }
+    }
+
+    pub fn with_code(mut self, code: &'static str) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
 }
 
 impl std::fmt::Display for TypeError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Line {}, Col {}: {}", self.line, self.col, self.message)
+        write!(f, "Line {}, Col {}: ", self.line, self.col)?;
+        if let Some(code) = self.code {
+            write!(f, "{}[{}]: ", self.severity, code)?;
+        }
+        write!(f, "{}", self.message)
     }
 }
 
@@ -30,9 +98,234 @@ pub struct TypeChecker {
     bi_infer: BiInfer,
     constraints: ConstraintSolver,
     variance: VarianceAnalyzer,
+    conditions: ConditionAnalyzer,
     class_attributes: std::collections::HashMap<String, std::collections::HashMap<String, Type>>,
     current_class: Option<String>,
+    /// Checking the statements directly in a class body, where `def` makes a method
+    in_class_body: bool,
     current_function_return_type: Option<Type>,
+    /// Set while checking the body of a function that yields
+    current_generator: Option<GeneratorScope>,
+    /// Start offsets of the `match` statements whose cases cover every
+    /// value of their subject's type
+    exhaustive_matches: HashSet<usize>,
+    /// Start offsets of the calls to functions returning `Never`
+    never_calls: HashSet<usize>,
+    /// Phase timings (always) and rule timings (when `detailed_stats`)
+    metrics: Arc<PerformanceMetrics>,
+    detailed_stats: bool,
+    /// File name timings are attributed to
+    source_name: String,
+    /// Phase currently being timed; rule timings nest under it
+    current_phase: &'static str,
+    plugins: Vec<Box<dyn CheckerPlugin>>,
+    options: CheckerOptions,
+    /// Layering rules checked on imports, with the file being checked
+    architecture: Option<Arc<ArchitectureRules>>,
+    source_path: Option<PathBuf>,
+    /// Where imports are resolved and the exports of checked modules kept
+    modules: Option<Arc<ModuleResolver>>,
+    /// Modules whose checks are waiting on this one, by `ModuleOrigin::key`,
+    /// to spot import cycles
+    import_stack: Vec<String>,
+    /// Checking a `.pyi` stub: declarations only, no function bodies
+    stub: bool,
+    /// Signatures declared with `@overload`, by function name or `Class.method`
+    overloads: HashMap<String, OverloadSet>,
+    /// Set when an import led back to a file on `import_stack`
+    import_cycle: bool,
+    /// Modules bound by `import`, by the dotted name they're reached through
+    imported_modules: HashMap<String, Arc<ModuleExports>>,
+    /// Reads of names nothing in the module binds, reported by the
+    /// top-level statement they're in
+    undefined: Vec<UndefinedName>,
+    /// Lines of the source being checked, for error locations
+    line_index: Option<LineIndex>,
+    /// Text of the source being checked, for lints whose fixes edit it
+    source: Option<Arc<str>>,
+    /// `# type: ignore` comments of the source being checked
+    suppressions: Suppressions,
+    /// Lines whose `# type: ignore` silenced an error in the last check
+    used_suppressions: HashSet<usize>,
+    /// Overridden signatures used by the statement being checked
+    override_uses: Vec<SignatureOverride>,
+    /// Types of `==`/`!=` operands, recorded for type-aware lints
+    operand_types: OperandTypes,
+    /// Types of names where they're bound or used, by the name's byte
+    /// range, for editors to show
+    name_types: HashMap<Range<usize>, Type>,
+    /// What functions without a return annotation return, by where their
+    /// definitions start
+    inferred_returns: HashMap<usize, Type>,
+    /// Types the unannotated function being checked returns so far
+    returned: Option<Vec<Type>>,
+    /// Type variables standing for what top-level functions without a
+    /// return annotation return, by where their definitions start; calls
+    /// checked before a definition use them, and its check solves them
+    declared_returns: HashMap<usize, Type>,
+    /// Module-level `Final` and enum constants annotations can refer to
+    consts: ConstEnv,
+    /// Types of the values assigned to enum members, by `Class.MEMBER`
+    enum_values: HashMap<String, Type>,
+    /// Fields of the `@dataclass` classes checked so far, by class name
+    dataclasses: HashMap<String, Dataclass>,
+    /// `TypeVar`s declared so far, by the name they're assigned to
+    type_vars: HashMap<String, TypeParameter>,
+    /// Classes parameterized over type variables, by name
+    generics: HashMap<String, GenericType>,
+    /// Module-level type aliases, by name
+    aliases: HashMap<String, Alias>,
+    /// Refinement types variables are declared with, which assignments have
+    /// to respect even where a condition narrowed the variable
+    refined: HashMap<String, Type>,
+    /// Types of the module's and its functions' bindings, for `inferred_bindings`
+    inferred: InferredBindings,
+    /// Qualified names of the functions being checked, innermost last
+    binding_scopes: Vec<String>,
+    /// Function, class and comprehension bodies being checked, innermost
+    /// last; the context holds what's visible in the innermost
+    local_scopes: Vec<LocalScope>,
+    /// Attribute paths (`self.size`) the context holds narrowed types for
+    narrowed_paths: HashSet<String>,
+    /// Types narrowed names held before narrowing replaced them, which
+    /// assignments to them are checked against
+    narrowed_from: HashMap<String, TypeRef>,
+    /// Aliases whose values are being resolved, innermost last
+    resolving_aliases: Vec<String>,
+    /// Aliases found to refer to themselves while being resolved
+    recursive_aliases: HashSet<String>,
+    /// Once cancelled, checking stops at the next statement
+    cancellation: Option<CancellationToken>,
+    module_doc: Option<Docstring>,
+    /// Kept `with_item_records`, for `recheck_function`
+    item_records: Option<ItemRecords>,
+    /// Test hook: artificial delay added to a phase
+    phase_delays: HashMap<&'static str, Duration>,
+}
+
+/// A type alias, resolved the first time an annotation uses it
+struct Alias {
+    value: Expr,
+    /// Start of the statement defining it
+    definition: usize,
+    resolved: Option<Type>,
+}
+
+/// One `@overload` signature of a function; a method's without its receiver
+#[derive(Clone)]
+struct Overload {
+    ty: Type,
+    sig: FunctionSig,
+}
+
+impl Overload {
+    /// `f(x: int) -> int`, as errors list candidates
+    fn label(&self, name: &str) -> String {
+        let Type::Function(params, ret) = TypeChecker::strip_effects(self.ty.clone()) else { return name.to_string() };
+        let mut parts: Vec<String> = self.sig.params.iter().zip(&params)
+            .map(|(param, ty)| format!("{}: {}{}", param.name, ty, if param.has_default { " = ..." } else { "" }))
+            .collect();
+        if let Some(varargs) = &self.sig.varargs {
+            parts.push(format!("*args: {}", varargs));
+        } else if !self.sig.kwonly.is_empty() {
+            parts.push("*".to_string());
+        }
+        parts.extend(self.sig.kwonly.iter().map(|(param, ty)| format!("{}: {}", param.name, ty)));
+        if let Some(kwargs) = &self.sig.kwargs {
+            parts.push(format!("**kwargs: {}", kwargs));
+        }
+        format!("{}({}) -> {}", name, parts.join(", "), ret)
+    }
+}
+
+/// The `@overload` signatures declared for a function, in order
+#[derive(Default)]
+struct OverloadSet {
+    items: Vec<Overload>,
+    /// The implementation followed them; another `@overload` starts a new set
+    implemented: bool,
+}
+
+/// Yields seen in the generator function being checked
+struct GeneratorScope {
+    /// Set when the return annotation declares it
+    yield_type: Option<Type>,
+    /// What `yield` expressions evaluate to
+    send_type: Type,
+    yielded: Vec<Type>,
+}
+
+/// What the last full check left behind for `recheck_function`
+#[derive(Default)]
+struct ItemRecords {
+    /// One per top-level statement
+    items: Vec<ItemRecord>,
+    effects: HashMap<String, EffectSet>,
+    /// The check ran to completion
+    complete: bool,
+}
+
+#[derive(Default)]
+struct ItemRecord {
+    errors: Vec<TypeError>,
+    /// Solved together with every other item's, with their sites
+    constraints: Vec<(Constraint, Option<Range<usize>>)>,
+    function: Option<FunctionRecord>,
+}
+
+/// A top-level function and the module scope around it
+struct FunctionRecord {
+    range: Range<usize>,
+    before: Scope,
+    after: Scope,
+    /// Fresh type variable counter when the function was checked
+    next_var: u64,
+}
+
+/// Module-level bindings at one point of a check
+#[derive(Clone, PartialEq)]
+struct Scope {
+    types: HashMap<String, TypeRef>,
+    signatures: HashMap<String, FunctionSig>,
+}
+
+/// A function, class or comprehension body being checked. Its names are
+/// its own: entering a function or comprehension hides what enclosing
+/// scopes bind them to, and leaving any of them puts those bindings back
+struct LocalScope {
+    kind: ScopeKind,
+    /// Names bound in the scope, by where each is first bound
+    locals: HashMap<String, usize>,
+    /// Names declared `global` that an enclosing scope binds as well; what
+    /// they're bound to on leaving is the module's binding
+    globals: Vec<String>,
+    /// Bindings the scope replaced, in the order it replaced them
+    shadowed: Vec<(String, Binding)>,
+}
+
+/// Types a branch left the names it assigns with
+type AssignedTypes = HashMap<String, Option<TypeRef>>;
+
+/// What a name is bound to in the context
+#[derive(Clone)]
+struct Binding {
+    ty: Option<TypeRef>,
+    sig: Option<FunctionSig>,
+    refined: Option<Type>,
+    narrowed_from: Option<TypeRef>,
+}
+
+/// Phase names used in timing breakdowns
+pub mod phases {
+    pub const PARSE: &str = "parse";
+    pub const EFFECTS: &str = "effects";
+    pub const STATEMENTS: &str = "statements";
+    pub const CONSTRAINTS: &str = "constraints";
+    pub const PLUGINS: &str = "plugins";
+    /// Looking a module's results up in the result cache
+    pub const CACHE: &str = "cache";
+    /// A single function checked again by `recheck_function`
+    pub const RECHECK: &str = "recheck";
 }
 
 impl TypeChecker {
@@ -42,31 +335,132 @@ impl TypeChecker {
         Self {
             effects: EffectAnalyzer::new(ctx.clone()),
             bi_infer: BiInfer::new(ctx.clone()),
+            conditions: ConditionAnalyzer::new(ctx.clone()),
+            constraints: ConstraintSolver::with_context(ctx.clone()),
             ctx,
             errors: Vec::new(),
             advanced: AdvancedTypeAnalyzer::new(),
             refinements: RefinementAnalyzer::new(),
-            constraints: ConstraintSolver::new(),
             variance: VarianceAnalyzer::new(),
             class_attributes: std::collections::HashMap::new(),
             current_class: None,
+            in_class_body: false,
             current_function_return_type: None,
+            current_generator: None,
+            exhaustive_matches: HashSet::new(),
+            never_calls: HashSet::new(),
+            metrics: Arc::new(PerformanceMetrics::new()),
+            detailed_stats: false,
+            source_name: "<module>".to_string(),
+            current_phase: phases::STATEMENTS,
+            plugins: Vec::new(),
+            options: CheckerOptions::default(),
+            architecture: None,
+            source_path: None,
+            modules: None,
+            import_stack: Vec::new(),
+            stub: false,
+            overloads: HashMap::new(),
+            import_cycle: false,
+            imported_modules: HashMap::new(),
+            undefined: Vec::new(),
+            line_index: None,
+            source: None,
+            suppressions: Suppressions::default(),
+            used_suppressions: HashSet::new(),
+            override_uses: Vec::new(),
+            operand_types: OperandTypes::new(),
+            name_types: HashMap::new(),
+            inferred_returns: HashMap::new(),
+            returned: None,
+            declared_returns: HashMap::new(),
+            consts: ConstEnv::new(),
+            enum_values: HashMap::new(),
+            dataclasses: HashMap::new(),
+            type_vars: HashMap::new(),
+            generics: HashMap::new(),
+            aliases: HashMap::new(),
+            refined: HashMap::new(),
+            inferred: InferredBindings::default(),
+            binding_scopes: Vec::new(),
+            local_scopes: Vec::new(),
+            narrowed_paths: HashSet::new(),
+            narrowed_from: HashMap::new(),
+            resolving_aliases: Vec::new(),
+            recursive_aliases: HashSet::new(),
+            cancellation: None,
+            module_doc: None,
+            item_records: None,
+            phase_delays: HashMap::new(),
         }
     }
 
+    /// Checker with its own context, applying `options`
+    pub fn new_with_options(options: CheckerOptions) -> Self {
+        Self::new().with_options(options)
+    }
+
     pub fn with_context(ctx: Arc<TypeContext>) -> Self {
         Self {
             effects: EffectAnalyzer::new(ctx.clone()),
             bi_infer: BiInfer::new(ctx.clone()),
+            conditions: ConditionAnalyzer::new(ctx.clone()),
+            constraints: ConstraintSolver::with_context(ctx.clone()),
             ctx,
             errors: Vec::new(),
             advanced: AdvancedTypeAnalyzer::new(),
             refinements: RefinementAnalyzer::new(),
-            constraints: ConstraintSolver::new(),
             variance: VarianceAnalyzer::new(),
             class_attributes: std::collections::HashMap::new(),
             current_class: None,
+            in_class_body: false,
             current_function_return_type: None,
+            current_generator: None,
+            exhaustive_matches: HashSet::new(),
+            never_calls: HashSet::new(),
+            metrics: Arc::new(PerformanceMetrics::new()),
+            detailed_stats: false,
+            source_name: "<module>".to_string(),
+            current_phase: phases::STATEMENTS,
+            plugins: Vec::new(),
+            options: CheckerOptions::default(),
+            architecture: None,
+            source_path: None,
+            modules: None,
+            import_stack: Vec::new(),
+            stub: false,
+            overloads: HashMap::new(),
+            import_cycle: false,
+            imported_modules: HashMap::new(),
+            undefined: Vec::new(),
+            line_index: None,
+            source: None,
+            suppressions: Suppressions::default(),
+            used_suppressions: HashSet::new(),
+            override_uses: Vec::new(),
+            operand_types: OperandTypes::new(),
+            name_types: HashMap::new(),
+            inferred_returns: HashMap::new(),
+            returned: None,
+            declared_returns: HashMap::new(),
+            consts: ConstEnv::new(),
+            enum_values: HashMap::new(),
+            dataclasses: HashMap::new(),
+            type_vars: HashMap::new(),
+            generics: HashMap::new(),
+            aliases: HashMap::new(),
+            refined: HashMap::new(),
+            inferred: InferredBindings::default(),
+            binding_scopes: Vec::new(),
+            local_scopes: Vec::new(),
+            narrowed_paths: HashSet::new(),
+            narrowed_from: HashMap::new(),
+            resolving_aliases: Vec::new(),
+            recursive_aliases: HashSet::new(),
+            cancellation: None,
+            module_doc: None,
+            item_records: None,
+            phase_delays: HashMap::new(),
         }
     }
 
@@ -74,42 +468,728 @@ impl TypeChecker {
     pub fn check(&mut self, module: &Mod) -> Vec<TypeError> {
         info!("Starting type checking");
         self.errors.clear();
+        self.operand_types.clear();
+        self.name_types.clear();
+        self.inferred_returns.clear();
+        self.declared_returns.clear();
+        self.exhaustive_matches.clear();
+        self.never_calls.clear();
+        self.enum_values.clear();
+        self.dataclasses.clear();
+        self.type_vars.clear();
+        self.generics.clear();
+        self.aliases.clear();
+        self.refined.clear();
+        self.inferred.clear();
+        self.imported_modules.clear();
+        self.import_cycle = false;
+        self.overloads.clear();
+        self.local_scopes.clear();
+        self.narrowed_paths.clear();
+        self.narrowed_from.clear();
 
         if let Mod::Module(ModModule { body, .. }) = module {
+            self.undefined = if self.stub { Vec::new() } else { undefined_names(module, self.source.as_deref().unwrap_or_default()) };
+            self.consts = ConstEnv::collect(body);
+            self.conditions.set_consts(self.consts.clone());
+            self.module_doc = Docstring::of(body);
+            for (name, stmt, value) in aliases::collect_aliases(body) {
+                let alias = Alias { value: value.clone(), definition: stmt.start().to_usize(), resolved: None };
+                self.aliases.insert(name, alias);
+            }
+
             // Phase 1: Analyze effects across the module (killer feature!)
             debug!("Phase 1: Analyzing effects");
-            let effect_results = self.effects.analyze_module(module);
+            let effect_results = self.timed_phase(phases::EFFECTS, |this| this.effects.analyze_module(module));
             info!(functions_analyzed = effect_results.len(), "Effect analysis complete");
 
-            // Store effect analysis results for later use
-            for (_func_name, _effects) in &effect_results {
-                // Effect information is now tracked and available
+            if let Some(records) = &mut self.item_records {
+                *records = ItemRecords { effects: effect_results, ..ItemRecords::default() };
             }
 
-            // Phase 2: Check statements with all analyzers
+            // Phase 2: Check statements with all analyzers, once what the
+            // module defines is declared
             debug!(statements = body.len(), "Phase 2: Checking statements");
-            for stmt in body {
-                self.check_stmt(stmt);
+            self.timed_phase(phases::STATEMENTS, |this| {
+                this.declare_items(body);
+                for stmt in body {
+                    if this.item_records.is_none() {
+                        this.check_stmt(stmt);
+                        this.report_undefined(stmt);
+                        this.record_bindings(bindings::binding_sites(std::slice::from_ref(stmt)));
+                        continue;
+                    }
+                    let first_error = this.errors.len();
+                    let first_constraint = this.constraints.pending().len();
+                    let function = matches!(stmt, Stmt::FunctionDef(_))
+                        .then(|| (this.scope(), this.ctx.next_var_id()));
+                    this.check_stmt(stmt);
+                    this.report_undefined(stmt);
+                    this.record_bindings(bindings::binding_sites(std::slice::from_ref(stmt)));
+                    let record = ItemRecord {
+                        errors: this.errors[first_error..].to_vec(),
+                        constraints: pending_constraints(&this.constraints, first_constraint),
+                        function: function.map(|(before, next_var)| FunctionRecord {
+                            range: item_range(stmt),
+                            before,
+                            after: this.scope(),
+                            next_var,
+                        }),
+                    };
+                    if let Some(records) = &mut this.item_records {
+                        records.items.push(record);
+                    }
+                }
+            });
+
+            if self.is_cancelled() {
+                warn!("Type checking cancelled");
+                self.errors.push(TypeError::new("Type checking cancelled before it completed"));
+                return self.errors.clone();
             }
 
             // Phase 3: Solve constraints
-            debug!("Phase 3: Solving constraints");
-            if let Err(err) = self.constraints.solve() {
-                error!(error = ?err, "Constraint solving failed");
-                self.errors.push(TypeError {
-                    message: format!("Constraint solving failed: {:?}", err),
-                    line: 0,
-                    col: 0,
-                });
-            } else {
-                info!("Constraint solving complete");
+            let errors = self.solve_constraints();
+            self.errors.extend(errors);
+
+            // Phase 4: Third-party plugins
+            self.run_plugins(module);
+
+            if let Some(records) = &mut self.item_records {
+                records.complete = true;
             }
+
+            self.check_import_cycle(body);
+            self.publish_exports(body);
         }
 
+        self.finish_errors();
         info!(error_count = self.errors.len(), "Type checking complete");
         self.errors.clone()
     }
 
+    /// Is `stmt` a simple statement with a blanket `# type: ignore` on one of its lines?
+    fn is_suppressed(&self, stmt: &Stmt) -> bool {
+        let compound = matches!(
+            stmt,
+            Stmt::FunctionDef(_) | Stmt::AsyncFunctionDef(_) | Stmt::ClassDef(_) | Stmt::If(_)
+                | Stmt::For(_) | Stmt::AsyncFor(_) | Stmt::While(_) | Stmt::With(_) | Stmt::AsyncWith(_)
+                | Stmt::Try(_) | Stmt::TryStar(_) | Stmt::Match(_)
+        );
+        if compound || self.suppressions.is_empty() {
+            return false;
+        }
+        let (first, _) = self.position(stmt.start().to_usize());
+        let (last, _) = self.position(stmt.end().to_usize());
+        (first..=last).any(|line| self.suppressions.matching(line, None).is_some())
+    }
+
+    /// Drop errors with disabled codes and those on lines with a
+    /// `# type: ignore`, then apply `max_errors`
+    fn finish_errors(&mut self) {
+        let options = &self.options;
+        self.errors.retain(|error| options.code_enabled(error.code));
+
+        self.used_suppressions.clear();
+        if !self.suppressions.is_empty() {
+            let (suppressions, used) = (&self.suppressions, &mut self.used_suppressions);
+            self.errors.retain(|error| match suppressions.matching(error.line, error.code) {
+                Some(suppression) => {
+                    used.insert(suppression.line);
+                    false
+                }
+                None => true,
+            });
+        }
+        if let Some(max) = self.options.max_errors {
+            self.errors.truncate(max);
+        }
+    }
+
+    /// Collect timings into `metrics`; `detailed` also times individual rules
+    pub fn with_metrics(mut self, metrics: Arc<PerformanceMetrics>, detailed: bool) -> Self {
+        self.metrics = metrics;
+        self.detailed_stats = detailed;
+        self
+    }
+
+    /// Stop checking once `token` is cancelled, e.g. when a time budget runs out
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Keep per-statement errors and scopes so `recheck_function` can redo
+    /// one function of the checked module
+    pub fn with_item_records(mut self) -> Self {
+        self.item_records = Some(ItemRecords::default());
+        self
+    }
+
+    /// Check again only the top-level function at `index` of `module`, a new
+    /// version of the last checked module that differs from it only inside
+    /// that function's body (see `items::diff_items`), and return the updated
+    /// errors for the whole module
+    ///
+    /// The function is checked in the scope it saw during the full check.
+    /// Give the new text to `set_source` first for error lines to match it.
+    /// `None` means the edit may reach past the function: it now binds
+    /// different module names or changes inferred effects (or plugins, which
+    /// see the whole module, are registered). The checker is then in an
+    /// unspecified state; check the module from scratch with a new one.
+    pub fn recheck_function(&mut self, module: &Mod, index: usize) -> Option<Vec<TypeError>> {
+        let Mod::Module(ModModule { body, .. }) = module else { return None };
+        let stmt @ Stmt::FunctionDef(_) = body.get(index)? else { return None };
+        let records = self.item_records.as_ref()?;
+        let eligible = records.complete
+            && records.items.len() == body.len()
+            && self.plugins.is_empty()
+            && !self.is_cancelled();
+        let record = records.items[index].function.as_ref().filter(|_| eligible)?;
+        let (old_range, new_range) = (record.range.clone(), item_range(stmt));
+        if old_range.start != new_range.start {
+            return None;
+        }
+        let (before, expected_after, next_var) = (record.before.clone(), record.after.clone(), record.next_var);
+
+        // Effects propagate to callers, so any change means a full check
+        if self.effects.analyze_module(module) != records.effects {
+            return None;
+        }
+        // So does a change to what's undefined outside the function, e.g.
+        // from a name it binds or stops binding with `global`
+        let delta = new_range.end as isize - old_range.end as isize;
+        let undefined = undefined_names(module, self.source.as_deref().unwrap_or_default());
+        let outside = |reads: &[UndefinedName], range: &Range<usize>, delta: isize| -> Vec<UndefinedName> {
+            reads.iter()
+                .filter(|read| !range.contains(&read.range.start))
+                .map(|read| UndefinedName {
+                    range: if read.range.start >= range.end { shift(&read.range, delta) } else { read.range.clone() },
+                    ..read.clone()
+                })
+                .collect()
+        };
+        if outside(&self.undefined, &old_range, delta) != outside(&undefined, &new_range, 0) {
+            return None;
+        }
+        self.undefined = undefined;
+
+        // Operand types inside the function are recomputed; later ones move
+        self.operand_types = std::mem::take(&mut self.operand_types).into_iter()
+            .filter(|(range, _)| range.end <= old_range.start || range.start >= old_range.end)
+            .map(|(range, ty)| match range.start >= old_range.end {
+                true => (shift(&range, delta), ty),
+                false => (range, ty),
+            })
+            .collect();
+        self.name_types = std::mem::take(&mut self.name_types).into_iter()
+            .filter(|(range, _)| range.end <= old_range.start || range.start >= old_range.end)
+            .map(|(range, ty)| match range.start >= old_range.end {
+                true => (shift(&range, delta), ty),
+                false => (range, ty),
+            })
+            .collect();
+        self.never_calls = std::mem::take(&mut self.never_calls).into_iter()
+            .filter(|start| *start < old_range.start || *start >= old_range.end)
+            .map(|start| if start >= old_range.end { start.saturating_add_signed(delta) } else { start })
+            .collect();
+        self.inferred_returns = std::mem::take(&mut self.inferred_returns).into_iter()
+            .filter(|(start, _)| *start < old_range.start || *start >= old_range.end)
+            .map(|(start, ty)| if start >= old_range.end { (start.saturating_add_signed(delta), ty) } else { (start, ty) })
+            .collect();
+
+        let module_scope = self.scope();
+        let module_next_var = self.ctx.next_var_id();
+        self.restore_scope(&before);
+        self.ctx.set_next_var_id(next_var);
+        let outer_errors = std::mem::take(&mut self.errors);
+        let module_constraints = std::mem::take(&mut self.constraints);
+
+        self.timed_phase(phases::RECHECK, |this| {
+            this.check_stmt(stmt);
+            this.report_undefined(stmt);
+        });
+
+        let errors = std::mem::replace(&mut self.errors, outer_errors);
+        let constraints = pending_constraints(&std::mem::replace(&mut self.constraints, module_constraints), 0);
+        let after = self.scope();
+        self.restore_scope(&module_scope);
+        self.ctx.set_next_var_id(module_next_var.max(self.ctx.next_var_id()));
+        if after != expected_after {
+            return None;
+        }
+
+        let records = self.item_records.as_mut()?;
+        records.items[index].errors = errors;
+        records.items[index].constraints = constraints;
+        // Later items moved by the change in length (and maybe in lines)
+        for item in records.items.iter_mut().skip(index + 1) {
+            if let Some(function) = &mut item.function {
+                function.range = shift(&function.range, delta);
+            }
+            for (_, site) in &mut item.constraints {
+                if let Some(site) = site {
+                    *site = shift(site, delta);
+                }
+            }
+            for error in &mut item.errors {
+                if let Some(range) = &mut error.range {
+                    *range = shift(range, delta);
+                    (error.line, error.col) = self.line_index.as_ref()
+                        .map_or((0, 0), |index| index.offset_to_position(range.start));
+                }
+            }
+        }
+        if let Some(function) = &mut records.items[index].function {
+            function.range = new_range;
+        }
+        self.constraints = ConstraintSolver::with_context(self.ctx.clone());
+        for (constraint, site) in records.items.iter().flat_map(|item| &item.constraints) {
+            self.constraints.add_constraint_at(constraint.clone(), site.clone());
+        }
+        self.errors = records.items.iter().flat_map(|item| item.errors.iter().cloned()).collect();
+        let errors = self.solve_constraints();
+        self.errors.extend(errors);
+        self.finish_errors();
+        Some(self.errors.clone())
+    }
+
+    /// Phase 3: solve the constraints gathered from all statements
+    ///
+    /// Each failure is reported at the node its constraint came from,
+    /// unless an error there already covers it.
+    fn solve_constraints(&mut self) -> Vec<TypeError> {
+        debug!("Phase 3: Solving constraints");
+        self.metrics.record_peak(counters::CONSTRAINTS, self.constraints.pending().len() as u64);
+        let solved = self.timed_phase(phases::CONSTRAINTS, |this| this.constraints.solve_located());
+        self.apply_solution();
+        let Err(failures) = solved else {
+            info!("Constraint solving complete");
+            return Vec::new();
+        };
+        error!(failures = failures.len(), "Constraint solving failed");
+        let mut errors: Vec<TypeError> = Vec::new();
+        for (failure, site) in failures {
+            let covers = |error: &&mut TypeError| match (&error.range, &site) {
+                (Some(range), Some(site)) => range.start < site.end && site.start < range.end,
+                _ => false,
+            };
+            // Reported already; keep only the hints the solver adds
+            if let Some(error) = self.errors.iter_mut().chain(&mut errors).find(covers) {
+                for suggestion in failure.suggestions {
+                    if !error.suggestions.contains(&suggestion) {
+                        error.suggestions.push(suggestion);
+                    }
+                }
+                continue;
+            }
+            let (line, col) = site.as_ref().map_or((0, 0), |site| self.position(site.start));
+            errors.push(TypeError {
+                message: failure.kind.to_string(),
+                code: Some(failure.kind.code()),
+                severity: Severity::Error,
+                line,
+                col,
+                range: site,
+                suggestions: failure.suggestions,
+            });
+        }
+        errors
+    }
+
+    /// Replace the type variables solving decided in the bindings, the
+    /// types of names and the inferred return types with what they were
+    /// solved to
+    fn apply_solution(&mut self) {
+        if self.constraints.substitution().is_empty() {
+            return;
+        }
+        for (name, ty) in self.ctx.bindings() {
+            if ty.has_type_vars() {
+                self.ctx.set_type(name, self.constraints.apply(&ty));
+            }
+        }
+        for ty in self.name_types.values_mut().chain(self.inferred_returns.values_mut()).filter(|ty| ty.has_type_vars()) {
+            *ty = self.constraints.apply(ty);
+        }
+    }
+
+    fn scope(&self) -> Scope {
+        Scope { types: self.ctx.binding_refs(), signatures: self.ctx.signatures() }
+    }
+
+    fn restore_scope(&mut self, scope: &Scope) {
+        self.ctx.restore_binding_refs(&scope.types);
+        self.ctx.restore_signatures(&scope.signatures);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation.as_ref().is_some_and(CancellationToken::is_cancelled)
+    }
+
+    /// Apply run settings (strictness, limits, plugin toggles)
+    pub fn with_options(mut self, options: CheckerOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn options(&self) -> &CheckerOptions {
+        &self.options
+    }
+
+    /// Run the lints enabled in the options over `module`, leaving out those
+    /// a `# type: ignore` silences
+    ///
+    /// Call after `check` on the same module so type-aware lints see inferred
+    /// types and `warn_unused_ignores` knows which comments silenced errors.
+    pub fn lint(&self, module: &Mod) -> Vec<LintDiagnostic> {
+        let mut diagnostics = lints::lint_module_with_types(module, &self.options.lint_levels(), &self.operand_types);
+        if let Mod::Module(ModModule { body, .. }) = module {
+            diagnostics.extend(self.unreachable_code(body));
+        }
+        // A package's `__init__` imports what the package exports
+        let package = self.source_path.as_ref().is_some_and(|path| path.file_stem().is_some_and(|stem| stem == "__init__"));
+        if let Some(source) = self.source.as_ref().filter(|_| !package) {
+            diagnostics.extend(unused_imports(module, source).into_iter().map(|import| {
+                let kind = ErrorKind::UnusedImport { name: import.name.clone() };
+                let (message, title) = match import.duplicates {
+                    Some(line) => (
+                        format!("'{}' is already imported on line {}", import.name, line),
+                        format!("Remove repeated import of '{}'", import.name),
+                    ),
+                    None => (kind.to_string(), format!("Remove unused import '{}'", import.name)),
+                };
+                LintDiagnostic {
+                    code: kind.code(),
+                    level: LintLevel::Warn,
+                    message,
+                    range: import.range,
+                    fix: Some(Fix { title, edits: vec![import.removal] }),
+                }
+            }));
+        }
+        if let Some(source) = self.source.as_ref().filter(|_| self.options.warn_unused_variables) {
+            diagnostics.extend(unused_variables(module, source).into_iter().map(|binding| {
+                let kind = if binding.parameter {
+                    ErrorKind::UnusedArgument { name: binding.name }
+                } else {
+                    ErrorKind::UnusedVariable { name: binding.name }
+                };
+                LintDiagnostic { code: kind.code(), level: LintLevel::Warn, message: kind.to_string(), range: binding.range, fix: None }
+            }));
+        }
+        diagnostics.retain(|lint| self.options.code_enabled(Some(lint.code)));
+        let Some(index) = &self.line_index else { return diagnostics };
+
+        let mut used = self.used_suppressions.clone();
+        diagnostics.retain(|lint| {
+            let line = index.offset_to_position(lint.range.start).0;
+            match self.suppressions.matching(line, Some(lint.code)) {
+                Some(suppression) => {
+                    used.insert(suppression.line);
+                    false
+                }
+                None => true,
+            }
+        });
+
+        if self.options.warn_unused_ignores {
+            for suppression in self.suppressions.iter().filter(|s| !used.contains(&s.line)) {
+                diagnostics.push(LintDiagnostic {
+                    code: lints::UNUSED_IGNORE,
+                    level: LintLevel::Warn,
+                    message: "unused 'type: ignore' comment".to_string(),
+                    range: suppression.range.clone(),
+                    fix: Some(Fix {
+                        title: "Remove unused 'type: ignore'".to_string(),
+                        edits: vec![TextEdit { range: suppression.removal.clone(), new_text: String::new() }],
+                    }),
+                });
+            }
+        }
+        diagnostics
+    }
+
+    /// A warning at the first statement of each block no path reaches
+    fn unreachable_code(&self, body: &[Stmt]) -> Vec<LintDiagnostic> {
+        reachability::unreachable(body, self).into_iter()
+            .map(|(dead, cause)| {
+                let after = match cause {
+                    Stmt::Return(_) => "'return'",
+                    Stmt::Raise(_) => "'raise'",
+                    Stmt::Break(_) => "'break'",
+                    Stmt::Continue(_) => "'continue'",
+                    Stmt::Expr(_) => "a call that never returns",
+                    Stmt::If(_) => "an 'if' whose every branch exits",
+                    Stmt::Try(_) | Stmt::TryStar(_) => "a 'try' whose every branch exits",
+                    Stmt::Match(_) => "a 'match' whose every case exits",
+                    Stmt::While(_) => "a loop that never finishes",
+                    _ => "a block that always exits",
+                };
+                let kind = ErrorKind::UnreachableCode { after: after.to_string() };
+                LintDiagnostic {
+                    code: kind.code(),
+                    level: LintLevel::Warn,
+                    message: kind.to_string(),
+                    range: dead.start().to_usize()..dead.end().to_usize(),
+                    fix: None,
+                }
+            })
+            .collect()
+    }
+
+    /// Timings collected so far
+    pub fn metrics(&self) -> &Arc<PerformanceMetrics> {
+        &self.metrics
+    }
+
+    /// Attribute subsequent timings to `name` (usually the file path)
+    pub fn set_source_name(&mut self, name: impl Into<String>) {
+        self.source_name = name.into();
+    }
+
+    /// Parse an annotation string such as `Callable[[str], Optional[int]]`
+    pub fn parse_annotation(&mut self, annotation: &str) -> Result<Type, String> {
+        let expr = crate::compiler::frontend::parser::parse_expression(annotation)
+            .map_err(|e| format!("invalid annotation '{}': {}", annotation, e))?;
+        Ok(self.type_from_annotation(&expr))
+    }
+
+    /// Shadow the member at dotted `path` (e.g. `builtins.str.find`) with
+    /// the type written as `annotation`
+    pub fn override_signature(&mut self, path: &str, annotation: &str, origin: OverrideOrigin) -> Result<(), String> {
+        let (class, member) = parse_member_path(path)?;
+        let ty = self.parse_annotation(annotation)?;
+        self.ctx.overrides().set(&class, &member, ty, origin);
+        Ok(())
+    }
+
+    /// Check imports against layering rules
+    pub fn with_architecture(mut self, rules: Arc<ArchitectureRules>) -> Self {
+        self.architecture = Some(rules);
+        self
+    }
+
+    /// Check the project modules imported by the next `check` first and
+    /// bind the names it imports to their types; `resolver` keeps their
+    /// exports for every checker sharing it
+    pub fn with_modules(mut self, resolver: Arc<ModuleResolver>) -> Self {
+        self.modules = Some(resolver);
+        self
+    }
+
+    /// Fill `scratch`'s collections while checking instead of growing new
+    /// ones; `into_scratch` hands them back for the next checker
+    pub fn with_scratch(mut self, scratch: CheckerScratch) -> Self {
+        let CheckerScratch { errors, constraints, name_types, operand_types, inferred_returns, class_attributes } = scratch;
+        self.errors = errors;
+        self.constraints.reuse_buffer(constraints);
+        self.name_types = name_types;
+        self.operand_types = operand_types;
+        self.inferred_returns = inferred_returns;
+        self.class_attributes = class_attributes;
+        self
+    }
+
+    /// The collections this checker filled, emptied, for another checker's
+    /// `with_scratch`
+    pub fn into_scratch(mut self) -> CheckerScratch {
+        let mut scratch = CheckerScratch {
+            errors: std::mem::take(&mut self.errors),
+            constraints: self.constraints.take_buffer(),
+            name_types: std::mem::take(&mut self.name_types),
+            operand_types: std::mem::take(&mut self.operand_types),
+            inferred_returns: std::mem::take(&mut self.inferred_returns),
+            class_attributes: std::mem::take(&mut self.class_attributes),
+        };
+        scratch.clear();
+        scratch
+    }
+
+    /// File the next `check` reads, used to resolve its imports and locate
+    /// their diagnostics
+    pub fn set_source_file(&mut self, path: &Path, source: &str) {
+        self.source_path = Some(path.to_path_buf());
+        self.set_source(source);
+    }
+
+    /// Text of the module the next `check` (or `recheck_function`) gets, so
+    /// errors carry lines and columns and `# type: ignore` comments apply;
+    /// without it they only have byte ranges
+    pub fn set_source(&mut self, source: &str) {
+        self.line_index = Some(LineIndex::new(source));
+        self.source = Some(source.into());
+        self.suppressions = Suppressions::scan(source);
+    }
+
+    /// Report `message` at `node`
+    fn report(&mut self, node: &impl Ranged, code: &'static str, message: String) {
+        let range = node.start().to_usize()..node.end().to_usize();
+        self.report_range(range, code, message);
+    }
+
+    /// Report the names read in top-level statement `stmt` that nothing binds
+    fn report_undefined(&mut self, stmt: &Stmt) {
+        let range = item_range(stmt);
+        let reads: Vec<UndefinedName> = self.undefined.iter().filter(|read| range.contains(&read.range.start)).cloned().collect();
+        for read in reads {
+            let (code, kind) = match read.called {
+                true => (codes::UNDEFINED_FUNCTION, ErrorKind::UndefinedFunction { name: read.name }),
+                false => (codes::UNDEFINED_VARIABLE, ErrorKind::UndefinedVariable { name: read.name }),
+            };
+            let mut message = kind.to_string();
+            if !read.suggestions.is_empty() {
+                message.push_str(&format!(". Did you mean: {}?", read.suggestions.join(", ")));
+            }
+            self.report_suggesting(read.range, code, message, read.suggestions);
+        }
+    }
+
+    fn report_range(&mut self, range: Range<usize>, code: &'static str, message: String) {
+        self.report_suggesting(range, code, message, Vec::new());
+    }
+
+    fn report_suggesting(&mut self, range: Range<usize>, code: &'static str, message: String, suggestions: Vec<String>) {
+        let (line, col) = self.position(range.start);
+        self.errors.push(TypeError { message, code: Some(code), severity: Severity::Error, line, col, range: Some(range), suggestions });
+    }
+
+    /// Line and column of a byte offset, (0, 0) without source text
+    fn position(&self, offset: usize) -> (usize, usize) {
+        self.line_index.as_ref().map_or((0, 0), |index| index.offset_to_position(offset))
+    }
+
+    /// Note the current types of the names bound at `sites`, in the scope
+    /// of the function being checked
+    fn record_bindings(&mut self, sites: Vec<(String, Range<usize>)>) {
+        for (name, range) in sites {
+            let Some(ty) = self.ctx.get_type(&name) else { continue };
+            // Sites that are just the name, such as assignment targets;
+            // parameters are noted by `record_parameter_types`
+            if range.len() == name.len() {
+                self.name_types.insert(range.clone(), ty.clone());
+            }
+            let qualified = match self.binding_scopes.last() {
+                Some(scope) => format!("{}.{}", scope, name),
+                None => name,
+            };
+            let location = SourceLocation::from_range(self.position(range.start), self.position(range.end));
+            self.inferred.record(qualified, ty, location);
+        }
+    }
+
+    /// Note the types of `args`' names, which start their sites
+    fn record_parameter_types(&mut self, args: &rustpython_parser::ast::Arguments) {
+        for (name, range) in bindings::parameter_sites(args) {
+            if let Some(ty) = self.ctx.get_type(&name) {
+                self.name_types.insert(range.start..range.start + name.len(), ty);
+            }
+        }
+    }
+
+    /// Type of the name at byte `offset` of the last checked module, where
+    /// it's bound or used, with the name's range. Types are as of that
+    /// point in the code, so a name narrowed by an `if` has its narrowed
+    /// type there
+    pub fn type_at(&self, offset: usize) -> Option<(Range<usize>, Type)> {
+        self.name_types.iter()
+            .find(|(range, _)| range.start <= offset && offset < range.end)
+            .map(|(range, ty)| (range.clone(), ty.clone()))
+    }
+
+    /// What the function without a return annotation defined at byte
+    /// `start` of the last checked module returns: the union of its
+    /// `return` values, with `None` when it can end without one
+    pub fn inferred_return(&self, start: usize) -> Option<Type> {
+        self.inferred_returns.get(&start).cloned()
+    }
+
+    /// How a call to `callee` binds its arguments, as of the end of the
+    /// last checked module: names in its top-level scope resolve, locals of
+    /// its functions don't. `None` when not statically known
+    pub fn call_signature(&self, callee: &Expr) -> Option<FunctionSig> {
+        self.signature_of(callee).or_else(|| {
+            // Calling a class binds the parameters of its `__init__`
+            let Expr::Name(name) = callee else { return None };
+            match self.ctx.get_type(&name.id)? {
+                Type::ClassObject(class_name) => self.ctx.member_signature(&class_name, "__init__"),
+                _ => None,
+            }
+        })
+    }
+
+    /// Every top-level and function-local binding of the last checked
+    /// module with its final inferred type, in the order first bound.
+    /// Names local to a function are qualified with its name, e.g.
+    /// `greet.name`, and methods' with their class's, e.g. `C.m.x`.
+    /// Locations are 0 without `set_source`
+    pub fn inferred_bindings(&self) -> Vec<(String, Type, SourceLocation)> {
+        self.inferred.entries().to_vec()
+    }
+
+    /// Final inferred type of `name` local to `scope`, the qualified name
+    /// of a function as in [`Self::inferred_bindings`]
+    pub fn local_binding(&self, scope: &str, name: &str) -> Option<Type> {
+        self.inferred.get(&format!("{}.{}", scope, name)).cloned()
+    }
+
+    /// Run `plugin` after the built-in passes of every `check`
+    pub fn register_plugin(&mut self, plugin: Box<dyn CheckerPlugin>) {
+        self.plugins.push(plugin);
+    }
+
+    /// Test hook: sleep for `delay` inside `phase` to simulate a slow pass
+    #[doc(hidden)]
+    pub fn inject_phase_delay(&mut self, phase: &'static str, delay: Duration) {
+        self.phase_delays.insert(phase, delay);
+    }
+
+    /// Run a phase, attributing its wall time to `phase`
+    fn timed_phase<T>(&mut self, phase: &'static str, f: impl FnOnce(&mut Self) -> T) -> T {
+        let start = Instant::now();
+        let outer = std::mem::replace(&mut self.current_phase, phase);
+
+        if let Some(delay) = self.phase_delays.get(phase) {
+            std::thread::sleep(*delay);
+        }
+        let result = f(self);
+
+        self.current_phase = outer;
+        self.metrics.record_phase(&self.source_name, phase, None, start.elapsed());
+        result
+    }
+
+    /// Run a rule, attributing its time under the current phase in detailed mode
+    fn timed_rule<T>(&mut self, rule: &'static str, f: impl FnOnce(&mut Self) -> T) -> T {
+        if !self.detailed_stats {
+            return f(self);
+        }
+
+        let start = Instant::now();
+        let result = f(self);
+        self.metrics.record_phase(&self.source_name, self.current_phase, Some(rule), start.elapsed());
+        result
+    }
+
+    /// Run plugin hooks, each timed under the plugin's name
+    fn run_plugins(&mut self, module: &Mod) {
+        if self.plugins.is_empty() {
+            return;
+        }
+
+        let mut plugins = std::mem::take(&mut self.plugins);
+        self.timed_phase(phases::PLUGINS, |this| {
+            for plugin in plugins.iter_mut().filter(|plugin| this.options.plugin_enabled(plugin.name())) {
+                let start = Instant::now();
+                let errors = plugin.check_module(module, &this.ctx);
+                this.metrics.record_phase(&this.source_name, phases::PLUGINS, Some(plugin.name()), start.elapsed());
+                debug!(plugin = plugin.name(), errors = errors.len(), "Plugin finished");
+                this.errors.extend(errors);
+            }
+        });
+        self.plugins = plugins;
+    }
+
     pub fn infer(&mut self, module: &Mod) -> Type {
         if let Mod::Module(ModModule { body, .. }) = module {
             if let Some(last) = body.last() {
@@ -119,78 +1199,98 @@ impl TypeChecker {
         Type::None
     }
 
+    /// Check `stmt`, noting on its errors any overridden signatures it used
     fn check_stmt(&mut self, stmt: &Stmt) {
-        match stmt {
-            Stmt::FunctionDef(func_def) => {
-                // Infer parameter types
-                let param_types: Vec<Type> = func_def.args.args.iter()
-                    .map(|arg| {
-                        if let Some(ann) = &arg.def.annotation {
-                            self.type_from_annotation(ann)
-                        } else {
-                            self.ctx.fresh_var()
-                        }
-                    })
-                    .collect();
-
-                // Infer return type (only check if explicitly annotated)
-                let (return_type, has_return_annotation) = if let Some(ret) = &func_def.returns {
-                    (self.type_from_annotation(ret), true)
-                } else {
-                    (self.ctx.fresh_var(), false)
-                };
-
-                // Create base function type
-                let base_func_type = Type::Function(param_types.clone(), Box::new(return_type.clone()));
+        if self.is_cancelled() {
+            return;
+        }
+        let outer_uses = std::mem::take(&mut self.override_uses);
+        let first_error = self.errors.len();
+        let first_constraint = self.constraints.pending().len();
 
-                // Set parameters in context for function body
-                for (arg, param_ty) in func_def.args.args.iter().zip(param_types.iter()) {
-                    self.ctx.set_type(arg.def.arg.to_string(), param_ty.clone());
-                }
+        self.check_stmt_kind(stmt);
 
-                // Track current function return type for validation (only if annotated)
-                let prev_return_type = self.current_function_return_type.take();
-                if has_return_annotation {
-                    self.current_function_return_type = Some(return_type.clone());
-                }
+        // What a `# type: ignore` silences shouldn't resurface from the solver
+        if self.is_suppressed(stmt) {
+            self.constraints.truncate(first_constraint);
+        }
 
-                // Check function body and infer effects
-                for stmt in &func_def.body {
-                    self.check_stmt(stmt);
+        let uses = std::mem::replace(&mut self.override_uses, outer_uses);
+        if !uses.is_empty() {
+            for error in &mut self.errors[first_error..] {
+                for used in &uses {
+                    let note = used.note();
+                    if !error.message.contains(&note) {
+                        error.message = format!("{}; {}", error.message, note);
+                    }
                 }
+            }
+        }
+    }
 
-                // Restore previous return type
-                self.current_function_return_type = prev_return_type;
+    fn check_stmt_kind(&mut self, stmt: &Stmt) {
+        if let Some(name) = self.alias_defined_by(stmt) {
+            self.define_alias(&name);
+            return;
+        }
 
-                // Annotate with inferred effects (killer feature!)
-                let func_type = self.effects.annotate_function_type(&func_def.name, base_func_type);
+        match stmt {
+            Stmt::FunctionDef(func_def) => self.check_function_def(func_def, false),
 
-                self.ctx.set_type(func_def.name.to_string(), func_type);
-            }
+            Stmt::AsyncFunctionDef(func_def) => self.check_function_def(&as_function_def(func_def), true),
 
             Stmt::Assign(assign) => {
+                if let ([Expr::Name(target)], Some((name, keys))) = (assign.targets.as_slice(), self.functional_typed_dict(&assign.value)) {
+                    let schema = ClassSchema::new(name.clone()).with_bases(vec!["dict".to_string()]).with_typed_dict(keys);
+                    self.ctx.register_class(schema);
+                    self.ctx.set_type(target.id.to_string(), Type::ClassObject(name));
+                    return;
+                }
+                if let ([Expr::Name(target)], Some(param)) = (assign.targets.as_slice(), generics::type_var(&assign.value)) {
+                    self.type_vars.insert(target.id.to_string(), param);
+                }
+
                 let value_type = self.infer_expr(&assign.value);
+                let value_sig = self.signature_of(&assign.value);
 
                 for target in &assign.targets {
+                    self.forget_assigned(target);
                     match target {
                         Expr::Name(name_expr) => {
-                            // Check if there's an annotation
-                            if let Some(ann_type) = self.ctx.get_type(&name_expr.id) {
+                            // A narrowed name takes what it held before narrowing
+                            let narrowed_from = self.narrowed_from.get(name_expr.id.as_str()).copied();
+                            if let Some(handle) = narrowed_from {
+                                self.ctx.set_type_ref(name_expr.id.to_string(), handle);
+                            }
+                            // Refined ints are checked against their declared bounds
+                            let bounds = self.interval_of(&assign.value, &value_type);
+                            let bounded = match self.refined.get(name_expr.id.as_str()).cloned() {
+                                Some(declared) => self.check_bounds(&*assign.value, bounds, &declared),
+                                None => None,
+                            };
+                            if bounded.is_some() {
+                                // Judged by its bounds already
+                            } else if let Some(ann_type) = self.ctx.get_type(&name_expr.id) {
                                 // Use bidirectional checking with expected type
-                                if !self.bi_infer.check(&assign.value, &ann_type) {
-                                    self.errors.push(TypeError {
-                                        message: format!("Type mismatch in assignment to {}", name_expr.id),
-                                        line: 0,
-                                        col: 0,
-                                    });
+                                let fits = self.bi_infer.check(&assign.value, &ann_type);
+                                if !fits {
+                                    self.report(&*assign.value, codes::TYPE_MISMATCH, format!("Type mismatch in assignment to {}", name_expr.id));
                                 }
                                 // Add constraint for solver (subtype constraint)
-                                self.constraints.add_constraint(Constraint::Subtype(value_type.clone(), ann_type));
+                                let site = assign.value.start().to_usize()..assign.value.end().to_usize();
+                                self.constraints.add_constraint_at(Constraint::Subtype(value_type.clone(), ann_type), Some(site));
+                                // and is narrowed to what it was assigned
+                                if fits && narrowed_from.is_some() {
+                                    self.ctx.set_type(name_expr.id.to_string(), value_type.clone());
+                                }
                             } else {
+                                self.check_implicit_any(&assign.value, &name_expr.id, &value_type);
                                 self.ctx.set_type(name_expr.id.to_string(), value_type.clone());
                             }
+                            self.ctx.set_signature(name_expr.id.to_string(), value_sig.clone());
                         }
                         Expr::Attribute(attr) => {
+                            self.check_frozen_assignment(attr);
                             // Track class attribute assignments (self.x = value)
                             if let Expr::Name(base) = &*attr.value {
                                 if base.id.as_str() == "self" {
@@ -202,6 +1302,14 @@ impl TypeChecker {
                                 }
                             }
                         }
+                        Expr::Subscript(_) => {
+                            // d[key] = value still has to hash the key
+                            self.infer_expr(target);
+                        }
+                        Expr::Tuple(_) | Expr::List(_) => {
+                            let bound = LengthBound::from_expr(&assign.value, &value_type);
+                            self.check_unpack(target, bound, &value_type);
+                        }
                         _ => {}
                     }
                 }
@@ -209,98 +1317,174 @@ impl TypeChecker {
 
             Stmt::AnnAssign(ann_assign) => {
                 // Handle annotated assignments: x: int = value
-                let ann_type = self.type_from_annotation(&ann_assign.annotation);
+                self.forget_assigned(&ann_assign.target);
+                if let Expr::Name(name) = &*ann_assign.target {
+                    self.narrowed_from.remove(name.id.as_str());
+                }
+                let mut ann_type = self.type_from_annotation(&ann_assign.annotation);
 
                 if let Some(value) = &ann_assign.value {
-                    let value_type = self.infer_expr(value);
+                    let value_type = self.infer_against(value, &ann_type);
+                    // A bare `Final` takes the type of its value
+                    if matches!(&*ann_assign.annotation, Expr::Name(_) | Expr::Attribute(_))
+                        && consteval::is_final(&ann_assign.annotation)
+                    {
+                        ann_type = value_type.clone();
+                    }
+
+                    // Constants are checked exactly against Literal and Bounded annotations
+                    let exact = self.consts.eval(value).ok().and_then(|constant| {
+                        self.constant_fits(&constant, &ann_type).map(|fits| (fits, constant.to_string()))
+                    });
+                    // and refined ints by their bounds, reported as they're checked
+                    let bounded = match exact {
+                        Some(_) => None,
+                        None => {
+                            let bounds = self.interval_of(value, &value_type);
+                            self.check_bounds(&**value, bounds, &ann_type)
+                        }
+                    };
+                    let checked_exactly = exact.is_some() || bounded.is_some();
+                    let (fits, shown) = match (exact, bounded) {
+                        (Some(exact), _) => exact,
+                        (None, Some(_)) => (true, value_type.to_string()),
+                        (None, None) => (self.is_compatible(&value_type, &ann_type), value_type.to_string()),
+                    };
 
                     // Check type compatibility
-                    if !self.is_compatible(&value_type, &ann_type) {
-                        if let Expr::Name(name_expr) = &*ann_assign.target {
-                            self.errors.push(TypeError {
-                                message: format!(
-                                    "Type mismatch: cannot assign {} to variable '{}' of type {}",
-                                    value_type, name_expr.id, ann_type
-                                ),
-                                line: 0,
-                                col: 0,
-                            });
-                        } else {
-                            self.errors.push(TypeError {
-                                message: format!(
-                                    "Type mismatch: cannot assign {} to type {}",
-                                    value_type, ann_type
-                                ),
-                                line: 0,
-                                col: 0,
-                            });
-                        }
-                    }
-
-                    // Add constraint
-                    self.constraints.add_constraint(Constraint::Subtype(value_type, ann_type.clone()));
+                    if !fits {
+                        let note = self.mismatch_note(&value_type, &ann_type);
+                        let message = match &*ann_assign.target {
+                            Expr::Name(name_expr) => format!(
+                                "Type mismatch: cannot assign {} to variable '{}' of type {}{}",
+                                shown, name_expr.id, ann_type, note
+                            ),
+                            _ => format!("Type mismatch: cannot assign {} to type {}{}", shown, ann_type, note),
+                        };
+                        self.report(&**value, codes::TYPE_MISMATCH, message);
+                    }
+
+                    // Add constraint; exactly checked constants have been judged already
+                    if !checked_exactly {
+                        let site = value.start().to_usize()..value.end().to_usize();
+                        self.constraints.add_constraint_at(Constraint::Subtype(value_type, ann_type.clone()), Some(site));
+                    }
                 }
 
                 // Register the variable with its annotation type
                 if let Expr::Name(name_expr) = &*ann_assign.target {
+                    self.declare_refined(name_expr.id.to_string(), &ann_type);
                     self.ctx.set_type(name_expr.id.to_string(), ann_type);
                 }
             }
 
             Stmt::Return(ret) => {
                 if let Some(val) = &ret.value {
-                    let inferred = self.infer_expr(val);
+                    let inferred = match self.current_function_return_type.clone() {
+                        Some(expected) => self.infer_against(val, &expected),
+                        None => self.infer_expr(val),
+                    };
+                    if let Some(returned) = &mut self.returned {
+                        returned.push(inferred.clone());
+                    }
                     // Check against expected return type
                     if let Some(expected) = &self.current_function_return_type {
-                        if !inferred.is_subtype(expected) {
-                            self.errors.push(TypeError {
-                                message: format!(
-                                    "Return type mismatch: expected {:?}, got {:?}",
-                                    expected, inferred
-                                ),
-                                line: 0,
-                                col: 0,
-                            });
+                        if !self.ctx.is_subtype(&inferred, expected) {
+                            let message = format!("Return type mismatch: expected {:?}, got {:?}", expected, inferred);
+                            self.report(&**val, codes::RETURN_TYPE, message);
+                        } else if self.options.reports_implicit_any() && Self::is_implicit_any(&inferred) {
+                            let message = format!("Returning an implicit Any from a function declared to return {}", expected);
+                            self.report(&**val, codes::IMPLICIT_ANY, message);
                         }
                     }
+                } else if let Some(returned) = &mut self.returned {
+                    returned.push(Type::None);
                 } else if let Some(expected) = &self.current_function_return_type {
                     // Empty return, check if function expects None
                     if !matches!(expected, Type::None) {
-                        self.errors.push(TypeError {
-                            message: format!("Expected return value of type {:?}, got None", expected),
-                            line: 0,
-                            col: 0,
-                        });
+                        let message = format!("Expected return value of type {:?}, got None", expected);
+                        self.report(ret, codes::RETURN_TYPE, message);
                     }
                 }
             }
 
+            Stmt::AugAssign(aug_assign) => self.check_aug_assign(aug_assign),
+
             Stmt::Expr(expr_stmt) => {
                 self.infer_expr(&expr_stmt.value);
             }
 
             Stmt::Import(_) | Stmt::ImportFrom(_) => {
-                // Import statements are valid, no type checking needed
                 // Types from typing module are handled in type_from_annotation
+                self.check_layering(stmt);
+                self.timed_rule("imports", |this| this.bind_imports(stmt));
             }
 
             Stmt::ClassDef(class_def) => {
-                // Register class type
-                let class_type = Type::Class(class_def.name.to_string());
+                // The name is bound to the class object; calling it makes instances
+                let class_type = Type::ClassObject(class_def.name.to_string());
+                let doc = Docstring::of(&class_def.body);
                 self.ctx.set_type(class_def.name.to_string(), class_type);
+                self.ctx.set_doc(class_def.name.to_string(), doc.clone());
+
+                // Record bases so subclass relationships are known
+                let mut bases = class_bases(class_def);
+                let mut schema = ClassSchema::new(class_def.name.to_string()).with_doc(doc);
+                let type_params = generics::class_type_params(&class_def.bases, &self.type_vars);
+                if !type_params.is_empty() {
+                    schema = schema.with_variances(type_params.iter().map(|param| (param.name.clone(), param.variance)).collect());
+                    self.record_generic(&class_def.name, type_params);
+                }
+                // A TypedDict is a dict at runtime, so it has the dict methods
+                if let Some(keys) = self.typed_dict_class(class_def, &bases) {
+                    bases.push("dict".to_string());
+                    schema = schema.with_typed_dict(keys);
+                }
+                let protocol = class_def.bases.iter().any(is_protocol_base);
+                if protocol {
+                    schema = schema.with_protocol();
+                }
+                self.ctx.register_class(schema.with_bases(bases.clone()));
 
                 // Track current class for attribute resolution
                 let prev_class = self.current_class.clone();
                 self.current_class = Some(class_def.name.to_string());
                 self.class_attributes.insert(class_def.name.to_string(), std::collections::HashMap::new());
+                let in_class_body = std::mem::replace(&mut self.in_class_body, true);
+
+                // Names the body binds are the class's, not the enclosing scope's
+                let mut locals = HashMap::new();
+                for (name, range) in bindings::binding_sites(&class_def.body) {
+                    locals.entry(name).or_insert(range.start);
+                }
+                self.enter_scope(ScopeKind::Class, locals, HashSet::new());
+
+                // Methods and attributes are declared before any body is
+                // checked, so each method can use those defined below it
+                self.declare_members(class_def);
 
                 // Check class body
+                let dataclass = dataclasses::dataclass_options(&class_def.decorator_list);
+                let mut fields = Vec::new();
                 for stmt in &class_def.body {
                     self.check_stmt(stmt);
+                    self.record_class_member(&class_def.name, stmt);
+                    if dataclass.is_some() {
+                        fields.extend(self.dataclass_field(stmt));
+                    }
+                    if protocol {
+                        self.record_protocol_attribute(&class_def.name, stmt);
+                    }
+                }
+                if let Some(options) = dataclass {
+                    self.record_dataclass(&class_def.name, &bases, options, fields);
                 }
+                self.check_variances(class_def);
+                self.leave_scope();
 
                 // Restore previous class context
                 self.current_class = prev_class;
+                self.in_class_body = in_class_body;
             }
 
             Stmt::For(for_stmt) => {
@@ -308,21 +1492,15 @@ impl TypeChecker {
                 let iterable_ty = self.infer_expr(&for_stmt.iter);
 
                 // Get the element type from the iterable
-                let elem_ty = match iterable_ty {
-                    Type::List(elem) => *elem,
-                    Type::Set(elem) => *elem,
-                    Type::Tuple(elems) if !elems.is_empty() => {
-                        // For tuple, use union of all element types
-                        Type::union(elems)
-                    }
-                    Type::Dict(key, _) => *key, // Iterating over dict gives keys
-                    Type::Str => Type::Str, // String iteration gives strings
-                    _ => self.ctx.fresh_var(),
-                };
+                let elem_ty = self.element_type(iterable_ty);
 
-                // Set the loop variable type
-                if let Expr::Name(name_expr) = &*for_stmt.target {
-                    self.ctx.set_type(name_expr.id.to_string(), elem_ty);
+                // Set the loop variable types, unpacking `for k, v in d.items()`
+                match &*for_stmt.target {
+                    Expr::Name(name_expr) => self.ctx.set_type(name_expr.id.to_string(), elem_ty),
+                    target @ (Expr::Tuple(_) | Expr::List(_)) => {
+                        self.check_unpack(target, LengthBound::from_type(&elem_ty), &elem_ty);
+                    }
+                    _ => {}
                 }
 
                 // Check the loop body
@@ -351,29 +1529,50 @@ impl TypeChecker {
                 }
             }
 
-            Stmt::If(if_stmt) => {
-                // Check the condition
-                let _cond_ty = self.infer_expr(&if_stmt.test);
+            Stmt::If(if_stmt) => self.check_if(if_stmt),
 
-                // Check the if body
-                for stmt in &if_stmt.body {
-                    self.check_stmt(stmt);
+            Stmt::Assert(assert_stmt) => {
+                self.infer_expr(&assert_stmt.test);
+                if let Some(msg) = &assert_stmt.msg {
+                    self.infer_expr(msg);
                 }
 
-                // Check elif/else clauses
-                for stmt in &if_stmt.orelse {
-                    self.check_stmt(stmt);
-                }
+                // The rest of the block only runs if the assertion held
+                let narrowing = self.narrow(&assert_stmt.test);
+                self.apply_narrowing(narrowing.positive);
             }
 
-            Stmt::With(_) => {
-                // Context manager - basic traversal for now
-                // Full implementation would track resource types
+            Stmt::Match(match_stmt) => self.check_match(match_stmt),
+
+            Stmt::Try(try_stmt) => {
+                self.check_try(&try_stmt.body, &try_stmt.handlers, &try_stmt.orelse, &try_stmt.finalbody, false);
             }
 
-            _ => {}
-        }
-    }
+            Stmt::TryStar(try_stmt) => {
+                self.check_try(&try_stmt.body, &try_stmt.handlers, &try_stmt.orelse, &try_stmt.finalbody, true);
+            }
+
+            Stmt::With(with_stmt) => {
+                for item in &with_stmt.items {
+                    self.check_with_item(item, false);
+                }
+                for stmt in &with_stmt.body {
+                    self.check_stmt(stmt);
+                }
+            }
+
+            Stmt::AsyncWith(with_stmt) => {
+                for item in &with_stmt.items {
+                    self.check_with_item(item, true);
+                }
+                for stmt in &with_stmt.body {
+                    self.check_stmt(stmt);
+                }
+            }
+
+            _ => {}
+        }
+    }
 
     fn infer_stmt(&mut self, stmt: &Stmt) -> Type {
         self.check_stmt(stmt);
@@ -395,67 +1594,68 @@ impl TypeChecker {
                 }
             }
 
-            Expr::Name(name_expr) => {
-                self.ctx.get_type(&name_expr.id).unwrap_or_else(|| self.ctx.fresh_var())
-            }
+            Expr::Name(name_expr) => match self.ctx.get_type(&name_expr.id) {
+                Some(ty) => {
+                    self.name_types.insert(name_expr.start().to_usize()..name_expr.end().to_usize(), ty.clone());
+                    ty
+                }
+                None => {
+                    if let Some(ty) = self.class_fallback(&name_expr.id) {
+                        return ty;
+                    }
+                    if self.reads_unbound_local(&name_expr.id, name_expr.start().to_usize()) {
+                        let kind = ErrorKind::UnboundLocal { name: name_expr.id.to_string() };
+                        self.report(name_expr, codes::UNBOUND_LOCAL, kind.to_string());
+                    }
+                    self.ctx.fresh_var()
+                }
+            },
 
             Expr::BinOp(binop) => {
                 let left_ty = self.infer_expr(&binop.left);
                 let right_ty = self.infer_expr(&binop.right);
+                let operation = || format!("operator '{}'", operator_symbol(binop.op));
+                let left_ty = self.require_not_none(&binop.left, left_ty, operation);
+                let right_ty = self.require_not_none(&binop.right, right_ty, operation);
+                if let Some(ty) = self.dunder_binop(binop, &left_ty, &right_ty) {
+                    return ty;
+                }
+                let bounds = (self.interval_of(&binop.left, &left_ty), self.interval_of(&binop.right, &right_ty));
+                if let (Operator::Mod, Some(template)) = (binop.op, formatting::string_literal(&binop.left)) {
+                    self.check_percent_format(binop, template, &right_ty);
+                }
+                let refined = matches!(left_ty, Type::Refinement(..)) || matches!(right_ty, Type::Refinement(..));
+                match (binop.op, bounds) {
+                    // Arithmetic on refined ints keeps track of their bounds
+                    (Operator::Add, (Some(left), Some(right))) if refined => (left + right).to_type(),
+                    (Operator::Sub, (Some(left), Some(right))) if refined => (left - right).to_type(),
+                    (Operator::Mult, (Some(left), Some(right))) if refined => (left * right).to_type(),
+                    _ => Self::binop_type(binop.op, Self::unrefined(left_ty), Self::unrefined(right_ty)),
+                }
+            }
 
-                use rustpython_parser::ast::Operator as Op;
-                match binop.op {
-                    // Addition
-                    Op::Add => {
-                        if left_ty == Type::Int && right_ty == Type::Int {
-                            Type::Int
-                        } else if matches!(left_ty, Type::Int | Type::Float)
-                            && matches!(right_ty, Type::Int | Type::Float) {
-                            Type::Float
-                        } else if left_ty == Type::Str && right_ty == Type::Str {
-                            Type::Str  // String concatenation
-                        } else if matches!(left_ty, Type::List(_)) && matches!(right_ty, Type::List(_)) {
-                            left_ty  // List concatenation
-                        } else {
-                            Type::Any
-                        }
-                    }
-                    // Multiplication
-                    Op::Mult => {
-                        if left_ty == Type::Int && right_ty == Type::Int {
-                            Type::Int
-                        } else if matches!(left_ty, Type::Int | Type::Float)
-                            && matches!(right_ty, Type::Int | Type::Float) {
-                            Type::Float
-                        } else if (left_ty == Type::Str && right_ty == Type::Int) ||
-                                  (left_ty == Type::Int && right_ty == Type::Str) {
-                            Type::Str  // String repetition
-                        } else {
-                            Type::Any
-                        }
-                    }
-                    // Subtraction, Modulo, Power
-                    Op::Sub | Op::Mod | Op::Pow => {
-                        if left_ty == Type::Int && right_ty == Type::Int {
-                            Type::Int
-                        } else if matches!(left_ty, Type::Int | Type::Float)
-                            && matches!(right_ty, Type::Int | Type::Float) {
-                            Type::Float
-                        } else {
-                            Type::Any
-                        }
-                    }
-                    // Division always returns float
-                    Op::Div => Type::Float,
-                    // Floor division returns int
-                    Op::FloorDiv => Type::Int,
-                    _ => Type::Any,
+            // `(x := value)` binds `x` and evaluates to the value
+            Expr::NamedExpr(named) => {
+                let value_ty = self.infer_expr(&named.value);
+                if let Expr::Name(target) = &*named.target {
+                    self.ctx.set_type(target.id.to_string(), value_ty.clone());
                 }
+                value_ty
             }
 
-            Expr::Compare(_compare) => {
-                // All comparisons return bool (==, !=, <, >, <=, >=, in, not in, is, is not)
-                Type::Bool
+            Expr::Compare(compare) => {
+                // `(x := f()) is not None` still binds `x`
+                for operand in std::iter::once(&*compare.left).chain(&compare.comparators) {
+                    if matches!(operand, Expr::NamedExpr(_)) {
+                        self.infer_expr(operand);
+                    }
+                }
+                if self.options.lint_level(lints::FLOAT_EQUALITY) != LintLevel::Off {
+                    self.record_operand_types(compare);
+                }
+                // Comparisons return bool (==, !=, <, >, <=, >=, in, not in, is, is not),
+                // unless a class's ordering method says otherwise
+                self.check_comparison_dunders(compare).unwrap_or(Type::Bool)
             }
 
             Expr::UnaryOp(unary) => {
@@ -486,77 +1686,29 @@ impl TypeChecker {
             }
 
             Expr::ListComp(list_comp) => {
-                // Handle generators to set loop variable types
-                for generator in &list_comp.generators {
-                    let iterable_ty = self.infer_expr(&generator.iter);
-
-                    // Get element type from iterable
-                    let elem_ty = match iterable_ty {
-                        Type::List(elem) => *elem,
-                        Type::Set(elem) => *elem,
-                        Type::Tuple(elems) if !elems.is_empty() => Type::union(elems),
-                        Type::Dict(key, _) => *key,
-                        Type::Str => Type::Str,
-                        _ => self.ctx.fresh_var(),
-                    };
-
-                    // Set loop variable type
-                    if let Expr::Name(name_expr) = &generator.target {
-                        self.ctx.set_type(name_expr.id.to_string(), elem_ty);
-                    }
-                }
-
+                self.enter_comprehension(&list_comp.generators);
                 // Infer type of list comprehension from element expression
                 let elem_type = self.infer_expr(&list_comp.elt);
+                self.leave_scope();
                 Type::List(Box::new(elem_type))
             }
 
             Expr::DictComp(dict_comp) => {
-                // Handle generators to set loop variable types
-                for generator in &dict_comp.generators {
-                    let iterable_ty = self.infer_expr(&generator.iter);
-
-                    let elem_ty = match iterable_ty {
-                        Type::List(elem) => *elem,
-                        Type::Set(elem) => *elem,
-                        Type::Tuple(elems) if !elems.is_empty() => Type::union(elems),
-                        Type::Dict(key, _) => *key,
-                        Type::Str => Type::Str,
-                        _ => self.ctx.fresh_var(),
-                    };
-
-                    if let Expr::Name(name_expr) = &generator.target {
-                        self.ctx.set_type(name_expr.id.to_string(), elem_ty);
-                    }
-                }
-
+                self.enter_comprehension(&dict_comp.generators);
                 // Infer type of dict comprehension
                 let key_type = self.infer_expr(&dict_comp.key);
+                self.require_hashable(&dict_comp.key, &key_type);
                 let value_type = self.infer_expr(&dict_comp.value);
+                self.leave_scope();
                 Type::Dict(Box::new(key_type), Box::new(value_type))
             }
 
             Expr::SetComp(set_comp) => {
-                // Handle generators to set loop variable types
-                for generator in &set_comp.generators {
-                    let iterable_ty = self.infer_expr(&generator.iter);
-
-                    let elem_ty = match iterable_ty {
-                        Type::List(elem) => *elem,
-                        Type::Set(elem) => *elem,
-                        Type::Tuple(elems) if !elems.is_empty() => Type::union(elems),
-                        Type::Dict(key, _) => *key,
-                        Type::Str => Type::Str,
-                        _ => self.ctx.fresh_var(),
-                    };
-
-                    if let Expr::Name(name_expr) = &generator.target {
-                        self.ctx.set_type(name_expr.id.to_string(), elem_ty);
-                    }
-                }
-
+                self.enter_comprehension(&set_comp.generators);
                 // Infer type of set comprehension
                 let elem_type = self.infer_expr(&set_comp.elt);
+                self.require_hashable(&set_comp.elt, &elem_type);
+                self.leave_scope();
                 Type::Set(Box::new(elem_type))
             }
 
@@ -565,6 +1717,9 @@ impl TypeChecker {
                     Type::Set(Box::new(self.ctx.fresh_var()))
                 } else {
                     let elem_types: Vec<Type> = set_expr.elts.iter().map(|e| self.infer_expr(e)).collect();
+                    for (elt, elem_type) in set_expr.elts.iter().zip(&elem_types) {
+                        self.require_hashable(elt, elem_type);
+                    }
                     let unified = Type::union(elem_types);
                     Type::Set(Box::new(unified))
                 }
@@ -576,9 +1731,11 @@ impl TypeChecker {
             }
 
             Expr::Dict(dict_expr) => {
-                let key_types: Vec<Type> = dict_expr.keys.iter()
-                    .filter_map(|k| k.as_ref().map(|e| self.infer_expr(e)))
-                    .collect();
+                let keys: Vec<&Expr> = dict_expr.keys.iter().flatten().collect();
+                let key_types: Vec<Type> = keys.iter().map(|key| self.infer_expr(key)).collect();
+                for (key, key_type) in keys.iter().zip(&key_types) {
+                    self.require_hashable(key, key_type);
+                }
                 let value_types: Vec<Type> = dict_expr.values.iter().map(|v| self.infer_expr(v)).collect();
 
                 let key_type = if key_types.is_empty() {
@@ -597,305 +1754,3710 @@ impl TypeChecker {
             }
 
             Expr::Call(call_expr) => {
+                if let Some(template) = Self::format_template(&call_expr.func) {
+                    return self.check_format_call(call_expr, template);
+                }
+                if let Some(arg) = self.builtin_len_arg(call_expr) {
+                    return self.infer_len(arg);
+                }
+                if let Some(is_method) = self.partial_kind(&call_expr.func) {
+                    return self.timed_rule("partial", |this| this.infer_partial(call_expr, is_method));
+                }
+
                 let func_ty = self.infer_expr(&call_expr.func);
+                let func_ty = self.require_not_none(&call_expr.func, func_ty, || "calling".to_string());
+
+                if let Some(overloads) = self.overloads_of(&call_expr.func) {
+                    let ret = self.call_overloaded(call_expr, &overloads);
+                    if ret == Type::Never {
+                        self.never_calls.insert(call_expr.start().to_usize());
+                    }
+                    return ret;
+                }
 
-                match func_ty {
+                match Self::strip_effects(func_ty) {
+                    // Instantiating a class yields an instance of it
+                    Type::ClassObject(name) => {
+                        self.check_constructor_args(call_expr, &name);
+                        Type::Class(name)
+                    }
                     Type::Function(params, ret) => {
-                        // Check argument count
-                        if call_expr.args.len() != params.len() {
-                            self.errors.push(TypeError {
-                                message: format!(
-                                    "Function call argument count mismatch: expected {}, got {}",
-                                    params.len(),
-                                    call_expr.args.len()
-                                ),
-                                line: 0,
-                                col: 0,
-                            });
-                        }
-
-                        // Check argument types
-                        for (i, (arg, param_ty)) in call_expr.args.iter().zip(params.iter()).enumerate() {
-                            let arg_ty = self.infer_expr(arg);
-                            if !arg_ty.is_subtype(param_ty) {
-                                self.errors.push(TypeError {
-                                    message: format!(
-                                        "Argument {} type mismatch: expected {:?}, got {:?}",
-                                        i, param_ty, arg_ty
-                                    ),
-                                    line: 0,
-                                    col: 0,
-                                });
+                        let first_error = self.errors.len();
+                        // A signature recorded for a different binding of the name doesn't apply
+                        let sig = self.signature_of(&call_expr.func).filter(|sig| sig.params.len() == params.len());
+                        self.check_call_args(call_expr, &params, sig.as_ref());
+
+                        // Point misuse at the callee's documented contract
+                        if self.errors.len() > first_error {
+                            if let Some(note) = self.callee_doc_note(&call_expr.func) {
+                                for error in &mut self.errors[first_error..] {
+                                    error.message = format!("{}; {}", error.message, note);
+                                }
                             }
                         }
 
-                        *ret
+                        // Nothing after a call to a `-> Never` function runs
+                        if *ret == Type::Never {
+                            self.never_calls.insert(call_expr.start().to_usize());
+                        }
+                        match type_guard(&ret) {
+                            Some(_) => Type::Bool,
+                            None => *ret,
+                        }
                     }
-                    _ => self.ctx.fresh_var()
-                }
-            }
-
-            Expr::Subscript(subscript_expr) => {
-                // Handle indexing: list[i], dict[key], tuple[i]
-                let value_ty = self.infer_expr(&subscript_expr.value);
-
-                match value_ty {
-                    Type::List(elem_ty) => *elem_ty,
-                    Type::Dict(_, val_ty) => *val_ty,
-                    Type::Tuple(types) => {
-                        // For tuple indexing, if we can determine the index statically, return that type
-                        // Otherwise, return union of all types
-                        if types.is_empty() {
-                            Type::Any
-                        } else if types.len() == 1 {
-                            types[0].clone()
-                        } else {
-                            Type::union(types)
+                    // Values of builtin types other than functions and classes can't be called
+                    ty @ (Type::None | Type::Bool | Type::Int | Type::Float | Type::Str | Type::Bytes
+                    | Type::List(_) | Type::Tuple(_) | Type::VarTuple(_) | Type::Dict(..) | Type::Set(_)) => {
+                        for arg in call_expr.args.iter().chain(call_expr.keywords.iter().map(|keyword| &keyword.value)) {
+                            self.infer_expr(arg);
                         }
+                        let kind = ErrorKind::NonCallable { ty: ty.to_string() };
+                        self.report(&*call_expr.func, kind.code(), kind.to_string());
+                        self.ctx.fresh_var()
+                    }
+                    Type::Class(name) if self.ctx.knows_hierarchy(&name) => self.call_instance(call_expr, &name),
+                    // Arguments to unknown callees aren't checked, only
+                    // inferred for `type_at`, so their errors are dropped
+                    _ => {
+                        let errors = self.errors.len();
+                        for arg in call_expr.args.iter().chain(call_expr.keywords.iter().map(|keyword| &keyword.value)) {
+                            self.infer_expr(arg);
+                        }
+                        self.errors.truncate(errors);
+                        self.ctx.fresh_var()
                     }
-                    Type::Str => Type::Str,  // String indexing returns str
-                    _ => self.ctx.fresh_var(),
                 }
             }
 
-            Expr::Attribute(attr_expr) => {
-                let value_ty = self.infer_expr(&attr_expr.value);
+            Expr::Subscript(subscript_expr) => self.infer_subscript(subscript_expr),
 
-                // For class types, look up in class_attributes
-                if let Type::Class(class_name) = &value_ty {
-                    if let Some(attrs) = self.class_attributes.get(class_name) {
-                        if let Some(attr_ty) = attrs.get(attr_expr.attr.as_str()) {
-                            return attr_ty.clone();
-                        }
-                    }
-                }
+            Expr::Attribute(attr_expr) => self.timed_rule("attributes", |this| this.infer_attribute(attr_expr)),
 
-                // Otherwise, lookup attribute from context
-                self.ctx.has_attribute(&value_ty, &attr_expr.attr)
-                    .unwrap_or_else(|| {
-                        // Don't generate error for class types - attributes might be set dynamically
-                        if matches!(value_ty, Type::Class(_)) {
-                            self.ctx.fresh_var()
-                        } else {
-                            // Generate error with suggestions for non-class types
-                            let available = self.ctx.get_attributes(&value_ty);
-                            let similar = crate::compiler::errors::find_similar_names(&attr_expr.attr, &available, 2);
+            Expr::Await(await_expr) => self.infer_await(&await_expr.value),
 
-                            let mut msg = format!(
-                                "Type '{}' has no attribute '{}'",
-                                value_ty, attr_expr.attr
-                            );
-                            if !similar.is_empty() {
-                                msg.push_str(&format!(". Did you mean: {}?", similar.join(", ")));
-                            }
+            Expr::Yield(yield_expr) => {
+                let value_ty = yield_expr.value.as_ref().map_or(Type::None, |value| self.infer_expr(value));
+                self.record_yield(expr, value_ty)
+            }
+
+            // Each element is yielded in turn; the result is the inner generator's return value
+            Expr::YieldFrom(yield_from) => {
+                let inner_ty = Self::strip_effects(self.infer_expr(&yield_from.value));
+                let returned = match &inner_ty {
+                    Type::Generic(name, args) if name == "Generator" && args.len() == 3 => args[2].clone(),
+                    _ => Type::Any,
+                };
+                let elem_ty = self.element_type(inner_ty);
+                self.record_yield(expr, elem_ty);
+                returned
+            }
 
-                            self.errors.push(TypeError {
-                                message: msg,
-                                line: 0,
-                                col: 0,
-                            });
+            Expr::Lambda(lambda) => self.infer_lambda(lambda, None),
 
-                            self.ctx.fresh_var()
-                        }
-                    })
+            Expr::JoinedStr(joined) => {
+                for value in &joined.values {
+                    self.infer_expr(value);
+                }
+                Type::Str
+            }
+
+            // `{value!r:>10}`: after a conversion the spec applies to a str
+            Expr::FormattedValue(formatted) => {
+                let value_ty = self.infer_expr(&formatted.value);
+                if let Some(spec) = &formatted.format_spec {
+                    self.infer_expr(spec);
+                    let ty = match formatted.conversion {
+                        ConversionFlag::None => value_ty,
+                        _ => Type::Str,
+                    };
+                    if let Some(spec) = formatting::constant_spec(spec) {
+                        self.check_format_spec(&formatted.value, &ty, &spec);
+                    }
+                }
+                Type::Str
             }
 
             _ => Type::Any,
         }
     }
 
-    fn is_compatible(&self, actual: &Type, expected: &Type) -> bool {
-        // Check if actual type is compatible with expected type
-        match (actual, expected) {
-            // Exact matches
-            (Type::Int, Type::Int) => true,
-            (Type::Float, Type::Float) => true,
-            (Type::Str, Type::Str) => true,
-            (Type::Bool, Type::Bool) => true,
-            (Type::Bytes, Type::Bytes) => true,
-            (Type::None, Type::None) => true,
-            (Type::Any, _) | (_, Type::Any) => true,
+    /// Template of a `"...".format(...)` call on a string literal
+    fn format_template(func: &Expr) -> Option<&str> {
+        match func {
+            Expr::Attribute(attr) if attr.attr.as_str() == "format" => formatting::string_literal(&attr.value),
+            _ => None,
+        }
+    }
 
-            // Int is compatible with Float (subtyping)
-            (Type::Int, Type::Float) => true,
+    /// Check a `"...".format(...)` call's arguments against the template's
+    /// replacement fields; unpacked `*args` or `**kwargs` make the positional
+    /// or keyword fields unknowable
+    fn check_format_call(&mut self, call_expr: &ExprCall, template: &str) -> Type {
+        let mut positional = Vec::new();
+        let mut unpacked = false;
+        for arg in &call_expr.args {
+            let ty = self.infer_expr(arg);
+            unpacked |= matches!(arg, Expr::Starred(_));
+            positional.push((arg, ty));
+        }
+        let mut keywords = HashMap::new();
+        let mut unpacked_keywords = false;
+        for keyword in &call_expr.keywords {
+            let ty = self.infer_expr(&keyword.value);
+            match &keyword.arg {
+                Some(name) => {
+                    keywords.insert(name.as_str(), (&keyword.value, ty));
+                }
+                None => unpacked_keywords = true,
+            }
+        }
 
-            // Collection types
-            (Type::List(a), Type::List(b)) => self.is_compatible(a, b),
-            (Type::Set(a), Type::Set(b)) => self.is_compatible(a, b),
-            (Type::Dict(ka, va), Type::Dict(kb, vb)) => {
-                self.is_compatible(ka, kb) && self.is_compatible(va, vb)
+        let fields = match formatting::format_fields(template) {
+            Ok(fields) => fields,
+            Err(message) => {
+                self.report(&*call_expr.func, codes::STRING_FORMAT, format!("Invalid format string: {}", message));
+                return Type::Str;
             }
-            (Type::Tuple(ta), Type::Tuple(tb)) => {
-                ta.len() == tb.len() &&
-                ta.iter().zip(tb.iter()).all(|(a, b)| self.is_compatible(a, b))
+        };
+        let needed = match formatting::positional_needed(&fields) {
+            Ok(needed) => needed,
+            Err(message) => {
+                self.report(&*call_expr.func, codes::STRING_FORMAT, format!("Invalid format string: {}", message));
+                return Type::Str;
             }
+        };
+        if !unpacked && needed != positional.len() {
+            let message = format!(
+                "Format string expects {} positional argument(s), found {}",
+                needed, positional.len()
+            );
+            self.report(call_expr, codes::STRING_FORMAT, message);
+        }
 
-            // Function types
-            (Type::Function(pa, ra), Type::Function(pb, rb)) => {
-                pa.len() == pb.len() &&
-                pa.iter().zip(pb.iter()).all(|(a, b)| self.is_compatible(a, b)) &&
-                self.is_compatible(ra, rb)
-            }
+        let mut next_auto = 0;
+        for field in &fields {
+            let arg = match &field.arg {
+                FieldRef::Name(name) => {
+                    let arg = keywords.get(name.as_str());
+                    if arg.is_none() && !unpacked_keywords {
+                        let message = format!("Format string refers to missing keyword argument '{}'", name);
+                        self.report(call_expr, codes::STRING_FORMAT, message);
+                    }
+                    arg
+                }
+                FieldRef::Index(index) => positional.get(*index).filter(|_| !unpacked),
+                FieldRef::Auto => {
+                    next_auto += 1;
+                    positional.get(next_auto - 1).filter(|_| !unpacked)
+                }
+            };
+            let (Some((value, ty)), Some(spec), false) = (arg, &field.spec, field.lookup) else { continue };
+            let ty = if field.conversion.is_some() { Type::Str } else { ty.clone() };
+            self.check_format_spec(value, &ty, spec);
+        }
+        Type::Str
+    }
 
-            // Union types - actual must be one of the expected union members
-            (actual, Type::Union(expected_types)) => {
-                expected_types.iter().any(|t| self.is_compatible(actual, t))
+    /// Check a format spec's presentation type, e.g. the `d` in `{:>5d}`,
+    /// against the type of the value it formats
+    fn check_format_spec(&mut self, value: &Expr, ty: &Type, spec: &str) {
+        let (Some(code), Some(kind)) = (formatting::spec_type(spec), ValueKind::of(ty)) else { return };
+        if !kind.accepts_spec(code) {
+            let message = format!("Format code '{}' is not valid for a value of type {}", code, ty);
+            self.report(value, codes::STRING_FORMAT, message);
+        }
+    }
+
+    /// Check `template % args` against the template's conversions: a tuple's
+    /// arity against their count and each value against its conversion, or a
+    /// dict literal's keys against the `%(name)s` keys
+    fn check_percent_format(&mut self, binop: &ExprBinOp, template: &str, args_ty: &Type) {
+        let conversions = match formatting::percent_conversions(template) {
+            Ok(conversions) => conversions,
+            Err(message) => {
+                self.report(&*binop.left, codes::STRING_FORMAT, format!("Invalid format string: {}", message));
+                return;
+            }
+        };
+        if conversions.iter().any(|conversion| conversion.key.is_some()) {
+            let Expr::Dict(dict) = &*binop.right else { return };
+            let keys: Option<HashSet<&str>> =
+                dict.keys.iter().map(|key| key.as_ref().and_then(formatting::string_literal)).collect();
+            let Some(keys) = keys else { return };
+            for key in conversions.iter().filter_map(|conversion| conversion.key.as_deref()) {
+                if !keys.contains(key) {
+                    let message = format!("Format string refers to missing key '{}'", key);
+                    self.report(&*binop.right, codes::STRING_FORMAT, message);
+                }
             }
+            return;
+        }
 
-            // Class types
-            (Type::Class(a), Type::Class(b)) => a == b,
+        let args = match args_ty {
+            Type::Tuple(types) => types.clone(),
+            // A lone mapping fills a template without conversions
+            Type::Dict(..) if conversions.is_empty() => return,
+            Type::None | Type::Bytes | Type::List(_) | Type::Set(_) | Type::Dict(..) => vec![args_ty.clone()],
+            ty if matches!(ValueKind::of(ty), Some(ValueKind::Int | ValueKind::Float | ValueKind::Str)) => vec![ty.clone()],
+            _ => return,
+        };
+        let needed: usize = conversions.iter().map(|conversion| conversion.stars + 1).sum();
+        if needed != args.len() {
+            let problem = match needed > args.len() {
+                true => "Not enough arguments for format string",
+                false => "Not all arguments converted during string formatting",
+            };
+            let message = format!("{}: expects {}, found {}", problem, needed, args.len());
+            self.report(binop, codes::STRING_FORMAT, message);
+            return;
+        }
 
-            // Generic types
-            (Type::Generic(na, ta), Type::Generic(nb, tb)) => {
-                na == nb && ta.len() == tb.len() &&
-                ta.iter().zip(tb.iter()).all(|(a, b)| self.is_compatible(a, b))
+        let values: Vec<&Expr> = match &*binop.right {
+            Expr::Tuple(tuple) if tuple.elts.len() == args.len() => tuple.elts.iter().collect(),
+            right => vec![right; args.len()],
+        };
+        let mut index = 0;
+        for conversion in &conversions {
+            index += conversion.stars;
+            let (Some(kind), Some(requirement)) = (ValueKind::of(&args[index]), formatting::percent_requirement(conversion.code)) else {
+                index += 1;
+                continue;
+            };
+            if !kind.accepts_percent(conversion.code) {
+                let message = format!("%{} format: {} is required, not {}", conversion.code, requirement, args[index]);
+                self.report(values[index], codes::STRING_FORMAT, message);
             }
+            index += 1;
+        }
+    }
 
-            // Type variables are always compatible (will be resolved by constraint solver)
-            (Type::Var(_), _) | (_, Type::Var(_)) => true,
+    /// Resolve `value[index]`, checking constant indices against known lengths
+    fn infer_subscript(&mut self, subscript_expr: &ExprSubscript) -> Type {
+        // Handle indexing: list[i], dict[key], tuple[i]
+        let value_ty = self.infer_expr(&subscript_expr.value);
+        let value_ty = self.require_not_none(&subscript_expr.value, value_ty, || "subscripting".to_string());
+        if let Expr::Slice(slice) = &*subscript_expr.slice {
+            return self.infer_slice(&subscript_expr.value, value_ty, slice);
+        }
 
-            // Default: incompatible
-            _ => false,
+        let index = const_int(&subscript_expr.slice);
+        if let (Some(bound), Some(index)) = (LengthBound::from_expr(&subscript_expr.value, &value_ty), index) {
+            if bound.index_in_range(index) == Some(false) {
+                let kind = if matches!(value_ty, Type::Tuple(_)) { "tuple" } else { "list" };
+                let message = format!("{} index {} out of range (length {})", kind, index, bound.describe());
+                self.report(&*subscript_expr.slice, codes::INVALID_SUBSCRIPT, message);
+                return Type::Any;
+            }
         }
-    }
 
-    fn type_from_annotation(&mut self, expr: &Expr) -> Type {
-        match expr {
-            Expr::Name(name_expr) => match name_expr.id.as_str() {
-                "int" => Type::Int,
-                "float" => Type::Float,
-                "str" => Type::Str,
-                "bool" => Type::Bool,
-                "bytes" => Type::Bytes,
-                "None" => Type::None,
-                "Any" => Type::Any,
-                // Check for common refinement types
-                "Positive" => RefinementAnalyzer::positive_int(),
-                "Negative" => RefinementAnalyzer::negative_int(),
-                "NonEmpty" => RefinementAnalyzer::non_empty_str(),
-                _ => Type::Class(name_expr.id.to_string()),
-            },
+        if let Some((name, keys)) = self.typed_dict_of(&value_ty) {
+            return self.typed_dict_item(&subscript_expr.slice, &name, &keys);
+        }
 
-            Expr::Subscript(subscript) => {
-                if let Expr::Name(name_expr) = &*subscript.value {
-                    match name_expr.id.as_str() {
-                        "list" | "List" => Type::List(Box::new(self.type_from_annotation(&subscript.slice))),
-                        "set" | "Set" => Type::Set(Box::new(self.type_from_annotation(&subscript.slice))),
-                        "tuple" | "Tuple" => {
-                            // Handle tuple type annotations
-                            if let Expr::Tuple(tuple_expr) = &*subscript.slice {
-                                // Empty tuple: tuple[()] or tuple with elements
-                                if tuple_expr.elts.is_empty() {
-                                    Type::Tuple(vec![])
-                                } else {
-                                    let types = tuple_expr.elts.iter()
-                                        .map(|e| self.type_from_annotation(e))
-                                        .collect();
-                                    Type::Tuple(types)
-                                }
-                            } else {
-                                // Single element tuple or error - treat as single element
-                                Type::Tuple(vec![self.type_from_annotation(&subscript.slice)])
-                            }
-                        }
-                        "dict" | "Dict" => {
-                            if let Expr::Tuple(tuple_expr) = &*subscript.slice {
-                                if tuple_expr.elts.len() == 2 {
-                                    return Type::Dict(
-                                        Box::new(self.type_from_annotation(&tuple_expr.elts[0])),
-                                        Box::new(self.type_from_annotation(&tuple_expr.elts[1])),
-                                    );
-                                }
-                            }
-                            Type::Dict(Box::new(Type::Any), Box::new(Type::Any))
-                        }
-                        "Union" => {
-                            // Handle Union[T1, T2, ...] from typing
-                            if let Expr::Tuple(tuple_expr) = &*subscript.slice {
-                                let types = tuple_expr.elts.iter()
-                                    .map(|e| self.type_from_annotation(e))
-                                    .collect();
-                                Type::Union(types)
-                            } else {
-                                // Single type in Union
-                                Type::Union(vec![self.type_from_annotation(&subscript.slice)])
-                            }
-                        }
-                        "Optional" => {
-                            // Optional[T] is Union[T, None]
-                            let inner_type = self.type_from_annotation(&subscript.slice);
-                            Type::Union(vec![inner_type, Type::None])
-                        }
-                        // Advanced type annotations
-                        "EffectType" => {
-                            // Parse effect type annotation
-                            let base = self.type_from_annotation(&subscript.slice);
-                            base // For now, return base type; effects added via decorator analysis
-                        }
-                        "RefinementType" => {
-                            // Parse refinement type annotation
-                            self.type_from_annotation(&subscript.slice)
-                        }
-                        "RecursiveType" => {
-                            // Handle recursive type annotation
-                            if let Expr::Constant(c) = &*subscript.slice {
-                                if let Constant::Str(name) = &c.value {
-                                    // Register as recursive type placeholder
-                                    Type::Class(name.to_string())
-                                } else {
-                                    Type::Any
-                                }
-                            } else {
-                                Type::Any
-                            }
-                        }
-                        _ => Type::Generic(name_expr.id.to_string(), vec![self.type_from_annotation(&subscript.slice)]),
-                    }
-                } else {
+        match Self::strip_length(value_ty) {
+            Type::List(elem_ty) | Type::VarTuple(elem_ty) => *elem_ty,
+            Type::Dict(_, val_ty) => {
+                let key_ty = self.infer_expr(&subscript_expr.slice);
+                self.require_hashable(&subscript_expr.slice, &key_ty);
+                *val_ty
+            }
+            Type::Tuple(types) => {
+                // A constant index picks out its element; otherwise return union of all types
+                let position = index.map(|i| if i < 0 { types.len() as i64 + i } else { i });
+                if let Some(ty) = position.and_then(|i| types.get(usize::try_from(i).ok()?)) {
+                    ty.clone()
+                } else if types.is_empty() {
                     Type::Any
+                } else if types.len() == 1 {
+                    types[0].clone()
+                } else {
+                    Type::union(types)
                 }
             }
+            Type::Str => Type::Str,  // String indexing returns str
+            instance @ (Type::Class(_) | Type::Generic(..)) => self.dunder_subscript(subscript_expr, &instance),
+            _ => self.ctx.fresh_var(),
+        }
+    }
 
-            Expr::BinOp(binop) => {
-                if matches!(binop.op, Operator::BitOr) {
-                    let left_ty = self.type_from_annotation(&binop.left);
-                    let right_ty = self.type_from_annotation(&binop.right);
-                    Type::Union(vec![left_ty, right_ty])
-                } else {
-                    Type::Any
-                }
+    /// Resolve `value[lower:upper:step]`, carrying lengths through constant bounds
+    ///
+    /// Non-constant bounds or steps leave the result's length unconstrained.
+    /// Builtin sequences only take ints and `None` as bounds and steps.
+    fn infer_slice(&mut self, value: &Expr, value_ty: Type, slice: &ExprSlice) -> Type {
+        let parts: Vec<(&Expr, Type)> = [&slice.lower, &slice.upper, &slice.step].into_iter().flatten()
+            .map(|part| (&**part, self.infer_expr(part)))
+            .collect();
+        let sequence = Self::strip_length(value_ty.clone());
+        if matches!(sequence, Type::List(_) | Type::Tuple(_) | Type::VarTuple(_) | Type::Str | Type::Bytes) {
+            for (part, ty) in parts.iter().filter(|(_, ty)| Self::never_index(ty)) {
+                let message = format!("slice indices must be integers or None, not {}", ty);
+                self.report(*part, codes::INVALID_SUBSCRIPT, message);
             }
+        }
 
-            Expr::Call(call) => {
-                // Handle type constructor calls like Bounded(0, 100)
-                if let Expr::Name(name) = &*call.func {
-                    match name.id.as_str() {
-                        "Bounded" => {
+        let constant = |part: &Option<Box<Expr>>| match part {
+            Some(expr) => const_int(expr).map(Some),
+            None => Some(None),
+        };
+        let bounds = match (constant(&slice.lower), constant(&slice.upper), constant(&slice.step)) {
+            (Some(lower), Some(upper), Some(step)) if step != Some(0) => Some((lower, upper, step.unwrap_or(1))),
+            _ => None,
+        };
+
+        let known = LengthBound::from_expr(value, &value_ty);
+        match Self::strip_length(value_ty) {
+            list @ Type::List(_) => match (bounds, known) {
+                (Some((lower, upper, step)), Some(bound)) => {
+                    bound.slice(lower, upper, step).apply(&list).unwrap_or(list)
+                }
+                _ => list,
+            },
+            Type::Tuple(types) => match bounds {
+                Some((lower, upper, step)) => {
+                    let (start, len) = slice_indices(types.len() as i128, lower, upper, step);
+                    Type::Tuple((0..len).map(|i| types[(start + i * step as i128) as usize].clone()).collect())
+                }
+                None if types.is_empty() => Type::Tuple(types),
+                None => Type::VarTuple(Box::new(Type::union(types))),
+            },
+            var_tuple @ Type::VarTuple(_) => var_tuple,
+            Type::Str => Type::Str,
+            Type::Bytes => Type::Bytes,
+            _ => self.ctx.fresh_var(),
+        }
+    }
+
+    /// Whether no value of `ty` can be a sequence index; classes might
+    /// define `__index__`, so only builtins are ruled out
+    fn never_index(ty: &Type) -> bool {
+        match ty {
+            Type::Float | Type::Str | Type::Bytes | Type::List(_) | Type::Set(_) | Type::Dict(..) | Type::Tuple(_) | Type::VarTuple(_) => true,
+            Type::Effect(inner, _) | Type::Refinement(inner, _) | Type::Dependent(inner, _) => Self::never_index(inner),
+            Type::Union(members) => members.iter().all(Self::never_index),
+            _ => false,
+        }
+    }
+
+    /// Bind `a, b = value`, checking arity against the value's known length
+    fn check_unpack(&mut self, target: &Expr, bound: Option<LengthBound>, value_ty: &Type) {
+        let targets = match target {
+            Expr::Tuple(tuple) => &tuple.elts,
+            Expr::List(list) => &list.elts,
+            _ => return,
+        };
+        // A starred target absorbs any surplus
+        if targets.iter().any(|target| matches!(target, Expr::Starred(_))) {
+            return;
+        }
+
+        let expected = targets.len();
+        if let Some(bound) = bound {
+            let message = if bound.min > expected {
+                Some(format!("too many values to unpack (expected {})", expected))
+            } else {
+                bound.max.filter(|&max| max < expected).map(|max| {
+                    let got = if max == bound.min { max.to_string() } else { format!("at most {}", max) };
+                    format!("not enough values to unpack (expected {}, got {})", expected, got)
+                })
+            };
+            if let Some(message) = message {
+                self.report(target, codes::INVALID_SUBSCRIPT, message);
+            }
+        }
+
+        let value_ty = Self::strip_length(value_ty.clone());
+        for (i, target) in targets.iter().enumerate() {
+            let elem_ty = match &value_ty {
+                Type::Tuple(types) if types.len() == expected => types[i].clone(),
+                Type::List(elem) => (**elem).clone(),
+                _ => self.ctx.fresh_var(),
+            };
+            match target {
+                Expr::Name(name) => self.ctx.set_type(name.id.to_string(), elem_ty),
+                Expr::Tuple(_) | Expr::List(_) => self.check_unpack(target, LengthBound::from_type(&elem_ty), &elem_ty),
+                _ => {}
+            }
+        }
+    }
+
+    /// Check a function's body and bind its type and signature; `return`s in
+    /// a coroutine are checked against the declared type it wraps
+    /// Bind the module's top-level functions to the signatures their
+    /// annotations declare and its classes to their schemas and members,
+    /// so code checked before a definition can use it. Unannotated
+    /// parameters are `Any`; an unannotated return is a type variable the
+    /// function's own check solves
+    fn declare_items(&mut self, body: &[Stmt]) {
+        let first_error = self.errors.len();
+        // Annotations can use type variables declared anywhere at the top level
+        for stmt in body {
+            if let Stmt::Assign(assign) = stmt {
+                if let ([Expr::Name(target)], Some(param)) = (assign.targets.as_slice(), generics::type_var(&assign.value)) {
+                    self.type_vars.insert(target.id.to_string(), param);
+                }
+            }
+        }
+        // Calls to overloaded functions try each overload, which are only
+        // known once they're checked
+        let overloaded: HashSet<&str> = body.iter()
+            .filter_map(|stmt| match stmt {
+                Stmt::FunctionDef(func_def) => Some((func_def.name.as_str(), &func_def.decorator_list)),
+                Stmt::AsyncFunctionDef(func_def) => Some((func_def.name.as_str(), &func_def.decorator_list)),
+                _ => None,
+            })
+            .filter(|(_, decorator_list)| decorators::has_decorator(decorator_list, "overload"))
+            .map(|(name, _)| name)
+            .collect();
+        for stmt in body {
+            match stmt {
+                Stmt::FunctionDef(func_def) if !overloaded.contains(func_def.name.as_str()) => {
+                    self.declare_function(func_def, false);
+                }
+                Stmt::AsyncFunctionDef(func_def) if !overloaded.contains(func_def.name.as_str()) => {
+                    self.declare_function(&as_function_def(func_def), true);
+                }
+                Stmt::ClassDef(class_def) => self.declare_class(class_def),
+                _ => {}
+            }
+        }
+        // Annotations are reported where the definitions are checked
+        self.errors.truncate(first_error);
+    }
+
+    fn declare_function(&mut self, func_def: &StmtFunctionDef, is_async: bool) {
+        let (param_types, sig) = self.parameter_types(func_def, |_| Type::Any);
+        let is_generator = reachability::yields(&func_def.body);
+        let call_result = match &func_def.returns {
+            Some(returns) => {
+                let ret = self.type_from_annotation(returns);
+                if is_async && !is_generator { Type::awaitable(ret) } else { ret }
+            }
+            None if is_generator || self.stub => Type::Any,
+            None => {
+                let ret = self.ctx.fresh_var();
+                self.declared_returns.insert(func_def.range.start().to_usize(), ret.clone());
+                if is_async { Type::awaitable(ret) } else { ret }
+            }
+        };
+        let name = func_def.name.to_string();
+        let (func_type, sig) = match self.erases_signature(func_def) {
+            true => (Type::callable(Type::Any), None),
+            false => (self.effects.annotate_function_type(&name, Type::Function(param_types, Box::new(call_result))), Some(sig)),
+        };
+        self.ctx.set_type(name.clone(), func_type);
+        self.ctx.set_doc(name.clone(), Docstring::of(&func_def.body));
+        self.ctx.set_signature(name, sig);
+    }
+
+    fn declare_class(&mut self, class_def: &StmtClassDef) {
+        let name = class_def.name.to_string();
+        self.ctx.set_type(name.clone(), Type::ClassObject(name.clone()));
+        let schema = ClassSchema::new(name.clone()).with_doc(Docstring::of(&class_def.body));
+        self.ctx.register_class(schema.with_bases(class_bases(class_def)));
+
+        let prev_class = self.current_class.replace(name.clone());
+        let in_class_body = std::mem::replace(&mut self.in_class_body, true);
+        self.class_attributes.insert(name, HashMap::new());
+        self.declare_members(class_def);
+        self.current_class = prev_class;
+        self.in_class_body = in_class_body;
+    }
+
+    fn check_function_def(&mut self, func_def: &StmtFunctionDef, is_async: bool) {
+        if self.options.requires_annotations() {
+            self.require_annotations(func_def, is_async);
+        }
+
+        // Infer parameter types
+        let args = &func_def.args;
+        let positional: Vec<_> = args.posonlyargs.iter().chain(&args.args).collect();
+        let (param_types, sig) = self.parameter_types(func_def, |this| this.ctx.fresh_var());
+
+        // Infer return type (only check if explicitly annotated)
+        let (return_type, has_return_annotation) = if let Some(ret) = &func_def.returns {
+            (self.type_from_annotation(ret), true)
+        } else {
+            let declared = self.declared_returns.get(&func_def.range.start().to_usize()).cloned();
+            (declared.unwrap_or_else(|| self.ctx.fresh_var()), false)
+        };
+
+        // Parameters and whatever the body binds are local to the function,
+        // unless declared `global` or `nonlocal`
+        let (globals, nonlocals) = bindings::declarations(&func_def.body);
+        let mut locals = HashMap::new();
+        for (name, range) in bindings::parameter_sites(args).into_iter().chain(bindings::binding_sites(&func_def.body)) {
+            if !globals.contains(&name) && !nonlocals.contains(&name) {
+                locals.entry(name).or_insert(range.start);
+            }
+        }
+        self.enter_scope(ScopeKind::Function, locals, globals);
+
+        // Set parameters in context for function body
+        for (arg, param_ty) in positional.iter().zip(param_types.iter()) {
+            self.declare_refined(arg.def.arg.to_string(), param_ty);
+            self.ctx.set_type(arg.def.arg.to_string(), param_ty.clone());
+        }
+        for (param, param_ty) in &sig.kwonly {
+            self.declare_refined(param.name.clone(), param_ty);
+            self.ctx.set_type(param.name.clone(), param_ty.clone());
+        }
+        if let (Some(arg), Some(elem_ty)) = (&args.vararg, &sig.varargs) {
+            self.ctx.set_type(arg.arg.to_string(), Type::VarTuple(Box::new(elem_ty.clone())));
+        }
+        if let (Some(arg), Some(value_ty)) = (&args.kwarg, &sig.kwargs) {
+            self.ctx.set_type(arg.arg.to_string(), Type::Dict(Box::new(Type::Str), Box::new(value_ty.clone())));
+        }
+
+        // A generator's annotation describes the iterator it returns; its
+        // `return` value is the one `Generator[Y, S, R]` declares
+        let is_generator = reachability::yields(&func_def.body);
+        let generator_parts = match (&func_def.returns, is_generator) {
+            (Some(returns), true) => {
+                let parts = generator_parts(&return_type);
+                if parts.is_none() && return_type != Type::Any {
+                    let message = format!(
+                        "Generator function '{}' must be annotated with Generator, Iterator or Iterable, not {}",
+                        func_def.name, return_type
+                    );
+                    self.report(&**returns, codes::INVALID_ANNOTATION, message);
+                }
+                parts
+            }
+            _ => None,
+        };
+
+        // Track current function return type for validation (only if annotated)
+        let prev_return_type = self.current_function_return_type.take();
+        if has_return_annotation {
+            self.current_function_return_type = match &generator_parts {
+                Some((_, _, returned)) => Some(returned.clone()),
+                None if is_generator => None,
+                // A type guard returns whether its argument has the guarded type
+                None if type_guard(&return_type).is_some() => Some(Type::Bool),
+                None => Some(return_type.clone()),
+            };
+        }
+        let generator = is_generator.then(|| GeneratorScope {
+            yield_type: generator_parts.as_ref().map(|(yielded, _, _)| yielded.clone()),
+            send_type: generator_parts.as_ref().map_or(Type::Any, |(_, sent, _)| sent.clone()),
+            yielded: Vec::new(),
+        });
+        let prev_generator = std::mem::replace(&mut self.current_generator, generator);
+        let prev_returned = std::mem::replace(&mut self.returned, (!has_return_annotation).then(Vec::new));
+
+        // Check function body and infer effects; a stub has only its declaration
+        let qualified = match (self.in_class_body, &self.current_class) {
+            (true, Some(class_name)) => format!("{}.{}", class_name, func_def.name),
+            _ => func_def.name.to_string(),
+        };
+        let qualified = match self.binding_scopes.last() {
+            Some(outer) => format!("{}.{}", outer, qualified),
+            None => qualified,
+        };
+        self.binding_scopes.push(qualified);
+        self.record_bindings(bindings::parameter_sites(args));
+        self.record_parameter_types(args);
+        // A recursive call returns what the function does, which the body
+        // decides; until then it's the variable `return_type` is
+        let name = func_def.name.to_string();
+        let provisional = (!has_return_annotation && !is_generator && !self.stub && !self.in_class_body).then(|| {
+            let call_result = if is_async { Type::awaitable(return_type.clone()) } else { return_type.clone() };
+            let shadowed = self.ctx.type_ref(&name);
+            self.ctx.set_type(name.clone(), Type::Function(param_types.clone(), Box::new(call_result)));
+            shadowed
+        });
+        let in_class_body = std::mem::replace(&mut self.in_class_body, false);
+        if !self.stub {
+            for stmt in &func_def.body {
+                self.check_stmt(stmt);
+                self.record_bindings(bindings::binding_sites(std::slice::from_ref(stmt)));
+            }
+        }
+        self.in_class_body = in_class_body;
+        self.binding_scopes.pop();
+        match provisional {
+            Some(Some(shadowed)) => self.ctx.set_type_ref(name, shadowed),
+            Some(None) => {
+                self.ctx.remove_type(&name);
+            }
+            None => {}
+        }
+        self.leave_scope();
+
+        // Restore previous return type
+        self.current_function_return_type = prev_return_type;
+        let generator = std::mem::replace(&mut self.current_generator, prev_generator);
+        let returned = std::mem::replace(&mut self.returned, prev_returned);
+        let mut return_type = return_type;
+        if let Some(mut returned) = returned.filter(|_| !is_generator && !self.stub) {
+            if reachability::falls_through_with(&func_def.body, self) {
+                returned.push(Type::None);
+            }
+            // Recursive calls returned add nothing to what's returned
+            returned.retain(|ty| *ty != return_type);
+            let ty = if returned.is_empty() { Type::Never } else { Type::union(returned) };
+            self.inferred_returns.insert(func_def.range.start().to_usize(), ty.clone());
+            let site = func_def.start().to_usize()..func_def.end().to_usize();
+            self.constraints.add_constraint_at(Constraint::Equal(return_type, ty.clone()), Some(site));
+            return_type = ty;
+        }
+
+        if has_return_annotation && !self.stub {
+            self.check_missing_return(func_def, &return_type);
+        }
+        if let Some(declared) = effects::declared_effects(&func_def.decorator_list).filter(|_| !self.stub) {
+            self.check_declared_effects(func_def, &declared);
+        }
+
+        // Calling a generator function makes a generator, and calling a
+        // coroutine function an awaitable, unless annotated otherwise
+        let call_result = match generator {
+            Some(_) if has_return_annotation => return_type,
+            Some(scope) => {
+                let yielded = if scope.yielded.is_empty() { Type::Any } else { Type::union(scope.yielded) };
+                match is_async {
+                    true => Type::Generic("AsyncGenerator".to_string(), vec![yielded, Type::Any]),
+                    false => Type::Generic("Generator".to_string(), vec![yielded, Type::Any, return_type]),
+                }
+            }
+            None if is_async => Type::awaitable(return_type),
+            None => return_type,
+        };
+        let base_func_type = Type::Function(param_types.clone(), Box::new(call_result));
+
+        let key = match &self.current_class {
+            Some(class_name) => format!("{}.{}", class_name, func_def.name),
+            None => func_def.name.to_string(),
+        };
+
+        // Annotate with inferred effects (killer feature!)
+        let func_type = self.effects.annotate_function_type(&key, base_func_type);
+
+        // Overloads are collapsed into a union, and no single signature binds
+        // calls to them; calls try each in turn instead
+        let is_overload = decorators::has_decorator(&func_def.decorator_list, "overload");
+        let declared = self.overload_of(func_def, func_type.clone(), sig.clone());
+        if is_overload {
+            let name = func_def.name.to_string();
+            let set = self.overloads.entry(key).or_default();
+            if set.implemented {
+                *set = OverloadSet::default();
+            }
+            set.items.push(declared);
+            let first = set.items.len() == 1;
+            let func_type = match self.ctx.get_type(&name) {
+                Some(previous) if !first => Type::union(vec![previous, func_type]),
+                _ => func_type,
+            };
+            self.ctx.set_type(name.clone(), func_type);
+            self.ctx.set_doc(name.clone(), Docstring::of(&func_def.body));
+            self.ctx.set_signature(name, first.then_some(sig));
+            return;
+        }
+        match self.overloads.get_mut(&key) {
+            Some(set) if !set.implemented => {
+                set.implemented = true;
+                let overloads = set.items.clone();
+                self.check_overload_implementation(func_def, &declared, &overloads);
+            }
+            // Redefined without overloads
+            Some(_) => {
+                self.overloads.remove(&key);
+            }
+            None => {}
+        }
+
+        let (func_type, sig) = match self.erases_signature(func_def) {
+            true => (Type::callable(Type::Any), None),
+            false => (func_type, Some(sig)),
+        };
+        self.ctx.set_type(func_def.name.to_string(), func_type);
+        self.ctx.set_doc(func_def.name.to_string(), Docstring::of(&func_def.body));
+        self.ctx.set_signature(func_def.name.to_string(), sig);
+    }
+
+    /// Whether calls can't rely on `func_def`'s own signature: an unknown
+    /// decorator keeps it unless options say such decorators erase it
+    fn erases_signature(&self, func_def: &StmtFunctionDef) -> bool {
+        self.options.erases_unknown_decorators() && decorators::has_unknown_decorator(&func_def.decorator_list)
+    }
+
+    /// Types of the parameters a call can pass by position, and the
+    /// signature binding calls. A method's unannotated receiver is its class
+    /// instance, or the class object for class methods; other unannotated
+    /// parameters get what `unannotated` gives
+    fn parameter_types(&mut self, func_def: &StmtFunctionDef, mut unannotated: impl FnMut(&mut Self) -> Type) -> (Vec<Type>, FunctionSig) {
+        let args = &func_def.args;
+        let positional: Vec<_> = args.posonlyargs.iter().chain(&args.args).collect();
+        let receiver = self.receiver_type(func_def);
+        let mut param_type = |this: &mut Self, arg: &rustpython_parser::ast::Arg| match &arg.annotation {
+            Some(ann) => this.type_from_annotation(ann),
+            None => unannotated(this),
+        };
+        let param_types: Vec<Type> = positional.iter().enumerate()
+            .map(|(i, arg)| match (&receiver, &arg.def.annotation) {
+                (Some(receiver), None) if i == 0 => receiver.clone(),
+                _ => param_type(self, &arg.def),
+            })
+            .collect();
+        let kwonly: Vec<(Param, Type)> = args.kwonlyargs.iter()
+            .map(|arg| (Param::new(arg.def.arg.as_str(), arg.default.is_some()), param_type(self, &arg.def)))
+            .collect();
+        let sig = FunctionSig {
+            params: positional.iter().map(|arg| Param::new(arg.def.arg.as_str(), arg.default.is_some())).collect(),
+            posonly: args.posonlyargs.len(),
+            kwonly,
+            varargs: args.vararg.as_ref().map(|arg| param_type(self, arg)),
+            kwargs: args.kwarg.as_ref().map(|arg| param_type(self, arg)),
+        };
+        (param_types, sig)
+    }
+
+    /// Type of the receiver of a method defined directly in a class body;
+    /// static methods take none
+    fn receiver_type(&self, func_def: &StmtFunctionDef) -> Option<Type> {
+        let class_name = self.current_class.as_ref().filter(|_| self.in_class_body)?;
+        let has_decorator = |decorator: &str| decorators::has_decorator(&func_def.decorator_list, decorator);
+        if has_decorator("staticmethod") {
+            None
+        } else if has_decorator("classmethod") || matches!(func_def.name.as_str(), "__new__" | "__init_subclass__" | "__class_getitem__") {
+            Some(Type::ClassObject(class_name.clone()))
+        } else {
+            Some(Type::Class(class_name.clone()))
+        }
+    }
+
+    /// Type of the elements iterating over a value of `iterable_ty` gives
+    fn element_type(&self, iterable_ty: Type) -> Type {
+        match Self::strip_length(iterable_ty) {
+            Type::List(elem) => *elem,
+            Type::Set(elem) => *elem,
+            Type::Tuple(elems) if !elems.is_empty() => {
+                // For tuple, use union of all element types
+                Type::union(elems)
+            }
+            Type::VarTuple(elem) => *elem,
+            Type::Dict(key, _) => *key, // Iterating over dict gives keys
+            Type::ClassObject(name) if self.consts.is_enum(&name) => Type::Class(name),
+            Type::Str => Type::Str, // String iteration gives strings
+            Type::Generic(name, mut args) if matches!(name.as_str(), "Generator" | "Iterator" | "Iterable") && !args.is_empty() => {
+                args.remove(0)
+            }
+            _ => self.ctx.fresh_var(),
+        }
+    }
+
+    /// Record a value `node` yields in the generator being checked, checking
+    /// it against the declared yield type; gives what is sent in
+    fn record_yield(&mut self, node: &Expr, value_ty: Type) -> Type {
+        let Some(scope) = &mut self.current_generator else { return Type::Any };
+        scope.yielded.push(value_ty.clone());
+        let (expected, sent) = (scope.yield_type.clone(), scope.send_type.clone());
+        if let Some(expected) = expected.filter(|expected| !self.ctx.is_subtype(&value_ty, expected)) {
+            self.report(node, codes::TYPE_MISMATCH, format!("Yield type mismatch: expected {}, got {}", expected, value_ty));
+        }
+        sent
+    }
+
+    /// Check an `if`: its branches under the test's narrowing, and what
+    /// follows under what the branches that fall through leave
+    fn check_if(&mut self, if_stmt: &StmtIf) {
+        // Check the condition
+        let _cond_ty = self.infer_expr(&if_stmt.test);
+        let narrowing = self.narrow(&if_stmt.test);
+
+        // Check the if body under the narrowed types
+        let saved = self.apply_narrowing(narrowing.positive.clone());
+        for stmt in &if_stmt.body {
+            self.check_stmt(stmt);
+        }
+        let body_assigned = self.assigned_types(&if_stmt.body);
+        self.restore_narrowing(saved);
+
+        // Check elif/else clauses under the complement
+        let saved = self.apply_narrowing(narrowing.negative.clone());
+        for stmt in &if_stmt.orelse {
+            self.check_stmt(stmt);
+        }
+        let orelse_assigned = self.assigned_types(&if_stmt.orelse);
+        self.restore_narrowing(saved);
+
+        // After `if x is None: return`, the rest of the block only
+        // runs when the test failed, and vice versa
+        let body_exits = !reachability::falls_through_with(&if_stmt.body, self);
+        let orelse_exits = !if_stmt.orelse.is_empty() && !reachability::falls_through_with(&if_stmt.orelse, self);
+        match (body_exits, orelse_exits) {
+            (true, false) => {
+                self.apply_narrowing(narrowing.negative.clone());
+            }
+            (false, true) => {
+                self.apply_narrowing(narrowing.positive.clone());
+            }
+            _ => {}
+        }
+
+        // What the branches assigned outlives them
+        let mut falling_through = Vec::new();
+        if !body_exits {
+            falling_through.push((&narrowing.positive, body_assigned));
+        }
+        if !orelse_exits {
+            falling_through.push((&narrowing.negative, orelse_assigned));
+        }
+        self.join_assigned(falling_through);
+    }
+
+    /// Check each case of a `match`: capture names are bound, and a named
+    /// subject is narrowed to what the pattern matches in the case body
+    fn check_match(&mut self, match_stmt: &StmtMatch) {
+        let subject_ty = Self::strip_effects(self.infer_expr(&match_stmt.subject));
+        // Values of the subject no case has matched yet, for exhaustiveness:
+        // union members, with `bool` and enums split into their literal values
+        let mut remaining = self.conditions.literal_members(&subject_ty);
+
+        for case in &match_stmt.cases {
+            let matched = self.bind_pattern(&case.pattern, &subject_ty);
+            let narrowed = match &*match_stmt.subject {
+                Expr::Name(name) if matched != subject_ty && !matches!(matched, Type::Any | Type::Var(_)) => {
+                    HashMap::from([(name.id.to_string(), matched)])
+                }
+                _ => HashMap::new(),
+            };
+            let saved = self.apply_narrowing(narrowed);
+            let guard_saved = case.guard.as_ref().map(|guard| {
+                self.infer_expr(guard);
+                let narrowing = self.narrow(guard);
+                self.apply_narrowing(narrowing.positive)
+            });
+
+            for stmt in &case.body {
+                self.check_stmt(stmt);
+            }
+
+            if let Some(guard_saved) = guard_saved {
+                self.restore_narrowing(guard_saved);
+            }
+            self.restore_narrowing(saved);
+            if case.guard.is_none() {
+                remaining.retain(|member| !self.pattern_covers(&case.pattern, member));
+            }
+        }
+
+        if remaining.is_empty() {
+            self.exhaustive_matches.insert(match_stmt.start().to_usize());
+        }
+    }
+
+    /// Does `pattern` match every value of `member`, a value of the subject
+    /// from `literal_members`?
+    fn pattern_covers(&self, pattern: &Pattern, member: &Type) -> bool {
+        match pattern {
+            Pattern::MatchAs(pattern) => pattern.pattern.as_ref().map_or(true, |inner| self.pattern_covers(inner, member)),
+            Pattern::MatchOr(pattern) => pattern.patterns.iter().any(|alt| self.pattern_covers(alt, member)),
+            Pattern::MatchValue(pattern) => self.consts.eval(&pattern.value)
+                .is_ok_and(|value| value.matches_literal(member) == Some(true)),
+            Pattern::MatchSingleton(pattern) => match &pattern.value {
+                Constant::None => *member == Type::None,
+                Constant::Bool(value) => *member == consteval::ConstValue::Bool(*value).literal_type(),
+                _ => false,
+            },
+            Pattern::MatchClass(pattern) if pattern.patterns.is_empty() && pattern.kwd_patterns.is_empty() => {
+                let class = self.conditions.pattern_class(&pattern.cls);
+                let (kept, remaining) = self.conditions.split_by_class(member, &[class]);
+                kept.is_some() && remaining.is_none() && !matches!(member, Type::Any | Type::Var(_))
+            }
+            _ => false,
+        }
+    }
+
+    /// The members of a union `ty` that satisfy `keep`; unknown types and
+    /// types with no such member are left as they are
+    fn members_where(ty: &Type, keep: impl Fn(&Type) -> bool) -> Type {
+        let members = ConditionAnalyzer::members(ty);
+        match ty {
+            Type::Union(_) if members.iter().any(&keep) => Type::union(members.into_iter().filter(keep).collect()),
+            other => other.clone(),
+        }
+    }
+
+    /// Bind the capture names in `pattern`, matched against a value of
+    /// `subject_ty`, and return the type of the values it matches
+    fn bind_pattern(&mut self, pattern: &Pattern, subject_ty: &Type) -> Type {
+        match pattern {
+            Pattern::MatchValue(pattern) => self.infer_expr(&pattern.value),
+            Pattern::MatchSingleton(pattern) => match pattern.value {
+                Constant::None => Type::None,
+                Constant::Bool(_) => Type::Bool,
+                _ => Type::Any,
+            },
+            Pattern::MatchSequence(pattern) => {
+                // Strings never match sequence patterns
+                let subject_ty = &Self::members_where(subject_ty, |member| {
+                    matches!(member, Type::List(_) | Type::Tuple(_) | Type::VarTuple(_))
+                });
+                let starred = pattern.patterns.iter().any(|p| matches!(p, Pattern::MatchStar(_)));
+                let positions = match subject_ty {
+                    Type::Tuple(elems) if !starred && elems.len() == pattern.patterns.len() => Some(elems.clone()),
+                    _ => None,
+                };
+                let elem_ty = self.element_type(subject_ty.clone());
+                for (i, sub_pattern) in pattern.patterns.iter().enumerate() {
+                    match sub_pattern {
+                        Pattern::MatchStar(star) => {
+                            if let Some(name) = &star.name {
+                                self.ctx.set_type(name.to_string(), Type::List(Box::new(elem_ty.clone())));
+                            }
+                        }
+                        _ => {
+                            let item_ty = positions.as_ref().map_or_else(|| elem_ty.clone(), |elems| elems[i].clone());
+                            self.bind_pattern(sub_pattern, &item_ty);
+                        }
+                    }
+                }
+                subject_ty.clone()
+            }
+            Pattern::MatchMapping(pattern) => {
+                let subject_ty = &Self::members_where(subject_ty, |member| matches!(member, Type::Dict(_, _)));
+                let (key_ty, value_ty) = match subject_ty {
+                    Type::Dict(key, value) => ((**key).clone(), (**value).clone()),
+                    _ => (Type::Any, Type::Any),
+                };
+                for key in &pattern.keys {
+                    self.infer_expr(key);
+                }
+                for sub_pattern in &pattern.patterns {
+                    self.bind_pattern(sub_pattern, &value_ty);
+                }
+                if let Some(rest) = &pattern.rest {
+                    self.ctx.set_type(rest.to_string(), Type::Dict(Box::new(key_ty), Box::new(value_ty)));
+                }
+                subject_ty.clone()
+            }
+            Pattern::MatchClass(pattern) => {
+                let class = self.conditions.pattern_class(&pattern.cls);
+                let matched = match self.conditions.split_by_class(subject_ty, std::slice::from_ref(&class)) {
+                    (Some(kept), _) => kept,
+                    (None, _) => {
+                        let message = format!("Class pattern '{}' can never match a subject of type {}", class, subject_ty);
+                        self.report(&*pattern.cls, codes::TYPE_MISMATCH, message);
+                        class.clone()
+                    }
+                };
+                // Builtins match a single positional pattern against the whole value
+                for sub_pattern in &pattern.patterns {
+                    let item_ty = match (&class, pattern.patterns.len()) {
+                        (Type::Class(_) | Type::Any, _) | (_, 2..) => Type::Any,
+                        _ => matched.clone(),
+                    };
+                    self.bind_pattern(sub_pattern, &item_ty);
+                }
+                for (attr, sub_pattern) in pattern.kwd_attrs.iter().zip(&pattern.kwd_patterns) {
+                    let attr_ty = self.ctx.has_attribute(&class, attr).map_or(Type::Any, Self::strip_effects);
+                    self.bind_pattern(sub_pattern, &attr_ty);
+                }
+                matched
+            }
+            Pattern::MatchStar(_) => Type::Any,
+            Pattern::MatchAs(pattern) => {
+                let matched = match &pattern.pattern {
+                    Some(inner) => self.bind_pattern(inner, subject_ty),
+                    None => subject_ty.clone(),
+                };
+                if let Some(name) = &pattern.name {
+                    self.ctx.set_type(name.to_string(), matched.clone());
+                }
+                matched
+            }
+            Pattern::MatchOr(pattern) => {
+                let alternatives = pattern.patterns.iter().map(|alt| self.bind_pattern(alt, subject_ty)).collect();
+                Type::union(alternatives)
+            }
+        }
+    }
+
+    /// Check every block of a `try`, binding each handler's `as` name to the
+    /// exception it catches (an `ExceptionGroup` for `except*`)
+    fn check_try(&mut self, body: &[Stmt], handlers: &[ExceptHandler], orelse: &[Stmt], finalbody: &[Stmt], is_star: bool) {
+        for stmt in body {
+            self.check_stmt(stmt);
+        }
+        for ExceptHandler::ExceptHandler(handler) in handlers {
+            if let Some(type_expr) = &handler.type_ {
+                let caught = self.exception_type(type_expr);
+                if let Some(name) = &handler.name {
+                    let bound = if is_star { Type::Class("ExceptionGroup".to_string()) } else { caught };
+                    self.ctx.set_type(name.to_string(), bound);
+                }
+            }
+            for stmt in &handler.body {
+                self.check_stmt(stmt);
+            }
+        }
+        for stmt in orelse.iter().chain(finalbody) {
+            self.check_stmt(stmt);
+        }
+    }
+
+    /// Instance type of the exception(s) an `except` clause names
+    fn exception_type(&self, expr: &Expr) -> Type {
+        match expr {
+            Expr::Name(name) => match self.ctx.get_type(&name.id) {
+                Some(Type::ClassObject(class_name)) => Type::Class(class_name),
+                Some(Type::Any | Type::Var(_)) => Type::Any,
+                _ => Type::Class(name.id.to_string()),
+            },
+            Expr::Attribute(attr) => Type::Class(attr.attr.to_string()),
+            Expr::Tuple(tuple) => Type::union(tuple.elts.iter().map(|elt| self.exception_type(elt)).collect()),
+            _ => Type::Any,
+        }
+    }
+
+    /// Check a `with` item's context manager and bind its `as` target to what
+    /// `__enter__` (`__aenter__` for `async with`) returns
+    fn check_with_item(&mut self, item: &WithItem, is_async: bool) {
+        let manager_ty = Self::strip_effects(self.infer_expr(&item.context_expr));
+        let (enter, protocol, kind) = if is_async {
+            let protocol = ProtocolLibrary::async_context_manager(Type::Any);
+            ("__aenter__", protocol, "asynchronous context manager")
+        } else {
+            ("__enter__", ProtocolLibrary::context_manager(Type::Any), "context manager")
+        };
+
+        let missing = ProtocolChecker::missing_methods(&manager_ty, &protocol, &self.ctx);
+        if self.knows_members(&manager_ty) && !missing.is_empty() {
+            let message = format!(
+                "Type '{}' does not support the {} protocol (missing {})",
+                manager_ty, kind, missing.join(", ")
+            );
+            self.report(&item.context_expr, codes::INVALID_ATTRIBUTE, message);
+        }
+
+        let Some(target) = &item.optional_vars else { return };
+        let resource_ty = match self.ctx.has_attribute(&manager_ty, enter).map(Self::strip_effects) {
+            Some(Type::Function(_, ret)) if is_async => ret.awaited().unwrap_or(*ret),
+            Some(Type::Function(_, ret)) => *ret,
+            // Nothing is known about what an unknown `__enter__` returns
+            _ => Type::Any,
+        };
+        match &**target {
+            Expr::Name(name) => self.ctx.set_type(name.id.to_string(), resource_ty),
+            Expr::Tuple(_) | Expr::List(_) => self.check_unpack(target, LengthBound::from_type(&resource_ty), &resource_ty),
+            _ => {}
+        }
+    }
+
+    /// Only types whose members are all known can be said to lack some
+    fn knows_members(&self, ty: &Type) -> bool {
+        match ty {
+            Type::Class(name) => self.ctx.knows_hierarchy(name),
+            Type::Int | Type::Float | Type::Bool | Type::Str | Type::Bytes | Type::None
+                | Type::List(_) | Type::Tuple(_) | Type::Dict(_, _) | Type::Set(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Type `await value` produces, reporting values that can't be awaited
+    fn infer_await(&mut self, value: &Expr) -> Type {
+        let awaitable_ty = Self::strip_effects(self.infer_expr(value));
+        if let Some(result) = awaitable_ty.awaited() {
+            return result;
+        }
+
+        let protocol = ProtocolLibrary::awaitable(Type::Any);
+        if self.knows_members(&awaitable_ty) && !ProtocolChecker::missing_methods(&awaitable_ty, &protocol, &self.ctx).is_empty() {
+            self.report(value, codes::INVALID_ATTRIBUTE, format!("Type '{}' is not awaitable (missing __await__)", awaitable_ty));
+            return Type::Any;
+        }
+        // `__await__` returns a generator whose return value is the result
+        match self.ctx.has_attribute(&awaitable_ty, "__await__").map(Self::strip_effects) {
+            Some(Type::Function(_, ret)) => match *ret {
+                Type::Generic(name, args) if name == "Generator" && args.len() == 3 => args[2].clone(),
+                Type::Generic(name, args) if name == "Iterator" && args.len() == 1 => args[0].clone(),
+                _ => Type::Any,
+            },
+            _ => Type::Any,
+        }
+    }
+
+    /// `name` and `value` of an enum member: `Color.RED.value` has the type
+    /// assigned to `RED`, and `value` of any `Color` the union of them all
+    fn enum_member_attribute(&self, value: &Expr, value_ty: &Type, attr: &str) -> Option<Type> {
+        let (class, member) = match (value, value_ty) {
+            (Expr::Attribute(member), Type::Class(class)) => match &*member.value {
+                Expr::Name(name) if name.id.as_str() == class && self.consts.is_enum_member(class, &member.attr) => {
+                    (class, Some(member.attr.to_string()))
+                }
+                _ => (class, None),
+            },
+            (_, Type::Class(class)) => (class, None),
+            // Narrowed to a single member by `==` or `is`
+            (_, Type::Dependent(inner, DependentConstraint::ValueEq(path))) => match &**inner {
+                Type::Class(class) => (class, path.strip_prefix(&format!("{}.", class)).map(str::to_string)),
+                _ => return None,
+            },
+            _ => return None,
+        };
+        if !self.consts.is_enum(class) {
+            return None;
+        }
+        match attr {
+            "name" => Some(Type::Str),
+            "value" => match member {
+                Some(member) => Some(self.enum_values.get(&format!("{}.{}", class, member)).cloned().unwrap_or(Type::Any)),
+                None => {
+                    let values: Vec<Type> = self.consts.enum_members(class).unwrap_or_default().iter()
+                        .filter_map(|member| self.enum_values.get(&member.to_string()).cloned())
+                        .collect();
+                    Some(if values.is_empty() { Type::Any } else { Type::union(values) })
+                }
+            },
+            _ => None,
+        }
+    }
+
+    /// Resolve `value.attr`, reporting unknown attributes with suggestions
+    fn infer_attribute(&mut self, attr_expr: &ExprAttribute) -> Type {
+        if let Some(module) = self.module_of(&attr_expr.value) {
+            return module.types.get(attr_expr.attr.as_str()).cloned().unwrap_or_else(|| self.ctx.fresh_var());
+        }
+        // Narrowed by a test like `self.size is not None`
+        if !self.narrowed_paths.is_empty() {
+            let path = narrowing::target_path(&attr_expr.value).map(|value| format!("{}.{}", value, attr_expr.attr));
+            if let Some(ty) = path.filter(|path| self.narrowed_paths.contains(path)).and_then(|path| self.ctx.get_type(&path)) {
+                return ty;
+            }
+        }
+
+        let value_ty = self.infer_expr(&attr_expr.value);
+        let value_ty = self.require_not_none(&attr_expr.value, value_ty, || format!("attribute '{}'", attr_expr.attr));
+
+        // Mutating a narrowed list invalidates what we knew about its length
+        if let (Expr::Name(name), Type::Dependent(inner, _)) = (&*attr_expr.value, &value_ty) {
+            if matches!(attr_expr.attr.as_str(), "append" | "extend" | "insert" | "pop" | "remove" | "clear") {
+                self.ctx.set_type(name.id.to_string(), (**inner).clone());
+            }
+        }
+
+        if value_ty == Type::Any {
+            return Type::Any;
+        }
+
+        // Instance attributes assigned through `self`; methods and properties
+        // are on the class schema
+        match &value_ty {
+            Type::Class(class_name) => {
+                if let Some(attr_ty) = self.instance_attribute(class_name, &attr_expr.attr) {
+                    return attr_ty;
+                }
+            }
+            Type::Generic(class_name, args) => {
+                if let Some(attr_ty) = self.instance_attribute(class_name, &attr_expr.attr) {
+                    let params = self.ctx.get_class(class_name).map(|schema| schema.type_params.clone()).unwrap_or_default();
+                    return attr_ty.substitute_params(&params, args);
+                }
+            }
+            _ => {}
+        }
+
+        if let Some(ty) = self.enum_member_attribute(&attr_expr.value, &value_ty, &attr_expr.attr) {
+            return ty;
+        }
+
+        if let Some(used) = self.ctx.member_override(&value_ty, &attr_expr.attr) {
+            if !self.override_uses.contains(&used) {
+                self.override_uses.push(used);
+            }
+        }
+
+        // Otherwise, lookup attribute from context
+        self.ctx.has_attribute(&value_ty, &attr_expr.attr)
+            .unwrap_or_else(|| {
+                // Don't generate error for class types - attributes might be set
+                // dynamically - except on enums, whose members are fixed, and
+                // plain `object`s
+                let closed = (matches!(&value_ty, Type::ClassObject(name) if self.consts.is_enum(name))
+                    && !attr_expr.attr.starts_with('_'))
+                    || matches!(&value_ty, Type::Class(name) if name == "object");
+                // Within its own methods, a class declares everything `self` has
+                let receiver = match (&*attr_expr.value, &value_ty) {
+                    (Expr::Name(name), Type::Class(class_name))
+                        if name.id.as_str() == "self" && self.current_class.as_ref() == Some(class_name) =>
+                    {
+                        self.closed_attributes(class_name)
+                    }
+                    _ => None,
+                };
+                if matches!(value_ty, Type::Class(_) | Type::ClassObject(_)) && !closed && receiver.is_none() {
+                    self.ctx.fresh_var()
+                } else {
+                    // Generate error with suggestions for non-class types
+                    let available = receiver.unwrap_or_else(|| self.ctx.get_attributes(&value_ty));
+                    let similar = crate::compiler::errors::find_similar_names(&attr_expr.attr, &available, 2);
+
+                    let mut msg = format!(
+                        "Type '{}' has no attribute '{}'",
+                        value_ty, attr_expr.attr
+                    );
+                    if !similar.is_empty() {
+                        msg.push_str(&format!(". Did you mean: {}?", similar.join(", ")));
+                    }
+
+                    let range = attr_expr.start().to_usize()..attr_expr.end().to_usize();
+                    self.report_suggesting(range, codes::INVALID_ATTRIBUTE, msg, similar);
+
+                    self.ctx.fresh_var()
+                }
+            })
+    }
+
+    fn is_compatible(&self, actual: &Type, expected: &Type) -> bool {
+        // Check if actual type is compatible with expected type
+        match (actual, expected) {
+            // Exact matches
+            (Type::Int, Type::Int) => true,
+            (Type::Float, Type::Float) => true,
+            (Type::Str, Type::Str) => true,
+            (Type::Bool, Type::Bool) => true,
+            (Type::Bytes, Type::Bytes) => true,
+            (Type::None, Type::None) => true,
+            (Type::Any, _) | (_, Type::Any) => true,
+            (Type::Dependent(..), Type::Dependent(..)) if actual == expected => true,
+
+            // A conditional is compared as the branch it picks, and one still
+            // undecided by both of its branches
+            (Type::Conditional { .. }, _) | (_, Type::Conditional { .. }) if actual == expected => true,
+            (Type::Conditional { .. }, _) | (_, Type::Conditional { .. }) => {
+                let (actual, expected) = (actual.reduce_conditionals(&*self.ctx), expected.reduce_conditionals(&*self.ctx));
+                match (&actual, &expected) {
+                    (Type::Conditional { then_type, else_type, .. }, _) => {
+                        self.is_compatible(then_type, &expected) && self.is_compatible(else_type, &expected)
+                    }
+                    (_, Type::Conditional { then_type, else_type, .. }) => {
+                        self.is_compatible(&actual, then_type) && self.is_compatible(&actual, else_type)
+                    }
+                    _ => self.is_compatible(&actual, &expected),
+                }
+            }
+
+            // Effects are compared only when both sides declare them
+            (Type::Effect(a, ea), Type::Effect(b, eb)) => ea.is_subset(eb) && self.is_compatible(a, b),
+            (Type::Effect(a, _), b) => self.is_compatible(a, b),
+            (a, Type::Effect(b, _)) => self.is_compatible(a, b),
+            (_, Type::Class(name)) if name == "object" => true,
+
+            // Int is compatible with Float (subtyping)
+            (Type::Int, Type::Float) => true,
+
+            // Collection types; lists are mutable, so their elements are invariant
+            (Type::List(a), Type::List(b)) => self.is_equivalent(a, b),
+            (Type::Set(a), Type::Set(b)) => self.is_compatible(a, b),
+            (Type::Dict(ka, va), Type::Dict(kb, vb)) => {
+                self.is_compatible(ka, kb) && self.is_compatible(va, vb)
+            }
+            (Type::VarTuple(a), Type::VarTuple(b)) => self.is_compatible(a, b),
+            (Type::Tuple(ta), Type::VarTuple(b)) => ta.iter().all(|a| self.is_compatible(a, b)),
+            (Type::Tuple(ta), Type::Tuple(tb)) => {
+                ta.len() == tb.len() &&
+                ta.iter().zip(tb.iter()).all(|(a, b)| self.is_compatible(a, b))
+            }
+
+            // Function types: parameters are contravariant, and those of
+            // `Callable[..., R]` fit any others
+            (Type::Function(pa, ra), Type::Function(pb, rb)) => {
+                let params_fit = Type::takes_any_args(pa) || Type::takes_any_args(pb) || (
+                    pa.len() == pb.len() && pa.iter().zip(pb.iter()).all(|(a, b)| self.is_compatible(b, a))
+                );
+                params_fit && self.is_compatible(ra, rb)
+            }
+
+            // Recursive aliases fit what one level of their body does
+            (Type::Recursive(..), _) if actual == expected => true,
+            (Type::Recursive(..), Type::Recursive(..)) => self.ctx.is_subtype(actual, expected),
+            (Type::Recursive(..), expected) => self.is_compatible(&actual.unfold(), expected),
+            (actual, Type::Recursive(..)) => self.is_compatible(actual, &expected.unfold()),
+
+            // A union fits where each of its members does
+            (Type::Union(actual_types), _) => actual_types.iter().all(|t| self.is_compatible(t, expected)),
+
+            // Union types - actual must be one of the expected union members
+            (actual, Type::Union(expected_types)) => {
+                expected_types.iter().any(|t| self.is_compatible(actual, t))
+            }
+
+            // Class types: a subclass fits wherever its bases are expected,
+            // and any class with a protocol's members where the protocol is
+            (Type::Class(a), Type::Class(b)) => self.ctx.is_subclass(a, b) || self.ctx.satisfies_protocol(a, b),
+            (Type::ClassObject(a), Type::ClassObject(b)) => self.ctx.is_subclass(a, b),
+
+            // Generic types: arguments relate the way their parameter varies,
+            // and a generic class used bare has unknown arguments
+            (Type::Generic(na, ta), Type::Generic(nb, tb)) => {
+                na == nb && ta.len() == tb.len() &&
+                ta.iter().zip(tb.iter()).enumerate().all(|(i, (a, b))| match self.ctx.variance(na, i) {
+                    Variance::Covariant => self.is_compatible(a, b),
+                    Variance::Contravariant => self.is_compatible(b, a),
+                    Variance::Invariant => self.is_equivalent(a, b),
+                    Variance::Bivariant => true,
+                })
+            }
+            (Type::Class(a), Type::Generic(b, _)) | (Type::Generic(a, _), Type::Class(b)) => self.ctx.is_subclass(a, b),
+
+            // Type variables are always compatible (will be resolved by constraint solver)
+            (Type::Var(_), _) | (_, Type::Var(_)) => true,
+
+            // Refinement annotations accept their base type; constant values
+            // are checked exactly by `constant_fits`. A literal only accepts
+            // itself, or a value narrowed to it
+            (actual, Type::Refinement(inner, _)) => self.is_compatible(actual, inner),
+            (Type::Refinement(inner, _), expected) => self.is_compatible(inner, expected),
+            (Type::Dependent(inner, DependentConstraint::ValueEq(_)), expected) => self.is_compatible(inner, expected),
+
+            // Default: incompatible
+            _ => false,
+        }
+    }
+
+    /// `lambda` as a function type. Its parameters take the types of
+    /// `expected` when it accepts that many arguments, and are left to
+    /// inference otherwise
+    fn infer_lambda(&mut self, lambda: &ExprLambda, expected: Option<&[Type]>) -> Type {
+        let args = &lambda.args;
+        let names: Vec<String> = args.posonlyargs.iter().chain(&args.args).map(|arg| arg.def.arg.to_string()).collect();
+        let required = args.posonlyargs.iter().chain(&args.args).filter(|arg| arg.default.is_none()).count();
+        let accepts = |n: usize| n >= required && (n <= names.len() || args.vararg.is_some());
+        let params: Vec<Type> = match expected {
+            Some(expected) if accepts(expected.len()) => expected.to_vec(),
+            _ => names.iter().map(|_| self.ctx.fresh_var()).collect(),
+        };
+
+        let bindings = names.iter().enumerate()
+            .map(|(i, name)| (name.clone(), params.get(i).cloned().unwrap_or_else(|| self.ctx.fresh_var())))
+            .collect();
+        let saved = self.apply_narrowing(bindings);
+        let ret = self.infer_expr(&lambda.body);
+        self.restore_narrowing(saved);
+        Type::Function(params, Box::new(ret))
+    }
+
+    /// Whether values of either type fit wherever the other is expected, as
+    /// the arguments of an invariant parameter must
+    fn is_equivalent(&self, a: &Type, b: &Type) -> bool {
+        a == b || (self.is_compatible(a, b) && self.is_compatible(b, a))
+    }
+
+    /// Whether the constant `value` satisfies a Literal or refinement type;
+    /// `None` when `expected` is neither
+    fn constant_fits(&self, value: &consteval::ConstValue, expected: &Type) -> Option<bool> {
+        if let Some(fits) = value.matches_literal(expected) {
+            return Some(fits);
+        }
+        match (value, expected) {
+            (consteval::ConstValue::Int(n), Type::Refinement(_, predicate)) => {
+                Some(self.refinements.validate(&serde_json::json!(n), predicate))
+            }
+            _ => None,
+        }
+    }
+
+    /// The ints a refined `int` may hold; `None` for any other type
+    fn bounds(ty: &Type) -> Option<Interval> {
+        match ty {
+            Type::Refinement(base, predicate) if **base == Type::Int => Interval::of(predicate),
+            _ => None,
+        }
+    }
+
+    /// The ints `expr` of type `ty` may hold: just its value when it's a
+    /// constant, the bounds of its refinement otherwise
+    fn interval_of(&self, expr: &Expr, ty: &Type) -> Option<Interval> {
+        match self.consts.eval(expr) {
+            Ok(consteval::ConstValue::Int(n)) => Some(Interval::exact(n)),
+            _ => Self::bounds(ty),
+        }
+    }
+
+    /// Check ints `actual` may hold against the bounds `expected` puts on
+    /// them, reporting a violation when some escape. `None` when either is
+    /// unknown, leaving the value to the usual type checks
+    fn check_bounds(&mut self, node: &impl Ranged, actual: Option<Interval>, expected: &Type) -> Option<bool> {
+        let allowed = Self::bounds(expected)?;
+        let actual = actual?;
+        let fits = allowed.contains(&actual);
+        if !fits {
+            let Type::Refinement(_, predicate) = expected else { unreachable!() };
+            let kind = ErrorKind::ConstraintViolation { constraint: predicate.to_string(), value: actual.to_string() };
+            self.report(node, kind.code(), kind.to_string());
+        }
+        Some(fits)
+    }
+
+    /// Remember the refinement `name` is declared with, or that it has none
+    fn declare_refined(&mut self, name: String, ty: &Type) {
+        match ty {
+            Type::Refinement(..) => {
+                self.refined.insert(name, ty.clone());
+            }
+            _ => {
+                self.refined.remove(&name);
+            }
+        }
+    }
+
+    /// Fold a constant an annotation requires, reporting why it isn't one
+    fn require_constant(&mut self, expr: &Expr, context: &str) -> Option<consteval::ConstValue> {
+        match self.consts.eval(expr) {
+            Ok(value) => Some(value),
+            Err(not_constant) => {
+                self.report(expr, codes::INVALID_ANNOTATION, format!("{} must be a constant expression: {}", context, not_constant));
+                None
+            }
+        }
+    }
+
+    fn type_from_annotation(&mut self, expr: &Expr) -> Type {
+        match expr {
+            Expr::Name(name_expr) => match name_expr.id.as_str() {
+                "int" => Type::Int,
+                "float" => Type::Float,
+                "str" => Type::Str,
+                "bool" => Type::Bool,
+                "bytes" => Type::Bytes,
+                "None" => Type::None,
+                "Any" => Type::Any,
+                "Never" | "NoReturn" => Type::Never,
+                "Callable" => Type::callable(Type::Any),
+                // Check for common refinement types
+                "Positive" => RefinementAnalyzer::positive_int(),
+                "Negative" => RefinementAnalyzer::negative_int(),
+                "NonEmpty" => RefinementAnalyzer::non_empty_str(),
+                name if self.type_vars.contains_key(name) => self.type_var_annotation(name),
+                name if self.aliases.contains_key(name) => self.alias_type(name),
+                _ => Type::Class(name_expr.id.to_string()),
+            },
+
+            Expr::Subscript(subscript) => {
+                if let Expr::Name(name_expr) = &*subscript.value {
+                    match name_expr.id.as_str() {
+                        "list" | "List" => Type::List(Box::new(self.type_from_annotation(&subscript.slice))),
+                        "set" | "Set" => Type::Set(Box::new(self.type_from_annotation(&subscript.slice))),
+                        "tuple" | "Tuple" => {
+                            // Handle tuple type annotations
+                            if let Expr::Tuple(tuple_expr) = &*subscript.slice {
+                                // tuple[T, ...]: any length
+                                if let [elem, Expr::Constant(ellipsis)] = tuple_expr.elts.as_slice() {
+                                    if matches!(ellipsis.value, Constant::Ellipsis) {
+                                        return Type::VarTuple(Box::new(self.type_from_annotation(elem)));
+                                    }
+                                }
+                                // Empty tuple: tuple[()] or tuple with elements
+                                if tuple_expr.elts.is_empty() {
+                                    Type::Tuple(vec![])
+                                } else {
+                                    let types = tuple_expr.elts.iter()
+                                        .map(|e| self.type_from_annotation(e))
+                                        .collect();
+                                    Type::Tuple(types)
+                                }
+                            } else {
+                                // Single element tuple or error - treat as single element
+                                Type::Tuple(vec![self.type_from_annotation(&subscript.slice)])
+                            }
+                        }
+                        "dict" | "Dict" => {
+                            if let Expr::Tuple(tuple_expr) = &*subscript.slice {
+                                if tuple_expr.elts.len() == 2 {
+                                    return Type::Dict(
+                                        Box::new(self.type_from_annotation(&tuple_expr.elts[0])),
+                                        Box::new(self.type_from_annotation(&tuple_expr.elts[1])),
+                                    );
+                                }
+                            }
+                            Type::Dict(Box::new(Type::Any), Box::new(Type::Any))
+                        }
+                        "Union" => {
+                            // Handle Union[T1, T2, ...] from typing
+                            if let Expr::Tuple(tuple_expr) = &*subscript.slice {
+                                let types = tuple_expr.elts.iter()
+                                    .map(|e| self.type_from_annotation(e))
+                                    .collect();
+                                Type::Union(types)
+                            } else {
+                                // Single type in Union
+                                Type::Union(vec![self.type_from_annotation(&subscript.slice)])
+                            }
+                        }
+                        "Literal" => {
+                            let members: Vec<&Expr> = match &*subscript.slice {
+                                Expr::Tuple(tuple_expr) => tuple_expr.elts.iter().collect(),
+                                member => vec![member],
+                            };
+                            let types: Vec<Type> = members.into_iter()
+                                .map(|member| {
+                                    self.require_constant(member, "Literal[...] value")
+                                        .map_or(Type::Any, |value| value.literal_type())
+                                })
+                                .collect();
+                            match <[Type; 1]>::try_from(types) {
+                                Ok([single]) => single,
+                                Err(types) => Type::Union(types),
+                            }
+                        }
+                        "Final" | "Required" | "NotRequired" => self.type_from_annotation(&subscript.slice),
+                        "Optional" => {
+                            // Optional[T] is Union[T, None]
+                            let inner_type = self.type_from_annotation(&subscript.slice);
+                            Type::Union(vec![inner_type, Type::None])
+                        }
+                        "Callable" => {
+                            // Callable[[P1, P2], R] or Callable[..., R]; other forms stay generic
+                            if let Expr::Tuple(tuple_expr) = &*subscript.slice {
+                                match tuple_expr.elts.as_slice() {
+                                    [Expr::List(params), ret] => {
+                                        let param_types = params.elts.iter()
+                                            .map(|e| self.type_from_annotation(e))
+                                            .collect();
+                                        return Type::Function(param_types, Box::new(self.type_from_annotation(ret)));
+                                    }
+                                    [Expr::Constant(ellipsis), ret] if matches!(ellipsis.value, Constant::Ellipsis) => {
+                                        return Type::callable(self.type_from_annotation(ret));
+                                    }
+                                    _ => {}
+                                }
+                            }
+                            Type::Generic(name_expr.id.to_string(), vec![self.type_from_annotation(&subscript.slice)])
+                        }
+                        // Advanced type annotations
+                        // `EffectType[T, "io", ...]` is a `T` with at most the effects named,
+                        // and `EffectType[T]` a pure one
+                        "EffectType" => {
+                            let (base, names) = match &*subscript.slice {
+                                Expr::Tuple(tuple_expr) if !tuple_expr.elts.is_empty() => {
+                                    (&tuple_expr.elts[0], &tuple_expr.elts[1..])
+                                }
+                                base => (base, &[][..]),
+                            };
+                            let effects = names.iter()
+                                .filter_map(|name| match name {
+                                    Expr::Constant(c) => match &c.value {
+                                        Constant::Str(name) => Some(Effect::from_name(name)),
+                                        _ => None,
+                                    },
+                                    _ => None,
+                                })
+                                .fold(EffectSet::pure(), |acc, effect| acc.union(EffectSet::single(effect)));
+                            Type::Effect(Box::new(self.type_from_annotation(base)), effects)
+                        }
+                        // `Conditional[T, U, Then, Else]` is `Then` where `T` extends `U`
+                        // and `Else` elsewhere, decided once the types are known
+                        "Conditional" => {
+                            let args = match &*subscript.slice {
+                                Expr::Tuple(tuple_expr) => tuple_expr.elts.as_slice(),
+                                slice => std::slice::from_ref(slice),
+                            };
+                            match args {
+                                [checked, bound, then_type, else_type] => {
+                                    let condition = TypeCondition::Extends(self.type_from_annotation(checked), self.type_from_annotation(bound));
+                                    let conditional = Type::Conditional {
+                                        condition: Box::new(condition),
+                                        then_type: Box::new(self.type_from_annotation(then_type)),
+                                        else_type: Box::new(self.type_from_annotation(else_type)),
+                                    };
+                                    conditional.reduce_conditionals(&*self.ctx)
+                                }
+                                _ => {
+                                    let message = format!(
+                                        "Conditional[...] takes a type, the type it must extend, and the types when it does and doesn't; got {} arguments",
+                                        args.len()
+                                    );
+                                    self.report(&*subscript.slice, codes::INVALID_ANNOTATION, message);
+                                    Type::Any
+                                }
+                            }
+                        }
+                        "RefinementType" => {
+                            // Parse refinement type annotation
+                            self.type_from_annotation(&subscript.slice)
+                        }
+                        "RecursiveType" => {
+                            // Handle recursive type annotation
+                            if let Expr::Constant(c) = &*subscript.slice {
+                                if let Constant::Str(name) = &c.value {
+                                    // Register as recursive type placeholder
+                                    Type::Class(name.to_string())
+                                } else {
+                                    Type::Any
+                                }
+                            } else {
+                                Type::Any
+                            }
+                        }
+                        _ => {
+                            let args = match &*subscript.slice {
+                                Expr::Tuple(tuple_expr) => tuple_expr.elts.iter().map(|e| self.type_from_annotation(e)).collect(),
+                                arg => vec![self.type_from_annotation(arg)],
+                            };
+                            Type::Generic(name_expr.id.to_string(), args)
+                        }
+                    }
+                } else {
+                    Type::Any
+                }
+            }
+
+            Expr::BinOp(binop) => {
+                if matches!(binop.op, Operator::BitOr) {
+                    let left_ty = self.type_from_annotation(&binop.left);
+                    let right_ty = self.type_from_annotation(&binop.right);
+                    Type::Union(vec![left_ty, right_ty])
+                } else {
+                    Type::Any
+                }
+            }
+
+            // `str | None`
+            Expr::Constant(constant) if matches!(constant.value, Constant::None) => Type::None,
+
+            // A quoted forward reference
+            Expr::Constant(constant) => match &constant.value {
+                Constant::Str(annotation) => self.forward_reference(constant, annotation),
+                _ => Type::Any,
+            },
+
+            Expr::Call(call) => {
+                // Handle type constructor calls like Bounded(0, 100)
+                if let Expr::Name(name) = &*call.func {
+                    match name.id.as_str() {
+                        "Bounded" => {
                             if call.args.len() == 2 {
-                                if let (Expr::Constant(ExprConstant { value: Constant::Int(min), .. }),
-                                        Expr::Constant(ExprConstant { value: Constant::Int(max), .. })) =
-                                    (&call.args[0], &call.args[1]) {
-                                    // Convert BigInt to i64
-                                    if let (Some(min_i64), Some(max_i64)) = (min.to_i64(), max.to_i64()) {
-                                        return RefinementAnalyzer::bounded_int(min_i64, max_i64);
+                                let min = self.require_constant(&call.args[0], "Bounded() lower bound");
+                                let max = self.require_constant(&call.args[1], "Bounded() upper bound");
+                                match (min, max) {
+                                    (Some(consteval::ConstValue::Int(min)), Some(consteval::ConstValue::Int(max))) => {
+                                        return RefinementAnalyzer::bounded_int(min, max);
+                                    }
+                                    (Some(min), Some(max)) => {
+                                        self.report(call, codes::INVALID_ANNOTATION, format!("Bounded() bounds must be integers, got {} and {}", min, max));
                                     }
+                                    _ => {}
                                 }
                             }
-                            Type::Int
+                            Type::Int
+                        }
+                        "effect" | "refine" | "dependent" | "newtype" | "recursive" => {
+                            // These are constructor calls; parse the result
+                            Type::Any
+                        }
+                        _ => Type::Any,
+                    }
+                } else {
+                    Type::Any
+                }
+            }
+
+            _ => Type::Any,
+        }
+    }
+
+    /// Type of the annotation quoted in a string, parsed where it stands so
+    /// errors inside point into the string
+    fn forward_reference(&mut self, constant: &ExprConstant, annotation: &str) -> Type {
+        let start = constant.start().to_usize() + 1;
+        match crate::compiler::frontend::parser::parse_expression_at(annotation, start) {
+            Ok(expr) => self.type_from_annotation(&expr),
+            Err(_) => {
+                self.report(constant, codes::INVALID_ANNOTATION, format!("Invalid forward reference '{}'", annotation));
+                Type::Any
+            }
+        }
+    }
+
+    /// Name of the type alias `stmt` defines, if it's one of the module's
+    fn alias_defined_by(&self, stmt: &Stmt) -> Option<String> {
+        if !matches!(stmt, Stmt::Assign(_) | Stmt::AnnAssign(_) | Stmt::TypeAlias(_)) {
+            return None;
+        }
+        let start = stmt.start().to_usize();
+        self.aliases.iter().find(|(_, alias)| alias.definition == start).map(|(name, _)| name.clone())
+    }
+
+    /// Resolve the alias `name` where it's defined, reporting what's wrong
+    /// with its value, and bind the name. Only an alias of a class can be
+    /// used as a value the way the class can
+    fn define_alias(&mut self, name: &str) {
+        let Some(alias) = self.aliases.get(name) else { return };
+        let value = alias.value.clone();
+        let ty = self.resolve_alias(name, &value);
+        if let Some(alias) = self.aliases.get_mut(name) {
+            alias.resolved = Some(ty);
+        }
+
+        let ty = match value {
+            Expr::Name(_) => self.infer_expr(&value),
+            _ => Type::Any,
+        };
+        self.ctx.set_type(name.to_string(), ty);
+    }
+
+    /// Type the alias `name` stands for where an annotation uses it; what's
+    /// wrong with its value is reported where it's defined instead
+    fn alias_type(&mut self, name: &str) -> Type {
+        if self.resolving_aliases.iter().any(|resolving| resolving == name) {
+            self.recursive_aliases.insert(name.to_string());
+            return Type::Class(name.to_string());
+        }
+        let Some(alias) = self.aliases.get(name) else { return Type::Any };
+        if let Some(ty) = &alias.resolved {
+            return ty.clone();
+        }
+        let value = alias.value.clone();
+
+        let first_error = self.errors.len();
+        let ty = self.resolve_alias(name, &value);
+        self.errors.truncate(first_error);
+        // Resolved inside another alias, it may still refer to that one
+        if self.resolving_aliases.is_empty() {
+            if let Some(alias) = self.aliases.get_mut(name) {
+                alias.resolved = Some(ty.clone());
+            }
+        }
+        ty
+    }
+
+    /// Type of the alias `name` with `value`; one referring to itself
+    /// becomes a `Type::Recursive`
+    fn resolve_alias(&mut self, name: &str, value: &Expr) -> Type {
+        self.resolving_aliases.push(name.to_string());
+        let body = self.type_from_annotation(value);
+        self.resolving_aliases.pop();
+        if !self.recursive_aliases.remove(name) {
+            return body;
+        }
+        if aliases::is_unguarded(&body, name) {
+            let message = format!("Type alias '{}' refers to itself outside of a container", name);
+            self.report(value, codes::INVALID_ANNOTATION, message);
+            return Type::Any;
+        }
+        Type::Recursive(name.to_string(), Box::new(body))
+    }
+
+    /// Derive narrowings from a condition, reporting malformed class arguments
+    fn narrow(&mut self, test: &Expr) -> Narrowing {
+        let attributes: HashMap<String, Type> = narrowing::attribute_targets(test).into_iter()
+            .filter_map(|target| Some((narrowing::target_path(target)?, self.path_type(target)?)))
+            .collect();
+        let narrowing = self.timed_rule("narrowing", |this| this.conditions.analyze_with(test, &attributes));
+        for message in self.conditions.take_errors() {
+            self.report(test, codes::INVALID_ANNOTATION, message);
+        }
+        narrowing
+    }
+
+    /// Bind narrowed types, returning the previous bindings for restoration
+    fn apply_narrowing(&mut self, types: HashMap<String, Type>) -> Vec<(String, Option<TypeRef>)> {
+        types.into_iter()
+            .map(|(name, ty)| {
+                let previous = self.ctx.type_ref(&name);
+                if name.contains('.') {
+                    self.narrowed_paths.insert(name.clone());
+                } else if let Some(handle) = previous {
+                    self.narrowed_from.entry(name.clone()).or_insert(handle);
+                }
+                self.ctx.set_type(name.clone(), ty);
+                (name, previous)
+            })
+            .collect()
+    }
+
+    fn restore_narrowing(&mut self, saved: Vec<(String, Option<TypeRef>)>) {
+        for (name, previous) in saved {
+            match previous {
+                Some(handle) => {
+                    if self.narrowed_from.get(&name) == Some(&handle) {
+                        self.narrowed_from.remove(&name);
+                    }
+                    self.ctx.set_type_ref(name, handle);
+                }
+                None => {
+                    self.narrowed_paths.remove(&name);
+                    self.narrowed_from.remove(&name);
+                    self.ctx.remove_type(&name);
+                }
+            }
+        }
+    }
+
+    /// Types of the names `body` assigns, as it left them
+    fn assigned_types(&self, body: &[Stmt]) -> AssignedTypes {
+        bindings::binding_sites(body).into_iter()
+            .map(|(name, _)| {
+                let handle = self.ctx.type_ref(&name);
+                (name, handle)
+            })
+            .collect()
+    }
+
+    /// Bind each name a branch that falls through assigned to the join of
+    /// what every such branch leaves it: the type it assigned, or else the
+    /// type the branch narrowed the name to or found it with
+    fn join_assigned(&mut self, branches: Vec<(&HashMap<String, Type>, AssignedTypes)>) {
+        let names: BTreeSet<String> = branches.iter().flat_map(|(_, assigned)| assigned.keys().cloned()).collect();
+        // Refined ints are judged by their declared bounds instead
+        for name in names.into_iter().filter(|name| !self.refined.contains_key(name)) {
+            let current = self.ctx.type_ref(&name);
+            let ends: Option<Vec<Type>> = branches.iter()
+                .map(|(narrowed, assigned)| match assigned.get(&name) {
+                    Some(handle) => handle.map(|handle| self.ctx.resolve_ref(handle)),
+                    None => narrowed.get(&name).cloned().or_else(|| current.map(|handle| self.ctx.resolve_ref(handle))),
+                })
+                .collect();
+            let Some(ends) = ends else { continue };
+            let joined = Type::union(ends);
+            if current.map(|handle| self.ctx.resolve_ref(handle)).as_ref() == Some(&joined) {
+                continue;
+            }
+            // The join narrows what the name was found with
+            if let Some(handle) = self.narrowed_from.get(&name).copied().or(current) {
+                self.narrowed_from.insert(name.clone(), handle);
+            }
+            self.ctx.set_type(name, joined);
+        }
+    }
+
+    /// Type of the attribute path `expr` (`self.size`) as narrowing sees
+    /// it: what it was narrowed to, or the instance attribute's type
+    fn path_type(&self, expr: &Expr) -> Option<Type> {
+        match expr {
+            Expr::Name(name) => self.ctx.get_type(&name.id),
+            Expr::NamedExpr(named) => self.path_type(&named.target),
+            Expr::Attribute(attr) => {
+                let narrowed = narrowing::target_path(expr).filter(|path| self.narrowed_paths.contains(path));
+                if let Some(ty) = narrowed.and_then(|path| self.ctx.get_type(&path)) {
+                    return Some(ty);
+                }
+                match Self::strip_effects(self.path_type(&attr.value)?) {
+                    Type::Class(class_name) => self.instance_attribute(&class_name, &attr.attr),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Forget the narrowed types of the attribute paths `target` assigns
+    /// over: an attribute and those below it, or those below a name
+    fn forget_assigned(&mut self, target: &Expr) {
+        match target {
+            Expr::Tuple(tuple) => tuple.elts.iter().for_each(|elt| self.forget_assigned(elt)),
+            Expr::List(list) => list.elts.iter().for_each(|elt| self.forget_assigned(elt)),
+            Expr::Starred(starred) => self.forget_assigned(&starred.value),
+            _ => {
+                if let Some(path) = narrowing::target_path(target) {
+                    self.forget_paths(&path);
+                }
+            }
+        }
+    }
+
+    /// Forget the narrowed types of `path` and the attribute paths below it
+    fn forget_paths(&mut self, path: &str) {
+        if self.narrowed_paths.is_empty() {
+            return;
+        }
+        let below = format!("{}.", path);
+        let forgotten: Vec<String> = self.narrowed_paths.iter()
+            .filter(|narrowed| *narrowed == path || narrowed.starts_with(&below))
+            .cloned()
+            .collect();
+        for narrowed in forgotten {
+            self.narrowed_paths.remove(&narrowed);
+            self.ctx.remove_type(&narrowed);
+        }
+    }
+
+    fn binding(&self, name: &str) -> Binding {
+        Binding {
+            ty: self.ctx.type_ref(name),
+            sig: self.ctx.get_signature(name),
+            refined: self.refined.get(name).cloned(),
+            narrowed_from: self.narrowed_from.get(name).copied(),
+        }
+    }
+
+    fn rebind(&mut self, name: String, binding: Binding) {
+        match binding.ty {
+            Some(handle) => self.ctx.set_type_ref(name.clone(), handle),
+            None => {
+                self.ctx.remove_type(&name);
+            }
+        }
+        self.ctx.set_signature(name.clone(), binding.sig);
+        match binding.narrowed_from {
+            Some(handle) => self.narrowed_from.insert(name.clone(), handle),
+            None => self.narrowed_from.remove(&name),
+        };
+        match binding.refined {
+            Some(ty) => self.refined.insert(name, ty),
+            None => self.refined.remove(&name),
+        };
+    }
+
+    /// Start checking a body binding `locals` in a scope of its own. It
+    /// sees none of what enclosing scopes bind its locals to, and a
+    /// function or comprehension none of what a class body directly around
+    /// it binds; its `globals` see the module's bindings
+    fn enter_scope(&mut self, kind: ScopeKind, locals: HashMap<String, usize>, globals: HashSet<String>) {
+        let unbound = Binding { ty: None, sig: None, refined: None, narrowed_from: None };
+        let mut replaced: Vec<(String, Option<Binding>)> = locals.keys()
+            .map(|name| (name.clone(), Some(unbound.clone())))
+            .collect();
+        let mut seen: HashSet<String> = locals.keys().cloned().collect();
+        // What the class bound the name over, i.e. the enclosing binding
+        let outer = |scope: &LocalScope, name: &str| {
+            scope.shadowed.iter().find(|(shadowed, _)| shadowed == name).map(|(_, binding)| binding.clone())
+        };
+        if let Some(class) = self.local_scopes.last().filter(|scope| scope.kind == ScopeKind::Class && kind != ScopeKind::Class) {
+            for name in class.locals.keys().filter(|name| seen.insert((*name).clone())) {
+                replaced.push((name.clone(), outer(class, name)));
+            }
+        }
+        let mut redirected = Vec::new();
+        for name in globals.into_iter().filter(|name| !locals.contains_key(name)) {
+            let Some(owner) = self.local_scopes.iter().find(|scope| scope.locals.contains_key(&name)) else { continue };
+            if seen.insert(name.clone()) {
+                replaced.push((name.clone(), outer(owner, &name)));
+            }
+            redirected.push(name);
+        }
+
+        let mut shadowed = Vec::with_capacity(replaced.len());
+        for (name, replacement) in replaced {
+            shadowed.push((name.clone(), self.binding(&name)));
+            if let Some(binding) = replacement {
+                self.rebind(name, binding);
+            }
+        }
+        self.local_scopes.push(LocalScope { kind, locals, globals: redirected, shadowed });
+    }
+
+    /// Finish the innermost scope, putting back the bindings it hid and
+    /// handing its `global` names' bindings to the module
+    fn leave_scope(&mut self) {
+        let Some(scope) = self.local_scopes.pop() else { return };
+        // Attributes narrowed through its locals go with them
+        for name in scope.locals.keys() {
+            self.forget_paths(name);
+        }
+        for name in &scope.globals {
+            let current = self.binding(name);
+            let owner = self.local_scopes.iter_mut().find(|outer| outer.locals.contains_key(name));
+            if let Some((_, binding)) = owner.and_then(|owner| owner.shadowed.iter_mut().find(|(shadowed, _)| shadowed == name)) {
+                *binding = current;
+            }
+        }
+        for (name, binding) in scope.shadowed.into_iter().rev() {
+            self.rebind(name, binding);
+        }
+    }
+
+    /// What a class body reads for one of its names it hasn't bound yet:
+    /// the enclosing binding
+    fn class_fallback(&self, name: &str) -> Option<Type> {
+        let class = self.local_scopes.last().filter(|scope| scope.kind == ScopeKind::Class)?;
+        let (_, binding) = class.shadowed.iter().find(|(shadowed, _)| shadowed == name)?;
+        binding.ty.map(|handle| self.ctx.resolve_ref(handle))
+    }
+
+    /// Whether reading `name` at byte `offset` reads a local of the
+    /// function being checked before anything in it assigns the name
+    fn reads_unbound_local(&self, name: &str, offset: usize) -> bool {
+        for scope in self.local_scopes.iter().rev() {
+            if let Some(&bound) = scope.locals.get(name) {
+                return scope.kind == ScopeKind::Function && offset < bound;
+            }
+            // Enclosing functions may bind it after defining this one
+            if scope.kind == ScopeKind::Function {
+                return false;
+            }
+        }
+        false
+    }
+
+    /// Bind the targets of a comprehension's `for` clauses in a scope of
+    /// its own, left once its elements are inferred. The first iterable is
+    /// evaluated in the enclosing scope
+    fn enter_comprehension(&mut self, generators: &[Comprehension]) {
+        let mut locals = HashMap::new();
+        for (name, range) in bindings::comprehension_sites(generators) {
+            locals.entry(name).or_insert(range.start);
+        }
+        for (i, generator) in generators.iter().enumerate() {
+            let iterable_ty = self.infer_expr(&generator.iter);
+            if i == 0 {
+                self.enter_scope(ScopeKind::Comprehension, std::mem::take(&mut locals), HashSet::new());
+            }
+
+            // Get element type from iterable
+            let elem_ty = match iterable_ty {
+                Type::List(elem) => *elem,
+                Type::Set(elem) => *elem,
+                Type::Tuple(elems) if !elems.is_empty() => Type::union(elems),
+                Type::VarTuple(elem) => *elem,
+                Type::Dict(key, _) => *key,
+                Type::Str => Type::Str,
+                _ => self.ctx.fresh_var(),
+            };
+
+            // Set loop variable type
+            if let Expr::Name(name_expr) = &generator.target {
+                self.ctx.set_type(name_expr.id.to_string(), elem_ty);
+            }
+        }
+        if generators.is_empty() {
+            self.enter_scope(ScopeKind::Comprehension, locals, HashSet::new());
+        }
+    }
+
+    /// Detect `partial`/`partialmethod` callees; the flag is set for `partialmethod`
+    fn partial_kind(&self, func: &Expr) -> Option<bool> {
+        let name = match func {
+            // A user-defined `partial` shadows the functools one
+            Expr::Name(name) if !self.ctx.has_type(&name.id) => name.id.as_str(),
+            Expr::Attribute(attr) if matches!(&*attr.value, Expr::Name(m) if m.id.as_str() == "functools") => {
+                attr.attr.as_str()
+            }
+            _ => return None,
+        };
+
+        match name {
+            "partial" => Some(false),
+            "partialmethod" => Some(true),
+            _ => None,
+        }
+    }
+
+    /// Infer `partial(f, *args, **kwargs)` as `f` minus the bound parameters,
+    /// checking the bound arguments at the construction site
+    fn infer_partial(&mut self, call: &ExprCall, is_method: bool) -> Type {
+        let Some((target, bound)) = call.args.split_first() else {
+            self.report(call, codes::ARG_COUNT, "partial() requires a callable as its first argument".to_string());
+            return self.ctx.fresh_var();
+        };
+
+        let (base, effects) = match self.infer_expr(target) {
+            Type::Effect(inner, effects) => (*inner, Some(effects)),
+            other => (other, None),
+        };
+
+        let Type::Function(param_types, ret) = base else {
+            // Unknown callable: the residual signature can't be computed
+            for arg in bound {
+                self.infer_expr(arg);
+            }
+            for keyword in &call.keywords {
+                self.infer_expr(&keyword.value);
+            }
+            return self.ctx.fresh_var();
+        };
+
+        let sig = self.signature_of(target).filter(|sig| sig.params.len() == param_types.len());
+        let mut params: Vec<(Option<String>, Type)> = param_types.into_iter()
+            .enumerate()
+            .map(|(i, ty)| (sig.as_ref().map(|sig| sig.params[i].name.clone()), ty))
+            .collect();
+
+        // partialmethod binds after the receiver, which stays in front
+        let receiver = if is_method && !params.is_empty() {
+            Some(params.remove(0))
+        } else {
+            None
+        };
+
+        if bound.len() > params.len() {
+            let message = format!(
+                "partial() binds {} positional arguments but the callable accepts {}",
+                bound.len(),
+                params.len()
+            );
+            self.report(call, codes::ARG_COUNT, message);
+        }
+
+        for (i, arg) in bound.iter().enumerate() {
+            let arg_ty = match params.get(i) {
+                Some((_, param_ty)) => self.infer_against(arg, &param_ty.clone()),
+                None => self.infer_expr(arg),
+            };
+            if let Some((_, param_ty)) = params.get(i) {
+                if !self.ctx.is_subtype(&arg_ty, param_ty) {
+                    let message = format!("partial() argument {} type mismatch: expected {}, got {}", i, param_ty, arg_ty);
+                    self.report(arg, codes::ARG_TYPE, message);
+                }
+            }
+        }
+        params.drain(..bound.len().min(params.len()));
+
+        for keyword in &call.keywords {
+            let arg_ty = self.infer_expr(&keyword.value);
+            // `**kwargs` can't be matched statically
+            let Some(name) = &keyword.arg else { continue };
+
+            let param_ty = match params.iter().position(|(param, _)| param.as_deref() == Some(name.as_str())) {
+                Some(idx) => params.remove(idx).1,
+                None => {
+                    let Some(sig) = &sig else { continue };
+                    match sig.kwonly.iter().find(|(param, _)| param.name == name.as_str()) {
+                        Some((_, param_ty)) => param_ty.clone(),
+                        None => {
+                            if sig.kwargs.is_none() {
+                                self.report(keyword, codes::INVALID_KEYWORD, format!("partial() got an unexpected keyword argument '{}'", name));
+                            }
+                            continue;
+                        }
+                    }
+                }
+            };
+            if !self.ctx.is_subtype(&arg_ty, &param_ty) {
+                let message = format!(
+                    "partial() keyword argument '{}' type mismatch: expected {}, got {}",
+                    name, param_ty, arg_ty
+                );
+                self.report(keyword, codes::ARG_TYPE, message);
+            }
+        }
+
+        let residual = receiver.into_iter().chain(params).map(|(_, ty)| ty).collect();
+        let func_type = Type::Function(residual, ret);
+        match effects {
+            Some(effects) => Type::Effect(Box::new(func_type), effects),
+            None => func_type,
+        }
+    }
+
+    /// How a callable expression binds call arguments, when statically known
+    fn signature_of(&self, expr: &Expr) -> Option<FunctionSig> {
+        match expr {
+            Expr::Name(name) => self.ctx.get_signature(&name.id),
+            Expr::Attribute(attr) => {
+                if let Some(module) = self.module_of(&attr.value) {
+                    return module.signatures.get(attr.attr.as_str()).cloned();
+                }
+                let Expr::Name(base) = &*attr.value else { return None };
+                match self.ctx.get_type(&base.id)? {
+                    Type::Class(class_name) => self.ctx.member_signature(&class_name, &attr.attr),
+                    Type::ClassObject(class_name) => self.ctx.class_object_signature(&class_name, &attr.attr),
+                    _ => None,
+                }
+            }
+            Expr::Call(call) => {
+                let is_method = self.partial_kind(&call.func)?;
+                let mut sig = self.signature_of(call.args.first()?)?;
+                // partialmethod binds after the receiver, which stays in front
+                let first = usize::from(is_method && !sig.params.is_empty());
+                let bound = (call.args.len() - 1).min(sig.params.len() - first);
+                sig.params.drain(first..first + bound);
+                sig.posonly -= sig.posonly.min(first + bound).saturating_sub(first);
+
+                let named = |name: &str| {
+                    call.keywords.iter().any(|kw| kw.arg.as_ref().is_some_and(|arg| arg.as_str() == name))
+                };
+                sig.params.retain(|param| !named(&param.name));
+                // Keyword-only parameters partial() binds become optional
+                for (param, _) in &mut sig.kwonly {
+                    param.has_default |= named(&param.name);
+                }
+                Some(sig)
+            }
+            _ => None,
+        }
+    }
+
+    /// Result of `left op right` for the builtin numeric, string and list
+    /// operators; `Any` for operands they don't cover
+    fn binop_type(op: Operator, left_ty: Type, right_ty: Type) -> Type {
+        match op {
+            // Addition
+            Operator::Add => {
+                if left_ty == Type::Int && right_ty == Type::Int {
+                    Type::Int
+                } else if matches!(left_ty, Type::Int | Type::Float)
+                    && matches!(right_ty, Type::Int | Type::Float) {
+                    Type::Float
+                } else if left_ty == Type::Str && right_ty == Type::Str {
+                    Type::Str  // String concatenation
+                } else if let (Type::List(left), Type::List(right)) = (&left_ty, &right_ty) {
+                    // List concatenation; an empty list takes the other's elements
+                    match (&**left, &**right) {
+                        (Type::Var(_), _) => right_ty,
+                        (_, Type::Var(_)) => left_ty,
+                        _ => Type::List(Box::new(Type::union(vec![(**left).clone(), (**right).clone()]))),
+                    }
+                } else {
+                    Type::Any
+                }
+            }
+            // Multiplication
+            Operator::Mult => {
+                if left_ty == Type::Int && right_ty == Type::Int {
+                    Type::Int
+                } else if matches!(left_ty, Type::Int | Type::Float)
+                    && matches!(right_ty, Type::Int | Type::Float) {
+                    Type::Float
+                } else if (left_ty == Type::Str && right_ty == Type::Int) ||
+                          (left_ty == Type::Int && right_ty == Type::Str) {
+                    Type::Str  // String repetition
+                } else {
+                    Type::Any
+                }
+            }
+            // Subtraction, Modulo, Power
+            Operator::Sub | Operator::Mod | Operator::Pow => {
+                if left_ty == Type::Int && right_ty == Type::Int {
+                    Type::Int
+                } else if matches!(left_ty, Type::Int | Type::Float)
+                    && matches!(right_ty, Type::Int | Type::Float) {
+                    Type::Float
+                } else {
+                    Type::Any
+                }
+            }
+            // Division always returns float
+            Operator::Div => Type::Float,
+            // Floor division returns int
+            Operator::FloorDiv => Type::Int,
+            _ => Type::Any,
+        }    }
+
+    /// Resolve `left <method> right` through dunder methods when a class
+    /// instance is involved: the left operand's `method`, else the right
+    /// operand's `reflected` one, each taking the other operand
+    ///
+    /// `None` when neither operand is an instance, or when a method nothing
+    /// is known about might still apply. `Err` carries a note on the
+    /// parameter type an instance's method expected.
+    fn resolve_dunder(&self, left: &Type, right: &Type, method: &str, reflected: &str) -> Option<Result<Type, String>> {
+        let (left, right) = (Self::strip_effects(left.clone()), Self::strip_effects(right.clone()));
+        let instance = |ty: &Type| matches!(ty, Type::Class(_) | Type::Generic(..));
+        if !instance(&left) && !instance(&right) {
+            return None;
+        }
+
+        let mut note = String::new();
+        for (receiver, name, operand) in [(&left, method, &right), (&right, reflected, &left)] {
+            let Some(Type::Function(params, ret)) = self.ctx.has_attribute(receiver, name).map(Self::strip_effects) else { continue };
+            match params.first() {
+                Some(param) if !self.is_compatible(operand, param) => {
+                    if instance(receiver) && note.is_empty() {
+                        note = format!(" ({}.{} expects {})", receiver, name, param);
+                    }
+                }
+                _ => return Some(Ok(*ret)),
+            }
+        }
+        // Either side may have a method nothing is known about
+        (self.knows_members(&left) && self.knows_members(&right)).then_some(Err(note))
+    }
+
+    /// `a + b` with a class instance on either side, through `__add__` or `__radd__`
+    fn dunder_binop(&mut self, binop: &ExprBinOp, left_ty: &Type, right_ty: &Type) -> Option<Type> {
+        let (method, reflected) = operator_dunders(binop.op);
+        match self.resolve_dunder(left_ty, right_ty, method, reflected)? {
+            Ok(ty) => Some(ty),
+            Err(note) => {
+                let message = format!(
+                    "Unsupported operand types for {}: {} and {}{}",
+                    operator_symbol(binop.op), left_ty, right_ty, note
+                );
+                self.report(binop, codes::UNSUPPORTED_OPERAND, message);
+                Some(Type::Any)
+            }
+        }
+    }
+
+    /// Check ordering comparisons involving class instances against their
+    /// `__lt__`-style methods, and `in` against the container's
+    /// `__contains__`. Operands are inferred only for their types, so their
+    /// own errors are dropped
+    ///
+    /// Returns the ordering method's result for a single, unchained comparison.
+    fn check_comparison_dunders(&mut self, compare: &ExprCompare) -> Option<Type> {
+        if !compare.ops.iter().any(|op| matches!(op, CmpOp::Lt | CmpOp::LtE | CmpOp::Gt | CmpOp::GtE | CmpOp::In | CmpOp::NotIn)) {
+            return None;
+        }
+        let operands: Vec<&Expr> = std::iter::once(&*compare.left).chain(&compare.comparators).collect();
+        let errors = self.errors.len();
+        let types: Vec<Type> = operands.iter().map(|operand| self.infer_expr(operand)).collect();
+        self.errors.truncate(errors);
+
+        let mut result = None;
+        for (i, op) in compare.ops.iter().enumerate() {
+            let range = operands[i].start().to_usize()..operands[i + 1].end().to_usize();
+            let (left, right) = (&types[i], &types[i + 1]);
+            let (symbol, resolved) = match comparison_dunders(*op) {
+                Some((symbol, method, reflected)) => (symbol, self.resolve_dunder(left, right, method, reflected)),
+                None if matches!(op, CmpOp::In | CmpOp::NotIn) => ("in", self.resolve_contains(left, right)),
+                None => continue,
+            };
+            match resolved {
+                Some(Ok(ty)) => result = Some(ty),
+                Some(Err(note)) => {
+                    let message = format!("Unsupported operand types for {}: {} and {}{}", symbol, left, right, note);
+                    self.report_range(range, codes::UNSUPPORTED_OPERAND, message);
+                }
+                None => {}
+            }
+        }
+        result.filter(|_| compare.ops.len() == 1 && comparison_dunders(compare.ops[0]).is_some())
+    }
+
+    /// `item in container` through the container's `__contains__`, or
+    /// iteration when it has none
+    fn resolve_contains(&self, item: &Type, container: &Type) -> Option<Result<Type, String>> {
+        let container = Self::strip_effects(container.clone());
+        if !self.knows_members(&container) {
+            return None;
+        }
+        match self.ctx.has_attribute(&container, "__contains__").map(Self::strip_effects) {
+            // Only a class's own parameter is trusted; `int in bytes` is fine
+            Some(Type::Function(params, _)) if matches!(container, Type::Class(_)) => match params.first() {
+                Some(param) if !self.is_compatible(item, param) => {
+                    Some(Err(format!(" ({}.__contains__ expects {})", container, param)))
+                }
+                _ => Some(Ok(Type::Bool)),
+            },
+            Some(_) => Some(Ok(Type::Bool)),
+            None if ["__iter__", "__getitem__"].iter().any(|name| self.ctx.has_attribute(&container, name).is_some()) => {
+                Some(Ok(Type::Bool))
+            }
+            None => Some(Err(String::new())),
+        }
+    }
+
+    /// The argument of a call to the builtin `len`
+    fn builtin_len_arg<'a>(&self, call: &'a ExprCall) -> Option<&'a Expr> {
+        match (&*call.func, call.args.as_slice()) {
+            (Expr::Name(name), [arg]) if name.id.as_str() == "len" && call.keywords.is_empty() && !self.ctx.has_type("len") => {
+                Some(arg)
+            }
+            _ => None,
+        }
+    }
+
+    /// `len(value)`, which needs the value's type to define `__len__`
+    fn infer_len(&mut self, value: &Expr) -> Type {
+        let ty = Self::strip_effects(self.infer_expr(value));
+        if self.knows_members(&ty) && self.ctx.has_attribute(&ty, "__len__").is_none() {
+            let kind = ErrorKind::InvalidArgType { param: "obj".to_string(), expected: "Sized".to_string(), found: ty.to_string() };
+            self.report(value, kind.code(), kind.to_string());
+        }
+        Type::Int
+    }
+
+    /// `instance[key]` through the class's `__getitem__`
+    fn dunder_subscript(&mut self, subscript_expr: &ExprSubscript, instance: &Type) -> Type {
+        let key_ty = self.infer_expr(&subscript_expr.slice);
+        let invalid = ErrorKind::InvalidSubscript { container: instance.to_string(), key: key_ty.to_string() };
+        match self.ctx.has_attribute(instance, "__getitem__").map(Self::strip_effects) {
+            Some(Type::Function(params, ret)) => {
+                if params.first().is_some_and(|param| !self.is_compatible(&key_ty, param)) {
+                    self.report(&*subscript_expr.slice, invalid.code(), invalid.to_string());
+                }
+                *ret
+            }
+            None if self.knows_members(instance) => {
+                self.report(subscript_expr, invalid.code(), invalid.to_string());
+                Type::Any
+            }
+            _ => self.ctx.fresh_var(),
+        }
+    }
+
+    /// Call an instance of `class_name` through its `__call__`
+    fn call_instance(&mut self, call_expr: &ExprCall, class_name: &str) -> Type {
+        let instance = Type::Class(class_name.to_string());
+        match self.ctx.has_attribute(&instance, "__call__").map(Self::strip_effects) {
+            Some(Type::Function(params, ret)) => {
+                let sig = self.ctx.member_signature(class_name, "__call__").filter(|sig| sig.params.len() == params.len());
+                self.check_call_args(call_expr, &params, sig.as_ref());
+                *ret
+            }
+            Some(_) => self.ctx.fresh_var(),
+            None => {
+                for arg in call_expr.args.iter().chain(call_expr.keywords.iter().map(|keyword| &keyword.value)) {
+                    self.infer_expr(arg);
+                }
+                let kind = ErrorKind::NonCallable { ty: instance.to_string() };
+                self.report(&*call_expr.func, kind.code(), kind.to_string());
+                self.ctx.fresh_var()
+            }
+        }
+    }
+
+    /// `x op= value`: the result has to fit what `x` already holds
+    fn check_aug_assign(&mut self, aug_assign: &StmtAugAssign) {
+        if let Expr::Attribute(attr) = &*aug_assign.target {
+            self.check_frozen_assignment(attr);
+        }
+        let target_ty = self.infer_expr(&aug_assign.target);
+        let value_ty = self.infer_expr(&aug_assign.value);
+        self.forget_assigned(&aug_assign.target);
+        let bounds = (self.interval_of(&aug_assign.target, &target_ty), self.interval_of(&aug_assign.value, &value_ty));
+        let result = match (aug_assign.op, bounds) {
+            (Operator::Add, (Some(target), Some(value))) => (target + value).to_type(),
+            (Operator::Sub, (Some(target), Some(value))) => (target - value).to_type(),
+            (Operator::Mult, (Some(target), Some(value))) => (target * value).to_type(),
+            _ => Self::binop_type(aug_assign.op, Self::unrefined(target_ty.clone()), Self::unrefined(value_ty.clone())),
+        };
+
+        let builtin = |ty: &Type| matches!(
+            ty,
+            Type::Int | Type::Float | Type::Str | Type::Bytes | Type::None
+                | Type::List(_) | Type::Tuple(_) | Type::Dict(_, _) | Type::Set(_)
+        );
+        if result == Type::Any {
+            if builtin(&target_ty) && builtin(&value_ty) {
+                let message = format!(
+                    "Unsupported operand types for {}=: {} and {}",
+                    operator_symbol(aug_assign.op), target_ty, value_ty
+                );
+                self.report(aug_assign, codes::UNSUPPORTED_OPERAND, message);
+            }
+            return;
+        }
+
+        let Expr::Name(name) = &*aug_assign.target else { return };
+        if let Some(declared) = self.refined.get(name.id.as_str()).cloned() {
+            if self.check_bounds(aug_assign, Self::bounds(&result), &declared).is_some() {
+                return;
+            }
+        }
+        let unresolved = match &target_ty {
+            Type::Var(_) => true,
+            Type::List(elem) | Type::Set(elem) => matches!(**elem, Type::Var(_)),
+            _ => false,
+        };
+        if unresolved {
+            self.ctx.set_type(name.id.to_string(), result);
+        } else if !self.is_compatible(&result, &target_ty) {
+            let message = format!(
+                "Type mismatch: cannot assign {} to variable '{}' of type {}",
+                result, name.id, target_ty
+            );
+            self.report(aug_assign, codes::TYPE_MISMATCH, message);
+        }
+    }
+
+    /// Infer `==`/`!=` operands for the lints; comparisons are not otherwise
+    /// checked, so errors raised while inferring them are dropped
+    fn record_operand_types(&mut self, compare: &ExprCompare) {
+        if !compare.ops.iter().any(|op| matches!(op, CmpOp::Eq | CmpOp::NotEq)) {
+            return;
+        }
+        let errors = self.errors.len();
+        for operand in std::iter::once(&*compare.left).chain(&compare.comparators) {
+            let ty = self.infer_expr(operand);
+            self.operand_types.insert(operand.start().to_usize()..operand.end().to_usize(), ty);
+        }
+        self.errors.truncate(errors);
+    }
+
+    /// Match a call's arguments to the callee's parameters and check each
+    /// against the type of the parameter it fills
+    ///
+    /// Without a signature, keywords and unpacked arguments can't be placed,
+    /// so only plain positional arguments are checked.
+    fn check_call_args(&mut self, call: &ExprCall, params: &[Type], sig: Option<&FunctionSig>) {
+        // A `Callable[..., R]` takes anything
+        if Type::takes_any_args(params) {
+            for arg in call.args.iter().chain(call.keywords.iter().map(|keyword| &keyword.value)) {
+                self.infer_expr(arg);
+            }
+            return;
+        }
+
+        // Arguments from a `*iterable` onwards have no known position
+        let positional = call.args.iter().position(|arg| matches!(arg, Expr::Starred(_))).unwrap_or(call.args.len());
+        let unpacked = positional < call.args.len();
+        let mut keyword_types: Vec<Option<Type>> = vec![None; call.keywords.len()];
+
+        match sig {
+            Some(sig) => {
+                let names: Vec<Option<&str>> = call.keywords.iter().map(|kw| kw.arg.as_ref().map(|arg| arg.as_str())).collect();
+                let binding = sig.bind(positional, unpacked, &names);
+                let callee = self.callee_name(&call.func);
+                let at_least = if sig.has_defaults() { "at least " } else { "" };
+
+                if let Some(max) = binding.too_many {
+                    let at_most = if sig.has_defaults() { "at most " } else { "" };
+                    let message = format!("Function call argument count mismatch: expected {}{}, got {}", at_most, max, positional);
+                    self.report(call, codes::ARG_COUNT, message);
+                }
+                // Plain positional calls keep the count form
+                if call.keywords.is_empty() && !binding.missing.is_empty() && sig.kwonly.iter().all(|(param, _)| param.has_default) {
+                    let message = format!("Function call argument count mismatch: expected {}{}, got {}", at_least, sig.required(), positional);
+                    self.report(call, codes::ARG_COUNT, message);
+                } else {
+                    for name in &binding.missing {
+                        self.report(call, codes::MISSING_ARGUMENT, format!("{}() missing required argument '{}'", callee, name));
+                    }
+                }
+
+                for ((keyword, slot), expected) in call.keywords.iter().zip(&binding.keywords).zip(&mut keyword_types) {
+                    let name = keyword.arg.as_ref().map_or("", |arg| arg.as_str());
+                    let message = match slot {
+                        Ok(KeywordSlot::Param(i)) => {
+                            *expected = Some(params[*i].clone());
+                            continue;
+                        }
+                        Ok(KeywordSlot::KwOnly(i)) => {
+                            *expected = Some(sig.kwonly[*i].1.clone());
+                            continue;
+                        }
+                        Ok(KeywordSlot::Kwargs) => {
+                            *expected = sig.kwargs.clone();
+                            continue;
+                        }
+                        Ok(KeywordSlot::Unpacked) => continue,
+                        Err(KeywordMismatch::Unexpected) => format!("{}() got an unexpected keyword argument '{}'", callee, name),
+                        Err(KeywordMismatch::MultipleValues) => format!("{}() got multiple values for argument '{}'", callee, name),
+                        Err(KeywordMismatch::PositionalOnly) => {
+                            format!("{}() got positional-only argument '{}' passed as keyword", callee, name)
+                        }
+                    };
+                    self.report(keyword, codes::INVALID_KEYWORD, message);
+                }
+            }
+            // Keywords and unpacking could fill any parameter
+            None if call.keywords.is_empty() && !unpacked && call.args.len() != params.len() => {
+                let message = format!(
+                    "Function call argument count mismatch: expected {}, got {}",
+                    params.len(),
+                    call.args.len()
+                );
+                self.report(call, codes::ARG_COUNT, message);
+            }
+            None => {}
+        }
+
+        for (i, arg) in call.args.iter().enumerate() {
+            let expected = match sig {
+                Some(sig) => sig.positional_type(params, i),
+                None => params.get(i),
+            };
+            let arg_ty = match expected {
+                Some(param_ty) if i < positional => self.infer_against(arg, param_ty),
+                _ => self.infer_expr(arg),
+            };
+            let bounds = self.interval_of(arg, &arg_ty);
+            match expected {
+                Some(param_ty) if i < positional && self.check_bounds(arg, bounds, param_ty).is_some() => {}
+                Some(param_ty) if i < positional && !self.ctx.is_subtype(&arg_ty, param_ty) => {
+                    let message = format!(
+                        "Argument {} type mismatch: expected {}, got {}{}",
+                        i, param_ty, self.shown_argument(arg, &arg_ty, param_ty), self.mismatch_note(&arg_ty, param_ty)
+                    );
+                    self.report(arg, codes::ARG_TYPE, message);
+                }
+                _ => {}
+            }
+        }
+        for (keyword, expected) in call.keywords.iter().zip(keyword_types) {
+            let arg_ty = match &expected {
+                Some(param_ty) => self.infer_against(&keyword.value, param_ty),
+                None => self.infer_expr(&keyword.value),
+            };
+            let bounds = self.interval_of(&keyword.value, &arg_ty);
+            match (&keyword.arg, expected) {
+                (Some(_), Some(param_ty)) if self.check_bounds(&keyword.value, bounds, &param_ty).is_some() => {}
+                (Some(name), Some(param_ty)) if !self.ctx.is_subtype(&arg_ty, &param_ty) => {
+                    let message = format!(
+                        "Keyword argument '{}' type mismatch: expected {}, got {}{}",
+                        name, param_ty, self.shown_argument(&keyword.value, &arg_ty, &param_ty), self.mismatch_note(&arg_ty, &param_ty)
+                    );
+                    self.report(keyword, codes::ARG_TYPE, message);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// How call errors name a callee: `f` or `Class.method`
+    fn callee_name(&self, func: &Expr) -> String {
+        match func {
+            Expr::Name(name) => name.id.to_string(),
+            Expr::Attribute(attr) => {
+                let receiver = match &*attr.value {
+                    Expr::Name(receiver) => self.ctx.get_type(&receiver.id).map(Self::strip_effects),
+                    _ => None,
+                };
+                match receiver {
+                    Some(Type::Class(class_name) | Type::ClassObject(class_name)) => format!("{}.{}", class_name, attr.attr),
+                    _ => attr.attr.to_string(),
+                }
+            }
+            _ => "function".to_string(),
+        }
+    }
+
+    /// Check a constructor call against the class's `__init__`, when it or a
+    /// known base defines one
+    fn check_constructor_args(&mut self, call: &ExprCall, class_name: &str) {
+        let init = self.ctx.has_attribute(&Type::Class(class_name.to_string()), "__init__").map(Self::strip_effects);
+        let Some(Type::Function(params, _)) = init else {
+            for arg in &call.args {
+                self.infer_expr(arg);
+            }
+            for keyword in &call.keywords {
+                self.infer_expr(&keyword.value);
+            }
+            return;
+        };
+        let sig = self.ctx.member_signature(class_name, "__init__").filter(|sig| sig.params.len() == params.len());
+        self.check_call_args(call, &params, sig.as_ref());
+    }
+
+    /// `note: f(): <summary>` for a documented callee
+    fn callee_doc_note(&self, func: &Expr) -> Option<String> {
+        let (callee, doc) = match func {
+            Expr::Name(name) => (name.id.to_string(), self.ctx.get_doc(&name.id)?),
+            Expr::Attribute(attr) => {
+                let Expr::Name(receiver) = &*attr.value else { return None };
+                let class_name = match Self::strip_effects(self.ctx.get_type(&receiver.id)?) {
+                    Type::Class(name) | Type::ClassObject(name) => name,
+                    _ => return None,
+                };
+                let doc = self.ctx.member_doc(&class_name, &attr.attr)?;
+                (format!("{}.{}", class_name, attr.attr), doc)
+            }
+            _ => return None,
+        };
+        Some(format!("note: {}(): {}", callee, doc.summary))
+    }
+
+    /// Keys of a class-based `TypedDict`: its annotated fields on top of
+    /// those of its `TypedDict` bases; `None` for other classes
+    fn typed_dict_class(&mut self, class_def: &StmtClassDef, bases: &[String]) -> Option<TypedDictKeys> {
+        let mut keys = TypedDictKeys::default();
+        let mut is_typed_dict = false;
+        for base in bases {
+            if base == "TypedDict" {
+                is_typed_dict = true;
+            } else if let Some(inherited) = self.ctx.typed_dict(base) {
+                is_typed_dict = true;
+                for (key, ty) in &inherited.keys {
+                    keys.insert(key.clone(), ty.clone(), inherited.is_required(key));
+                }
+            }
+        }
+        if !is_typed_dict {
+            return None;
+        }
+
+        let total = typed_dict_total(&class_def.keywords);
+        for stmt in &class_def.body {
+            if let Stmt::AnnAssign(field) = stmt {
+                if let Expr::Name(name) = &*field.target {
+                    let ty = self.type_from_annotation(&field.annotation);
+                    keys.insert(name.id.to_string(), ty, key_required(&field.annotation, total));
+                }
+            }
+        }
+        Some(keys)
+    }
+
+    /// `TypedDict("Movie", {"title": str, "year": int})`: the class name and keys
+    fn functional_typed_dict(&mut self, value: &Expr) -> Option<(String, TypedDictKeys)> {
+        let Expr::Call(call) = value else { return None };
+        let callee = match &*call.func {
+            Expr::Name(name) => name.id.as_str(),
+            Expr::Attribute(attr) => attr.attr.as_str(),
+            _ => return None,
+        };
+        let (true, [Expr::Constant(name), Expr::Dict(fields)]) = (callee == "TypedDict", call.args.as_slice()) else {
+            return None;
+        };
+        let Constant::Str(name) = &name.value else { return None };
+
+        let total = typed_dict_total(&call.keywords);
+        let mut keys = TypedDictKeys::default();
+        for (key, annotation) in fields.keys.iter().zip(&fields.values) {
+            if let Some(key) = key.as_ref().and_then(str_constant) {
+                let ty = self.type_from_annotation(annotation);
+                keys.insert(key.to_string(), ty, key_required(annotation, total));
+            }
+        }
+        Some((name.to_string(), keys))
+    }
+
+    /// The `TypedDict` class `ty` is an instance of, with its keys; for a
+    /// union, its first `TypedDict` member
+    fn typed_dict_of(&self, ty: &Type) -> Option<(String, TypedDictKeys)> {
+        ConditionAnalyzer::members(ty).into_iter().find_map(|member| match member {
+            Type::Class(name) => self.ctx.typed_dict(&name).map(|keys| (name, keys)),
+            _ => None,
+        })
+    }
+
+    /// Infer `expr` where a value of type `expected` is wanted: a dict
+    /// literal wanted as a `TypedDict` is checked key by key and takes its
+    /// type, and a constant wanted as a `Literal` it matches takes that literal
+    fn infer_against(&mut self, expr: &Expr, expected: &Type) -> Type {
+        if let Type::Recursive(..) = expected {
+            return self.infer_against(expr, &expected.unfold());
+        }
+        if let (Expr::Dict(dict), Some((name, keys))) = (expr, self.typed_dict_of(expected)) {
+            self.check_typed_dict_literal(dict, &name, &keys);
+            return Type::Class(name);
+        }
+        // Lists are invariant in their elements, so a list literal takes the
+        // element type expected of it when every element fits it
+        let expected_elem = ConditionAnalyzer::members(expected).into_iter().find_map(|member| match member {
+            Type::List(elem) => Some(*elem),
+            _ => None,
+        });
+        if let Expr::Lambda(lambda) = expr {
+            let expected_params = ConditionAnalyzer::members(expected).into_iter().find_map(|member| match Self::strip_effects(member) {
+                Type::Function(params, _) if !Type::takes_any_args(&params) => Some(params),
+                _ => None,
+            });
+            return self.infer_lambda(lambda, expected_params.as_deref());
+        }
+        if let (Expr::List(list), Some(elem)) = (expr, expected_elem) {
+            if !list.elts.is_empty() {
+                let elem_types: Vec<Type> = list.elts.iter().map(|e| self.infer_against(e, &elem)).collect();
+                let fits = elem_types.iter().all(|ty| self.is_compatible(ty, &elem));
+                return Type::List(Box::new(if fits { elem } else { Type::union(elem_types) }));
+            }
+        }
+        // Likewise a dict literal's values, so lists nested in them can take
+        // what's expected of them too
+        let expected_entry = ConditionAnalyzer::members(expected).into_iter().find_map(|member| match member {
+            Type::Dict(key, value) => Some((*key, *value)),
+            _ => None,
+        });
+        if let (Expr::Dict(dict), Some((key, value))) = (expr, expected_entry) {
+            if !dict.keys.is_empty() && dict.keys.iter().all(Option::is_some) {
+                let key_types: Vec<Type> = dict.keys.iter().flatten().map(|k| self.infer_against(k, &key)).collect();
+                let value_types: Vec<Type> = dict.values.iter().map(|v| self.infer_against(v, &value)).collect();
+                let keys_fit = key_types.iter().all(|ty| self.is_compatible(ty, &key));
+                let values_fit = value_types.iter().all(|ty| self.is_compatible(ty, &value));
+                return Type::Dict(
+                    Box::new(if keys_fit { key } else { Type::union(key_types) }),
+                    Box::new(if values_fit { value } else { Type::union(value_types) }),
+                );
+            }
+        }
+        let ty = self.infer_expr(expr);
+        match self.consts.eval(expr) {
+            Ok(value) if ConditionAnalyzer::members(expected).iter().any(|member| value.matches_literal(member) == Some(true)) => {
+                value.literal_type()
+            }
+            _ => ty,
+        }
+    }
+
+    /// Report missing, unknown and mistyped keys of a dict literal built as
+    /// the `TypedDict` `name`. A `**mapping` may supply any key
+    fn check_typed_dict_literal(&mut self, dict: &ExprDict, name: &str, keys: &TypedDictKeys) {
+        let mut seen = HashSet::new();
+        let mut unpacked = false;
+        for (key, value) in dict.keys.iter().zip(&dict.values) {
+            let Some(key) = key else {
+                unpacked = true;
+                self.infer_expr(value);
+                continue;
+            };
+            let Some(key_name) = str_constant(key) else {
+                self.infer_expr(key);
+                self.infer_expr(value);
+                continue;
+            };
+            seen.insert(key_name);
+
+            match keys.get(key_name) {
+                Some(expected) => {
+                    let value_ty = self.infer_against(value, expected);
+                    if !self.is_compatible(&value_ty, expected) {
+                        let message = format!(
+                            "Type mismatch for key '{}' of TypedDict '{}': expected {}, got {}",
+                            key_name, name, expected, value_ty
+                        );
+                        self.report(value, codes::TYPE_MISMATCH, message);
+                    }
+                }
+                None => {
+                    self.infer_expr(value);
+                    self.report(key, codes::TYPE_MISMATCH, format!("TypedDict '{}' has no key '{}'", name, key_name));
+                }
+            }
+        }
+
+        let missing: Vec<String> = keys.keys.iter()
+            .filter(|(key, _)| keys.is_required(key) && !seen.contains(key.as_str()))
+            .map(|(key, _)| format!("'{}'", key))
+            .collect();
+        if !missing.is_empty() && !unpacked {
+            let noun = if missing.len() == 1 { "key" } else { "keys" };
+            let message = format!("Missing {} {} for TypedDict '{}'", noun, missing.join(", "), name);
+            self.report(dict, codes::TYPE_MISMATCH, message);
+        }
+    }
+
+    /// `movie["title"]`: the type of a literal key; other keys can't be checked
+    fn typed_dict_item(&mut self, key: &Expr, name: &str, keys: &TypedDictKeys) -> Type {
+        let Some(key_name) = str_constant(key) else {
+            self.infer_expr(key);
+            return Type::Any;
+        };
+        match keys.get(key_name) {
+            Some(ty) => ty.clone(),
+            None => {
+                self.report(key, codes::INVALID_SUBSCRIPT, format!("TypedDict '{}' has no key '{}'", name, key_name));
+                Type::Any
+            }
+        }
+    }
+
+    /// The field a statement in the body of a `@dataclass` declares, once
+    /// checked: an annotated name that isn't a `ClassVar`
+    fn dataclass_field(&self, stmt: &Stmt) -> Option<dataclasses::Field> {
+        let Stmt::AnnAssign(ann_assign) = stmt else { return None };
+        let Expr::Name(name) = &*ann_assign.target else { return None };
+        if dataclasses::is_class_var(&ann_assign.annotation) {
+            return None;
+        }
+        let (has_default, init) = dataclasses::field_spec(ann_assign.value.as_deref());
+        let ty = self.ctx.get_type(&name.id).unwrap_or(Type::Any);
+        Some(dataclasses::Field { name: name.id.to_string(), ty, has_default, init })
+    }
+
+    /// A protocol requires the attributes its body only declares, `name: str`
+    fn record_protocol_attribute(&self, class_name: &str, stmt: &Stmt) {
+        let Stmt::AnnAssign(ann_assign) = stmt else { return };
+        let (Expr::Name(name), None) = (&*ann_assign.target, &ann_assign.value) else { return };
+        if let (Some(schema), Some(ty)) = (self.ctx.get_class(class_name), self.ctx.get_type(&name.id)) {
+            schema.add_class_var(name.id.to_string(), ty);
+        }
+    }
+
+    /// How a mismatched argument is shown: the constant itself where it's
+    /// checked against `Literal` values, like assignments show it, and its
+    /// type otherwise
+    fn shown_argument(&self, arg: &Expr, arg_ty: &Type, expected: &Type) -> String {
+        let literal = match expected {
+            Type::Union(members) => members.iter().any(|member| member.literal_value().is_some()),
+            other => other.literal_value().is_some(),
+        };
+        match self.consts.eval(arg) {
+            Ok(constant) if literal => constant.to_string(),
+            _ => arg_ty.to_string(),
+        }
+    }
+
+    /// What a mismatch comes down to, as a `; ...` note: the members behind
+    /// a mismatch between a class and a protocol, or the arity of a function
+    /// passed where a callable of another arity is expected; or nothing
+    fn mismatch_note(&self, actual: &Type, expected: &Type) -> String {
+        let (class_name, protocol) = match (Self::strip_effects(actual.clone()), Self::strip_effects(expected.clone())) {
+            (Type::Class(class_name), Type::Class(protocol)) => (class_name, protocol),
+            (Type::Function(actual_params, _), Type::Function(expected_params, _))
+                if actual_params.len() != expected_params.len()
+                    && !Type::takes_any_args(&actual_params)
+                    && !Type::takes_any_args(&expected_params) =>
+            {
+                let arguments = |n: usize| if n == 1 { "1 argument".to_string() } else { format!("{} arguments", n) };
+                return format!("; the callable takes {}, expected {}", arguments(actual_params.len()), arguments(expected_params.len()));
+            }
+            _ => return String::new(),
+        };
+        let Some(members) = self.ctx.protocol_members(&protocol) else { return String::new() };
+        let mismatches = self.constraints.protocol_mismatches(&Type::Class(class_name.clone()), &members);
+        if mismatches.is_empty() {
+            return String::new();
+        }
+        format!("; '{}' does not satisfy protocol '{}': {}", class_name, protocol, mismatches.join(", "))
+    }
+
+    /// Make the fields of a `@dataclass` attributes of its instances and,
+    /// unless it defines its own, synthesize its `__init__` from them and the
+    /// fields of its dataclass bases
+    fn record_dataclass(&mut self, class_name: &str, bases: &[String], options: DataclassOptions, fields: Vec<dataclasses::Field>) {
+        let mut dataclass = Dataclass { frozen: options.frozen, ..Dataclass::default() };
+        for base in bases {
+            for field in self.dataclasses.get(base).map(|base| base.fields.clone()).unwrap_or_default() {
+                dataclass.insert(field);
+            }
+        }
+        for field in fields {
+            dataclass.insert(field);
+        }
+
+        if let Some(schema) = self.ctx.get_class(class_name) {
+            for field in &dataclass.fields {
+                if !schema.has_member(&field.name) {
+                    schema.add_class_var(field.name.clone(), field.ty.clone());
+                }
+            }
+            if options.init && !schema.has_member("__init__") {
+                let (params, sig) = dataclass.init_signature();
+                schema.add_method("__init__".to_string(), Type::Function(params, Box::new(Type::None)));
+                self.ctx.set_signature(format!("{}.__init__", class_name), Some(sig));
+            }
+        }
+        self.dataclasses.insert(class_name.to_string(), dataclass);
+    }
+
+    /// Report assigning a field of a frozen dataclass through `name.field`
+    fn check_frozen_assignment(&mut self, target: &ExprAttribute) {
+        let Expr::Name(owner) = &*target.value else { return };
+        let Some(Type::Class(class_name)) = self.ctx.get_type(&owner.id).map(Self::strip_effects) else { return };
+        let frozen = self.dataclasses.get(&class_name)
+            .is_some_and(|dataclass| dataclass.frozen && dataclass.field(&target.attr).is_some());
+        if frozen {
+            let message = format!("Cannot assign to field '{}' of frozen dataclass '{}'", target.attr, class_name);
+            self.report(target, codes::INVALID_ATTRIBUTE, message);
+        }
+    }
+
+    /// A type variable in an annotation: the type parameter it stands for in
+    /// the generic class being checked, `Any` elsewhere
+    fn type_var_annotation(&self, name: &str) -> Type {
+        let in_class = self.current_class.as_ref()
+            .and_then(|class_name| self.generics.get(class_name))
+            .is_some_and(|generic| generic.params.iter().any(|param| param.name == name));
+        if in_class { Type::param(name) } else { Type::Any }
+    }
+
+    /// Record `class_name` as parameterized over `params`, with their
+    /// declared variances
+    fn record_generic(&mut self, class_name: &str, params: Vec<TypeParameter>) {
+        let variances: Vec<Variance> = params.iter().map(|param| param.variance).collect();
+        self.variance.declare(class_name, &variances);
+        let definition = Type::Generic(class_name.to_string(), params.iter().map(|param| Type::param(&param.name)).collect());
+        self.generics.insert(class_name.to_string(), GenericType::new(class_name.to_string(), params, definition));
+    }
+
+    /// Check the methods of a generic class only use its covariant type
+    /// parameters where values come out, and its contravariant ones where
+    /// they go in. `__init__` takes its arguments before there's an
+    /// instance to be seen through a wider type, so it may use either
+    fn check_variances(&mut self, class_def: &StmtClassDef) {
+        let Some(generic) = self.generics.get(class_def.name.as_str()).cloned() else { return };
+        let Some(schema) = self.ctx.get_class(&class_def.name) else { return };
+        for stmt in &class_def.body {
+            let name = match stmt {
+                Stmt::FunctionDef(func_def) => func_def.name.as_str(),
+                Stmt::AsyncFunctionDef(func_def) => func_def.name.as_str(),
+                _ => continue,
+            };
+            if matches!(name, "__init__" | "__new__") {
+                continue;
+            }
+            let Some(member) = schema.get_member(name) else { continue };
+            for param in &generic.params {
+                if let Err(context) = self.variance.check_usage(&class_def.name, param, &member) {
+                    let message = ErrorKind::VarianceError { context: format!("{} in '{}'", context, name) }.to_string();
+                    self.report(stmt, codes::VARIANCE, message);
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Record methods, `partialmethod` bindings and class variables as
+    /// attributes of a class
+    /// Give a class's methods the types their annotations declare, and its
+    /// instances the attributes its methods assign through `self` and the
+    /// fields its body annotates; checking the body refines both
+    fn declare_members(&mut self, class_def: &StmtClassDef) {
+        let first_error = self.errors.len();
+        let mut attributes = HashMap::new();
+        for stmt in &class_def.body {
+            match stmt {
+                Stmt::FunctionDef(func_def) => self.declare_method(&class_def.name, stmt, func_def, false, &mut attributes),
+                Stmt::AsyncFunctionDef(func_def) => {
+                    self.declare_method(&class_def.name, stmt, &as_function_def(func_def), true, &mut attributes)
+                }
+                Stmt::AnnAssign(ann_assign) if ann_assign.value.is_none() => {
+                    if let Expr::Name(target) = &*ann_assign.target {
+                        let annotation = match &*ann_assign.annotation {
+                            Expr::Subscript(subscript) if dataclasses::is_class_var(&ann_assign.annotation) => &*subscript.slice,
+                            annotation => annotation,
+                        };
+                        attributes.insert(target.id.to_string(), self.type_from_annotation(annotation));
+                    }
+                }
+                _ => {}
+            }
+        }
+        // Annotations are reported where the body is checked
+        self.errors.truncate(first_error);
+
+        if let Some(attrs) = self.class_attributes.get_mut(class_def.name.as_str()) {
+            for (name, ty) in attributes {
+                attrs.entry(name).or_insert(ty);
+            }
+        }
+    }
+
+    /// Record the method `func_def` on the class schema as its annotations
+    /// declare it, and what it assigns to `self` in `attributes`; those
+    /// `__init__` assigns come first
+    fn declare_method(
+        &mut self,
+        class_name: &str,
+        stmt: &Stmt,
+        func_def: &StmtFunctionDef,
+        is_async: bool,
+        attributes: &mut HashMap<String, Type>,
+    ) {
+        let (param_types, sig) = self.parameter_types(func_def, |_| Type::Any);
+        let ret = func_def.returns.as_ref().map_or(Type::Any, |returns| self.type_from_annotation(returns));
+        let ret = match is_async && func_def.returns.is_some() && !reachability::yields(&func_def.body) {
+            true => Type::awaitable(ret),
+            false => ret,
+        };
+
+        // Members are recorded from the names the body binds them to
+        let name = func_def.name.to_string();
+        let previous = (self.ctx.type_ref(&name), self.ctx.get_signature(&name));
+        if self.erases_signature(func_def) {
+            self.ctx.set_type(name.clone(), Type::callable(Type::Any));
+            self.ctx.set_signature(name.clone(), None);
+        } else {
+            self.ctx.set_type(name.clone(), Type::Function(param_types.clone(), Box::new(ret)));
+            self.ctx.set_signature(name.clone(), Some(sig.clone()));
+        }
+        self.record_class_member(class_name, stmt);
+        match previous {
+            (Some(handle), previous_sig) => {
+                self.ctx.set_type_ref(name.clone(), handle);
+                self.ctx.set_signature(name, previous_sig);
+            }
+            (None, _) => {
+                self.ctx.remove_type(&name);
+                self.ctx.set_signature(name, None);
+            }
+        }
+
+        let annotated: HashMap<&str, &Type> = sig.params.iter().zip(&param_types)
+            .filter(|(param, _)| param.name != "self")
+            .map(|(param, ty)| (param.name.as_str(), ty))
+            .collect();
+        let is_init = func_def.name.as_str() == "__init__";
+        for (attr, value, annotation) in self_assignments(&func_def.body) {
+            let ty = match (annotation, value) {
+                (Some(annotation), _) => self.type_from_annotation(annotation),
+                (None, Some(Expr::Name(value))) => annotated.get(value.id.as_str()).map_or(Type::Any, |ty| (*ty).clone()),
+                _ => Type::Any,
+            };
+            if is_init {
+                attributes.insert(attr.to_string(), ty);
+            } else {
+                attributes.entry(attr.to_string()).or_insert(ty);
+            }
+        }
+    }
+
+    /// Type of the instance attribute `attr` of `class_name` or one of its
+    /// bases, assigned through `self` or annotated in the class body
+    fn instance_attribute(&self, class_name: &str, attr: &str) -> Option<Type> {
+        let mut classes = vec![class_name.to_string()];
+        let mut seen = HashSet::new();
+        while let Some(class) = classes.pop() {
+            if !seen.insert(class.clone()) {
+                continue;
+            }
+            if let Some(ty) = self.class_attributes.get(&class).and_then(|attrs| attrs.get(attr)) {
+                return Some(ty.clone());
+            }
+            if let Some(schema) = self.ctx.get_class(&class) {
+                classes.extend(schema.bases.iter().rev().cloned());
+            }
+        }
+        None
+    }
+
+    /// Every attribute instances of `class_name` are known to have, when its
+    /// bases are all known classes without a `__getattr__`
+    fn closed_attributes(&self, class_name: &str) -> Option<Vec<String>> {
+        let mut names = Vec::new();
+        let mut classes = vec![class_name.to_string()];
+        let mut seen = HashSet::new();
+        while let Some(class) = classes.pop() {
+            if !seen.insert(class.clone()) {
+                continue;
+            }
+            let schema = self.ctx.get_class(&class)?;
+            if schema.get_member("__getattr__").is_some() || schema.get_member("__getattribute__").is_some() {
+                return None;
+            }
+            if let Some(attrs) = self.class_attributes.get(&class) {
+                names.extend(attrs.keys().cloned());
+            }
+            classes.extend(schema.bases.iter().cloned());
+        }
+        names.extend(self.ctx.get_attributes(&Type::Class(class_name.to_string())));
+        names.sort();
+        names.dedup();
+        Some(names)
+    }
+
+    fn record_class_member(&mut self, class_name: &str, stmt: &Stmt) {
+        let (name, decorator_list): (&str, &[Expr]) = match stmt {
+            Stmt::FunctionDef(func_def) => (func_def.name.as_str(), &func_def.decorator_list),
+            Stmt::AsyncFunctionDef(func_def) => (func_def.name.as_str(), &func_def.decorator_list),
+            Stmt::Assign(assign) => match (assign.targets.as_slice(), &*assign.value) {
+                ([Expr::Name(target)], Expr::Call(call)) if self.partial_kind(&call.func) == Some(true) => {
+                    (target.id.as_str(), &[])
+                }
+                ([Expr::Name(target)], _) => return self.record_class_var(class_name, &target.id),
+                _ => return,
+            },
+            Stmt::AnnAssign(ann_assign) if ann_assign.value.is_some() => match &*ann_assign.target {
+                Expr::Name(target) => return self.record_class_var(class_name, &target.id),
+                _ => return,
+            },
+            _ => return,
+        };
+        // A property's setter and deleter leave the property as it is
+        if decorators::is_accessor(decorator_list) {
+            return;
+        }
+
+        let (Some(ty), Some(schema)) = (self.ctx.get_type(name), self.ctx.get_class(class_name)) else { return };
+        if let Some(doc) = self.ctx.get_doc(name) {
+            schema.set_member_doc(name.to_string(), doc);
+        }
+        let has_decorator = |decorator: &str| decorators::has_decorator(decorator_list, decorator);
+
+        if decorators::is_property(decorator_list) {
+            let value_ty = match Self::strip_effects(ty) {
+                Type::Function(_, ret) => *ret,
+                other => other,
+            };
+            schema.add_property(name.to_string(), value_ty);
+            return;
+        }
+
+        // Subclasses find methods through the schema's bases. Class methods
+        // bind `cls` whether reached through the class or an instance
+        let is_static = has_decorator("staticmethod");
+        if is_static {
+            schema.add_static_method(name.to_string(), ty);
+        } else if has_decorator("classmethod") {
+            schema.add_static_method(name.to_string(), Self::bind_receiver(ty));
+        } else {
+            schema.add_method(name.to_string(), Self::bind_receiver(ty));
+        }
+
+        if let Some(sig) = self.ctx.get_signature(name) {
+            let sig = if is_static { sig } else { sig.without_receiver() };
+            self.ctx.set_signature(format!("{}.{}", class_name, name), Some(sig));
+        }
+    }
+
+    fn record_class_var(&mut self, class_name: &str, name: &str) {
+        let Some(schema) = self.ctx.get_class(class_name) else { return };
+        // Enum members are instances of their enum, not of the assigned value
+        let ty = if self.consts.is_enum_member(class_name, name) {
+            if let Some(value_ty) = self.ctx.get_type(name) {
+                self.enum_values.insert(format!("{}.{}", class_name, name), value_ty);
+            }
+            Some(Type::Class(class_name.to_string()))
+        } else {
+            self.ctx.get_type(name)
+        };
+        if let Some(ty) = ty {
+            schema.add_class_var(name.to_string(), ty);
+        }
+    }
+
+    /// Drop the receiver (`self`/`cls`) from a method type
+    fn bind_receiver(ty: Type) -> Type {
+        match ty {
+            Type::Function(mut params, ret) if !params.is_empty() && !Type::takes_any_args(&params) => {
+                params.remove(0);
+                Type::Function(params, ret)
+            }
+            Type::Effect(inner, effects) => Type::Effect(Box::new(Self::bind_receiver(*inner)), effects),
+            other => other,
+        }
+    }
+
+    /// `func_def` as an overload: its type and signature as calls see them,
+    /// so without the receiver for methods other than static ones
+    fn overload_of(&self, func_def: &StmtFunctionDef, ty: Type, sig: FunctionSig) -> Overload {
+        let is_static = decorators::has_decorator(&func_def.decorator_list, "staticmethod");
+        match self.current_class {
+            Some(_) if !is_static => Overload { ty: Self::bind_receiver(ty), sig: sig.without_receiver() },
+            _ => Overload { ty, sig },
+        }
+    }
+
+    /// Check an implementation takes every argument list each of its
+    /// overloads does, and returns something compatible with what they promise
+    fn check_overload_implementation(&mut self, func_def: &StmtFunctionDef, implementation: &Overload, overloads: &[Overload]) {
+        let Type::Function(impl_params, impl_ret) = Self::strip_effects(implementation.ty.clone()) else { return };
+        let impl_sig = &implementation.sig;
+        for (i, overload) in overloads.iter().enumerate() {
+            let Type::Function(params, ret) = Self::strip_effects(overload.ty.clone()) else { continue };
+            let mismatched_param = impl_sig.params.iter().zip(&impl_params).zip(&params)
+                .find(|((_, impl_ty), ty)| !self.ctx.is_subtype(ty, impl_ty));
+            let reason = if impl_sig.required() > overload.sig.required() {
+                format!("it requires {} positional arguments, the overload {}", impl_sig.required(), overload.sig.required())
+            } else if impl_sig.varargs.is_none() && impl_params.len() < params.len() {
+                format!("it takes {} positional arguments, the overload {}", impl_params.len(), params.len())
+            } else if let Some(((param, _), ty)) = mismatched_param {
+                format!("parameter '{}' doesn't accept {}", param.name, ty)
+            } else if !self.ctx.is_subtype(&ret, &impl_ret) && !self.ctx.is_subtype(&impl_ret, &ret) {
+                format!("it returns {}, the overload {}", impl_ret, ret)
+            } else {
+                continue;
+            };
+            let kind = ErrorKind::OverloadImplementation { function: func_def.name.to_string(), overload: i + 1, reason };
+            self.report(func_def, kind.code(), kind.to_string());
+        }
+    }
+
+    /// Overloads a call to `func` picks from, in declaration order
+    fn overloads_of(&self, func: &Expr) -> Option<Vec<Overload>> {
+        let key = match func {
+            Expr::Name(name) => name.id.to_string(),
+            Expr::Attribute(_) => Some(self.callee_name(func)).filter(|name| name.contains('.'))?,
+            _ => return None,
+        };
+        self.overloads.get(&key).map(|set| set.items.clone())
+    }
+
+    /// Check a call against each overload in turn, giving the return type
+    /// of the first its arguments fit; when none does, one error lists them all
+    fn call_overloaded(&mut self, call: &ExprCall, overloads: &[Overload]) -> Type {
+        let first_error = self.errors.len();
+        for overload in overloads {
+            let Type::Function(params, ret) = Self::strip_effects(overload.ty.clone()) else { continue };
+            self.check_call_args(call, &params, Some(&overload.sig));
+            if self.errors.len() == first_error {
+                return *ret;
+            }
+            self.errors.truncate(first_error);
+        }
+
+        let mut arguments: Vec<String> = call.args.iter().map(|arg| self.infer_expr(arg).to_string()).collect();
+        for keyword in &call.keywords {
+            let ty = self.infer_expr(&keyword.value);
+            arguments.push(match &keyword.arg {
+                Some(name) => format!("{}={}", name, ty),
+                None => format!("**{}", ty),
+            });
+        }
+        let function = self.callee_name(&call.func);
+        let candidates = overloads.iter().map(|overload| overload.label(&function)).collect();
+        let kind = ErrorKind::NoMatchingOverload { function, arguments, candidates };
+        self.report(call, kind.code(), kind.to_string());
+        self.ctx.fresh_var()
+    }
+
+    /// Look through attached length constraints to the underlying sequence type
+    fn strip_length(ty: Type) -> Type {
+        match ty {
+            Type::Dependent(inner, _) => *inner,
+            other => other,
+        }
+    }
+
+    /// Look through effect annotations to the underlying type
+    /// `ty` without its refinement; operations other than interval
+    /// arithmetic only know the base type
+    fn unrefined(ty: Type) -> Type {
+        match ty {
+            Type::Refinement(inner, _) => *inner,
+            other => other,
+        }
+    }
+
+    fn strip_effects(ty: Type) -> Type {
+        match ty {
+            Type::Effect(inner, _) => *inner,
+            other => other,
+        }
+    }
+
+    /// Report an annotated function whose body can end without returning,
+    /// unless the annotation admits the implicit `None`. Generators, stubs,
+    /// overloads and abstract methods are exempt
+    fn check_missing_return(&mut self, func_def: &StmtFunctionDef, return_type: &Type) {
+        let Some(returns) = &func_def.returns else { return };
+        let exempt = decorators::has_decorator(&func_def.decorator_list, "overload")
+            || decorators::has_decorator(&func_def.decorator_list, "abstractmethod");
+        if exempt
+            || self.ctx.is_subtype(&Type::None, return_type)
+            || reachability::is_stub(&func_def.body)
+            || reachability::yields(&func_def.body)
+            || !reachability::falls_through_with(&func_def.body, self)
+        {
+            return;
+        }
+        let kind = ErrorKind::MissingReturn { function: func_def.name.to_string(), expected: return_type.to_string() };
+        self.report(&**returns, kind.code(), kind.to_string());
+    }
+
+    /// Report effects a function has beyond those its `@effects(...)`
+    /// declares: at each call bringing them in, and at the definition for
+    /// those its own statements have. Mutation isn't enforced, since the
+    /// analysis counts binding a local as one
+    fn check_declared_effects(&mut self, func_def: &StmtFunctionDef, declared: &EffectSet) {
+        let key = match &self.current_class {
+            Some(class_name) => format!("{}.{}", class_name, func_def.name),
+            None => func_def.name.to_string(),
+        };
+        let Some(inferred) = self.effects.get_function_effects(&key) else { return };
+        let allowed = declared.clone().union(EffectSet::single(Effect::Mutation));
+        let undeclared = inferred.difference(&allowed);
+
+        let mut reported = EffectSet::empty();
+        for (range, effects) in self.effects.call_effects(&key) {
+            let extra = effects.difference(&allowed);
+            if extra.is_pure() {
+                continue;
+            }
+            reported = reported.union(extra.clone());
+            let kind = ErrorKind::EffectViolation {
+                function: func_def.name.to_string(),
+                declared: declared.to_string(),
+                inferred: extra.to_string(),
+            };
+            self.report_range(range, kind.code(), kind.to_string());
+        }
+
+        let rest = undeclared.difference(&reported);
+        if !rest.is_pure() {
+            let kind = ErrorKind::EffectViolation {
+                function: func_def.name.to_string(),
+                declared: declared.to_string(),
+                inferred: rest.to_string(),
+            };
+            self.report(func_def, kind.code(), kind.to_string());
+        }
+    }
+
+    /// Strict mode: every parameter (bar `self`/`cls`) and the return need annotations
+    fn require_annotations(&mut self, func_def: &StmtFunctionDef, is_async: bool) {
+        let in_class = self.current_class.is_some();
+        for (i, arg) in func_def.args.args.iter().enumerate() {
+            let receiver = in_class && i == 0 && matches!(arg.def.arg.as_str(), "self" | "cls");
+            if arg.def.annotation.is_none() && !receiver {
+                let message = format!(
+                    "Function '{}' is missing a type annotation for parameter '{}'",
+                    func_def.name, arg.def.arg
+                );
+                self.report(&arg.def, codes::MISSING_ANNOTATION, message);
+            }
+        }
+
+        if func_def.returns.is_none() && func_def.name.as_str() != "__init__" {
+            // Point at `def name` rather than the whole function
+            let start = func_def.start().to_usize();
+            let keyword = if is_async { "async def " } else { "def " };
+            let header = start..start + keyword.len() + func_def.name.len();
+            self.report_range(header, codes::MISSING_ANNOTATION, format!("Function '{}' is missing a return type annotation", func_def.name));
+        }
+    }
+
+    /// Strict mode: report `name = value` when the value's type comes from
+    /// something unannotated and so is unknown. Empty containers are left to
+    /// the `S003` lint
+    fn check_implicit_any(&mut self, value: &Expr, name: &str, value_type: &Type) {
+        if self.options.reports_implicit_any()
+            && Self::is_implicit_any(value_type)
+            && lints::empty_container(value).is_none()
+        {
+            let message = format!("Variable '{}' has an implicit Any type; annotate it or the value it comes from", name);
+            self.report(value, codes::IMPLICIT_ANY, message);
+        }
+    }
+
+    /// An unsolved type variable: what unannotated parameters, returns and
+    /// unknown names stand for, as opposed to an explicit `Any`
+    fn is_implicit_any(ty: &Type) -> bool {
+        match ty {
+            Type::Effect(inner, _) => matches!(**inner, Type::Var(_)),
+            other => matches!(other, Type::Var(_)),
+        }
+    }
+
+    /// Report imports that cross architecture layers against the rules
+    fn check_layering(&mut self, stmt: &Stmt) {
+        let (Some(rules), Some(path)) = (&self.architecture, &self.source_path) else { return };
+        for violation in rules.check_import(path, stmt) {
+            self.report_range(violation.range, codes::LAYER_VIOLATION, violation.message);
+        }
+    }
+
+    /// Bind the names `stmt` imports from project modules and stubs,
+    /// checking those modules first when no checker sharing the resolver has
+    fn bind_imports(&mut self, stmt: &Stmt) {
+        let modules = self.modules.clone().unwrap_or_else(ModuleResolver::stubs_only);
+        let path = self.source_path.clone();
+        let locate = |this: &Self, level: u32, module: &str| {
+            modules.locate(path.as_deref(), level, module, &this.options.stub_paths)
+        };
+        match stmt {
+            Stmt::Import(import) => {
+                for alias in &import.names {
+                    let name = alias.name.as_str();
+                    if let Some(asname) = &alias.asname {
+                        if let Some(module) = self.import_module(locate(self, 0, name)) {
+                            self.imported_modules.insert(asname.to_string(), module);
+                        }
+                        continue;
+                    }
+                    // `import a.b` binds `a`, and `a.b` is reached through it
+                    let parts: Vec<&str> = name.split('.').collect();
+                    for end in 1..=parts.len() {
+                        let dotted = parts[..end].join(".");
+                        if let Some(module) = self.import_module(locate(self, 0, &dotted)) {
+                            self.imported_modules.insert(dotted, module);
+                        }
+                    }
+                }
+            }
+            Stmt::ImportFrom(import) => {
+                let level = import.level.map_or(0, |level| level.to_u32());
+                let module_name = import.module.as_ref().map_or("", |m| m.as_str());
+                let mut package = None;
+                for alias in &import.names {
+                    let name = alias.name.as_str();
+                    let bound = alias.asname.as_ref().unwrap_or(&alias.name).to_string();
+                    let dotted = if module_name.is_empty() { name.to_string() } else { format!("{}.{}", module_name, name) };
+
+                    // `from pkg import submodule`
+                    if name != "*" {
+                        if let Some(submodule) = locate(self, level, &dotted) {
+                            if let Some(module) = self.import_module(Some(submodule)) {
+                                self.imported_modules.insert(bound, module);
+                            }
+                            continue;
                         }
-                        "effect" | "refine" | "dependent" | "newtype" | "recursive" => {
-                            // These are constructor calls; parse the result
-                            Type::Any
+                    }
+
+                    let package = match &package {
+                        Some(package) => package,
+                        None => package.insert(self.import_module(locate(self, level, module_name))),
+                    };
+                    let Some(package) = package.clone() else { break };
+                    if name == "*" {
+                        let public: Vec<&String> = package.types.keys().filter(|name| !name.starts_with('_')).collect();
+                        for name in public {
+                            self.bind_export(&package, name, name);
                         }
-                        _ => Type::Any,
+                    } else if let Some(module) = package.modules.get(name) {
+                        self.imported_modules.insert(bound, module.clone());
+                    } else {
+                        self.bind_export(&package, name, &bound);
                     }
-                } else {
-                    Type::Any
                 }
             }
+            _ => {}
+        }
+    }
 
-            _ => Type::Any,
+    /// Exports of the module at `origin`, checking it unless they're already
+    /// known. `None` for modules nowhere to be found, files that don't parse
+    /// and imports back into a module still being checked
+    fn import_module(&mut self, origin: Option<ModuleOrigin>) -> Option<Arc<ModuleExports>> {
+        let origin = origin?;
+        let modules = self.modules.clone().unwrap_or_else(ModuleResolver::stubs_only);
+        let key = origin.key();
+        let own = self.source_path.as_ref().map(|path| ModuleOrigin::File(path.clone()).key());
+        if self.import_stack.contains(&key) || own.as_ref() == Some(&key) {
+            self.import_cycle = true;
+            return None;
+        }
+        if let Some(exports) = modules.exports_of(&origin) {
+            self.bind_module(&exports);
+            return Some(exports);
+        }
+
+        let source = origin.source()?;
+        let parsed = parse_module(&source).ok()?;
+        let Mod::Module(ModModule { body, .. }) = &parsed else { return None };
+        debug!(module = %key, "Checking imported module");
+        let ctx = Arc::new(TypeContext::with_overrides(self.ctx.overrides().clone()));
+        let mut checker = TypeChecker::with_context(ctx).with_options(self.options.clone());
+        checker.modules = self.modules.clone();
+        checker.cancellation = self.cancellation.clone();
+        checker.stub = origin.is_stub();
+        checker.import_stack = self.import_stack.iter().cloned().chain(own).collect();
+        match &origin {
+            ModuleOrigin::File(file) => checker.set_source_file(file, &source),
+            ModuleOrigin::Bundled(_) => checker.set_source(&source),
+        }
+        // Its own diagnostics are reported when it's checked in its own right
+        checker.check(&parsed);
+
+        let exports = checker.exports(body);
+        let exports = if checker.import_cycle {
+            self.import_cycle = true;
+            Arc::new(exports)
+        } else {
+            modules.insert(&origin, exports)
+        };
+        self.bind_module(&exports);
+        Some(exports)
+    }
+
+    /// Classes of an imported module, so instances of them and calls to
+    /// their methods check as they do in the module itself
+    fn bind_module(&mut self, exports: &ModuleExports) {
+        for schema in &exports.classes {
+            if self.ctx.get_class(&schema.name).is_none() {
+                self.ctx.register_class(schema.clone());
+            }
+        }
+        for (name, sig) in exports.signatures.iter().filter(|(name, _)| name.contains('.')) {
+            self.ctx.set_signature(name.clone(), Some(sig.clone()));
+        }
+        for (class, attributes) in &exports.instance_attributes {
+            self.class_attributes.entry(class.clone()).or_insert_with(|| attributes.clone());
+        }
+    }
+
+    /// Bind `name` from an imported module as `bound`
+    fn bind_export(&mut self, exports: &ModuleExports, name: &str, bound: &str) {
+        let Some(ty) = exports.types.get(name) else { return };
+        self.ctx.set_type(bound.to_string(), ty.clone());
+        self.ctx.set_signature(bound.to_string(), exports.signatures.get(name).cloned());
+        self.ctx.set_doc(bound.to_string(), exports.docs.get(name).cloned());
+    }
+
+    /// Module an expression like `pkg.mod` names through an `import`
+    fn module_of(&self, expr: &Expr) -> Option<Arc<ModuleExports>> {
+        fn dotted(expr: &Expr) -> Option<String> {
+            match expr {
+                Expr::Name(name) => Some(name.id.to_string()),
+                Expr::Attribute(attr) => Some(format!("{}.{}", dotted(&attr.value)?, attr.attr)),
+                _ => None,
+            }
+        }
+        if self.imported_modules.is_empty() {
+            return None;
+        }
+        if let Some(module) = self.imported_modules.get(&dotted(expr)?) {
+            return Some(module.clone());
+        }
+        // A submodule its package imports, like `os.path`
+        let Expr::Attribute(attr) = expr else { return None };
+        self.module_of(&attr.value)?.modules.get(attr.attr.as_str()).cloned()
+    }
+
+    /// What the module just checked binds at the top level, as importers see it
+    fn exports(&self, body: &[Stmt]) -> ModuleExports {
+        let mut exports = ModuleExports::default();
+        let mut classes = Vec::new();
+        for name in top_level_names(body) {
+            if let Some(module) = self.imported_modules.get(&name) {
+                exports.modules.insert(name, module.clone());
+                continue;
+            }
+            let Some(ty) = self.ctx.get_type(&name) else { continue };
+            if let Type::ClassObject(class) = &ty {
+                classes.push(class.clone());
+            }
+            exports.types.insert(name.clone(), erase_vars(ty));
+            if let Some(sig) = self.ctx.get_signature(&name) {
+                exports.signatures.insert(name.clone(), erase_sig_vars(sig));
+            }
+            if let Some(doc) = self.ctx.get_doc(&name) {
+                exports.docs.insert(name, doc);
+            }
+        }
+
+        // Bases defined in the project come along, so inherited members resolve
+        let mut seen = HashSet::new();
+        while let Some(class) = classes.pop() {
+            if !seen.insert(class.clone()) {
+                continue;
+            }
+            let Some(schema) = self.ctx.get_class(&class) else { continue };
+            classes.extend(schema.bases.iter().cloned());
+            let prefix = format!("{}.", class);
+            for (name, sig) in self.ctx.signatures() {
+                if name.starts_with(&prefix) {
+                    exports.signatures.insert(name, erase_sig_vars(sig));
+                }
+            }
+            if let Some(attributes) = self.class_attributes.get(&class) {
+                let attributes = attributes.iter().map(|(name, ty)| (name.clone(), erase_vars(ty.clone()))).collect();
+                exports.instance_attributes.insert(class.clone(), attributes);
+            }
+            exports.classes.push((*schema).clone());
+        }
+        exports
+    }
+
+    /// Record the project files this module imports when it's loaded, and
+    /// report the import cycle through it at the import that closes it.
+    /// Each cycle is reported once, by the module it ends with, whichever
+    /// of its modules is checked
+    fn check_import_cycle(&mut self, body: &[Stmt]) {
+        let (Some(modules), Some(path), Some(source)) = (&self.modules, &self.source_path, &self.source) else { return };
+        let imports: Vec<(&Stmt, Vec<PathBuf>)> = load_time_imports(body).into_iter()
+            .map(|stmt| (stmt, modules.targets(path, stmt)))
+            .collect();
+        let targets: Vec<PathBuf> = imports.iter().flat_map(|(_, targets)| targets.iter().cloned()).collect();
+        modules.record_imports(path, source, &targets);
+        // Modules checked for their importers report nothing
+        if !self.import_stack.is_empty() {
+            return;
+        }
+
+        let Some(chain) = modules.import_cycle(path) else { return };
+        let key = |file: &Path| ModuleOrigin::File(file.to_path_buf()).key();
+        let (Some(first), Some(last)) = (chain.first(), chain.last()) else { return };
+        if key(last) != key(path) {
+            return;
         }
+        let Some((closing, _)) = imports.iter().find(|(_, targets)| targets.iter().any(|target| key(target) == key(first))) else { return };
+        let range = closing.start().to_usize()..closing.end().to_usize();
+        let names = chain.iter().chain([first]).map(|file| modules.module_name(file)).collect();
+        let kind = ErrorKind::CircularDependency { chain: names };
+        self.report_range(range, kind.code(), kind.to_string());
+    }
+
+    /// Record this module's exports for its importers, unless it's on an
+    /// import cycle and they depend on which module of it was checked first.
+    /// Imported modules are recorded by their importer
+    fn publish_exports(&mut self, body: &[Stmt]) {
+        if self.import_cycle || !self.import_stack.is_empty() {
+            return;
+        }
+        if let (Some(modules), Some(path)) = (&self.modules, &self.source_path) {
+            modules.insert(&ModuleOrigin::File(path.clone()), self.exports(body));
+        }
+    }
+
+    /// Report dict keys and set members the runtime would refuse to hash
+    fn require_hashable(&mut self, node: &Expr, ty: &Type) {
+        if let Some(name) = ConstraintSolver::unhashable_name(ty) {
+            self.report(node, codes::UNHASHABLE, format!("unhashable type: '{}'", name));
+        }
+    }
+
+    /// Strict optional: report `operation` on a name that may be `None`,
+    /// and go on with the rest of its type. Only names are checked, as
+    /// only names can be narrowed
+    fn require_not_none(&mut self, node: &Expr, ty: Type, operation: impl FnOnce() -> String) -> Type {
+        let Expr::Name(name) = node else { return ty };
+        if !self.options.checks_optional() {
+            return ty;
+        }
+        let Some(without) = ConditionAnalyzer::without_none(&ty) else { return ty };
+
+        let kind = ErrorKind::PossiblyNone { name: name.id.to_string(), operation: operation() };
+        let suggestion = format!("Add an `if {} is not None:` check first", name.id);
+        let range = node.start().to_usize()..node.end().to_usize();
+        self.report_suggesting(range, kind.code(), kind.to_string(), vec![suggestion]);
+        without
     }
 
     /// Get effects for a function
@@ -904,10 +5466,36 @@ impl TypeChecker {
     }
 
     /// Get type for a name
+    /// Docstring of a module-level function or class
+    pub fn get_docstring(&self, name: &str) -> Option<Docstring> {
+        self.ctx.get_doc(name)
+    }
+
+    pub fn module_docstring(&self) -> Option<&Docstring> {
+        self.module_doc.as_ref()
+    }
+
     pub fn get_type(&self, name: &str) -> Option<Type> {
         self.ctx.get_type(name)
     }
 
+    /// Members of values of type `ty`, sorted by name: those its classes
+    /// declare, and for instances of module classes the attributes their
+    /// methods assign to `self`, as properties
+    pub fn members(&self, ty: &Type) -> Vec<(String, MemberKind)> {
+        let mut members = self.ctx.get_members(ty);
+        if let Type::Class(class) = Self::strip_effects(ty.clone()) {
+            let owners = self.class_attributes.iter().filter(|(owner, _)| self.ctx.is_subclass(&class, owner));
+            for (name, attr_ty) in owners.flat_map(|(_, attributes)| attributes) {
+                if !members.iter().any(|(seen, _)| seen == name) {
+                    members.push((name.clone(), MemberKind::Property(attr_ty.clone())));
+                }
+            }
+            members.sort_by(|a, b| a.0.cmp(&b.0));
+        }
+        members
+    }
+
     /// Check if a recursive type is well-formed
     pub fn check_recursive_type(&mut self, ty: &Type) -> bool {
         self.advanced.is_productive(ty)
@@ -943,3 +5531,212 @@ impl Default for TypeChecker {
         Self::new()
     }
 }
+
+impl reachability::Flow for TypeChecker {
+    fn exhaustive(&self, stmt: &StmtMatch) -> bool {
+        self.exhaustive_matches.contains(&stmt.start().to_usize())
+    }
+
+    fn never_returns(&self, call: &ExprCall) -> bool {
+        self.never_calls.contains(&call.start().to_usize())
+    }
+}
+
+/// The constraints `solver` has pending from the `first`th on, with their sites
+fn pending_constraints(solver: &ConstraintSolver, first: usize) -> Vec<(Constraint, Option<Range<usize>>)> {
+    solver.pending()[first..].iter().cloned().zip(solver.pending_sites()[first..].iter().cloned()).collect()
+}
+
+fn shift(range: &Range<usize>, delta: isize) -> Range<usize> {
+    range.start.saturating_add_signed(delta)..range.end.saturating_add_signed(delta)
+}
+
+/// How Python spells a binary operator
+fn operator_symbol(op: Operator) -> &'static str {
+    match op {
+        Operator::Add => "+",
+        Operator::Sub => "-",
+        Operator::Mult => "*",
+        Operator::MatMult => "@",
+        Operator::Div => "/",
+        Operator::Mod => "%",
+        Operator::Pow => "**",
+        Operator::LShift => "<<",
+        Operator::RShift => ">>",
+        Operator::BitOr => "|",
+        Operator::BitXor => "^",
+        Operator::BitAnd => "&",
+        Operator::FloorDiv => "//",
+    }
+}
+
+/// Methods implementing a binary operator: the left operand's, then the
+/// reflected one tried on the right operand, e.g. `__add__` and `__radd__`
+fn operator_dunders(op: Operator) -> (&'static str, &'static str) {
+    match op {
+        Operator::Add => ("__add__", "__radd__"),
+        Operator::Sub => ("__sub__", "__rsub__"),
+        Operator::Mult => ("__mul__", "__rmul__"),
+        Operator::MatMult => ("__matmul__", "__rmatmul__"),
+        Operator::Div => ("__truediv__", "__rtruediv__"),
+        Operator::Mod => ("__mod__", "__rmod__"),
+        Operator::Pow => ("__pow__", "__rpow__"),
+        Operator::LShift => ("__lshift__", "__rlshift__"),
+        Operator::RShift => ("__rshift__", "__rrshift__"),
+        Operator::BitOr => ("__or__", "__ror__"),
+        Operator::BitXor => ("__xor__", "__rxor__"),
+        Operator::BitAnd => ("__and__", "__rand__"),
+        Operator::FloorDiv => ("__floordiv__", "__rfloordiv__"),
+    }
+}
+
+/// Symbol and methods of an ordering comparison; the right operand's
+/// method is the mirrored one, `__gt__` for `<`
+fn comparison_dunders(op: CmpOp) -> Option<(&'static str, &'static str, &'static str)> {
+    match op {
+        CmpOp::Lt => Some(("<", "__lt__", "__gt__")),
+        CmpOp::LtE => Some(("<=", "__le__", "__ge__")),
+        CmpOp::Gt => Some((">", "__gt__", "__lt__")),
+        CmpOp::GtE => Some((">=", "__ge__", "__le__")),
+        _ => None,
+    }
+}
+
+/// Yield, send and return types of a generator annotation: `Generator[Y, S, R]`,
+/// `Iterator[Y]` or `Iterable[Y]`, or their async counterparts
+fn generator_parts(annotation: &Type) -> Option<(Type, Type, Type)> {
+    let Type::Generic(name, args) = annotation else { return None };
+    match (name.as_str(), args.as_slice()) {
+        ("Generator", [yielded, sent, returned]) => Some((yielded.clone(), sent.clone(), returned.clone())),
+        ("AsyncGenerator", [yielded, sent]) => Some((yielded.clone(), sent.clone(), Type::None)),
+        ("Iterator" | "Iterable" | "AsyncIterator" | "AsyncIterable", [yielded]) => {
+            Some((yielded.clone(), Type::None, Type::None))
+        }
+        _ => None,
+    }
+}
+
+/// Names of the classes `class_def` derives from, without `Generic` and
+/// without the parameters of subscripted bases
+fn class_bases(class_def: &StmtClassDef) -> Vec<String> {
+    class_def.bases.iter()
+        .filter(|base| !generics::is_generic_base(base))
+        .map(|base| match base {
+            Expr::Subscript(subscript) => &*subscript.value,
+            base => base,
+        })
+        .filter_map(|base| match base {
+            Expr::Name(name) => Some(name.id.to_string()),
+            Expr::Attribute(attr) => Some(attr.attr.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// `Protocol` or `Protocol[T]`, from `typing` or `typing_extensions`
+fn is_protocol_base(base: &Expr) -> bool {
+    match base {
+        Expr::Name(name) => name.id.as_str() == "Protocol",
+        Expr::Attribute(attr) => attr.attr.as_str() == "Protocol",
+        Expr::Subscript(subscript) => is_protocol_base(&subscript.value),
+        _ => false,
+    }
+}
+
+/// `total=False` on a `TypedDict` makes its own keys optional
+fn typed_dict_total(keywords: &[Keyword]) -> bool {
+    !keywords.iter().any(|keyword| {
+        keyword.arg.as_ref().is_some_and(|arg| arg.as_str() == "total")
+            && matches!(&keyword.value, Expr::Constant(c) if matches!(c.value, Constant::Bool(false)))
+    })
+}
+
+/// Whether a `TypedDict` key declared as `annotation` must be present;
+/// `Required[...]` and `NotRequired[...]` override the class's `total`
+fn key_required(annotation: &Expr, total: bool) -> bool {
+    let Expr::Subscript(subscript) = annotation else { return total };
+    let wrapper = match &*subscript.value {
+        Expr::Name(name) => name.id.as_str(),
+        Expr::Attribute(attr) => attr.attr.as_str(),
+        _ => return total,
+    };
+    match wrapper {
+        "Required" => true,
+        "NotRequired" => false,
+        _ => total,
+    }
+}
+
+fn str_constant(expr: &Expr) -> Option<&str> {
+    match expr {
+        Expr::Constant(constant) => match &constant.value {
+            Constant::Str(value) => Some(value.as_str()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Attributes assigned through `self` in a method body, with the value and
+/// annotation of each assignment, in order
+fn self_assignments(body: &[Stmt]) -> Vec<(&str, Option<&Expr>, Option<&Expr>)> {
+    fn self_attr(target: &Expr) -> Option<&str> {
+        match target {
+            Expr::Attribute(attr) if matches!(&*attr.value, Expr::Name(base) if base.id.as_str() == "self") => Some(attr.attr.as_str()),
+            _ => None,
+        }
+    }
+
+    let mut found = Vec::new();
+    for stmt in body {
+        match stmt {
+            Stmt::Assign(assign) => {
+                found.extend(assign.targets.iter().filter_map(self_attr).map(|attr| (attr, Some(&*assign.value), None)));
+            }
+            Stmt::AnnAssign(ann_assign) => {
+                if let Some(attr) = self_attr(&ann_assign.target) {
+                    found.push((attr, ann_assign.value.as_deref(), Some(&*ann_assign.annotation)));
+                }
+            }
+            Stmt::AugAssign(aug_assign) => found.extend(self_attr(&aug_assign.target).map(|attr| (attr, None, None))),
+            Stmt::If(if_stmt) => {
+                found.extend(self_assignments(&if_stmt.body));
+                found.extend(self_assignments(&if_stmt.orelse));
+            }
+            Stmt::For(for_stmt) => {
+                found.extend(self_assignments(&for_stmt.body));
+                found.extend(self_assignments(&for_stmt.orelse));
+            }
+            Stmt::While(while_stmt) => {
+                found.extend(self_assignments(&while_stmt.body));
+                found.extend(self_assignments(&while_stmt.orelse));
+            }
+            Stmt::With(with_stmt) => found.extend(self_assignments(&with_stmt.body)),
+            Stmt::Try(try_stmt) => {
+                found.extend(self_assignments(&try_stmt.body));
+                for handler in &try_stmt.handlers {
+                    let ExceptHandler::ExceptHandler(handler) = handler;
+                    found.extend(self_assignments(&handler.body));
+                }
+                found.extend(self_assignments(&try_stmt.orelse));
+                found.extend(self_assignments(&try_stmt.finalbody));
+            }
+            _ => {}
+        }
+    }
+    found
+}
+
+/// An `async def` viewed as a plain definition, to share its checks
+fn as_function_def(func_def: &StmtAsyncFunctionDef) -> StmtFunctionDef {
+    StmtFunctionDef {
+        range: func_def.range,
+        name: func_def.name.clone(),
+        args: func_def.args.clone(),
+        body: func_def.body.clone(),
+        decorator_list: func_def.decorator_list.clone(),
+        returns: func_def.returns.clone(),
+        type_comment: func_def.type_comment.clone(),
+        type_params: func_def.type_params.clone(),
+    }
+}
@@ -0,0 +1,400 @@
+//! Constant evaluation for annotation expressions
+//!
+//! Folds the small expressions that show up inside annotations - arithmetic
+//! and string concatenation over literals, module-level `Final` constants and
+//! enum members - so `Bounded(0, 2 * KB)` and `Literal[STATUS_OK]` mean what
+//! they say. Anything that could run code (calls, comprehensions, subscripts)
+//! is refused, and reference chains are cut off at `MAX_DEPTH`.
+
+use crate::compiler::types::{DependentConstraint, Type};
+use num_traits::ToPrimitive;
+use rustpython_parser::ast::*;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Deepest nesting of operators and constant references that is folded
+pub const MAX_DEPTH: usize = 32;
+
+/// Longest string or bytes value a fold may produce
+const MAX_LEN: usize = 4096;
+
+/// Base classes that make a class an enum
+const ENUM_BASES: &[&str] = &["Enum", "IntEnum", "StrEnum", "Flag", "IntFlag"];
+
+/// A folded value
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConstValue {
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Bytes(Vec<u8>),
+    Bool(bool),
+    None,
+    EnumMember { class: String, member: String },
+}
+
+impl ConstValue {
+    /// The type of exactly this value, as used for `Literal[...]` members
+    pub fn literal_type(&self) -> Type {
+        let base = match self {
+            ConstValue::Int(_) => Type::Int,
+            ConstValue::Float(_) => Type::Float,
+            ConstValue::Str(_) => Type::Str,
+            ConstValue::Bytes(_) => Type::Bytes,
+            ConstValue::Bool(_) => Type::Bool,
+            ConstValue::None => return Type::None,
+            ConstValue::EnumMember { class, .. } => Type::Class(class.clone()),
+        };
+        Type::Dependent(Box::new(base), DependentConstraint::ValueEq(self.to_string()))
+    }
+
+    /// Whether `ty` admits exactly this value; `None` when `ty` isn't a
+    /// literal type or a union of them
+    pub fn matches_literal(&self, ty: &Type) -> Option<bool> {
+        match ty {
+            Type::Dependent(_, DependentConstraint::ValueEq(value)) => Some(*value == self.to_string()),
+            Type::None => Some(*self == ConstValue::None),
+            Type::Union(members) => members.iter()
+                .map(|member| self.matches_literal(member))
+                .try_fold(false, |any, fits| Some(any || fits?)),
+            _ => None,
+        }
+    }
+
+    /// Integer value, with `bool` counting as `int` like it does in Python
+    fn as_int(&self) -> Option<i64> {
+        match self {
+            ConstValue::Int(n) => Some(*n),
+            ConstValue::Bool(b) => Some(*b as i64),
+            _ => None,
+        }
+    }
+
+    fn as_float(&self) -> Option<f64> {
+        match self {
+            ConstValue::Float(f) => Some(*f),
+            other => other.as_int().map(|n| n as f64),
+        }
+    }
+
+    fn truthy(&self) -> bool {
+        match self {
+            ConstValue::Int(n) => *n != 0,
+            ConstValue::Float(f) => *f != 0.0,
+            ConstValue::Str(s) => !s.is_empty(),
+            ConstValue::Bytes(b) => !b.is_empty(),
+            ConstValue::Bool(b) => *b,
+            ConstValue::None => false,
+            ConstValue::EnumMember { .. } => true,
+        }
+    }
+}
+
+/// Python spelling of the value
+impl fmt::Display for ConstValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstValue::Int(n) => write!(f, "{}", n),
+            ConstValue::Float(x) => write!(f, "{:?}", x),
+            ConstValue::Str(s) => write!(f, "'{}'", s.replace('\\', "\\\\").replace('\'', "\\'")),
+            ConstValue::Bytes(b) => write!(f, "b'{}'", b.escape_ascii()),
+            ConstValue::Bool(true) => write!(f, "True"),
+            ConstValue::Bool(false) => write!(f, "False"),
+            ConstValue::None => write!(f, "None"),
+            ConstValue::EnumMember { class, member } => write!(f, "{}.{}", class, member),
+        }
+    }
+}
+
+/// Why an expression couldn't be folded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotConstant {
+    pub reason: String,
+}
+
+impl NotConstant {
+    fn new(reason: impl Into<String>) -> Self {
+        Self { reason: reason.into() }
+    }
+}
+
+impl fmt::Display for NotConstant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.reason)
+    }
+}
+
+/// Module-level constants that annotations may refer to
+#[derive(Debug, Clone, Default)]
+pub struct ConstEnv {
+    /// `NAME: Final = value`, unevaluated
+    finals: HashMap<String, Expr>,
+    /// Enum class -> member names, in declaration order
+    enums: HashMap<String, Vec<String>>,
+}
+
+impl ConstEnv {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Collect `Final` assignments and enum classes from a module body
+    pub fn collect(body: &[Stmt]) -> Self {
+        let mut env = Self::new();
+        for stmt in body {
+            match stmt {
+                Stmt::AnnAssign(ann) if is_final(&ann.annotation) => {
+                    if let (Expr::Name(name), Some(value)) = (&*ann.target, &ann.value) {
+                        env.finals.insert(name.id.to_string(), (**value).clone());
+                    }
+                }
+                Stmt::ClassDef(class) if class.bases.iter().any(is_enum_base) => {
+                    env.enums.insert(class.name.to_string(), enum_members(&class.body));
+                }
+                _ => {}
+            }
+        }
+        env
+    }
+
+    pub fn is_enum(&self, class: &str) -> bool {
+        self.enums.contains_key(class)
+    }
+
+    pub fn is_enum_member(&self, class: &str, member: &str) -> bool {
+        self.enums.get(class).is_some_and(|members| members.iter().any(|m| m == member))
+    }
+
+    /// Every member of the enum `class`, if it is one
+    pub fn enum_members(&self, class: &str) -> Option<Vec<ConstValue>> {
+        let members = self.enums.get(class)?;
+        Some(members.iter()
+            .map(|member| ConstValue::EnumMember { class: class.to_string(), member: member.clone() })
+            .collect())
+    }
+
+    pub fn eval(&self, expr: &Expr) -> Result<ConstValue, NotConstant> {
+        self.eval_at(expr, 0)
+    }
+
+    fn eval_at(&self, expr: &Expr, depth: usize) -> Result<ConstValue, NotConstant> {
+        if depth > MAX_DEPTH {
+            return Err(NotConstant::new("constant expression nests too deeply"));
+        }
+        match expr {
+            Expr::Constant(constant) => literal(&constant.value),
+            Expr::Name(name) => match self.finals.get(name.id.as_str()) {
+                Some(value) => self.eval_at(value, depth + 1),
+                None => Err(NotConstant::new(format!("'{}' is not a Final or enum constant", name.id))),
+            },
+            Expr::Attribute(attr) => {
+                let member = match &*attr.value {
+                    Expr::Name(class) if self.is_enum_member(&class.id, &attr.attr) => {
+                        Some(ConstValue::EnumMember { class: class.id.to_string(), member: attr.attr.to_string() })
+                    }
+                    _ => None,
+                };
+                member.ok_or_else(|| NotConstant::new(format!("'{}' is not an enum member", attribute_path(attr))))
+            }
+            Expr::UnaryOp(unary) => unary_op(unary.op, self.eval_at(&unary.operand, depth + 1)?),
+            Expr::BinOp(binop) => {
+                let left = self.eval_at(&binop.left, depth + 1)?;
+                let right = self.eval_at(&binop.right, depth + 1)?;
+                binary_op(binop.op, left, right)
+            }
+            Expr::Call(_) => Err(NotConstant::new("calls are not constant expressions")),
+            _ => Err(NotConstant::new("expression is not constant")),
+        }
+    }
+}
+
+fn literal(constant: &Constant) -> Result<ConstValue, NotConstant> {
+    Ok(match constant {
+        Constant::Int(n) => ConstValue::Int(n.to_i64().ok_or_else(|| NotConstant::new("integer is too large"))?),
+        Constant::Float(f) => ConstValue::Float(*f),
+        Constant::Str(s) => ConstValue::Str(s.clone()),
+        Constant::Bytes(b) => ConstValue::Bytes(b.clone()),
+        Constant::Bool(b) => ConstValue::Bool(*b),
+        Constant::None => ConstValue::None,
+        _ => return Err(NotConstant::new("only int, float, str, bytes, bool and None constants are supported")),
+    })
+}
+
+fn unary_op(op: UnaryOp, operand: ConstValue) -> Result<ConstValue, NotConstant> {
+    match (op, &operand) {
+        (UnaryOp::Not, _) => Ok(ConstValue::Bool(!operand.truthy())),
+        (UnaryOp::USub, ConstValue::Float(f)) => Ok(ConstValue::Float(-f)),
+        (UnaryOp::UAdd, ConstValue::Float(_)) => Ok(operand),
+        _ => {
+            let n = operand.as_int()
+                .ok_or_else(|| NotConstant::new(format!("bad operand for unary operator: {}", operand)))?;
+            match op {
+                UnaryOp::USub => n.checked_neg().map(ConstValue::Int).ok_or_else(overflow),
+                UnaryOp::Invert => Ok(ConstValue::Int(!n)),
+                _ => Ok(ConstValue::Int(n)),
+            }
+        }
+    }
+}
+
+fn binary_op(op: Operator, left: ConstValue, right: ConstValue) -> Result<ConstValue, NotConstant> {
+    match (&left, &right) {
+        (ConstValue::Str(a), ConstValue::Str(b)) if op == Operator::Add => bounded_str(a.clone() + b),
+        (ConstValue::Bytes(a), ConstValue::Bytes(b)) if op == Operator::Add => {
+            bounded_bytes([a.as_slice(), b.as_slice()].concat())
+        }
+        (ConstValue::Str(s), n) | (n, ConstValue::Str(s)) if op == Operator::Mult && n.as_int().is_some() => {
+            bounded_str(s.repeat(repeat_count(s.len(), n.as_int().unwrap_or(0))?))
+        }
+        (ConstValue::Bytes(b), n) | (n, ConstValue::Bytes(b)) if op == Operator::Mult && n.as_int().is_some() => {
+            bounded_bytes(b.repeat(repeat_count(b.len(), n.as_int().unwrap_or(0))?))
+        }
+        _ => match (left.as_int(), right.as_int()) {
+            (Some(a), Some(b)) => int_op(op, a, b),
+            _ => match (left.as_float(), right.as_float()) {
+                (Some(a), Some(b)) => float_op(op, a, b),
+                _ => Err(NotConstant::new(format!("unsupported operands {} and {}", left, right))),
+            },
+        },
+    }
+}
+
+fn int_op(op: Operator, a: i64, b: i64) -> Result<ConstValue, NotConstant> {
+    let value = match op {
+        Operator::Add => a.checked_add(b),
+        Operator::Sub => a.checked_sub(b),
+        Operator::Mult => a.checked_mul(b),
+        Operator::Div => return float_op(op, a as f64, b as f64),
+        Operator::FloorDiv | Operator::Mod if b == 0 => return Err(NotConstant::new("division by zero")),
+        // Python rounds toward negative infinity
+        Operator::FloorDiv => a.checked_div(b).map(|q| if a % b != 0 && (a < 0) != (b < 0) { q - 1 } else { q }),
+        Operator::Mod => a.checked_rem(b).map(|r| if r != 0 && (r < 0) != (b < 0) { r + b } else { r }),
+        Operator::Pow if b < 0 => return float_op(op, a as f64, b as f64),
+        Operator::Pow => u32::try_from(b).ok().and_then(|b| a.checked_pow(b)),
+        Operator::LShift if b < 0 => return Err(NotConstant::new("negative shift count")),
+        Operator::LShift => u32::try_from(b).ok().and_then(|b| 2i64.checked_pow(b)).and_then(|scale| a.checked_mul(scale)),
+        Operator::RShift if b < 0 => return Err(NotConstant::new("negative shift count")),
+        Operator::RShift => Some(a >> b.min(63)),
+        Operator::BitOr => Some(a | b),
+        Operator::BitAnd => Some(a & b),
+        Operator::BitXor => Some(a ^ b),
+        Operator::MatMult => return Err(NotConstant::new("unsupported operator @")),
+    };
+    value.map(ConstValue::Int).ok_or_else(overflow)
+}
+
+fn float_op(op: Operator, a: f64, b: f64) -> Result<ConstValue, NotConstant> {
+    let value = match op {
+        Operator::Add => a + b,
+        Operator::Sub => a - b,
+        Operator::Mult => a * b,
+        Operator::Div | Operator::FloorDiv | Operator::Mod if b == 0.0 => {
+            return Err(NotConstant::new("division by zero"));
+        }
+        Operator::Div => a / b,
+        Operator::FloorDiv => (a / b).floor(),
+        Operator::Mod => a - b * (a / b).floor(),
+        Operator::Pow => a.powf(b),
+        _ => return Err(NotConstant::new("unsupported operator for float operands")),
+    };
+    Ok(ConstValue::Float(value))
+}
+
+fn repeat_count(len: usize, times: i64) -> Result<usize, NotConstant> {
+    let times = usize::try_from(times.max(0)).map_err(|_| overflow())?;
+    if len.saturating_mul(times) > MAX_LEN {
+        return Err(NotConstant::new(format!("constant is longer than {} characters", MAX_LEN)));
+    }
+    Ok(times)
+}
+
+fn bounded_str(s: String) -> Result<ConstValue, NotConstant> {
+    if s.len() > MAX_LEN {
+        return Err(NotConstant::new(format!("constant is longer than {} characters", MAX_LEN)));
+    }
+    Ok(ConstValue::Str(s))
+}
+
+fn bounded_bytes(b: Vec<u8>) -> Result<ConstValue, NotConstant> {
+    if b.len() > MAX_LEN {
+        return Err(NotConstant::new(format!("constant is longer than {} bytes", MAX_LEN)));
+    }
+    Ok(ConstValue::Bytes(b))
+}
+
+fn overflow() -> NotConstant {
+    NotConstant::new("integer overflow")
+}
+
+/// `Final`, `Final[T]`, `typing.Final` or `typing.Final[T]`
+pub fn is_final(annotation: &Expr) -> bool {
+    match annotation {
+        Expr::Name(name) => name.id.as_str() == "Final",
+        Expr::Attribute(attr) => attr.attr.as_str() == "Final",
+        Expr::Subscript(subscript) => is_final(&subscript.value),
+        _ => false,
+    }
+}
+
+fn is_enum_base(base: &Expr) -> bool {
+    match base {
+        Expr::Name(name) => ENUM_BASES.contains(&name.id.as_str()),
+        Expr::Attribute(attr) => ENUM_BASES.contains(&attr.attr.as_str()),
+        _ => false,
+    }
+}
+
+/// Names bound at the top of an enum body, minus private and dunder names
+fn enum_members(body: &[Stmt]) -> Vec<String> {
+    let target_name = |target: &Expr| match target {
+        Expr::Name(name) if !name.id.starts_with('_') => Some(name.id.to_string()),
+        _ => None,
+    };
+    body.iter()
+        .filter_map(|stmt| match stmt {
+            Stmt::Assign(assign) if assign.targets.len() == 1 => target_name(&assign.targets[0]),
+            Stmt::AnnAssign(ann) if ann.value.is_some() => target_name(&ann.target),
+            _ => None,
+        })
+        .collect()
+}
+
+fn attribute_path(attr: &ExprAttribute) -> String {
+    match &*attr.value {
+        Expr::Name(name) => format!("{}.{}", name.id, attr.attr),
+        Expr::Attribute(inner) => format!("{}.{}", attribute_path(inner), attr.attr),
+        _ => attr.attr.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::frontend::parse_module;
+
+    fn eval(source: &str, expr: &str) -> Result<ConstValue, NotConstant> {
+        let Mod::Module(module) = parse_module(source).unwrap() else { unreachable!() };
+        let Mod::Module(expr_module) = parse_module(expr).unwrap() else { unreachable!() };
+        let Stmt::Expr(expr) = &expr_module.body[0] else { unreachable!() };
+        ConstEnv::collect(&module.body).eval(&expr.value)
+    }
+
+    #[test]
+    fn test_folds_python_arithmetic() {
+        assert_eq!(eval("", "-7 // 2"), Ok(ConstValue::Int(-4)));
+        assert_eq!(eval("", "-7 % 2"), Ok(ConstValue::Int(1)));
+        assert_eq!(eval("", "1 << 10"), Ok(ConstValue::Int(1024)));
+        assert_eq!(eval("", "True + 1"), Ok(ConstValue::Int(2)));
+        assert_eq!(eval("", "'ab' * 2 + 'c'"), Ok(ConstValue::Str("ababc".to_string())));
+        assert_eq!(eval("", "7 / 2"), Ok(ConstValue::Float(3.5)));
+        assert_eq!(eval("", "2 ** 64"), Err(NotConstant::new("integer overflow")));
+        assert_eq!(eval("", "1 // 0"), Err(NotConstant::new("division by zero")));
+        assert!(eval("", "'x' * 10000").is_err());
+    }
+
+    #[test]
+    fn test_reference_cycles_hit_the_depth_cap() {
+        let source = "from typing import Final\nA: Final = B + 1\nB: Final = A + 1\n";
+        assert_eq!(eval(source, "A"), Err(NotConstant::new("constant expression nests too deeply")));
+    }
+}
@@ -1,6 +1,9 @@
-use crate::compiler::types::{Type, TypeContext};
+use crate::compiler::types::{Type, TypeContext, TypeRef, TypeTable};
 use crate::compiler::errors::{TypeError, SourceLocation};
-use std::collections::HashMap;
+use crate::compiler::errors::ErrorKind;
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap};
+use std::ops::Range;
 use std::sync::Arc;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -33,10 +36,99 @@ pub enum Constraint {
     Hashable(Type),
 }
 
+impl Constraint {
+    /// The types the constraint relates
+    pub fn types(&self) -> Vec<&Type> {
+        match self {
+            Constraint::Subtype(a, b) | Constraint::Equal(a, b) | Constraint::Bounded(a, b) => vec![a, b],
+            Constraint::HasAttribute(ty, _, attr_ty) => vec![ty, attr_ty],
+            Constraint::Callable(ty, params, ret) => std::iter::once(ty).chain(params).chain([ret]).collect(),
+            Constraint::Protocol(ty, methods) => std::iter::once(ty).chain(methods.iter().map(|(_, ty)| ty)).collect(),
+            Constraint::Numeric(ty) | Constraint::Comparable(ty) | Constraint::Hashable(ty) => vec![ty],
+        }
+    }
+
+    /// The constraint with `f` applied to the types it relates
+    pub fn map_types(&self, f: impl Fn(&Type) -> Type) -> Self {
+        match self {
+            Constraint::Subtype(a, b) => Constraint::Subtype(f(a), f(b)),
+            Constraint::Equal(a, b) => Constraint::Equal(f(a), f(b)),
+            Constraint::HasAttribute(ty, attr, attr_ty) => Constraint::HasAttribute(f(ty), attr.clone(), f(attr_ty)),
+            Constraint::Callable(ty, params, ret) => Constraint::Callable(f(ty), params.iter().map(&f).collect(), f(ret)),
+            Constraint::Protocol(ty, methods) => {
+                Constraint::Protocol(f(ty), methods.iter().map(|(name, ty)| (name.clone(), f(ty))).collect())
+            }
+            Constraint::Bounded(a, b) => Constraint::Bounded(f(a), f(b)),
+            Constraint::Numeric(ty) => Constraint::Numeric(f(ty)),
+            Constraint::Comparable(ty) => Constraint::Comparable(f(ty)),
+            Constraint::Hashable(ty) => Constraint::Hashable(f(ty)),
+        }
+    }
+}
+
+/// A solving error with the site of the constraint it came from
+pub type LocatedError = (TypeError, Option<Range<usize>>);
+
+/// Call `f` with each inference variable `ty` mentions
+fn visit_vars(ty: &Type, f: &mut impl FnMut(u64)) {
+    match ty {
+        Type::Var(id) => f(*id),
+        Type::List(inner) | Type::Set(inner) | Type::VarTuple(inner) => visit_vars(inner, f),
+        Type::Effect(inner, _) | Type::Refinement(inner, _) | Type::Dependent(inner, _) | Type::Nominal(_, inner) => {
+            visit_vars(inner, f)
+        }
+        Type::Dict(key, value) => {
+            visit_vars(key, f);
+            visit_vars(value, f);
+        }
+        Type::Tuple(types) | Type::Union(types) | Type::Intersection(types) | Type::Generic(_, types) => {
+            types.iter().for_each(|ty| visit_vars(ty, f));
+        }
+        Type::Function(params, ret) => {
+            params.iter().for_each(|ty| visit_vars(ty, f));
+            visit_vars(ret, f);
+        }
+        Type::Conditional { then_type, else_type, .. } => {
+            visit_vars(then_type, f);
+            visit_vars(else_type, f);
+        }
+        _ => {}
+    }
+}
+
+/// Solves constraints by unifying type variables with what they must
+/// equal, and bounding them by what flows in and out of them
+///
+/// Variables unified with each other share a class whose root stands for
+/// them all; a root is solved once it's unified with anything else, or
+/// when solving ends with its bounds known. Bounds are kept only for
+/// unsolved roots and checked against what the root is solved to.
+///
+/// Constraints may carry the byte range of the node they came from, so
+/// what fails can be reported there (`solve_located`).
 pub struct ConstraintSolver {
     constraints: Vec<Constraint>,
-    bounds: HashMap<u64, Type>, // Type variable bounds
+    /// Site of each pending constraint, by position
+    sites: Vec<Option<Range<usize>>>,
+    /// Upper bounds of unsolved roots, interned in `table`
+    bounds: HashMap<u64, TypeRef>,
+    /// Lower bounds of unsolved roots: the union of the types flowing in
+    lower_bounds: HashMap<u64, TypeRef>,
+    table: TypeTable,
+    /// Variable → the one it was unified with; roots aren't keys
+    parents: HashMap<u64, u64>,
+    /// Roots → the types they're solved to
+    solutions: HashMap<u64, Type>,
+    /// `(sub, sup)` pairs of variables, one flowing into the other
+    flows: Vec<(u64, u64)>,
+    /// Variables the constraints solved so far mention
+    seen: BTreeSet<u64>,
+    /// Site of the first constraint each variable appeared in, for the
+    /// errors solving its bounds finds
+    var_sites: HashMap<u64, Range<usize>>,
     errors: Vec<TypeError>,
+    /// Site of each error, by position
+    error_sites: Vec<Option<Range<usize>>>,
     ctx: Option<Arc<TypeContext>>,
 }
 
@@ -44,30 +136,91 @@ impl ConstraintSolver {
     pub fn new() -> Self {
         Self {
             constraints: Vec::new(),
+            sites: Vec::new(),
             bounds: HashMap::new(),
+            lower_bounds: HashMap::new(),
+            table: TypeTable::new(),
+            parents: HashMap::new(),
+            solutions: HashMap::new(),
+            flows: Vec::new(),
+            seen: BTreeSet::new(),
+            var_sites: HashMap::new(),
             errors: Vec::new(),
+            error_sites: Vec::new(),
             ctx: None,
         }
     }
 
     pub fn with_context(ctx: Arc<TypeContext>) -> Self {
-        Self {
-            constraints: Vec::new(),
-            bounds: HashMap::new(),
-            errors: Vec::new(),
-            ctx: Some(ctx),
-        }
+        Self { ctx: Some(ctx), ..Self::new() }
     }
 
     pub fn add_constraint(&mut self, constraint: Constraint) {
+        self.add_constraint_at(constraint, None);
+    }
+
+    /// Add a constraint from the node at `site`, where it's reported if
+    /// it fails
+    pub fn add_constraint_at(&mut self, constraint: Constraint, site: Option<Range<usize>>) {
         self.constraints.push(constraint);
+        self.sites.push(site);
+    }
+
+    /// Constraints added and not solved yet
+    pub fn pending(&self) -> &[Constraint] {
+        &self.constraints
+    }
+
+    /// Sites of the pending constraints, in the same order
+    pub fn pending_sites(&self) -> &[Option<Range<usize>>] {
+        &self.sites
+    }
+
+    /// Drop the constraints added after the first `len`
+    pub fn truncate(&mut self, len: usize) {
+        self.constraints.truncate(len);
+        self.sites.truncate(len);
+    }
+
+    /// Add constraints to `buffer` from now on, once it's emptied, keeping
+    /// its capacity
+    pub fn reuse_buffer(&mut self, mut buffer: Vec<Constraint>) {
+        buffer.clear();
+        buffer.append(&mut self.constraints);
+        self.constraints = buffer;
+    }
+
+    /// The vector constraints are added to, emptied, leaving a new one
+    pub fn take_buffer(&mut self) -> Vec<Constraint> {
+        let mut buffer = std::mem::take(&mut self.constraints);
+        buffer.clear();
+        self.sites.clear();
+        buffer
     }
 
     pub fn add_bound(&mut self, var: u64, bound: Type) {
-        self.bounds.entry(var).or_insert(bound);
+        let root = self.find(var);
+        if !self.solutions.contains_key(&root) && !self.bounds.contains_key(&root) {
+            self.bounds.insert(root, self.table.intern(bound));
+        }
+    }
+
+    /// `sub <: sup`, following class bases when the solver has a context
+    fn is_subtype(&self, sub: &Type, sup: &Type) -> bool {
+        match &self.ctx {
+            Some(ctx) => ctx.is_subtype(sub, sup),
+            None => sub.is_subtype(sup),
+        }
     }
 
     pub fn solve(&mut self) -> Result<(), Vec<TypeError>> {
+        self.solve_located()
+            .map_err(|errors| errors.into_iter().map(|(error, _)| error).collect())
+    }
+
+    /// Solve, pairing each error with the site of the constraint it came
+    /// from (`None` for constraints added without one)
+    pub fn solve_located(&mut self) -> Result<(), Vec<LocatedError>> {
         // Iteratively solve constraints
         let mut changed = true;
         let mut iterations = 0;
@@ -77,27 +230,63 @@ impl ConstraintSolver {
             changed = false;
             iterations += 1;
 
-            let constraints = std::mem::take(&mut self.constraints);
-            for constraint in constraints {
+            let mut constraints = std::mem::take(&mut self.constraints);
+            let sites = std::mem::take(&mut self.sites);
+            for (constraint, site) in constraints.drain(..).zip(sites) {
+                for ty in constraint.types() {
+                    visit_vars(ty, &mut |var| {
+                        self.seen.insert(var);
+                        if let Some(site) = &site {
+                            self.var_sites.entry(var).or_insert_with(|| site.clone());
+                        }
+                    });
+                }
                 match self.solve_constraint(&constraint) {
                     Ok(true) => changed = true,
-                    Ok(false) => self.constraints.push(constraint),
-                    Err(error) => {
-                        self.errors.push(error);
+                    Ok(false) => {
+                        self.constraints.push(constraint);
+                        self.sites.push(site);
                     }
+                    Err(error) => self.push_error(error, site),
                 }
             }
+            // Keep the space of the vector solved, which is the larger
+            if self.constraints.is_empty() {
+                self.constraints = constraints;
+            }
+            // Once nothing else changes, solve what the bounds decide, which
+            // may let deferred constraints through
+            if !changed {
+                changed = self.propagate();
+            }
         }
 
         if self.errors.is_empty() {
             Ok(())
         } else {
-            Err(std::mem::take(&mut self.errors))
+            let sites = std::mem::take(&mut self.error_sites);
+            Err(std::mem::take(&mut self.errors).into_iter().zip(sites).collect())
         }
     }
 
+    fn push_error(&mut self, error: TypeError, site: Option<Range<usize>>) {
+        self.errors.push(error);
+        self.error_sites.push(site);
+    }
+
+    /// Where the constraints mentioning `var` came from, if known
+    fn var_site(&self, var: u64) -> Option<Range<usize>> {
+        self.var_sites.get(&var)
+            .or_else(|| self.var_sites.get(&self.root(var)))
+            .cloned()
+    }
+
     fn solve_constraint(&mut self, constraint: &Constraint) -> Result<bool, TypeError> {
-        match constraint {
+        let constraint = match self.solutions.is_empty() && self.parents.is_empty() {
+            true => Cow::Borrowed(constraint),
+            false => Cow::Owned(constraint.map_types(|ty| self.apply(ty))),
+        };
+        match &*constraint {
             Constraint::Subtype(sub, sup) => self.check_subtype(sub, sup),
             Constraint::Equal(a, b) => self.check_equal(a, b),
             Constraint::HasAttribute(ty, attr, attr_ty) => {
@@ -113,45 +302,248 @@ impl ConstraintSolver {
     }
 
     fn check_subtype(&mut self, sub: &Type, sup: &Type) -> Result<bool, TypeError> {
-        if sub.is_subtype(sup) {
-            Ok(true)
-        } else {
-            // Try to extract type variables and add bounds
-            if let Type::Var(id) = sub {
-                if let Some(bound) = self.bounds.get(id) {
-                    if bound.is_subtype(sup) {
-                        return Ok(true);
-                    }
+        let undecided = |ty: &Type| matches!(ty, Type::Conditional { condition, .. } if condition.has_type_vars());
+        match (sub, sup) {
+            (Type::Var(sub), Type::Var(sup)) => {
+                let (sub, sup) = (self.find(*sub), self.find(*sup));
+                if sub != sup {
+                    self.flows.push((sub, sup));
                 }
-                // Add bound
-                self.bounds.insert(*id, sup.clone());
                 Ok(true)
-            } else {
-                Err(TypeError::type_mismatch(
-                    sup.clone(),
-                    sub.clone(),
-                    SourceLocation::new(0, 0, 0, 0),
-                ))
             }
+            (Type::Var(id), _) => {
+                self.add_upper_bound(*id, sup);
+                Ok(true)
+            }
+            (_, Type::Var(id)) => {
+                self.add_lower_bound(*id, sub);
+                Ok(true)
+            }
+            _ if self.is_subtype(sub, sup) => Ok(true),
+            // Deferred until the type variables it depends on are known
+            _ if undecided(sub) || undecided(sup) => Ok(false),
+            _ => Err(TypeError::type_mismatch(
+                sup.clone(),
+                sub.clone(),
+                SourceLocation::new(0, 0, 0, 0),
+            )),
         }
     }
 
     fn check_equal(&mut self, a: &Type, b: &Type) -> Result<bool, TypeError> {
-        if a == b {
-            Ok(true)
-        } else if let Type::Var(id) = a {
-            self.bounds.insert(*id, b.clone());
-            Ok(true)
-        } else if let Type::Var(id) = b {
-            self.bounds.insert(*id, a.clone());
-            Ok(true)
-        } else {
-            Err(TypeError::type_mismatch(
-                a.clone(),
-                b.clone(),
-                SourceLocation::new(0, 0, 0, 0),
-            ))
+        self.unify(a, b)
+            .map(|()| true)
+            .map_err(|kind| TypeError::new(kind, SourceLocation::new(0, 0, 0, 0)))
+    }
+
+    /// Make `a` and `b` the same type, solving the variables in them; what
+    /// conflicts when they can't be
+    pub fn unify(&mut self, a: &Type, b: &Type) -> Result<(), ErrorKind> {
+        use Type::*;
+
+        let (a, b) = (self.apply(a), self.apply(b));
+        let pairwise = |solver: &mut Self, xs: &[Type], ys: &[Type]| {
+            xs.iter().zip(ys).try_for_each(|(x, y)| solver.unify(x, y))
+        };
+        match (&a, &b) {
+            (a, b) if a == b => Ok(()),
+            (Any, _) | (_, Any) => Ok(()),
+            // Roots, since they've been applied
+            (Var(x), Var(y)) => {
+                self.union(*x, *y);
+                Ok(())
+            }
+            (Var(var), ty) | (ty, Var(var)) => self.bind(*var, ty.clone()),
+
+            (List(x), List(y)) | (Set(x), Set(y)) | (VarTuple(x), VarTuple(y)) => self.unify(x, y),
+            (Dict(k1, v1), Dict(k2, v2)) => {
+                self.unify(k1, k2)?;
+                self.unify(v1, v2)
+            }
+            (Tuple(xs), Tuple(ys)) if xs.len() == ys.len() => pairwise(self, xs, ys),
+            (Function(p1, r1), Function(p2, r2)) if p1.len() == p2.len() => {
+                pairwise(self, p1, p2)?;
+                self.unify(r1, r2)
+            }
+            (Generic(n1, xs), Generic(n2, ys)) if n1 == n2 && xs.len() == ys.len() => pairwise(self, xs, ys),
+            (Effect(inner, _), other) | (other, Effect(inner, _)) => self.unify(inner, other),
+            // Types written differently that are the same, e.g. unions in another order
+            (a, b) if !a.has_type_vars() && !b.has_type_vars() && self.is_subtype(a, b) && self.is_subtype(b, a) => Ok(()),
+            (a, b) => Err(ErrorKind::TypeMismatch { expected: a.to_string(), found: b.to_string() }),
+        }
+    }
+
+    /// Root of `var`'s class, pointing the variables on the way at it
+    fn find(&mut self, var: u64) -> u64 {
+        let root = self.root(var);
+        let mut current = var;
+        while let Some(parent) = self.parents.insert(current, root) {
+            if parent == root {
+                break;
+            }
+            current = parent;
+        }
+        self.parents.remove(&root);
+        root
+    }
+
+    fn root(&self, mut var: u64) -> u64 {
+        while let Some(parent) = self.parents.get(&var) {
+            var = *parent;
         }
+        var
+    }
+
+    /// Merge the classes of the unsolved roots `from` and `into`
+    fn union(&mut self, from: u64, into: u64) {
+        self.parents.insert(from, into);
+        if let Some(upper) = self.bounds.remove(&from) {
+            let upper = self.table.resolve(upper);
+            self.add_upper_bound(into, &upper);
+        }
+        if let Some(lower) = self.lower_bounds.remove(&from) {
+            let lower = self.table.resolve(lower);
+            self.add_lower_bound(into, &lower);
+        }
+    }
+
+    /// Solve the unsolved root `var` to `ty`, checking it against the
+    /// bounds `var` had
+    fn bind(&mut self, var: u64, ty: Type) -> Result<(), ErrorKind> {
+        let mut occurs = false;
+        visit_vars(&ty, &mut |other| occurs |= other == var);
+        if occurs {
+            return Err(ErrorKind::InfiniteType { var: Type::Var(var).to_string(), ty: ty.to_string() });
+        }
+        let upper = self.bounds.remove(&var).map(|bound| self.apply(&self.table.get(bound)));
+        let lower = self.lower_bounds.remove(&var).map(|bound| self.apply(&self.table.get(bound)));
+        self.solutions.insert(var, ty.clone());
+        if let Some(upper) = upper.filter(|upper| !upper.has_type_vars() && !self.is_subtype(&ty, upper)) {
+            return Err(ErrorKind::TypeMismatch { expected: upper.to_string(), found: ty.to_string() });
+        }
+        if let Some(lower) = lower.filter(|lower| !lower.has_type_vars() && !self.is_subtype(lower, &ty)) {
+            return Err(ErrorKind::TypeMismatch { expected: ty.to_string(), found: lower.to_string() });
+        }
+        Ok(())
+    }
+
+    /// Bound `var` from above by `bound`, keeping the tighter of two
+    fn add_upper_bound(&mut self, var: u64, bound: &Type) {
+        let root = self.find(var);
+        let tighter = match self.bound(root) {
+            Some(existing) => self.is_subtype(bound, &existing) && *existing != *bound,
+            None => true,
+        };
+        if tighter {
+            self.bounds.insert(root, self.table.intern_ref(bound));
+        }
+    }
+
+    /// Bound `var` from below by `bound`, joined with the bound it has
+    fn add_lower_bound(&mut self, var: u64, bound: &Type) {
+        let root = self.find(var);
+        let joined = match self.lower_bounds.get(&root) {
+            Some(existing) => Type::union(vec![self.table.resolve(*existing), bound.clone()]),
+            None => bound.clone(),
+        };
+        self.lower_bounds.insert(root, self.table.intern(joined));
+    }
+
+    /// Pass solutions along the flows between variables, then solve each
+    /// unsolved root whose bounds no longer mention variables: to its lower
+    /// bound, or lacking one to its upper bound. Whether anything was solved
+    fn propagate(&mut self) -> bool {
+        let mut solved = false;
+        loop {
+            for (sub, sup) in std::mem::take(&mut self.flows) {
+                let (sub, sup) = (self.find(sub), self.find(sup));
+                match (self.solutions.get(&sub).cloned(), self.solutions.get(&sup).cloned()) {
+                    (Some(sub_ty), Some(sup_ty)) => {
+                        let sub_ty = self.apply(&sub_ty);
+                        let sup_ty = self.apply(&sup_ty);
+                        if let Err(error) = self.check_subtype(&sub_ty, &sup_ty) {
+                            let site = self.var_site(sub).or_else(|| self.var_site(sup));
+                            self.push_error(error, site);
+                        }
+                    }
+                    (Some(sub_ty), None) => self.add_lower_bound(sup, &self.apply(&sub_ty)),
+                    (None, Some(sup_ty)) => self.add_upper_bound(sub, &self.apply(&sup_ty)),
+                    (None, None) if sub != sup => self.flows.push((sub, sup)),
+                    (None, None) => {}
+                }
+            }
+
+            let decided = |solver: &Self, bounds: &HashMap<u64, TypeRef>| -> Option<(u64, Type)> {
+                let mut vars: Vec<&u64> = bounds.keys().collect();
+                vars.sort_unstable();
+                vars.into_iter().find_map(|var| {
+                    let bound = solver.apply(&solver.table.get(bounds[var]));
+                    (!bound.has_type_vars()).then_some((*var, bound))
+                })
+            };
+            let Some((var, ty)) = decided(self, &self.lower_bounds).or_else(|| decided(self, &self.bounds)) else {
+                return solved;
+            };
+            solved = true;
+            if let Err(kind) = self.bind(var, ty) {
+                let site = self.var_site(var);
+                self.push_error(TypeError::new(kind, SourceLocation::new(0, 0, 0, 0)), site);
+            }
+        }
+    }
+
+    /// `ty` with each solved variable replaced by its solution and each
+    /// unsolved one by the root of its class
+    pub fn apply(&self, ty: &Type) -> Type {
+        let apply = |ty: &Type| self.apply(ty);
+        let boxed = |ty: &Type| Box::new(self.apply(ty));
+        match ty {
+            Type::Var(id) => {
+                let root = self.root(*id);
+                match self.solutions.get(&root) {
+                    Some(solution) => self.apply(solution),
+                    None => Type::Var(root),
+                }
+            }
+            Type::List(elem) => Type::List(boxed(elem)),
+            Type::Set(elem) => Type::Set(boxed(elem)),
+            Type::VarTuple(elem) => Type::VarTuple(boxed(elem)),
+            Type::Tuple(elems) => Type::Tuple(elems.iter().map(apply).collect()),
+            Type::Dict(key, value) => Type::Dict(boxed(key), boxed(value)),
+            Type::Function(params, ret) => Type::Function(params.iter().map(apply).collect(), boxed(ret)),
+            Type::Union(members) => Type::union(members.iter().map(apply).collect()),
+            Type::Intersection(members) => Type::intersection(members.iter().map(apply).collect()),
+            Type::Generic(name, args) => Type::Generic(name.clone(), args.iter().map(apply).collect()),
+            Type::Effect(inner, effects) => Type::Effect(boxed(inner), effects.clone()),
+            Type::Conditional { condition, then_type, else_type } => Type::Conditional {
+                condition: Box::new(condition.map_types(apply)),
+                then_type: boxed(then_type),
+                else_type: boxed(else_type),
+            },
+            other => other.clone(),
+        }
+    }
+
+    /// What each variable the solved constraints mention was unified with
+    /// or solved to, fully applied
+    pub fn substitution(&self) -> HashMap<u64, Type> {
+        self.seen.iter()
+            .chain(self.parents.keys())
+            .filter_map(|var| match self.apply(&Type::Var(*var)) {
+                Type::Var(root) if root == *var => None,
+                ty => Some((*var, ty)),
+            })
+            .collect()
+    }
+
+    /// Roots of the variables the constraints mention that solving left
+    /// unsolved, which are no error but aren't known either
+    pub fn unsolved(&self) -> Vec<u64> {
+        let roots: BTreeSet<u64> = self.seen.iter()
+            .map(|var| self.root(*var))
+            .filter(|root| !self.solutions.contains_key(root))
+            .collect();
+        roots.into_iter().collect()
     }
 
     fn check_has_attribute(
@@ -170,7 +562,7 @@ impl ConstraintSolver {
         match ctx.has_attribute(ty, attr) {
             Some(actual_ty) => {
                 // Verify attribute type matches expected
-                if actual_ty.is_subtype(expected_ty) || expected_ty.is_subtype(&actual_ty) {
+                if self.is_subtype(&actual_ty, expected_ty) || self.is_subtype(expected_ty, &actual_ty) {
                     Ok(true)
                 } else {
                     Err(TypeError::new(
@@ -226,7 +618,7 @@ impl ConstraintSolver {
 
             // Check parameter types (contravariant)
             for (expected, actual) in params.iter().zip(fn_params.iter()) {
-                if !expected.is_subtype(actual) {
+                if !self.is_subtype(expected, actual) {
                     return Err(TypeError::type_mismatch(
                         expected.clone(),
                         actual.clone(),
@@ -236,7 +628,7 @@ impl ConstraintSolver {
             }
 
             // Check return type (covariant)
-            if !fn_ret.is_subtype(ret) {
+            if !self.is_subtype(fn_ret, ret) {
                 return Err(TypeError::type_mismatch(
                     ret.clone(),
                     *fn_ret.clone(),
@@ -250,60 +642,95 @@ impl ConstraintSolver {
         }
     }
 
+    /// Check that `ty` has every member of the protocol `methods` with a
+    /// compatible type. Defers on type variables; a union must satisfy it in
+    /// every variant. The error names the first missing member, if any, and
+    /// its suggestions list every problem found
     pub fn check_protocol(&self, ty: &Type, methods: &[(String, Type)]) -> Result<bool, TypeError> {
-        // Get context or defer
-        let ctx = match &self.ctx {
-            Some(c) => c,
-            None => return Ok(false),
-        };
-
-        // For each required method, check if type has it with compatible signature
-        for (method_name, expected_method_type) in methods {
-            match ctx.has_attribute(ty, method_name) {
-                Some(actual_method_type) => {
-                    // Verify method type compatibility with variance
-                    if !self.check_method_compatibility(&actual_method_type, expected_method_type)? {
-                        return Err(TypeError::new(
-                            crate::compiler::errors::ErrorKind::TypeMismatch {
-                                expected: format!("method '{}' with type {}", method_name, expected_method_type),
-                                found: format!("method '{}' with incompatible type {}", method_name, actual_method_type),
-                            },
-                            SourceLocation::new(0, 0, 0, 0),
-                        ));
-                    }
+        let Some(ctx) = &self.ctx else { return Ok(false) };
+        match ty {
+            Type::Var(_) => return Ok(false),
+            Type::Any => return Ok(true),
+            Type::Union(variants) => {
+                let mut satisfied = true;
+                for variant in variants {
+                    satisfied &= self.check_protocol(variant, methods)?;
                 }
-                None => {
-                    // Missing required method
-                    let available = ctx.get_attributes(ty);
-                    let similar = crate::compiler::errors::find_similar_names(method_name, &available, 2);
-
-                    let mut error = TypeError::new(
-                        crate::compiler::errors::ErrorKind::InvalidAttribute {
-                            ty: ty.to_string(),
-                            attr: method_name.clone(),
-                        },
-                        SourceLocation::new(0, 0, 0, 0),
-                    );
+                return Ok(satisfied);
+            }
+            _ => {}
+        }
 
-                    if !similar.is_empty() {
-                        error = error.with_suggestions(
-                            similar.iter()
-                                .take(3)
-                                .map(|s| format!("Protocol requires '{}', did you mean '{}'?", method_name, s))
-                                .collect()
-                        );
-                    } else {
-                        error = error.with_suggestion(
-                            format!("Type '{}' must implement method '{}' to satisfy protocol", ty, method_name)
-                        );
-                    }
+        let mismatches = self.protocol_mismatches(ty, methods);
+        if mismatches.is_empty() {
+            return Ok(true);
+        }
 
-                    return Err(error);
+        let missing = methods.iter()
+            .map(|(name, _)| name)
+            .find(|name| ctx.has_attribute(ty, name).is_none());
+        let mut error = match missing {
+            Some(method_name) => {
+                let available = ctx.get_attributes(ty);
+                let similar = crate::compiler::errors::find_similar_names(method_name, &available, 2);
+                let error = TypeError::new(
+                    crate::compiler::errors::ErrorKind::InvalidAttribute {
+                        ty: ty.to_string(),
+                        attr: method_name.clone(),
+                    },
+                    SourceLocation::new(0, 0, 0, 0),
+                );
+                if similar.is_empty() {
+                    error.with_suggestion(
+                        format!("Type '{}' must implement method '{}' to satisfy protocol", ty, method_name)
+                    )
+                } else {
+                    error.with_suggestions(
+                        similar.iter()
+                            .take(3)
+                            .map(|s| format!("Protocol requires '{}', did you mean '{}'?", method_name, s))
+                            .collect()
+                    )
                 }
             }
+            None => TypeError::new(
+                crate::compiler::errors::ErrorKind::TypeMismatch {
+                    expected: "members compatible with the protocol".to_string(),
+                    found: format!("{} with incompatible members", ty),
+                },
+                SourceLocation::new(0, 0, 0, 0),
+            ),
+        };
+
+        for mismatch in mismatches {
+            error = error.with_suggestion(format!("Type '{}' does not satisfy protocol: {}", ty, mismatch));
         }
+        Err(error)
+    }
 
-        Ok(true)
+    /// Why `ty` doesn't satisfy the protocol `methods`: one entry per member
+    /// it lacks or has with an incompatible type, empty when it satisfies it
+    /// or there's no context to look members up in. The protocol's `Self`
+    /// parameter stands for `ty`
+    pub fn protocol_mismatches(&self, ty: &Type, methods: &[(String, Type)]) -> Vec<String> {
+        let Some(ctx) = &self.ctx else { return Vec::new() };
+        let strip = |ty: Type| match ty {
+            Type::Effect(inner, _) => *inner,
+            other => other,
+        };
+        let self_param = ["Self".to_string()];
+        methods.iter()
+            .filter_map(|(name, expected)| match ctx.has_attribute(ty, name).map(strip) {
+                None => Some(format!("missing '{}'", name)),
+                Some(actual) => {
+                    let expected = strip(expected.substitute_params(&self_param, std::slice::from_ref(ty)));
+                    match self.check_method_compatibility(&actual, &expected) {
+                        Ok(true) => None,
+                        _ => Some(format!("'{}' is {}, expected {}", name, actual, expected)),
+                    }
+                }
+            })
+            .collect()
     }
 
     /// Check if actual method type is compatible with expected (variance-aware)
@@ -317,36 +744,42 @@ impl ConstraintSolver {
 
                 // Parameters are contravariant: expected <: actual
                 for (expected_param, actual_param) in expected_params.iter().zip(actual_params.iter()) {
-                    if !expected_param.is_subtype(actual_param) {
+                    if !self.is_subtype(expected_param, actual_param) {
                         return Ok(false);
                     }
                 }
 
                 // Return type is covariant: actual <: expected
-                if !actual_ret.is_subtype(expected_ret) {
+                if !self.is_subtype(actual_ret, expected_ret) {
                     return Ok(false);
                 }
 
                 Ok(true)
             }
             // Non-function types: simple subtyping
-            (actual_ty, expected_ty) => Ok(actual_ty.is_subtype(expected_ty)),
+            (actual_ty, expected_ty) => Ok(self.is_subtype(actual_ty, expected_ty)),
         }
     }
 
     fn check_bounded(&mut self, var: &Type, bound: &Type) -> Result<bool, TypeError> {
         if let Type::Var(id) = var {
-            if let Some(existing_bound) = self.bounds.get(id) {
-                // Check consistency
-                if !existing_bound.is_subtype(bound) && !bound.is_subtype(existing_bound) {
-                    return Err(TypeError::type_mismatch(
-                        existing_bound.clone(),
-                        bound.clone(),
-                        SourceLocation::new(0, 0, 0, 0),
-                    ));
+            let root = self.find(*id);
+            match self.bound(root) {
+                // The same type, which is consistent with itself
+                Some(existing) if *existing == *bound => {}
+                Some(existing) => {
+                    // Check consistency
+                    if !self.is_subtype(&existing, bound) && !self.is_subtype(bound, &existing) {
+                        return Err(TypeError::type_mismatch(
+                            (*existing).clone(),
+                            bound.clone(),
+                            SourceLocation::new(0, 0, 0, 0),
+                        ));
+                    }
+                }
+                None => {
+                    self.bounds.insert(root, self.table.intern_ref(bound));
                 }
-            } else {
-                self.bounds.insert(*id, bound.clone());
             }
             Ok(true)
         } else {
@@ -384,29 +817,48 @@ impl ConstraintSolver {
     }
 
     fn check_hashable(&self, ty: &Type) -> Result<bool, TypeError> {
-        match ty {
-            Type::Int | Type::Float | Type::Str | Type::Bool | Type::Bytes | Type::None => Ok(true),
-            Type::Tuple(elems) => {
-                // All elements must be hashable
-                for elem in elems {
-                    self.check_hashable(elem)?;
-                }
-                Ok(true)
-            }
-            Type::List(_) | Type::Dict(_, _) | Type::Set(_) => Err(TypeError::new(
+        if Self::unhashable_name(ty).is_some() {
+            return Err(TypeError::new(
                 crate::compiler::errors::ErrorKind::TypeMismatch {
                     expected: "hashable type".to_string(),
                     found: ty.to_string(),
                 },
                 SourceLocation::new(0, 0, 0, 0),
-            )),
+            ));
+        }
+
+        match ty {
+            Type::Int | Type::Float | Type::Str | Type::Bool | Type::Bytes | Type::None => Ok(true),
+            Type::Tuple(_) | Type::VarTuple(_) => Ok(true), // Elements already vetted above
             Type::Var(_) => Ok(false), // Defer
             _ => Ok(false),
         }
     }
 
-    pub fn get_bound(&self, var: u64) -> Option<&Type> {
-        self.bounds.get(&var)
+    /// Python name of the type that makes `ty` unhashable, if any
+    ///
+    /// Looks through tuples, so `tuple[str, list[int]]` yields `list`. This is
+    /// the same rule the runtime applies when hashing dict keys, and its
+    /// `unhashable type: '<name>'` error uses the same name.
+    pub fn unhashable_name(ty: &Type) -> Option<&'static str> {
+        match ty {
+            Type::List(_) => Some("list"),
+            Type::Dict(_, _) => Some("dict"),
+            Type::Set(_) => Some("set"),
+            Type::Tuple(elems) => elems.iter().find_map(Self::unhashable_name),
+            Type::VarTuple(elem) => Self::unhashable_name(elem),
+            Type::Effect(inner, _) | Type::Refinement(inner, _) => Self::unhashable_name(inner),
+            _ => None,
+        }
+    }
+
+    pub fn get_bound(&self, var: u64) -> Option<Type> {
+        self.bounds.get(&self.root(var)).map(|handle| self.table.resolve(*handle))
+    }
+
+    /// The upper bound of `var`, shared with the table rather than copied
+    fn bound(&self, var: u64) -> Option<Arc<Type>> {
+        self.bounds.get(&self.root(var)).map(|handle| self.table.get(*handle))
     }
 
     pub fn errors(&self) -> &[TypeError] {
@@ -575,6 +1027,7 @@ impl GenericType {
                 Box::new(self.substitute(v, args)),
             ),
             Type::Tuple(elems) => Type::Tuple(elems.iter().map(|e| self.substitute(e, args)).collect()),
+            Type::VarTuple(elem) => Type::VarTuple(Box::new(self.substitute(elem, args))),
             Type::Function(params, ret) => Type::Function(
                 params.iter().map(|p| self.substitute(p, args)).collect(),
                 Box::new(self.substitute(ret, args)),
@@ -601,6 +1054,71 @@ mod tests {
         assert!(solver.solve().is_ok());
     }
 
+    #[test]
+    fn test_chained_equalities_resolve_transitively() {
+        let list_of = |elem: Type| Type::List(Box::new(elem));
+        let mut solver = ConstraintSolver::new();
+        solver.add_constraint(Constraint::Equal(Type::Var(1), list_of(Type::Var(2))));
+        solver.add_constraint(Constraint::Equal(Type::Var(3), Type::Var(2)));
+        solver.add_constraint(Constraint::Equal(Type::Var(3), Type::Int));
+        assert!(solver.solve().is_ok());
+        assert_eq!(solver.apply(&Type::Var(1)), list_of(Type::Int));
+        assert_eq!(solver.substitution().get(&2), Some(&Type::Int));
+        assert!(solver.unsolved().is_empty());
+    }
+
+    #[test]
+    fn test_variables_flowing_into_each_other_share_solutions() {
+        let mut solver = ConstraintSolver::new();
+        solver.add_constraint(Constraint::Subtype(Type::Var(1), Type::Var(2)));
+        solver.add_constraint(Constraint::Subtype(Type::Int, Type::Var(1)));
+        assert!(solver.solve().is_ok());
+        assert_eq!(solver.apply(&Type::Var(2)), Type::Int);
+    }
+
+    #[test]
+    fn test_infinite_type_is_an_error() {
+        let mut solver = ConstraintSolver::new();
+        solver.add_constraint(Constraint::Equal(Type::Var(1), Type::List(Box::new(Type::Var(1)))));
+        let errors = solver.solve().unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind.to_string(), "Infinite type: T1 = list[T1]");
+    }
+
+    #[test]
+    fn test_unsolved_variables_are_not_conflicts() {
+        let mut solver = ConstraintSolver::new();
+        solver.add_constraint(Constraint::Equal(Type::Var(1), Type::Var(2)));
+        solver.add_constraint(Constraint::Equal(Type::Var(3), Type::Str));
+        assert!(solver.solve().is_ok());
+        assert_eq!(solver.unsolved(), vec![2]);
+
+        let mut solver = ConstraintSolver::new();
+        solver.add_constraint(Constraint::Equal(Type::Var(1), Type::Str));
+        solver.add_constraint(Constraint::Subtype(Type::Var(1), Type::Int));
+        assert_eq!(solver.solve().map_err(|errors| errors.len()), Err(1));
+        assert!(solver.unsolved().is_empty());
+    }
+
+    #[test]
+    fn test_errors_keep_the_site_of_their_constraint() {
+        let mut solver = ConstraintSolver::new();
+        solver.add_constraint_at(Constraint::Subtype(Type::Str, Type::Int), Some(4..7));
+        solver.add_constraint(Constraint::Subtype(Type::Int, Type::Int));
+        let errors = solver.solve_located().unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].1, Some(4..7));
+        assert_eq!(errors[0].0.kind.to_string(), "Type mismatch: expected int, found str");
+
+        // Variables solved from bounds report where they first appeared
+        let mut solver = ConstraintSolver::new();
+        solver.add_constraint_at(Constraint::Subtype(Type::Var(1), Type::Var(2)), Some(10..12));
+        solver.add_constraint(Constraint::Subtype(Type::Str, Type::Var(1)));
+        solver.add_constraint(Constraint::Equal(Type::Var(2), Type::Int));
+        let errors = solver.solve_located().unwrap_err();
+        assert_eq!(errors.iter().map(|(_, site)| site.clone()).collect::<Vec<_>>(), vec![Some(10..12)]);
+    }
+
     #[test]
     fn test_numeric_constraint() {
         let mut solver = ConstraintSolver::new();
@@ -621,6 +1139,16 @@ mod tests {
         let mut solver = ConstraintSolver::new();
         solver.add_constraint(Constraint::Hashable(Type::List(Box::new(Type::Int))));
         assert!(solver.solve().is_err());
+
+        let mut solver = ConstraintSolver::new();
+        solver.add_constraint(Constraint::Hashable(Type::Tuple(vec![Type::Str, Type::Str])));
+        assert!(solver.solve().is_ok());
+
+        let nested = Type::Tuple(vec![Type::Str, Type::List(Box::new(Type::Int))]);
+        assert_eq!(ConstraintSolver::unhashable_name(&nested), Some("list"));
+        let mut solver = ConstraintSolver::new();
+        solver.add_constraint(Constraint::Hashable(nested));
+        assert!(solver.solve().is_err());
     }
 
     #[test]
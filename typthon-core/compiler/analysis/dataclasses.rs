@@ -0,0 +1,180 @@
+//! `@dataclass` classes
+//!
+//! A dataclass gets an `__init__` taking its annotated class-level fields in
+//! declaration order, inherited fields first. A field has a default when it
+//! is assigned a value, or `field(default=...)` / `field(default_factory=...)`,
+//! and `field(init=False)` leaves it out of `__init__`. The fields of a
+//! `frozen=True` dataclass can't be assigned after construction.
+
+use crate::compiler::types::{FunctionSig, Param, Type};
+use rustpython_parser::ast::*;
+
+/// Arguments of the `@dataclass(...)` decorator the checker honours
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DataclassOptions {
+    pub init: bool,
+    pub frozen: bool,
+}
+
+impl Default for DataclassOptions {
+    fn default() -> Self {
+        Self { init: true, frozen: false }
+    }
+}
+
+/// One annotated field of a dataclass
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub ty: Type,
+    pub has_default: bool,
+    /// Whether `__init__` takes it
+    pub init: bool,
+}
+
+/// The fields of a dataclass, inherited ones first
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Dataclass {
+    pub fields: Vec<Field>,
+    pub frozen: bool,
+}
+
+impl Dataclass {
+    /// Add `field`, replacing an inherited one of the same name in place
+    pub fn insert(&mut self, field: Field) {
+        match self.fields.iter_mut().find(|existing| existing.name == field.name) {
+            Some(existing) => *existing = field,
+            None => self.fields.push(field),
+        }
+    }
+
+    pub fn field(&self, name: &str) -> Option<&Field> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+
+    /// Parameter types and signature of the synthesized `__init__`, without `self`
+    pub fn init_signature(&self) -> (Vec<Type>, FunctionSig) {
+        let fields: Vec<&Field> = self.fields.iter().filter(|field| field.init).collect();
+        let params = fields.iter().map(|field| field.ty.clone()).collect();
+        let sig = FunctionSig {
+            params: fields.iter().map(|field| Param::new(field.name.clone(), field.has_default)).collect(),
+            ..FunctionSig::default()
+        };
+        (params, sig)
+    }
+}
+
+/// Options of the `@dataclass` or `@dataclasses.dataclass` decorator among
+/// `decorators`, bare or called; `None` when there isn't one
+pub fn dataclass_options(decorators: &[Expr]) -> Option<DataclassOptions> {
+    decorators.iter().find_map(|decorator| match decorator {
+        Expr::Call(call) if is_dataclass(&call.func) => {
+            let mut options = DataclassOptions::default();
+            for keyword in &call.keywords {
+                match (keyword.arg.as_ref().map(|arg| arg.as_str()), bool_constant(&keyword.value)) {
+                    (Some("init"), Some(value)) => options.init = value,
+                    (Some("frozen"), Some(value)) => options.frozen = value,
+                    _ => {}
+                }
+            }
+            Some(options)
+        }
+        decorator if is_dataclass(decorator) => Some(DataclassOptions::default()),
+        _ => None,
+    })
+}
+
+/// Whether a field assigned `value` has a default, and whether `__init__`
+/// takes it: `(has_default, init)`
+pub fn field_spec(value: Option<&Expr>) -> (bool, bool) {
+    match value {
+        None => (false, true),
+        Some(Expr::Call(call)) if is_field(&call.func) => {
+            let keyword = |name: &str| call.keywords.iter()
+                .find(|keyword| keyword.arg.as_ref().is_some_and(|arg| arg.as_str() == name));
+            let has_default = keyword("default").is_some() || keyword("default_factory").is_some();
+            let init = keyword("init").and_then(|keyword| bool_constant(&keyword.value)).unwrap_or(true);
+            (has_default, init)
+        }
+        Some(_) => (true, true),
+    }
+}
+
+/// `ClassVar[...]` annotations declare class attributes, not fields
+pub fn is_class_var(annotation: &Expr) -> bool {
+    match annotation {
+        Expr::Subscript(subscript) => is_class_var(&subscript.value),
+        Expr::Name(name) => name.id.as_str() == "ClassVar",
+        Expr::Attribute(attr) => attr.attr.as_str() == "ClassVar",
+        _ => false,
+    }
+}
+
+fn is_dataclass(expr: &Expr) -> bool {
+    is_named(expr, "dataclass")
+}
+
+fn is_field(expr: &Expr) -> bool {
+    is_named(expr, "field")
+}
+
+/// `name` or `dataclasses.name`
+fn is_named(expr: &Expr, name: &str) -> bool {
+    match expr {
+        Expr::Name(n) => n.id.as_str() == name,
+        Expr::Attribute(attr) => attr.attr.as_str() == name
+            && matches!(&*attr.value, Expr::Name(module) if module.id.as_str() == "dataclasses"),
+        _ => false,
+    }
+}
+
+fn bool_constant(expr: &Expr) -> Option<bool> {
+    match expr {
+        Expr::Constant(constant) => match constant.value {
+            Constant::Bool(value) => Some(value),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::frontend::parse_module;
+
+    fn class_def(source: &str) -> StmtClassDef {
+        let Mod::Module(module) = parse_module(source).unwrap() else { unreachable!() };
+        match module.body.into_iter().last() {
+            Some(Stmt::ClassDef(class_def)) => class_def,
+            other => panic!("expected a class, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decorator_options() {
+        let options = |source: &str| dataclass_options(&class_def(source).decorator_list);
+
+        assert_eq!(options("@dataclass\nclass A: pass\n"), Some(DataclassOptions::default()));
+        assert_eq!(
+            options("@dataclasses.dataclass(frozen=True)\nclass A: pass\n"),
+            Some(DataclassOptions { init: true, frozen: true }),
+        );
+        assert_eq!(options("@other.dataclass\nclass A: pass\n"), None);
+        assert_eq!(options("class A: pass\n"), None);
+    }
+
+    #[test]
+    fn test_field_defaults() {
+        let class_def = class_def(
+            "class A:\n    a: int\n    b: int = 0\n    c: list = field(default_factory=list)\n    d: int = field(init=False)\n",
+        );
+        let specs: Vec<(bool, bool)> = class_def.body.iter()
+            .map(|stmt| match stmt {
+                Stmt::AnnAssign(ann) => field_spec(ann.value.as_deref()),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(specs, vec![(false, true), (true, true), (true, true), (false, false)]);
+    }
+}
@@ -0,0 +1,83 @@
+//! Decorators on function definitions
+//!
+//! A decorator is known by the last segment of its name, bare, dotted or
+//! called: `@staticmethod`, `@functools.cached_property` and
+//! `@functools.lru_cache(maxsize=None)` are all recognized. `@name.setter` and
+//! `@name.deleter` define accessors of the property `name` rather than a
+//! member of their own.
+
+use rustpython_parser::ast::*;
+
+/// Decorators the checker understands, or that return the function they
+/// wrap with its signature unchanged
+const KNOWN_DECORATORS: &[&str] = &[
+    "staticmethod", "classmethod", "property", "cached_property", "abstractmethod", "overload", "override",
+    "final", "wraps", "lru_cache", "cache", "total_ordering", "dataclass", "no_type_check", "effects", "pure",
+];
+
+/// Name of `decorator`: the last segment of its dotted name, or of the
+/// callee when it is called with arguments
+pub fn decorator_name(decorator: &Expr) -> Option<&str> {
+    match decorator {
+        Expr::Name(name) => Some(name.id.as_str()),
+        Expr::Attribute(attr) => Some(attr.attr.as_str()),
+        Expr::Call(call) => decorator_name(&call.func),
+        _ => None,
+    }
+}
+
+/// Whether one of `decorators` is named `name`
+pub fn has_decorator(decorators: &[Expr], name: &str) -> bool {
+    decorators.iter().any(|decorator| decorator_name(decorator) == Some(name))
+}
+
+/// Whether `decorators` make the function a property: `@property` or
+/// `@cached_property`
+pub fn is_property(decorators: &[Expr]) -> bool {
+    has_decorator(decorators, "property") || has_decorator(decorators, "cached_property")
+}
+
+/// Whether the function is the setter or deleter of a property defined earlier
+pub fn is_accessor(decorators: &[Expr]) -> bool {
+    decorators.iter().any(|decorator| {
+        matches!(decorator, Expr::Attribute(attr) if matches!(attr.attr.as_str(), "setter" | "deleter" | "getter"))
+    })
+}
+
+/// Whether any of `decorators` is one the checker knows nothing about, so
+/// may replace the function with something of a different signature
+pub fn has_unknown_decorator(decorators: &[Expr]) -> bool {
+    !is_accessor(decorators)
+        && decorators.iter().any(|decorator| !decorator_name(decorator).is_some_and(|name| KNOWN_DECORATORS.contains(&name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::frontend::parse_module;
+
+    fn decorators(source: &str) -> Vec<Expr> {
+        let Mod::Module(module) = parse_module(source).unwrap() else { unreachable!() };
+        match module.body.into_iter().last() {
+            Some(Stmt::FunctionDef(func_def)) => func_def.decorator_list,
+            other => panic!("expected a function, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decorator_names() {
+        let names = |source: &str| decorators(source).iter().map(|d| decorator_name(d).map(str::to_string)).collect::<Vec<_>>();
+        assert_eq!(
+            names("@staticmethod\n@functools.lru_cache(maxsize=None)\n@registry['x']\ndef f(): pass\n"),
+            vec![Some("staticmethod".to_string()), Some("lru_cache".to_string()), None],
+        );
+    }
+
+    #[test]
+    fn test_unknown_decorators() {
+        assert!(!has_unknown_decorator(&decorators("@functools.wraps(g)\n@cache\ndef f(): pass\n")));
+        assert!(!has_unknown_decorator(&decorators("@value.setter\ndef value(self, v): pass\n")));
+        assert!(has_unknown_decorator(&decorators("@app.route('/')\ndef f(): pass\n")));
+        assert!(is_property(&decorators("@functools.cached_property\ndef f(self): pass\n")));
+    }
+}
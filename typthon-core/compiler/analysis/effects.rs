@@ -1,13 +1,124 @@
+//! Side effects of functions and methods
+//!
+//! Each function's body is summarized once: the effects of its own
+//! statements and of the builtins it calls, the functions and methods it
+//! calls, and the functions flowing into its variables and parameters.
+//! Effects then propagate from callees to callers until nothing changes, so
+//! recursion and mutual recursion settle instead of looping.
+
+use crate::compiler::ast::ir::{self, ComprehensionKind, ExprKind, ParamKind, StmtKind};
+use crate::compiler::ast::{lower_expr, lower_module};
+use crate::compiler::analysis::decorators;
 use crate::compiler::types::{Type, Effect, EffectSet, TypeContext};
-use rustpython_parser::ast::*;
-use std::collections::HashMap;
+use rustpython_parser::ast::{Constant, Expr, Mod};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::ops::Range;
 use std::sync::Arc;
 
+/// What a call may reach
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Callee {
+    /// A function or method of the module, by key: `f` or `Class.method`
+    Function(String),
+    /// A name called in the scope of the function keyed `scope`, or at
+    /// module level when empty; resolved once every variable's flows are known
+    Name { scope: String, name: String },
+}
+
+/// How a method is bound when called through an instance or its class
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Receiver {
+    /// Plain functions and static methods
+    #[default]
+    None,
+    Instance,
+    Class,
+}
+
+/// Parameters of a function of the module
+#[derive(Debug, Clone, Default)]
+struct FunctionInfo {
+    /// Parameters arguments can fill by position, receiver included
+    positional: Vec<String>,
+    /// Every named parameter
+    named: HashSet<String>,
+    receiver: Receiver,
+}
+
+impl FunctionInfo {
+    /// Parameter an argument fills, when `offset` leading parameters are
+    /// bound by the call
+    fn parameter(&self, slot: &Slot, offset: usize) -> Option<&str> {
+        match slot {
+            Slot::Position(i) => self.positional.get(i + offset).map(String::as_str),
+            Slot::Keyword(name) => self.named.get(name).map(String::as_str),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct ClassInfo {
+    methods: HashSet<String>,
+    bases: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Slot {
+    Position(usize),
+    Keyword(String),
+}
+
+/// Arguments that may be functions, passed by `caller` to `callee`. A
+/// function of the module receives them as parameters; anything else may
+/// call them
+#[derive(Debug, Clone)]
+struct Pass {
+    caller: String,
+    callee: Option<Callee>,
+    /// Leading parameters bound by the call itself, such as `self`
+    offset: usize,
+    args: Vec<(Slot, BTreeSet<Callee>)>,
+}
+
+/// A call in a function's body, kept to pinpoint the effects it brings in
+#[derive(Debug, Clone)]
+struct CallSite {
+    range: Range<usize>,
+    /// Effects of the builtins and functions from elsewhere it calls
+    effects: EffectSet,
+    callee: Option<Callee>,
+    /// Functions passed as arguments, which a callee from elsewhere may call
+    passed: BTreeSet<Callee>,
+}
+
+/// The function whose body is being summarized
+#[derive(Debug, Default)]
+struct Scope {
+    /// Empty at module level
+    key: String,
+    class: Option<String>,
+    /// Name of the method's receiver and whether it is the class object
+    receiver: Option<(String, bool)>,
+    params: HashSet<String>,
+    /// Classes of the locals known to hold instances
+    instances: HashMap<String, String>,
+    calls: BTreeSet<Callee>,
+    sites: Vec<CallSite>,
+}
+
 /// Effect analyzer tracks side effects through the program
 pub struct EffectAnalyzer {
     ctx: Arc<TypeContext>,
     function_effects: HashMap<String, EffectSet>,
     builtin_effects: HashMap<String, EffectSet>,
+    functions: HashMap<String, FunctionInfo>,
+    classes: HashMap<String, ClassInfo>,
+    /// Functions each variable may hold, by scope key and name
+    flows: HashMap<(String, String), BTreeSet<Callee>>,
+    passes: Vec<Pass>,
+    /// Calls in the body of each function, by key
+    call_sites: HashMap<String, Vec<CallSite>>,
+    scope: Scope,
 }
 
 impl EffectAnalyzer {
@@ -16,6 +127,12 @@ impl EffectAnalyzer {
             ctx,
             function_effects: HashMap::new(),
             builtin_effects: HashMap::new(),
+            functions: HashMap::new(),
+            classes: HashMap::new(),
+            flows: HashMap::new(),
+            passes: Vec::new(),
+            call_sites: HashMap::new(),
+            scope: Scope::default(),
         };
         analyzer.init_builtins();
         analyzer
@@ -46,154 +163,457 @@ impl EffectAnalyzer {
 
     /// Analyze effects in a module
     pub fn analyze_module(&mut self, module: &Mod) -> HashMap<String, EffectSet> {
-        if let Mod::Module(mod_module) = module {
-            for stmt in &mod_module.body {
-                self.analyze_stmt(stmt);
-            }
+        self.analyze_ir(&lower_module(module, None))
+    }
+
+    /// Analyze effects in an already-lowered module
+    pub fn analyze_ir(&mut self, module: &ir::Module) -> HashMap<String, EffectSet> {
+        self.function_effects.clear();
+        self.functions.clear();
+        self.classes.clear();
+        self.flows.clear();
+        self.passes.clear();
+        self.call_sites.clear();
+
+        let mut defs = Vec::new();
+        self.collect(&module.body, None, &mut defs);
+
+        // Module-level code only matters for the functions it binds and passes
+        self.scope = Scope::default();
+        for stmt in &module.body {
+            self.infer_stmt_effects(stmt);
         }
+
+        let mut summaries: Vec<(String, EffectSet, BTreeSet<Callee>)> = Vec::new();
+        for (key, class, func) in defs {
+            let effects = self.analyze_function(&key, class, func);
+            let calls = std::mem::take(&mut self.scope.calls);
+            let sites = std::mem::take(&mut self.scope.sites);
+            self.call_sites.entry(key.clone()).or_default().extend(sites);
+            summaries.push((key, effects, calls));
+        }
+        self.scope = Scope::default();
+
+        self.propagate(summaries);
         self.function_effects.clone()
     }
 
-    /// Analyze statement for effects
-    fn analyze_stmt(&mut self, stmt: &Stmt) {
-        match stmt {
-            Stmt::FunctionDef(func) => self.analyze_function(func),
-            Stmt::AsyncFunctionDef(func) => self.analyze_async_function(func),
-            Stmt::For(for_stmt) => {
-                self.infer_expr_effects(&for_stmt.iter);
-                for_stmt.body.iter().for_each(|s| self.analyze_stmt(s));
-            }
-            Stmt::While(while_stmt) => {
-                self.infer_expr_effects(&while_stmt.test);
-                while_stmt.body.iter().for_each(|s| self.analyze_stmt(s));
-            }
-            Stmt::If(if_stmt) => {
-                self.infer_expr_effects(&if_stmt.test);
-                if_stmt.body.iter().for_each(|s| self.analyze_stmt(s));
-                if_stmt.orelse.iter().for_each(|s| self.analyze_stmt(s));
-            }
-            Stmt::With(with_stmt) => {
-                // Context managers might have effects
-                for item in &with_stmt.items {
-                    self.infer_expr_effects(&item.context_expr);
+    /// Record the functions, methods and classes defined anywhere in `stmts`
+    fn collect<'r, 'a>(
+        &mut self,
+        stmts: &'r [ir::Stmt<'a>],
+        class: Option<&str>,
+        out: &mut Vec<(String, Option<String>, &'r ir::FunctionDef<'a>)>,
+    ) {
+        for stmt in stmts {
+            match &stmt.kind {
+                StmtKind::FunctionDef(func) => {
+                    let key = match class {
+                        Some(class) => format!("{}.{}", class, func.name),
+                        None => func.name.to_string(),
+                    };
+                    self.functions.insert(key.clone(), Self::function_info(func, class.is_some()));
+                    out.push((key, class.map(str::to_string), func));
+                    self.collect(&func.body, None, out);
                 }
-                with_stmt.body.iter().for_each(|s| self.analyze_stmt(s));
-            }
-            Stmt::Try(try_stmt) => {
-                // Exception handling adds Exception effect
-                try_stmt.body.iter().for_each(|s| self.analyze_stmt(s));
-                for handler in &try_stmt.handlers {
-                    let ExceptHandler::ExceptHandler(h) = handler;
-                    h.body.iter().for_each(|s| self.analyze_stmt(s));
+                StmtKind::ClassDef(class_def) => {
+                    let info = ClassInfo {
+                        methods: class_def.body.iter()
+                            .filter_map(|stmt| match &stmt.kind {
+                                StmtKind::FunctionDef(func) => Some(func.name.to_string()),
+                                _ => None,
+                            })
+                            .collect(),
+                        bases: class_def.bases.iter()
+                            .filter_map(|base| match &base.kind {
+                                ExprKind::Name(name) => Some(name.to_string()),
+                                _ => None,
+                            })
+                            .collect(),
+                    };
+                    self.classes.insert(class_def.name.to_string(), info);
+                    self.collect(&class_def.body, Some(&class_def.name), out);
+                }
+                _ => {
+                    for block in Self::blocks(stmt) {
+                        self.collect(block, class, out);
+                    }
                 }
             }
-            Stmt::Expr(expr_stmt) => {
-                self.infer_expr_effects(&expr_stmt.value);
+        }
+    }
+
+    /// Statement blocks nested in a compound statement other than a definition
+    fn blocks<'r, 'a>(stmt: &'r ir::Stmt<'a>) -> Vec<&'r [ir::Stmt<'a>]> {
+        match &stmt.kind {
+            StmtKind::For { body, orelse, .. } | StmtKind::While { body, orelse, .. } | StmtKind::If { body, orelse, .. } => {
+                vec![body, orelse]
             }
-            Stmt::Assign(assign) => {
-                self.infer_expr_effects(&assign.value);
+            StmtKind::With { body, .. } => vec![body],
+            StmtKind::Try { body, handlers, orelse, finalbody, .. } => {
+                let mut blocks: Vec<&[ir::Stmt]> = vec![body, orelse, finalbody];
+                blocks.extend(handlers.iter().map(|handler| handler.body.as_slice()));
+                blocks
             }
-            _ => {}
+            StmtKind::Match { cases, .. } => cases.iter().map(|case| case.body.as_slice()).collect(),
+            _ => Vec::new(),
         }
     }
 
-    /// Analyze function definition
-    fn analyze_function(&mut self, func: &StmtFunctionDef) {
-        let mut effects = EffectSet::pure();
+    fn function_info(func: &ir::FunctionDef, is_method: bool) -> FunctionInfo {
+        let has_decorator = |name: &str| func.decorators.iter().any(|d| decorators::decorator_name(d.node) == Some(name));
+        let receiver = match is_method {
+            false => Receiver::None,
+            true if has_decorator("staticmethod") => Receiver::None,
+            true if has_decorator("classmethod") => Receiver::Class,
+            true => Receiver::Instance,
+        };
+        let named = func.params.iter()
+            .filter(|param| !matches!(param.kind, ParamKind::VarArgs | ParamKind::VarKeywords))
+            .map(|param| param.name.to_string())
+            .collect();
+        let positional = func.params.iter()
+            .filter(|param| matches!(param.kind, ParamKind::PositionalOnly | ParamKind::Positional))
+            .map(|param| param.name.to_string())
+            .collect();
+        FunctionInfo { positional, named, receiver }
+    }
+
+    /// Summarize a function body; async functions always have the Async effect
+    fn analyze_function(&mut self, key: &str, class: Option<String>, func: &ir::FunctionDef) -> EffectSet {
+        let receiver = match self.functions.get(key).map(|info| info.receiver) {
+            Some(Receiver::Instance) => func.params.first().map(|param| (param.name.to_string(), false)),
+            Some(Receiver::Class) => func.params.first().map(|param| (param.name.to_string(), true)),
+            _ => None,
+        };
+        self.scope = Scope {
+            key: key.to_string(),
+            class,
+            receiver,
+            params: func.params.iter().map(|param| param.name.to_string()).collect(),
+            ..Scope::default()
+        };
+        for param in &func.params {
+            if let Some(class) = param.annotation.as_ref().and_then(|annotation| self.class_named(annotation)) {
+                self.scope.instances.insert(param.name.to_string(), class);
+            }
+        }
+
+        let mut effects = if func.is_async {
+            EffectSet::single(Effect::Async)
+        } else {
+            EffectSet::pure()
+        };
 
         // Analyze function body
         for stmt in &func.body {
             let stmt_effects = self.infer_stmt_effects(stmt);
             effects = effects.union(stmt_effects);
         }
+        effects
+    }
+
+    /// Let arguments flow into parameters and callee effects into callers
+    /// until neither grows
+    fn propagate(&mut self, mut summaries: Vec<(String, EffectSet, BTreeSet<Callee>)>) {
+        for (key, effects, _) in &summaries {
+            self.function_effects.insert(key.clone(), effects.clone());
+        }
+
+        loop {
+            let mut changed = false;
+
+            for pass in self.passes.clone() {
+                let reached = pass.callee.as_ref().map(|callee| self.resolve(callee).0).unwrap_or_default();
+                if reached.is_empty() {
+                    // Builtins and functions from elsewhere may call what they're given
+                    if let Some((_, _, calls)) = summaries.iter_mut().find(|(key, _, _)| *key == pass.caller) {
+                        for (_, targets) in &pass.args {
+                            for target in targets {
+                                changed |= calls.insert(target.clone());
+                            }
+                        }
+                    }
+                    continue;
+                }
+                for function in reached {
+                    let Some(info) = self.functions.get(&function) else { continue };
+                    let offset = pass.offset;
+                    let params: Vec<(String, &BTreeSet<Callee>)> = pass.args.iter()
+                        .filter_map(|(slot, targets)| Some((info.parameter(slot, offset)?.to_string(), targets)))
+                        .collect();
+                    for (param, targets) in params {
+                        let flows = self.flows.entry((function.clone(), param)).or_default();
+                        for target in targets {
+                            changed |= flows.insert(target.clone());
+                        }
+                    }
+                }
+            }
 
-        self.function_effects.insert(func.name.to_string(), effects);
+            for (key, local, calls) in &summaries {
+                let effects = calls.iter().fold(local.clone(), |acc, callee| acc.union(self.effects_of(callee)));
+                // Compared after the union, since a pure set isn't a subset
+                // of an impure one
+                let current = &self.function_effects[key];
+                let grown = current.clone().union(effects);
+                if grown != *current {
+                    self.function_effects.insert(key.clone(), grown);
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
     }
 
-    /// Analyze async function (always has Async effect)
-    fn analyze_async_function(&mut self, func: &StmtAsyncFunctionDef) {
-        let mut effects = EffectSet::single(Effect::Async);
+    /// Functions of the module `callee` may reach, and the effects of the
+    /// builtins or functions from elsewhere it names
+    fn resolve(&self, callee: &Callee) -> (BTreeSet<String>, EffectSet) {
+        let mut functions = BTreeSet::new();
+        let mut effects = EffectSet::pure();
+        let mut seen = HashSet::new();
+        self.resolve_into(callee, &mut seen, &mut functions, &mut effects);
+        (functions, effects)
+    }
 
-        // Analyze function body
-        for stmt in &func.body {
-            let stmt_effects = self.infer_stmt_effects(stmt);
-            effects = effects.union(stmt_effects);
+    fn resolve_into(&self, callee: &Callee, seen: &mut HashSet<Callee>, functions: &mut BTreeSet<String>, effects: &mut EffectSet) {
+        if !seen.insert(callee.clone()) {
+            return;
         }
+        let (scope, name) = match callee {
+            Callee::Function(key) => {
+                functions.insert(key.clone());
+                return;
+            }
+            Callee::Name { scope, name } => (scope, name),
+        };
 
-        self.function_effects.insert(func.name.to_string(), effects);
+        // Locals and parameters hold whatever flows into them
+        let local = (scope.clone(), name.clone());
+        let is_param = self.functions.get(scope).is_some_and(|info| info.named.contains(name));
+        if !scope.is_empty() && (is_param || self.flows.contains_key(&local)) {
+            for target in self.flows.get(&local).into_iter().flatten() {
+                self.resolve_into(target, seen, functions, effects);
+            }
+            return;
+        }
+        if self.functions.contains_key(name) {
+            functions.insert(name.clone());
+            return;
+        }
+        if let Some(targets) = self.flows.get(&(String::new(), name.clone())) {
+            for target in targets {
+                self.resolve_into(target, seen, functions, effects);
+            }
+            return;
+        }
+        if let Some(builtin) = self.builtin_effects.get(name) {
+            *effects = effects.clone().union(builtin.clone());
+            return;
+        }
+        // Functions checked elsewhere carry their effects in their type
+        if let Some(Type::Effect(_, found)) = self.ctx.get_type(name) {
+            *effects = effects.clone().union(found);
+        }
     }
 
     /// Infer effects of a statement
-    fn infer_stmt_effects(&mut self, stmt: &Stmt) -> EffectSet {
-        match stmt {
-            Stmt::Expr(expr_stmt) => self.infer_expr_effects(&expr_stmt.value),
-            Stmt::Assign(assign) => {
-                let mut effects = self.infer_expr_effects(&assign.value);
+    fn infer_stmt_effects(&mut self, stmt: &ir::Stmt) -> EffectSet {
+        match &stmt.kind {
+            StmtKind::Expr(value) => self.infer_expr_effects(value),
+            StmtKind::Assign { targets, value, .. } => {
+                for target in targets {
+                    self.bind(target, Some(value), None);
+                }
+                let mut effects = self.infer_expr_effects(value);
                 // Assignment is mutation
                 effects = effects.union(EffectSet::single(Effect::Mutation));
                 effects
             }
-            Stmt::AugAssign(aug) => {
-                let mut effects = self.infer_expr_effects(&aug.value);
+            StmtKind::AnnAssign { target, annotation, value, .. } => {
+                self.bind(target, value.as_ref(), Some(annotation));
+                match value {
+                    Some(value) => self.infer_expr_effects(value).union(EffectSet::single(Effect::Mutation)),
+                    None => EffectSet::pure(),
+                }
+            }
+            StmtKind::AugAssign { value, .. } => {
+                let mut effects = self.infer_expr_effects(value);
                 effects = effects.union(EffectSet::single(Effect::Mutation));
                 effects
             }
-            Stmt::Raise(_) => EffectSet::single(Effect::Exception),
-            Stmt::Return(ret) => {
-                ret.value.as_ref()
+            StmtKind::Raise { .. } => EffectSet::single(Effect::Exception),
+            StmtKind::Return(value) => {
+                value.as_ref()
                     .map(|v| self.infer_expr_effects(v))
                     .unwrap_or_else(EffectSet::pure)
             }
-            Stmt::For(for_stmt) => {
-                let mut effects = self.infer_expr_effects(&for_stmt.iter);
-                for body_stmt in &for_stmt.body {
+            StmtKind::For { is_async: false, iter, body, .. } => {
+                let mut effects = self.infer_expr_effects(iter);
+                for body_stmt in body {
                     effects = effects.union(self.infer_stmt_effects(body_stmt));
                 }
                 effects
             }
-            Stmt::While(while_stmt) => {
-                let mut effects = self.infer_expr_effects(&while_stmt.test);
-                for body_stmt in &while_stmt.body {
+            StmtKind::While { test, body, .. } => {
+                let mut effects = self.infer_expr_effects(test);
+                for body_stmt in body {
                     effects = effects.union(self.infer_stmt_effects(body_stmt));
                 }
                 effects
             }
-            Stmt::If(if_stmt) => {
-                let mut effects = self.infer_expr_effects(&if_stmt.test);
-                for body_stmt in &if_stmt.body {
+            StmtKind::If { test, body, orelse } => {
+                let mut effects = self.infer_expr_effects(test);
+                for body_stmt in body {
                     effects = effects.union(self.infer_stmt_effects(body_stmt));
                 }
-                for else_stmt in &if_stmt.orelse {
+                for else_stmt in orelse {
                     effects = effects.union(self.infer_stmt_effects(else_stmt));
                 }
                 effects
             }
+            // A `raise` anywhere in the blocks counts, even if a handler catches it
+            StmtKind::Try { body, handlers, orelse, finalbody, .. } => {
+                let handler_stmts = handlers.iter().flat_map(|handler| &handler.body);
+                body.iter().chain(handler_stmts).chain(orelse).chain(finalbody)
+                    .fold(EffectSet::pure(), |acc, s| acc.union(self.infer_stmt_effects(s)))
+            }
+            StmtKind::With { items, body, .. } => {
+                let effects = items.iter()
+                    .fold(EffectSet::pure(), |acc, item| acc.union(self.infer_expr_effects(&item.context)));
+                body.iter().fold(effects, |acc, s| acc.union(self.infer_stmt_effects(s)))
+            }
             _ => EffectSet::pure(),
         }
     }
 
+    /// Record what assigning `value` to `target` binds: the functions it may
+    /// hold, and the class of the instance it holds
+    fn bind(&mut self, target: &ir::Expr, value: Option<&ir::Expr>, annotation: Option<&ir::Expr>) {
+        let ExprKind::Name(name) = &target.kind else { return };
+        if let Some(value) = value {
+            let targets = self.targets_of(value);
+            if !targets.is_empty() {
+                self.flows.entry((self.scope.key.clone(), name.to_string())).or_default().extend(targets);
+            }
+        }
+        let class = annotation.and_then(|annotation| self.class_named(annotation)).or_else(|| match value.map(|value| &value.kind) {
+            Some(ExprKind::Call { func, .. }) => self.class_named(func),
+            _ => None,
+        });
+        if let Some(class) = class {
+            self.scope.instances.insert(name.to_string(), class);
+        }
+    }
+
+    /// Functions an expression may evaluate to
+    fn targets_of(&self, expr: &ir::Expr) -> BTreeSet<Callee> {
+        match &expr.kind {
+            ExprKind::Name(name) => BTreeSet::from([self.name(name)]),
+            ExprKind::Attribute { value, attr } => self.receiver_class(value)
+                .and_then(|(class, _)| self.find_method(&class, attr))
+                .map(|key| BTreeSet::from([Callee::Function(key)]))
+                .unwrap_or_default(),
+            ExprKind::IfExp { body, orelse, .. } => {
+                let mut targets = self.targets_of(body);
+                targets.extend(self.targets_of(orelse));
+                targets
+            }
+            ExprKind::BoolOp { values, .. } => values.iter().flat_map(|value| self.targets_of(value)).collect(),
+            _ => BTreeSet::new(),
+        }
+    }
+
+    /// Whether `name` is a parameter, variable or function of the module
+    /// rather than a builtin
+    fn is_bound(&self, name: &str) -> bool {
+        self.scope.params.contains(name)
+            || self.functions.contains_key(name)
+            || self.flows.contains_key(&(self.scope.key.clone(), name.to_string()))
+            || self.flows.contains_key(&(String::new(), name.to_string()))
+    }
+
+    fn name(&self, name: &str) -> Callee {
+        Callee::Name { scope: self.scope.key.clone(), name: name.to_string() }
+    }
+
+    /// Class an annotation or callee names, when it is a class of the module
+    /// or one the context knows
+    fn class_named(&self, expr: &ir::Expr) -> Option<String> {
+        let ExprKind::Name(name) = &expr.kind else { return None };
+        (self.classes.contains_key(name.as_ref()) || self.ctx.get_class(name).is_some()).then(|| name.to_string())
+    }
+
+    /// Class of the object a method is called on, and whether it is the
+    /// class object rather than an instance
+    fn receiver_class(&self, value: &ir::Expr) -> Option<(String, bool)> {
+        match &value.kind {
+            ExprKind::Name(name) => {
+                if let Some((receiver, is_class)) = &self.scope.receiver {
+                    if receiver.as_str() == name.as_ref() {
+                        return self.scope.class.clone().map(|class| (class, *is_class));
+                    }
+                }
+                if let Some(class) = self.scope.instances.get(name.as_ref()) {
+                    return Some((class.clone(), false));
+                }
+                if self.classes.contains_key(name.as_ref()) && !self.scope.params.contains(name.as_ref()) {
+                    return Some((name.to_string(), true));
+                }
+                match self.ctx.get_type(name) {
+                    Some(Type::Class(class)) => Some((class, false)),
+                    Some(Type::ClassObject(class)) => Some((class, true)),
+                    _ => None,
+                }
+            }
+            ExprKind::Call { func, .. } => self.class_named(func).map(|class| (class, false)),
+            _ => None,
+        }
+    }
+
+    /// Key of the method `attr` of `class` or the nearest of its bases in
+    /// the module defining it
+    fn find_method(&self, class: &str, attr: &str) -> Option<String> {
+        let mut pending = vec![class.to_string()];
+        let mut seen = HashSet::new();
+        while let Some(class) = pending.pop() {
+            if !seen.insert(class.clone()) {
+                continue;
+            }
+            let Some(info) = self.classes.get(&class) else { continue };
+            if info.methods.contains(attr) {
+                return Some(format!("{}.{}", class, attr));
+            }
+            pending.extend(info.bases.iter().rev().cloned());
+        }
+        None
+    }
+
     /// Infer effects of an expression
-    fn infer_expr_effects(&mut self, expr: &Expr) -> EffectSet {
-        match expr {
-            Expr::Call(call) => self.infer_call_effects(call),
-            Expr::Await(_) => EffectSet::single(Effect::Async),
-            Expr::Yield(_) | Expr::YieldFrom(_) => EffectSet::single(Effect::Async),
-            Expr::BinOp(binop) => {
-                let left = self.infer_expr_effects(&binop.left);
-                let right = self.infer_expr_effects(&binop.right);
+    fn infer_expr_effects(&mut self, expr: &ir::Expr) -> EffectSet {
+        match &expr.kind {
+            ExprKind::Call { func, args, keywords } => self.infer_call_effects(expr, func, args, keywords),
+            ExprKind::Await(_) => EffectSet::single(Effect::Async),
+            ExprKind::Yield(_) | ExprKind::YieldFrom(_) => EffectSet::single(Effect::Async),
+            ExprKind::BinOp { left, right, .. } => {
+                let left = self.infer_expr_effects(left);
+                let right = self.infer_expr_effects(right);
                 left.union(right)
             }
-            Expr::UnaryOp(unary) => self.infer_expr_effects(&unary.operand),
-            Expr::Lambda(lambda) => self.infer_expr_effects(&lambda.body),
-            Expr::IfExp(if_exp) => {
-                let test = self.infer_expr_effects(&if_exp.test);
-                let body = self.infer_expr_effects(&if_exp.body);
-                let orelse = self.infer_expr_effects(&if_exp.orelse);
+            ExprKind::UnaryOp { operand, .. } => self.infer_expr_effects(operand),
+            ExprKind::Lambda { body, .. } => self.infer_expr_effects(body),
+            ExprKind::IfExp { test, body, orelse } => {
+                let test = self.infer_expr_effects(test);
+                let body = self.infer_expr_effects(body);
+                let orelse = self.infer_expr_effects(orelse);
                 test.union(body).union(orelse)
             }
-            Expr::ListComp(comp) => {
-                let mut effects = self.infer_expr_effects(&comp.elt);
-                for gen in &comp.generators {
+            ExprKind::Comprehension { kind: ComprehensionKind::List, elt, generators, .. } => {
+                let mut effects = self.infer_expr_effects(elt);
+                for gen in generators {
                     effects = effects.union(self.infer_expr_effects(&gen.iter));
                     for cond in &gen.ifs {
                         effects = effects.union(self.infer_expr_effects(cond));
@@ -201,22 +621,16 @@ impl EffectAnalyzer {
                 }
                 effects
             }
-            Expr::List(list) => {
-                list.elts.iter()
-                    .fold(EffectSet::pure(), |acc, e| acc.union(self.infer_expr_effects(e)))
-            }
-            Expr::Tuple(tuple) => {
-                tuple.elts.iter()
+            ExprKind::List(elts) | ExprKind::Tuple(elts) => {
+                elts.iter()
                     .fold(EffectSet::pure(), |acc, e| acc.union(self.infer_expr_effects(e)))
             }
-            Expr::Dict(dict) => {
+            ExprKind::Dict { keys, values } => {
                 let mut effects = EffectSet::pure();
-                for key in &dict.keys {
-                    if let Some(k) = key {
-                        effects = effects.union(self.infer_expr_effects(k));
-                    }
+                for key in keys.iter().flatten() {
+                    effects = effects.union(self.infer_expr_effects(key));
                 }
-                for value in &dict.values {
+                for value in values {
                     effects = effects.union(self.infer_expr_effects(value));
                 }
                 effects
@@ -225,66 +639,149 @@ impl EffectAnalyzer {
         }
     }
 
-    /// Infer effects of a function call
-    fn infer_call_effects(&mut self, call: &ExprCall) -> EffectSet {
-        // Check if it's a builtin
-        if let Expr::Name(name) = &*call.func {
-            if let Some(effects) = self.builtin_effects.get(name.id.as_str()) {
-                return effects.clone();
-            }
-
-            // Check if we've analyzed this function
-            if let Some(effects) = self.function_effects.get(name.id.as_str()) {
-                return effects.clone();
-            }
-        }
-
-        // Check if function type has effects
-        if let Some(func_ty) = self.get_function_type(&call.func) {
-            if let Type::Effect(_, effects) = func_ty {
-                return effects;
-            }
-        }
-
-        // Analyze arguments
+    /// Infer effects of a function call: those of its arguments now, and
+    /// the callee's once every function's are known
+    fn infer_call_effects(&mut self, call: &ir::Expr, func: &ir::Expr, args: &[ir::Expr], keywords: &[ir::Keyword]) -> EffectSet {
         let mut effects = EffectSet::pure();
-        for arg in &call.args {
+        for arg in args.iter().chain(keywords.iter().map(|keyword| &keyword.value)) {
             effects = effects.union(self.infer_expr_effects(arg));
         }
 
-        // Conservative: assume unknown functions might have effects
-        effects
-    }
+        // Calling a class runs its `__init__`; calling a method through an
+        // instance or class binds its receiver
+        let mut own = EffectSet::pure();
+        let (callee, offset) = match &func.kind {
+            ExprKind::Name(name) if self.classes.contains_key(name.as_ref()) => {
+                (self.find_method(name, "__init__").map(Callee::Function), 1)
+            }
+            // Builtins nothing in the module shadows are known right away
+            ExprKind::Name(name) if !self.is_bound(name) && self.builtin_effects.contains_key(name.as_ref()) => {
+                own = self.builtin_effects[name.as_ref()].clone();
+                (None, 0)
+            }
+            ExprKind::Name(name) => (Some(self.name(name)), 0),
+            ExprKind::Attribute { value, attr } => {
+                effects = effects.union(self.infer_expr_effects(value));
+                match self.receiver_class(value) {
+                    Some((class, is_class)) => match self.find_method(&class, attr) {
+                        Some(key) => {
+                            let binds = match self.functions.get(&key).map(|info| info.receiver) {
+                                Some(Receiver::Instance) => !is_class,
+                                Some(Receiver::Class) => true,
+                                _ => false,
+                            };
+                            (Some(Callee::Function(key)), binds as usize)
+                        }
+                        None => {
+                            // Methods of classes checked elsewhere carry their effects in their type
+                            if let Some(Type::Effect(_, found)) = self.ctx.has_attribute(&Type::Class(class), attr) {
+                                own = found;
+                            }
+                            (None, 0)
+                        }
+                    },
+                    None => (None, 0),
+                }
+            }
+            _ => {
+                effects = effects.union(self.infer_expr_effects(func));
+                (None, 0)
+            }
+        };
 
-    fn get_function_type(&self, expr: &Expr) -> Option<Type> {
-        if let Expr::Name(name) = expr {
-            self.ctx.get_type(&name.id)
-        } else {
-            None
+        let slots = args.iter().enumerate().map(|(i, arg)| (Slot::Position(i), arg))
+            .chain(keywords.iter().filter_map(|keyword| Some((Slot::Keyword(keyword.arg.as_ref()?.to_string()), &keyword.value))));
+        let passed: Vec<(Slot, BTreeSet<Callee>)> = slots
+            .map(|(slot, arg)| (slot, self.targets_of(arg)))
+            .filter(|(_, targets)| !targets.is_empty())
+            .collect();
+        self.scope.sites.push(CallSite {
+            range: call.span.range.clone(),
+            effects: own.clone(),
+            callee: callee.clone(),
+            passed: passed.iter().flat_map(|(_, targets)| targets.iter().cloned()).collect(),
+        });
+        if !passed.is_empty() {
+            self.passes.push(Pass { caller: self.scope.key.clone(), callee: callee.clone(), offset, args: passed });
+        }
+        if let Some(callee) = callee {
+            self.scope.calls.insert(callee);
         }
+        effects.union(own)
     }
 
-    /// Get inferred effects for a function
+    /// Get inferred effects for a function, or `Class.method` for a method
     pub fn get_function_effects(&self, name: &str) -> Option<&EffectSet> {
         self.function_effects.get(name)
     }
 
+    /// Effects of each call in the body of `function` (`Class.method` for a
+    /// method), by the byte range of the call. A call's arguments are calls
+    /// of their own
+    pub fn call_effects(&self, function: &str) -> Vec<(Range<usize>, EffectSet)> {
+        let sites = self.call_sites.get(function).map(Vec::as_slice).unwrap_or_default();
+        sites.iter()
+            .map(|site| {
+                let reached = site.callee.as_ref().map(|callee| self.resolve(callee).0).unwrap_or_default();
+                // Builtins and functions from elsewhere may call what they're given
+                let passed = reached.is_empty().then_some(&site.passed).into_iter().flatten();
+                let effects = site.callee.iter().chain(passed)
+                    .fold(site.effects.clone(), |acc, callee| acc.union(self.effects_of(callee)));
+                (site.range.clone(), effects)
+            })
+            .collect()
+    }
+
+    /// Effects of whatever `callee` may reach
+    fn effects_of(&self, callee: &Callee) -> EffectSet {
+        let (functions, external) = self.resolve(callee);
+        functions.iter()
+            .filter_map(|function| self.function_effects.get(function))
+            .fold(external, |acc, effects| acc.union(effects.clone()))
+    }
+
     /// Check if an expression is pure
     pub fn is_pure_expr(&mut self, expr: &Expr) -> bool {
-        self.infer_expr_effects(expr).is_pure()
+        let mut effects = self.infer_expr_effects(&lower_expr(expr, None));
+        self.scope.sites.clear();
+        for callee in std::mem::take(&mut self.scope.calls) {
+            effects = effects.union(self.effects_of(&callee));
+        }
+        effects.is_pure()
     }
 
-    /// Add effect annotation to function type
+    /// Wrap the type of the function `name` (`Class.method` for a method)
+    /// in its inferred effects, pure ones included
     pub fn annotate_function_type(&self, name: &str, base_type: Type) -> Type {
-        if let Some(effects) = self.function_effects.get(name) {
-            if !effects.is_pure() {
-                return Type::Effect(Box::new(base_type), effects.clone());
-            }
+        match self.function_effects.get(name) {
+            Some(effects) => Type::Effect(Box::new(base_type), effects.clone()),
+            None => base_type,
         }
-        base_type
     }
 }
 
+/// Effects a function's decorators declare it may have: `@effects("io",
+/// "network")` allows those, and `@pure` or `@effects("pure")` none. `None`
+/// when it declares nothing
+pub fn declared_effects(decorator_list: &[Expr]) -> Option<EffectSet> {
+    decorator_list.iter().fold(None, |declared, decorator| {
+        let effects = match (decorators::decorator_name(decorator), decorator) {
+            (Some("pure"), Expr::Name(_) | Expr::Attribute(_)) => EffectSet::pure(),
+            (Some("effects"), Expr::Call(call)) => call.args.iter()
+                .filter_map(|arg| match arg {
+                    Expr::Constant(constant) => match &constant.value {
+                        Constant::Str(name) => Some(Effect::from_name(name)),
+                        _ => None,
+                    },
+                    _ => None,
+                })
+                .fold(EffectSet::pure(), |acc, effect| acc.union(EffectSet::single(effect))),
+            _ => return declared,
+        };
+        Some(declared.unwrap_or_else(EffectSet::pure).union(effects))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -331,5 +828,110 @@ mod tests {
         let effects = analyzer.get_function_effects("modify").unwrap();
         assert!(effects.contains(&Effect::Mutation));
     }
-}
 
+    #[test]
+    fn test_raise_inside_try() {
+        let ctx = Arc::new(TypeContext::new());
+        let mut analyzer = EffectAnalyzer::new(ctx);
+
+        let source = "def parse(text):\n    try:\n        pass\n    finally:\n        raise ValueError(text)";
+        let module = parse_module(source).unwrap();
+
+        analyzer.analyze_module(&module);
+
+        let effects = analyzer.get_function_effects("parse").unwrap();
+        assert!(effects.contains(&Effect::Exception));
+    }
+
+    fn analyze(source: &str) -> EffectAnalyzer {
+        let mut analyzer = EffectAnalyzer::new(Arc::new(TypeContext::new()));
+        analyzer.analyze_module(&parse_module(source).unwrap());
+        analyzer
+    }
+
+    #[test]
+    fn test_effects_propagate_to_callers() {
+        // `f` is defined before the `g` it calls
+        let analyzer = analyze("def f():\n    return g()\n\ndef g():\n    print('hi')\n");
+        assert!(analyzer.get_function_effects("g").unwrap().contains(&Effect::IO));
+        assert!(analyzer.get_function_effects("f").unwrap().contains(&Effect::IO));
+    }
+
+    #[test]
+    fn test_recursion_settles() {
+        let source = "\
+def fact(n):
+    return 1 if n <= 1 else n * fact(n - 1)
+
+def ping(n):
+    return pong(n - 1) if n else 0
+
+def pong(n):
+    print(n)
+    return ping(n)
+";
+        let analyzer = analyze(source);
+        assert!(analyzer.get_function_effects("fact").unwrap().is_pure());
+        assert!(analyzer.get_function_effects("ping").unwrap().contains(&Effect::IO));
+    }
+
+    #[test]
+    fn test_methods_and_indirect_calls() {
+        let source = "\
+class Logger:
+    def log(self, message):
+        self.emit(message)
+
+    def emit(self, message):
+        print(message)
+
+def report():
+    logger = Logger()
+    logger.log('done')
+
+def shout(text):
+    print(text)
+
+def through_variable():
+    speak = shout
+    speak('hi')
+
+def through_callback(items):
+    return list(map(shout, items))
+
+def apply(callback, value):
+    return callback(value)
+
+def through_parameter():
+    return apply(shout, 1)
+
+def pure_callback(items):
+    return sorted(items, key=abs)
+";
+        let analyzer = analyze(source);
+        let io = |name: &str| analyzer.get_function_effects(name).unwrap().contains(&Effect::IO);
+        assert!(io("Logger.log"));
+        assert!(io("report"));
+        assert!(io("through_variable"));
+        assert!(io("through_callback"));
+        assert!(io("through_parameter"));
+        assert!(!io("pure_callback"));
+    }
+
+    #[test]
+    fn test_redefined_method_settles() {
+        // The getter and setter share the key `Account.balance`
+        let source = "\
+class Account:
+    @property
+    def balance(self):
+        return self._balance
+
+    @balance.setter
+    def balance(self, value):
+        self._balance = value
+";
+        let analyzer = analyze(source);
+        assert!(analyzer.get_function_effects("Account.balance").unwrap().contains(&Effect::Mutation));
+    }
+}
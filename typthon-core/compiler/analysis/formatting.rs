@@ -0,0 +1,339 @@
+use crate::compiler::types::Type;
+use rustpython_parser::ast::*;
+
+/// Which argument a `str.format` replacement field refers to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldRef {
+    /// `{}`: the next positional argument
+    Auto,
+    /// `{0}`
+    Index(usize),
+    /// `{name}`
+    Name(String),
+}
+
+/// Replacement field of a `str.format` template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatField {
+    pub arg: FieldRef,
+    /// Formats an attribute or item of the argument, `{0.real}` or `{0[1]}`
+    pub lookup: bool,
+    /// `r`, `s` or `a` for `!r`, `!s` and `!a`
+    pub conversion: Option<char>,
+    /// Text after `:`; `None` when it has nested fields, whose values
+    /// aren't known until the call
+    pub spec: Option<String>,
+}
+
+/// Conversion in a `%`-style template, e.g. `%5.2f` or `%(name)s`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conversion {
+    pub key: Option<String>,
+    /// `*` widths and precisions, each taking an int argument before the value
+    pub stars: usize,
+    pub code: char,
+}
+
+/// What builtin formatting can do with a value, as far as its type tells
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    Int,
+    Float,
+    Str,
+    /// None, bytes and containers: no numeric conversions and no format spec
+    Other,
+}
+
+impl ValueKind {
+    /// Kind of every value of type `ty`, `None` if it could be anything,
+    /// e.g. a class with its own `__format__`
+    pub fn of(ty: &Type) -> Option<Self> {
+        match ty {
+            Type::Int | Type::Bool => Some(Self::Int),
+            Type::Float => Some(Self::Float),
+            Type::Str => Some(Self::Str),
+            Type::None | Type::Bytes | Type::List(_) | Type::Set(_) | Type::Dict(..) | Type::Tuple(_) | Type::VarTuple(_) => {
+                Some(Self::Other)
+            }
+            Type::Effect(inner, _) | Type::Refinement(inner, _) | Type::Dependent(inner, _) | Type::Nominal(_, inner) => {
+                Self::of(inner)
+            }
+            Type::Union(members) => {
+                let first = Self::of(members.first()?)?;
+                members.iter().all(|member| Self::of(member) == Some(first)).then_some(first)
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether `format(value, spec)` takes a spec ending in type code `code`
+    pub fn accepts_spec(self, code: char) -> bool {
+        match self {
+            Self::Int => "bcdoxXneEfFgG%".contains(code),
+            Self::Float => "eEfFgGn%".contains(code),
+            Self::Str => code == 's',
+            Self::Other => false,
+        }
+    }
+
+    /// Whether `%`-formatting takes this kind of value for conversion `code`
+    pub fn accepts_percent(self, code: char) -> bool {
+        match percent_requirement(code) {
+            None => true,
+            Some(INTEGER) => self == Self::Int,
+            Some(REAL) => matches!(self, Self::Int | Self::Float),
+            Some(_) => matches!(self, Self::Int | Self::Str),
+        }
+    }
+}
+
+const INTEGER: &str = "an integer";
+const REAL: &str = "a real number";
+
+/// What `%`-conversion `code` requires of its value, e.g. `"a real number"`
+/// for `%d`; `None` for `%s`, `%r` and `%a`, which take anything
+pub fn percent_requirement(code: char) -> Option<&'static str> {
+    match code {
+        'd' | 'i' | 'u' | 'e' | 'E' | 'f' | 'F' | 'g' | 'G' => Some(REAL),
+        'o' | 'x' | 'X' => Some(INTEGER),
+        'c' => Some("an int or a single character"),
+        _ => None,
+    }
+}
+
+/// Value of a string literal expression
+pub fn string_literal(expr: &Expr) -> Option<&str> {
+    match expr {
+        Expr::Constant(ExprConstant { value: Constant::Str(s), .. }) => Some(s),
+        _ => None,
+    }
+}
+
+/// Text of an f-string format spec, `None` if it has nested fields
+pub fn constant_spec(spec: &Expr) -> Option<String> {
+    match spec {
+        Expr::JoinedStr(joined) => joined.values.iter().map(|value| string_literal(value).map(str::to_string)).collect(),
+        other => string_literal(other).map(str::to_string),
+    }
+}
+
+/// Presentation type a format spec ends in, e.g. `f` for `>10.2f`
+pub fn spec_type(spec: &str) -> Option<char> {
+    spec.chars().last().filter(|c| "bcdeEfFgGnosxX%".contains(*c))
+}
+
+/// Replacement fields of `template` in argument order: each field comes
+/// before the fields nested in its spec, the way `str.format` numbers them
+pub fn format_fields(template: &str) -> Result<Vec<FormatField>, String> {
+    let mut fields = Vec::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' | '}' if chars.peek() == Some(&c) => {
+                chars.next();
+            }
+            '}' => return Err("Single '}' encountered in format string".to_string()),
+            '{' => {
+                let mut body = String::new();
+                let mut depth = 1;
+                loop {
+                    let next = chars.next().ok_or_else(|| match body.is_empty() {
+                        true => "Single '{' encountered in format string".to_string(),
+                        false => "expected '}' before end of string".to_string(),
+                    })?;
+                    match next {
+                        '{' => depth += 1,
+                        '}' => depth -= 1,
+                        _ => {}
+                    }
+                    if depth == 0 {
+                        break;
+                    }
+                    body.push(next);
+                }
+                parse_field(&body, &mut fields)?;
+            }
+            _ => {}
+        }
+    }
+    Ok(fields)
+}
+
+/// Parse the text between a field's braces, `name[.attr]!conv:spec`
+fn parse_field(body: &str, fields: &mut Vec<FormatField>) -> Result<(), String> {
+    // `!` and `:` inside an item lookup, `{0[a:b]}`, belong to the name
+    let mut in_brackets = false;
+    let name_end = body
+        .char_indices()
+        .find(|&(_, c)| {
+            match c {
+                '[' => in_brackets = true,
+                ']' => in_brackets = false,
+                _ => {}
+            }
+            !in_brackets && (c == '!' || c == ':')
+        })
+        .map_or(body.len(), |(i, _)| i);
+    let (name, rest) = body.split_at(name_end);
+
+    let arg_end = name.find(['.', '[']).unwrap_or(name.len());
+    let arg = match &name[..arg_end] {
+        "" => FieldRef::Auto,
+        index if index.bytes().all(|b| b.is_ascii_digit()) => {
+            FieldRef::Index(index.parse().map_err(|_| "Too many decimal digits in format string".to_string())?)
+        }
+        name => FieldRef::Name(name.to_string()),
+    };
+
+    let (conversion, spec) = match rest.strip_prefix('!') {
+        Some(after) => {
+            let mut after = after.chars();
+            let conversion = after.next().ok_or("end of string while looking for conversion specifier")?;
+            if !matches!(conversion, 'r' | 's' | 'a') {
+                return Err(format!("Unknown conversion specifier {}", conversion));
+            }
+            let spec = after.as_str();
+            match spec.strip_prefix(':') {
+                Some(spec) => (Some(conversion), Some(spec)),
+                None if spec.is_empty() => (Some(conversion), None),
+                None => return Err("expected ':' after conversion specifier".to_string()),
+            }
+        }
+        None => (None, rest.strip_prefix(':')),
+    };
+
+    let nested = spec.is_some_and(|spec| spec.contains('{'));
+    fields.push(FormatField {
+        arg,
+        lookup: arg_end < name.len(),
+        conversion,
+        spec: spec.filter(|_| !nested).map(str::to_string),
+    });
+    if let Some(spec) = spec.filter(|_| nested) {
+        fields.extend(format_fields(spec)?);
+    }
+    Ok(())
+}
+
+/// Positional arguments `fields` need: the count of automatic fields, or
+/// one past the highest index. Mixing the two styles is an error
+pub fn positional_needed(fields: &[FormatField]) -> Result<usize, String> {
+    let auto = fields.iter().filter(|field| field.arg == FieldRef::Auto).count();
+    let highest = fields.iter().filter_map(|field| match field.arg {
+        FieldRef::Index(index) => Some(index + 1),
+        _ => None,
+    }).max();
+    match highest {
+        Some(_) if auto > 0 => Err("cannot switch from automatic field numbering to manual field specification".to_string()),
+        Some(highest) => Ok(highest),
+        None => Ok(auto),
+    }
+}
+
+/// Conversions of a `%`-style template, without the `%%` escapes
+pub fn percent_conversions(template: &str) -> Result<Vec<Conversion>, String> {
+    let mut conversions = Vec::new();
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            continue;
+        }
+        let mut key = None;
+        let mut stars = 0;
+        let mut next = chars.next();
+
+        if next == Some('(') {
+            let mut name = String::new();
+            let mut depth = 1;
+            loop {
+                let c = chars.next().ok_or("incomplete format key")?;
+                match c {
+                    '(' => depth += 1,
+                    ')' => depth -= 1,
+                    _ => {}
+                }
+                if depth == 0 {
+                    break;
+                }
+                name.push(c);
+            }
+            key = Some(name);
+            next = chars.next();
+        }
+        while matches!(next, Some('#' | '0' | '-' | ' ' | '+')) {
+            next = chars.next();
+        }
+        next = skip_width(&mut chars, next, &mut stars);
+        if next == Some('.') {
+            next = chars.next();
+            next = skip_width(&mut chars, next, &mut stars);
+        }
+        while matches!(next, Some('h' | 'l' | 'L')) {
+            next = chars.next();
+        }
+
+        match next {
+            None => return Err("incomplete format".to_string()),
+            Some('%') => {}
+            Some(code) if "diouxXeEfFgGcrsa".contains(code) => conversions.push(Conversion { key, stars, code }),
+            Some(code) => return Err(format!("unsupported format character '{}'", code)),
+        }
+    }
+    Ok(conversions)
+}
+
+/// Skip a width or precision, counting it in `stars` if it's `*`; returns the character after it
+fn skip_width(chars: &mut std::str::Chars, mut next: Option<char>, stars: &mut usize) -> Option<char> {
+    if next == Some('*') {
+        *stars += 1;
+        return chars.next();
+    }
+    while next.is_some_and(|c| c.is_ascii_digit()) {
+        next = chars.next();
+    }
+    next
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_fields_number_nested_specs_after_their_field() {
+        let fields = format_fields("{{x}} {0.real!r:>{1}} {name[0]}").unwrap();
+        let args: Vec<_> = fields.iter().map(|field| field.arg.clone()).collect();
+        assert_eq!(args, vec![FieldRef::Index(0), FieldRef::Index(1), FieldRef::Name("name".to_string())]);
+        assert!(fields[0].lookup && fields[0].spec.is_none());
+        assert_eq!(fields[0].conversion, Some('r'));
+        assert_eq!(positional_needed(&fields), Ok(2));
+    }
+
+    #[test]
+    fn test_format_fields_reject_malformed_templates() {
+        assert!(format_fields("{").is_err());
+        assert!(format_fields("}").is_err());
+        assert!(format_fields("{0").is_err());
+        assert!(format_fields("{!x}").is_err());
+        assert!(positional_needed(&format_fields("{} {0}").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_percent_conversions_count_stars_and_skip_escapes() {
+        let conversions = percent_conversions("%-*.*f%% %(name)s %05d").unwrap();
+        let codes: Vec<_> = conversions.iter().map(|conversion| (conversion.code, conversion.stars)).collect();
+        assert_eq!(codes, vec![('f', 2), ('s', 0), ('d', 0)]);
+        assert_eq!(conversions[1].key.as_deref(), Some("name"));
+        assert!(percent_conversions("100%").is_err());
+        assert!(percent_conversions("%y").is_err());
+    }
+
+    #[test]
+    fn test_value_kinds_accept_their_codes() {
+        assert!(ValueKind::Int.accepts_spec('x') && !ValueKind::Float.accepts_spec('x'));
+        assert!(!ValueKind::Str.accepts_spec('d'));
+        assert!(ValueKind::Float.accepts_percent('d') && !ValueKind::Float.accepts_percent('x'));
+        assert!(!ValueKind::Str.accepts_percent('d') && ValueKind::Str.accepts_percent('c'));
+        assert_eq!(ValueKind::of(&Type::Union(vec![Type::Int, Type::Bool])), Some(ValueKind::Int));
+        assert_eq!(ValueKind::of(&Type::Union(vec![Type::Int, Type::Str])), None);
+    }
+}
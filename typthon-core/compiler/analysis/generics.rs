@@ -0,0 +1,137 @@
+//! `TypeVar`s and the generic classes parameterized over them
+//!
+//! `T_co = TypeVar("T_co", covariant=True)` declares a type variable; a class
+//! with `Generic[T_co]` among its bases, or any subscripted base mentioning
+//! type variables, such as `Protocol[T]` or `Mapping[K, V]`, takes them as its
+//! type parameters, in order.
+
+use super::constraints::TypeParameter;
+use super::variance::Variance;
+use rustpython_parser::ast::*;
+use std::collections::HashMap;
+
+/// The type variable declared by `TypeVar("T", ...)`, with the variance its
+/// `covariant=` / `contravariant=` keywords give it
+pub fn type_var(value: &Expr) -> Option<TypeParameter> {
+    let Expr::Call(call) = value else { return None };
+    if !is_type_var(&call.func) {
+        return None;
+    }
+    let name = match call.args.first() {
+        Some(Expr::Constant(constant)) => match &constant.value {
+            Constant::Str(name) => name.clone(),
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    let flag = |keyword: &str| call.keywords.iter().any(|k| {
+        k.arg.as_ref().is_some_and(|arg| arg.as_str() == keyword)
+            && matches!(&k.value, Expr::Constant(c) if matches!(c.value, Constant::Bool(true)))
+    });
+    let variance = match (flag("covariant"), flag("contravariant")) {
+        (true, false) => Variance::Covariant,
+        (false, true) => Variance::Contravariant,
+        _ => Variance::Invariant,
+    };
+    Some(TypeParameter::new(name).with_variance(variance))
+}
+
+/// Type parameters of a class with `bases`: those of its `Generic[...]`
+/// base, or else every type variable its subscripted bases mention
+pub fn class_type_params(bases: &[Expr], type_vars: &HashMap<String, TypeParameter>) -> Vec<TypeParameter> {
+    let subscripts = bases.iter().filter_map(|base| match base {
+        Expr::Subscript(subscript) => Some(subscript),
+        _ => None,
+    });
+    let mut names = Vec::new();
+    for subscript in subscripts {
+        let args: Vec<&Expr> = match &*subscript.slice {
+            Expr::Tuple(tuple) => tuple.elts.iter().collect(),
+            arg => vec![arg],
+        };
+        let vars = args.into_iter().filter_map(|arg| match arg {
+            Expr::Name(name) if type_vars.contains_key(name.id.as_str()) => Some(name.id.as_str()),
+            _ => None,
+        });
+        if is_named(&subscript.value, "Generic") {
+            names = vars.collect();
+            break;
+        }
+        for var in vars {
+            if !names.contains(&var) {
+                names.push(var);
+            }
+        }
+    }
+    names.into_iter().map(|name| type_vars[name].clone()).collect()
+}
+
+/// `Generic` or `Generic[...]`, which isn't a class instances inherit from
+pub fn is_generic_base(base: &Expr) -> bool {
+    match base {
+        Expr::Subscript(subscript) => is_generic_base(&subscript.value),
+        base => is_named(base, "Generic"),
+    }
+}
+
+fn is_type_var(expr: &Expr) -> bool {
+    is_named(expr, "TypeVar")
+}
+
+/// `name`, or `typing.name` / `typing_extensions.name`
+fn is_named(expr: &Expr, name: &str) -> bool {
+    match expr {
+        Expr::Name(n) => n.id.as_str() == name,
+        Expr::Attribute(attr) => attr.attr.as_str() == name
+            && matches!(&*attr.value, Expr::Name(module) if matches!(module.id.as_str(), "typing" | "typing_extensions")),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::frontend::parse_module;
+
+    fn statements(source: &str) -> Vec<Stmt> {
+        let Mod::Module(module) = parse_module(source).unwrap() else { unreachable!() };
+        module.body
+    }
+
+    fn assigned(stmt: &Stmt) -> &Expr {
+        match stmt {
+            Stmt::Assign(assign) => &assign.value,
+            other => panic!("expected an assignment, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_type_var_variance() {
+        let stmts = statements(
+            "T = TypeVar('T')\nT_co = TypeVar('T_co', covariant=True)\nT_contra = typing.TypeVar('T_contra', contravariant=True)\nX = f('X')\n",
+        );
+        let variances: Vec<Option<Variance>> = stmts.iter()
+            .map(|stmt| type_var(assigned(stmt)).map(|param| param.variance))
+            .collect();
+        assert_eq!(variances, vec![
+            Some(Variance::Invariant), Some(Variance::Covariant), Some(Variance::Contravariant), None,
+        ]);
+    }
+
+    #[test]
+    fn test_class_type_params() {
+        let type_vars: HashMap<String, TypeParameter> = ["K", "V", "T"].iter()
+            .map(|name| (name.to_string(), TypeParameter::new(name.to_string())))
+            .collect();
+        let params = |source: &str| {
+            let Some(Stmt::ClassDef(class_def)) = statements(source).pop() else { unreachable!() };
+            class_type_params(&class_def.bases, &type_vars).into_iter().map(|param| param.name).collect::<Vec<_>>()
+        };
+
+        assert_eq!(params("class A(Generic[V, K]): pass\n"), vec!["V", "K"]);
+        assert_eq!(params("class A(Mapping[K, V], Protocol[T]): pass\n"), vec!["K", "V", "T"]);
+        assert_eq!(params("class A(Base[int], Generic[T]): pass\n"), vec!["T"]);
+        assert!(params("class A(Base): pass\n").is_empty());
+    }
+}
@@ -0,0 +1,404 @@
+//! Module resolution - types of names imported from other project files
+//!
+//! An import that resolves to a project file has that file checked first,
+//! and the names it binds at the top level come into the importing module
+//! with their types, call signatures and class schemas. Exports are kept per
+//! file by a `ModuleResolver`, which the checkers of different files share.
+//! Modules on an import cycle see each other's names as unknown and are never
+//! recorded, so what an importer sees doesn't depend on which file of the
+//! cycle was checked first. The resolver keeps the imports each module runs
+//! when it's loaded in a `DependencyGraph`, where the cycles are found.
+//!
+//! A `.pyi` stub next to a module is read instead of it. Modules outside the
+//! project come from stubs under the configured stub paths, or from the few
+//! bundled with typthon for the standard library. Stubs are declarations
+//! only: their function bodies aren't checked, and `@overload`s of a function
+//! are collapsed into a union of their types.
+
+use dashmap::DashMap;
+use rustpython_parser::ast::{ExceptHandler, Expr, Stmt};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use crate::compiler::analysis::architecture::module_file;
+use crate::compiler::ast::Docstring;
+use crate::compiler::types::{ClassSchema, FunctionSig, Type};
+use crate::infrastructure::incremental::{ContentHash, DependencyGraph, ModuleId, ModuleMetadata};
+use crate::infrastructure::paths::ModulePath;
+
+/// What a module binds at the top level, as its importers see it
+#[derive(Debug, Clone, Default)]
+pub struct ModuleExports {
+    pub types: HashMap<String, Type>,
+    pub docs: HashMap<String, Docstring>,
+    /// Exported functions by name, and methods of exported classes as `Class.method`
+    pub signatures: HashMap<String, FunctionSig>,
+    /// Exported classes and the project classes they derive from
+    pub classes: Vec<ClassSchema>,
+    /// Instance attributes assigned through `self`, by class
+    pub instance_attributes: HashMap<String, HashMap<String, Type>>,
+    /// Modules bound by importing them, e.g. `path` in `from os import path`
+    pub modules: HashMap<String, Arc<ModuleExports>>,
+}
+
+/// Stubs shipped with typthon, by module name
+const BUNDLED_STUBS: &[(&str, &str)] = &[
+    ("builtins", include_str!("../../stubs/builtins.pyi")),
+    ("json", include_str!("../../stubs/json.pyi")),
+    ("math", include_str!("../../stubs/math.pyi")),
+    ("os", include_str!("../../stubs/os/__init__.pyi")),
+    ("os.path", include_str!("../../stubs/os/path.pyi")),
+    ("sys", include_str!("../../stubs/sys.pyi")),
+];
+
+/// Modules typthon bundles stubs for, by name, with each stub's source
+pub fn bundled_stubs() -> &'static [(&'static str, &'static str)] {
+    BUNDLED_STUBS
+}
+
+/// Where an imported module is read from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModuleOrigin {
+    /// A module or stub file
+    File(PathBuf),
+    /// One of the stubs bundled with typthon, by module name
+    Bundled(&'static str),
+}
+
+impl ModuleOrigin {
+    /// Whether the module only declares its names, as a `.pyi` does
+    pub fn is_stub(&self) -> bool {
+        match self {
+            Self::File(path) => path.extension().is_some_and(|ext| ext == "pyi"),
+            Self::Bundled(_) => true,
+        }
+    }
+
+    pub fn source(&self) -> Option<String> {
+        match self {
+            Self::File(path) => fs::read_to_string(path).ok(),
+            Self::Bundled(name) => BUNDLED_STUBS.iter().find(|(module, _)| module == name).map(|(_, source)| source.to_string()),
+        }
+    }
+
+    /// What exports are recorded and import cycles spotted by
+    pub fn key(&self) -> String {
+        match self {
+            Self::File(path) => ModulePath::new(absolute(path)).key().to_string(),
+            Self::Bundled(name) => format!("<bundled>/{}", name),
+        }
+    }
+}
+
+/// Maps imports to the files or stubs they load and keeps the exports of
+/// those checked
+pub struct ModuleResolver {
+    roots: Vec<PathBuf>,
+    exports: DashMap<String, Arc<ModuleExports>>,
+    /// Project files each checked module imports when it's loaded
+    graph: DependencyGraph,
+}
+
+impl ModuleResolver {
+    /// Absolute imports are looked up under `roots` in order, then next to
+    /// the importing file, as Python does for a script's directory
+    pub fn new(roots: impl IntoIterator<Item = PathBuf>) -> Self {
+        let mut resolved: Vec<PathBuf> = Vec::new();
+        for root in roots.into_iter().map(|root| absolute(&root)) {
+            if !resolved.contains(&root) {
+                resolved.push(root);
+            }
+        }
+        Self { roots: resolved, exports: DashMap::new(), graph: DependencyGraph::new() }
+    }
+
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// Resolver without project roots, for the stubs alone; shared by
+    /// checkers that weren't given one
+    pub fn stubs_only() -> Arc<Self> {
+        static SHARED: OnceLock<Arc<ModuleResolver>> = OnceLock::new();
+        SHARED.get_or_init(|| Arc::new(Self::new([]))).clone()
+    }
+
+    /// File loaded for `<level><module>` imported from `file`: `a.b` is
+    /// `a/b.py` or the package `a/b/__init__.py`, or a `.pyi` stub beside
+    /// either
+    pub fn resolve(&self, file: &Path, level: u32, module: &str) -> Option<PathBuf> {
+        let file = absolute(file);
+        if level > 0 {
+            let mut dir = file.parent()?;
+            for _ in 1..level {
+                dir = dir.parent()?;
+            }
+            // `from . import x` names the package itself
+            if module.is_empty() {
+                return ["__init__.pyi", "__init__.py"].iter().map(|init| dir.join(init)).find(|init| init.is_file());
+            }
+            return module_or_stub(&join_module(dir, module));
+        }
+
+        self.roots.iter()
+            .map(PathBuf::as_path)
+            .chain(file.parent())
+            .find_map(|root| module_or_stub(&join_module(root, module)))
+    }
+
+    /// Where `<level><module>` imported from `file` (if known) is read from:
+    /// the project, then stubs under `stub_paths`, then the bundled stubs
+    pub fn locate(&self, file: Option<&Path>, level: u32, module: &str, stub_paths: &[PathBuf]) -> Option<ModuleOrigin> {
+        if let Some(found) = match file {
+            Some(file) => self.resolve(file, level, module),
+            None if level == 0 => self.roots.iter().find_map(|root| module_or_stub(&join_module(root, module))),
+            None => None,
+        } {
+            return Some(ModuleOrigin::File(found));
+        }
+        if level > 0 {
+            return None;
+        }
+        stub_paths.iter()
+            .find_map(|dir| stub_file(&join_module(&absolute(dir), module)))
+            .map(ModuleOrigin::File)
+            .or_else(|| BUNDLED_STUBS.iter().find(|(name, _)| *name == module).map(|(name, _)| ModuleOrigin::Bundled(name)))
+    }
+
+    /// Project files `stmt` in `file` loads: each package along a dotted
+    /// name, and for `from m import x` the submodule `m.x` or else `m`
+    pub fn targets(&self, file: &Path, stmt: &Stmt) -> Vec<PathBuf> {
+        let mut targets = Vec::new();
+        match stmt {
+            Stmt::Import(import) => {
+                for alias in &import.names {
+                    let parts: Vec<&str> = alias.name.split('.').collect();
+                    targets.extend((1..=parts.len()).filter_map(|end| self.resolve(file, 0, &parts[..end].join("."))));
+                }
+            }
+            Stmt::ImportFrom(import) => {
+                let level = import.level.map_or(0, |level| level.to_u32());
+                let module = import.module.as_ref().map_or("", |m| m.as_str());
+                for alias in &import.names {
+                    let dotted = if module.is_empty() { alias.name.to_string() } else { format!("{}.{}", module, alias.name) };
+                    let submodule = (alias.name.as_str() != "*").then(|| self.resolve(file, level, &dotted)).flatten();
+                    targets.extend(submodule.or_else(|| self.resolve(file, level, module)));
+                }
+            }
+            _ => {}
+        }
+        targets.sort();
+        targets.dedup();
+        targets
+    }
+
+    /// Exports recorded for `file`, if it was checked
+    pub fn get(&self, file: &Path) -> Option<Arc<ModuleExports>> {
+        self.exports_of(&ModuleOrigin::File(file.to_path_buf()))
+    }
+
+    pub fn exports_of(&self, origin: &ModuleOrigin) -> Option<Arc<ModuleExports>> {
+        self.exports.get(&origin.key()).map(|entry| entry.value().clone())
+    }
+
+    pub fn insert(&self, origin: &ModuleOrigin, exports: ModuleExports) -> Arc<ModuleExports> {
+        let exports = Arc::new(exports);
+        self.exports.insert(origin.key(), exports.clone());
+        exports
+    }
+
+    /// Drop the exports and imports recorded for `file`, e.g. after it's
+    /// edited, so the next import checks it again
+    pub fn forget(&self, file: &Path) {
+        self.exports.remove(&ModuleOrigin::File(file.to_path_buf()).key());
+        self.graph.remove_module(ModuleId::from_path(&absolute(file)));
+    }
+
+    /// Record the project files `file`, whose text is `source`, imports
+    /// when it's loaded, replacing those recorded before
+    pub fn record_imports(&self, file: &Path, source: &str, imports: &[PathBuf]) {
+        let path = absolute(file);
+        self.graph.add_module(ModuleMetadata {
+            id: ModuleId::from_path(&path),
+            path,
+            hash: ContentHash::from_str(source),
+            timestamp: 0,
+            imports: imports.iter().map(|import| ModuleId::from_path(&absolute(import))).collect(),
+        });
+    }
+
+    /// The files along an import cycle through `file`, as
+    /// `DependencyGraph::cycle_through` orders them
+    pub fn import_cycle(&self, file: &Path) -> Option<Vec<PathBuf>> {
+        let chain = self.graph.cycle_through(ModuleId::from_path(&absolute(file)))?;
+        chain.into_iter().map(|id| self.graph.get_metadata(id).map(|meta| meta.path)).collect()
+    }
+
+    /// Dotted name of a project file, from the root it's under
+    /// (`app/db/__init__.py` is `app.db`), or its name alone outside them
+    pub fn module_name(&self, file: &Path) -> String {
+        let file = absolute(file);
+        let relative = match self.roots.iter().find_map(|root| file.strip_prefix(root).ok()) {
+            Some(relative) => relative.with_extension(""),
+            // Outside them, the package's directory for its `__init__`
+            None if file.file_stem().is_some_and(|stem| stem == "__init__") => {
+                PathBuf::from(file.parent().and_then(Path::file_name).unwrap_or_default())
+            }
+            None => PathBuf::from(file.file_stem().unwrap_or_default()),
+        };
+        let mut parts: Vec<String> = relative.components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        if parts.len() > 1 && parts.last().is_some_and(|last| last == "__init__") {
+            parts.pop();
+        }
+        parts.join(".")
+    }
+}
+
+/// `a.b` under `base` as `base/a/b`
+fn join_module(base: &Path, module: &str) -> PathBuf {
+    module.split('.').filter(|part| !part.is_empty()).fold(base.to_path_buf(), |path, part| path.join(part))
+}
+
+/// `path.pyi` or the package stub `path/__init__.pyi`, if either exists
+fn stub_file(path: &Path) -> Option<PathBuf> {
+    let package = path.join("__init__.pyi");
+    if package.is_file() {
+        return Some(package);
+    }
+    let stub = path.with_file_name(format!("{}.pyi", path.file_name()?.to_string_lossy()));
+    stub.is_file().then_some(stub)
+}
+
+/// A module's stub when it has one beside it, otherwise the module
+fn module_or_stub(path: &Path) -> Option<PathBuf> {
+    stub_file(path).or_else(|| module_file(path))
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    match std::env::current_dir() {
+        Ok(cwd) if path.is_relative() => cwd.join(path),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Names a module body binds at the top level, including inside
+/// conditionals and `try` blocks. Star imports bind nothing nameable
+pub fn top_level_names(body: &[Stmt]) -> Vec<String> {
+    fn walk(body: &[Stmt], out: &mut Vec<String>) {
+        for stmt in body {
+            match stmt {
+                Stmt::FunctionDef(def) => out.push(def.name.to_string()),
+                Stmt::AsyncFunctionDef(def) => out.push(def.name.to_string()),
+                Stmt::ClassDef(class) => out.push(class.name.to_string()),
+                Stmt::Assign(assign) => {
+                    out.extend(assign.targets.iter().filter_map(|target| target.as_name_expr()).map(|name| name.id.to_string()));
+                }
+                Stmt::AnnAssign(ann_assign) => {
+                    out.extend(ann_assign.target.as_name_expr().map(|name| name.id.to_string()));
+                }
+                Stmt::Import(import) => {
+                    for alias in &import.names {
+                        let bound = alias.asname.as_ref().map_or_else(|| alias.name.split('.').next().unwrap_or_default(), |asname| asname.as_str());
+                        out.push(bound.to_string());
+                    }
+                }
+                Stmt::ImportFrom(import) => {
+                    for alias in import.names.iter().filter(|alias| alias.name.as_str() != "*") {
+                        out.push(alias.asname.as_ref().unwrap_or(&alias.name).to_string());
+                    }
+                }
+                Stmt::If(if_stmt) => {
+                    walk(&if_stmt.body, out);
+                    walk(&if_stmt.orelse, out);
+                }
+                Stmt::Try(try_stmt) => {
+                    walk(&try_stmt.body, out);
+                    for ExceptHandler::ExceptHandler(handler) in &try_stmt.handlers {
+                        walk(&handler.body, out);
+                    }
+                    walk(&try_stmt.orelse, out);
+                    walk(&try_stmt.finalbody, out);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut names = Vec::new();
+    walk(body, &mut names);
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Import statements a module body runs when it's loaded: those at the top
+/// level and in its conditionals, `try` blocks and class bodies, but not
+/// the late imports of functions, nor those under `if TYPE_CHECKING:`
+pub fn load_time_imports(body: &[Stmt]) -> Vec<&Stmt> {
+    fn walk<'a>(body: &'a [Stmt], out: &mut Vec<&'a Stmt>) {
+        for stmt in body {
+            match stmt {
+                Stmt::Import(_) | Stmt::ImportFrom(_) => out.push(stmt),
+                Stmt::ClassDef(class) => walk(&class.body, out),
+                Stmt::If(if_stmt) => {
+                    let type_checking = match &*if_stmt.test {
+                        Expr::Name(name) => name.id.as_str() == "TYPE_CHECKING",
+                        Expr::Attribute(attr) => attr.attr.as_str() == "TYPE_CHECKING",
+                        _ => false,
+                    };
+                    if !type_checking {
+                        walk(&if_stmt.body, out);
+                    }
+                    walk(&if_stmt.orelse, out);
+                }
+                Stmt::Try(try_stmt) => {
+                    walk(&try_stmt.body, out);
+                    for ExceptHandler::ExceptHandler(handler) in &try_stmt.handlers {
+                        walk(&handler.body, out);
+                    }
+                    walk(&try_stmt.orelse, out);
+                    walk(&try_stmt.finalbody, out);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut imports = Vec::new();
+    walk(body, &mut imports);
+    imports
+}
+
+/// `ty` with the inference variables of the context it came from replaced
+/// by `Any`; they mean nothing in the importing module's context
+pub fn erase_vars(ty: Type) -> Type {
+    let erase = |types: Vec<Type>| types.into_iter().map(erase_vars).collect();
+    let boxed = |ty: Box<Type>| Box::new(erase_vars(*ty));
+    match ty {
+        Type::Var(_) => Type::Any,
+        Type::List(elem) => Type::List(boxed(elem)),
+        Type::Set(elem) => Type::Set(boxed(elem)),
+        Type::VarTuple(elem) => Type::VarTuple(boxed(elem)),
+        Type::Tuple(elems) => Type::Tuple(erase(elems)),
+        Type::Dict(key, value) => Type::Dict(boxed(key), boxed(value)),
+        Type::Function(params, ret) => Type::Function(erase(params), boxed(ret)),
+        Type::Union(members) => Type::Union(erase(members)),
+        Type::Intersection(members) => Type::Intersection(erase(members)),
+        Type::Generic(name, args) => Type::Generic(name, erase(args)),
+        Type::Effect(inner, effects) => Type::Effect(boxed(inner), effects),
+        other => other,
+    }
+}
+
+/// `sig` with its keyword and variadic types passed through `erase_vars`
+pub fn erase_sig_vars(mut sig: FunctionSig) -> FunctionSig {
+    for (_, ty) in &mut sig.kwonly {
+        *ty = erase_vars(std::mem::replace(ty, Type::Any));
+    }
+    sig.varargs = sig.varargs.map(erase_vars);
+    sig.kwargs = sig.kwargs.map(erase_vars);
+    sig
+}
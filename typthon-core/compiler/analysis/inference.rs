@@ -52,6 +52,7 @@ impl InferenceEngine {
                 self.unify(*v1, *v2)
             }
 
+            (VarTuple(a), VarTuple(b)) => self.unify(*a, *b),
             (Tuple(a), Tuple(b)) if a.len() == b.len() => {
                 for (x, y) in a.into_iter().zip(b) {
                     self.unify(x, y)?;
@@ -81,6 +82,7 @@ impl InferenceEngine {
             }
             Type::List(inner) => Type::List(Box::new(self.apply_substitution(*inner))),
             Type::Set(inner) => Type::Set(Box::new(self.apply_substitution(*inner))),
+            Type::VarTuple(inner) => Type::VarTuple(Box::new(self.apply_substitution(*inner))),
             Type::Dict(k, v) => Type::Dict(
                 Box::new(self.apply_substitution(*k)),
                 Box::new(self.apply_substitution(*v)),
@@ -99,7 +101,7 @@ impl InferenceEngine {
     fn occurs_check(&self, var_id: u64, ty: &Type) -> bool {
         match ty {
             Type::Var(id) => *id == var_id,
-            Type::List(inner) | Type::Set(inner) => self.occurs_check(var_id, inner),
+            Type::List(inner) | Type::Set(inner) | Type::VarTuple(inner) => self.occurs_check(var_id, inner),
             Type::Dict(k, v) => self.occurs_check(var_id, k) || self.occurs_check(var_id, v),
             Type::Tuple(types) | Type::Union(types) | Type::Intersection(types) => {
                 types.iter().any(|t| self.occurs_check(var_id, t))
@@ -0,0 +1,144 @@
+//! Top-level items of a module, fingerprinted for sub-file incrementality
+//!
+//! Each item hashes its header (decorators and `def` line, or the whole
+//! statement for anything but a function) separately from its body. Two
+//! versions of a module that differ only inside one function body can have
+//! just that function re-checked (`TypeChecker::recheck_function`); any other
+//! difference needs the whole module checked again.
+
+use crate::infrastructure::ContentHash;
+use rustpython_parser::ast::{Ranged, Stmt};
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemKind {
+    Function,
+    Class,
+    Import,
+    Statement,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemFingerprint {
+    pub kind: ItemKind,
+    pub name: Option<String>,
+    /// Byte range, decorators included
+    pub range: Range<usize>,
+    pub header: ContentHash,
+    /// Function body; the empty hash for other items
+    pub body: ContentHash,
+}
+
+/// How a module changed between two versions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemChange {
+    Unchanged,
+    /// Only the body of the function at this index changed
+    FunctionBody(usize),
+    /// Anything else: signatures, classes, imports, added or removed items
+    Structural,
+}
+
+/// Fingerprint the top-level statements of `body`, parsed from `source`
+pub fn fingerprint_items(source: &str, body: &[Stmt]) -> Vec<ItemFingerprint> {
+    let text = |range: Range<usize>| source.get(range).unwrap_or_default();
+    body.iter().map(|stmt| {
+        let (kind, name, inner) = match stmt {
+            Stmt::FunctionDef(f) => (ItemKind::Function, Some(f.name.to_string()), Some(&f.body)),
+            Stmt::AsyncFunctionDef(f) => (ItemKind::Function, Some(f.name.to_string()), Some(&f.body)),
+            Stmt::ClassDef(c) => (ItemKind::Class, Some(c.name.to_string()), None),
+            Stmt::Import(_) | Stmt::ImportFrom(_) => (ItemKind::Import, None, None),
+            _ => (ItemKind::Statement, None, None),
+        };
+        let Range { start, end } = item_range(stmt);
+        let body_start = inner.and_then(|body| body.first()).map_or(end, |first| first.start().to_usize());
+        ItemFingerprint {
+            kind,
+            name,
+            range: start..end,
+            header: ContentHash::from_str(text(start..body_start)),
+            body: ContentHash::from_str(text(body_start..end)),
+        }
+    }).collect()
+}
+
+/// Byte range of a top-level statement, decorators included
+pub fn item_range(stmt: &Stmt) -> Range<usize> {
+    let decorators = match stmt {
+        Stmt::FunctionDef(f) => f.decorator_list.as_slice(),
+        Stmt::AsyncFunctionDef(f) => f.decorator_list.as_slice(),
+        Stmt::ClassDef(c) => c.decorator_list.as_slice(),
+        _ => &[],
+    };
+    // Each decorator expression starts just after its `@`
+    let start = decorators.iter().map(|d| d.start().to_usize() - 1).fold(stmt.start().to_usize(), usize::min);
+    start..stmt.end().to_usize()
+}
+
+/// Compare two versions of a module's items
+///
+/// A body-only change also needs every other item in place: the ones before
+/// it at the same offsets and the ones after shifted by the change in its
+/// length, so offsets recorded for them can be moved rather than recomputed.
+pub fn diff_items(old: &[ItemFingerprint], new: &[ItemFingerprint]) -> ItemChange {
+    if old.len() != new.len() {
+        return ItemChange::Structural;
+    }
+    let changed: Vec<usize> = (0..old.len())
+        .filter(|&i| old[i].header != new[i].header || old[i].body != new[i].body)
+        .collect();
+    let &[index] = changed.as_slice() else {
+        let same_places = old.iter().zip(new).all(|(a, b)| a.range == b.range);
+        return if changed.is_empty() && same_places { ItemChange::Unchanged } else { ItemChange::Structural };
+    };
+
+    let (before, after) = (&old[index], &new[index]);
+    if before.kind != ItemKind::Function || before.header != after.header || before.range.start != after.range.start {
+        return ItemChange::Structural;
+    }
+    let delta = after.range.end as isize - before.range.end as isize;
+    let in_place = old.iter().zip(new).enumerate().all(|(i, (a, b))| match i.cmp(&index) {
+        std::cmp::Ordering::Less => a.range == b.range,
+        std::cmp::Ordering::Equal => true,
+        std::cmp::Ordering::Greater => b.range.start as isize - a.range.start as isize == delta,
+    });
+    if in_place { ItemChange::FunctionBody(index) } else { ItemChange::Structural }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustpython_parser::ast::Mod;
+    use rustpython_parser::{parse, Mode};
+
+    fn items(source: &str) -> Vec<ItemFingerprint> {
+        match parse(source, Mode::Module, "<test>").unwrap() {
+            Mod::Module(module) => fingerprint_items(source, &module.body),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_body_edit_is_local() {
+        let old = items("import os\n\n@cache\ndef f(x: int) -> str:\n    return 'a'\n\ny = f(1)\n");
+        let body = items("import os\n\n@cache\ndef f(x: int) -> str:\n    return 'abc'\n\ny = f(1)\n");
+
+        assert_eq!(old[1].range.start, 11);
+        assert_eq!(diff_items(&old, &body), ItemChange::FunctionBody(1));
+        assert_eq!(diff_items(&old, &old), ItemChange::Unchanged);
+    }
+
+    #[test]
+    fn test_other_edits_are_structural() {
+        let old = items("def f(x: int) -> str:\n    return 'a'\ny = 1\n");
+        for new in [
+            "def f(y: int) -> str:\n    return 'a'\ny = 1\n",
+            "def f(x: int) -> str:\n    return 'a'\ny = 2\n",
+            "# moved\ndef f(x: int) -> str:\n    return 'a'\ny = 1\n",
+            "def f(x: int) -> str:\n    return 'b'\ny = 2\n",
+            "def f(x: int) -> str:\n    return 'a'\n",
+        ] {
+            assert_eq!(diff_items(&old, &items(new)), ItemChange::Structural, "{}", new);
+        }
+    }
+}
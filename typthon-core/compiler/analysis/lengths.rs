@@ -0,0 +1,246 @@
+use crate::compiler::types::{Type, DependentConstraint};
+use num_traits::ToPrimitive;
+use rustpython_parser::ast::*;
+
+/// Known bounds on a sequence's length; `max: None` means unbounded
+///
+/// Stored on types as `Dependent(seq, Length(n))` or
+/// `Dependent(seq, LengthRange(min, max))`, with `usize::MAX` as an open max.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LengthBound {
+    pub min: usize,
+    pub max: Option<usize>,
+}
+
+/// Probe length standing in for "unbounded" when evaluating slice arithmetic
+const PROBE: i128 = 1 << 62;
+
+impl LengthBound {
+    pub fn exact(n: usize) -> Self {
+        Self { min: n, max: Some(n) }
+    }
+
+    pub fn at_least(n: usize) -> Self {
+        Self { min: n, max: None }
+    }
+
+    pub fn between(min: usize, max: usize) -> Self {
+        Self { min, max: Some(max) }
+    }
+
+    pub fn unknown() -> Self {
+        Self::at_least(0)
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        *self == Self::unknown()
+    }
+
+    /// Length known from a type: tuple arity or an attached length constraint
+    pub fn from_type(ty: &Type) -> Option<Self> {
+        match ty {
+            Type::Tuple(types) => Some(Self::exact(types.len())),
+            Type::Dependent(_, DependentConstraint::Length(n)) => Some(Self::exact(*n)),
+            Type::Dependent(_, DependentConstraint::LengthRange(min, max)) => {
+                Some(Self { min: *min, max: (*max != usize::MAX).then_some(*max) })
+            }
+            Type::Effect(inner, _) | Type::Refinement(inner, _) => Self::from_type(inner),
+            _ => None,
+        }
+    }
+
+    /// Length known from an expression: literals, or else its type
+    pub fn from_expr(expr: &Expr, ty: &Type) -> Option<Self> {
+        match expr {
+            Expr::List(ExprList { elts, .. }) | Expr::Tuple(ExprTuple { elts, .. })
+                if !elts.iter().any(|e| matches!(e, Expr::Starred(_))) =>
+            {
+                Some(Self::exact(elts.len()))
+            }
+            _ => Self::from_type(ty),
+        }
+    }
+
+    fn constraint(&self) -> DependentConstraint {
+        match self.max {
+            Some(max) if max == self.min => DependentConstraint::Length(max),
+            Some(max) => DependentConstraint::LengthRange(self.min, max),
+            None => DependentConstraint::LengthRange(self.min, usize::MAX),
+        }
+    }
+
+    /// Overlap of two bounds, `None` if they are contradictory
+    pub fn intersect(&self, other: &Self) -> Option<Self> {
+        let min = self.min.max(other.min);
+        let max = match (self.max, other.max) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        match max {
+            Some(max) if max < min => None,
+            _ => Some(Self { min, max }),
+        }
+    }
+
+    /// Attach this bound to a sequence type, refining any bound it already has
+    ///
+    /// Returns `None` for types whose length isn't tracked, so unknown types
+    /// stay silent.
+    pub fn apply(&self, ty: &Type) -> Option<Type> {
+        match ty {
+            Type::List(_) => {
+                if self.is_unknown() {
+                    return Some(ty.clone());
+                }
+                Some(Type::Dependent(Box::new(ty.clone()), self.constraint()))
+            }
+            Type::Dependent(inner, _) => {
+                let bound = Self::from_type(ty)?.intersect(self)?;
+                bound.apply(inner)
+            }
+            // Arity is already exact; only keep consistent tuples
+            Type::Tuple(types) => self.contains(types.len()).then(|| ty.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn contains(&self, n: usize) -> bool {
+        n >= self.min && self.max.map_or(true, |max| n <= max)
+    }
+
+    /// Bounds implied by `len(x) <op> n` holding (positive) and failing (negative)
+    pub fn from_comparison(op: CmpOp, n: usize) -> (Option<Self>, Option<Self>) {
+        let below = |n: usize| n.checked_sub(1).map(|max| Self::between(0, max));
+        match op {
+            CmpOp::Eq => (Some(Self::exact(n)), (n == 0).then(|| Self::at_least(1))),
+            CmpOp::NotEq => ((n == 0).then(|| Self::at_least(1)), Some(Self::exact(n))),
+            CmpOp::GtE => (Some(Self::at_least(n)), below(n)),
+            CmpOp::Gt => (Some(Self::at_least(n + 1)), Some(Self::between(0, n))),
+            CmpOp::LtE => (Some(Self::between(0, n)), Some(Self::at_least(n + 1))),
+            CmpOp::Lt => (below(n), Some(Self::at_least(n))),
+            _ => (None, None),
+        }
+    }
+
+    /// Whether constant index `idx` is in range: `Some(false)` when provably
+    /// out of range, `Some(true)` when provably in range, `None` if unknown
+    pub fn index_in_range(&self, idx: i64) -> Option<bool> {
+        let needed = if idx < 0 { idx.unsigned_abs() as usize } else { idx as usize + 1 };
+        if needed <= self.min {
+            Some(true)
+        } else if self.max.is_some_and(|max| needed > max) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Length of `x[lower:upper:step]` for constant bounds (`None` bounds are omitted)
+    pub fn slice(&self, lower: Option<i64>, upper: Option<i64>, step: i64) -> Self {
+        let len = |n: i128| slice_indices(n, lower, upper, step).1;
+
+        let min = len(self.min as i128) as usize;
+        let max = match self.max {
+            Some(max) => Some(len(max as i128) as usize),
+            // Slice length is monotone in the input length, so probe a huge one
+            None => {
+                let probed = len(PROBE);
+                (probed < PROBE / 2).then_some(probed as usize)
+            }
+        };
+        Self { min, max }
+    }
+
+    /// Human-readable length for diagnostics
+    pub fn describe(&self) -> String {
+        match self.max {
+            Some(max) if max == self.min => max.to_string(),
+            Some(max) if self.min == 0 => format!("at most {}", max),
+            Some(max) => format!("between {} and {}", self.min, max),
+            None => format!("at least {}", self.min),
+        }
+    }
+}
+
+/// CPython's slice index adjustment: `(start, length)` of `x[lower:upper:step]` over `n` items
+pub fn slice_indices(n: i128, lower: Option<i64>, upper: Option<i64>, step: i64) -> (i128, i128) {
+    let step = step as i128;
+    let adjust = |bound: i64| {
+        let bound = bound as i128;
+        if bound < 0 {
+            let bound = bound + n;
+            if bound < 0 { if step < 0 { -1 } else { 0 } } else { bound }
+        } else if bound >= n {
+            if step < 0 { n - 1 } else { n }
+        } else {
+            bound
+        }
+    };
+
+    let (start, stop) = if step > 0 {
+        (lower.map_or(0, adjust), upper.map_or(n, adjust))
+    } else {
+        (lower.map_or(n - 1, adjust), upper.map_or(-1, adjust))
+    };
+
+    let len = if step < 0 {
+        if stop < start { (start - stop - 1) / -step + 1 } else { 0 }
+    } else if start < stop {
+        (stop - start - 1) / step + 1
+    } else {
+        0
+    };
+    (start, len)
+}
+
+/// Integer value of a constant expression such as `3` or `-1`
+pub fn const_int(expr: &Expr) -> Option<i64> {
+    match expr {
+        Expr::Constant(ExprConstant { value: Constant::Int(i), .. }) => i.to_i64(),
+        Expr::UnaryOp(unary) if matches!(unary.op, UnaryOp::USub) => const_int(&unary.operand)?.checked_neg(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slice_of_exact_length() {
+        let five = LengthBound::exact(5);
+        assert_eq!(five.slice(Some(1), Some(3), 1), LengthBound::exact(2));
+        assert_eq!(five.slice(Some(-3), Some(-1), 1), LengthBound::exact(2));
+        assert_eq!(five.slice(None, None, -1), LengthBound::exact(5));
+        assert_eq!(five.slice(None, None, 2), LengthBound::exact(3));
+        assert_eq!(five.slice(Some(10), None, 1), LengthBound::exact(0));
+    }
+
+    #[test]
+    fn test_slice_of_open_range() {
+        let at_least_two = LengthBound::at_least(2);
+        assert_eq!(at_least_two.slice(Some(-2), None, 1), LengthBound::exact(2));
+        assert_eq!(at_least_two.slice(Some(1), None, 1), LengthBound::at_least(1));
+        assert_eq!(at_least_two.slice(None, Some(3), 1), LengthBound::between(2, 3));
+    }
+
+    #[test]
+    fn test_comparison_bounds() {
+        let (pos, neg) = LengthBound::from_comparison(CmpOp::GtE, 2);
+        assert_eq!(pos, Some(LengthBound::at_least(2)));
+        assert_eq!(neg, Some(LengthBound::between(0, 1)));
+
+        let (pos, neg) = LengthBound::from_comparison(CmpOp::Eq, 1);
+        assert_eq!(pos, Some(LengthBound::exact(1)));
+        assert_eq!(neg, None);
+    }
+
+    #[test]
+    fn test_index_in_range() {
+        let one = LengthBound::exact(1);
+        assert_eq!(one.index_in_range(0), Some(true));
+        assert_eq!(one.index_in_range(-1), Some(true));
+        assert_eq!(one.index_in_range(1), Some(false));
+        assert_eq!(LengthBound::at_least(2).index_in_range(5), None);
+    }
+}
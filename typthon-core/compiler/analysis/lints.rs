@@ -0,0 +1,613 @@
+//! Lints - opt-in style checks with machine-applicable fixes
+//!
+//! Each lint has a stable code so configuration can set its level:
+//!
+//! ```toml
+//! [lints]
+//! S001 = "warn"
+//! S002 = "error"
+//! C001 = "warn"
+//! ```
+//!
+//! Style lints (`S` codes) and correctness lints (`C` codes) are off unless
+//! enabled; strict mode turns the `STRICT_LINTS` on as warnings. Fixes are byte-offset edits on the checked source, ready for an
+//! editor quick-fix. Type-aware lints read operand types the checker recorded
+//! while checking the same module.
+
+use rustpython_parser::ast::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use crate::compiler::ast::ir::{ExprKind, NodeRef, StmtKind};
+use crate::compiler::ast::{lower_module, LineIndex};
+use crate::compiler::errors::{Severity, SourceLocation};
+use crate::compiler::types::Type;
+
+/// Literal `True`/`False` passed positionally to a named parameter
+pub const BOOLEAN_POSITIONAL: &str = "S001";
+
+/// Two or more adjacent boolean parameters that callers can pass positionally
+pub const ADJACENT_BOOL_PARAMS: &str = "S002";
+
+/// `==`/`!=` between floats, where `math.isclose` is almost always meant
+pub const FLOAT_EQUALITY: &str = "C001";
+
+/// Module-level name bound to `[]`, `{}` or `set()` without an annotation
+pub const EMPTY_CONTAINER: &str = "S003";
+
+/// `# type: ignore` comment that silences nothing, when `warn_unused_ignores` is set
+pub const UNUSED_IGNORE: &str = "S004";
+
+/// Every lint code
+pub const CODES: &[&str] = &[BOOLEAN_POSITIONAL, ADJACENT_BOOL_PARAMS, FLOAT_EQUALITY, EMPTY_CONTAINER, UNUSED_IGNORE];
+
+/// Lints strict mode warns about unless configured otherwise
+pub const STRICT_LINTS: &[&str] = &[EMPTY_CONTAINER];
+
+/// Inferred types of comparison operands, keyed by byte range
+pub type OperandTypes = HashMap<Range<usize>, Type>;
+
+/// How a lint is reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    Off,
+    Warn,
+    Error,
+}
+
+/// Replace `range` of the source with `new_text` (an insertion when empty)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub new_text: String,
+}
+
+/// A titled set of edits resolving one diagnostic
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fix {
+    pub title: String,
+    pub edits: Vec<TextEdit>,
+}
+
+impl Fix {
+    /// Source with every edit applied
+    pub fn apply(&self, source: &str) -> String {
+        let mut edits: Vec<&TextEdit> = self.edits.iter().collect();
+        edits.sort_by_key(|edit| std::cmp::Reverse(edit.range.start));
+
+        let mut fixed = source.to_string();
+        for edit in edits {
+            fixed.replace_range(edit.range.clone(), &edit.new_text);
+        }
+        fixed
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LintDiagnostic {
+    pub code: &'static str,
+    pub level: LintLevel,
+    pub message: String,
+    /// Byte range of the offending node
+    pub range: Range<usize>,
+    pub fix: Option<Fix>,
+}
+
+impl LintDiagnostic {
+    /// Lints at `Warn` are warnings; only those at `Error` fail a check
+    pub fn severity(&self) -> Severity {
+        match self.level {
+            LintLevel::Error => Severity::Error,
+            LintLevel::Warn | LintLevel::Off => Severity::Warning,
+        }
+    }
+
+    pub fn location(&self, index: &LineIndex) -> SourceLocation {
+        SourceLocation::from_range(
+            index.offset_to_position(self.range.start),
+            index.offset_to_position(self.range.end),
+        )
+    }
+}
+
+/// Run every enabled lint over `module`; type-aware lints stay silent
+pub fn lint_module(module: &Mod, levels: &BTreeMap<String, LintLevel>) -> Vec<LintDiagnostic> {
+    lint_module_with_types(module, levels, &OperandTypes::new())
+}
+
+/// Run every enabled lint over `module`, with operand types from the checker
+pub fn lint_module_with_types(
+    module: &Mod,
+    levels: &BTreeMap<String, LintLevel>,
+    types: &OperandTypes,
+) -> Vec<LintDiagnostic> {
+    let Mod::Module(ModModule { body, .. }) = module else { return Vec::new() };
+
+    let level = |code: &str| levels.get(code).copied().unwrap_or(LintLevel::Off);
+    let mut linter = Linter {
+        signatures: collect_signatures(body),
+        boolean_positional: level(BOOLEAN_POSITIONAL),
+        adjacent_bools: level(ADJACENT_BOOL_PARAMS),
+        float_equality: level(FLOAT_EQUALITY),
+        empty_containers: level(EMPTY_CONTAINER),
+        types,
+        isclose: None,
+        diagnostics: Vec::new(),
+    };
+    if linter.boolean_positional == LintLevel::Off
+        && linter.adjacent_bools == LintLevel::Off
+        && linter.float_equality == LintLevel::Off
+        && linter.empty_containers == LintLevel::Off
+    {
+        return Vec::new();
+    }
+    // Exact comparisons in tests that already use `pytest.approx` are deliberate
+    if linter.float_equality != LintLevel::Off && !uses_pytest_approx(module) {
+        linter.isclose = Some(match imported_name(body, "math", "isclose") {
+            Some(name) => (name, None),
+            None => ("math.isclose".to_string(), Some(import_edit(body, "math"))),
+        });
+    }
+
+    if linter.empty_containers != LintLevel::Off {
+        for stmt in body {
+            linter.check_module_assign(stmt);
+        }
+    }
+    linter.stmts(body);
+    linter.diagnostics
+}
+
+/// Parameters of a function callable by plain name
+struct Signature {
+    posonly: usize,
+    /// Positional parameters, positional-only ones first
+    params: Vec<String>,
+}
+
+/// Signatures of module-level and nested functions; names defined twice are dropped
+fn collect_signatures(body: &[Stmt]) -> HashMap<String, Signature> {
+    fn walk(body: &[Stmt], out: &mut HashMap<String, Option<Signature>>) {
+        for stmt in body {
+            let (name, args, inner) = match stmt {
+                Stmt::FunctionDef(def) => (&def.name, &def.args, &def.body),
+                Stmt::AsyncFunctionDef(def) => (&def.name, &def.args, &def.body),
+                _ => continue,
+            };
+            let signature = Signature {
+                posonly: args.posonlyargs.len(),
+                params: args.posonlyargs.iter().chain(&args.args).map(|arg| arg.def.arg.to_string()).collect(),
+            };
+            out.entry(name.to_string())
+                .and_modify(|existing| *existing = None)
+                .or_insert(Some(signature));
+            walk(inner, out);
+        }
+    }
+
+    let mut signatures = HashMap::new();
+    walk(body, &mut signatures);
+    signatures.into_iter().filter_map(|(name, sig)| Some((name, sig?))).collect()
+}
+
+struct Linter<'t> {
+    signatures: HashMap<String, Signature>,
+    boolean_positional: LintLevel,
+    adjacent_bools: LintLevel,
+    float_equality: LintLevel,
+    empty_containers: LintLevel,
+    types: &'t OperandTypes,
+    /// How to spell `isclose` here, and the import to add when it isn't available
+    isclose: Option<(String, Option<TextEdit>)>,
+    diagnostics: Vec<LintDiagnostic>,
+}
+
+impl Linter<'_> {
+    fn stmts(&mut self, body: &[Stmt]) {
+        for stmt in body {
+            self.stmt(stmt);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::FunctionDef(def) => {
+                self.check_definition(&def.args);
+                self.exprs(def.args.defaults());
+                self.stmts(&def.body);
+            }
+            Stmt::AsyncFunctionDef(def) => {
+                self.check_definition(&def.args);
+                self.exprs(def.args.defaults());
+                self.stmts(&def.body);
+            }
+            Stmt::ClassDef(class) => self.stmts(&class.body),
+            Stmt::Return(ret) => self.exprs(ret.value.as_deref()),
+            Stmt::Assign(assign) => self.expr(&assign.value),
+            Stmt::AugAssign(aug) => self.expr(&aug.value),
+            Stmt::AnnAssign(ann) => self.exprs(ann.value.as_deref()),
+            Stmt::Expr(expr) => self.expr(&expr.value),
+            Stmt::For(for_stmt) => {
+                self.expr(&for_stmt.iter);
+                self.stmts(&for_stmt.body);
+                self.stmts(&for_stmt.orelse);
+            }
+            Stmt::While(while_stmt) => {
+                self.expr(&while_stmt.test);
+                self.stmts(&while_stmt.body);
+                self.stmts(&while_stmt.orelse);
+            }
+            Stmt::If(if_stmt) => {
+                self.expr(&if_stmt.test);
+                self.stmts(&if_stmt.body);
+                self.stmts(&if_stmt.orelse);
+            }
+            Stmt::With(with) => {
+                self.exprs(with.items.iter().map(|item| &item.context_expr));
+                self.stmts(&with.body);
+            }
+            Stmt::Try(try_stmt) => {
+                self.stmts(&try_stmt.body);
+                for ExceptHandler::ExceptHandler(handler) in &try_stmt.handlers {
+                    self.stmts(&handler.body);
+                }
+                self.stmts(&try_stmt.orelse);
+                self.stmts(&try_stmt.finalbody);
+            }
+            Stmt::Assert(assert) => self.expr(&assert.test),
+            Stmt::Raise(raise) => self.exprs(raise.exc.as_deref()),
+            _ => {}
+        }
+    }
+
+    fn exprs<'a>(&mut self, exprs: impl IntoIterator<Item = &'a Expr>) {
+        for expr in exprs {
+            self.expr(expr);
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Call(call) => {
+                self.check_call(call);
+                self.expr(&call.func);
+                self.exprs(&call.args);
+                self.exprs(call.keywords.iter().map(|keyword| &keyword.value));
+            }
+            Expr::BoolOp(op) => self.exprs(&op.values),
+            Expr::NamedExpr(named) => self.expr(&named.value),
+            Expr::BinOp(op) => {
+                self.expr(&op.left);
+                self.expr(&op.right);
+            }
+            Expr::UnaryOp(op) => self.expr(&op.operand),
+            Expr::Lambda(lambda) => self.expr(&lambda.body),
+            Expr::IfExp(if_exp) => self.exprs([&*if_exp.test, &if_exp.body, &if_exp.orelse]),
+            Expr::Dict(dict) => {
+                self.exprs(dict.keys.iter().flatten());
+                self.exprs(&dict.values);
+            }
+            Expr::Set(set) => self.exprs(&set.elts),
+            Expr::List(list) => self.exprs(&list.elts),
+            Expr::Tuple(tuple) => self.exprs(&tuple.elts),
+            Expr::ListComp(comp) => self.comprehension(&[&comp.elt], &comp.generators),
+            Expr::SetComp(comp) => self.comprehension(&[&comp.elt], &comp.generators),
+            Expr::GeneratorExp(comp) => self.comprehension(&[&comp.elt], &comp.generators),
+            Expr::DictComp(comp) => self.comprehension(&[&comp.key, &comp.value], &comp.generators),
+            Expr::Await(await_expr) => self.expr(&await_expr.value),
+            Expr::Compare(compare) => {
+                self.check_compare(compare);
+                self.expr(&compare.left);
+                self.exprs(&compare.comparators);
+            }
+            Expr::Attribute(attr) => self.expr(&attr.value),
+            Expr::Subscript(subscript) => {
+                self.expr(&subscript.value);
+                self.expr(&subscript.slice);
+            }
+            Expr::Starred(starred) => self.expr(&starred.value),
+            Expr::JoinedStr(joined) => self.exprs(&joined.values),
+            Expr::FormattedValue(formatted) => self.expr(&formatted.value),
+            _ => {}
+        }
+    }
+
+    fn comprehension(&mut self, elts: &[&Expr], generators: &[Comprehension]) {
+        self.exprs(elts.iter().copied());
+        for generator in generators {
+            self.expr(&generator.iter);
+            self.exprs(&generator.ifs);
+        }
+    }
+
+    /// S001: `f(x, True)` where `f`'s parameter could be named instead
+    fn check_call(&mut self, call: &ExprCall) {
+        if self.boolean_positional == LintLevel::Off {
+            return;
+        }
+        let Expr::Name(func) = &*call.func else { return };
+        let Some(signature) = self.signatures.get(func.id.as_str()) else { return };
+
+        // Positional arguments up to the first `*args`, each matched to its parameter
+        let positional: Vec<(&Expr, Option<&String>)> = call.args.iter()
+            .take_while(|arg| !matches!(arg, Expr::Starred(_)))
+            .enumerate()
+            .map(|(i, arg)| (arg, (i >= signature.posonly).then(|| signature.params.get(i)).flatten()))
+            .collect();
+
+        for (i, (arg, param)) in positional.iter().enumerate() {
+            let (Some(value), Some(param)) = (bool_literal(arg), param) else { continue };
+
+            let literal = if value { "True" } else { "False" };
+            // Keywords can't be followed by positionals, so the rest of the call goes by name too
+            let edits: Option<Vec<TextEdit>> = positional[i..].iter()
+                .map(|(arg, param)| param.map(|name| TextEdit {
+                    range: arg.start().to_usize()..arg.start().to_usize(),
+                    new_text: format!("{}=", name),
+                }))
+                .collect();
+            let trailing_star = call.args.len() > positional.len();
+
+            self.diagnostics.push(LintDiagnostic {
+                code: BOOLEAN_POSITIONAL,
+                level: self.boolean_positional,
+                message: format!("boolean positional argument; use {}={} for clarity", param, literal),
+                range: arg.start().to_usize()..arg.end().to_usize(),
+                fix: edits.filter(|_| !trailing_star).map(|edits| Fix {
+                    title: format!("Pass as keyword argument {}={}", param, literal),
+                    edits,
+                }),
+            });
+        }
+    }
+
+    /// C001: `a == b` where both sides are floats
+    fn check_compare(&mut self, compare: &ExprCompare) {
+        let Some((isclose, import)) = &self.isclose else { return };
+        let operands: Vec<&Expr> = std::iter::once(&*compare.left).chain(&compare.comparators).collect();
+
+        for (i, op) in compare.ops.iter().enumerate() {
+            let negated = match op {
+                CmpOp::Eq => false,
+                CmpOp::NotEq => true,
+                _ => continue,
+            };
+            let (left, right) = (operands[i], operands[i + 1]);
+            if is_float_sentinel(left) || is_float_sentinel(right) {
+                continue;
+            }
+            if !is_float_operand(self.types, left) || !is_float_operand(self.types, right) {
+                continue;
+            }
+
+            let (start, end) = (left.start().to_usize(), right.end().to_usize());
+            let whole = compare.start().to_usize()..compare.end().to_usize();
+            // Only rewrite `a == b` itself; chains and parenthesized operands need a human
+            let rewritable = compare.ops.len() == 1 && whole == (start..end);
+            let range = if compare.ops.len() == 1 { whole } else { start..end };
+            let prefix = if negated { "not " } else { "" };
+            let fix = rewritable.then(|| {
+                let mut edits = vec![
+                    TextEdit { range: start..start, new_text: format!("{}{}(", prefix, isclose) },
+                    TextEdit { range: left.end().to_usize()..right.start().to_usize(), new_text: ", ".to_string() },
+                    TextEdit { range: end..end, new_text: ")".to_string() },
+                ];
+                edits.extend(import.clone());
+                Fix { title: format!("Use {}{}(a, b)", prefix, isclose), edits }
+            });
+
+            self.diagnostics.push(LintDiagnostic {
+                code: FLOAT_EQUALITY,
+                level: self.float_equality,
+                message: format!(
+                    "float {} comparison; use {}{}(a, b) or compare against an explicit tolerance",
+                    if negated { "inequality" } else { "equality" },
+                    prefix,
+                    isclose,
+                ),
+                range,
+                fix,
+            });
+        }
+    }
+
+    /// S003: `cache = {}` at module level, whose element types nothing pins down
+    fn check_module_assign(&mut self, stmt: &Stmt) {
+        let Stmt::Assign(assign) = stmt else { return };
+        let [Expr::Name(target)] = assign.targets.as_slice() else { return };
+        let Some(kind) = empty_container(&assign.value) else { return };
+
+        self.diagnostics.push(LintDiagnostic {
+            code: EMPTY_CONTAINER,
+            level: self.empty_containers,
+            message: format!(
+                "empty {} assigned to '{}' without an annotation; declare its element types, e.g. '{}: {}[...]'",
+                kind, target.id, target.id, kind,
+            ),
+            range: stmt.start().to_usize()..stmt.end().to_usize(),
+            fix: None,
+        });
+    }
+
+    /// S002: `def f(a: bool, b: bool)` lets callers write `f(True, False)`
+    fn check_definition(&mut self, args: &Arguments) {
+        if self.adjacent_bools == LintLevel::Off {
+            return;
+        }
+
+        let mut run: Vec<&ArgWithDefault> = Vec::new();
+        for arg in &args.args {
+            if is_bool_param(arg) {
+                run.push(arg);
+            } else if run.len() >= 2 {
+                break;
+            } else {
+                run.clear();
+            }
+        }
+        if run.len() < 2 {
+            return;
+        }
+
+        let names: Vec<String> = run.iter().map(|arg| format!("'{}'", arg.def.arg)).collect();
+        let listed = match names.split_last() {
+            Some((last, rest)) if !rest.is_empty() => format!("{} and {}", rest.join(", "), last),
+            _ => names.join(""),
+        };
+        let first = run[0].def.start().to_usize();
+        let last = run[run.len() - 1];
+        let last = last.default.as_deref().map_or(last.def.end(), |default| default.end()).to_usize();
+
+        self.diagnostics.push(LintDiagnostic {
+            code: ADJACENT_BOOL_PARAMS,
+            level: self.adjacent_bools,
+            message: format!("boolean parameters {} are adjacent; make them keyword-only", listed),
+            range: first..last,
+            // A `*args` already takes the `*` slot
+            fix: args.vararg.is_none().then(|| Fix {
+                title: "Make boolean parameters keyword-only".to_string(),
+                edits: vec![TextEdit { range: first..first, new_text: "*, ".to_string() }],
+            }),
+        });
+    }
+}
+
+/// Builtin container `expr` creates empty: `[]`, `{}`, `list()`, `dict()` or `set()`
+pub(crate) fn empty_container(expr: &Expr) -> Option<&'static str> {
+    match expr {
+        Expr::List(list) if list.elts.is_empty() => Some("list"),
+        Expr::Dict(dict) if dict.keys.is_empty() => Some("dict"),
+        Expr::Call(call) if call.args.is_empty() && call.keywords.is_empty() => match &*call.func {
+            Expr::Name(name) => match name.id.as_str() {
+                "list" => Some("list"),
+                "dict" => Some("dict"),
+                "set" => Some("set"),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn bool_literal(expr: &Expr) -> Option<bool> {
+    match expr {
+        Expr::Constant(ExprConstant { value: Constant::Bool(value), .. }) => Some(*value),
+        _ => None,
+    }
+}
+
+/// `float`, or a union of `float` and `int` (bare `int` never qualifies)
+fn is_float_operand(types: &OperandTypes, expr: &Expr) -> bool {
+    match types.get(&(expr.start().to_usize()..expr.end().to_usize())) {
+        Some(Type::Float) => true,
+        Some(Type::Union(members)) => {
+            members.contains(&Type::Float) && members.iter().all(|t| matches!(t, Type::Float | Type::Int))
+        }
+        _ => false,
+    }
+}
+
+/// `0.0`, `float("inf")`, `float("nan")`, `math.inf` and `math.nan`, which
+/// compare exactly on purpose
+fn is_float_sentinel(expr: &Expr) -> bool {
+    match expr {
+        Expr::Constant(ExprConstant { value: Constant::Float(value), .. }) => *value == 0.0,
+        Expr::UnaryOp(unary) => matches!(unary.op, UnaryOp::USub | UnaryOp::UAdd) && is_float_sentinel(&unary.operand),
+        Expr::Call(call) => {
+            let is_float = matches!(&*call.func, Expr::Name(name) if name.id.as_str() == "float");
+            let spelled = match call.args.as_slice() {
+                [Expr::Constant(ExprConstant { value: Constant::Str(text), .. })] => {
+                    let text = text.trim().trim_start_matches(['+', '-']).to_ascii_lowercase();
+                    matches!(text.as_str(), "inf" | "infinity" | "nan")
+                }
+                _ => false,
+            };
+            is_float && spelled
+        }
+        Expr::Attribute(attr) => {
+            matches!(&*attr.value, Expr::Name(name) if name.id.as_str() == "math")
+                && matches!(attr.attr.as_str(), "inf" | "nan")
+        }
+        _ => false,
+    }
+}
+
+fn uses_pytest_approx(module: &Mod) -> bool {
+    let mut found = false;
+    lower_module(module, None).walk(&mut |node| match node {
+        NodeRef::Stmt(stmt) => {
+            if let StmtKind::ImportFrom { module: Some(from), names, .. } = &stmt.kind {
+                found |= from.as_ref() == "pytest" && names.iter().any(|alias| alias.name.as_ref() == "approx");
+            }
+        }
+        NodeRef::Expr(expr) => {
+            if let ExprKind::Attribute { value, attr } = &expr.kind {
+                found |= attr.as_ref() == "approx" && matches!(&value.kind, ExprKind::Name(name) if name.as_ref() == "pytest");
+            }
+        }
+    });
+    found
+}
+
+/// How module-level imports spell `module.member`, if they provide it
+fn imported_name(body: &[Stmt], module: &str, member: &str) -> Option<String> {
+    body.iter().find_map(|stmt| match stmt {
+        Stmt::Import(import) => import.names.iter()
+            .find(|alias| alias.name.as_str() == module)
+            .map(|alias| format!("{}.{}", alias.asname.as_ref().unwrap_or(&alias.name), member)),
+        Stmt::ImportFrom(from) if from.module.as_deref() == Some(module) && from.level.map_or(true, |l| l.to_u32() == 0) => {
+            from.names.iter()
+                .find(|alias| alias.name.as_str() == member)
+                .map(|alias| alias.asname.as_ref().unwrap_or(&alias.name).to_string())
+        }
+        _ => None,
+    })
+}
+
+/// Insert `import module` after the docstring and `__future__` imports
+fn import_edit(body: &[Stmt], module: &str) -> TextEdit {
+    let is_preamble = |(i, stmt): &(usize, &Stmt)| match stmt {
+        Stmt::Expr(expr) => *i == 0 && matches!(&*expr.value, Expr::Constant(ExprConstant { value: Constant::Str(_), .. })),
+        Stmt::ImportFrom(from) => from.module.as_deref() == Some("__future__"),
+        _ => false,
+    };
+    let text = format!("import {}\n", module);
+    match body.iter().enumerate().find(|entry| !is_preamble(entry)) {
+        Some((_, stmt)) => TextEdit { range: stmt.start().to_usize()..stmt.start().to_usize(), new_text: text },
+        None => {
+            let end = body.last().map_or(0, |stmt| stmt.end().to_usize());
+            TextEdit { range: end..end, new_text: format!("\n{}", text.trim_end()) }
+        }
+    }
+}
+
+/// Annotated `bool` or defaulting to `True`/`False`
+fn is_bool_param(arg: &ArgWithDefault) -> bool {
+    let annotated = matches!(arg.def.annotation.as_deref(), Some(Expr::Name(name)) if name.id.as_str() == "bool");
+    annotated || arg.default.as_deref().and_then(bool_literal).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::frontend::parse_module;
+
+    fn lint(source: &str, codes: &[(&str, LintLevel)]) -> Vec<LintDiagnostic> {
+        let levels = codes.iter().map(|(code, level)| (code.to_string(), *level)).collect();
+        lint_module(&parse_module(source).unwrap(), &levels)
+    }
+
+    #[test]
+    fn test_lints_default_off() {
+        let source = "def f(a: bool, b: bool):\n    pass\nf(True, False)\n";
+        assert!(lint(source, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_positional_only_and_unknown_callees_are_silent() {
+        let source = "def f(x, /, flag):\n    pass\nf(True, flag)\ng(True)\nf(1, not x)\n";
+        assert!(lint(source, &[(BOOLEAN_POSITIONAL, LintLevel::Warn)]).is_empty());
+    }
+}
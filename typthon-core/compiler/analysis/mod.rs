@@ -7,6 +7,27 @@ pub mod protocols;
 pub mod refinement;
 pub mod variance;
 pub mod advanced;
+pub mod narrowing;
+pub mod lengths;
+pub mod plugins;
+pub mod options;
+pub mod lints;
+pub mod architecture;
+pub mod imports;
+pub mod consteval;
+pub mod items;
+pub mod reachability;
+pub mod suppressions;
+pub mod dataclasses;
+pub mod generics;
+pub mod aliases;
+pub mod decorators;
+pub mod bindings;
+pub mod undefined;
+pub mod scopes;
+pub mod unused_imports;
+pub mod unused_variables;
+pub mod formatting;
 
 pub use checker::TypeChecker;
 pub use inference::InferenceEngine;
@@ -17,3 +38,16 @@ pub use protocols::ProtocolChecker;
 pub use refinement::RefinementAnalyzer;
 pub use variance::VarianceAnalyzer;
 pub use advanced::AdvancedTypeAnalyzer;
+pub use narrowing::{ConditionAnalyzer, Narrowing};
+pub use lengths::LengthBound;
+pub use plugins::CheckerPlugin;
+pub use options::CheckerOptions;
+pub use lints::{LintDiagnostic, LintLevel};
+pub use architecture::{ArchitectureRules, LayerViolation};
+pub use imports::{ModuleExports, ModuleResolver};
+pub use consteval::{ConstEnv, ConstValue, NotConstant};
+pub use items::{diff_items, fingerprint_items, ItemChange, ItemFingerprint};
+pub use suppressions::{Suppression, Suppressions};
+pub use scopes::{Occurrence, ScopeId, ScopeKind, Scopes, Symbol};
+pub use unused_imports::UnusedImport;
+pub use unused_variables::UnusedBinding;
@@ -0,0 +1,746 @@
+use crate::compiler::types::{CompareOp, Type, TypeContext};
+use super::consteval::{ConstEnv, ConstValue};
+use super::lengths::{LengthBound, const_int};
+use super::refinement::Interval;
+use rustpython_parser::ast::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Variable types implied by a condition being true or false, by name, or
+/// by dotted path for attributes (`self.size`)
+#[derive(Debug, Clone, Default)]
+pub struct Narrowing {
+    pub positive: HashMap<String, Type>,
+    pub negative: HashMap<String, Type>,
+}
+
+impl Narrowing {
+    fn swap(self) -> Self {
+        Self { positive: self.negative, negative: self.positive }
+    }
+}
+
+/// Condition analyzer derives narrowings from `isinstance`, `type()`, `len()`,
+/// `is None`, `== literal`, bounds on refined ints, truthiness tests and calls
+/// to user-defined `TypeGuard` / `TypeIs` functions
+pub struct ConditionAnalyzer {
+    ctx: Arc<TypeContext>,
+    /// Enum members and `Final` constants literals may be compared against
+    consts: ConstEnv,
+    errors: Vec<String>,
+}
+
+impl ConditionAnalyzer {
+    pub fn new(ctx: Arc<TypeContext>) -> Self {
+        Self { ctx, consts: ConstEnv::new(), errors: Vec::new() }
+    }
+
+    pub fn set_consts(&mut self, consts: ConstEnv) {
+        self.consts = consts;
+    }
+
+    /// Analyze a test expression against the current context
+    pub fn analyze(&mut self, test: &Expr) -> Narrowing {
+        self.analyze_in(test, &HashMap::new())
+    }
+
+    /// Like [`Self::analyze`], with the types of the attributes the test
+    /// may narrow (see [`attribute_targets`]), by dotted path
+    pub fn analyze_with(&mut self, test: &Expr, attributes: &HashMap<String, Type>) -> Narrowing {
+        self.analyze_in(test, attributes)
+    }
+
+    /// Drain diagnostics produced while analyzing conditions
+    pub fn take_errors(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.errors)
+    }
+
+    fn analyze_in(&mut self, test: &Expr, env: &HashMap<String, Type>) -> Narrowing {
+        match test {
+            Expr::UnaryOp(unary) if matches!(unary.op, UnaryOp::Not) => {
+                self.analyze_in(&unary.operand, env).swap()
+            }
+            Expr::BoolOp(bool_op) => match bool_op.op {
+                BoolOp::And => self.analyze_chain(&bool_op.values, env),
+                // `a or b` is `not (not a and not b)`
+                BoolOp::Or => self.analyze_chain_negated(&bool_op.values, env),
+            },
+            Expr::Call(call) => self.analyze_guard_call(call, env)
+                .unwrap_or_else(|| self.analyze_isinstance(call, env)),
+            Expr::Compare(compare) => self.analyze_len_compare(compare, env)
+                .or_else(|| self.analyze_bound_compare(compare, env))
+                .or_else(|| self.analyze_none_compare(compare, env))
+                .or_else(|| self.analyze_literal_compare(compare, env))
+                .unwrap_or_else(|| self.analyze_type_compare(compare, env)),
+            Expr::Name(_) | Expr::NamedExpr(_) | Expr::Attribute(_) => self.analyze_truthiness(test, env),
+            _ => Narrowing::default(),
+        }
+    }
+
+    /// `a and b and ...`: each operand is analyzed under the narrowing of the previous ones
+    fn analyze_chain(&mut self, values: &[Expr], env: &HashMap<String, Type>) -> Narrowing {
+        let mut scope = env.clone();
+        let mut positive = HashMap::new();
+        let mut negative: Option<HashMap<String, Type>> = None;
+
+        for value in values {
+            let narrowing = self.analyze_in(value, &scope);
+            // The whole test fails if any operand fails after the earlier ones held
+            negative = Some(match negative {
+                None => narrowing.negative,
+                Some(prev) => self.join(&prev, env, &narrowing.negative, &scope),
+            });
+            scope.extend(narrowing.positive.clone());
+            positive.extend(narrowing.positive);
+        }
+
+        Narrowing { positive, negative: negative.unwrap_or_default() }
+    }
+
+    fn analyze_chain_negated(&mut self, values: &[Expr], env: &HashMap<String, Type>) -> Narrowing {
+        let mut scope = env.clone();
+        let mut negative = HashMap::new();
+        let mut positive: Option<HashMap<String, Type>> = None;
+
+        for value in values {
+            let narrowing = self.analyze_in(value, &scope);
+            positive = Some(match positive {
+                None => narrowing.positive,
+                Some(prev) => self.join(&prev, env, &narrowing.positive, &scope),
+            });
+            scope.extend(narrowing.negative.clone());
+            negative.extend(narrowing.negative);
+        }
+
+        Narrowing { positive: positive.unwrap_or_default(), negative }
+    }
+
+    /// Merge two alternative narrowings; names narrowed on only one side fall back to their scope type
+    fn join(
+        &self,
+        left: &HashMap<String, Type>,
+        left_env: &HashMap<String, Type>,
+        right: &HashMap<String, Type>,
+        right_env: &HashMap<String, Type>,
+    ) -> HashMap<String, Type> {
+        let mut joined = HashMap::new();
+        for name in left.keys().chain(right.keys()) {
+            if joined.contains_key(name) {
+                continue;
+            }
+            let left_ty = left.get(name).cloned().or_else(|| self.lookup(name, left_env));
+            let right_ty = right.get(name).cloned().or_else(|| self.lookup(name, right_env));
+            if let (Some(l), Some(r)) = (left_ty, right_ty) {
+                joined.insert(name.clone(), Type::union(vec![l, r]));
+            }
+        }
+        joined
+    }
+
+    fn lookup(&self, name: &str, env: &HashMap<String, Type>) -> Option<Type> {
+        env.get(name).cloned().or_else(|| self.ctx.get_type(name))
+    }
+
+    /// `isinstance(x, C)` and `isinstance(x, (A, B, ...))`
+    fn analyze_isinstance(&mut self, call: &ExprCall, env: &HashMap<String, Type>) -> Narrowing {
+        let mut narrowing = Narrowing::default();
+
+        let is_isinstance = matches!(&*call.func, Expr::Name(name) if name.id.as_str() == "isinstance");
+        let (Some(target), Some(spec), 2) = (call.args.first().and_then(target_path), call.args.get(1), call.args.len()) else {
+            return narrowing;
+        };
+        if !is_isinstance {
+            return narrowing;
+        }
+
+        let mut classes = Vec::new();
+        if let Err(msg) = self.class_spec(spec, &mut classes) {
+            self.errors.push(msg);
+            return narrowing;
+        }
+
+        let declared = self.lookup(&target, env).unwrap_or(Type::Any);
+        let (kept, remaining) = self.split_by_class(&declared, &classes);
+        if let Some(kept) = kept {
+            narrowing.positive.insert(target.clone(), kept);
+        }
+        if let Some(remaining) = remaining {
+            narrowing.negative.insert(target, remaining);
+        }
+        narrowing
+    }
+
+    /// `guard(x, ...)` where `guard` returns `TypeGuard[T]` narrows `x` to `T`
+    /// when true; one returning `TypeIs[T]` also rules `T` out when false
+    ///
+    /// Returns `None` when the callee isn't a type guard.
+    fn analyze_guard_call(&self, call: &ExprCall, env: &HashMap<String, Type>) -> Option<Narrowing> {
+        let Expr::Name(callee) = &*call.func else { return None };
+        let ret = match self.lookup(&callee.id, env)? {
+            Type::Function(_, ret) => *ret,
+            Type::Effect(inner, _) => match *inner {
+                Type::Function(_, ret) => *ret,
+                _ => return None,
+            },
+            _ => return None,
+        };
+        let (guarded, strict) = type_guard(&ret)?;
+
+        let mut narrowing = Narrowing::default();
+        let Some(target) = call.args.first().and_then(target_path) else { return Some(narrowing) };
+        if !strict {
+            narrowing.positive.insert(target, guarded);
+            return Some(narrowing);
+        }
+        let declared = self.lookup(&target, env).unwrap_or(Type::Any);
+        let (kept, remaining) = self.split_by_class(&declared, std::slice::from_ref(&guarded));
+        narrowing.positive.insert(target.to_string(), kept.unwrap_or(guarded));
+        if let Some(remaining) = remaining {
+            narrowing.negative.insert(target.to_string(), remaining);
+        }
+        Some(narrowing)
+    }
+
+    /// Split `declared` into the part that is an instance of one of
+    /// `classes` and the part that isn't; `None` where a part is empty, or
+    /// for the second when the check rules nothing out
+    pub fn split_by_class(&self, declared: &Type, classes: &[Type]) -> (Option<Type>, Option<Type>) {
+        // Nothing known: a successful check still learns the classes
+        if matches!(declared, Type::Any | Type::Var(_)) {
+            return (Some(Type::union(classes.to_vec())), None);
+        }
+        let members = Self::members(declared);
+
+        let mut kept: Vec<Type> = members.iter()
+            .filter(|m| classes.iter().any(|c| self.is_instance(m, c)))
+            .cloned()
+            .collect();
+        // A narrower class than a declared member (Animal -> Dog) narrows down to that class
+        for class in classes {
+            let covered = kept.iter().any(|k| self.is_instance(k, class));
+            if !covered && members.iter().any(|m| self.is_instance(class, m)) {
+                kept.push(class.clone());
+            }
+        }
+
+        let remaining: Vec<Type> = members.iter()
+            .filter(|m| !classes.iter().any(|c| self.is_instance(m, c)))
+            .cloned()
+            .collect();
+        let remaining = (!remaining.is_empty() && remaining.len() < members.len()).then(|| Type::union(remaining));
+        ((!kept.is_empty()).then(|| Type::union(kept)), remaining)
+    }
+
+    /// The class a class pattern such as `case Point(...)` names; `Any`
+    /// when it names something else
+    pub fn pattern_class(&self, cls: &Expr) -> Type {
+        let mut classes = Vec::new();
+        match self.class_spec(cls, &mut classes) {
+            Ok(()) if classes.len() == 1 => classes.remove(0),
+            _ => Type::Any,
+        }
+    }
+
+    /// `type(x) is C`, `type(x) == C` and their negations: exact class, subclasses excluded
+    fn analyze_type_compare(&mut self, compare: &ExprCompare, env: &HashMap<String, Type>) -> Narrowing {
+        let mut narrowing = Narrowing::default();
+
+        let ([op], [right]) = (compare.ops.as_slice(), compare.comparators.as_slice()) else {
+            return narrowing;
+        };
+        let negated = match op {
+            CmpOp::Is | CmpOp::Eq => false,
+            CmpOp::IsNot | CmpOp::NotEq => true,
+            _ => return narrowing,
+        };
+
+        // Accept `type(x) is C` as well as `C is type(x)`
+        let (target, class_expr) = match (Self::type_call_target(&compare.left), Self::type_call_target(right)) {
+            (Some(target), None) => (target, right),
+            (None, Some(target)) => (target, &*compare.left),
+            _ => return narrowing,
+        };
+
+        // Tuples make no sense here; `type(x) is (A, B)` is always false
+        let mut classes = Vec::new();
+        if matches!(class_expr, Expr::Tuple(_)) || self.class_spec(class_expr, &mut classes).is_err() {
+            return narrowing;
+        }
+        let Some(class) = classes.pop() else { return narrowing };
+
+        let declared = self.lookup(target, env);
+        match &declared {
+            Some(ty) if !matches!(ty, Type::Any | Type::Var(_)) => {
+                let members = Self::members(ty);
+                if members.iter().any(|m| self.is_instance(&class, m)) {
+                    narrowing.positive.insert(target.to_string(), class.clone());
+                }
+                let remaining: Vec<Type> = members.iter()
+                    .filter(|m| !self.is_exact(m, &class))
+                    .cloned()
+                    .collect();
+                if !remaining.is_empty() && remaining.len() < members.len() {
+                    narrowing.negative.insert(target.to_string(), Type::union(remaining));
+                }
+            }
+            _ => {
+                narrowing.positive.insert(target.to_string(), class);
+            }
+        }
+
+        if negated { narrowing.swap() } else { narrowing }
+    }
+
+    /// `x is None`, `x is not None` and their `==` forms; `x` may be an
+    /// attribute like `self.x` or a walrus like `(x := f())`
+    ///
+    /// Returns `None` when the test doesn't compare a name with `None`.
+    fn analyze_none_compare(&self, compare: &ExprCompare, env: &HashMap<String, Type>) -> Option<Narrowing> {
+        let ([op], [right]) = (compare.ops.as_slice(), compare.comparators.as_slice()) else {
+            return None;
+        };
+        let is_none = |expr: &Expr| matches!(expr, Expr::Constant(c) if matches!(c.value, Constant::None));
+        let target = match (target_path(&compare.left), target_path(right)) {
+            (Some(target), None) if is_none(right) => target,
+            (None, Some(target)) if is_none(&compare.left) => target,
+            _ => return None,
+        };
+        let negated = match op {
+            CmpOp::Is | CmpOp::Eq => false,
+            CmpOp::IsNot | CmpOp::NotEq => true,
+            _ => return None,
+        };
+
+        let mut narrowing = Narrowing::default();
+        let declared = self.lookup(&target, env).unwrap_or(Type::Any);
+        if let Some(without) = Self::without_none(&declared) {
+            narrowing.positive.insert(target.clone(), Type::None);
+            narrowing.negative.insert(target, without);
+        }
+        Some(if negated { narrowing.swap() } else { narrowing })
+    }
+
+    /// `x == 'r'` and `x != 'r'` against a str, int, bool or bytes constant,
+    /// and `x is Color.RED` or `x == Color.RED` against an enum member:
+    /// where equal, `x` is that literal; where not, the literal is dropped
+    /// from a union of literals or from the members of a `bool` or enum
+    ///
+    /// Returns `None` when the test doesn't compare a name with such a constant.
+    fn analyze_literal_compare(&self, compare: &ExprCompare, env: &HashMap<String, Type>) -> Option<Narrowing> {
+        let ([op], [right]) = (compare.ops.as_slice(), compare.comparators.as_slice()) else {
+            return None;
+        };
+        // An enum member like `Color.RED` is a path too; it's the value
+        let (target, value) = match (self.literal_value(right), self.literal_value(&compare.left)) {
+            (Some(value), _) => (target_path(&compare.left)?, value),
+            (None, Some(value)) => (target_path(right)?, value),
+            _ => return None,
+        };
+        // Enum members are singletons, so identity is equality
+        let singleton = matches!(value, ConstValue::EnumMember { .. } | ConstValue::Bool(_));
+        let negated = match op {
+            CmpOp::Eq => false,
+            CmpOp::NotEq => true,
+            CmpOp::Is if singleton => false,
+            CmpOp::IsNot if singleton => true,
+            _ => return None,
+        };
+
+        let mut narrowing = Narrowing::default();
+        let declared = self.lookup(&target, env).unwrap_or(Type::Any);
+        let literal = value.literal_type();
+        let members = self.literal_members(&declared);
+        if members.iter().any(|member| literal.is_subtype(member)) {
+            narrowing.positive.insert(target.to_string(), literal.clone());
+        }
+        let rest: Vec<Type> = members.iter().filter(|member| **member != literal).cloned().collect();
+        if !rest.is_empty() && rest.len() < members.len() {
+            narrowing.negative.insert(target.to_string(), Type::union(rest));
+        }
+        Some(if negated { narrowing.swap() } else { narrowing })
+    }
+
+    /// The value of a constant or enum member a literal type can be narrowed to
+    fn literal_value(&self, expr: &Expr) -> Option<ConstValue> {
+        if !matches!(expr, Expr::Constant(_) | Expr::Attribute(_)) {
+            return None;
+        }
+        match self.consts.eval(expr).ok()? {
+            value @ (ConstValue::Str(_)
+            | ConstValue::Int(_)
+            | ConstValue::Bool(_)
+            | ConstValue::Bytes(_)
+            | ConstValue::EnumMember { .. }) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Union members of `ty`, with `bool` and enums split into their literal
+    /// values
+    pub fn literal_members(&self, ty: &Type) -> Vec<Type> {
+        Self::members(ty).into_iter()
+            .flat_map(|member| match &member {
+                Type::Bool => vec![ConstValue::Bool(true).literal_type(), ConstValue::Bool(false).literal_type()],
+                Type::Class(name) => match self.consts.enum_members(name) {
+                    Some(values) => values.iter().map(ConstValue::literal_type).collect(),
+                    None => vec![member],
+                },
+                _ => vec![member],
+            })
+            .collect()
+    }
+
+    /// `if x:` rules out `None`; a falsy value may still be `0` or `""`,
+    /// so the other branch learns nothing
+    fn analyze_truthiness(&self, test: &Expr, env: &HashMap<String, Type>) -> Narrowing {
+        let mut narrowing = Narrowing::default();
+        let Some(target) = target_path(test) else { return narrowing };
+        let declared = self.lookup(&target, env).unwrap_or(Type::Any);
+        if let Some(without) = Self::without_none(&declared) {
+            narrowing.positive.insert(target, without);
+        }
+        narrowing
+    }
+
+    /// `ty` without its `None` members; `None` when it has none to remove
+    /// or nothing else
+    pub fn without_none(ty: &Type) -> Option<Type> {
+        let members = Self::members(ty);
+        let rest: Vec<Type> = members.iter().filter(|m| **m != Type::None).cloned().collect();
+        (!rest.is_empty() && rest.len() < members.len()).then(|| Type::union(rest))
+    }
+
+    /// `len(x) <op> N` and `N <op> len(x)`: attach length bounds to sequences
+    ///
+    /// Returns `None` when the test isn't a length comparison at all; types
+    /// without a tracked length are left alone.
+    fn analyze_len_compare(&self, compare: &ExprCompare, env: &HashMap<String, Type>) -> Option<Narrowing> {
+        let ([op], [right]) = (compare.ops.as_slice(), compare.comparators.as_slice()) else {
+            return None;
+        };
+
+        let (target, n, op) = match (Self::len_call_target(&compare.left), Self::len_call_target(right)) {
+            (Some(target), None) => (target, const_int(right)?, *op),
+            (None, Some(target)) => {
+                let flipped = match op {
+                    CmpOp::Lt => CmpOp::Gt,
+                    CmpOp::LtE => CmpOp::GtE,
+                    CmpOp::Gt => CmpOp::Lt,
+                    CmpOp::GtE => CmpOp::LtE,
+                    other => *other,
+                };
+                (target, const_int(&compare.left)?, flipped)
+            }
+            _ => return None,
+        };
+        let n = usize::try_from(n).ok()?;
+
+        let mut narrowing = Narrowing::default();
+        let Some(declared) = self.lookup(target, env) else { return Some(narrowing) };
+
+        let (positive, negative) = LengthBound::from_comparison(op, n);
+        if let Some(ty) = positive.and_then(|bound| bound.apply(&declared)) {
+            narrowing.positive.insert(target.to_string(), ty);
+        }
+        if let Some(ty) = negative.and_then(|bound| bound.apply(&declared)) {
+            narrowing.negative.insert(target.to_string(), ty);
+        }
+        Some(narrowing)
+    }
+
+    /// `x <op> N` and `N <op> x` where `x` is a refined int: tighten its
+    /// bounds in each branch
+    fn analyze_bound_compare(&self, compare: &ExprCompare, env: &HashMap<String, Type>) -> Option<Narrowing> {
+        let ([op], [right]) = (compare.ops.as_slice(), compare.comparators.as_slice()) else {
+            return None;
+        };
+        let op = match op {
+            CmpOp::Eq => CompareOp::Eq,
+            CmpOp::NotEq => CompareOp::Ne,
+            CmpOp::Lt => CompareOp::Lt,
+            CmpOp::LtE => CompareOp::Le,
+            CmpOp::Gt => CompareOp::Gt,
+            CmpOp::GtE => CompareOp::Ge,
+            _ => return None,
+        };
+
+        let (target, n, op) = match (&*compare.left, right) {
+            (Expr::Name(name), _) => (name.id.as_str(), const_int(right)?, op),
+            (_, Expr::Name(name)) => (name.id.as_str(), const_int(&compare.left)?, op.flipped()),
+            _ => return None,
+        };
+        let Some(Type::Refinement(base, predicate)) = self.lookup(target, env) else { return None };
+        if *base != Type::Int {
+            return None;
+        }
+        let bounds = Interval::of(&predicate)?;
+
+        let mut narrowing = Narrowing::default();
+        narrowing.positive.insert(target.to_string(), bounds.narrow(op, n).to_type());
+        narrowing.negative.insert(target.to_string(), bounds.narrow(op.negated(), n).to_type());
+        Some(narrowing)
+    }
+
+    fn len_call_target(expr: &Expr) -> Option<&str> {
+        let Expr::Call(call) = expr else { return None };
+        match (&*call.func, call.args.as_slice()) {
+            (Expr::Name(func), [Expr::Name(arg)]) if func.id.as_str() == "len" => Some(arg.id.as_str()),
+            _ => None,
+        }
+    }
+
+    fn type_call_target(expr: &Expr) -> Option<&str> {
+        let Expr::Call(call) = expr else { return None };
+        match (&*call.func, call.args.as_slice()) {
+            (Expr::Name(func), [Expr::Name(arg)]) if func.id.as_str() == "type" => Some(arg.id.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Resolve the class argument of `isinstance`, flattening nested tuples
+    fn class_spec(&self, expr: &Expr, out: &mut Vec<Type>) -> Result<(), String> {
+        match expr {
+            Expr::Tuple(tuple) => {
+                for elt in &tuple.elts {
+                    self.class_spec(elt, out)?;
+                }
+                Ok(())
+            }
+            Expr::Name(name) => {
+                let ty = match self.ctx.get_type(&name.id) {
+                    Some(Type::ClassObject(class)) => Type::Class(class),
+                    Some(Type::Any | Type::Var(_)) => Type::Any,
+                    Some(other) => {
+                        return Err(format!(
+                            "isinstance() expects a class or tuple of classes, got '{}' of type {}",
+                            name.id, other
+                        ));
+                    }
+                    None => Self::builtin_class(&name.id),
+                };
+                out.push(ty);
+                Ok(())
+            }
+            Expr::Attribute(attr) => {
+                out.push(Type::Class(attr.attr.to_string()));
+                Ok(())
+            }
+            Expr::Constant(constant) => Err(format!(
+                "isinstance() expects a class or tuple of classes, got {}",
+                Self::describe_constant(&constant.value)
+            )),
+            _ => Err("isinstance() expects a class or tuple of classes".to_string()),
+        }
+    }
+
+    fn builtin_class(name: &str) -> Type {
+        match name {
+            "int" => Type::Int,
+            "float" => Type::Float,
+            "str" => Type::Str,
+            "bool" => Type::Bool,
+            "bytes" => Type::Bytes,
+            "list" => Type::List(Box::new(Type::Any)),
+            "dict" => Type::Dict(Box::new(Type::Any), Box::new(Type::Any)),
+            "set" => Type::Set(Box::new(Type::Any)),
+            "tuple" => Type::Tuple(vec![]),
+            "object" => Type::Any,
+            _ => Type::Class(name.to_string()),
+        }
+    }
+
+    fn describe_constant(value: &Constant) -> String {
+        match value {
+            Constant::Int(i) => i.to_string(),
+            Constant::Float(f) => f.to_string(),
+            Constant::Str(s) => format!("'{}'", s),
+            Constant::Bool(b) => if *b { "True".to_string() } else { "False".to_string() },
+            Constant::None => "None".to_string(),
+            _ => "a constant".to_string(),
+        }
+    }
+
+    /// Members of a possibly nested union
+    pub fn members(ty: &Type) -> Vec<Type> {
+        match ty {
+            Type::Union(types) => types.iter().flat_map(Self::members).collect(),
+            other => vec![other.clone()],
+        }
+    }
+
+    /// isinstance semantics: subclasses count
+    fn is_instance(&self, member: &Type, class: &Type) -> bool {
+        match (member, class) {
+            (_, Type::Any) => true,
+            (_, Type::Class(name)) if name == "object" => true,
+            (Type::Class(a), Type::Class(b)) => self.ctx.is_subclass(a, b),
+            (Type::Bool, Type::Int) => true,
+            (Type::List(_), Type::List(_))
+            | (Type::Dict(_, _), Type::Dict(_, _))
+            | (Type::Set(_), Type::Set(_))
+            | (Type::Tuple(_) | Type::VarTuple(_), Type::Tuple(_)) => true,
+            (Type::Refinement(inner, _) | Type::Effect(inner, _) | Type::Dependent(inner, _), _) => {
+                self.is_instance(inner, class)
+            }
+            (Type::Nominal(_, inner), _) => self.is_instance(inner, class),
+            _ => member == class,
+        }
+    }
+
+    /// type() identity semantics: subclasses don't count
+    fn is_exact(&self, member: &Type, class: &Type) -> bool {
+        match (member, class) {
+            (Type::List(_), Type::List(_))
+            | (Type::Dict(_, _), Type::Dict(_, _))
+            | (Type::Set(_), Type::Set(_))
+            | (Type::Tuple(_) | Type::VarTuple(_), Type::Tuple(_)) => true,
+            (Type::Refinement(inner, _) | Type::Effect(inner, _) | Type::Dependent(inner, _), _) => {
+                self.is_exact(inner, class)
+            }
+            _ => member == class,
+        }
+    }
+}
+
+/// The type a function returning `ret` narrows its first argument to, and
+/// whether it is a `TypeIs`, which narrows both ways, rather than a `TypeGuard`
+pub fn type_guard(ret: &Type) -> Option<(Type, bool)> {
+    match ret {
+        Type::Generic(name, args) if args.len() == 1 => match name.as_str() {
+            "TypeGuard" => Some((args[0].clone(), false)),
+            "TypeIs" => Some((args[0].clone(), true)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// What a condition operand tests: a name `x`, an attribute path like
+/// `self.x` or `a.b.c`, or the target of `(x := ...)`
+pub fn target_path(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Name(name) => Some(name.id.to_string()),
+        Expr::Attribute(attr) => Some(format!("{}.{}", target_path(&attr.value)?, attr.attr)),
+        Expr::NamedExpr(named) => target_path(&named.target),
+        _ => None,
+    }
+}
+
+/// The attribute paths `test` may narrow, whose types the analyzer can't
+/// look up itself
+pub fn attribute_targets(test: &Expr) -> Vec<&Expr> {
+    match test {
+        Expr::Attribute(_) => target_path(test).map(|_| test).into_iter().collect(),
+        Expr::UnaryOp(unary) => attribute_targets(&unary.operand),
+        Expr::BoolOp(bool_op) => bool_op.values.iter().flat_map(attribute_targets).collect(),
+        Expr::Compare(compare) => std::iter::once(&*compare.left).chain(&compare.comparators).flat_map(attribute_targets).collect(),
+        Expr::Call(call) => call.args.iter().flat_map(attribute_targets).collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::frontend::parser::parse_expression;
+
+    fn analyzer_with(vars: &[(&str, Type)]) -> ConditionAnalyzer {
+        let ctx = Arc::new(TypeContext::new());
+        for (name, ty) in vars {
+            ctx.set_type(name.to_string(), ty.clone());
+        }
+        ConditionAnalyzer::new(ctx)
+    }
+
+    #[test]
+    fn test_isinstance_tuple_narrowing() {
+        let mut analyzer = analyzer_with(&[("x", Type::Union(vec![Type::Int, Type::Str, Type::Bytes]))]);
+        let test = parse_expression("isinstance(x, (str, (bytes,)))").unwrap();
+
+        let narrowing = analyzer.analyze(&test);
+        assert_eq!(narrowing.positive.get("x"), Some(&Type::Union(vec![Type::Str, Type::Bytes])));
+        assert_eq!(narrowing.negative.get("x"), Some(&Type::Int));
+    }
+
+    #[test]
+    fn test_not_swaps_branches() {
+        let mut analyzer = analyzer_with(&[("x", Type::Union(vec![Type::Int, Type::Str]))]);
+        let test = parse_expression("not isinstance(x, int)").unwrap();
+
+        let narrowing = analyzer.analyze(&test);
+        assert_eq!(narrowing.positive.get("x"), Some(&Type::Str));
+        assert_eq!(narrowing.negative.get("x"), Some(&Type::Int));
+    }
+
+    #[test]
+    fn test_non_class_element_diagnostic() {
+        let mut analyzer = analyzer_with(&[("x", Type::Int)]);
+        let test = parse_expression("isinstance(x, (str, 42))").unwrap();
+
+        let narrowing = analyzer.analyze(&test);
+        assert!(narrowing.positive.is_empty());
+        let errors = analyzer.take_errors();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("got 42"));
+    }
+
+    #[test]
+    fn test_none_comparison_and_truthiness() {
+        let mut analyzer = analyzer_with(&[("x", Type::Union(vec![Type::Str, Type::None]))]);
+
+        let narrowing = analyzer.analyze(&parse_expression("x is None").unwrap());
+        assert_eq!(narrowing.positive.get("x"), Some(&Type::None));
+        assert_eq!(narrowing.negative.get("x"), Some(&Type::Str));
+
+        let narrowing = analyzer.analyze(&parse_expression("x").unwrap());
+        assert_eq!(narrowing.positive.get("x"), Some(&Type::Str));
+        assert!(narrowing.negative.is_empty());
+    }
+
+    #[test]
+    fn test_attribute_paths_narrow() {
+        let mut analyzer = analyzer_with(&[]);
+        let test = parse_expression("self.size is not None and isinstance(self.box.label, str)").unwrap();
+        let attributes: HashMap<String, Type> = attribute_targets(&test).into_iter()
+            .map(|target| target_path(target).unwrap())
+            .zip([Type::Union(vec![Type::Int, Type::None]), Type::Union(vec![Type::Str, Type::Bytes])])
+            .collect();
+
+        let narrowing = analyzer.analyze_with(&test, &attributes);
+        assert_eq!(narrowing.positive.get("self.size"), Some(&Type::Int));
+        assert_eq!(narrowing.positive.get("self.box.label"), Some(&Type::Str));
+    }
+
+    #[test]
+    fn test_len_comparison_attaches_bounds() {
+        use crate::compiler::types::DependentConstraint;
+
+        let list = Type::List(Box::new(Type::Int));
+        let mut analyzer = analyzer_with(&[("xs", list.clone())]);
+        let test = parse_expression("2 <= len(xs)").unwrap();
+
+        let narrowing = analyzer.analyze(&test);
+        assert_eq!(
+            narrowing.positive.get("xs"),
+            Some(&Type::Dependent(Box::new(list.clone()), DependentConstraint::LengthRange(2, usize::MAX)))
+        );
+        assert_eq!(
+            narrowing.negative.get("xs"),
+            Some(&Type::Dependent(Box::new(list), DependentConstraint::LengthRange(0, 1)))
+        );
+    }
+
+    #[test]
+    fn test_bound_comparison_narrows_refined_int() {
+        use crate::compiler::analysis::RefinementAnalyzer;
+
+        let mut analyzer = analyzer_with(&[("x", RefinementAnalyzer::bounded_int(0, 100)), ("n", Type::Int)]);
+        let narrowing = analyzer.analyze(&parse_expression("50 > x").unwrap());
+        assert_eq!(narrowing.positive.get("x"), Some(&RefinementAnalyzer::bounded_int(0, 49)));
+        assert_eq!(narrowing.negative.get("x"), Some(&RefinementAnalyzer::bounded_int(50, 100)));
+
+        // Plain ints are left alone
+        let narrowing = analyzer.analyze(&parse_expression("n < 50").unwrap());
+        assert!(narrowing.positive.is_empty());
+    }
+}
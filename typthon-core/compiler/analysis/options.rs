@@ -0,0 +1,178 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use super::lints::{self, LintLevel};
+use crate::compiler::frontend::config::Config;
+
+/// Settings for a checker run, assembled by the CLI or the Python bindings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CheckerOptions {
+    /// Turn on every `disallow_*` check and the strict-only lints
+    pub strict: bool,
+    /// Exempt function signatures from `strict` and `disallow_untyped_defs`
+    pub allow_untyped_defs: bool,
+    /// Require annotations on every function parameter and return
+    pub disallow_untyped_defs: bool,
+    /// Report names bound to the implicit `Any` of unannotated values
+    pub disallow_any: bool,
+    /// Report uses of values that may be `None` unless narrowed first
+    pub strict_optional: bool,
+    /// Report `# type: ignore` comments that suppress nothing
+    pub warn_unused_ignores: bool,
+    /// Report function-local variables and parameters nothing reads
+    pub warn_unused_variables: bool,
+    /// In strict mode, type functions with a decorator the checker doesn't
+    /// know as taking any arguments and returning `Any`, rather than keeping
+    /// the signature they were defined with
+    pub erase_unknown_decorators: bool,
+    /// Target Python version as (major, minor)
+    pub python_version: (u8, u8),
+    /// Extra directories searched for `.pyi` stubs
+    pub stub_paths: Vec<PathBuf>,
+    /// Stop reporting after this many errors
+    pub max_errors: Option<usize>,
+    /// Registered plugins that should not run, by name
+    pub disabled_plugins: Vec<String>,
+    /// Lint levels by code; unlisted lints use their default (style lints are off)
+    pub lints: BTreeMap<String, LintLevel>,
+    /// Error and lint codes (`TYP005`, `S001`) never reported
+    pub disabled_codes: BTreeSet<String>,
+    /// When set, only these codes (and errors without one) are reported
+    pub enabled_codes: Option<BTreeSet<String>>,
+}
+
+impl Default for CheckerOptions {
+    fn default() -> Self {
+        Self {
+            strict: false,
+            allow_untyped_defs: false,
+            disallow_untyped_defs: false,
+            disallow_any: false,
+            strict_optional: false,
+            warn_unused_ignores: false,
+            warn_unused_variables: false,
+            erase_unknown_decorators: false,
+            python_version: (3, 12),
+            stub_paths: Vec::new(),
+            max_errors: None,
+            disabled_plugins: Vec::new(),
+            lints: BTreeMap::new(),
+            disabled_codes: BTreeSet::new(),
+            enabled_codes: None,
+        }
+    }
+}
+
+impl CheckerOptions {
+    /// Options for a project's `[check]` and `[lints]` settings, and the
+    /// codes its `[errors]` section disables or enables
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            strict: config.check.strict,
+            allow_untyped_defs: config.check.allow_untyped_defs,
+            strict_optional: config.check.strict_optional,
+            warn_unused_ignores: config.check.warn_unused_ignores,
+            warn_unused_variables: config.check.warn_unused_variables,
+            erase_unknown_decorators: config.check.erase_unknown_decorators,
+            lints: config.lints.clone(),
+            disabled_codes: config.errors.disable.iter().map(|code| code.to_uppercase()).collect(),
+            enabled_codes: config.errors.enable.as_ref().map(|codes| codes.iter().map(|code| code.to_uppercase()).collect()),
+            ..Self::default()
+        }
+    }
+
+    /// Whether unannotated function signatures are errors
+    pub fn requires_annotations(&self) -> bool {
+        (self.strict || self.disallow_untyped_defs) && !self.allow_untyped_defs
+    }
+
+    /// Whether binding a name to an implicit `Any` is an error
+    pub fn reports_implicit_any(&self) -> bool {
+        self.strict || self.disallow_any
+    }
+
+    /// Whether an unknown decorator erases the signature of the function it wraps
+    pub fn erases_unknown_decorators(&self) -> bool {
+        self.strict && self.erase_unknown_decorators
+    }
+
+    /// Whether operations on a possibly-`None` value are errors
+    pub fn checks_optional(&self) -> bool {
+        self.strict || self.strict_optional
+    }
+
+    /// Configured level of a lint; unlisted lints are off, except that
+    /// strict mode warns for its own lints
+    pub fn lint_level(&self, code: &str) -> LintLevel {
+        match self.lints.get(code) {
+            Some(level) => *level,
+            None if self.strict && lints::STRICT_LINTS.contains(&code) => LintLevel::Warn,
+            None => LintLevel::Off,
+        }
+    }
+
+    /// Levels of every lint configured or turned on by strict mode
+    pub fn lint_levels(&self) -> BTreeMap<String, LintLevel> {
+        let mut levels = self.lints.clone();
+        if self.strict {
+            for code in lints::STRICT_LINTS {
+                levels.entry(code.to_string()).or_insert(LintLevel::Warn);
+            }
+        }
+        levels
+    }
+
+    /// Whether diagnostics with `code` are reported under `disabled_codes`
+    /// and `enabled_codes`
+    pub fn code_enabled(&self, code: Option<&str>) -> bool {
+        let Some(code) = code else { return true };
+        !self.disabled_codes.contains(code)
+            && self.enabled_codes.as_ref().map_or(true, |enabled| enabled.contains(code))
+    }
+
+    pub fn plugin_enabled(&self, name: &str) -> bool {
+        !self.disabled_plugins.iter().any(|disabled| disabled == name)
+    }
+
+    /// Parse a version like `"3.11"`; only Python 3 is supported
+    pub fn parse_python_version(version: &str) -> Result<(u8, u8), String> {
+        let parsed = version.split_once('.')
+            .and_then(|(major, minor)| Some((major.parse::<u8>().ok()?, minor.parse::<u8>().ok()?)));
+        match parsed {
+            Some((3, minor)) => Ok((3, minor)),
+            _ => Err(format!("python_version must look like '3.X', got '{}'", version)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_python_version() {
+        assert_eq!(CheckerOptions::parse_python_version("3.10"), Ok((3, 10)));
+        assert!(CheckerOptions::parse_python_version("2.7").is_err());
+        assert!(CheckerOptions::parse_python_version("3").is_err());
+    }
+
+    #[test]
+    fn test_strict_requires_annotations() {
+        use crate::compiler::analysis::TypeChecker;
+        use crate::compiler::frontend::parse_module;
+
+        let module = parse_module("def add(x, y: int):\n    return x + y\n").unwrap();
+        assert!(TypeChecker::new().check(&module).is_empty());
+
+        let strict = CheckerOptions { strict: true, ..CheckerOptions::default() };
+        let errors = TypeChecker::new().with_options(strict.clone()).check(&module);
+        let messages: Vec<_> = errors.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, vec![
+            "Function 'add' is missing a type annotation for parameter 'x'",
+            "Function 'add' is missing a return type annotation",
+        ]);
+
+        let capped = CheckerOptions { max_errors: Some(1), ..strict };
+        assert_eq!(TypeChecker::new().with_options(capped).check(&module).len(), 1);
+    }
+}
@@ -0,0 +1,17 @@
+//! Checker plugins - third-party rules run after the built-in passes
+
+use rustpython_parser::ast::Mod;
+use crate::compiler::types::TypeContext;
+use super::checker::TypeError;
+
+/// A third-party rule set hooked into `TypeChecker::check`
+///
+/// Hooks are timed under the plugin's name, so a slow plugin shows up in
+/// `--stats` without any instrumentation on its side.
+pub trait CheckerPlugin: Send + Sync {
+    /// Stable name used in timing reports
+    fn name(&self) -> &str;
+
+    /// Inspect a checked module and report additional errors
+    fn check_module(&mut self, module: &Mod, ctx: &TypeContext) -> Vec<TypeError>;
+}
@@ -1,4 +1,4 @@
-use crate::compiler::types::Type;
+use crate::compiler::types::{Type, TypeContext};
 use crate::compiler::analysis::constraints::ConstraintSolver;
 
 /// Common protocol definitions for structural typing
@@ -25,7 +25,7 @@ impl ProtocolLibrary {
     /// Iterator protocol: has __next__ and __iter__ methods
     pub fn iterator(elem_type: Type) -> Vec<(String, Type)> {
         vec![
-            ("__iter__".to_string(), Type::Function(vec![], Box::new(Type::Class("Self".to_string())))),
+            ("__iter__".to_string(), Type::Function(vec![], Box::new(Type::param("Self")))),
             ("__next__".to_string(), Type::Function(vec![], Box::new(elem_type))),
         ]
     }
@@ -48,10 +48,24 @@ impl ProtocolLibrary {
         ]
     }
 
+    /// AsyncContextManager protocol: supports `async with`
+    pub fn async_context_manager(resource_type: Type) -> Vec<(String, Type)> {
+        vec![
+            ("__aenter__".to_string(), Type::Function(
+                vec![],
+                Box::new(Type::Generic("Awaitable".to_string(), vec![resource_type]))
+            )),
+            ("__aexit__".to_string(), Type::Function(
+                vec![Type::Any, Type::Any, Type::Any],
+                Box::new(Type::Generic("Awaitable".to_string(), vec![Type::None]))
+            )),
+        ]
+    }
+
     /// Comparable protocol: has comparison operators
     pub fn comparable() -> Vec<(String, Type)> {
         let comparison_type = Type::Function(
-            vec![Type::Class("Self".to_string())],
+            vec![Type::param("Self")],
             Box::new(Type::Bool)
         );
 
@@ -67,7 +81,7 @@ impl ProtocolLibrary {
 
     /// Numeric protocol: has arithmetic operators
     pub fn numeric() -> Vec<(String, Type)> {
-        let self_type = Type::Class("Self".to_string());
+        let self_type = Type::param("Self");
         let binary_op = Type::Function(vec![self_type.clone()], Box::new(self_type.clone()));
 
         vec![
@@ -182,7 +196,7 @@ impl ProtocolLibrary {
         vec![
             ("__aiter__".to_string(), Type::Function(
                 vec![],
-                Box::new(Type::Class("Self".to_string()))
+                Box::new(Type::param("Self"))
             )),
             ("__anext__".to_string(), Type::Function(
                 vec![],
@@ -275,6 +289,7 @@ impl ProtocolLibrary {
                 }
             }
             "ContextManager" => type_args.get(0).map(|t| Self::context_manager(t.clone())),
+            "AsyncContextManager" => type_args.first().map(|t| Self::async_context_manager(t.clone())),
             "Awaitable" => type_args.get(0).map(|t| Self::awaitable(t.clone())),
             "AsyncIterable" => type_args.get(0).map(|t| Self::async_iterable(t.clone())),
             "AsyncIterator" => type_args.get(0).map(|t| Self::async_iterator(t.clone())),
@@ -308,6 +323,17 @@ impl ProtocolChecker {
         }
     }
 
+    /// Methods of `protocol` that `ty` doesn't have at all
+    ///
+    /// Unlike `implements_protocol` this ignores signatures, which vary too
+    /// much in practice (`__exit__(self, *exc)`) to be held to the library's.
+    pub fn missing_methods(ty: &Type, protocol: &[(String, Type)], ctx: &TypeContext) -> Vec<String> {
+        protocol.iter()
+            .filter(|(name, _)| ctx.has_attribute(ty, name).is_none())
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
     /// Get all protocols implemented by a type
     pub fn implemented_protocols(ty: &Type, solver: &ConstraintSolver) -> Vec<String> {
         let protocols = vec![
@@ -393,6 +419,43 @@ mod tests {
         assert_eq!(methods.len(), 2);
         assert!(methods.iter().any(|(name, _)| name == "__enter__"));
         assert!(methods.iter().any(|(name, _)| name == "__exit__"));
+
+        let ctx = TypeContext::new();
+        let missing = ProtocolChecker::missing_methods(&Type::Str, &methods, &ctx);
+        assert_eq!(missing, vec!["__enter__".to_string(), "__exit__".to_string()]);
+    }
+
+    #[test]
+    fn test_builtin_implemented_protocols() {
+        let solver = ConstraintSolver::with_context(Arc::new(TypeContext::new()));
+        let implemented = |ty: &Type| ProtocolChecker::implemented_protocols(ty, &solver);
+
+        assert_eq!(implemented(&Type::Int), vec![
+            "Hashable", "Equality", "Comparable", "SupportsInt", "SupportsFloat", "SupportsStr",
+            "SupportsRepr", "SupportsBool",
+        ]);
+        assert_eq!(implemented(&Type::Float), vec![
+            "Hashable", "Equality", "Comparable", "Numeric", "SupportsInt", "SupportsFloat",
+            "SupportsStr", "SupportsRepr", "SupportsBool",
+        ]);
+        assert_eq!(implemented(&Type::Str), vec![
+            "Sized", "Hashable", "Equality", "Comparable", "SupportsStr", "SupportsRepr",
+        ]);
+        assert_eq!(implemented(&Type::List(Box::new(Type::Int))), vec![
+            "Sized", "Equality", "Comparable", "SupportsStr", "SupportsRepr",
+        ]);
     }
-}
 
+    #[test]
+    fn test_protocol_self_is_the_checked_type() {
+        let solver = ConstraintSolver::with_context(Arc::new(TypeContext::new()));
+
+        // `int / int` is a float, so int isn't closed under the numeric operators
+        let error = solver.check_protocol(&Type::Int, &ProtocolLibrary::numeric()).unwrap_err();
+        assert!(error.suggestions.iter().any(|suggestion| suggestion.contains("'__truediv__'")));
+
+        let error = solver.check_protocol(&Type::Int, &ProtocolLibrary::sized()).unwrap_err();
+        assert!(error.to_string().contains("__len__"));
+        assert!(solver.check_protocol(&Type::Str, &ProtocolLibrary::sized()).unwrap());
+    }
+}
@@ -0,0 +1,267 @@
+//! Whether control can fall off the end of a block
+//!
+//! Used to find functions annotated with a return type that can finish
+//! without returning, and statements no path reaches. The analysis is
+//! syntactic: `return`, `raise`, `break` and `continue` end a path, `while
+//! True` without a `break` never exits, and compound statements fall through
+//! when any of their branches does. Calls are assumed to return unless the
+//! checker found they call a function returning `Never`.
+
+use rustpython_parser::ast::*;
+
+/// What type checking learned about control flow that the syntax doesn't show
+pub trait Flow {
+    /// Does `stmt` cover every value of its subject?
+    fn exhaustive(&self, _stmt: &StmtMatch) -> bool {
+        false
+    }
+
+    /// Does `call` never return, like `sys.exit()`?
+    fn never_returns(&self, _call: &ExprCall) -> bool {
+        false
+    }
+}
+
+/// Nothing beyond the syntax
+impl Flow for () {}
+
+/// Can execution reach the end of `body`?
+pub fn falls_through(body: &[Stmt]) -> bool {
+    falls_through_with(body, &())
+}
+
+/// `falls_through`, also using what `flow` knows about `match` statements
+/// and calls
+pub fn falls_through_with(body: &[Stmt], flow: &dyn Flow) -> bool {
+    body.iter().all(|stmt| stmt_falls_through(stmt, flow))
+}
+
+/// The first statement of each block in `body`, nested blocks included, that
+/// follows a statement control never gets past, paired with that statement.
+/// Dead code isn't searched further
+pub fn unreachable<'a>(body: &'a [Stmt], flow: &dyn Flow) -> Vec<(&'a Stmt, &'a Stmt)> {
+    let mut found = Vec::new();
+    collect_unreachable(body, flow, &mut found);
+    found
+}
+
+fn collect_unreachable<'a>(body: &'a [Stmt], flow: &dyn Flow, out: &mut Vec<(&'a Stmt, &'a Stmt)>) {
+    for (i, stmt) in body.iter().enumerate() {
+        for block in blocks(stmt) {
+            collect_unreachable(block, flow, out);
+        }
+        if !stmt_falls_through(stmt, flow) {
+            if let Some(dead) = body.get(i + 1) {
+                out.push((dead, stmt));
+            }
+            return;
+        }
+    }
+}
+
+/// The blocks nested directly in `stmt`
+fn blocks(stmt: &Stmt) -> Vec<&[Stmt]> {
+    match stmt {
+        Stmt::FunctionDef(func_def) => vec![&func_def.body],
+        Stmt::AsyncFunctionDef(func_def) => vec![&func_def.body],
+        Stmt::ClassDef(class_def) => vec![&class_def.body],
+        Stmt::If(if_stmt) => vec![&if_stmt.body, &if_stmt.orelse],
+        Stmt::While(while_stmt) => vec![&while_stmt.body, &while_stmt.orelse],
+        Stmt::For(for_stmt) => vec![&for_stmt.body, &for_stmt.orelse],
+        Stmt::AsyncFor(for_stmt) => vec![&for_stmt.body, &for_stmt.orelse],
+        Stmt::With(with_stmt) => vec![&with_stmt.body],
+        Stmt::AsyncWith(with_stmt) => vec![&with_stmt.body],
+        Stmt::Try(try_stmt) => try_blocks(&try_stmt.body, &try_stmt.handlers, &try_stmt.orelse, &try_stmt.finalbody),
+        Stmt::TryStar(try_stmt) => {
+            try_blocks(&try_stmt.body, &try_stmt.handlers, &try_stmt.orelse, &try_stmt.finalbody)
+        }
+        Stmt::Match(match_stmt) => match_stmt.cases.iter().map(|case| case.body.as_slice()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn try_blocks<'a>(body: &'a [Stmt], handlers: &'a [ExceptHandler], orelse: &'a [Stmt], finalbody: &'a [Stmt]) -> Vec<&'a [Stmt]> {
+    let handlers = handlers.iter().map(|ExceptHandler::ExceptHandler(handler)| handler.body.as_slice());
+    std::iter::once(body).chain(handlers).chain([orelse, finalbody]).collect()
+}
+
+fn stmt_falls_through(stmt: &Stmt, flow: &dyn Flow) -> bool {
+    let falls_through = |body: &[Stmt]| falls_through_with(body, flow);
+    match stmt {
+        // A `break` or `continue` leaves its block; whether the loop ends is
+        // for the loop to say
+        Stmt::Return(_) | Stmt::Raise(_) | Stmt::Break(_) | Stmt::Continue(_) => false,
+        Stmt::Expr(expr_stmt) => !matches!(&*expr_stmt.value, Expr::Call(call) if flow.never_returns(call)),
+        Stmt::If(if_stmt) => falls_through(&if_stmt.body) || falls_through(&if_stmt.orelse),
+        // The `else` runs whenever the loop ends without `break`
+        Stmt::While(while_stmt) => {
+            breaks(&while_stmt.body) || (!always_true(&while_stmt.test) && falls_through(&while_stmt.orelse))
+        }
+        Stmt::For(for_stmt) => breaks(&for_stmt.body) || falls_through(&for_stmt.orelse),
+        Stmt::AsyncFor(for_stmt) => breaks(&for_stmt.body) || falls_through(&for_stmt.orelse),
+        Stmt::With(with_stmt) => falls_through(&with_stmt.body),
+        Stmt::AsyncWith(with_stmt) => falls_through(&with_stmt.body),
+        Stmt::Try(try_stmt) => {
+            try_falls_through(&try_stmt.body, &try_stmt.handlers, &try_stmt.orelse, &try_stmt.finalbody, flow)
+        }
+        Stmt::TryStar(try_stmt) => {
+            try_falls_through(&try_stmt.body, &try_stmt.handlers, &try_stmt.orelse, &try_stmt.finalbody, flow)
+        }
+        // Without a catch-all case, a subject may match nothing
+        Stmt::Match(match_stmt) => {
+            let catch_all = match_stmt.cases.iter().any(|case| case.guard.is_none() && is_wildcard(&case.pattern));
+            !(catch_all || flow.exhaustive(match_stmt)) || match_stmt.cases.iter().any(|case| falls_through(&case.body))
+        }
+        _ => true,
+    }
+}
+
+fn try_falls_through(
+    body: &[Stmt],
+    handlers: &[ExceptHandler],
+    orelse: &[Stmt],
+    finalbody: &[Stmt],
+    flow: &dyn Flow,
+) -> bool {
+    let falls_through = |body: &[Stmt]| falls_through_with(body, flow);
+    let handled = handlers.iter().any(|ExceptHandler::ExceptHandler(handler)| falls_through(&handler.body));
+    falls_through(finalbody) && ((falls_through(body) && falls_through(orelse)) || handled)
+}
+
+/// Does a `break` in `body` leave the enclosing loop? Breaks inside nested
+/// loops leave those instead
+fn breaks(body: &[Stmt]) -> bool {
+    body.iter().any(|stmt| match stmt {
+        Stmt::Break(_) => true,
+        Stmt::If(if_stmt) => breaks(&if_stmt.body) || breaks(&if_stmt.orelse),
+        Stmt::While(while_stmt) => breaks(&while_stmt.orelse),
+        Stmt::For(for_stmt) => breaks(&for_stmt.orelse),
+        Stmt::AsyncFor(for_stmt) => breaks(&for_stmt.orelse),
+        Stmt::With(with_stmt) => breaks(&with_stmt.body),
+        Stmt::AsyncWith(with_stmt) => breaks(&with_stmt.body),
+        Stmt::Try(try_stmt) => try_breaks(&try_stmt.body, &try_stmt.handlers, &try_stmt.orelse, &try_stmt.finalbody),
+        Stmt::TryStar(try_stmt) => {
+            try_breaks(&try_stmt.body, &try_stmt.handlers, &try_stmt.orelse, &try_stmt.finalbody)
+        }
+        Stmt::Match(match_stmt) => match_stmt.cases.iter().any(|case| breaks(&case.body)),
+        _ => false,
+    })
+}
+
+fn try_breaks(body: &[Stmt], handlers: &[ExceptHandler], orelse: &[Stmt], finalbody: &[Stmt]) -> bool {
+    breaks(body) || breaks(orelse) || breaks(finalbody)
+        || handlers.iter().any(|ExceptHandler::ExceptHandler(handler)| breaks(&handler.body))
+}
+
+/// Does the function body `yield`, making it a generator? Only yields at
+/// statement level (`yield x`, `x = yield`) are seen
+pub fn yields(body: &[Stmt]) -> bool {
+    let is_yield = |expr: &Expr| matches!(expr, Expr::Yield(_) | Expr::YieldFrom(_));
+    body.iter().any(|stmt| match stmt {
+        Stmt::Expr(expr_stmt) => is_yield(&expr_stmt.value),
+        Stmt::Assign(assign) => is_yield(&assign.value),
+        Stmt::AnnAssign(ann_assign) => ann_assign.value.as_deref().is_some_and(is_yield),
+        Stmt::If(if_stmt) => yields(&if_stmt.body) || yields(&if_stmt.orelse),
+        Stmt::While(while_stmt) => yields(&while_stmt.body) || yields(&while_stmt.orelse),
+        Stmt::For(for_stmt) => yields(&for_stmt.body) || yields(&for_stmt.orelse),
+        Stmt::AsyncFor(for_stmt) => yields(&for_stmt.body) || yields(&for_stmt.orelse),
+        Stmt::With(with_stmt) => yields(&with_stmt.body),
+        Stmt::AsyncWith(with_stmt) => yields(&with_stmt.body),
+        Stmt::Try(try_stmt) => {
+            yields(&try_stmt.body) || yields(&try_stmt.orelse) || yields(&try_stmt.finalbody)
+                || try_stmt.handlers.iter().any(|ExceptHandler::ExceptHandler(handler)| yields(&handler.body))
+        }
+        Stmt::Match(match_stmt) => match_stmt.cases.iter().any(|case| yields(&case.body)),
+        _ => false,
+    })
+}
+
+/// A stub body: only a docstring, `...` or `pass`
+pub fn is_stub(body: &[Stmt]) -> bool {
+    body.iter().all(|stmt| match stmt {
+        Stmt::Pass(_) => true,
+        Stmt::Expr(expr_stmt) => matches!(
+            &*expr_stmt.value,
+            Expr::Constant(constant) if matches!(constant.value, Constant::Ellipsis | Constant::Str(_))
+        ),
+        _ => false,
+    })
+}
+
+fn always_true(test: &Expr) -> bool {
+    match test {
+        Expr::Constant(constant) => match &constant.value {
+            Constant::Bool(value) => *value,
+            Constant::Int(value) => value.to_string() != "0",
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn is_wildcard(pattern: &Pattern) -> bool {
+    match pattern {
+        Pattern::MatchAs(pattern) => pattern.pattern.as_deref().map_or(true, is_wildcard),
+        Pattern::MatchOr(pattern) => pattern.patterns.iter().any(is_wildcard),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::frontend::parse_module;
+
+    fn body(source: &str) -> Vec<Stmt> {
+        let Mod::Module(module) = parse_module(source).unwrap() else { unreachable!() };
+        module.body
+    }
+
+    #[test]
+    fn test_branches_and_loops() {
+        assert!(!falls_through(&body("if x:\n    return 1\nelse:\n    raise ValueError()\n")));
+        assert!(falls_through(&body("if x:\n    return 1\nelif y:\n    return 2\n")));
+        assert!(!falls_through(&body("while True:\n    if x:\n        return 1\n")));
+        assert!(falls_through(&body("while True:\n    if x:\n        break\n")));
+        assert!(!falls_through(&body("while True:\n    for y in x:\n        break\n")));
+        assert!(falls_through(&body("for y in x:\n    return y\n")));
+        assert!(!falls_through(&body("try:\n    return 1\nexcept E:\n    raise\n")));
+        assert!(falls_through(&body("try:\n    return 1\nexcept E:\n    pass\n")));
+        assert!(!falls_through(&body("try:\n    pass\nfinally:\n    return 0\n")));
+        assert!(!falls_through(&body("match x:\n    case 1:\n        return 1\n    case _:\n        return 0\n")));
+        assert!(falls_through(&body("match x:\n    case 1:\n        return 1\n")));
+    }
+
+    #[test]
+    fn test_unreachable_statements() {
+        let module = body("\
+def f(x):
+    for y in x:
+        if y:
+            continue
+            print(y)
+    return x
+    print(x)
+if x:
+    raise ValueError()
+else:
+    pass
+");
+        let dead: Vec<&str> = unreachable(&module, &()).iter()
+            .map(|(_, cause)| match cause {
+                Stmt::Continue(_) => "continue",
+                Stmt::Return(_) => "return",
+                _ => "other",
+            })
+            .collect();
+        assert_eq!(dead, vec!["continue", "return"]);
+    }
+
+    #[test]
+    fn test_generators_and_stubs() {
+        assert!(yields(&body("for y in x:\n    yield y\n")));
+        assert!(!yields(&body("return [y for y in x]\n")));
+        assert!(is_stub(&body("'''Docs.'''\n...\n")));
+        assert!(!is_stub(&body("x = 1\n")));
+    }
+}
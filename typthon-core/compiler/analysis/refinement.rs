@@ -1,6 +1,8 @@
 use crate::compiler::types::{Type, Predicate, PredicateExpr, CompareOp, BinOp};
 use rustpython_parser::ast::*;
 use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
 
 /// Refinement type analyzer for extracting and validating predicates
 pub struct RefinementAnalyzer {
@@ -14,45 +16,12 @@ impl RefinementAnalyzer {
         }
     }
 
-    /// Parse predicate from string expression
+    /// Parse a predicate such as `value > 0 and value < 100`,
+    /// `len(value) >= 3` or `not value % 2 == 0`
+    ///
+    /// Errors name the position, counted in characters, where parsing failed.
     pub fn parse_predicate(&self, expr_str: &str) -> Result<Predicate, String> {
-        // Simple predicate parser
-        // Supports: value > 0, value < 100, value == 42, etc.
-
-        let parts: Vec<&str> = expr_str.split_whitespace().collect();
-        if parts.len() != 3 {
-            return Err(format!("Invalid predicate format: {}", expr_str));
-        }
-
-        let left = self.parse_pred_expr(parts[0])?;
-        let op = self.parse_compare_op(parts[1])?;
-        let right = self.parse_pred_expr(parts[2])?;
-
-        Ok(Predicate::Compare { op, left, right })
-    }
-
-    fn parse_pred_expr(&self, s: &str) -> Result<PredicateExpr, String> {
-        if s == "value" {
-            Ok(PredicateExpr::Value)
-        } else if let Ok(n) = s.parse::<i64>() {
-            Ok(PredicateExpr::Literal(n))
-        } else if s.starts_with("len(") && s.ends_with(')') {
-            Ok(PredicateExpr::Property("len".to_string()))
-        } else {
-            Ok(PredicateExpr::Property(s.to_string()))
-        }
-    }
-
-    fn parse_compare_op(&self, s: &str) -> Result<CompareOp, String> {
-        match s {
-            "==" => Ok(CompareOp::Eq),
-            "!=" => Ok(CompareOp::Ne),
-            "<" => Ok(CompareOp::Lt),
-            "<=" => Ok(CompareOp::Le),
-            ">" => Ok(CompareOp::Gt),
-            ">=" => Ok(CompareOp::Ge),
-            _ => Err(format!("Unknown comparison operator: {}", s)),
-        }
+        PredicateParser::new(expr_str)?.parse()
     }
 
     /// Extract predicates from function annotations
@@ -196,12 +165,418 @@ impl RefinementAnalyzer {
     }
 }
 
+/// Token of a predicate string
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Int(i64),
+    Name(String),
+    Symbol(&'static str),
+    End,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Int(n) => write!(f, "'{}'", n),
+            Token::Name(name) => write!(f, "'{}'", name),
+            Token::Symbol(symbol) => write!(f, "'{}'", symbol),
+            Token::End => write!(f, "end of predicate"),
+        }
+    }
+}
+
+/// Symbols, longest first so `<=` isn't read as `<`
+const SYMBOLS: &[&str] = &[
+    "==", "!=", "<=", ">=", "&&", "||", "<", ">", "!", "(", ")", "+", "-", "*", "/", "%",
+];
+
+/// Recursive descent parser for refinement predicates:
+///
+/// ```text
+/// predicate  := and (("or" | "||") and)*
+/// and        := not (("and" | "&&") not)*
+/// not        := ("not" | "!") not | "true" | "(" predicate ")" | comparison
+/// comparison := expr (compare-op expr)+
+/// expr       := term (("+" | "-") term)*
+/// term       := unary (("*" | "/" | "%") unary)*
+/// unary      := "-" unary | "value" | INT | property "(" "value" ")" | "(" expr ")"
+/// ```
+///
+/// Chained comparisons such as `0 <= value < 10` are conjunctions.
+struct PredicateParser<'a> {
+    source: &'a str,
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl<'a> PredicateParser<'a> {
+    fn new(source: &'a str) -> Result<Self, String> {
+        let chars: Vec<char> = source.chars().collect();
+        let mut tokens = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            if c.is_whitespace() {
+                i += 1;
+            } else if c.is_ascii_digit() {
+                let start = i;
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+                let digits: String = chars[start..i].iter().collect();
+                let n = digits.parse().map_err(|_| {
+                    format!("Integer '{}' at position {} is too large in predicate '{}'", digits, start, source)
+                })?;
+                tokens.push((Token::Int(n), start));
+            } else if c.is_alphabetic() || c == '_' {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                tokens.push((Token::Name(chars[start..i].iter().collect()), start));
+            } else {
+                let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+                let Some(symbol) = SYMBOLS.iter().find(|symbol| rest.starts_with(**symbol)) else {
+                    return Err(format!("Unexpected character '{}' at position {} in predicate '{}'", c, i, source));
+                };
+                tokens.push((Token::Symbol(symbol), i));
+                i += symbol.len();
+            }
+        }
+        tokens.push((Token::End, chars.len()));
+        Ok(Self { source, tokens, pos: 0 })
+    }
+
+    fn parse(mut self) -> Result<Predicate, String> {
+        let predicate = self.predicate()?;
+        match self.peek() {
+            Token::End => Ok(predicate),
+            _ => Err(self.unexpected("'and', 'or' or end of predicate")),
+        }
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos].0
+    }
+
+    /// Consume the next token when it's one of `words`, names or symbols
+    fn eat(&mut self, words: &[&str]) -> bool {
+        let matches = match self.peek() {
+            Token::Name(name) => words.contains(&name.as_str()),
+            Token::Symbol(symbol) => words.contains(symbol),
+            _ => false,
+        };
+        if matches {
+            self.pos += 1;
+        }
+        matches
+    }
+
+    fn expect(&mut self, word: &str) -> Result<(), String> {
+        match self.eat(&[word]) {
+            true => Ok(()),
+            false => Err(self.unexpected(&format!("'{}'", word))),
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> String {
+        let (token, position) = &self.tokens[self.pos];
+        format!("Expected {} at position {}, found {} in predicate '{}'", expected, position, token, self.source)
+    }
+
+    fn predicate(&mut self) -> Result<Predicate, String> {
+        let mut predicate = self.conjunction()?;
+        while self.eat(&["or", "||"]) {
+            predicate = predicate.or(self.conjunction()?);
+        }
+        Ok(predicate)
+    }
+
+    fn conjunction(&mut self) -> Result<Predicate, String> {
+        let mut predicate = self.negation()?;
+        while self.eat(&["and", "&&"]) {
+            predicate = predicate.and(self.negation()?);
+        }
+        Ok(predicate)
+    }
+
+    fn negation(&mut self) -> Result<Predicate, String> {
+        if self.eat(&["not", "!"]) {
+            return Ok(Predicate::Not(Box::new(self.negation()?)));
+        }
+        if self.eat(&["true", "True"]) {
+            return Ok(Predicate::True);
+        }
+        // `(` opens either a predicate or an operand of a comparison, as in
+        // `(value + 1) > 0`; the latter is tried when the former doesn't fit
+        if *self.peek() != Token::Symbol("(") {
+            return self.comparison();
+        }
+        let start = self.pos;
+        self.pos += 1;
+        let nested = self.predicate().and_then(|predicate| self.expect(")").map(|_| predicate));
+        let reached = self.pos;
+        match nested {
+            Ok(predicate) if Self::compare_op(self.peek()).is_none() && !Self::is_arithmetic(self.peek()) => {
+                return Ok(predicate);
+            }
+            _ => self.pos = start,
+        }
+        // Failing both ways, the error from the reading that got further
+        match (self.comparison(), nested) {
+            (Err(_), Err(error)) if self.pos < reached => Err(error),
+            (result, _) => result,
+        }
+    }
+
+    fn comparison(&mut self) -> Result<Predicate, String> {
+        let mut left = self.expr()?;
+        let mut predicate: Option<Predicate> = None;
+        while let Some(op) = Self::compare_op(self.peek()) {
+            self.pos += 1;
+            let right = self.expr()?;
+            let compare = Predicate::Compare { op, left, right: right.clone() };
+            predicate = Some(match predicate {
+                Some(chain) => chain.and(compare),
+                None => compare,
+            });
+            left = right;
+        }
+        predicate.ok_or_else(|| self.unexpected("a comparison operator"))
+    }
+
+    fn expr(&mut self) -> Result<PredicateExpr, String> {
+        let mut expr = self.term()?;
+        loop {
+            let op = match self.peek() {
+                Token::Symbol("+") => BinOp::Add,
+                Token::Symbol("-") => BinOp::Sub,
+                _ => return Ok(expr),
+            };
+            self.pos += 1;
+            expr = PredicateExpr::BinOp(Box::new(expr), op, Box::new(self.term()?));
+        }
+    }
+
+    fn term(&mut self) -> Result<PredicateExpr, String> {
+        let mut expr = self.unary()?;
+        loop {
+            let op = match self.peek() {
+                Token::Symbol("*") => BinOp::Mul,
+                Token::Symbol("/") => BinOp::Div,
+                Token::Symbol("%") => BinOp::Mod,
+                _ => return Ok(expr),
+            };
+            self.pos += 1;
+            expr = PredicateExpr::BinOp(Box::new(expr), op, Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<PredicateExpr, String> {
+        let position = self.tokens[self.pos].1;
+        match self.peek().clone() {
+            Token::Symbol("-") => {
+                self.pos += 1;
+                Ok(match self.unary()? {
+                    PredicateExpr::Literal(n) => PredicateExpr::Literal(-n),
+                    operand => PredicateExpr::BinOp(Box::new(PredicateExpr::Literal(0)), BinOp::Sub, Box::new(operand)),
+                })
+            }
+            Token::Symbol("(") => {
+                self.pos += 1;
+                let expr = self.expr()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Token::Int(n) => {
+                self.pos += 1;
+                Ok(PredicateExpr::Literal(n))
+            }
+            Token::Name(name) if name == "value" => {
+                self.pos += 1;
+                Ok(PredicateExpr::Value)
+            }
+            Token::Name(name) if name == "len" || name == "abs" => {
+                self.pos += 1;
+                self.expect("(")?;
+                self.expect("value")?;
+                self.expect(")")?;
+                Ok(PredicateExpr::Property(name))
+            }
+            Token::Name(name) => Err(format!(
+                "Unknown name '{}' at position {} in predicate '{}'; the checked value is 'value'",
+                name, position, self.source
+            )),
+            _ => Err(self.unexpected("'value', an integer, 'len(value)' or 'abs(value)'")),
+        }
+    }
+
+    fn compare_op(token: &Token) -> Option<CompareOp> {
+        match token {
+            Token::Symbol("==") => Some(CompareOp::Eq),
+            Token::Symbol("!=") => Some(CompareOp::Ne),
+            Token::Symbol("<") => Some(CompareOp::Lt),
+            Token::Symbol("<=") => Some(CompareOp::Le),
+            Token::Symbol(">") => Some(CompareOp::Gt),
+            Token::Symbol(">=") => Some(CompareOp::Ge),
+            _ => None,
+        }
+    }
+
+    fn is_arithmetic(token: &Token) -> bool {
+        matches!(token, Token::Symbol("+" | "-" | "*" | "/" | "%"))
+    }
+}
+
 impl Default for RefinementAnalyzer {
     fn default() -> Self {
         Self::new()
     }
 }
 
+/// Integers a refined `int` may hold, both ends included; `i64::MIN` and
+/// `i64::MAX` stand for an unbounded end. Empty when `min > max`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Interval {
+    pub min: i64,
+    pub max: i64,
+}
+
+impl Interval {
+    pub const ALL: Interval = Interval { min: i64::MIN, max: i64::MAX };
+
+    pub fn exact(n: i64) -> Self {
+        Self { min: n, max: n }
+    }
+
+    /// Values satisfying `predicate`, when it only bounds the value itself
+    /// by constants
+    pub fn of(predicate: &Predicate) -> Option<Self> {
+        match predicate {
+            Predicate::True => Some(Self::ALL),
+            Predicate::Compare { op, left: PredicateExpr::Value, right: PredicateExpr::Literal(n) } => {
+                Some(Self::ALL.narrow(*op, *n))
+            }
+            Predicate::Compare { op, left: PredicateExpr::Literal(n), right: PredicateExpr::Value } => {
+                Some(Self::ALL.narrow(op.flipped(), *n))
+            }
+            Predicate::And(preds) => preds.iter().try_fold(Self::ALL, |acc, pred| Some(acc.intersect(Self::of(pred)?))),
+            _ => None,
+        }
+    }
+
+    /// Values of this interval for which `value op n` holds; `!=` only
+    /// removes an end
+    pub fn narrow(self, op: CompareOp, n: i64) -> Self {
+        match op {
+            CompareOp::Lt => self.intersect(Self { min: i64::MIN, max: n.saturating_sub(1) }),
+            CompareOp::Le => self.intersect(Self { min: i64::MIN, max: n }),
+            CompareOp::Gt => self.intersect(Self { min: n.saturating_add(1), max: i64::MAX }),
+            CompareOp::Ge => self.intersect(Self { min: n, max: i64::MAX }),
+            CompareOp::Eq => self.intersect(Self::exact(n)),
+            CompareOp::Ne if self.min == n => Self { min: n.saturating_add(1), ..self },
+            CompareOp::Ne if self.max == n => Self { max: n.saturating_sub(1), ..self },
+            CompareOp::Ne => self,
+        }
+    }
+
+    pub fn intersect(self, other: Self) -> Self {
+        Self { min: self.min.max(other.min), max: self.max.min(other.max) }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min > self.max
+    }
+
+    /// Whether every value of `other` is in this interval
+    pub fn contains(&self, other: &Self) -> bool {
+        other.is_empty() || (self.min <= other.min && other.max <= self.max)
+    }
+
+    /// The refined `int` holding this interval: plain `int` when unbounded,
+    /// and `Never` when empty
+    pub fn to_type(self) -> Type {
+        if self.is_empty() {
+            return Type::Never;
+        }
+        let bound = |op, n| Predicate::Compare { op, left: PredicateExpr::Value, right: PredicateExpr::Literal(n) };
+        let lower = (self.min != i64::MIN).then(|| bound(CompareOp::Ge, self.min));
+        let upper = (self.max != i64::MAX).then(|| bound(CompareOp::Le, self.max));
+        match (lower, upper) {
+            (Some(lower), Some(upper)) => Type::Int.refine(lower.and(upper)),
+            (Some(bound), None) | (None, Some(bound)) => Type::Int.refine(bound),
+            (None, None) => Type::Int,
+        }
+    }
+}
+
+impl Add for Interval {
+    type Output = Interval;
+
+    fn add(self, other: Self) -> Self {
+        let end = |a: i64, b: i64, unbounded: i64| match a == unbounded || b == unbounded {
+            true => unbounded,
+            false => a.saturating_add(b),
+        };
+        Self { min: end(self.min, other.min, i64::MIN), max: end(self.max, other.max, i64::MAX) }
+    }
+}
+
+impl Sub for Interval {
+    type Output = Interval;
+
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl Mul for Interval {
+    type Output = Interval;
+
+    fn mul(self, other: Self) -> Self {
+        let product = |a: i64, b: i64| match (a, b) {
+            (0, _) | (_, 0) => 0,
+            (i64::MIN | i64::MAX, _) | (_, i64::MIN | i64::MAX) if (a > 0) == (b > 0) => i64::MAX,
+            (i64::MIN | i64::MAX, _) | (_, i64::MIN | i64::MAX) => i64::MIN,
+            _ => a.saturating_mul(b),
+        };
+        let corners = [
+            product(self.min, other.min),
+            product(self.min, other.max),
+            product(self.max, other.min),
+            product(self.max, other.max),
+        ];
+        Self { min: *corners.iter().min().unwrap(), max: *corners.iter().max().unwrap() }
+    }
+}
+
+impl Neg for Interval {
+    type Output = Interval;
+
+    fn neg(self) -> Self {
+        let negate = |n: i64| match n {
+            i64::MIN => i64::MAX,
+            i64::MAX => i64::MIN,
+            n => -n,
+        };
+        Self { min: negate(self.max), max: negate(self.min) }
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let end = |n: i64| match n {
+            i64::MIN => "-inf".to_string(),
+            i64::MAX => "inf".to_string(),
+            n => n.to_string(),
+        };
+        match self.min == self.max {
+            true => write!(f, "{}", self.min),
+            false => write!(f, "[{}, {}]", end(self.min), end(self.max)),
+        }
+    }
+}
+
 /// Common refinement type constructors
 pub mod refinements {
     use super::*;
@@ -278,6 +653,69 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_predicate_round_trips() {
+        let analyzer = RefinementAnalyzer::new();
+        for source in [
+            "value > 0 and value < 100",
+            "len(value) >= 3",
+            "value % 2 == 0",
+            "not (value < 0 or value > 10)",
+            "(value + 1) * 2 <= abs(value) - -3",
+            "0 <= value < 10",
+            "!value == 1 || true",
+        ] {
+            let pred = analyzer.parse_predicate(source).unwrap();
+            let reparsed = analyzer.parse_predicate(&pred.to_string()).unwrap();
+            assert_eq!(reparsed, pred, "{} printed as {}", source, pred);
+        }
+
+        assert_eq!(
+            analyzer.parse_predicate("value % 2 == 0").unwrap(),
+            Predicate::Compare {
+                op: CompareOp::Eq,
+                left: PredicateExpr::BinOp(Box::new(PredicateExpr::Value), BinOp::Mod, Box::new(PredicateExpr::Literal(2))),
+                right: PredicateExpr::Literal(0),
+            }
+        );
+        assert_eq!(
+            analyzer.parse_predicate("0 <= value <= 10").unwrap(),
+            analyzer.parse_predicate("0 <= value and value <= 10").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_predicate_errors() {
+        let analyzer = RefinementAnalyzer::new();
+        let error = |source: &str| analyzer.parse_predicate(source).unwrap_err();
+
+        assert_eq!(error("value >"), "Expected 'value', an integer, 'len(value)' or 'abs(value)' at position 7, found end of predicate in predicate 'value >'");
+        assert_eq!(error("x > 0"), "Unknown name 'x' at position 0 in predicate 'x > 0'; the checked value is 'value'");
+        assert_eq!(error("value > 0 value"), "Expected 'and', 'or' or end of predicate at position 10, found 'value' in predicate 'value > 0 value'");
+        assert_eq!(error("(value > 0"), "Expected ')' at position 10, found end of predicate in predicate '(value > 0'");
+        assert_eq!(error("len(value > 1"), "Expected ')' at position 10, found '>' in predicate 'len(value > 1'");
+        assert_eq!(error("value ? 1"), "Unexpected character '?' at position 6 in predicate 'value ? 1'");
+    }
+
+    #[test]
+    fn test_validate_parsed_predicates() {
+        let analyzer = RefinementAnalyzer::new();
+        let valid = |source: &str, value: serde_json::Value| {
+            analyzer.validate(&value, &analyzer.parse_predicate(source).unwrap())
+        };
+
+        assert!(valid("value > 0 and value < 100", serde_json::json!(42)));
+        assert!(!valid("value > 0 and value < 100", serde_json::json!(100)));
+        assert!(valid("len(value) >= 3", serde_json::json!("abc")));
+        assert!(valid("len(value) >= 3", serde_json::json!([1, 2, 3, 4])));
+        assert!(!valid("len(value) >= 3", serde_json::json!("ab")));
+        assert!(valid("value % 2 == 0", serde_json::json!(8)));
+        assert!(!valid("value % 2 == 0", serde_json::json!(7)));
+        assert!(valid("not (value < 0 or value > 10)", serde_json::json!(10)));
+        assert!(!valid("not (value < 0 or value > 10)", serde_json::json!(-1)));
+        assert!(valid("abs(value) <= 5", serde_json::json!(-5)));
+    }
+
     #[test]
     fn test_validate_positive() {
         let analyzer = RefinementAnalyzer::new();
@@ -292,6 +730,32 @@ mod tests {
         assert!(!analyzer.validate(&serde_json::json!(0), &pred));
     }
 
+    #[test]
+    fn test_interval_arithmetic() {
+        let bounded = Interval::of(&Predicate::Compare {
+            op: CompareOp::Ge,
+            left: PredicateExpr::Value,
+            right: PredicateExpr::Literal(0),
+        }.and(Predicate::Compare {
+            op: CompareOp::Le,
+            left: PredicateExpr::Value,
+            right: PredicateExpr::Literal(10),
+        })).unwrap();
+        assert_eq!(bounded + Interval::exact(5), Interval { min: 5, max: 15 });
+        assert_eq!(bounded - Interval::exact(5), Interval { min: -5, max: 5 });
+        assert_eq!(bounded * Interval::exact(-2), Interval { min: -20, max: 0 });
+        assert_eq!(bounded.narrow(CompareOp::Lt, 5), Interval { min: 0, max: 4 });
+
+        let positive = match RefinementAnalyzer::positive_int() {
+            Type::Refinement(_, pred) => Interval::of(&pred).unwrap(),
+            other => panic!("Expected Refinement type, got {:?}", other),
+        };
+        assert_eq!(positive, Interval { min: 1, max: i64::MAX });
+        assert_eq!((positive + Interval::exact(1)).to_string(), "[2, inf]");
+        assert_eq!((positive * Interval::exact(-1)).to_string(), "[-inf, -1]");
+        assert!(!positive.contains(&Interval::exact(-1)));
+    }
+
     #[test]
     fn test_bounded_int() {
         let ty = refinements::range(0, 100);
@@ -0,0 +1,620 @@
+//! Scopes and what each name refers to
+//!
+//! Every name in a module, where it's bound and where it's read, resolves
+//! to the scope binding it, by Python's rules: a name bound anywhere in a
+//! function is local to all of it unless declared `global` or `nonlocal`;
+//! class bodies don't enclose the functions and comprehensions inside them;
+//! lambdas and comprehensions are scopes of their own, though a
+//! comprehension's first iterable and a function's defaults, annotations
+//! and decorators are evaluated outside. A name nothing binds resolves to
+//! the module, as globals and builtins do.
+//!
+//! Attributes looked up on a method's receiver (`self.size`, `cls.count`)
+//! resolve to the class defining them or, failing that, to the first of its
+//! bases that does; a class's members are the names its body binds and the
+//! attributes its methods assign through the receiver. Attributes of any
+//! other value are left for callers that know its type.
+
+use crate::compiler::analysis::bindings;
+use crate::compiler::ast::ir::{Alias, ExprKind, NodeRef, Param, StmtKind};
+use crate::compiler::ast::lower_module;
+use rustpython_parser::ast::{self, ExprContext, Mod, Pattern};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+/// Index of a scope; the module is scope 0
+pub type ScopeId = usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeKind {
+    Module,
+    Function,
+    Class,
+    Comprehension,
+}
+
+/// A name as bound in one scope: a variable, parameter, function or class,
+/// or for a class scope also an attribute of its instances
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Symbol {
+    pub scope: ScopeId,
+    pub name: String,
+}
+
+/// One appearance of a name in the source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Occurrence {
+    pub name: String,
+    /// Bytes of the name itself
+    pub range: Range<usize>,
+    /// Innermost scope it appears in
+    pub scope: ScopeId,
+    /// Whether it binds the name rather than reading it
+    pub binding: bool,
+    /// For attributes, the bytes of the value they're looked up on
+    pub receiver: Option<Range<usize>>,
+    /// What it refers to; `None` for attributes of values whose class
+    /// isn't known
+    pub symbol: Option<Symbol>,
+}
+
+#[derive(Debug, Clone)]
+struct Scope {
+    kind: ScopeKind,
+    parent: Option<ScopeId>,
+    /// Bytes of the code that runs in the scope
+    range: Range<usize>,
+    /// Bytes inside `range` that run in the parent: a comprehension's first
+    /// iterable
+    outside: Option<Range<usize>>,
+    bound: HashSet<String>,
+    globals: HashSet<String>,
+    nonlocals: HashSet<String>,
+    /// A method's first parameter, through which it reaches its class
+    receiver: Option<String>,
+    /// Occurrence of a function's or class's name where it's defined
+    definition: Option<usize>,
+    /// Bytes of a class's bases
+    bases: Vec<Range<usize>>,
+}
+
+impl Scope {
+    fn new(kind: ScopeKind, parent: Option<ScopeId>, range: Range<usize>) -> Self {
+        Self {
+            kind,
+            parent,
+            range,
+            outside: None,
+            bound: HashSet::new(),
+            globals: HashSet::new(),
+            nonlocals: HashSet::new(),
+            receiver: None,
+            definition: None,
+            bases: Vec::new(),
+        }
+    }
+
+    fn contains(&self, offset: usize) -> bool {
+        self.range.contains(&offset) && !self.outside.as_ref().is_some_and(|outside| outside.contains(&offset))
+    }
+}
+
+/// The scopes of a module and every name in it, resolved
+#[derive(Debug, Clone)]
+pub struct Scopes {
+    scopes: Vec<Scope>,
+    occurrences: Vec<Occurrence>,
+}
+
+impl Scopes {
+    /// Resolve the names of `module`, parsed from `source`. The source
+    /// locates names spans don't, such as a definition's; without it (`""`)
+    /// those are still bound but have no occurrence
+    pub fn resolve(module: &Mod, source: &str) -> Self {
+        let lowered = lower_module(module, None);
+        let mut builder = Builder {
+            source,
+            scopes: vec![Scope::new(ScopeKind::Module, None, 0..source.len().max(1))],
+            occurrences: Vec::new(),
+            open: vec![0],
+            walrus: HashSet::new(),
+        };
+        lowered.walk(&mut |node| builder.visit(node));
+
+        let mut scopes = Self { scopes: builder.scopes, occurrences: builder.occurrences };
+        scopes.resolve_names();
+        scopes.resolve_attributes();
+        scopes
+    }
+
+    pub fn occurrences(&self) -> &[Occurrence] {
+        &self.occurrences
+    }
+
+    /// The name at byte `offset`, or ending right before it
+    pub fn occurrence_at(&self, offset: usize) -> Option<&Occurrence> {
+        self.occurrences.iter().find(|occurrence| occurrence.range.start <= offset && offset <= occurrence.range.end)
+    }
+
+    /// Occurrences referring to `symbol`, in source order
+    pub fn references(&self, symbol: &Symbol) -> Vec<&Occurrence> {
+        let mut references: Vec<&Occurrence> = self.occurrences.iter()
+            .filter(|occurrence| occurrence.symbol.as_ref() == Some(symbol))
+            .collect();
+        references.sort_by_key(|occurrence| occurrence.range.start);
+        references
+    }
+
+    /// What `name` refers to when read in `scope`
+    pub fn lookup(&self, scope: ScopeId, name: &str) -> Symbol {
+        self.lookup_from(scope, name).unwrap_or_else(|| Symbol { scope: 0, name: name.to_string() })
+    }
+
+    /// Whether anything in the module binds `symbol`; a module symbol that
+    /// nothing binds is a builtin or undefined
+    pub fn binds(&self, symbol: &Symbol) -> bool {
+        self.occurrences.iter().any(|occurrence| occurrence.binding && occurrence.symbol.as_ref() == Some(symbol))
+    }
+
+    /// Whether `name` read in `scope` refers to something the module binds;
+    /// when it doesn't, it's a builtin or undefined
+    pub fn resolves(&self, scope: ScopeId, name: &str) -> bool {
+        let symbol = self.lookup(scope, name);
+        self.scopes[symbol.scope].bound.contains(name) || self.binds(&symbol)
+    }
+
+    /// Names a read in `scope` can see: those bound in it and in the scopes
+    /// enclosing it, class bodies aside, and globals bound from functions
+    pub fn visible_names(&self, scope: ScopeId) -> HashSet<String> {
+        let mut names = self.scopes[scope].bound.clone();
+        let mut enclosing = self.scopes[scope].parent;
+        while let Some(id) = enclosing {
+            if self.scopes[id].kind != ScopeKind::Class {
+                names.extend(self.scopes[id].bound.iter().cloned());
+            }
+            enclosing = self.scopes[id].parent;
+        }
+        names.extend(self.occurrences.iter()
+            .filter(|occurrence| occurrence.binding && occurrence.receiver.is_none())
+            .filter(|occurrence| occurrence.symbol.as_ref().is_some_and(|symbol| symbol.scope == 0))
+            .map(|occurrence| occurrence.name.clone()));
+        names
+    }
+
+    /// Whether `symbol` names a function or class its scope defines
+    pub fn defines(&self, symbol: &Symbol) -> bool {
+        self.scopes.iter()
+            .filter(|scope| scope.parent == Some(symbol.scope))
+            .filter_map(|scope| scope.definition)
+            .any(|definition| self.occurrences[definition].name == symbol.name)
+    }
+
+    pub fn kind(&self, scope: ScopeId) -> ScopeKind {
+        self.scopes[scope].kind
+    }
+
+    /// Whether `symbol` is a method's receiver, its `self` or `cls`
+    pub fn is_receiver(&self, symbol: &Symbol) -> bool {
+        self.scopes[symbol.scope].receiver.as_deref() == Some(symbol.name.as_str())
+    }
+
+    /// The scope of the class named `name`, preferring one at the top level
+    pub fn class_named(&self, name: &str) -> Option<ScopeId> {
+        let classes = || (0..self.scopes.len()).filter(|&id| self.scopes[id].kind == ScopeKind::Class);
+        let named = |&id: &ScopeId| self.definition_name(id) == Some(name);
+        classes().filter(named).find(|&id| self.scopes[id].parent == Some(0)).or_else(|| classes().find(named))
+    }
+
+    /// The member `name` of instances of `class`: declared by it or by the
+    /// first of its bases that does, and by `class` itself when none does
+    pub fn member(&self, class: ScopeId, name: &str) -> Symbol {
+        self.member_owner(class, name, &mut Vec::new())
+            .map(|owner| Symbol { scope: owner, name: name.to_string() })
+            .unwrap_or_else(|| Symbol { scope: class, name: name.to_string() })
+    }
+
+    /// Whether renaming `symbol` to `name` would change what some name
+    /// means: `name` is already bound where `symbol` is used, or is
+    /// already a member of its class
+    pub fn conflicts(&self, symbol: &Symbol, name: &str) -> bool {
+        let member = self.scopes[symbol.scope].kind == ScopeKind::Class
+            && self.member_owner(symbol.scope, name, &mut Vec::new()).is_some();
+        member || self.references(symbol).into_iter().filter(|occurrence| occurrence.receiver.is_none()).any(|occurrence| {
+            let existing = self.lookup(occurrence.scope, name);
+            self.binds(&existing)
+        })
+    }
+
+    fn definition_name(&self, scope: ScopeId) -> Option<&str> {
+        self.scopes[scope].definition.map(|index| self.occurrences[index].name.as_str())
+    }
+
+    /// Scope binding `name` as read from `scope`
+    fn lookup_from(&self, scope: ScopeId, name: &str) -> Option<Symbol> {
+        let current = &self.scopes[scope];
+        if current.globals.contains(name) {
+            return Some(Symbol { scope: 0, name: name.to_string() });
+        }
+        if current.nonlocals.contains(name) {
+            return current.parent.and_then(|parent| self.lookup_enclosing(parent, name));
+        }
+        if current.bound.contains(name) {
+            return Some(Symbol { scope, name: name.to_string() });
+        }
+        current.parent.and_then(|parent| self.lookup_enclosing(parent, name))
+    }
+
+    /// Like [`Self::lookup_from`] from a scope enclosing another: class
+    /// bodies don't enclose what's inside them
+    fn lookup_enclosing(&self, scope: ScopeId, name: &str) -> Option<Symbol> {
+        match self.scopes[scope].kind {
+            ScopeKind::Class => self.scopes[scope].parent.and_then(|parent| self.lookup_enclosing(parent, name)),
+            _ => self.lookup_from(scope, name),
+        }
+    }
+
+    fn resolve_names(&mut self) {
+        for i in 0..self.occurrences.len() {
+            if self.occurrences[i].receiver.is_none() {
+                let occurrence = &self.occurrences[i];
+                self.occurrences[i].symbol = Some(self.lookup(occurrence.scope, &occurrence.name));
+            }
+        }
+    }
+
+    /// Resolve attributes of method receivers to the class of the method
+    fn resolve_attributes(&mut self) {
+        let names: HashMap<usize, usize> = self.occurrences.iter().enumerate()
+            .filter(|(_, occurrence)| occurrence.receiver.is_none())
+            .map(|(i, occurrence)| (occurrence.range.start, i))
+            .collect();
+        // Classes whose receiver each attribute is looked up on
+        let mut classes = Vec::new();
+        for (i, occurrence) in self.occurrences.iter().enumerate() {
+            let Some(receiver) = &occurrence.receiver else { continue };
+            let Some(&name) = names.get(&receiver.start).filter(|&&name| self.occurrences[name].range == *receiver) else { continue };
+            let Some(symbol) = &self.occurrences[name].symbol else { continue };
+            let method = &self.scopes[symbol.scope];
+            if method.receiver.as_deref() == Some(symbol.name.as_str()) {
+                classes.extend(method.parent.map(|class| (i, class)));
+            }
+        }
+        // Members first, so those assigned through the receiver count
+        for &(i, class) in &classes {
+            if self.occurrences[i].binding {
+                self.occurrences[i].symbol = Some(Symbol { scope: class, name: self.occurrences[i].name.clone() });
+            }
+        }
+        for (i, class) in classes {
+            let symbol = self.member(class, &self.occurrences[i].name);
+            self.occurrences[i].symbol = Some(symbol);
+        }
+    }
+
+    fn member_owner(&self, class: ScopeId, name: &str, seen: &mut Vec<ScopeId>) -> Option<ScopeId> {
+        if seen.contains(&class) {
+            return None;
+        }
+        seen.push(class);
+        let scope = &self.scopes[class];
+        let declared = scope.bound.contains(name) || self.occurrences.iter().any(|occurrence| {
+            occurrence.binding && occurrence.receiver.is_some() && occurrence.name == name
+                && occurrence.symbol.as_ref().is_some_and(|symbol| symbol.scope == class)
+        });
+        if declared {
+            return Some(class);
+        }
+        scope.bases.iter().find_map(|base| {
+            let occurrence = self.occurrences.iter().find(|occurrence| occurrence.range == *base)?;
+            let symbol = occurrence.symbol.as_ref()?;
+            let base = (0..self.scopes.len()).find(|&id| {
+                self.scopes[id].kind == ScopeKind::Class
+                    && self.scopes[id].definition.and_then(|index| self.occurrences[index].symbol.as_ref()) == Some(symbol)
+            })?;
+            self.member_owner(base, name, seen)
+        })
+    }
+}
+
+/// Collects scopes and occurrences in one pass over the module
+struct Builder<'s> {
+    source: &'s str,
+    scopes: Vec<Scope>,
+    occurrences: Vec<Occurrence>,
+    /// Scopes that may still contain what's visited next, innermost last
+    open: Vec<ScopeId>,
+    /// Starts of walrus targets in comprehensions, bound outside them
+    walrus: HashSet<usize>,
+}
+
+impl Builder<'_> {
+    fn visit(&mut self, node: NodeRef) {
+        let start = match node {
+            NodeRef::Stmt(stmt) => stmt.span.range.start,
+            NodeRef::Expr(expr) => expr.span.range.start,
+        };
+        while self.open.len() > 1 && self.scopes[*self.open.last().unwrap()].range.end <= start {
+            self.open.pop();
+        }
+        let scope = *self.open.iter().rev().find(|&&id| self.scopes[id].contains(start)).unwrap_or(&0);
+
+        match node {
+            NodeRef::Stmt(stmt) => match &stmt.kind {
+                StmtKind::FunctionDef(func) => {
+                    let after = func.decorators.last().map_or(start, |decorator| decorator.span.range.end);
+                    let definition = self.name_after(after, "def").and_then(|def| self.name_after(def + 3, &func.name));
+                    let definition = self.bind_at(scope, &func.name, definition);
+                    self.scopes[scope].bound.extend(func.type_params.iter().map(|name| name.to_string()));
+                    let Some(first) = func.body.first() else { return };
+                    let id = self.open_scope(ScopeKind::Function, scope, first.span.range.start..stmt.span.range.end);
+                    self.scopes[id].definition = definition;
+                    let decorated = |name: &str| func.decorators.iter().any(|d| matches!(&d.kind, ExprKind::Name(found) if &**found == name));
+                    if self.scopes[scope].kind == ScopeKind::Class && !decorated("staticmethod") {
+                        self.scopes[id].receiver = func.params.first().map(|param| param.name.to_string());
+                    }
+                    self.bind_params(id, &func.params);
+                }
+                StmtKind::ClassDef(class) => {
+                    let after = class.decorators.last().map_or(start, |decorator| decorator.span.range.end);
+                    let definition = self.name_after(after, "class").and_then(|at| self.name_after(at + 5, &class.name));
+                    let definition = self.bind_at(scope, &class.name, definition);
+                    self.scopes[scope].bound.extend(class.type_params.iter().map(|name| name.to_string()));
+                    let Some(first) = class.body.first() else { return };
+                    let id = self.open_scope(ScopeKind::Class, scope, first.span.range.start..stmt.span.range.end);
+                    self.scopes[id].definition = definition;
+                    self.scopes[id].bases = class.bases.iter().map(|base| base.span.range.clone()).collect();
+                }
+                StmtKind::Import(aliases) => {
+                    for alias in aliases {
+                        // `import a.b` binds `a`
+                        let (name, at) = match &alias.asname {
+                            Some(asname) => (asname.to_string(), alias.span.range.end - asname.len()),
+                            None => (alias.name.split('.').next().unwrap_or_default().to_string(), alias.span.range.start),
+                        };
+                        self.bind(scope, &name, at);
+                    }
+                }
+                StmtKind::ImportFrom { names, .. } => {
+                    for alias in names.iter().filter(|alias| &*alias.name != "*") {
+                        let (name, at) = alias_binding(alias);
+                        self.bind(scope, &name, at);
+                    }
+                }
+                StmtKind::Global(names) | StmtKind::Nonlocal(names) => {
+                    let global = matches!(stmt.kind, StmtKind::Global(_));
+                    let mut after = start;
+                    for name in names {
+                        let declared = if global { &mut self.scopes[scope].globals } else { &mut self.scopes[scope].nonlocals };
+                        declared.insert(name.to_string());
+                        if let Some(at) = self.name_after(after, name) {
+                            self.occur(scope, name, at, false);
+                            after = at + name.len();
+                        }
+                    }
+                }
+                StmtKind::Try { handlers, .. } => {
+                    for handler in handlers {
+                        let Some(name) = &handler.name else { continue };
+                        let after = handler.type_.as_ref().map_or(handler.span.range.start, |type_| type_.span.range.end);
+                        let at = self.name_after(after, name);
+                        self.bind_at(scope, name, at);
+                    }
+                }
+                StmtKind::TypeAlias { type_params, .. } => {
+                    self.scopes[scope].bound.extend(type_params.iter().map(|name| name.to_string()));
+                }
+                StmtKind::Match { cases, .. } => {
+                    for case in cases {
+                        self.bind_pattern(scope, case.pattern);
+                    }
+                }
+                _ => {}
+            },
+            NodeRef::Expr(expr) => match &expr.kind {
+                ExprKind::Name(_) if self.walrus.contains(&start) => {}
+                ExprKind::Name(name) => {
+                    let binding = matches!(expr.node, ast::Expr::Name(node) if node.ctx != ExprContext::Load);
+                    self.occur(scope, name, start, binding);
+                    if binding {
+                        self.scopes[scope].bound.insert(name.to_string());
+                    }
+                }
+                ExprKind::NamedExpr { target, .. } => {
+                    // Binds outside any comprehensions it's in
+                    let mut outer = scope;
+                    while self.scopes[outer].kind == ScopeKind::Comprehension {
+                        outer = self.scopes[outer].parent.unwrap_or(0);
+                    }
+                    if let (ExprKind::Name(name), true) = (&target.kind, outer != scope) {
+                        self.bind(outer, name, target.span.range.start);
+                        self.walrus.insert(target.span.range.start);
+                    }
+                }
+                ExprKind::Attribute { value, attr } => {
+                    let binding = matches!(expr.node, ast::Expr::Attribute(node) if node.ctx != ExprContext::Load);
+                    let end = expr.span.range.end;
+                    self.occurrences.push(Occurrence {
+                        name: attr.to_string(),
+                        range: end - attr.len()..end,
+                        scope,
+                        binding,
+                        receiver: Some(value.span.range.clone()),
+                        symbol: None,
+                    });
+                }
+                ExprKind::Lambda { params, body } => {
+                    let id = self.open_scope(ScopeKind::Function, scope, body.span.range.clone());
+                    self.bind_params(id, params);
+                }
+                ExprKind::Comprehension { generators, .. } => {
+                    let id = self.open_scope(ScopeKind::Comprehension, scope, expr.span.range.clone());
+                    self.scopes[id].outside = generators.first().map(|generator| generator.iter.span.range.clone());
+                }
+                _ => {}
+            },
+        }
+    }
+
+    fn open_scope(&mut self, kind: ScopeKind, parent: ScopeId, range: Range<usize>) -> ScopeId {
+        self.scopes.push(Scope::new(kind, Some(parent), range));
+        let id = self.scopes.len() - 1;
+        self.open.push(id);
+        id
+    }
+
+    /// Record `name` at byte `at` binding it in `scope`; the index of the
+    /// occurrence
+    fn bind(&mut self, scope: ScopeId, name: &str, at: usize) -> usize {
+        self.scopes[scope].bound.insert(name.to_string());
+        self.occur(scope, name, at, true)
+    }
+
+    /// Like [`Self::bind`] when `at` is known; otherwise `name` is bound in
+    /// `scope` without an occurrence
+    fn bind_at(&mut self, scope: ScopeId, name: &str, at: Option<usize>) -> Option<usize> {
+        match at {
+            Some(at) => Some(self.bind(scope, name, at)),
+            None => {
+                self.scopes[scope].bound.insert(name.to_string());
+                None
+            }
+        }
+    }
+
+    fn occur(&mut self, scope: ScopeId, name: &str, at: usize, binding: bool) -> usize {
+        self.occurrences.push(Occurrence {
+            name: name.to_string(),
+            range: at..at + name.len(),
+            scope,
+            binding,
+            receiver: None,
+            symbol: None,
+        });
+        self.occurrences.len() - 1
+    }
+
+    fn bind_params(&mut self, scope: ScopeId, params: &[Param]) {
+        for param in params {
+            self.bind(scope, &param.name, param.span.range.start);
+        }
+    }
+
+    /// Names a match pattern captures
+    fn bind_pattern(&mut self, scope: ScopeId, pattern: &Pattern) {
+        for (name, range) in bindings::pattern_sites(pattern) {
+            let at = if range.len() == name.len() { Some(range.start) } else { self.name_after(range.start, &name) };
+            self.bind_at(scope, &name, at);
+        }
+    }
+
+    /// Byte offset of the first whole word `word` at or after byte `from`
+    fn name_after(&self, from: usize, word: &str) -> Option<usize> {
+        let is_ident = |ch: char| ch.is_alphanumeric() || ch == '_';
+        let text = self.source.get(from..)?;
+        text.match_indices(word)
+            .find(|(at, _)| {
+                let before = text[..*at].chars().next_back().is_some_and(is_ident);
+                let after = text[at + word.len()..].chars().next().is_some_and(is_ident);
+                !before && !after
+            })
+            .map(|(at, _)| from + at)
+    }
+}
+
+/// Name a `from` import binds and the byte it starts at
+fn alias_binding(alias: &Alias) -> (String, usize) {
+    match &alias.asname {
+        Some(asname) => (asname.to_string(), alias.span.range.end - asname.len()),
+        None => (alias.name.to_string(), alias.span.range.start),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_module;
+
+    /// Lines and columns of the occurrences referring to the name at
+    /// `line`:`col` of `source`
+    fn references(source: &str, line: usize, col: usize) -> Vec<(usize, usize)> {
+        let scopes = Scopes::resolve(&parse_module(source).unwrap(), source);
+        let offset = source.split_inclusive('\n').take(line).map(str::len).sum::<usize>() + col;
+        let symbol = scopes.occurrence_at(offset).unwrap().symbol.clone().unwrap();
+        scopes.references(&symbol).into_iter()
+            .map(|occurrence| {
+                let before = &source[..occurrence.range.start];
+                (before.matches('\n').count(), before.len() - before.rfind('\n').map_or(0, |newline| newline + 1))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_function_locals_stay_in_their_function() {
+        let source = "\
+x = 1
+def scale(x, factor=x):
+    y = x * factor
+    return [x for x in range(y)]
+def shift(y):
+    return x + y
+";
+        // The parameter, not the global its default reads
+        assert_eq!(references(source, 1, 10), vec![(1, 10), (2, 8)]);
+        assert_eq!(references(source, 0, 0), vec![(0, 0), (1, 20), (5, 11)]);
+        // The comprehension's own `x`
+        assert_eq!(references(source, 3, 12), vec![(3, 12), (3, 18)]);
+    }
+
+    #[test]
+    fn test_global_and_nonlocal_declarations() {
+        let source = "\
+count = 0
+def bump():
+    global count
+    count += 1
+def outer():
+    total = 0
+    def inner():
+        nonlocal total
+        total = 2
+    return total
+";
+        assert_eq!(references(source, 3, 4), vec![(0, 0), (2, 11), (3, 4)]);
+        assert_eq!(references(source, 8, 8), vec![(5, 4), (7, 17), (8, 8), (9, 11)]);
+    }
+
+    #[test]
+    fn test_class_bodies_and_members() {
+        let source = "\
+size = 3
+class Base:
+    size = 1
+    def grow(self):
+        self.width = size
+        return self.size
+class Box(Base):
+    def area(self):
+        return self.size * self.width
+";
+        // Methods don't see the class body's names, but reach them through `self`
+        assert_eq!(references(source, 4, 21), vec![(0, 0), (4, 21)]);
+        assert_eq!(references(source, 2, 4), vec![(2, 4), (5, 20), (8, 20)]);
+        assert_eq!(references(source, 8, 32), vec![(4, 13), (8, 32)]);
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let source = "\
+limit = 10
+def clamp(value, low):
+    return min(value, limit)
+";
+        let scopes = Scopes::resolve(&parse_module(source).unwrap(), source);
+        let value = scopes.occurrence_at(source.find("value").unwrap()).unwrap().symbol.clone().unwrap();
+        assert!(scopes.conflicts(&value, "low"));
+        assert!(scopes.conflicts(&value, "limit"));
+        assert!(!scopes.conflicts(&value, "number"));
+        let min = scopes.lookup(0, "min");
+        assert!(!scopes.binds(&min));
+    }
+}
@@ -0,0 +1,107 @@
+//! `# type: ignore` comments
+//!
+//! A trailing `# type: ignore` silences every diagnostic reported on its
+//! line; `# type: ignore[TYP001, S002]` only those with one of the listed
+//! codes. The AST drops comments, so they are found by lexing the raw source.
+
+use rustpython_parser::lexer::lex;
+use rustpython_parser::{Mode, Tok};
+use std::collections::BTreeMap;
+use std::ops::Range;
+use crate::compiler::ast::LineIndex;
+
+/// One `# type: ignore` comment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suppression {
+    /// 1-based line the comment is on
+    pub line: usize,
+    /// Codes it is limited to; empty silences everything
+    pub codes: Vec<String>,
+    /// Byte range of the comment
+    pub range: Range<usize>,
+    /// What to delete to remove the comment: it and the blanks before it
+    pub removal: Range<usize>,
+}
+
+impl Suppression {
+    /// Does this comment silence a diagnostic with `code` (`None` when it has none)?
+    pub fn covers(&self, code: Option<&str>) -> bool {
+        self.codes.is_empty() || code.is_some_and(|code| self.codes.iter().any(|c| c == code))
+    }
+}
+
+/// The `# type: ignore` comments of a module, by line
+#[derive(Debug, Clone, Default)]
+pub struct Suppressions {
+    by_line: BTreeMap<usize, Suppression>,
+}
+
+impl Suppressions {
+    /// Find the comments in `source`; lexing stops at the first invalid token
+    pub fn scan(source: &str) -> Self {
+        let index = LineIndex::new(source);
+        let by_line = lex(source, Mode::Module)
+            .map_while(Result::ok)
+            .filter_map(|(tok, range)| match tok {
+                Tok::Comment(text) => {
+                    let codes = parse_ignore(&text)?;
+                    let range = range.start().to_usize()..range.end().to_usize();
+                    let (line, _) = index.offset_to_position(range.start);
+                    let blanks = source[..range.start].len() - source[..range.start].trim_end_matches([' ', '\t']).len();
+                    let removal = range.start - blanks..range.end;
+                    Some((line, Suppression { line, codes, range, removal }))
+                }
+                _ => None,
+            })
+            .collect();
+        Self { by_line }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_line.is_empty()
+    }
+
+    /// The comment silencing a diagnostic with `code` on `line`, if any
+    pub fn matching(&self, line: usize, code: Option<&str>) -> Option<&Suppression> {
+        self.by_line.get(&line).filter(|suppression| suppression.covers(code))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Suppression> {
+        self.by_line.values()
+    }
+}
+
+/// Codes of a `# type: ignore[...]` comment (empty for a bare one), or
+/// `None` for any other comment
+fn parse_ignore(comment: &str) -> Option<Vec<String>> {
+    let rest = comment.trim_start_matches('#').trim_start().strip_prefix("type:")?;
+    let rest = rest.trim_start().strip_prefix("ignore")?;
+    // `# type: ignored` is prose, `# type: ignore # reason` is not
+    if rest.starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+    let Some(list) = rest.strip_prefix('[') else { return Some(Vec::new()) };
+    let (list, _) = list.split_once(']')?;
+    Some(list.split(',').map(str::trim).filter(|code| !code.is_empty()).map(String::from).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ignore() {
+        assert_eq!(parse_ignore("# type: ignore"), Some(vec![]));
+        assert_eq!(parse_ignore("#type:ignore  # flaky stub"), Some(vec![]));
+        assert_eq!(parse_ignore("# type: ignore[C001, S002]"), Some(vec!["C001".to_string(), "S002".to_string()]));
+        assert_eq!(parse_ignore("# type: ignored on purpose"), None);
+        assert_eq!(parse_ignore("# noqa"), None);
+    }
+
+    #[test]
+    fn test_comments_inside_strings_are_not_suppressions() {
+        let suppressions = Suppressions::scan("x = '# type: ignore'\ny = 1  # type: ignore\n");
+        let lines: Vec<usize> = suppressions.iter().map(|s| s.line).collect();
+        assert_eq!(lines, vec![2]);
+    }
+}
@@ -0,0 +1,252 @@
+//! Undefined names
+//!
+//! A name is undefined when it isn't a builtin and nothing binds it in the
+//! scope it's read in or the scopes enclosing it, by Python's rules (see
+//! `scopes`): a name bound only in a sibling function, or a class attribute
+//! read bare inside a method, is undefined. There's no flow analysis, so a
+//! name read before the statement binding it still counts as defined.
+//! Modules with a star import, or that bind names through `globals()`,
+//! `locals()`, `vars()` or `exec`, report nothing.
+
+use crate::compiler::analysis::scopes::Scopes;
+use crate::compiler::ast::ir::{ExprKind, NodeRef, StmtKind};
+use crate::compiler::ast::lower_module;
+use crate::compiler::errors::find_similar_names;
+use rustpython_parser::ast::{self, ExprContext, Mod};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+/// Names every module can use without binding them
+pub const BUILTINS: &[&str] = &[
+    "abs", "aiter", "all", "anext", "any", "ascii", "bin", "bool", "breakpoint", "bytearray", "bytes",
+    "callable", "chr", "classmethod", "compile", "complex", "copyright", "credits", "delattr", "dict", "dir",
+    "divmod", "enumerate", "eval", "exec", "exit", "filter", "float", "format", "frozenset", "getattr",
+    "globals", "hasattr", "hash", "help", "hex", "id", "input", "int", "isinstance", "issubclass", "iter",
+    "len", "license", "list", "locals", "map", "max", "memoryview", "min", "next", "object", "oct", "open",
+    "ord", "pow", "print", "property", "quit", "range", "repr", "reversed", "round", "set", "setattr",
+    "slice", "sorted", "staticmethod", "str", "sum", "super", "tuple", "type", "vars", "zip",
+    "True", "False", "None", "Ellipsis", "NotImplemented",
+    "__import__", "__build_class__", "__debug__", "__doc__", "__name__", "__qualname__", "__module__",
+    "__package__", "__loader__", "__spec__", "__file__", "__path__", "__builtins__", "__annotations__",
+    "__dict__", "__class__",
+    "BaseException", "BaseExceptionGroup", "Exception", "ExceptionGroup", "ArithmeticError",
+    "AssertionError", "AttributeError", "BlockingIOError", "BrokenPipeError", "BufferError", "BytesWarning",
+    "ChildProcessError", "ConnectionAbortedError", "ConnectionError", "ConnectionRefusedError",
+    "ConnectionResetError", "DeprecationWarning", "EOFError", "EncodingWarning", "EnvironmentError",
+    "FileExistsError", "FileNotFoundError", "FloatingPointError", "FutureWarning", "GeneratorExit",
+    "IOError", "ImportError", "ImportWarning", "IndentationError", "IndexError", "InterruptedError",
+    "IsADirectoryError", "KeyError", "KeyboardInterrupt", "LookupError", "MemoryError",
+    "ModuleNotFoundError", "NameError", "NotADirectoryError", "NotImplementedError", "OSError",
+    "OverflowError", "PendingDeprecationWarning", "PermissionError", "ProcessLookupError",
+    "RecursionError", "ReferenceError", "ResourceWarning", "RuntimeError", "RuntimeWarning",
+    "StopAsyncIteration", "StopIteration", "SyntaxError", "SyntaxWarning", "SystemError", "SystemExit",
+    "TabError", "TimeoutError", "TypeError", "UnboundLocalError", "UnicodeDecodeError",
+    "UnicodeEncodeError", "UnicodeError", "UnicodeTranslateError", "UnicodeWarning", "UserWarning",
+    "ValueError", "Warning", "WindowsError", "ZeroDivisionError",
+];
+
+/// Typthon's own types and decorators, which the checker knows by name
+/// without an import
+pub const TYPTHON_NAMES: &[&str] = &[
+    "Bounded", "Conditional", "EffectType", "RecursiveType", "RefinementType", "Positive", "Negative", "NonEmpty",
+    "effects", "pure",
+];
+
+/// Calls that can bind names no statement shows
+const DYNAMIC_BINDERS: &[&str] = &["globals", "locals", "vars", "exec"];
+
+/// A read of a name nothing binds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UndefinedName {
+    pub name: String,
+    pub range: Range<usize>,
+    /// Whether it's the callee of a call
+    pub called: bool,
+    /// Names visible where it's read, or builtins, it's likely a
+    /// misspelling of, closest first
+    pub suggestions: Vec<String>,
+}
+
+/// Reads of names nothing visible where they're read binds in `module`,
+/// parsed from `source`, in source order
+pub fn undefined_names(module: &Mod, source: &str) -> Vec<UndefinedName> {
+    let lowered = lower_module(module, None);
+    let mut reads: Vec<UndefinedName> = Vec::new();
+    let mut callees: HashSet<usize> = HashSet::new();
+    let mut dynamic = false;
+
+    lowered.walk(&mut |node| match node {
+        NodeRef::Stmt(stmt) => {
+            if let StmtKind::ImportFrom { names, .. } = &stmt.kind {
+                dynamic |= names.iter().any(|alias| &*alias.name == "*");
+            }
+        }
+        NodeRef::Expr(expr) => match &expr.kind {
+            ExprKind::Name(name) => {
+                if matches!(expr.node, ast::Expr::Name(node) if node.ctx == ExprContext::Load) {
+                    reads.push(UndefinedName {
+                        name: name.to_string(),
+                        range: expr.span.range.clone(),
+                        called: callees.contains(&expr.span.range.start),
+                        suggestions: Vec::new(),
+                    });
+                }
+            }
+            ExprKind::Call { func, .. } => {
+                if let ExprKind::Name(name) = &func.kind {
+                    dynamic |= DYNAMIC_BINDERS.contains(&&**name);
+                    callees.insert(func.span.range.start);
+                }
+            }
+            _ => {}
+        },
+    });
+
+    if dynamic {
+        return Vec::new();
+    }
+
+    let scopes = Scopes::resolve(module, source);
+    // Scope of each name read, by where it starts
+    let read_scopes: HashMap<usize, usize> = scopes.occurrences().iter()
+        .filter(|occurrence| !occurrence.binding && occurrence.receiver.is_none())
+        .map(|occurrence| (occurrence.range.start, occurrence.scope))
+        .collect();
+    let scope_of = |read: &UndefinedName| read_scopes.get(&read.range.start).copied().unwrap_or(0);
+    reads.retain(|read| {
+        let name = read.name.as_str();
+        !BUILTINS.contains(&name) && !TYPTHON_NAMES.contains(&name) && !scopes.resolves(scope_of(read), name)
+    });
+
+    let mut candidates: HashMap<usize, Vec<String>> = HashMap::new();
+    for read in &mut reads {
+        let candidates = candidates.entry(scope_of(read)).or_insert_with_key(|&scope| {
+            let mut names: Vec<String> = scopes.visible_names(scope).into_iter()
+                .chain(BUILTINS.iter().map(|name| name.to_string()))
+                .collect();
+            names.sort();
+            names.dedup();
+            names
+        });
+        // A couple of edits turn any short name into most other short names
+        let max_distance = ((read.name.chars().count() + 1) / 3).min(2);
+        read.suggestions = find_similar_names(&read.name, candidates, max_distance);
+        read.suggestions.truncate(3);
+    }
+    reads
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_module;
+
+    fn names(source: &str) -> Vec<(String, bool)> {
+        undefined_names(&parse_module(source).unwrap(), source).into_iter().map(|read| (read.name, read.called)).collect()
+    }
+
+    #[test]
+    fn test_reports_names_nothing_binds() {
+        let source = "\
+import os.path
+from json import dumps as to_json
+
+def area(side: float) -> float:
+    return sqrt(side) * pi
+
+def later() -> None:
+    helper(os.sep, to_json([]))
+
+def helper(*args: object) -> None:
+    print(len(args), math)
+";
+        assert_eq!(names(source), vec![("sqrt".to_string(), true), ("pi".to_string(), false), ("math".to_string(), false)]);
+    }
+
+    #[test]
+    fn test_bindings_visible_where_read_count() {
+        let source = "\
+def outer() -> None:
+    total = 0
+    def inner() -> int:
+        return total + later
+    inner()
+
+later = 1
+
+def setup() -> None:
+    global config
+    config = {}
+
+squares = [n * n for n in range(3)]
+first = lambda items: items[0]
+match squares:
+    case [head, *rest]:
+        print(head, rest, config)
+try:
+    pass
+except ValueError as err:
+    print(err)
+";
+        assert!(names(source).is_empty(), "{:?}", names(source));
+    }
+
+    #[test]
+    fn test_names_bound_in_other_scopes_are_undefined() {
+        let source = "\
+def f() -> None:
+    yy = 1
+
+def g() -> int:
+    return yy
+
+class Box:
+    size = 1
+    def grow(self) -> int:
+        return size
+";
+        assert_eq!(names(source), vec![("yy".to_string(), false), ("size".to_string(), false)]);
+    }
+
+    #[test]
+    fn test_suggests_only_names_visible_where_read() {
+        let source = "\
+def f() -> None:
+    count = 1
+
+def g() -> int:
+    counter = 2
+    return countr
+";
+        let read = undefined_names(&parse_module(source).unwrap(), source).remove(0);
+        assert_eq!(read.suggestions, vec!["counter".to_string()]);
+    }
+
+    #[test]
+    fn test_suggests_bound_names_and_builtins() {
+        let source = "\
+def total(values: list[int]) -> int:
+    return sum(values)
+
+length = 3
+print(totl([1]), lenght, valeus, q, rnage(2))
+";
+        let suggestions: Vec<(String, Vec<String>)> = undefined_names(&parse_module(source).unwrap(), source).into_iter()
+            .map(|read| (read.name, read.suggestions))
+            .collect();
+        assert_eq!(suggestions, vec![
+            ("totl".to_string(), vec!["total".to_string()]),
+            ("lenght".to_string(), vec!["length".to_string()]),
+            // `values` is local to `total`
+            ("valeus".to_string(), Vec::new()),
+            ("q".to_string(), Vec::new()),
+            ("rnage".to_string(), vec!["range".to_string()]),
+        ]);
+    }
+
+    #[test]
+    fn test_star_imports_and_dynamic_bindings_report_nothing() {
+        assert!(names("from math import *\nprint(sqrt(2))\n").is_empty());
+        assert!(names("globals()['x'] = 1\nprint(x)\n").is_empty());
+    }
+}
@@ -0,0 +1,272 @@
+//! Unused imports - names an `import` binds that nothing reads
+//!
+//! A name counts as used when anything in its scope reads it, when a quoted
+//! annotation names it, or, at the top level, when `__all__` lists it. An
+//! import of a name its scope already imported the same way is reported as
+//! well, used or not. Imports the module re-exports on purpose
+//! (`import a as a`, `from b import c as c`) and `__future__` imports are
+//! never reported.
+
+use crate::compiler::analysis::lints::TextEdit;
+use crate::compiler::analysis::scopes::{Scopes, Symbol};
+use crate::compiler::ast::ir::{self, Alias, ExprKind, Literal, NodeRef, StmtKind};
+use crate::compiler::ast::{lower_expr, lower_module};
+use rustpython_parser::ast::{self, ExprContext, Mod};
+use std::collections::HashSet;
+use std::ops::Range;
+
+/// An import binding a name nothing uses, or repeating an earlier one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnusedImport {
+    /// Name the import binds
+    pub name: String,
+    /// Bytes of the imported name, with its `as` clause
+    pub range: Range<usize>,
+    /// Bytes of the whole `import` statement
+    pub statement: Range<usize>,
+    /// Line of the import this one repeats, 1-based
+    pub duplicates: Option<usize>,
+    /// Removes the name from its statement, or the statement when it's the
+    /// only name imported
+    pub removal: TextEdit,
+}
+
+/// Level, module and name of an import, which make two imports the same
+type ImportKey<'m> = (u32, Option<&'m str>, &'m str);
+
+/// One name an import binds
+struct Imported<'m> {
+    name: String,
+    key: ImportKey<'m>,
+    alias: &'m Alias,
+    /// The statement's names
+    aliases: &'m [Alias],
+    statement: Range<usize>,
+}
+
+/// Imports in `module`, parsed from `source`, whose names go unused
+pub fn unused_imports(module: &Mod, source: &str) -> Vec<UnusedImport> {
+    let lowered = lower_module(module, None);
+    let scopes = Scopes::resolve(module, source);
+
+    let mut imports: Vec<Imported> = Vec::new();
+    let mut quoted: HashSet<String> = HashSet::new();
+    lowered.walk(&mut |node| {
+        let NodeRef::Stmt(stmt) = node else { return };
+        let statement = stmt.span.range.clone();
+        match &stmt.kind {
+            StmtKind::Import(aliases) => {
+                for alias in aliases.iter().filter(|alias| alias.asname.as_ref() != Some(&alias.name)) {
+                    let name = alias.asname.as_deref().unwrap_or_else(|| alias.name.split('.').next().unwrap_or_default());
+                    imports.push(Imported {
+                        name: name.to_string(),
+                        key: (0, None, &alias.name),
+                        alias,
+                        aliases,
+                        statement: statement.clone(),
+                    });
+                }
+            }
+            StmtKind::ImportFrom { module, names, level } if module.as_deref() != Some("__future__") => {
+                let reexported = |alias: &Alias| &*alias.name == "*" || alias.asname.as_ref() == Some(&alias.name);
+                for alias in names.iter().filter(|alias| !reexported(alias)) {
+                    imports.push(Imported {
+                        name: alias.asname.as_deref().unwrap_or(&alias.name).to_string(),
+                        key: (*level, module.as_deref(), &alias.name),
+                        alias,
+                        aliases: names,
+                        statement: statement.clone(),
+                    });
+                }
+            }
+            StmtKind::FunctionDef(func) => {
+                let annotations = func.params.iter().filter_map(|param| param.annotation.as_ref());
+                for annotation in annotations.chain(&func.returns) {
+                    quoted_names(annotation, &mut quoted);
+                }
+            }
+            StmtKind::AnnAssign { annotation, .. } => quoted_names(annotation, &mut quoted),
+            _ => {}
+        }
+    });
+    if let Mod::Module(ast::ModModule { body, .. }) = module {
+        quoted.extend(exported_names(body));
+    }
+
+    // Whether anything reads each symbol
+    let read: HashSet<&Symbol> = scopes.occurrences().iter()
+        .filter(|occurrence| !occurrence.binding)
+        .filter_map(|occurrence| occurrence.symbol.as_ref())
+        .collect();
+    let line = |offset: usize| source[..offset].matches('\n').count() + 1;
+
+    let mut seen: Vec<(&Symbol, ImportKey, usize)> = Vec::new();
+    let mut unused = Vec::new();
+    for import in &imports {
+        let at = match &import.alias.asname {
+            Some(asname) => import.alias.span.range.end - asname.len(),
+            None => import.alias.span.range.start,
+        };
+        let Some(symbol) = scopes.occurrence_at(at).and_then(|occurrence| occurrence.symbol.as_ref()) else { continue };
+        let duplicates = seen.iter()
+            .find(|(earlier, key, _)| *earlier == symbol && *key == import.key)
+            .map(|(_, _, line)| *line);
+        seen.push((symbol, import.key, line(import.statement.start)));
+        if duplicates.is_none() && (read.contains(symbol) || quoted.contains(&import.name)) {
+            continue;
+        }
+        unused.push(UnusedImport {
+            name: import.name.clone(),
+            range: import.alias.span.range.clone(),
+            statement: import.statement.clone(),
+            duplicates,
+            removal: removal(source, import),
+        });
+    }
+    unused
+}
+
+/// Edit taking `import`'s name out of its statement: the name and the comma
+/// before or after it, or for a lone name the statement and its line
+fn removal(source: &str, import: &Imported) -> TextEdit {
+    let aliases = import.aliases;
+    let index = aliases.iter().position(|alias| std::ptr::eq(alias, import.alias)).unwrap_or_default();
+    let range = if aliases.len() == 1 {
+        statement_lines(source, import.statement.clone())
+    } else if index + 1 < aliases.len() {
+        aliases[index].span.range.start..aliases[index + 1].span.range.start
+    } else {
+        aliases[index - 1].span.range.end..aliases[index].span.range.end
+    };
+    TextEdit { range, new_text: String::new() }
+}
+
+/// `statement` widened to its whole lines when nothing else shares them
+pub fn statement_lines(source: &str, statement: Range<usize>) -> Range<usize> {
+    let line_start = source[..statement.start].rfind('\n').map_or(0, |i| i + 1);
+    let rest = &source[statement.end..];
+    let line_end = rest.find('\n').map_or(source.len(), |i| statement.end + i + 1);
+    let alone_before = source[line_start..statement.start].trim().is_empty();
+    let alone_after = matches!(rest[..line_end - statement.end].trim().chars().next(), None | Some('#'));
+    if alone_before && alone_after {
+        line_start..line_end
+    } else {
+        statement
+    }
+}
+
+/// Names read by the quoted parts of `annotation`, e.g. `Node` of
+/// `list["Node"]`
+fn quoted_names(annotation: &ir::Expr, names: &mut HashSet<String>) {
+    annotation.walk(&mut |node| {
+        let NodeRef::Expr(expr) = node else { return };
+        let ExprKind::Constant(Literal::Str(text)) = &expr.kind else { return };
+        let Ok(parsed) = rustpython_parser::parse(text, rustpython_parser::Mode::Expression, "<annotation>") else { return };
+        let Mod::Expression(ast::ModExpression { body, .. }) = &parsed else { return };
+        let lowered = lower_expr(body, None);
+        names.extend(read_names(&lowered));
+        quoted_names(&lowered, names);
+    });
+}
+
+/// Names `expr` reads
+fn read_names(expr: &ir::Expr) -> Vec<String> {
+    let mut names = Vec::new();
+    expr.walk(&mut |node| {
+        if let NodeRef::Expr(expr) = node {
+            if let ast::Expr::Name(name) = expr.node {
+                if name.ctx == ExprContext::Load {
+                    names.push(name.id.to_string());
+                }
+            }
+        }
+    });
+    names
+}
+
+/// Names a module's `__all__` lists, assigned or extended with a list or
+/// tuple of strings
+fn exported_names(body: &[ast::Stmt]) -> Vec<String> {
+    let mut names = Vec::new();
+    for stmt in body {
+        let (target, value) = match stmt {
+            ast::Stmt::Assign(assign) if assign.targets.len() == 1 => (&assign.targets[0], &*assign.value),
+            ast::Stmt::AugAssign(assign) => (&*assign.target, &*assign.value),
+            ast::Stmt::AnnAssign(assign) => match &assign.value {
+                Some(value) => (&*assign.target, &**value),
+                None => continue,
+            },
+            _ => continue,
+        };
+        if !matches!(target, ast::Expr::Name(name) if name.id.as_str() == "__all__") {
+            continue;
+        }
+        let (ast::Expr::List(ast::ExprList { elts, .. }) | ast::Expr::Tuple(ast::ExprTuple { elts, .. })) = value else { continue };
+        for elt in elts {
+            if let ast::Expr::Constant(ast::ExprConstant { value: ast::Constant::Str(name), .. }) = elt {
+                names.push(name.clone());
+            }
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_module;
+
+    fn unused(source: &str) -> Vec<(String, Option<usize>)> {
+        unused_imports(&parse_module(source).unwrap(), source).into_iter()
+            .map(|import| (import.name, import.duplicates))
+            .collect()
+    }
+
+    #[test]
+    fn test_names_read_in_scope_quoted_or_exported_are_used() {
+        let source = "\
+from __future__ import annotations
+import os
+import sys
+from typing import TYPE_CHECKING
+from collections import OrderedDict, defaultdict
+if TYPE_CHECKING:
+    from pathlib import Path
+from json import dumps as dumps
+
+def where(path: \"Path\") -> str:
+    import re
+    return sys.argv[0]
+
+__all__ = [\"defaultdict\"]
+";
+        assert_eq!(unused(source), vec![
+            ("os".to_string(), None),
+            ("OrderedDict".to_string(), None),
+            ("re".to_string(), None),
+        ]);
+    }
+
+    #[test]
+    fn test_repeated_imports_are_reported_where_they_repeat() {
+        let source = "import os\nimport os.path\nimport os\nprint(os.sep)\n";
+        assert_eq!(unused(source), vec![("os".to_string(), Some(1))]);
+    }
+
+    #[test]
+    fn test_removal_takes_the_name_and_its_comma() {
+        let source = "from a import b, c, d\nimport e\nprint(b, d)\n";
+        let module = parse_module(source).unwrap();
+        let fixed: Vec<String> = unused_imports(&module, source).into_iter()
+            .map(|import| {
+                let mut fixed = source.to_string();
+                fixed.replace_range(import.removal.range, &import.removal.new_text);
+                fixed
+            })
+            .collect();
+        assert_eq!(fixed, vec![
+            "from a import b, d\nimport e\nprint(b, d)\n".to_string(),
+            "from a import b, c, d\nprint(b, d)\n".to_string(),
+        ]);
+    }
+}
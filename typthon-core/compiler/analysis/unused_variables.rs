@@ -0,0 +1,307 @@
+//! Unused variables and parameters - function-local names nothing reads
+//!
+//! A local bound in a function and read nowhere, in the function or in a
+//! function nested in it, is reported where it's first bound. Augmented
+//! assignments and `del` count as reads. Names starting with `_` follow the
+//! convention for values kept on purpose and are never reported, nor are
+//! names unpacked alongside others, `match` captures, nested functions and
+//! classes, imports (reported as unused imports instead), or the locals of
+//! a function that calls `locals()`.
+//!
+//! Parameters are reported apart from variables, except for a method's
+//! receiver and the parameters of functions whose signature is set from
+//! elsewhere: placeholder bodies, `@override`, `@abstractmethod` and
+//! `@overload` functions, dunder methods, methods a base class may define
+//! and methods named like those of a protocol class in the module.
+
+use crate::compiler::analysis::reachability;
+use crate::compiler::analysis::scopes::{ScopeKind, Scopes, Symbol};
+use crate::compiler::ast::ir::{self, ExprKind, NodeRef, StmtKind};
+use crate::compiler::ast::lower_module;
+use rustpython_parser::ast::{self, ExprContext, Mod, Ranged};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+/// A local variable or parameter nothing reads
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnusedBinding {
+    pub name: String,
+    /// Bytes of the name where it's first bound
+    pub range: Range<usize>,
+    pub parameter: bool,
+}
+
+/// Where bindings that aren't reported and uses that aren't reads are
+struct Sites {
+    /// Starts of bound names that are never reported
+    exempt: HashSet<usize>,
+    /// Starts of names an augmented assignment or `del` reads
+    reads: HashSet<usize>,
+    /// Starts of parameters, and whether their function's signature is its own
+    params: HashMap<usize, bool>,
+    /// Bytes of `match` patterns, whose captures aren't reported
+    patterns: Vec<Range<usize>>,
+}
+
+/// Local variables and parameters of the functions in `module`, parsed from
+/// `source`, that nothing reads
+pub fn unused_variables(module: &Mod, source: &str) -> Vec<UnusedBinding> {
+    let lowered = lower_module(module, None);
+    let scopes = Scopes::resolve(module, source);
+    let sites = sites(&lowered, source);
+
+    let mut read: HashSet<&Symbol> = HashSet::new();
+    // Scopes whose locals `locals()` may read
+    let mut dynamic: HashSet<usize> = HashSet::new();
+    for occurrence in scopes.occurrences().iter().filter(|occurrence| occurrence.receiver.is_none()) {
+        if !occurrence.binding && occurrence.name == "locals" {
+            dynamic.insert(occurrence.scope);
+        }
+        if !occurrence.binding || sites.reads.contains(&occurrence.range.start) {
+            read.extend(&occurrence.symbol);
+        }
+    }
+
+    let mut reported: HashSet<&Symbol> = HashSet::new();
+    let mut unused = Vec::new();
+    for occurrence in scopes.occurrences().iter().filter(|occurrence| occurrence.binding && occurrence.receiver.is_none()) {
+        let Some(symbol) = &occurrence.symbol else { continue };
+        // Reported once, where first bound
+        if !reported.insert(symbol) {
+            continue;
+        }
+        let start = occurrence.range.start;
+        let skipped = read.contains(symbol)
+            || scopes.kind(symbol.scope) != ScopeKind::Function
+            || dynamic.contains(&symbol.scope)
+            || symbol.name.starts_with('_')
+            || scopes.is_receiver(symbol)
+            || scopes.defines(symbol)
+            || sites.exempt.contains(&start)
+            || sites.patterns.iter().any(|pattern| pattern.contains(&start));
+        if skipped {
+            continue;
+        }
+        let parameter = match sites.params.get(&start) {
+            Some(own_signature) if !own_signature => continue,
+            Some(_) => true,
+            None => false,
+        };
+        unused.push(UnusedBinding { name: symbol.name.clone(), range: occurrence.range.clone(), parameter });
+    }
+    unused.sort_by_key(|binding| binding.range.start);
+    unused
+}
+
+fn sites(lowered: &ir::Module, source: &str) -> Sites {
+    let mut sites = Sites { exempt: HashSet::new(), reads: HashSet::new(), params: HashMap::new(), patterns: Vec::new() };
+    let classes = classes(lowered, source);
+    // Functions whose parameters are set from elsewhere, by where they start
+    let mut borrowed: HashSet<usize> = HashSet::new();
+
+    lowered.walk(&mut |node| match node {
+        NodeRef::Stmt(stmt) => match &stmt.kind {
+            StmtKind::ClassDef(class) => {
+                for method in &class.body {
+                    let StmtKind::FunctionDef(func) = &method.kind else { continue };
+                    let dunder = func.name.starts_with("__") && func.name.ends_with("__");
+                    if dunder || classes.may_define(&class.name, &func.name) {
+                        borrowed.insert(method.span.range.start);
+                    }
+                }
+            }
+            StmtKind::FunctionDef(func) => {
+                let own = !borrowed.contains(&stmt.span.range.start) && !signature_set_elsewhere(stmt, func);
+                for param in &func.params {
+                    sites.params.insert(param.span.range.start, own);
+                }
+            }
+            StmtKind::Assign { targets, .. } => {
+                for target in targets {
+                    unpacked(target, &mut sites.exempt);
+                }
+            }
+            StmtKind::For { target, .. } => unpacked(target, &mut sites.exempt),
+            StmtKind::With { items, .. } => {
+                for target in items.iter().filter_map(|item| item.target.as_ref()) {
+                    unpacked(target, &mut sites.exempt);
+                }
+            }
+            StmtKind::AugAssign { target, .. } => {
+                sites.reads.insert(target.span.range.start);
+            }
+            StmtKind::Import(aliases) | StmtKind::ImportFrom { names: aliases, .. } => {
+                for alias in aliases {
+                    let at = match &alias.asname {
+                        Some(asname) => alias.span.range.end - asname.len(),
+                        None => alias.span.range.start,
+                    };
+                    sites.exempt.insert(at);
+                }
+            }
+            StmtKind::Match { cases, .. } => {
+                sites.patterns.extend(cases.iter().map(|case| case.pattern.start().to_usize()..case.pattern.end().to_usize()));
+            }
+            _ => {}
+        },
+        NodeRef::Expr(expr) => match (&expr.kind, expr.node) {
+            (ExprKind::Name(_), ast::Expr::Name(name)) if name.ctx == ExprContext::Del => {
+                sites.reads.insert(expr.span.range.start);
+            }
+            // Lambdas take what their caller passes
+            (ExprKind::Lambda { params, .. }, _) => {
+                sites.exempt.extend(params.iter().map(|param| param.span.range.start));
+            }
+            _ => {}
+        },
+    });
+    sites
+}
+
+/// Whether `func`'s parameters come from a signature it implements rather
+/// than from what it does: a placeholder body, or a decorator saying so
+fn signature_set_elsewhere(stmt: &ir::Stmt, func: &ir::FunctionDef) -> bool {
+    let decorated = func.decorators.iter().any(|decorator| {
+        let name = match &decorator.kind {
+            ExprKind::Name(name) => name,
+            ExprKind::Attribute { attr, .. } => attr,
+            _ => return false,
+        };
+        matches!(&**name, "override" | "abstractmethod" | "overload")
+    });
+    let body = match stmt.node {
+        ast::Stmt::FunctionDef(def) => &def.body,
+        ast::Stmt::AsyncFunctionDef(def) => &def.body,
+        _ => return decorated,
+    };
+    // A docstring and `raise NotImplementedError()` is a placeholder too
+    let placeholder = match body.split_last() {
+        Some((ast::Stmt::Raise(_), rest)) => reachability::is_stub(rest),
+        _ => reachability::is_stub(body),
+    };
+    decorated || placeholder
+}
+
+/// Names bound by a tuple or list `target`, which unpacking binds whether
+/// they're wanted or not
+fn unpacked(target: &ir::Expr, exempt: &mut HashSet<usize>) {
+    if !matches!(target.kind, ExprKind::Tuple(_) | ExprKind::List(_)) {
+        return;
+    }
+    target.walk(&mut |node| {
+        if let NodeRef::Expr(expr) = node {
+            if let ExprKind::Name(_) = expr.kind {
+                exempt.insert(expr.span.range.start);
+            }
+        }
+    });
+}
+
+/// The module's classes: the source of their bases and the methods they define
+struct Classes {
+    classes: HashMap<String, (Vec<String>, HashSet<String>)>,
+    /// Methods of protocol classes, which any class may implement
+    protocol_methods: HashSet<String>,
+}
+
+impl Classes {
+    /// Whether a base of `class` may define `method`: one of the module's
+    /// classes that does, or any class from elsewhere
+    fn may_define(&self, class: &str, method: &str) -> bool {
+        if self.protocol_methods.contains(method) {
+            return true;
+        }
+        let mut pending: Vec<&str> = vec![class];
+        let mut seen: HashSet<&str> = HashSet::new();
+        while let Some(class) = pending.pop() {
+            if !seen.insert(class) {
+                continue;
+            }
+            let Some((bases, _)) = self.classes.get(class) else { continue };
+            for base in bases.iter().map(String::as_str).filter(|base| *base != "object") {
+                match self.classes.get(base) {
+                    Some((_, methods)) if methods.contains(method) => return true,
+                    Some(_) => pending.push(base),
+                    None => return true,
+                }
+            }
+        }
+        false
+    }
+}
+
+fn classes(lowered: &ir::Module, source: &str) -> Classes {
+    let mut classes = Classes { classes: HashMap::new(), protocol_methods: HashSet::new() };
+    lowered.walk(&mut |node| {
+        let NodeRef::Stmt(ir::Stmt { kind: StmtKind::ClassDef(class), .. }) = node else { return };
+        let bases: Vec<String> = class.bases.iter()
+            .map(|base| source.get(base.span.range.clone()).unwrap_or_default().to_string())
+            .collect();
+        let methods: HashSet<String> = class.body.iter()
+            .filter_map(|stmt| match &stmt.kind {
+                StmtKind::FunctionDef(func) => Some(func.name.to_string()),
+                _ => None,
+            })
+            .collect();
+        let protocol = bases.iter().any(|base| base.rsplit('.').next().is_some_and(|name| name.starts_with("Protocol")));
+        if protocol {
+            classes.protocol_methods.extend(methods.iter().cloned());
+        }
+        classes.classes.insert(class.name.to_string(), (bases, methods));
+    });
+    classes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_module;
+
+    fn unused(source: &str) -> Vec<(String, bool)> {
+        unused_variables(&parse_module(source).unwrap(), source).into_iter()
+            .map(|binding| (binding.name, binding.parameter))
+            .collect()
+    }
+
+    #[test]
+    fn test_unpacking_patterns_and_nested_definitions_are_exempt() {
+        let source = "\
+def split(pair):
+    first, second = pair
+    match pair:
+        case [x, y]:
+            pass
+    def helper():
+        pass
+    for _ in range(3):
+        pass
+    return first
+";
+        assert!(unused(source).is_empty(), "{:?}", unused(source));
+    }
+
+    #[test]
+    fn test_methods_a_base_may_define_keep_their_parameters() {
+        let source = "\
+from typing import Protocol
+
+class Sink(Protocol):
+    def write(self, data: str) -> None: ...
+
+class Base:
+    def size(self, scale: int) -> int:
+        raise NotImplementedError()
+
+class Disk(Base):
+    def size(self, scale: int) -> int:
+        return 1
+
+    def write(self, data: str) -> None:
+        print(self)
+
+    def flush(self, force: bool) -> None:
+        print(self)
+";
+        assert_eq!(unused(source), vec![("force".to_string(), true)]);
+    }
+}
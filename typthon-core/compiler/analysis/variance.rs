@@ -1,4 +1,5 @@
 use crate::compiler::types::Type;
+use super::constraints::TypeParameter;
 use std::collections::HashMap;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -31,8 +32,31 @@ impl Variance {
             Self::Bivariant => Self::Bivariant,
         }
     }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Covariant => "covariant",
+            Self::Contravariant => "contravariant",
+            Self::Invariant => "invariant",
+            Self::Bivariant => "bivariant",
+        }
+    }
 }
 
+/// Variances of the builtin generics, mutable containers being invariant
+const BUILTIN_VARIANCES: &[(&str, Variance)] = &[
+    ("List", Variance::Invariant),
+    ("Tuple", Variance::Covariant),
+    ("Dict", Variance::Invariant),
+    ("Set", Variance::Invariant),
+    ("FrozenSet", Variance::Covariant),
+    ("Callable", Variance::Contravariant), // Params
+    ("Iterator", Variance::Covariant),
+    ("Iterable", Variance::Covariant),
+    ("Sequence", Variance::Covariant),
+    ("Mapping", Variance::Covariant), // Immutable
+];
+
 pub struct VarianceAnalyzer {
     cache: HashMap<String, Variance>,
 }
@@ -48,23 +72,31 @@ impl VarianceAnalyzer {
 
     fn init_builtins(&mut self) {
         // Python builtin types and their variances
-        self.cache.insert("List".to_string(), Variance::Invariant); // Mutable
-        self.cache.insert("Tuple".to_string(), Variance::Covariant);
-        self.cache.insert("Dict".to_string(), Variance::Invariant); // Mutable
-        self.cache.insert("Set".to_string(), Variance::Invariant); // Mutable
-        self.cache.insert("FrozenSet".to_string(), Variance::Covariant);
-        self.cache.insert("Callable".to_string(), Variance::Contravariant); // Params
-        self.cache.insert("Iterator".to_string(), Variance::Covariant);
-        self.cache.insert("Iterable".to_string(), Variance::Covariant);
-        self.cache.insert("Sequence".to_string(), Variance::Covariant);
-        self.cache.insert("Mapping".to_string(), Variance::Covariant); // Immutable
+        for (name, variance) in BUILTIN_VARIANCES {
+            self.cache.insert(name.to_string(), *variance);
+        }
+    }
+
+    /// Variance of the parameters of a builtin generic, e.g. `Sequence`
+    pub fn builtin_variance(type_name: &str) -> Option<Variance> {
+        BUILTIN_VARIANCES.iter()
+            .find(|(name, _)| *name == type_name)
+            .map(|(_, variance)| *variance)
+    }
+
+    /// Record the variances a generic class declares for its parameters
+    /// through `TypeVar(..., covariant=True)` and the like
+    pub fn declare(&mut self, type_name: &str, variances: &[Variance]) {
+        for (index, variance) in variances.iter().enumerate() {
+            self.cache.insert(format!("{}[{}]", type_name, index), *variance);
+        }
     }
 
     /// Infer variance of a type parameter in a generic type
     pub fn infer_variance(&mut self, type_name: &str, param_index: usize) -> Variance {
         // Check cache
         let cache_key = format!("{}[{}]", type_name, param_index);
-        if let Some(&variance) = self.cache.get(&cache_key) {
+        if let Some(&variance) = self.cache.get(&cache_key).or_else(|| self.cache.get(type_name)) {
             return variance;
         }
 
@@ -147,6 +179,8 @@ impl VarianceAnalyzer {
                 }
             }
 
+            Type::VarTuple(elem) => self.compute_variance(elem, param_var, position),
+
             Type::Tuple(elems) => {
                 // Immutable: preserve variance
                 elems
@@ -226,7 +260,7 @@ impl VarianceAnalyzer {
     fn contains_var(&self, ty: &Type, var: u64) -> bool {
         match ty {
             Type::Var(id) => *id == var,
-            Type::List(inner) | Type::Set(inner) => self.contains_var(inner, var),
+            Type::List(inner) | Type::Set(inner) | Type::VarTuple(inner) => self.contains_var(inner, var),
             Type::Dict(k, v) => self.contains_var(k, var) || self.contains_var(v, var),
             Type::Tuple(elems) | Type::Union(elems) | Type::Intersection(elems) => {
                 elems.iter().any(|e| self.contains_var(e, var))
@@ -249,6 +283,63 @@ impl VarianceAnalyzer {
     }
 }
 
+impl VarianceAnalyzer {
+    /// Check a member type of the generic class `type_name` against the
+    /// declared variance of its parameter `param`, which member types refer
+    /// to as `Type::param(param)`: a covariant parameter can't be taken as
+    /// a method argument, nor a contravariant one returned. Uses inside
+    /// mutable containers are left alone
+    pub fn check_usage(&mut self, type_name: &str, param: &TypeParameter, member: &Type) -> Result<(), String> {
+        let mut usages = Vec::new();
+        self.collect_usages(member, &Type::param(&param.name), Variance::Covariant, &mut usages);
+        for usage in usages {
+            if matches!(usage, Variance::Covariant | Variance::Contravariant) {
+                self.validate_variance_annotation(type_name, param.variance, usage)
+                    .map_err(|_| format!(
+                        "{} type parameter '{}' of {} used in a {} position",
+                        param.variance.name(), param.name, type_name, usage.name(),
+                    ))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Variances of the positions `param` occurs at in `ty`, which itself
+    /// sits at `position`
+    fn collect_usages(&mut self, ty: &Type, param: &Type, position: Variance, out: &mut Vec<Variance>) {
+        match ty {
+            ty if ty == param => out.push(position),
+            Type::List(inner) | Type::Set(inner) => {
+                self.collect_usages(inner, param, Variance::Invariant, out);
+            }
+            Type::Dict(key, value) => {
+                self.collect_usages(key, param, Variance::Invariant, out);
+                self.collect_usages(value, param, Variance::Invariant, out);
+            }
+            Type::VarTuple(inner) => self.collect_usages(inner, param, position, out),
+            Type::Tuple(types) | Type::Union(types) | Type::Intersection(types) => {
+                for ty in types {
+                    self.collect_usages(ty, param, position, out);
+                }
+            }
+            Type::Function(params, ret) => {
+                for ty in params {
+                    self.collect_usages(ty, param, position.flip(), out);
+                }
+                self.collect_usages(ret, param, position, out);
+            }
+            Type::Generic(name, args) => {
+                for (index, arg) in args.iter().enumerate() {
+                    let variance = self.infer_variance(name, index);
+                    self.collect_usages(arg, param, position.compose(variance), out);
+                }
+            }
+            Type::Effect(inner, _) | Type::Refinement(inner, _) => self.collect_usages(inner, param, position, out),
+            _ => {}
+        }
+    }
+}
+
 impl Default for VarianceAnalyzer {
     fn default() -> Self {
         Self::new()
@@ -294,5 +385,23 @@ mod tests {
             Some(&Variance::Invariant)
         );
     }
-}
 
+    #[test]
+    fn test_usage_against_declared_variance() {
+        let mut analyzer = VarianceAnalyzer::new();
+        let covariant = TypeParameter::new("T".to_string()).with_variance(Variance::Covariant);
+        let param = Type::param("T");
+        let getter = Type::Function(vec![], Box::new(param.clone()));
+        let setter = Type::Function(vec![param.clone()], Box::new(Type::None));
+        // A callback taking T takes it in a covariant position again
+        let visitor = Type::Function(vec![Type::Function(vec![param.clone()], Box::new(Type::None))], Box::new(Type::None));
+
+        assert!(analyzer.check_usage("Box", &covariant, &getter).is_ok());
+        assert!(analyzer.check_usage("Box", &covariant, &setter).is_err());
+        assert!(analyzer.check_usage("Box", &covariant, &visitor).is_ok());
+
+        let invariant = TypeParameter::new("T".to_string());
+        assert!(analyzer.check_usage("Box", &invariant, &setter).is_ok());
+        assert_eq!(VarianceAnalyzer::builtin_variance("Sequence"), Some(Variance::Covariant));
+    }
+}
@@ -0,0 +1,99 @@
+//! Docstrings of modules, classes and functions
+//!
+//! A docstring is the first statement of a body when that statement is a
+//! bare string literal. The parser has already decoded escapes, raw prefixes
+//! and implicit concatenation (`"a" "b"`), so all that's left is the
+//! `inspect.cleandoc` treatment: tabs expanded, the common indentation of
+//! every line after the first removed, blank lines trimmed at both ends.
+
+use rustpython_parser::ast::{Constant, Expr, Stmt};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Docstring {
+    /// First line, for notes and one-line listings
+    pub summary: String,
+    /// Cleaned full text
+    pub text: String,
+}
+
+impl Docstring {
+    /// The docstring of a module, class or function body
+    pub fn of(body: &[Stmt]) -> Option<Self> {
+        let Some(Stmt::Expr(stmt)) = body.first() else { return None };
+        match &*stmt.value {
+            Expr::Constant(constant) => match &constant.value {
+                Constant::Str(raw) => Self::new(raw),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Clean `raw`; `None` when nothing but whitespace is left
+    pub fn new(raw: &str) -> Option<Self> {
+        let text = clean(raw);
+        let summary = text.lines().next()?.trim().to_string();
+        Some(Self { summary, text })
+    }
+
+    /// Text up to the first blank line
+    pub fn first_paragraph(&self) -> &str {
+        self.text.split("\n\n").next().unwrap_or(&self.text)
+    }
+}
+
+fn clean(raw: &str) -> String {
+    let expanded = raw.replace('\t', "        ");
+    let lines: Vec<&str> = expanded.lines().collect();
+    let indent = lines.iter().skip(1)
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    let mut cleaned: Vec<&str> = lines.iter().enumerate()
+        .map(|(i, line)| match i {
+            0 => line.trim_start(),
+            _ => line.get(indent..).unwrap_or("").trim_end(),
+        })
+        .collect();
+    while cleaned.last().is_some_and(|line| line.trim().is_empty()) {
+        cleaned.pop();
+    }
+    let start = cleaned.iter().position(|line| !line.trim().is_empty()).unwrap_or(cleaned.len());
+    cleaned[start..].join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustpython_parser::{parse, Mode};
+    use rustpython_parser::ast::Mod;
+
+    fn module_doc(source: &str) -> Option<Docstring> {
+        match parse(source, Mode::Module, "<test>").unwrap() {
+            Mod::Module(module) => Docstring::of(&module.body),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_indentation_is_stripped() {
+        let doc = module_doc("'''Add two numbers.\n\n    Returns the sum;\n      never fails.\n    '''\n").unwrap();
+
+        assert_eq!(doc.summary, "Add two numbers.");
+        assert_eq!(doc.text, "Add two numbers.\n\nReturns the sum;\n  never fails.");
+        assert_eq!(doc.first_paragraph(), "Add two numbers.");
+    }
+
+    #[test]
+    fn test_raw_and_concatenated_strings() {
+        let doc = module_doc("r'Match \\d+ digits' ' greedily.'\n").unwrap();
+        assert_eq!(doc.summary, "Match \\d+ digits greedily.");
+
+        assert_eq!(module_doc("x = 1\n'not a docstring'\n"), None);
+        assert_eq!(module_doc("b'bytes'\n"), None);
+        assert_eq!(module_doc("'''\n   \n'''\n"), None);
+    }
+}
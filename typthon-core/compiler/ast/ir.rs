@@ -0,0 +1,527 @@
+//! Lowered AST - span-carrying node types for our own analyses
+//!
+//! Analyses that only need node kinds, children and positions work on this
+//! IR instead of matching on rustpython nodes directly, so a parser upgrade
+//! touches `lower.rs` rather than every pass. Each node keeps:
+//!
+//! - `span`: byte range plus the resolved [`SourceLocation`]
+//! - `node`: a side-pointer to the original rustpython node, for passes that
+//!   need exact ranges (the LSP) or details the IR leaves out (match patterns)
+//!
+//! Identifiers are interned per lowering, so repeated names share storage.
+//! Async and star variants fold into flags (`FunctionDef::is_async`,
+//! `StmtKind::Try { is_star }`), and type comments ride on the nodes they
+//! annotate. `Error` kinds are what a recovering parser's placeholders lower to.
+
+use crate::compiler::errors::SourceLocation;
+use rustpython_parser::ast;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Operator enums carry no tree structure and are reused as-is
+pub use rustpython_parser::ast::{BoolOp, CmpOp, Operator, UnaryOp};
+
+/// Interned identifier
+pub type Name = Arc<str>;
+
+/// Byte range and resolved position of a node
+///
+/// `location` is all zeros when the module was lowered without source text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub range: Range<usize>,
+    pub location: SourceLocation,
+}
+
+#[derive(Debug, Clone)]
+pub struct Module<'a> {
+    pub body: Vec<Stmt<'a>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Stmt<'a> {
+    pub kind: StmtKind<'a>,
+    pub span: Span,
+    pub node: &'a ast::Stmt,
+}
+
+#[derive(Debug, Clone)]
+pub enum StmtKind<'a> {
+    FunctionDef(FunctionDef<'a>),
+    ClassDef(ClassDef<'a>),
+    Return(Option<Expr<'a>>),
+    Delete(Vec<Expr<'a>>),
+    Assign { targets: Vec<Expr<'a>>, value: Expr<'a>, type_comment: Option<String> },
+    TypeAlias { name: Expr<'a>, type_params: Vec<Name>, value: Expr<'a> },
+    AugAssign { target: Expr<'a>, op: Operator, value: Expr<'a> },
+    AnnAssign { target: Expr<'a>, annotation: Expr<'a>, value: Option<Expr<'a>>, simple: bool },
+    For {
+        is_async: bool,
+        target: Expr<'a>,
+        iter: Expr<'a>,
+        body: Vec<Stmt<'a>>,
+        orelse: Vec<Stmt<'a>>,
+        type_comment: Option<String>,
+    },
+    While { test: Expr<'a>, body: Vec<Stmt<'a>>, orelse: Vec<Stmt<'a>> },
+    If { test: Expr<'a>, body: Vec<Stmt<'a>>, orelse: Vec<Stmt<'a>> },
+    With { is_async: bool, items: Vec<WithItem<'a>>, body: Vec<Stmt<'a>>, type_comment: Option<String> },
+    Match { subject: Expr<'a>, cases: Vec<MatchCase<'a>> },
+    Raise { exc: Option<Expr<'a>>, cause: Option<Expr<'a>> },
+    Try {
+        is_star: bool,
+        body: Vec<Stmt<'a>>,
+        handlers: Vec<Handler<'a>>,
+        orelse: Vec<Stmt<'a>>,
+        finalbody: Vec<Stmt<'a>>,
+    },
+    Assert { test: Expr<'a>, msg: Option<Expr<'a>> },
+    Import(Vec<Alias>),
+    ImportFrom { module: Option<Name>, names: Vec<Alias>, level: u32 },
+    Global(Vec<Name>),
+    Nonlocal(Vec<Name>),
+    Expr(Expr<'a>),
+    Pass,
+    Break,
+    Continue,
+    /// Placeholder for a statement the parser could not recover
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct FunctionDef<'a> {
+    pub name: Name,
+    pub is_async: bool,
+    pub params: Vec<Param<'a>>,
+    pub returns: Option<Expr<'a>>,
+    pub decorators: Vec<Expr<'a>>,
+    pub type_params: Vec<Name>,
+    pub body: Vec<Stmt<'a>>,
+    pub type_comment: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ClassDef<'a> {
+    pub name: Name,
+    pub bases: Vec<Expr<'a>>,
+    pub keywords: Vec<Keyword<'a>>,
+    pub decorators: Vec<Expr<'a>>,
+    pub type_params: Vec<Name>,
+    pub body: Vec<Stmt<'a>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamKind {
+    PositionalOnly,
+    Positional,
+    VarArgs,
+    KeywordOnly,
+    VarKeywords,
+}
+
+/// Function or lambda parameter, in declaration order
+#[derive(Debug, Clone)]
+pub struct Param<'a> {
+    pub name: Name,
+    pub kind: ParamKind,
+    pub annotation: Option<Expr<'a>>,
+    pub default: Option<Expr<'a>>,
+    pub type_comment: Option<String>,
+    pub span: Span,
+    pub node: &'a ast::Arg,
+}
+
+#[derive(Debug, Clone)]
+pub struct Keyword<'a> {
+    /// `None` for `**kwargs` splats
+    pub arg: Option<Name>,
+    pub value: Expr<'a>,
+}
+
+#[derive(Debug, Clone)]
+pub struct WithItem<'a> {
+    pub context: Expr<'a>,
+    pub target: Option<Expr<'a>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchCase<'a> {
+    /// Patterns are not lowered; analyses that need them read the original
+    pub pattern: &'a ast::Pattern,
+    pub guard: Option<Expr<'a>>,
+    pub body: Vec<Stmt<'a>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Handler<'a> {
+    pub type_: Option<Expr<'a>>,
+    pub name: Option<Name>,
+    pub body: Vec<Stmt<'a>>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alias {
+    pub name: Name,
+    pub asname: Option<Name>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct Expr<'a> {
+    pub kind: ExprKind<'a>,
+    pub span: Span,
+    pub node: &'a ast::Expr,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComprehensionKind {
+    List,
+    Set,
+    Dict,
+    Generator,
+}
+
+#[derive(Debug, Clone)]
+pub struct Comprehension<'a> {
+    pub target: Expr<'a>,
+    pub iter: Expr<'a>,
+    pub ifs: Vec<Expr<'a>>,
+    pub is_async: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum ExprKind<'a> {
+    BoolOp { op: BoolOp, values: Vec<Expr<'a>> },
+    NamedExpr { target: Box<Expr<'a>>, value: Box<Expr<'a>> },
+    BinOp { left: Box<Expr<'a>>, op: Operator, right: Box<Expr<'a>> },
+    UnaryOp { op: UnaryOp, operand: Box<Expr<'a>> },
+    Lambda { params: Vec<Param<'a>>, body: Box<Expr<'a>> },
+    IfExp { test: Box<Expr<'a>>, body: Box<Expr<'a>>, orelse: Box<Expr<'a>> },
+    /// `None` keys are `**mapping` splats
+    Dict { keys: Vec<Option<Expr<'a>>>, values: Vec<Expr<'a>> },
+    Set(Vec<Expr<'a>>),
+    /// List, set, dict and generator comprehensions; `value` is set for dicts only
+    Comprehension {
+        kind: ComprehensionKind,
+        elt: Box<Expr<'a>>,
+        value: Option<Box<Expr<'a>>>,
+        generators: Vec<Comprehension<'a>>,
+    },
+    Await(Box<Expr<'a>>),
+    Yield(Option<Box<Expr<'a>>>),
+    YieldFrom(Box<Expr<'a>>),
+    Compare { left: Box<Expr<'a>>, ops: Vec<CmpOp>, comparators: Vec<Expr<'a>> },
+    Call { func: Box<Expr<'a>>, args: Vec<Expr<'a>>, keywords: Vec<Keyword<'a>> },
+    /// One `{...}` field of an f-string; `conversion` is `s`, `r` or `a`
+    FormattedValue { value: Box<Expr<'a>>, conversion: Option<char>, format_spec: Option<Box<Expr<'a>>> },
+    JoinedStr(Vec<Expr<'a>>),
+    Constant(Literal),
+    Attribute { value: Box<Expr<'a>>, attr: Name },
+    Subscript { value: Box<Expr<'a>>, slice: Box<Expr<'a>> },
+    Starred(Box<Expr<'a>>),
+    Name(Name),
+    List(Vec<Expr<'a>>),
+    Tuple(Vec<Expr<'a>>),
+    Slice { lower: Option<Box<Expr<'a>>>, upper: Option<Box<Expr<'a>>>, step: Option<Box<Expr<'a>>> },
+    /// Placeholder for an expression the parser could not recover
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    None,
+    Bool(bool),
+    Str(String),
+    Bytes(Vec<u8>),
+    /// Decimal digits, so arbitrarily large literals survive lowering
+    Int(String),
+    Float(f64),
+    Complex { real: f64, imag: f64 },
+    Ellipsis,
+    Tuple(Vec<Literal>),
+}
+
+/// A node visited by [`Module::walk`]
+#[derive(Debug, Clone, Copy)]
+pub enum NodeRef<'r, 'a> {
+    Stmt(&'r Stmt<'a>),
+    Expr(&'r Expr<'a>),
+}
+
+impl<'a> Module<'a> {
+    /// Visit every statement and expression in source order, parents first
+    pub fn walk<'r>(&'r self, visit: &mut dyn FnMut(NodeRef<'r, 'a>)) {
+        for stmt in &self.body {
+            stmt.walk(visit);
+        }
+    }
+}
+
+impl<'a> Stmt<'a> {
+    /// Variant name, for diagnostics and coverage checks
+    pub fn kind_name(&self) -> &'static str {
+        match &self.kind {
+            StmtKind::FunctionDef(func) if func.is_async => "AsyncFunctionDef",
+            StmtKind::FunctionDef(_) => "FunctionDef",
+            StmtKind::ClassDef(_) => "ClassDef",
+            StmtKind::Return(_) => "Return",
+            StmtKind::Delete(_) => "Delete",
+            StmtKind::Assign { .. } => "Assign",
+            StmtKind::TypeAlias { .. } => "TypeAlias",
+            StmtKind::AugAssign { .. } => "AugAssign",
+            StmtKind::AnnAssign { .. } => "AnnAssign",
+            StmtKind::For { is_async: true, .. } => "AsyncFor",
+            StmtKind::For { .. } => "For",
+            StmtKind::While { .. } => "While",
+            StmtKind::If { .. } => "If",
+            StmtKind::With { is_async: true, .. } => "AsyncWith",
+            StmtKind::With { .. } => "With",
+            StmtKind::Match { .. } => "Match",
+            StmtKind::Raise { .. } => "Raise",
+            StmtKind::Try { is_star: true, .. } => "TryStar",
+            StmtKind::Try { .. } => "Try",
+            StmtKind::Assert { .. } => "Assert",
+            StmtKind::Import(_) => "Import",
+            StmtKind::ImportFrom { .. } => "ImportFrom",
+            StmtKind::Global(_) => "Global",
+            StmtKind::Nonlocal(_) => "Nonlocal",
+            StmtKind::Expr(_) => "Expr",
+            StmtKind::Pass => "Pass",
+            StmtKind::Break => "Break",
+            StmtKind::Continue => "Continue",
+            StmtKind::Error => "Error",
+        }
+    }
+
+    pub fn walk<'r>(&'r self, visit: &mut dyn FnMut(NodeRef<'r, 'a>)) {
+        visit(NodeRef::Stmt(self));
+        match &self.kind {
+            StmtKind::FunctionDef(func) => {
+                func.decorators.iter().for_each(|d| d.walk(visit));
+                walk_params(&func.params, visit);
+                if let Some(returns) = &func.returns {
+                    returns.walk(visit);
+                }
+                walk_body(&func.body, visit);
+            }
+            StmtKind::ClassDef(class) => {
+                class.decorators.iter().for_each(|d| d.walk(visit));
+                class.bases.iter().for_each(|b| b.walk(visit));
+                class.keywords.iter().for_each(|k| k.value.walk(visit));
+                walk_body(&class.body, visit);
+            }
+            StmtKind::Return(value) => {
+                if let Some(value) = value {
+                    value.walk(visit);
+                }
+            }
+            StmtKind::Delete(targets) => targets.iter().for_each(|t| t.walk(visit)),
+            StmtKind::Assign { targets, value, .. } => {
+                targets.iter().for_each(|t| t.walk(visit));
+                value.walk(visit);
+            }
+            StmtKind::TypeAlias { name, value, .. } => {
+                name.walk(visit);
+                value.walk(visit);
+            }
+            StmtKind::AugAssign { target, value, .. } => {
+                target.walk(visit);
+                value.walk(visit);
+            }
+            StmtKind::AnnAssign { target, annotation, value, .. } => {
+                target.walk(visit);
+                annotation.walk(visit);
+                if let Some(value) = value {
+                    value.walk(visit);
+                }
+            }
+            StmtKind::For { target, iter, body, orelse, .. } => {
+                target.walk(visit);
+                iter.walk(visit);
+                walk_body(body, visit);
+                walk_body(orelse, visit);
+            }
+            StmtKind::While { test, body, orelse } | StmtKind::If { test, body, orelse } => {
+                test.walk(visit);
+                walk_body(body, visit);
+                walk_body(orelse, visit);
+            }
+            StmtKind::With { items, body, .. } => {
+                for item in items {
+                    item.context.walk(visit);
+                    if let Some(target) = &item.target {
+                        target.walk(visit);
+                    }
+                }
+                walk_body(body, visit);
+            }
+            StmtKind::Match { subject, cases } => {
+                subject.walk(visit);
+                for case in cases {
+                    if let Some(guard) = &case.guard {
+                        guard.walk(visit);
+                    }
+                    walk_body(&case.body, visit);
+                }
+            }
+            StmtKind::Raise { exc, cause } => {
+                exc.iter().chain(cause).for_each(|e| e.walk(visit));
+            }
+            StmtKind::Try { body, handlers, orelse, finalbody, .. } => {
+                walk_body(body, visit);
+                for handler in handlers {
+                    if let Some(type_) = &handler.type_ {
+                        type_.walk(visit);
+                    }
+                    walk_body(&handler.body, visit);
+                }
+                walk_body(orelse, visit);
+                walk_body(finalbody, visit);
+            }
+            StmtKind::Assert { test, msg } => {
+                test.walk(visit);
+                if let Some(msg) = msg {
+                    msg.walk(visit);
+                }
+            }
+            StmtKind::Expr(value) => value.walk(visit),
+            StmtKind::Import(_) | StmtKind::ImportFrom { .. } | StmtKind::Global(_)
+            | StmtKind::Nonlocal(_) | StmtKind::Pass | StmtKind::Break | StmtKind::Continue
+            | StmtKind::Error => {}
+        }
+    }
+}
+
+impl<'a> Expr<'a> {
+    /// Variant name, for diagnostics and coverage checks
+    pub fn kind_name(&self) -> &'static str {
+        match &self.kind {
+            ExprKind::BoolOp { .. } => "BoolOp",
+            ExprKind::NamedExpr { .. } => "NamedExpr",
+            ExprKind::BinOp { .. } => "BinOp",
+            ExprKind::UnaryOp { .. } => "UnaryOp",
+            ExprKind::Lambda { .. } => "Lambda",
+            ExprKind::IfExp { .. } => "IfExp",
+            ExprKind::Dict { .. } => "Dict",
+            ExprKind::Set(_) => "Set",
+            ExprKind::Comprehension { kind: ComprehensionKind::List, .. } => "ListComp",
+            ExprKind::Comprehension { kind: ComprehensionKind::Set, .. } => "SetComp",
+            ExprKind::Comprehension { kind: ComprehensionKind::Dict, .. } => "DictComp",
+            ExprKind::Comprehension { kind: ComprehensionKind::Generator, .. } => "GeneratorExp",
+            ExprKind::Await(_) => "Await",
+            ExprKind::Yield(_) => "Yield",
+            ExprKind::YieldFrom(_) => "YieldFrom",
+            ExprKind::Compare { .. } => "Compare",
+            ExprKind::Call { .. } => "Call",
+            ExprKind::FormattedValue { .. } => "FormattedValue",
+            ExprKind::JoinedStr(_) => "JoinedStr",
+            ExprKind::Constant(_) => "Constant",
+            ExprKind::Attribute { .. } => "Attribute",
+            ExprKind::Subscript { .. } => "Subscript",
+            ExprKind::Starred(_) => "Starred",
+            ExprKind::Name(_) => "Name",
+            ExprKind::List(_) => "List",
+            ExprKind::Tuple(_) => "Tuple",
+            ExprKind::Slice { .. } => "Slice",
+            ExprKind::Error => "Error",
+        }
+    }
+
+    pub fn walk<'r>(&'r self, visit: &mut dyn FnMut(NodeRef<'r, 'a>)) {
+        visit(NodeRef::Expr(self));
+        match &self.kind {
+            ExprKind::BoolOp { values, .. } => values.iter().for_each(|v| v.walk(visit)),
+            ExprKind::NamedExpr { target, value } => {
+                target.walk(visit);
+                value.walk(visit);
+            }
+            ExprKind::BinOp { left, right, .. } => {
+                left.walk(visit);
+                right.walk(visit);
+            }
+            ExprKind::UnaryOp { operand, .. } => operand.walk(visit),
+            ExprKind::Lambda { params, body } => {
+                walk_params(params, visit);
+                body.walk(visit);
+            }
+            ExprKind::IfExp { test, body, orelse } => {
+                test.walk(visit);
+                body.walk(visit);
+                orelse.walk(visit);
+            }
+            ExprKind::Dict { keys, values } => {
+                for (key, value) in keys.iter().zip(values) {
+                    if let Some(key) = key {
+                        key.walk(visit);
+                    }
+                    value.walk(visit);
+                }
+            }
+            ExprKind::Set(elts) | ExprKind::List(elts) | ExprKind::Tuple(elts)
+            | ExprKind::JoinedStr(elts) => elts.iter().for_each(|e| e.walk(visit)),
+            ExprKind::Comprehension { elt, value, generators, .. } => {
+                for generator in generators {
+                    generator.target.walk(visit);
+                    generator.iter.walk(visit);
+                    generator.ifs.iter().for_each(|cond| cond.walk(visit));
+                }
+                elt.walk(visit);
+                if let Some(value) = value {
+                    value.walk(visit);
+                }
+            }
+            ExprKind::Await(value) | ExprKind::YieldFrom(value) | ExprKind::Starred(value) => {
+                value.walk(visit)
+            }
+            ExprKind::Yield(value) => {
+                if let Some(value) = value {
+                    value.walk(visit);
+                }
+            }
+            ExprKind::Compare { left, comparators, .. } => {
+                left.walk(visit);
+                comparators.iter().for_each(|c| c.walk(visit));
+            }
+            ExprKind::Call { func, args, keywords } => {
+                func.walk(visit);
+                args.iter().for_each(|a| a.walk(visit));
+                keywords.iter().for_each(|k| k.value.walk(visit));
+            }
+            ExprKind::FormattedValue { value, format_spec, .. } => {
+                value.walk(visit);
+                if let Some(spec) = format_spec {
+                    spec.walk(visit);
+                }
+            }
+            ExprKind::Attribute { value, .. } => value.walk(visit),
+            ExprKind::Subscript { value, slice } => {
+                value.walk(visit);
+                slice.walk(visit);
+            }
+            ExprKind::Slice { lower, upper, step } => {
+                for part in [lower, upper, step].into_iter().flatten() {
+                    part.walk(visit);
+                }
+            }
+            ExprKind::Constant(_) | ExprKind::Name(_) | ExprKind::Error => {}
+        }
+    }
+}
+
+fn walk_body<'r, 'a>(body: &'r [Stmt<'a>], visit: &mut dyn FnMut(NodeRef<'r, 'a>)) {
+    for stmt in body {
+        stmt.walk(visit);
+    }
+}
+
+fn walk_params<'r, 'a>(params: &'r [Param<'a>], visit: &mut dyn FnMut(NodeRef<'r, 'a>)) {
+    for param in params {
+        if let Some(annotation) = &param.annotation {
+            annotation.walk(visit);
+        }
+        if let Some(default) = &param.default {
+            default.walk(visit);
+        }
+    }
+}
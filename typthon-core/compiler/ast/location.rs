@@ -9,7 +9,7 @@
 //! We convert these to line:column positions by maintaining line boundaries
 //! from the source text.
 
-use rustpython_parser::ast::{Expr, Stmt, Pattern, Mod, Ranged, ModModule};
+use rustpython_parser::ast::{Expr, ExprCall, Keyword, Stmt, Pattern, Mod, Ranged, ModModule};
 use crate::compiler::errors::SourceLocation;
 use std::sync::Arc;
 use parking_lot::RwLock;
@@ -87,6 +87,24 @@ impl SourceLocationExt for Pattern {
     }
 }
 
+impl SourceLocationExt for ExprCall {
+    fn source_location(&self, index: &LineIndex) -> SourceLocation {
+        let range = self.range();
+        let (start_line, start_col) = index.offset_to_position(range.start().to_usize());
+        let (end_line, end_col) = index.offset_to_position(range.end().to_usize());
+        SourceLocation::new(start_line, start_col, end_line, end_col)
+    }
+}
+
+impl SourceLocationExt for Keyword {
+    fn source_location(&self, index: &LineIndex) -> SourceLocation {
+        let range = self.range();
+        let (start_line, start_col) = index.offset_to_position(range.start().to_usize());
+        let (end_line, end_col) = index.offset_to_position(range.end().to_usize());
+        SourceLocation::new(start_line, start_col, end_line, end_col)
+    }
+}
+
 impl SourceLocationExt for Mod {
     fn source_location(&self, index: &LineIndex) -> SourceLocation {
         // Mod doesn't have a direct range, so we use the first/last statement
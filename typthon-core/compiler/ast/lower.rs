@@ -0,0 +1,423 @@
+//! Lowering from rustpython nodes to the IR in `ir.rs`
+//!
+//! This is the only place that matches on every rustpython statement and
+//! expression variant. Work that belongs between the parser and our passes
+//! lands here: folding async/star variants into flags, attaching type
+//! comments, and mapping a recovering parser's placeholders to `Error` kinds.
+
+use super::ir::*;
+use super::location::LineIndex;
+use crate::compiler::errors::SourceLocation;
+use rustpython_parser::ast::{self, Ranged};
+use rustpython_parser::text_size::TextRange;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Lower a parsed module; pass the line index to resolve spans to positions
+pub fn lower_module<'a>(module: &'a ast::Mod, index: Option<&LineIndex>) -> Module<'a> {
+    Lowerer::new(index).module(module)
+}
+
+/// Lower a single expression, for callers that hold one outside a module
+pub fn lower_expr<'a>(expr: &'a ast::Expr, index: Option<&LineIndex>) -> Expr<'a> {
+    Lowerer::new(index).expr(expr)
+}
+
+/// Lowering state: the line index and the identifier interner
+pub struct Lowerer<'i> {
+    index: Option<&'i LineIndex>,
+    names: HashMap<String, Name>,
+}
+
+impl<'i> Lowerer<'i> {
+    pub fn new(index: Option<&'i LineIndex>) -> Self {
+        Self { index, names: HashMap::new() }
+    }
+
+    pub fn module<'a>(&mut self, module: &'a ast::Mod) -> Module<'a> {
+        let body = match module {
+            ast::Mod::Module(m) => self.body(&m.body),
+            ast::Mod::Interactive(m) => self.body(&m.body),
+            ast::Mod::Expression(_) | ast::Mod::FunctionType(_) => Vec::new(),
+        };
+        Module { body }
+    }
+
+    fn intern(&mut self, name: &str) -> Name {
+        if let Some(interned) = self.names.get(name) {
+            return interned.clone();
+        }
+        let interned: Name = Arc::from(name);
+        self.names.insert(name.to_string(), interned.clone());
+        interned
+    }
+
+    fn span(&self, range: TextRange) -> Span {
+        let (start, end) = (range.start().to_usize(), range.end().to_usize());
+        let location = match self.index {
+            Some(index) => {
+                let (line, col) = index.offset_to_position(start);
+                let (end_line, end_col) = index.offset_to_position(end);
+                SourceLocation::new(line, col, end_line, end_col)
+            }
+            None => SourceLocation::new(0, 0, 0, 0),
+        };
+        Span { range: start..end, location }
+    }
+
+    fn body<'a>(&mut self, stmts: &'a [ast::Stmt]) -> Vec<Stmt<'a>> {
+        stmts.iter().map(|stmt| self.stmt(stmt)).collect()
+    }
+
+    fn exprs<'a>(&mut self, exprs: &'a [ast::Expr]) -> Vec<Expr<'a>> {
+        exprs.iter().map(|expr| self.expr(expr)).collect()
+    }
+
+    fn opt_expr<'a>(&mut self, expr: Option<&'a ast::Expr>) -> Option<Expr<'a>> {
+        expr.map(|e| self.expr(e))
+    }
+
+    fn boxed<'a>(&mut self, expr: &'a ast::Expr) -> Box<Expr<'a>> {
+        Box::new(self.expr(expr))
+    }
+
+    fn opt_boxed<'a>(&mut self, expr: Option<&'a ast::Expr>) -> Option<Box<Expr<'a>>> {
+        expr.map(|e| self.boxed(e))
+    }
+
+    pub fn stmt<'a>(&mut self, stmt: &'a ast::Stmt) -> Stmt<'a> {
+        let kind = match stmt {
+            ast::Stmt::FunctionDef(f) => StmtKind::FunctionDef(FunctionDef {
+                name: self.intern(&f.name),
+                is_async: false,
+                params: self.params(&f.args),
+                returns: self.opt_expr(f.returns.as_deref()),
+                decorators: self.exprs(&f.decorator_list),
+                type_params: self.type_params(&f.type_params),
+                body: self.body(&f.body),
+                type_comment: f.type_comment.clone(),
+            }),
+            ast::Stmt::AsyncFunctionDef(f) => StmtKind::FunctionDef(FunctionDef {
+                name: self.intern(&f.name),
+                is_async: true,
+                params: self.params(&f.args),
+                returns: self.opt_expr(f.returns.as_deref()),
+                decorators: self.exprs(&f.decorator_list),
+                type_params: self.type_params(&f.type_params),
+                body: self.body(&f.body),
+                type_comment: f.type_comment.clone(),
+            }),
+            ast::Stmt::ClassDef(c) => StmtKind::ClassDef(ClassDef {
+                name: self.intern(&c.name),
+                bases: self.exprs(&c.bases),
+                keywords: self.keywords(&c.keywords),
+                decorators: self.exprs(&c.decorator_list),
+                type_params: self.type_params(&c.type_params),
+                body: self.body(&c.body),
+            }),
+            ast::Stmt::Return(r) => StmtKind::Return(self.opt_expr(r.value.as_deref())),
+            ast::Stmt::Delete(d) => StmtKind::Delete(self.exprs(&d.targets)),
+            ast::Stmt::Assign(a) => StmtKind::Assign {
+                targets: self.exprs(&a.targets),
+                value: self.expr(&a.value),
+                type_comment: a.type_comment.clone(),
+            },
+            ast::Stmt::TypeAlias(t) => StmtKind::TypeAlias {
+                name: self.expr(&t.name),
+                type_params: self.type_params(&t.type_params),
+                value: self.expr(&t.value),
+            },
+            ast::Stmt::AugAssign(a) => StmtKind::AugAssign {
+                target: self.expr(&a.target),
+                op: a.op,
+                value: self.expr(&a.value),
+            },
+            ast::Stmt::AnnAssign(a) => StmtKind::AnnAssign {
+                target: self.expr(&a.target),
+                annotation: self.expr(&a.annotation),
+                value: self.opt_expr(a.value.as_deref()),
+                simple: a.simple,
+            },
+            ast::Stmt::For(f) => StmtKind::For {
+                is_async: false,
+                target: self.expr(&f.target),
+                iter: self.expr(&f.iter),
+                body: self.body(&f.body),
+                orelse: self.body(&f.orelse),
+                type_comment: f.type_comment.clone(),
+            },
+            ast::Stmt::AsyncFor(f) => StmtKind::For {
+                is_async: true,
+                target: self.expr(&f.target),
+                iter: self.expr(&f.iter),
+                body: self.body(&f.body),
+                orelse: self.body(&f.orelse),
+                type_comment: f.type_comment.clone(),
+            },
+            ast::Stmt::While(w) => StmtKind::While {
+                test: self.expr(&w.test),
+                body: self.body(&w.body),
+                orelse: self.body(&w.orelse),
+            },
+            ast::Stmt::If(i) => StmtKind::If {
+                test: self.expr(&i.test),
+                body: self.body(&i.body),
+                orelse: self.body(&i.orelse),
+            },
+            ast::Stmt::With(w) => StmtKind::With {
+                is_async: false,
+                items: self.with_items(&w.items),
+                body: self.body(&w.body),
+                type_comment: w.type_comment.clone(),
+            },
+            ast::Stmt::AsyncWith(w) => StmtKind::With {
+                is_async: true,
+                items: self.with_items(&w.items),
+                body: self.body(&w.body),
+                type_comment: w.type_comment.clone(),
+            },
+            ast::Stmt::Match(m) => StmtKind::Match {
+                subject: self.expr(&m.subject),
+                cases: m.cases.iter().map(|case| MatchCase {
+                    pattern: &case.pattern,
+                    guard: self.opt_expr(case.guard.as_deref()),
+                    body: self.body(&case.body),
+                }).collect(),
+            },
+            ast::Stmt::Raise(r) => StmtKind::Raise {
+                exc: self.opt_expr(r.exc.as_deref()),
+                cause: self.opt_expr(r.cause.as_deref()),
+            },
+            ast::Stmt::Try(t) => StmtKind::Try {
+                is_star: false,
+                body: self.body(&t.body),
+                handlers: self.handlers(&t.handlers),
+                orelse: self.body(&t.orelse),
+                finalbody: self.body(&t.finalbody),
+            },
+            ast::Stmt::TryStar(t) => StmtKind::Try {
+                is_star: true,
+                body: self.body(&t.body),
+                handlers: self.handlers(&t.handlers),
+                orelse: self.body(&t.orelse),
+                finalbody: self.body(&t.finalbody),
+            },
+            ast::Stmt::Assert(a) => StmtKind::Assert {
+                test: self.expr(&a.test),
+                msg: self.opt_expr(a.msg.as_deref()),
+            },
+            ast::Stmt::Import(i) => StmtKind::Import(self.aliases(&i.names)),
+            ast::Stmt::ImportFrom(i) => StmtKind::ImportFrom {
+                module: i.module.as_ref().map(|m| self.intern(m)),
+                names: self.aliases(&i.names),
+                level: i.level.map(|level| level.to_u32()).unwrap_or(0),
+            },
+            ast::Stmt::Global(g) => StmtKind::Global(g.names.iter().map(|n| self.intern(n)).collect()),
+            ast::Stmt::Nonlocal(n) => StmtKind::Nonlocal(n.names.iter().map(|n| self.intern(n)).collect()),
+            ast::Stmt::Expr(e) => StmtKind::Expr(self.expr(&e.value)),
+            ast::Stmt::Pass(_) => StmtKind::Pass,
+            ast::Stmt::Break(_) => StmtKind::Break,
+            ast::Stmt::Continue(_) => StmtKind::Continue,
+        };
+        Stmt { kind, span: self.span(stmt.range()), node: stmt }
+    }
+
+    pub fn expr<'a>(&mut self, expr: &'a ast::Expr) -> Expr<'a> {
+        let kind = match expr {
+            ast::Expr::BoolOp(b) => ExprKind::BoolOp { op: b.op, values: self.exprs(&b.values) },
+            ast::Expr::NamedExpr(n) => ExprKind::NamedExpr {
+                target: self.boxed(&n.target),
+                value: self.boxed(&n.value),
+            },
+            ast::Expr::BinOp(b) => ExprKind::BinOp {
+                left: self.boxed(&b.left),
+                op: b.op,
+                right: self.boxed(&b.right),
+            },
+            ast::Expr::UnaryOp(u) => ExprKind::UnaryOp { op: u.op, operand: self.boxed(&u.operand) },
+            ast::Expr::Lambda(l) => ExprKind::Lambda { params: self.params(&l.args), body: self.boxed(&l.body) },
+            ast::Expr::IfExp(i) => ExprKind::IfExp {
+                test: self.boxed(&i.test),
+                body: self.boxed(&i.body),
+                orelse: self.boxed(&i.orelse),
+            },
+            ast::Expr::Dict(d) => ExprKind::Dict {
+                keys: d.keys.iter().map(|key| self.opt_expr(key.as_ref())).collect(),
+                values: self.exprs(&d.values),
+            },
+            ast::Expr::Set(s) => ExprKind::Set(self.exprs(&s.elts)),
+            ast::Expr::ListComp(c) => self.comprehension(ComprehensionKind::List, &c.elt, None, &c.generators),
+            ast::Expr::SetComp(c) => self.comprehension(ComprehensionKind::Set, &c.elt, None, &c.generators),
+            ast::Expr::DictComp(c) => {
+                self.comprehension(ComprehensionKind::Dict, &c.key, Some(&c.value), &c.generators)
+            }
+            ast::Expr::GeneratorExp(c) => {
+                self.comprehension(ComprehensionKind::Generator, &c.elt, None, &c.generators)
+            }
+            ast::Expr::Await(a) => ExprKind::Await(self.boxed(&a.value)),
+            ast::Expr::Yield(y) => ExprKind::Yield(self.opt_boxed(y.value.as_deref())),
+            ast::Expr::YieldFrom(y) => ExprKind::YieldFrom(self.boxed(&y.value)),
+            ast::Expr::Compare(c) => ExprKind::Compare {
+                left: self.boxed(&c.left),
+                ops: c.ops.clone(),
+                comparators: self.exprs(&c.comparators),
+            },
+            ast::Expr::Call(c) => ExprKind::Call {
+                func: self.boxed(&c.func),
+                args: self.exprs(&c.args),
+                keywords: self.keywords(&c.keywords),
+            },
+            ast::Expr::FormattedValue(f) => ExprKind::FormattedValue {
+                value: self.boxed(&f.value),
+                conversion: f.conversion.to_byte().map(char::from),
+                format_spec: self.opt_boxed(f.format_spec.as_deref()),
+            },
+            ast::Expr::JoinedStr(j) => ExprKind::JoinedStr(self.exprs(&j.values)),
+            ast::Expr::Constant(c) => ExprKind::Constant(literal(&c.value)),
+            ast::Expr::Attribute(a) => ExprKind::Attribute { value: self.boxed(&a.value), attr: self.intern(&a.attr) },
+            ast::Expr::Subscript(s) => ExprKind::Subscript { value: self.boxed(&s.value), slice: self.boxed(&s.slice) },
+            ast::Expr::Starred(s) => ExprKind::Starred(self.boxed(&s.value)),
+            ast::Expr::Name(n) => ExprKind::Name(self.intern(&n.id)),
+            ast::Expr::List(l) => ExprKind::List(self.exprs(&l.elts)),
+            ast::Expr::Tuple(t) => ExprKind::Tuple(self.exprs(&t.elts)),
+            ast::Expr::Slice(s) => ExprKind::Slice {
+                lower: self.opt_boxed(s.lower.as_deref()),
+                upper: self.opt_boxed(s.upper.as_deref()),
+                step: self.opt_boxed(s.step.as_deref()),
+            },
+        };
+        Expr { kind, span: self.span(expr.range()), node: expr }
+    }
+
+    fn comprehension<'a>(
+        &mut self,
+        kind: ComprehensionKind,
+        elt: &'a ast::Expr,
+        value: Option<&'a ast::Expr>,
+        generators: &'a [ast::Comprehension],
+    ) -> ExprKind<'a> {
+        ExprKind::Comprehension {
+            kind,
+            elt: self.boxed(elt),
+            value: self.opt_boxed(value),
+            generators: generators.iter().map(|g| Comprehension {
+                target: self.expr(&g.target),
+                iter: self.expr(&g.iter),
+                ifs: self.exprs(&g.ifs),
+                is_async: g.is_async,
+            }).collect(),
+        }
+    }
+
+    fn params<'a>(&mut self, args: &'a ast::Arguments) -> Vec<Param<'a>> {
+        let mut params = Vec::new();
+        for arg in &args.posonlyargs {
+            params.push(self.param(&arg.def, arg.default.as_deref(), ParamKind::PositionalOnly));
+        }
+        for arg in &args.args {
+            params.push(self.param(&arg.def, arg.default.as_deref(), ParamKind::Positional));
+        }
+        if let Some(vararg) = &args.vararg {
+            params.push(self.param(vararg, None, ParamKind::VarArgs));
+        }
+        for arg in &args.kwonlyargs {
+            params.push(self.param(&arg.def, arg.default.as_deref(), ParamKind::KeywordOnly));
+        }
+        if let Some(kwarg) = &args.kwarg {
+            params.push(self.param(kwarg, None, ParamKind::VarKeywords));
+        }
+        params
+    }
+
+    fn param<'a>(&mut self, arg: &'a ast::Arg, default: Option<&'a ast::Expr>, kind: ParamKind) -> Param<'a> {
+        Param {
+            name: self.intern(&arg.arg),
+            kind,
+            annotation: self.opt_expr(arg.annotation.as_deref()),
+            default: self.opt_expr(default),
+            type_comment: arg.type_comment.clone(),
+            span: self.span(arg.range),
+            node: arg,
+        }
+    }
+
+    fn type_params(&mut self, params: &[ast::TypeParam]) -> Vec<Name> {
+        params.iter().map(|param| match param {
+            ast::TypeParam::TypeVar(p) => self.intern(&p.name),
+            ast::TypeParam::ParamSpec(p) => self.intern(&p.name),
+            ast::TypeParam::TypeVarTuple(p) => self.intern(&p.name),
+        }).collect()
+    }
+
+    fn keywords<'a>(&mut self, keywords: &'a [ast::Keyword]) -> Vec<Keyword<'a>> {
+        keywords.iter().map(|k| Keyword {
+            arg: k.arg.as_ref().map(|arg| self.intern(arg)),
+            value: self.expr(&k.value),
+        }).collect()
+    }
+
+    fn with_items<'a>(&mut self, items: &'a [ast::WithItem]) -> Vec<WithItem<'a>> {
+        items.iter().map(|item| WithItem {
+            context: self.expr(&item.context_expr),
+            target: self.opt_expr(item.optional_vars.as_deref()),
+        }).collect()
+    }
+
+    fn handlers<'a>(&mut self, handlers: &'a [ast::ExceptHandler]) -> Vec<Handler<'a>> {
+        handlers.iter().map(|handler| {
+            let ast::ExceptHandler::ExceptHandler(h) = handler;
+            Handler {
+                type_: self.opt_expr(h.type_.as_deref()),
+                name: h.name.as_ref().map(|n| self.intern(n)),
+                body: self.body(&h.body),
+                span: self.span(h.range),
+            }
+        }).collect()
+    }
+
+    fn aliases(&mut self, aliases: &[ast::Alias]) -> Vec<Alias> {
+        aliases.iter().map(|a| Alias {
+            name: self.intern(&a.name),
+            asname: a.asname.as_ref().map(|n| self.intern(n)),
+            span: self.span(a.range),
+        }).collect()
+    }
+}
+
+fn literal(constant: &ast::Constant) -> Literal {
+    match constant {
+        ast::Constant::None => Literal::None,
+        ast::Constant::Bool(b) => Literal::Bool(*b),
+        ast::Constant::Str(s) => Literal::Str(s.clone()),
+        ast::Constant::Bytes(b) => Literal::Bytes(b.clone()),
+        ast::Constant::Int(i) => Literal::Int(i.to_string()),
+        ast::Constant::Float(f) => Literal::Float(*f),
+        ast::Constant::Complex { real, imag } => Literal::Complex { real: *real, imag: *imag },
+        ast::Constant::Ellipsis => Literal::Ellipsis,
+        ast::Constant::Tuple(items) => Literal::Tuple(items.iter().map(literal).collect()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::frontend::parse_module;
+
+    #[test]
+    fn test_lowering_resolves_spans_and_interns_names() {
+        let source = "def f(a, *, b: int = 1):\n    return a + a\n";
+        let module = parse_module(source).unwrap();
+        let index = LineIndex::new(source);
+        let lowered = lower_module(&module, Some(&index));
+
+        let StmtKind::FunctionDef(func) = &lowered.body[0].kind else { panic!("expected a def") };
+        let kinds: Vec<_> = func.params.iter().map(|p| (p.name.as_ref(), p.kind)).collect();
+        assert_eq!(kinds, vec![("a", ParamKind::Positional), ("b", ParamKind::KeywordOnly)]);
+
+        let StmtKind::Return(Some(ret)) = &func.body[0].kind else { panic!("expected a return") };
+        assert_eq!(ret.span.location, SourceLocation::new(2, 11, 2, 16));
+        let ExprKind::BinOp { left, right, .. } = &ret.kind else { panic!("expected a binop") };
+        let (ExprKind::Name(l), ExprKind::Name(r)) = (&left.kind, &right.kind) else { panic!("expected names") };
+        assert!(Arc::ptr_eq(l, r));
+    }
+}
@@ -1,7 +1,12 @@
+pub mod docstrings;
+pub mod ir;
 pub mod location;
+pub mod lower;
 pub mod visitor;
 pub mod walker;
 
+pub use docstrings::Docstring;
 pub use visitor::AstVisitor;
 pub use walker::DefaultWalker;
 pub use location::*;
+pub use lower::{lower_expr, lower_module, Lowerer};
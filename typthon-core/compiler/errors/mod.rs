@@ -1,6 +1,129 @@
-use crate::compiler::types::Type;
+use crate::compiler::types::{KeywordMismatch, Type};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::fmt;
 
+pub mod render;
+
+/// Stable diagnostic codes, for configuration and tooling
+///
+/// Codes are never reused or renumbered; new categories get the next number.
+pub mod codes {
+    pub const TYPE_MISMATCH: &str = "TYP001";
+    pub const UNDEFINED_VARIABLE: &str = "TYP002";
+    pub const UNDEFINED_FUNCTION: &str = "TYP003";
+    pub const ARG_COUNT: &str = "TYP004";
+    pub const ARG_TYPE: &str = "TYP005";
+    pub const INVALID_KEYWORD: &str = "TYP006";
+    pub const MISSING_ARGUMENT: &str = "TYP007";
+    pub const RETURN_TYPE: &str = "TYP008";
+    pub const MISSING_RETURN: &str = "TYP009";
+    pub const NOT_CALLABLE: &str = "TYP010";
+    pub const INVALID_SUBSCRIPT: &str = "TYP011";
+    pub const INVALID_ATTRIBUTE: &str = "TYP012";
+    pub const CIRCULAR_DEPENDENCY: &str = "TYP013";
+    pub const CONSTRAINT_VIOLATION: &str = "TYP014";
+    pub const VARIANCE: &str = "TYP015";
+    pub const INFINITE_TYPE: &str = "TYP016";
+    /// Strict mode: unannotated parameter or return
+    pub const MISSING_ANNOTATION: &str = "TYP017";
+    /// Strict mode: a name or return bound to an unknown type
+    pub const IMPLICIT_ANY: &str = "TYP018";
+    pub const UNSUPPORTED_OPERAND: &str = "TYP019";
+    pub const UNHASHABLE: &str = "TYP020";
+    /// Import crossing the configured architecture layers
+    pub const LAYER_VIOLATION: &str = "TYP021";
+    /// Annotation that can't be evaluated, e.g. a non-constant `Literal` argument
+    pub const INVALID_ANNOTATION: &str = "TYP022";
+    /// Strict optional: using a value that may be `None` as if it weren't
+    pub const POSSIBLY_NONE: &str = "TYP023";
+    /// Statement no path reaches; a warning
+    pub const UNREACHABLE_CODE: &str = "TYP024";
+    /// Call matching none of a function's `@overload` signatures
+    pub const NO_MATCHING_OVERLOAD: &str = "TYP025";
+    /// Implementation of an overloaded function that doesn't fit one of its overloads
+    pub const OVERLOAD_IMPLEMENTATION: &str = "TYP026";
+    /// Function with effects its `@effects(...)` declaration doesn't allow
+    pub const EFFECT_VIOLATION: &str = "TYP027";
+    /// Import whose name nothing uses, or that repeats an earlier one; a warning
+    pub const UNUSED_IMPORT: &str = "TYP028";
+    /// Function-local variable nothing reads; a warning
+    pub const UNUSED_VARIABLE: &str = "TYP029";
+    /// Parameter its function never reads; a warning
+    pub const UNUSED_ARGUMENT: &str = "TYP030";
+    /// `str.format`, `%` or f-string placeholders that don't fit their arguments
+    pub const STRING_FORMAT: &str = "TYP031";
+    /// Function-local variable read before anything assigns it
+    pub const UNBOUND_LOCAL: &str = "TYP032";
+
+    /// Every code with its kebab-case name, in code order
+    pub const ALL: &[(&str, &str)] = &[
+        (TYPE_MISMATCH, "type-mismatch"),
+        (UNDEFINED_VARIABLE, "undefined-variable"),
+        (UNDEFINED_FUNCTION, "undefined-function"),
+        (ARG_COUNT, "arg-count"),
+        (ARG_TYPE, "arg-type"),
+        (INVALID_KEYWORD, "invalid-keyword"),
+        (MISSING_ARGUMENT, "missing-argument"),
+        (RETURN_TYPE, "return-type"),
+        (MISSING_RETURN, "missing-return"),
+        (NOT_CALLABLE, "not-callable"),
+        (INVALID_SUBSCRIPT, "invalid-subscript"),
+        (INVALID_ATTRIBUTE, "invalid-attribute"),
+        (CIRCULAR_DEPENDENCY, "circular-dependency"),
+        (CONSTRAINT_VIOLATION, "constraint-violation"),
+        (VARIANCE, "variance"),
+        (INFINITE_TYPE, "infinite-type"),
+        (MISSING_ANNOTATION, "missing-annotation"),
+        (IMPLICIT_ANY, "implicit-any"),
+        (UNSUPPORTED_OPERAND, "unsupported-operand"),
+        (UNHASHABLE, "unhashable"),
+        (LAYER_VIOLATION, "layer-violation"),
+        (INVALID_ANNOTATION, "invalid-annotation"),
+        (POSSIBLY_NONE, "possibly-none"),
+        (UNREACHABLE_CODE, "unreachable-code"),
+        (NO_MATCHING_OVERLOAD, "no-matching-overload"),
+        (OVERLOAD_IMPLEMENTATION, "overload-implementation"),
+        (EFFECT_VIOLATION, "effect-violation"),
+        (UNUSED_IMPORT, "unused-import"),
+        (UNUSED_VARIABLE, "unused-variable"),
+        (UNUSED_ARGUMENT, "unused-argument"),
+        (STRING_FORMAT, "string-format"),
+        (UNBOUND_LOCAL, "unbound-local"),
+    ];
+
+    /// Name of `code`, e.g. `type-mismatch` for `TYP001`
+    pub fn name(code: &str) -> Option<&'static str> {
+        ALL.iter().find(|(c, _)| *c == code).map(|(_, name)| *name)
+    }
+}
+
+/// How serious a diagnostic is; only errors fail a check by default
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Error,
+    Warning,
+    Note,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Note => "note",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SourceLocation {
     pub line: usize,
@@ -32,7 +155,10 @@ pub enum ErrorKind {
     UndefinedFunction { name: String },
     InvalidArgCount { expected: usize, found: usize },
     InvalidArgType { param: String, expected: String, found: String },
+    InvalidKeyword { name: String, reason: KeywordMismatch },
+    MissingArgument { name: String },
     InvalidReturnType { expected: String, found: String },
+    MissingReturn { function: String, expected: String },
     NonCallable { ty: String },
     InvalidSubscript { container: String, key: String },
     InvalidAttribute { ty: String, attr: String },
@@ -40,6 +166,64 @@ pub enum ErrorKind {
     ConstraintViolation { constraint: String, value: String },
     VarianceError { context: String },
     InfiniteType { var: String, ty: String },
+    /// `operation` describes the use, e.g. `attribute 'upper'` or `operator '+'`
+    PossiblyNone { name: String, operation: String },
+    /// `after` describes the statement control never gets past, e.g. `'return'`
+    UnreachableCode { after: String },
+    /// `candidates` are the overload signatures tried, in declaration order
+    NoMatchingOverload { function: String, arguments: Vec<String>, candidates: Vec<String> },
+    /// `overload` is 1-based, in declaration order
+    OverloadImplementation { function: String, overload: usize, reason: String },
+    /// `inferred` lists only the effects `declared` doesn't allow
+    EffectViolation { function: String, declared: String, inferred: String },
+    UnusedImport { name: String },
+    UnusedVariable { name: String },
+    UnusedArgument { name: String },
+    UnboundLocal { name: String },
+}
+
+impl ErrorKind {
+    /// Stable code of this kind of error, e.g. `TYP001` for a type mismatch
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::TypeMismatch { .. } => codes::TYPE_MISMATCH,
+            Self::UndefinedVariable { .. } => codes::UNDEFINED_VARIABLE,
+            Self::UndefinedFunction { .. } => codes::UNDEFINED_FUNCTION,
+            Self::InvalidArgCount { .. } => codes::ARG_COUNT,
+            Self::InvalidArgType { .. } => codes::ARG_TYPE,
+            Self::InvalidKeyword { .. } => codes::INVALID_KEYWORD,
+            Self::MissingArgument { .. } => codes::MISSING_ARGUMENT,
+            Self::InvalidReturnType { .. } => codes::RETURN_TYPE,
+            Self::MissingReturn { .. } => codes::MISSING_RETURN,
+            Self::NonCallable { .. } => codes::NOT_CALLABLE,
+            Self::InvalidSubscript { .. } => codes::INVALID_SUBSCRIPT,
+            Self::InvalidAttribute { .. } => codes::INVALID_ATTRIBUTE,
+            Self::CircularDependency { .. } => codes::CIRCULAR_DEPENDENCY,
+            Self::ConstraintViolation { .. } => codes::CONSTRAINT_VIOLATION,
+            Self::VarianceError { .. } => codes::VARIANCE,
+            Self::InfiniteType { .. } => codes::INFINITE_TYPE,
+            Self::PossiblyNone { .. } => codes::POSSIBLY_NONE,
+            Self::UnreachableCode { .. } => codes::UNREACHABLE_CODE,
+            Self::NoMatchingOverload { .. } => codes::NO_MATCHING_OVERLOAD,
+            Self::OverloadImplementation { .. } => codes::OVERLOAD_IMPLEMENTATION,
+            Self::EffectViolation { .. } => codes::EFFECT_VIOLATION,
+            Self::UnusedImport { .. } => codes::UNUSED_IMPORT,
+            Self::UnusedVariable { .. } => codes::UNUSED_VARIABLE,
+            Self::UnusedArgument { .. } => codes::UNUSED_ARGUMENT,
+            Self::UnboundLocal { .. } => codes::UNBOUND_LOCAL,
+        }
+    }
+
+    /// Severity errors of this kind are reported with unless overridden
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::UnreachableCode { .. }
+            | Self::UnusedImport { .. }
+            | Self::UnusedVariable { .. }
+            | Self::UnusedArgument { .. } => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
 }
 
 impl fmt::Display for ErrorKind {
@@ -60,9 +244,20 @@ impl fmt::Display for ErrorKind {
             Self::InvalidArgType { param, expected, found } => {
                 write!(f, "Invalid type for parameter '{}': expected {}, found {}", param, expected, found)
             }
+            Self::InvalidKeyword { name, reason } => match reason {
+                KeywordMismatch::Unexpected => write!(f, "Unexpected keyword argument '{}'", name),
+                KeywordMismatch::MultipleValues => write!(f, "Multiple values for argument '{}'", name),
+                KeywordMismatch::PositionalOnly => write!(f, "Positional-only argument '{}' passed as keyword", name),
+            },
+            Self::MissingArgument { name } => {
+                write!(f, "Missing required argument '{}'", name)
+            }
             Self::InvalidReturnType { expected, found } => {
                 write!(f, "Invalid return type: expected {}, found {}", expected, found)
             }
+            Self::MissingReturn { function, expected } => {
+                write!(f, "Missing return: '{}' is declared to return {} but can end without returning", function, expected)
+            }
             Self::NonCallable { ty } => {
                 write!(f, "Type {} is not callable", ty)
             }
@@ -84,6 +279,36 @@ impl fmt::Display for ErrorKind {
             Self::InfiniteType { var, ty } => {
                 write!(f, "Infinite type: {} = {}", var, ty)
             }
+            Self::PossiblyNone { name, operation } => {
+                write!(f, "'{}' is possibly None: {} is not supported on None", name, operation)
+            }
+            Self::UnreachableCode { after } => {
+                write!(f, "Unreachable code after {}", after)
+            }
+            Self::NoMatchingOverload { function, arguments, candidates } => {
+                write!(
+                    f, "No overload of '{}' matches argument types ({}); tried {}",
+                    function, arguments.join(", "), candidates.join(", ")
+                )
+            }
+            Self::OverloadImplementation { function, overload, reason } => {
+                write!(f, "Implementation of '{}' is incompatible with overload {}: {}", function, overload, reason)
+            }
+            Self::EffectViolation { function, declared, inferred } => {
+                write!(f, "'{}' is declared with effects {} but has effects {}", function, declared, inferred)
+            }
+            Self::UnusedImport { name } => {
+                write!(f, "'{}' is imported but never used", name)
+            }
+            Self::UnusedVariable { name } => {
+                write!(f, "Local variable '{}' is assigned but never used", name)
+            }
+            Self::UnusedArgument { name } => {
+                write!(f, "Parameter '{}' is never used", name)
+            }
+            Self::UnboundLocal { name } => {
+                write!(f, "Local variable '{}' is read before it's assigned", name)
+            }
         }
     }
 }
@@ -91,6 +316,7 @@ impl fmt::Display for ErrorKind {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TypeError {
     pub kind: ErrorKind,
+    pub severity: Severity,
     pub location: SourceLocation,
     pub file: String,
     pub suggestions: Vec<String>,
@@ -99,6 +325,7 @@ pub struct TypeError {
 impl TypeError {
     pub fn new(kind: ErrorKind, location: SourceLocation) -> Self {
         Self {
+            severity: kind.severity(),
             kind,
             location,
             file: String::new(),
@@ -106,6 +333,11 @@ impl TypeError {
         }
     }
 
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
     pub fn with_file(mut self, file: String) -> Self {
         self.file = file;
         self
@@ -182,9 +414,11 @@ impl fmt::Display for TypeError {
         }
         write!(
             f,
-            "{}:{}: {}",
+            "{}:{}: {}[{}]: {}",
             self.location.line,
             self.location.col,
+            self.severity,
+            self.kind.code(),
             self.kind
         )?;
 
@@ -199,39 +433,90 @@ impl fmt::Display for TypeError {
     }
 }
 
+/// Number of collected diagnostics of each severity
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SeverityCounts {
+    pub errors: usize,
+    pub warnings: usize,
+    pub notes: usize,
+}
+
+impl SeverityCounts {
+    pub fn add(&mut self, severity: Severity) {
+        match severity {
+            Severity::Error => self.errors += 1,
+            Severity::Warning => self.warnings += 1,
+            Severity::Note => self.notes += 1,
+        }
+    }
+
+    pub fn get(&self, severity: Severity) -> usize {
+        match severity {
+            Severity::Error => self.errors,
+            Severity::Warning => self.warnings,
+            Severity::Note => self.notes,
+        }
+    }
+}
+
 /// Error collector for gathering multiple errors during type checking
 pub struct ErrorCollector {
     errors: Vec<TypeError>,
+    /// Of the collected `errors`
+    counts: SeverityCounts,
     max_errors: usize,
+    /// Codes whose errors are dropped on `add`
+    disabled_codes: HashSet<String>,
 }
 
 impl ErrorCollector {
     pub fn new() -> Self {
-        Self {
-            errors: Vec::new(),
-            max_errors: 100,
-        }
+        Self::with_max(100)
     }
 
     pub fn with_max(max_errors: usize) -> Self {
         Self {
             errors: Vec::new(),
+            counts: SeverityCounts::default(),
             max_errors,
+            disabled_codes: HashSet::new(),
         }
     }
 
+    /// Drop errors with any of `codes` (e.g. `TYP005`) instead of collecting them
+    pub fn with_disabled_codes(mut self, codes: HashSet<String>) -> Self {
+        self.disabled_codes = codes;
+        self
+    }
+
     pub fn add(&mut self, error: TypeError) {
+        if self.disabled_codes.contains(error.kind.code()) {
+            return;
+        }
         if self.errors.len() < self.max_errors {
+            self.counts.add(error.severity);
             self.errors.push(error);
         }
     }
 
+    /// Whether any error-severity diagnostic was collected; warnings and
+    /// notes don't count
     pub fn has_errors(&self) -> bool {
-        !self.errors.is_empty()
+        self.counts.errors > 0
     }
 
+    /// Number of error-severity diagnostics collected
     pub fn error_count(&self) -> usize {
-        self.errors.len()
+        self.counts.errors
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.counts.warnings
+    }
+
+    /// Collected diagnostics of each severity
+    pub fn counts(&self) -> SeverityCounts {
+        self.counts
     }
 
     pub fn errors(&self) -> &[TypeError] {
@@ -244,6 +529,7 @@ impl ErrorCollector {
 
     pub fn clear(&mut self) {
         self.errors.clear();
+        self.counts = SeverityCounts::default();
     }
 }
 
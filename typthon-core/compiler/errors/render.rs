@@ -0,0 +1,219 @@
+//! Rendering diagnostics with a source excerpt
+//!
+//! ```text
+//! error[TYP001]: Type mismatch: cannot assign str to variable 'count' of type int
+//!  --> main.py:5:14
+//!   |
+//! 5 | count: int = "three"
+//!   |              ^~~~~~~
+//!   = help: use int() to convert the string
+//! ```
+//!
+//! The header column is 1-based, as editors expect; `SourceLocation`
+//! columns are 0-based byte offsets into the line. Spans over more than one
+//! line show the first and last line, with `...` for any in between.
+
+use super::{Severity, SourceLocation, TypeError};
+use std::fmt::Write;
+
+const RESET: &str = "\x1b[0m";
+const GUTTER: &str = "\x1b[1;34m";
+const HELP: &str = "\x1b[1;36m";
+
+/// What a rendered diagnostic says and where
+#[derive(Debug, Clone)]
+pub struct Diagnostic<'a> {
+    /// Shown in the header; left out when empty
+    pub file: &'a str,
+    pub severity: Severity,
+    pub code: Option<&'a str>,
+    pub message: &'a str,
+    /// 1-based lines; line 0 for diagnostics about the module as a whole,
+    /// which get no excerpt
+    pub location: SourceLocation,
+    /// Shown as `help:` lines, in order
+    pub suggestions: &'a [String],
+}
+
+/// Render `error` against `source`, the text of the file it's in
+pub fn render_error(error: &TypeError, source: &str, color: bool) -> String {
+    let message = error.kind.to_string();
+    let diagnostic = Diagnostic {
+        file: &error.file,
+        severity: error.severity,
+        code: Some(error.kind.code()),
+        message: &message,
+        location: error.location.clone(),
+        suggestions: &error.suggestions,
+    };
+    render(&diagnostic, source, color)
+}
+
+/// Render `diagnostic` against `source`, the text of the file it's in;
+/// the result ends with a newline
+pub fn render(diagnostic: &Diagnostic, source: &str, color: bool) -> String {
+    let paint = Paint { color };
+    let severity_style = match diagnostic.severity {
+        Severity::Error => "\x1b[1;31m",
+        Severity::Warning => "\x1b[1;33m",
+        Severity::Note => "\x1b[1;36m",
+    };
+    let marks = |line: &str, start: usize, end: usize| {
+        let (indent, marks) = underline(line, start, end);
+        format!("{}{}", " ".repeat(indent), paint.apply(severity_style, &marks))
+    };
+
+    let mut out = String::new();
+    let label = match diagnostic.code {
+        Some(code) => format!("{}[{}]", diagnostic.severity, code),
+        None => diagnostic.severity.to_string(),
+    };
+    let _ = writeln!(out, "{}: {}", paint.apply(severity_style, &label), paint.apply("\x1b[1m", diagnostic.message));
+
+    let location = &diagnostic.location;
+    let lines: Vec<&str> = source.lines().collect();
+    let excerpt = location.line > 0 && location.line <= lines.len();
+    let end_line = location.end_line.clamp(location.line, lines.len().max(location.line));
+    let width = if excerpt { end_line.to_string().len() } else { 1 };
+    let pad = " ".repeat(width);
+
+    if !diagnostic.file.is_empty() || excerpt {
+        let place = match (diagnostic.file.is_empty(), excerpt) {
+            (false, true) => format!("{}:{}:{}", diagnostic.file, location.line, location.col + 1),
+            (false, false) => diagnostic.file.to_string(),
+            (true, _) => format!("{}:{}", location.line, location.col + 1),
+        };
+        let _ = writeln!(out, "{}{} {}", pad, paint.apply(GUTTER, "-->"), place);
+    }
+
+    if excerpt {
+        let gutter = |number: Option<usize>| {
+            let number = number.map_or_else(|| pad.clone(), |n| format!("{:>width$}", n, width = width));
+            paint.apply(GUTTER, &format!("{} |", number))
+        };
+        let _ = writeln!(out, "{}", gutter(None));
+
+        let first = lines[location.line - 1];
+        if end_line == location.line {
+            let end_col = location.end_col.max(location.col);
+            let _ = writeln!(out, "{} {}", gutter(Some(location.line)), first);
+            let _ = writeln!(out, "{} {}", gutter(None), marks(first, location.col, end_col));
+        } else {
+            let last = lines[end_line - 1];
+            let _ = writeln!(out, "{} {}", gutter(Some(location.line)), first);
+            let _ = writeln!(out, "{} {}", gutter(None), marks(first, location.col, first.len()));
+            if end_line > location.line + 1 {
+                let _ = writeln!(out, "{}", paint.apply(GUTTER, "..."));
+            }
+            let indent = last.len() - last.trim_start().len();
+            let _ = writeln!(out, "{} {}", gutter(Some(end_line)), last);
+            let _ = writeln!(out, "{} {}", gutter(None), marks(last, indent, location.end_col));
+        }
+    }
+
+    for suggestion in diagnostic.suggestions {
+        let _ = writeln!(out, "{} {} {}", pad, paint.apply(GUTTER, "="), paint.apply(HELP, &format!("help: {}", suggestion)));
+    }
+    out
+}
+
+/// Indent and marks putting a caret under byte `start` of `line` and
+/// tildes on to byte `end`, aligned by character; a single caret for an
+/// empty span
+fn underline(line: &str, start: usize, end: usize) -> (usize, String) {
+    let chars = |bytes: usize| line.get(..bytes.min(line.len())).map_or(bytes, |prefix| prefix.chars().count());
+    let (start, end) = (chars(start), chars(end));
+    (start, format!("^{}", "~".repeat(end.saturating_sub(start + 1))))
+}
+
+/// ANSI styling, or none
+#[derive(Clone, Copy)]
+struct Paint {
+    color: bool,
+}
+
+impl Paint {
+    fn apply(self, style: &str, text: &str) -> String {
+        if self.color {
+            format!("{}{}{}", style, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::errors::ErrorKind;
+
+    const SOURCE: &str = "\
+def greet(name: str) -> str:
+    return \"hello \" + name
+
+
+count: int = \"three\"
+greet(1)
+";
+
+    fn diagnostic(location: SourceLocation, suggestions: &[String]) -> Diagnostic<'_> {
+        Diagnostic {
+            file: "main.py",
+            severity: Severity::Error,
+            code: Some("TYP001"),
+            message: "Type mismatch: cannot assign str to variable 'count' of type int",
+            location,
+            suggestions,
+        }
+    }
+
+    #[test]
+    fn test_single_line_span() {
+        let suggestions = vec!["use int(\"three\")".to_string()];
+        let rendered = render(&diagnostic(SourceLocation::new(5, 13, 5, 20), &suggestions), SOURCE, false);
+        assert_eq!(rendered, "\
+error[TYP001]: Type mismatch: cannot assign str to variable 'count' of type int
+ --> main.py:5:14
+  |
+5 | count: int = \"three\"
+  |              ^~~~~~~
+  = help: use int(\"three\")
+");
+    }
+
+    #[test]
+    fn test_multi_line_span_elides_the_middle() {
+        let source = "def f(\n    a,\n    b,\n):\n    pass\n";
+        let error = Diagnostic { code: None, message: "bad signature", ..diagnostic(SourceLocation::new(1, 4, 4, 1), &[]) };
+        assert_eq!(render(&error, source, false), "\
+error: bad signature
+ --> main.py:1:5
+  |
+1 | def f(
+  |     ^~
+...
+4 | ):
+  | ^
+");
+        let two_lines = Diagnostic { message: "bad signature", ..diagnostic(SourceLocation::new(1, 0, 2, 6), &[]) };
+        assert!(render(&two_lines, source, false).contains("1 | def f(\n  | ^~~~~~\n2 |     a,\n  |     ^~\n"));
+    }
+
+    #[test]
+    fn test_module_level_error_has_no_excerpt() {
+        let error = TypeError::new(ErrorKind::UnreachableCode { after: "'return'".to_string() }, SourceLocation::default());
+        assert_eq!(render_error(&error, SOURCE, false), "warning[TYP024]: Unreachable code after 'return'\n");
+    }
+
+    #[test]
+    fn test_color() {
+        let rendered = render(&diagnostic(SourceLocation::new(6, 6, 6, 7), &[]), SOURCE, true);
+        assert_eq!(rendered, "\
+\x1b[1;31merror[TYP001]\x1b[0m: \x1b[1mType mismatch: cannot assign str to variable 'count' of type int\x1b[0m
+ \x1b[1;34m-->\x1b[0m main.py:6:7
+\x1b[1;34m  |\x1b[0m
+\x1b[1;34m6 |\x1b[0m greet(1)
+\x1b[1;34m  |\x1b[0m       \x1b[1;31m^\x1b[0m
+");
+    }
+}
@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use crate::compiler::analysis::LintLevel;
+use crate::compiler::errors::find_similar_names;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -20,8 +22,26 @@ pub struct Config {
     #[serde(default)]
     pub plugins: Vec<String>,
 
+    /// Per-code lint levels, e.g. `S001 = "warn"`
+    #[serde(default)]
+    pub lints: BTreeMap<String, LintLevel>,
+
+    #[serde(default)]
+    pub architecture: ArchitectureConfig,
+
+    /// Member signature overrides, e.g. `"builtins.dict.get" = "Callable[[str], int]"`
+    #[serde(default)]
+    pub signatures: BTreeMap<String, String>,
+
     #[serde(default)]
     pub overrides: HashMap<String, OverrideConfig>,
+
+    /// Settings for modules matching a pattern, applied in order by `for_module`
+    #[serde(default)]
+    pub module_overrides: Vec<ModuleOverride>,
+
+    #[serde(default)]
+    pub cache: CacheConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +58,9 @@ pub struct CheckConfig {
     #[serde(default = "default_false")]
     pub allow_any: bool,
 
+    #[serde(default = "default_false")]
+    pub strict_optional: bool,
+
     #[serde(default = "default_true")]
     pub check_variance: bool,
 
@@ -49,6 +72,16 @@ pub struct CheckConfig {
 
     #[serde(default = "default_false")]
     pub warn_unused_ignores: bool,
+
+    #[serde(default = "default_false")]
+    pub warn_unused_variables: bool,
+
+    #[serde(default = "default_false")]
+    pub erase_unknown_decorators: bool,
+
+    /// Files checked in parallel; one per core when unset
+    #[serde(default)]
+    pub workers: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -82,6 +115,18 @@ pub struct ErrorConfig {
 
     #[serde(default)]
     pub format: ErrorFormat,
+
+    /// Error and lint codes never reported
+    #[serde(default)]
+    pub disable: Vec<String>,
+
+    /// When set, only these codes (and errors without one) are reported
+    #[serde(default)]
+    pub enable: Option<Vec<String>>,
+
+    /// Warnings fail the run too
+    #[serde(default = "default_false")]
+    pub warnings_as_errors: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -89,6 +134,7 @@ pub struct ErrorConfig {
 pub enum ErrorFormat {
     Default,
     Json,
+    Sarif,
     Compact,
     Verbose,
 }
@@ -106,6 +152,67 @@ pub struct PathsConfig {
 
     #[serde(default)]
     pub python_path: Vec<String>,
+
+    /// Directories checked when no paths are given on the command line,
+    /// relative to the project root
+    #[serde(default)]
+    pub roots: Vec<PathBuf>,
+}
+
+/// Layering rules between parts of the project
+///
+/// ```toml
+/// [architecture]
+/// layers = [
+///     { name = "ui", paths = ["app/ui/**"] },
+///     { name = "services", paths = ["app/services/**"] },
+///     { name = "db", paths = ["app/db/**"] },
+/// ]
+/// allow = { ui = ["services"], services = ["db"] }
+/// unknown = "unrestricted"
+/// ```
+///
+/// Without `allow`, layers are listed top-down and each may import the ones
+/// below it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArchitectureConfig {
+    #[serde(default)]
+    pub layers: Vec<LayerConfig>,
+
+    /// Layers each layer may import, besides itself
+    #[serde(default)]
+    pub allow: BTreeMap<String, Vec<String>>,
+
+    #[serde(default)]
+    pub unknown: UnknownLayerPolicy,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayerConfig {
+    pub name: String,
+
+    /// Globs relative to the project root
+    pub paths: Vec<String>,
+}
+
+/// Whether layered code may import project files that match no layer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnknownLayerPolicy {
+    #[default]
+    Unrestricted,
+    Forbidden,
+}
+
+/// Results kept between runs, so unchanged files aren't checked again
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Relative to the project root; `.typthon_cache` when unset
+    #[serde(default)]
+    pub dir: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -117,6 +224,50 @@ pub struct OverrideConfig {
     pub infer: Option<InferConfig>,
 }
 
+/// Settings for the modules `module` matches
+///
+/// ```toml
+/// [[tool.typthon.overrides]]
+/// module = "tests.*"
+/// strict = false
+/// disable = ["TYP005"]
+/// ```
+///
+/// A pattern is a dotted module name whose components may be `*`; a trailing
+/// `.*` also matches the package itself, so `tests.*` covers `tests` and
+/// everything under it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModuleOverride {
+    pub module: String,
+
+    #[serde(default)]
+    pub strict: Option<bool>,
+
+    /// Codes not reported in these modules, on top of the project's
+    #[serde(default)]
+    pub disable: Vec<String>,
+
+    /// Replaces the project's `enable` in these modules
+    #[serde(default)]
+    pub enable: Option<Vec<String>>,
+}
+
+impl ModuleOverride {
+    /// Whether the pattern covers the dotted module name `module`
+    pub fn matches(&self, module: &str) -> bool {
+        let pattern: Vec<&str> = self.module.split('.').collect();
+        let module: Vec<&str> = module.split('.').collect();
+        let component = |(pattern, name): (&&str, &&str)| *pattern == "*" || pattern == name;
+
+        match pattern.split_last() {
+            Some((&"*", package)) => {
+                module.len() >= package.len() && package.iter().zip(&module).all(component)
+            }
+            _ => pattern.len() == module.len() && pattern.iter().zip(&module).all(component),
+        }
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -125,7 +276,12 @@ impl Default for Config {
             errors: ErrorConfig::default(),
             paths: PathsConfig::default(),
             plugins: Vec::new(),
+            lints: BTreeMap::new(),
+            architecture: ArchitectureConfig::default(),
+            signatures: BTreeMap::new(),
             overrides: HashMap::new(),
+            module_overrides: Vec::new(),
+            cache: CacheConfig::default(),
         }
     }
 }
@@ -137,10 +293,14 @@ impl Default for CheckConfig {
             strict: false,
             allow_untyped_defs: false,
             allow_any: false,
+            strict_optional: false,
             check_variance: true,
             check_generics: true,
             warn_redundant_casts: false,
             warn_unused_ignores: false,
+            warn_unused_variables: false,
+            erase_unknown_decorators: false,
+            workers: None,
         }
     }
 }
@@ -164,10 +324,19 @@ impl Default for ErrorConfig {
             show_error_codes: true,
             color: true,
             format: ErrorFormat::Default,
+            disable: Vec::new(),
+            enable: None,
+            warnings_as_errors: false,
         }
     }
 }
 
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self { enabled: true, dir: None }
+    }
+}
+
 impl Default for ErrorFormat {
     fn default() -> Self {
         ErrorFormat::Default
@@ -186,6 +355,7 @@ impl Default for PathsConfig {
             ],
             follow_imports: true,
             python_path: Vec::new(),
+            roots: Vec::new(),
         }
     }
 }
@@ -194,13 +364,91 @@ fn default_true() -> bool { true }
 fn default_false() -> bool { false }
 fn default_100() -> usize { 100 }
 
+/// Files a project is configured by, in the order each directory is searched
+const CONFIG_FILES: &[&str] = &[".typyrc", ".typyrc.toml", "typthon.toml", "pyproject.toml"];
+
+/// Keys `[tool.typthon]` and `typthon.toml` take on top of the `.typyrc` sections
+const TOOL_KEYS: &[&str] = &[
+    "strict", "include", "exclude", "python_files", "disable", "enable",
+    "cache_dir", "workers", "warnings_as_errors", "overrides",
+];
+
+/// Sections of a `.typyrc`, also accepted under `[tool.typthon]`
+const SECTIONS: &[&str] = &[
+    "check", "infer", "errors", "paths", "plugins", "lints", "architecture", "signatures", "cache",
+];
+
+/// Keys of a `[[tool.typthon.overrides]]` entry
+const OVERRIDE_KEYS: &[&str] = &["module", "strict", "disable", "enable"];
+
+/// The shorthand keys of `[tool.typthon]`, each setting one field of a section
+#[derive(Deserialize)]
+struct ToolSettings {
+    strict: Option<bool>,
+    include: Option<Vec<String>>,
+    exclude: Option<Vec<String>>,
+    python_files: Option<Vec<PathBuf>>,
+    disable: Option<Vec<String>>,
+    enable: Option<Vec<String>>,
+    cache_dir: Option<PathBuf>,
+    workers: Option<usize>,
+    warnings_as_errors: Option<bool>,
+    #[serde(default)]
+    overrides: Vec<ModuleOverride>,
+}
+
+impl ToolSettings {
+    /// Set on `config`, over what its sections say
+    fn apply(self, config: &mut Config) {
+        if let Some(strict) = self.strict {
+            config.check.strict = strict;
+        }
+        if let Some(include) = self.include {
+            config.paths.include = include;
+        }
+        if let Some(exclude) = self.exclude {
+            config.paths.exclude = exclude;
+        }
+        if let Some(roots) = self.python_files {
+            config.paths.roots = roots;
+        }
+        if let Some(disable) = self.disable {
+            config.errors.disable = disable;
+        }
+        if let Some(enable) = self.enable {
+            config.errors.enable = Some(enable);
+        }
+        if let Some(dir) = self.cache_dir {
+            config.cache.dir = Some(dir);
+        }
+        if let Some(workers) = self.workers {
+            config.check.workers = Some(workers);
+        }
+        if let Some(warnings_as_errors) = self.warnings_as_errors {
+            config.errors.warnings_as_errors = warnings_as_errors;
+        }
+        config.module_overrides.extend(self.overrides);
+    }
+}
+
 impl Config {
-    /// Load configuration from .typyrc file
+    /// Load configuration from a `.typyrc`, `typthon.toml` or `pyproject.toml`
     pub fn load(path: &Path) -> Result<Self, String> {
+        Self::load_if_configured(path)?
+            .ok_or_else(|| format!("No [tool.typthon] table in {}", path.display()))
+    }
+
+    /// Like `load`, but `None` for a `pyproject.toml` without a
+    /// `[tool.typthon]` table
+    fn load_if_configured(path: &Path) -> Result<Option<Self>, String> {
         let content = fs::read_to_string(path)
             .map_err(|e| format!("Failed to read config: {}", e))?;
 
-        Self::parse(&content)
+        match path.file_name().and_then(|name| name.to_str()) {
+            Some("pyproject.toml") => Self::parse_pyproject(&content),
+            Some("typthon.toml") => Self::parse_tool(&content).map(Some),
+            _ => Self::parse(&content).map(Some),
+        }
     }
 
     /// Parse configuration from TOML string
@@ -209,30 +457,124 @@ impl Config {
             .map_err(|e| format!("Failed to parse config: {}", e))
     }
 
+    /// Parse the `[tool.typthon]` table of a `pyproject.toml`; `None` without one
+    pub fn parse_pyproject(content: &str) -> Result<Option<Self>, String> {
+        let document: toml::Table = toml::from_str(content)
+            .map_err(|e| format!("Failed to parse config: {}", e))?;
+
+        match document.get("tool").and_then(|tool| tool.get("typthon")) {
+            Some(toml::Value::Table(table)) => {
+                Self::from_tool_table(table.clone(), "[tool.typthon]", "[[tool.typthon.overrides]]").map(Some)
+            }
+            Some(_) => Err("[tool.typthon] must be a table".to_string()),
+            None => Ok(None),
+        }
+    }
+
+    /// Parse a `typthon.toml`, which holds what `[tool.typthon]` would
+    pub fn parse_tool(content: &str) -> Result<Self, String> {
+        let table: toml::Table = toml::from_str(content)
+            .map_err(|e| format!("Failed to parse config: {}", e))?;
+        Self::from_tool_table(table, "typthon.toml", "[[overrides]]")
+    }
+
+    /// Settings from a `[tool.typthon]`-style table: the shorthand keys,
+    /// over any `.typyrc` sections it also has. Unknown keys are errors,
+    /// suggesting the known key they're closest to
+    fn from_tool_table(table: toml::Table, name: &str, overrides_name: &str) -> Result<Self, String> {
+        let known: Vec<&str> = TOOL_KEYS.iter().chain(SECTIONS).copied().collect();
+        let mut unknown = Self::unknown_keys(&table, &known, name);
+        if let Some(toml::Value::Array(entries)) = table.get("overrides") {
+            for entry in entries.iter().filter_map(toml::Value::as_table) {
+                unknown.extend(Self::unknown_keys(entry, OVERRIDE_KEYS, overrides_name));
+            }
+        }
+        if !unknown.is_empty() {
+            return Err(unknown.join("\n"));
+        }
+
+        let (settings, sections): (toml::Table, toml::Table) = table.into_iter()
+            .partition(|(key, _)| TOOL_KEYS.contains(&key.as_str()));
+        let mut config: Config = toml::Value::Table(sections).try_into()
+            .map_err(|e| format!("Invalid {}: {}", name, e))?;
+        let settings: ToolSettings = toml::Value::Table(settings).try_into()
+            .map_err(|e| format!("Invalid {}: {}", name, e))?;
+        settings.apply(&mut config);
+        Ok(config)
+    }
+
+    /// A message for each key of `table` not in `known`
+    fn unknown_keys(table: &toml::Table, known: &[&str], name: &str) -> Vec<String> {
+        let candidates: Vec<String> = known.iter().map(|key| key.to_string()).collect();
+        table.keys()
+            .filter(|key| !known.contains(&key.as_str()))
+            .map(|key| match find_similar_names(key, &candidates, 2).first() {
+                Some(close) => format!("Unknown key '{}' in {}; did you mean '{}'?", key, name, close),
+                None => format!("Unknown key '{}' in {}", key, name),
+            })
+            .collect()
+    }
+
+    /// Cache directory for a project rooted at `root`
+    pub fn cache_dir(&self, root: &Path) -> PathBuf {
+        root.join(self.cache.dir.as_deref().unwrap_or(Path::new(".typthon_cache")))
+    }
+
     /// Find and load configuration file from current directory or parents
     pub fn discover() -> Self {
-        let mut current = std::env::current_dir().ok();
+        Self::discover_with_root().0
+    }
 
-        while let Some(dir) = current {
-            let config_path = dir.join(".typyrc");
-            if config_path.exists() {
-                if let Ok(config) = Self::load(&config_path) {
-                    return config;
-                }
-            }
+    /// Like `discover`, also returning the project root: the directory
+    /// holding the config file, or the current directory without one
+    pub fn discover_with_root() -> (Self, PathBuf) {
+        let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        Self::discover_from(&cwd)
+    }
 
-            // Try TOML extension
-            let config_path = dir.join(".typyrc.toml");
-            if config_path.exists() {
-                if let Ok(config) = Self::load(&config_path) {
-                    return config;
+    /// Like `discover_with_root`, searching upwards from `start`; an
+    /// invalid config file counts as none
+    pub fn discover_from(start: &Path) -> (Self, PathBuf) {
+        Self::try_discover_from(start).unwrap_or_else(|_| (Self::default(), start.to_path_buf()))
+    }
+
+    /// Like `discover_from`, failing on the first config file that can't be
+    /// loaded. In each directory `.typyrc`, `.typyrc.toml`, `typthon.toml`
+    /// and a `pyproject.toml` with a `[tool.typthon]` table are tried in turn
+    pub fn try_discover_from(start: &Path) -> Result<(Self, PathBuf), String> {
+        for dir in start.ancestors() {
+            for name in CONFIG_FILES {
+                let path = dir.join(name);
+                if !path.is_file() {
+                    continue;
+                }
+                let loaded = Self::load_if_configured(&path)
+                    .map_err(|e| format!("{}: {}", path.display(), e))?;
+                if let Some(config) = loaded {
+                    return Ok((config, dir.to_path_buf()));
                 }
             }
+        }
+
+        Ok((Self::default(), start.to_path_buf()))
+    }
 
-            current = dir.parent().map(|p| p.to_path_buf());
+    /// Settings for the dotted module `module`: the project's, with each
+    /// matching entry of `module_overrides` applied in order
+    pub fn for_module(&self, module: &str) -> Config {
+        let mut config = self.clone();
+
+        for entry in self.module_overrides.iter().filter(|entry| entry.matches(module)) {
+            if let Some(strict) = entry.strict {
+                config.check.strict = strict;
+            }
+            config.errors.disable.extend(entry.disable.iter().cloned());
+            if let Some(enable) = &entry.enable {
+                config.errors.enable = Some(enable.clone());
+            }
         }
 
-        Self::default()
+        config
     }
 
     /// Get configuration for specific file (applying overrides)
@@ -412,6 +754,97 @@ max_errors = 50
         assert_eq!(config.errors.max_errors, 50);
     }
 
+    #[test]
+    fn test_parse_pyproject() {
+        let pyproject = r#"
+[project]
+name = "app"
+
+[tool.ruff]
+line-length = 100
+
+[tool.typthon]
+strict = true
+python_files = ["src", "tests"]
+exclude = ["**/migrations/**"]
+disable = ["typ005"]
+cache_dir = ".cache/typthon"
+workers = 4
+warnings_as_errors = true
+
+[tool.typthon.lints]
+S001 = "warn"
+
+[[tool.typthon.overrides]]
+module = "tests.*"
+strict = false
+disable = ["TYP012"]
+"#;
+
+        let config = Config::parse_pyproject(pyproject).unwrap().unwrap();
+        assert!(config.check.strict);
+        assert_eq!(config.paths.roots, vec![PathBuf::from("src"), PathBuf::from("tests")]);
+        assert_eq!(config.paths.exclude, vec!["**/migrations/**"]);
+        assert_eq!(config.paths.include, PathsConfig::default().include);
+        assert_eq!(config.errors.disable, vec!["typ005"]);
+        assert_eq!(config.cache_dir(Path::new("/p")), PathBuf::from("/p/.cache/typthon"));
+        assert_eq!(config.check.workers, Some(4));
+        assert!(config.errors.warnings_as_errors);
+        assert_eq!(config.lints.get("S001"), Some(&LintLevel::Warn));
+
+        let tests = config.for_module("tests.test_api");
+        assert!(!tests.check.strict);
+        assert_eq!(tests.errors.disable, vec!["typ005", "TYP012"]);
+        let app = config.for_module("app.tests");
+        assert!(app.check.strict);
+        assert_eq!(app.errors.disable, vec!["typ005"]);
+    }
+
+    #[test]
+    fn test_pyproject_without_tool_table() {
+        assert!(Config::parse_pyproject("[tool.black]\nline-length = 88\n").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_module_override_patterns() {
+        let pattern = |module: &str| ModuleOverride { module: module.to_string(), strict: None, disable: Vec::new(), enable: None };
+
+        assert!(pattern("tests.*").matches("tests"));
+        assert!(pattern("tests.*").matches("tests.unit.test_io"));
+        assert!(!pattern("tests.*").matches("testsuite"));
+        assert!(pattern("app.*.models").matches("app.billing.models"));
+        assert!(!pattern("app.*.models").matches("app.billing.sub.models"));
+        assert!(pattern("app.db").matches("app.db"));
+        assert!(!pattern("app.db").matches("app.db.session"));
+    }
+
+    #[test]
+    fn test_unknown_keys_suggest_close_matches() {
+        let error = Config::parse_pyproject("[tool.typthon]\nstirct = true\nfrobnicate = 1\n").unwrap_err();
+        assert_eq!(error, "Unknown key 'frobnicate' in [tool.typthon]\nUnknown key 'stirct' in [tool.typthon]; did you mean 'strict'?");
+
+        let error = Config::parse_tool("[[overrides]]\nmodule = \"tests.*\"\ndisabel = [\"TYP005\"]\n").unwrap_err();
+        assert_eq!(error, "Unknown key 'disabel' in [[overrides]]; did you mean 'disable'?");
+    }
+
+    #[test]
+    fn test_discovery_finds_pyproject_with_tool_table() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let nested = dir.path().join("pkg/sub");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(dir.path().join("pyproject.toml"), "[tool.typthon]\nstrict = true\n").unwrap();
+        // Without a [tool.typthon] table, the search carries on upwards
+        fs::write(dir.path().join("pkg/pyproject.toml"), "[project]\nname = \"pkg\"\n").unwrap();
+
+        let (config, root) = Config::try_discover_from(&nested).unwrap();
+        assert!(config.check.strict);
+        assert_eq!(root, dir.path());
+
+        fs::write(dir.path().join("pkg/typthon.toml"), "workers = \"many\"\n").unwrap();
+        let error = Config::try_discover_from(&nested).unwrap_err();
+        assert!(error.starts_with(&dir.path().join("pkg/typthon.toml").display().to_string()), "{}", error);
+    }
+
     #[test]
     fn test_should_check() {
         let config = Config::default();
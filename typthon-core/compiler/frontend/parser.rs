@@ -1,4 +1,5 @@
-use rustpython_parser::{parse, Mode};
+use rustpython_parser::{parse, parse_starts_at, Mode};
+use rustpython_parser::text_size::TextSize;
 use rustpython_parser::ast::{Mod, ModExpression, Expr};
 use tracing::{debug, error, info, instrument};
 
@@ -38,6 +39,17 @@ pub fn parse_expression(source: &str) -> Result<Expr, ParseError> {
     }
 }
 
+/// Parse an expression found at byte `offset` of a larger source, such as
+/// the annotation in a string, so its ranges point into that source
+pub fn parse_expression_at(source: &str, offset: usize) -> Result<Expr, ParseError> {
+    let offset = TextSize::try_from(offset).map_err(|e| e.to_string())?;
+    match parse_starts_at(source, Mode::Expression, "<string>", offset) {
+        Ok(Mod::Expression(ModExpression { body, .. })) => Ok(*body),
+        Ok(_) => Err("Expected expression".to_string()),
+        Err(e) => Err(format!("Parse error: {}", e)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
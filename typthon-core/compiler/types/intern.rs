@@ -2,10 +2,18 @@
 //!
 //! Maps Rust Type enums to compact u64 TypeIds for C++ SIMD operations.
 //! Uses bidirectional hash maps for O(1) lookups in both directions.
+//!
+//! A `TypeTable` interns the types of one context the same way, as
+//! `TypeRef` handles: types are hashed structurally, so identical types,
+//! composite ones included, are stored once, and copying or comparing a
+//! handle costs no more than a `u32`.
 
 use crate::compiler::types::{Type, TypeId};
+use dashmap::mapref::entry::Entry;
 use dashmap::DashMap;
+use parking_lot::RwLock;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use once_cell::sync::Lazy;
 
 /// Global type interner for efficient Type ↔ TypeId mapping
@@ -90,6 +98,75 @@ impl TypeInterner {
     }
 }
 
+/// Handle to a type interned in a `TypeTable`; only meaningful to the
+/// table that handed it out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TypeRef(u32);
+
+impl TypeRef {
+    pub fn index(self) -> u32 {
+        self.0
+    }
+}
+
+/// Types interned for one context, each stored once
+#[derive(Default)]
+pub struct TypeTable {
+    /// Type → handle
+    refs: DashMap<Arc<Type>, TypeRef>,
+    /// Handle → type, by index
+    types: RwLock<Vec<Arc<Type>>>,
+}
+
+impl TypeTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handle for `ty`, interning it if it's new
+    pub fn intern(&self, ty: Type) -> TypeRef {
+        // Hashed once, since deep types are costlier to hash than to box
+        match self.refs.entry(Arc::new(ty)) {
+            Entry::Occupied(entry) => *entry.get(),
+            Entry::Vacant(entry) => {
+                let mut types = self.types.write();
+                let handle = TypeRef(u32::try_from(types.len()).expect("more types interned than handles can index"));
+                types.push(entry.key().clone());
+                *entry.insert(handle)
+            }
+        }
+    }
+
+    /// Handle for `ty`, cloning it only when it's new
+    pub fn intern_ref(&self, ty: &Type) -> TypeRef {
+        match self.refs.get(ty) {
+            Some(found) => *found,
+            None => self.intern(ty.clone()),
+        }
+    }
+
+    /// The type `handle` stands for, shared with the table
+    ///
+    /// # Panics
+    /// When `handle` came from another table with more types
+    pub fn get(&self, handle: TypeRef) -> Arc<Type> {
+        self.types.read()[handle.0 as usize].clone()
+    }
+
+    /// The type `handle` stands for, as an owned copy
+    pub fn resolve(&self, handle: TypeRef) -> Type {
+        Type::clone(&self.types.read()[handle.0 as usize])
+    }
+
+    pub fn len(&self) -> usize {
+        self.types.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
 /// Global functions for convenient access
 pub fn intern(ty: Type) -> TypeId {
     INTERNER.intern(ty)
@@ -132,6 +209,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_table_stores_identical_composites_once() {
+        let table = TypeTable::new();
+        let pair = |key: Type| Type::Dict(Box::new(key), Box::new(Type::List(Box::new(Type::Int))));
+        let first = table.intern(pair(Type::Str));
+        assert_eq!(table.intern_ref(&pair(Type::Str)), first);
+        assert_ne!(table.intern(pair(Type::Bytes)), first);
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.resolve(first), pair(Type::Str));
+    }
+
     #[test]
     fn test_complex_types() {
         let list_int = Type::List(Box::new(Type::Int));
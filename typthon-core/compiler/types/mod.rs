@@ -1,5 +1,9 @@
 pub mod types;
 pub mod intern;
+pub mod overrides;
+pub mod signature;
 
 pub use types::*;
 pub use intern::*;
+pub use overrides::{OverrideOrigin, SignatureOverride, SignatureOverrides};
+pub use signature::{CallBinding, FunctionSig, KeywordMismatch, KeywordSlot, Param};
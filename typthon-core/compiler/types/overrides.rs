@@ -0,0 +1,135 @@
+//! Signature overrides - replacement member types layered over builtins and stubs
+//!
+//! An override shadows `class.member` for every lookup through the owning
+//! `TypeContext`, without touching the registered schema. Each override
+//! remembers where it came from so diagnostics can say why a signature
+//! differs from the bundled one.
+
+use super::Type;
+use dashmap::DashMap;
+use std::fmt;
+
+/// Where an override was installed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OverrideOrigin {
+    /// `TypeContext::override_member` or `typthon.patch_signature()`
+    Session,
+    /// The `[signatures]` table of the project config
+    Config,
+}
+
+impl fmt::Display for OverrideOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OverrideOrigin::Session => write!(f, "for this session"),
+            OverrideOrigin::Config => write!(f, "by project configuration"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureOverride {
+    pub class: String,
+    pub member: String,
+    pub ty: Type,
+    pub origin: OverrideOrigin,
+}
+
+impl SignatureOverride {
+    /// Note attached to diagnostics that involved this override
+    pub fn note(&self) -> String {
+        format!("note: signature overridden {} ({}.{})", self.origin, self.class, self.member)
+    }
+}
+
+/// Overrides keyed by (class, member)
+#[derive(Debug, Default)]
+pub struct SignatureOverrides {
+    members: DashMap<(String, String), SignatureOverride>,
+}
+
+impl SignatureOverrides {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&self, class: &str, member: &str, ty: Type, origin: OverrideOrigin) {
+        self.members.insert(
+            (class.to_string(), member.to_string()),
+            SignatureOverride { class: class.to_string(), member: member.to_string(), ty, origin },
+        );
+    }
+
+    pub fn get(&self, class: &str, member: &str) -> Option<SignatureOverride> {
+        self.members.get(&(class.to_string(), member.to_string())).map(|entry| entry.value().clone())
+    }
+
+    /// Drop one override; returns whether it existed
+    pub fn remove(&self, class: &str, member: &str) -> bool {
+        self.members.remove(&(class.to_string(), member.to_string())).is_some()
+    }
+
+    pub fn clear(&self) {
+        self.members.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// Every override, ordered by class then member
+    pub fn list(&self) -> Vec<SignatureOverride> {
+        let mut overrides: Vec<_> = self.members.iter().map(|entry| entry.value().clone()).collect();
+        overrides.sort_by(|a, b| (&a.class, &a.member).cmp(&(&b.class, &b.member)));
+        overrides
+    }
+
+    /// Canonical text of the override set, empty when there are none
+    ///
+    /// Folded into cache keys so results computed under different overrides
+    /// never mix.
+    pub fn fingerprint(&self) -> String {
+        self.list().iter()
+            .map(|o| format!("{}.{}: {} [{:?}]\n", o.class, o.member, o.ty, o.origin))
+            .collect()
+    }
+}
+
+/// Split a dotted member path like `builtins.dict.get` or `str.find` into
+/// (class, member); any module prefix is dropped
+pub fn parse_member_path(path: &str) -> Result<(String, String), String> {
+    let parts: Vec<&str> = path.split('.').collect();
+    match parts.as_slice() {
+        [.., class, member] if !class.is_empty() && !member.is_empty() => {
+            Ok((class.to_string(), member.to_string()))
+        }
+        _ => Err(format!("expected a dotted path like 'builtins.str.find', got '{}'", path)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_member_path() {
+        assert_eq!(parse_member_path("builtins.dict.get"), Ok(("dict".to_string(), "get".to_string())));
+        assert_eq!(parse_member_path("str.find"), Ok(("str".to_string(), "find".to_string())));
+        assert!(parse_member_path("find").is_err());
+        assert!(parse_member_path("str.").is_err());
+    }
+
+    #[test]
+    fn test_fingerprint_tracks_contents() {
+        let overrides = SignatureOverrides::new();
+        assert_eq!(overrides.fingerprint(), "");
+
+        overrides.set("str", "find", Type::Int, OverrideOrigin::Session);
+        let one = overrides.fingerprint();
+        overrides.set("str", "find", Type::Str, OverrideOrigin::Session);
+        assert_ne!(one, overrides.fingerprint());
+
+        overrides.clear();
+        assert_eq!(overrides.fingerprint(), "");
+    }
+}
@@ -0,0 +1,187 @@
+//! How calls bind their arguments to a function's parameters
+//!
+//! `Type::Function` lists only the types of the parameters a call can pass by
+//! position. The `FunctionSig` bound next to it in the context adds what
+//! matching a call needs: parameter names, which ones have defaults, the
+//! keyword-only ones, and the types `*args`/`**kwargs` take the rest as.
+
+use super::Type;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+    pub name: String,
+    pub has_default: bool,
+}
+
+impl Param {
+    pub fn new(name: impl Into<String>, has_default: bool) -> Self {
+        Self { name: name.into(), has_default }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FunctionSig {
+    /// Parameters that can be passed by position, matching the function
+    /// type's parameter list; positional-only ones first
+    pub params: Vec<Param>,
+    /// How many of `params` can't be passed by keyword
+    pub posonly: usize,
+    /// Keyword-only parameters with their types
+    pub kwonly: Vec<(Param, Type)>,
+    /// Element type of `*args`, when the function takes it
+    pub varargs: Option<Type>,
+    /// Value type of `**kwargs`, when the function takes it
+    pub kwargs: Option<Type>,
+}
+
+/// Where a call's keyword argument goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeywordSlot {
+    /// The positional parameter at this index
+    Param(usize),
+    /// The keyword-only parameter at this index
+    KwOnly(usize),
+    /// Collected by `**kwargs`
+    Kwargs,
+    /// A `**mapping`, which may fill any parameter
+    Unpacked,
+}
+
+/// Why a keyword argument has no slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeywordMismatch {
+    Unexpected,
+    /// Its parameter is already filled by a positional argument
+    MultipleValues,
+    /// Its parameter is positional-only
+    PositionalOnly,
+}
+
+/// How a call's arguments fit a signature
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallBinding {
+    /// One entry per keyword argument, in call order
+    pub keywords: Vec<Result<KeywordSlot, KeywordMismatch>>,
+    /// Positional parameters the call can fill, when it passes more
+    pub too_many: Option<usize>,
+    /// Parameters without a default that no argument reaches
+    pub missing: Vec<String>,
+}
+
+impl FunctionSig {
+    pub fn has_defaults(&self) -> bool {
+        self.params.iter().any(|param| param.has_default)
+    }
+
+    /// Type the argument at `position` is checked against, if any
+    pub fn positional_type<'a>(&'a self, params: &'a [Type], position: usize) -> Option<&'a Type> {
+        params.get(position).or(self.varargs.as_ref())
+    }
+
+    /// Positional parameters a call has to pass
+    pub fn required(&self) -> usize {
+        self.params.iter().filter(|param| !param.has_default).count()
+    }
+
+    /// Bind a call passing `positional` plain positional arguments (those
+    /// before any `*iterable`), whether it also unpacks one, and its keyword
+    /// names (`None` for `**mapping`)
+    ///
+    /// Unpacked arguments may fill anything, so they never cause a
+    /// parameter to be reported missing.
+    pub fn bind(&self, positional: usize, unpacked: bool, keywords: &[Option<&str>]) -> CallBinding {
+        let too_many = (!unpacked && self.varargs.is_none() && positional > self.params.len()).then_some(self.params.len());
+        let mut filled: Vec<bool> = (0..self.params.len()).map(|i| i < positional).collect();
+        let mut kwonly_filled = vec![false; self.kwonly.len()];
+
+        let keywords = keywords.iter().map(|keyword| {
+            let Some(name) = keyword else { return Ok(KeywordSlot::Unpacked) };
+            if let Some(i) = self.params.iter().position(|param| param.name == *name) {
+                if i < self.posonly {
+                    // `**kwargs` collects names that clash with positional-only parameters
+                    return if self.kwargs.is_some() { Ok(KeywordSlot::Kwargs) } else { Err(KeywordMismatch::PositionalOnly) };
+                }
+                if i < positional {
+                    return Err(KeywordMismatch::MultipleValues);
+                }
+                filled[i] = true;
+                return Ok(KeywordSlot::Param(i));
+            }
+            if let Some(i) = self.kwonly.iter().position(|(param, _)| param.name == *name) {
+                kwonly_filled[i] = true;
+                return Ok(KeywordSlot::KwOnly(i));
+            }
+            if self.kwargs.is_some() { Ok(KeywordSlot::Kwargs) } else { Err(KeywordMismatch::Unexpected) }
+        }).collect::<Vec<_>>();
+
+        let mut missing = Vec::new();
+        if !keywords.contains(&Ok(KeywordSlot::Unpacked)) {
+            let params = self.params.iter().zip(&filled).filter(|_| !unpacked);
+            let kwonly = self.kwonly.iter().map(|(param, _)| param).zip(&kwonly_filled);
+            for (param, filled) in params.chain(kwonly) {
+                if !filled && !param.has_default {
+                    missing.push(param.name.clone());
+                }
+            }
+        }
+
+        CallBinding { keywords, too_many, missing }
+    }
+
+    /// Signature left once the receiver is bound, for methods
+    pub fn without_receiver(mut self) -> Self {
+        if !self.params.is_empty() {
+            self.params.remove(0);
+            self.posonly = self.posonly.saturating_sub(1);
+        }
+        self
+    }
+
+    /// Signature of a method reached through its class, which takes the
+    /// instance as its first argument
+    pub fn with_receiver(mut self) -> Self {
+        self.params.insert(0, Param::new("self", false));
+        if self.posonly > 0 {
+            self.posonly += 1;
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `def f(a, /, b, c=1, *, key, flag=False)`
+    fn sig() -> FunctionSig {
+        FunctionSig {
+            params: vec![Param::new("a", false), Param::new("b", false), Param::new("c", true)],
+            posonly: 1,
+            kwonly: vec![(Param::new("key", false), Type::Str), (Param::new("flag", true), Type::Bool)],
+            ..FunctionSig::default()
+        }
+    }
+
+    #[test]
+    fn test_bind_keywords_and_defaults() {
+        let binding = sig().bind(1, false, &[Some("key"), Some("b")]);
+        assert_eq!(binding.keywords, vec![Ok(KeywordSlot::KwOnly(0)), Ok(KeywordSlot::Param(1))]);
+        assert_eq!((binding.too_many, binding.missing), (None, vec![]));
+
+        let binding = sig().bind(2, false, &[Some("a"), Some("b"), Some("other")]);
+        assert_eq!(binding.keywords, vec![
+            Err(KeywordMismatch::PositionalOnly),
+            Err(KeywordMismatch::MultipleValues),
+            Err(KeywordMismatch::Unexpected),
+        ]);
+        assert_eq!(binding.missing, vec!["key".to_string()]);
+    }
+
+    #[test]
+    fn test_bind_unpacked_arguments() {
+        assert_eq!(sig().bind(4, false, &[]).too_many, Some(3));
+        assert_eq!(sig().bind(4, true, &[Some("key")]).too_many, None);
+        assert_eq!(sig().bind(0, true, &[]).missing, vec!["key".to_string()]);
+        assert_eq!(sig().bind(0, false, &[None]).missing, Vec::<String>::new());
+    }
+}
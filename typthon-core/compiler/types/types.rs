@@ -1,6 +1,13 @@
 use std::fmt;
 use dashmap::DashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 use serde::{Serialize, Deserialize};
+use super::overrides::{OverrideOrigin, SignatureOverride, SignatureOverrides};
+use super::intern::{TypeRef, TypeTable};
+use super::signature::FunctionSig;
+use crate::compiler::analysis::variance::{Variance, VarianceAnalyzer};
+use crate::compiler::ast::Docstring;
 
 pub type TypeId = u64;
 
@@ -18,6 +25,8 @@ pub enum Type {
     // Composite types
     List(Box<Type>),
     Tuple(Vec<Type>),
+    /// Tuple of any length with one element type: `tuple[T, ...]`
+    VarTuple(Box<Type>),
     Dict(Box<Type>, Box<Type>),
     Set(Box<Type>),
 
@@ -30,7 +39,10 @@ pub enum Type {
     Generic(String, Vec<Type>),
 
     // Nominal types
+    /// Instance of a class
     Class(String),
+    /// The class object itself: `type[Name]`
+    ClassObject(String),
 
     // Type variables for inference
     Var(u64),
@@ -107,7 +119,7 @@ pub enum Predicate {
     Custom(String),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum CompareOp {
     Eq, Ne, Lt, Le, Gt, Ge,
 }
@@ -131,7 +143,7 @@ pub enum DependentConstraint {
     /// Length constraint: len(x) == n
     Length(usize),
 
-    /// Range constraint: min <= len(x) <= max
+    /// Range constraint: min <= len(x) <= max (`usize::MAX` leaves max open)
     LengthRange(usize, usize),
 
     /// Value equals some expression
@@ -174,6 +186,49 @@ pub enum TypeKind {
     Arrow(Box<TypeKind>, Box<TypeKind>),
 }
 
+impl CompareOp {
+    /// The operator with its operands swapped: `<` for `>`
+    pub fn flipped(self) -> Self {
+        match self {
+            CompareOp::Lt => CompareOp::Gt,
+            CompareOp::Le => CompareOp::Ge,
+            CompareOp::Gt => CompareOp::Lt,
+            CompareOp::Ge => CompareOp::Le,
+            op => op,
+        }
+    }
+
+    /// The operator holding exactly when this one doesn't: `>=` for `<`
+    pub fn negated(self) -> Self {
+        match self {
+            CompareOp::Eq => CompareOp::Ne,
+            CompareOp::Ne => CompareOp::Eq,
+            CompareOp::Lt => CompareOp::Ge,
+            CompareOp::Le => CompareOp::Gt,
+            CompareOp::Gt => CompareOp::Le,
+            CompareOp::Ge => CompareOp::Lt,
+        }
+    }
+}
+
+impl Effect {
+    /// Effect a declaration names, e.g. `io` in `@effects("io")`; names
+    /// other than the built-in effects are custom
+    pub fn from_name(name: &str) -> Self {
+        match name.to_ascii_lowercase().as_str() {
+            "pure" => Effect::Pure,
+            "io" => Effect::IO,
+            "network" => Effect::Network,
+            "mutation" => Effect::Mutation,
+            "exception" => Effect::Exception,
+            "async" => Effect::Async,
+            "random" => Effect::Random,
+            "time" => Effect::Time,
+            _ => Effect::Custom(name.to_string()),
+        }
+    }
+}
+
 impl EffectSet {
     pub fn empty() -> Self {
         Self { effects: vec![] }
@@ -208,8 +263,54 @@ impl EffectSet {
         self.effects.contains(effect)
     }
 
+    /// Whether every effect of this set is in `other`; a pure set is a
+    /// subset of any
     pub fn is_subset(&self, other: &Self) -> bool {
-        self.effects.iter().all(|e| other.contains(e))
+        self.effects.iter().all(|e| *e == Effect::Pure || other.contains(e))
+    }
+
+    /// Effects of this set missing from `other`, ignoring `Pure`
+    pub fn difference(&self, other: &Self) -> Self {
+        Self {
+            effects: self.effects.iter()
+                .filter(|e| **e != Effect::Pure && !other.contains(e))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+impl TypeCondition {
+    /// Whether the condition holds; `None` while it depends on type
+    /// variables, or on members `hierarchy` doesn't know
+    pub fn evaluate(&self, hierarchy: &dyn Hierarchy) -> Option<bool> {
+        if self.has_type_vars() {
+            return None;
+        }
+        match self {
+            TypeCondition::Extends(sub, sup) => Some(sub.is_subtype_with(sup, hierarchy)),
+            TypeCondition::Equal(a, b) => Some(a == b),
+            TypeCondition::HasProperty(ty, name) => hierarchy.has_member(ty, name),
+            TypeCondition::Custom(_) => None,
+        }
+    }
+
+    pub fn has_type_vars(&self) -> bool {
+        match self {
+            TypeCondition::Extends(a, b) | TypeCondition::Equal(a, b) => a.has_type_vars() || b.has_type_vars(),
+            TypeCondition::HasProperty(ty, _) => ty.has_type_vars(),
+            TypeCondition::Custom(_) => false,
+        }
+    }
+
+    /// The condition with `f` applied to the types it compares
+    pub fn map_types(&self, f: impl Fn(&Type) -> Type) -> Self {
+        match self {
+            TypeCondition::Extends(a, b) => TypeCondition::Extends(f(a), f(b)),
+            TypeCondition::Equal(a, b) => TypeCondition::Equal(f(a), f(b)),
+            TypeCondition::HasProperty(ty, name) => TypeCondition::HasProperty(f(ty), name.clone()),
+            TypeCondition::Custom(s) => TypeCondition::Custom(s.clone()),
+        }
     }
 }
 
@@ -280,73 +381,174 @@ impl Predicate {
     }
 }
 
+/// How named types relate, which `Type::is_subtype_with` can't tell from
+/// the types alone
+pub trait Hierarchy {
+    /// Whether `class_name` is a subtype of the class `base`
+    fn is_subclass(&self, class_name: &str, base: &str) -> bool;
+
+    /// How the generic `name` varies in its parameter at `index`
+    fn variance(&self, name: &str, _index: usize) -> Variance {
+        VarianceAnalyzer::builtin_variance(name).unwrap_or(Variance::Covariant)
+    }
+
+    /// Whether values of `ty` have the attribute `name`; `None` when
+    /// classes' members aren't known
+    fn has_member(&self, _ty: &Type, _name: &str) -> Option<bool> {
+        None
+    }
+}
+
+impl<F: Fn(&str, &str) -> bool> Hierarchy for F {
+    fn is_subclass(&self, class_name: &str, base: &str) -> bool {
+        self(class_name, base)
+    }
+}
+
 impl Type {
+    /// Structural subtyping; classes are related only to themselves. Use
+    /// `TypeContext::is_subtype` to follow declared bases
     pub fn is_subtype(&self, other: &Type) -> bool {
+        self.is_subtype_with(other, &|sub: &str, sup: &str| sub == sup)
+    }
+
+    /// Subtyping with `hierarchy` deciding how named classes relate and
+    /// which way generic arguments vary
+    pub fn is_subtype_with(&self, other: &Type, hierarchy: &dyn Hierarchy) -> bool {
+        self.is_subtype_assuming(other, hierarchy, &mut Vec::new())
+    }
+
+    /// Subtyping with the pairs of recursive types in `assumed` taken to be
+    /// related: comparing a pair again while its unfoldings are compared
+    /// succeeds, where it would otherwise recurse forever. Pairs found to
+    /// be related stay there, so they aren't compared twice
+    fn is_subtype_assuming(&self, other: &Type, hierarchy: &dyn Hierarchy, assumed: &mut Vec<(Type, Type)>) -> bool {
         use Type::*;
 
         match (self, other) {
-            (_, Any) => true,
+            (_, Any) | (Any, _) => true,
             (Never, _) => true,
             (a, b) if a == b => true,
+            (_, Class(name)) if name == "object" => true,
 
             // Type variables are compatible with any type (will be unified later)
             (Var(_), _) | (_, Var(_)) => true,
 
-            // Union handling: A <: B | C if A <: B or A <: C
-            (a, Union(types)) => types.iter().any(|t| a.is_subtype(t)),
-            (Union(types), b) => types.iter().all(|t| t.is_subtype(b)),
+            // Conditional types: a decided one is the branch it picks; an
+            // undecided one fits where both branches do, and takes what
+            // fits in both
+            (Conditional { .. }, _) | (_, Conditional { .. }) => {
+                let (sub, sup) = (self.reduce_conditionals(hierarchy), other.reduce_conditionals(hierarchy));
+                match (&sub, &sup) {
+                    (Conditional { then_type, else_type, .. }, _) => {
+                        then_type.is_subtype_assuming(&sup, hierarchy, assumed) && else_type.is_subtype_assuming(&sup, hierarchy, assumed)
+                    }
+                    (_, Conditional { then_type, else_type, .. }) => {
+                        sub.is_subtype_assuming(then_type, hierarchy, assumed) && sub.is_subtype_assuming(else_type, hierarchy, assumed)
+                    }
+                    _ => sub.is_subtype_assuming(&sup, hierarchy, assumed),
+                }
+            }
 
-            // Intersection: A & B <: C if A <: C or B <: C
-            (Intersection(types), c) => types.iter().any(|t| t.is_subtype(c)),
+            // The numeric tower: an int goes wherever a float is expected
+            (Int, Float) => true,
+
+            // Recursive types are compared coinductively, one level of each
+            // unfolded at a time, and unfolded before a union body is split up.
+            // The names they bind don't matter
+            (Recursive(..), _) | (_, Recursive(..)) => {
+                let pair = (self.alpha_normalized(), other.alpha_normalized());
+                if pair.0 == pair.1 || assumed.contains(&pair) {
+                    return true;
+                }
+                let mark = assumed.len();
+                assumed.push(pair);
+                let holds = self.unfold().is_subtype_assuming(&other.unfold(), hierarchy, assumed);
+                if !holds {
+                    // Whatever followed from the pair being related doesn't hold
+                    assumed.truncate(mark);
+                }
+                holds
+            }
 
-            // Structural subtyping for containers
-            (List(a), List(b)) => a.is_subtype(b),
-            (Set(a), Set(b)) => a.is_subtype(b),
-            (Dict(k1, v1), Dict(k2, v2)) => k1.is_subtype(k2) && v1.is_subtype(v2),
+            // Union handling: A | B <: C if A <: C and B <: C, split before
+            // A <: B | C if A <: B or A <: C, so unions on both sides are
+            // compared member by member
+            (Union(types), b) => types.iter().all(|t| t.is_subtype_assuming(b, hierarchy, assumed)),
+            (a, Union(types)) => types.iter().any(|t| a.is_subtype_assuming(t, hierarchy, assumed)),
+
+            // Intersection: A & B <: C if A <: C or B <: C
+            (Intersection(types), c) => types.iter().any(|t| t.is_subtype_assuming(c, hierarchy, assumed)),
+
+            // Structural subtyping for containers; mutable ones are invariant
+            (List(a), List(b)) => a.is_equivalent_assuming(b, hierarchy, assumed),
+            (Set(a), Set(b)) => a.is_subtype_assuming(b, hierarchy, assumed),
+            (Dict(k1, v1), Dict(k2, v2)) => k1.is_subtype_assuming(k2, hierarchy, assumed) && v1.is_subtype_assuming(v2, hierarchy, assumed),
+
+            // Generic arguments relate the way their parameter varies
+            (Generic(n1, a1), Generic(n2, a2)) => {
+                n1 == n2 && a1.len() == a2.len()
+                    && a1.iter().zip(a2).enumerate().all(|(i, (x, y))| match hierarchy.variance(n1, i) {
+                        Variance::Covariant => x.is_subtype_assuming(y, hierarchy, assumed),
+                        Variance::Contravariant => y.is_subtype_assuming(x, hierarchy, assumed),
+                        Variance::Invariant => x.is_equivalent_assuming(y, hierarchy, assumed),
+                        Variance::Bivariant => true,
+                    })
+            }
+            // A generic class used bare has unknown arguments
+            (Class(a), Generic(b, _)) | (Generic(a, _), Class(b)) => hierarchy.is_subclass(a, b),
 
             // Tuple covariance
             (Tuple(a), Tuple(b)) => {
-                a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.is_subtype(y))
+                a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.is_subtype_assuming(y, hierarchy, assumed))
             }
+            (VarTuple(a), VarTuple(b)) => a.is_subtype_assuming(b, hierarchy, assumed),
+            (Tuple(a), VarTuple(b)) => a.iter().all(|x| x.is_subtype_assuming(b, hierarchy, assumed)),
 
-            // Function contravariance in params, covariance in return
+            // Function contravariance in params, covariance in return; the
+            // parameters of `Callable[..., R]` fit any others
             (Function(p1, r1), Function(p2, r2)) => {
-                p1.len() == p2.len()
-                    && p2.iter().zip(p1.iter()).all(|(a, b)| a.is_subtype(b))
-                    && r1.is_subtype(r2)
+                let params_fit = Type::takes_any_args(p1) || Type::takes_any_args(p2) || (
+                    p1.len() == p2.len() && p2.iter().zip(p1.iter()).all(|(a, b)| a.is_subtype_assuming(b, hierarchy, assumed))
+                );
+                params_fit && r1.is_subtype_assuming(r2, hierarchy, assumed)
             }
 
             // Effect types: covariant in type, must have subset of effects
             (Effect(t1, e1), Effect(t2, e2)) => {
-                t1.is_subtype(t2) && e1.is_subset(e2)
+                t1.is_subtype_assuming(t2, hierarchy, assumed) && e1.is_subset(e2)
             }
-            (t, Effect(inner, _)) => t.is_subtype(inner), // Can drop effects going up
+            (t, Effect(inner, _)) => t.is_subtype_assuming(inner, hierarchy, assumed), // Can drop effects going up
+            // Effects are ignored where the expected type has none
+            (Effect(inner, _), t) => inner.is_subtype_assuming(t, hierarchy, assumed),
 
             // Refinement types: covariant in base type, must satisfy predicate
             (Refinement(t1, p1), Refinement(t2, p2)) => {
-                t1.is_subtype(t2) && p1.implies(p2)
+                t1.is_subtype_assuming(t2, hierarchy, assumed) && p1.implies(p2)
             }
-            (Refinement(t, _), other) => t.is_subtype(other), // Can drop refinement
-            (t, Refinement(inner, _)) => t.is_subtype(inner), // Conservatively allow
+            (Refinement(t, _), other) => t.is_subtype_assuming(other, hierarchy, assumed), // Can drop refinement
+            (t, Refinement(inner, _)) => t.is_subtype_assuming(inner, hierarchy, assumed), // Conservatively allow
 
             // Dependent types: must match constraint
             (Dependent(t1, c1), Dependent(t2, c2)) => {
-                t1.is_subtype(t2) && c1 == c2
+                t1.is_subtype_assuming(t2, hierarchy, assumed) && c1 == c2
             }
-            (Dependent(t, _), other) => t.is_subtype(other),
+            // A literal value is a subtype of its base type, but not the
+            // other way round, so `str | Literal['r']` widens to `str`
+            (Dependent(t, _), other) => t.is_subtype_assuming(other, hierarchy, assumed),
+
+            // Nominal subclassing, for instances and class objects alike
+            (Class(a), Class(b)) | (ClassObject(a), ClassObject(b)) => hierarchy.is_subclass(a, b),
+
+            // Class objects are instances of `type`
+            (ClassObject(_), Class(name)) => name == "type",
 
             // Nominal types: must have same name (no structural subtyping)
             (Nominal(n1, _), Nominal(n2, _)) => n1 == n2,
             (Nominal(_, inner), other) if other == &Class(String::new()) => {
-                inner.is_subtype(other)
+                inner.is_subtype_assuming(other, hierarchy, assumed)
             }
 
-            // Recursive types: unfold and check
-            (Recursive(_, t1), Recursive(_, t2)) => t1.is_subtype(t2),
-
-            // Conditional types: evaluate and check
-            (Conditional { .. }, _) => false, // TODO: Implement evaluation
-
             // Higher-kinded types: structural equality for now
             (HigherKinded(n1, p1), HigherKinded(n2, p2)) => n1 == n2 && p1 == p2,
 
@@ -354,16 +556,191 @@ impl Type {
         }
     }
 
+    /// Whether values of either type fit wherever the other is expected, as
+    /// the arguments of an invariant parameter must
+    fn is_equivalent_assuming(&self, other: &Type, hierarchy: &dyn Hierarchy, assumed: &mut Vec<(Type, Type)>) -> bool {
+        matches!((self, other), (Type::Any, _) | (_, Type::Any))
+            || (self.is_subtype_assuming(other, hierarchy, assumed) && other.is_subtype_assuming(self, hierarchy, assumed))
+    }
+
     /// Create an effect type
     pub fn with_effect(self, effect: Effect) -> Type {
         Type::Effect(Box::new(self), EffectSet::single(effect))
     }
 
+    /// The source of the value a `Literal[...]` type holds, e.g. `'r'`
+    pub fn literal_value(&self) -> Option<&str> {
+        match self {
+            Type::Dependent(_, DependentConstraint::ValueEq(value)) => Some(value),
+            _ => None,
+        }
+    }
+
     /// Create a refinement type
     pub fn refine(self, predicate: Predicate) -> Type {
         Type::Refinement(Box::new(self), predicate)
     }
 
+    /// `Awaitable[result]`, what calling a coroutine function gives
+    pub fn awaitable(result: Type) -> Type {
+        Type::Generic("Awaitable".to_string(), vec![result])
+    }
+
+    /// What awaiting an `Awaitable[T]` or `Coroutine[Y, S, T]` produces
+    pub fn awaited(&self) -> Option<Type> {
+        match self {
+            Type::Generic(name, args) if name == "Awaitable" && args.len() == 1 => Some(args[0].clone()),
+            Type::Generic(name, args) if name == "Coroutine" && args.len() == 3 => Some(args[2].clone()),
+            _ => None,
+        }
+    }
+
+    /// Placeholder for type parameter `name` in a generic class's member
+    /// types, written `~T` as Python prints type variables
+    pub fn param(name: &str) -> Type {
+        Type::Class(format!("~{}", name))
+    }
+
+    /// This type with the placeholders of `params` replaced by `args`;
+    /// parameters without an argument become `Any`
+    pub fn substitute_params(&self, params: &[String], args: &[Type]) -> Type {
+        let subst = |ty: &Type| ty.substitute_params(params, args);
+        let boxed = |ty: &Type| Box::new(ty.substitute_params(params, args));
+        match self {
+            Type::Class(name) => match name.strip_prefix('~').and_then(|param| params.iter().position(|p| p == param)) {
+                Some(i) => args.get(i).cloned().unwrap_or(Type::Any),
+                None => self.clone(),
+            },
+            Type::List(elem) => Type::List(boxed(elem)),
+            Type::Set(elem) => Type::Set(boxed(elem)),
+            Type::VarTuple(elem) => Type::VarTuple(boxed(elem)),
+            Type::Tuple(elems) => Type::Tuple(elems.iter().map(subst).collect()),
+            Type::Dict(key, value) => Type::Dict(boxed(key), boxed(value)),
+            Type::Function(params_ty, ret) => Type::Function(params_ty.iter().map(subst).collect(), boxed(ret)),
+            Type::Union(members) => Type::union(members.iter().map(subst).collect()),
+            Type::Generic(name, type_args) => Type::Generic(name.clone(), type_args.iter().map(subst).collect()),
+            Type::Effect(inner, effects) => Type::Effect(boxed(inner), effects.clone()),
+            Type::Conditional { condition, then_type, else_type } => Type::Conditional {
+                condition: Box::new(condition.map_types(subst)),
+                then_type: boxed(then_type),
+                else_type: boxed(else_type),
+            },
+            other => other.clone(),
+        }
+    }
+
+    /// This type with each conditional whose condition can be decided
+    /// replaced by the branch it picks; conditions over type variables
+    /// are left for when the variables are known
+    pub fn reduce_conditionals(&self, hierarchy: &dyn Hierarchy) -> Type {
+        let reduce = |ty: &Type| ty.reduce_conditionals(hierarchy);
+        let boxed = |ty: &Type| Box::new(ty.reduce_conditionals(hierarchy));
+        match self {
+            Type::Conditional { condition, then_type, else_type } => match condition.evaluate(hierarchy) {
+                Some(true) => reduce(then_type),
+                Some(false) => reduce(else_type),
+                None => Type::Conditional { condition: condition.clone(), then_type: boxed(then_type), else_type: boxed(else_type) },
+            },
+            Type::List(elem) => Type::List(boxed(elem)),
+            Type::Set(elem) => Type::Set(boxed(elem)),
+            Type::VarTuple(elem) => Type::VarTuple(boxed(elem)),
+            Type::Tuple(elems) => Type::Tuple(elems.iter().map(reduce).collect()),
+            Type::Dict(key, value) => Type::Dict(boxed(key), boxed(value)),
+            Type::Function(params, ret) => Type::Function(params.iter().map(reduce).collect(), boxed(ret)),
+            Type::Union(members) => Type::union(members.iter().map(reduce).collect()),
+            Type::Generic(name, type_args) => Type::Generic(name.clone(), type_args.iter().map(reduce).collect()),
+            Type::Effect(inner, effects) => Type::Effect(boxed(inner), effects.clone()),
+            other => other.clone(),
+        }
+    }
+
+    /// Whether this type mentions a type variable: an inference variable or
+    /// a generic class's parameter
+    pub fn has_type_vars(&self) -> bool {
+        match self {
+            Type::Var(_) => true,
+            Type::Class(name) => name.starts_with('~'),
+            Type::List(elem) | Type::Set(elem) | Type::VarTuple(elem) => elem.has_type_vars(),
+            Type::Tuple(types) | Type::Union(types) | Type::Intersection(types) | Type::Generic(_, types) => {
+                types.iter().any(Type::has_type_vars)
+            }
+            Type::Dict(key, value) => key.has_type_vars() || value.has_type_vars(),
+            Type::Function(params, ret) => params.iter().any(Type::has_type_vars) || ret.has_type_vars(),
+            Type::Effect(inner, _) | Type::Refinement(inner, _) | Type::Dependent(inner, _) => inner.has_type_vars(),
+            Type::Conditional { condition, then_type, else_type } => {
+                condition.has_type_vars() || then_type.has_type_vars() || else_type.has_type_vars()
+            }
+            _ => false,
+        }
+    }
+
+    /// A recursive type with one level of its body exposed: the body, with
+    /// the type standing in for each reference to its name
+    pub fn unfold(&self) -> Type {
+        match self {
+            Type::Recursive(name, body) => body.substitute_class(name, self),
+            other => other.clone(),
+        }
+    }
+
+    fn substitute_class(&self, name: &str, replacement: &Type) -> Type {
+        let subst = |ty: &Type| ty.substitute_class(name, replacement);
+        let boxed = |ty: &Type| Box::new(ty.substitute_class(name, replacement));
+        match self {
+            Type::Class(class_name) if class_name == name => replacement.clone(),
+            Type::List(elem) => Type::List(boxed(elem)),
+            Type::Set(elem) => Type::Set(boxed(elem)),
+            Type::VarTuple(elem) => Type::VarTuple(boxed(elem)),
+            Type::Tuple(elems) => Type::Tuple(elems.iter().map(subst).collect()),
+            Type::Dict(key, value) => Type::Dict(boxed(key), boxed(value)),
+            Type::Function(params, ret) => Type::Function(params.iter().map(subst).collect(), boxed(ret)),
+            Type::Union(members) => Type::Union(members.iter().map(subst).collect()),
+            Type::Generic(generic, args) => Type::Generic(generic.clone(), args.iter().map(subst).collect()),
+            // An inner binding of the same name shadows this one
+            Type::Recursive(inner, _) if inner == name => self.clone(),
+            Type::Recursive(inner, body) => Type::Recursive(inner.clone(), boxed(body)),
+            other => other.clone(),
+        }
+    }
+
+    /// The type with each recursive binder renamed after how deeply it's
+    /// nested, so `rec X. list[X]` and `rec Y. list[Y]` come out equal
+    fn alpha_normalized(&self) -> Type {
+        self.alpha_renamed(0)
+    }
+
+    fn alpha_renamed(&self, depth: usize) -> Type {
+        let rename = |ty: &Type| ty.alpha_renamed(depth);
+        let boxed = |ty: &Type| Box::new(ty.alpha_renamed(depth));
+        match self {
+            Type::List(elem) => Type::List(boxed(elem)),
+            Type::Set(elem) => Type::Set(boxed(elem)),
+            Type::VarTuple(elem) => Type::VarTuple(boxed(elem)),
+            Type::Tuple(elems) => Type::Tuple(elems.iter().map(rename).collect()),
+            Type::Dict(key, value) => Type::Dict(boxed(key), boxed(value)),
+            Type::Function(params, ret) => Type::Function(params.iter().map(rename).collect(), boxed(ret)),
+            Type::Union(members) => Type::Union(members.iter().map(rename).collect()),
+            Type::Generic(generic, args) => Type::Generic(generic.clone(), args.iter().map(rename).collect()),
+            Type::Recursive(name, body) => {
+                // Source names can't start with '#', so these never capture one
+                let binder = format!("#{depth}");
+                let body = body.substitute_class(name, &Type::Class(binder.clone()));
+                Type::Recursive(binder, Box::new(body.alpha_renamed(depth + 1)))
+            }
+            other => other.clone(),
+        }
+    }
+
+    /// `Callable[..., ret]`: a function taking any arguments
+    pub fn callable(ret: Type) -> Type {
+        Type::Function(vec![Type::Class("...".to_string())], Box::new(ret))
+    }
+
+    /// Whether function parameters are those of `Callable[..., R]`
+    pub fn takes_any_args(params: &[Type]) -> bool {
+        matches!(params, [Type::Class(name)] if name == "...")
+    }
+
     /// Create a nominal wrapper
     pub fn nominal(name: String, inner: Type) -> Type {
         Type::Nominal(name, Box::new(inner))
@@ -517,6 +894,7 @@ impl fmt::Display for Type {
             Type::Bytes => write!(f, "bytes"),
             Type::List(t) => write!(f, "list[{}]", t),
             Type::Tuple(ts) => write!(f, "({})", ts.iter().map(|t| format!("{}", t)).collect::<Vec<_>>().join(", ")),
+            Type::VarTuple(t) => write!(f, "tuple[{}, ...]", t),
             Type::Dict(k, v) => write!(f, "dict[{}, {}]", k, v),
             Type::Set(t) => write!(f, "set[{}]", t),
             Type::Function(params, ret) => {
@@ -524,7 +902,21 @@ impl fmt::Display for Type {
                     params.iter().map(|t| format!("{}", t)).collect::<Vec<_>>().join(", "),
                     ret)
             }
-            Type::Union(ts) => write!(f, "{}", ts.iter().map(|t| format!("{}", t)).collect::<Vec<_>>().join(" | ")),
+            Type::Union(ts) => {
+                // Literal members read as one `Literal[...]`, where the first of them is
+                let literals: Vec<&str> = ts.iter().filter_map(Type::literal_value).collect();
+                let mut shown = Vec::new();
+                let mut grouped = false;
+                for t in ts {
+                    if t.literal_value().is_none() {
+                        shown.push(t.to_string());
+                    } else if !grouped {
+                        shown.push(format!("Literal[{}]", literals.join(", ")));
+                        grouped = true;
+                    }
+                }
+                write!(f, "{}", shown.join(" | "))
+            }
             Type::Intersection(ts) => write!(f, "{}", ts.iter().map(|t| format!("{}", t)).collect::<Vec<_>>().join(" & ")),
             Type::Generic(name, args) => {
                 if args.is_empty() {
@@ -534,6 +926,7 @@ impl fmt::Display for Type {
                 }
             }
             Type::Class(name) => write!(f, "{}", name),
+            Type::ClassObject(name) => write!(f, "type[{}]", name),
             Type::Var(id) => write!(f, "T{}", id),
 
             // Phase 3 types
@@ -545,6 +938,7 @@ impl fmt::Display for Type {
                 }
             }
             Type::Refinement(t, pred) => write!(f, "{}[{}]", t, pred),
+            Type::Dependent(_, DependentConstraint::ValueEq(value)) => write!(f, "Literal[{}]", value),
             Type::Dependent(t, constraint) => write!(f, "{}[{}]", t, constraint),
             Type::Nominal(name, _) => write!(f, "{}", name),
             Type::Conditional { condition, then_type, else_type } => {
@@ -604,7 +998,7 @@ impl fmt::Display for PredicateExpr {
         match self {
             PredicateExpr::Value => write!(f, "value"),
             PredicateExpr::Literal(n) => write!(f, "{}", n),
-            PredicateExpr::Property(prop) => write!(f, "{}", prop),
+            PredicateExpr::Property(prop) => write!(f, "{}(value)", prop),
             PredicateExpr::BinOp(left, op, right) => write!(f, "({} {} {})", left, op, right),
         }
     }
@@ -626,6 +1020,7 @@ impl fmt::Display for DependentConstraint {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             DependentConstraint::Length(n) => write!(f, "len={}", n),
+            DependentConstraint::LengthRange(min, usize::MAX) => write!(f, "len>={}", min),
             DependentConstraint::LengthRange(min, max) => write!(f, "{}<=len<={}", min, max),
             DependentConstraint::ValueEq(expr) => write!(f, "value={}", expr),
             DependentConstraint::Custom(s) => write!(f, "{}", s),
@@ -647,17 +1042,78 @@ impl fmt::Display for TypeCondition {
 /// Class member kinds for attribute resolution
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum MemberKind {
-    Method(Type),      // Method with function type
+    Method(Type),      // Method with function type, receiver bound
+    StaticMethod(Type), // Static method, or class method with `cls` bound
     Property(Type),    // Property/field with value type
     ClassVar(Type),    // Class variable
 }
 
+impl MemberKind {
+    fn substitute_params(self, params: &[String], args: &[Type]) -> Self {
+        if params.is_empty() {
+            return self;
+        }
+        match self {
+            Self::Method(ty) => Self::Method(ty.substitute_params(params, args)),
+            Self::StaticMethod(ty) => Self::StaticMethod(ty.substitute_params(params, args)),
+            Self::Property(ty) => Self::Property(ty.substitute_params(params, args)),
+            Self::ClassVar(ty) => Self::ClassVar(ty.substitute_params(params, args)),
+        }
+    }
+}
+
 /// Class schema: compact representation of class structure
 #[derive(Debug, Clone)]
 pub struct ClassSchema {
     pub name: String,
     pub members: DashMap<String, MemberKind>,
     pub bases: Vec<String>,  // Base class names for inheritance
+    /// Type parameters member types refer to through `Type::param`, e.g.
+    /// `K` and `V` for `dict`
+    pub type_params: Vec<String>,
+    /// How the class varies in each of its type parameters
+    pub variances: Vec<Variance>,
+    pub doc: Option<Docstring>,
+    /// Docstrings of methods and properties, by member name
+    pub member_docs: DashMap<String, Docstring>,
+    /// Keys of a `TypedDict` class; `None` for other classes
+    pub typed_dict: Option<TypedDictKeys>,
+    /// Declared with `Protocol` as a base: classes with its members are
+    /// subtypes whether or not they inherit from it
+    pub protocol: bool,
+}
+
+/// Keys a `TypedDict` instance has, with the types of their values
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TypedDictKeys {
+    /// In declaration order, inherited keys first
+    pub keys: Vec<(String, Type)>,
+    /// Keys that may be left out: those declared under `total=False` or as
+    /// `NotRequired[...]`
+    pub optional: HashSet<String>,
+}
+
+impl TypedDictKeys {
+    pub fn get(&self, key: &str) -> Option<&Type> {
+        self.keys.iter().find(|(name, _)| name == key).map(|(_, ty)| ty)
+    }
+
+    /// Add `key`, replacing an inherited one of the same name
+    pub fn insert(&mut self, key: String, ty: Type, required: bool) {
+        if !required {
+            self.optional.insert(key.clone());
+        } else {
+            self.optional.remove(&key);
+        }
+        match self.keys.iter_mut().find(|(name, _)| *name == key) {
+            Some(existing) => existing.1 = ty,
+            None => self.keys.push((key, ty)),
+        }
+    }
+
+    pub fn is_required(&self, key: &str) -> bool {
+        !self.optional.contains(key)
+    }
 }
 
 impl ClassSchema {
@@ -666,18 +1122,62 @@ impl ClassSchema {
             name,
             members: DashMap::new(),
             bases: Vec::new(),
+            type_params: Vec::new(),
+            variances: Vec::new(),
+            doc: None,
+            member_docs: DashMap::new(),
+            typed_dict: None,
+            protocol: false,
         }
     }
 
+    pub fn with_typed_dict(mut self, keys: TypedDictKeys) -> Self {
+        self.typed_dict = Some(keys);
+        self
+    }
+
+    pub fn with_protocol(mut self) -> Self {
+        self.protocol = true;
+        self
+    }
+
     pub fn with_bases(mut self, bases: Vec<String>) -> Self {
         self.bases = bases;
         self
     }
 
+    pub fn with_type_params(mut self, params: &[&str]) -> Self {
+        self.type_params = params.iter().map(|param| param.to_string()).collect();
+        self
+    }
+
+    /// Type parameters declared through `TypeVar`s, with their variances
+    pub fn with_variances(mut self, params: Vec<(String, Variance)>) -> Self {
+        (self.type_params, self.variances) = params.into_iter().unzip();
+        self
+    }
+
+    pub fn with_doc(mut self, doc: Option<Docstring>) -> Self {
+        self.doc = doc;
+        self
+    }
+
+    pub fn set_member_doc(&self, name: String, doc: Docstring) {
+        self.member_docs.insert(name, doc);
+    }
+
+    pub fn member_doc(&self, name: &str) -> Option<Docstring> {
+        self.member_docs.get(name).map(|doc| doc.value().clone())
+    }
+
     pub fn add_method(&self, name: String, ty: Type) {
         self.members.insert(name, MemberKind::Method(ty));
     }
 
+    pub fn add_static_method(&self, name: String, ty: Type) {
+        self.members.insert(name, MemberKind::StaticMethod(ty));
+    }
+
     pub fn add_property(&self, name: String, ty: Type) {
         self.members.insert(name, MemberKind::Property(ty));
     }
@@ -692,22 +1192,74 @@ impl ClassSchema {
 
     pub fn get_member(&self, name: &str) -> Option<Type> {
         self.members.get(name).map(|m| match m.value() {
-            MemberKind::Method(ty) | MemberKind::Property(ty) | MemberKind::ClassVar(ty) => ty.clone(),
+            MemberKind::Method(ty) | MemberKind::StaticMethod(ty) | MemberKind::Property(ty) | MemberKind::ClassVar(ty) => {
+                ty.clone()
+            }
         })
     }
 }
 
+/// Built-in exception classes below `BaseException`, with their bases
+const BUILTIN_EXCEPTIONS: &[(&str, &str)] = &[
+    ("Exception", "BaseException"),
+    ("KeyboardInterrupt", "BaseException"),
+    ("SystemExit", "BaseException"),
+    ("GeneratorExit", "BaseException"),
+    ("ArithmeticError", "Exception"),
+    ("ZeroDivisionError", "ArithmeticError"),
+    ("OverflowError", "ArithmeticError"),
+    ("AssertionError", "Exception"),
+    ("AttributeError", "Exception"),
+    ("ImportError", "Exception"),
+    ("ModuleNotFoundError", "ImportError"),
+    ("LookupError", "Exception"),
+    ("IndexError", "LookupError"),
+    ("KeyError", "LookupError"),
+    ("NameError", "Exception"),
+    ("OSError", "Exception"),
+    ("FileNotFoundError", "OSError"),
+    ("PermissionError", "OSError"),
+    ("TimeoutError", "OSError"),
+    ("RuntimeError", "Exception"),
+    ("NotImplementedError", "RuntimeError"),
+    ("RecursionError", "RuntimeError"),
+    ("StopIteration", "Exception"),
+    ("TypeError", "Exception"),
+    ("ValueError", "Exception"),
+    ("UnicodeError", "ValueError"),
+];
+
 pub struct TypeContext {
-    types: DashMap<String, Type>,
-    classes: DashMap<String, ClassSchema>,
+    /// Name bindings, as handles into `table`
+    types: DashMap<String, TypeRef>,
+    table: TypeTable,
+    /// Docstrings of functions and classes bound alongside `types`
+    docs: DashMap<String, Docstring>,
+    /// How calls bind arguments to the functions bound in `types`
+    signatures: DashMap<String, FunctionSig>,
+    /// Schemas are shared; members stay mutable through the schema's own DashMap
+    classes: DashMap<String, Arc<ClassSchema>>,
+    /// Member signatures shadowing the registered schemas; may be shared
+    /// between contexts
+    overrides: Arc<SignatureOverrides>,
     next_var: std::sync::atomic::AtomicU64,
 }
 
 impl TypeContext {
     pub fn new() -> Self {
+        Self::with_overrides(Arc::new(SignatureOverrides::new()))
+    }
+
+    /// Context whose member lookups consult `overrides`, which later changes
+    /// still reach
+    pub fn with_overrides(overrides: Arc<SignatureOverrides>) -> Self {
         let ctx = Self {
             types: DashMap::new(),
+            table: TypeTable::new(),
+            docs: DashMap::new(),
+            signatures: DashMap::new(),
             classes: DashMap::new(),
+            overrides,
             next_var: std::sync::atomic::AtomicU64::new(0),
         };
         ctx.init_builtins();
@@ -727,40 +1279,146 @@ impl TypeContext {
         str_schema.add_method("startswith".to_string(), Type::Function(vec![Type::Str], Box::new(Type::Bool)));
         str_schema.add_method("endswith".to_string(), Type::Function(vec![Type::Str], Box::new(Type::Bool)));
         str_schema.add_method("find".to_string(), Type::Function(vec![Type::Str], Box::new(Type::Int)));
-        self.classes.insert("str".to_string(), str_schema);
+        self.classes.insert("str".to_string(), Arc::new(str_schema));
+
+        // Container methods are generic in the element types, which
+        // attribute lookup fills in from the container type
+        let fun = |params: Vec<Type>, ret: Type| Type::Function(params, Box::new(ret));
+        let (t, k, v) = (Type::param("T"), Type::param("K"), Type::param("V"));
+        let list_of = |elem: &Type| Type::List(Box::new(elem.clone()));
+        let set_of = |elem: &Type| Type::Set(Box::new(elem.clone()));
 
         // list methods
-        let list_schema = ClassSchema::new("list".to_string());
-        list_schema.add_method("append".to_string(), Type::Function(vec![Type::Any], Box::new(Type::None)));
-        list_schema.add_method("extend".to_string(), Type::Function(vec![Type::List(Box::new(Type::Any))], Box::new(Type::None)));
-        list_schema.add_method("pop".to_string(), Type::Function(vec![], Box::new(Type::Any)));
-        list_schema.add_method("remove".to_string(), Type::Function(vec![Type::Any], Box::new(Type::None)));
-        list_schema.add_method("clear".to_string(), Type::Function(vec![], Box::new(Type::None)));
-        list_schema.add_method("sort".to_string(), Type::Function(vec![], Box::new(Type::None)));
-        list_schema.add_method("reverse".to_string(), Type::Function(vec![], Box::new(Type::None)));
-        list_schema.add_method("copy".to_string(), Type::Function(vec![], Box::new(Type::List(Box::new(Type::Any)))));
-        self.classes.insert("list".to_string(), list_schema);
+        let list_schema = ClassSchema::new("list".to_string()).with_type_params(&["T"]);
+        list_schema.add_method("append".to_string(), fun(vec![t.clone()], Type::None));
+        list_schema.add_method("extend".to_string(), fun(vec![list_of(&t)], Type::None));
+        list_schema.add_method("pop".to_string(), fun(vec![], t.clone()));
+        list_schema.add_method("remove".to_string(), fun(vec![t.clone()], Type::None));
+        list_schema.add_method("clear".to_string(), fun(vec![], Type::None));
+        list_schema.add_method("sort".to_string(), fun(vec![], Type::None));
+        list_schema.add_method("reverse".to_string(), fun(vec![], Type::None));
+        list_schema.add_method("copy".to_string(), fun(vec![], list_of(&t)));
+        self.classes.insert("list".to_string(), Arc::new(list_schema));
 
         // dict methods
-        let dict_schema = ClassSchema::new("dict".to_string());
-        dict_schema.add_method("keys".to_string(), Type::Function(vec![], Box::new(Type::List(Box::new(Type::Any)))));
-        dict_schema.add_method("values".to_string(), Type::Function(vec![], Box::new(Type::List(Box::new(Type::Any)))));
-        dict_schema.add_method("items".to_string(), Type::Function(vec![], Box::new(Type::List(Box::new(Type::Tuple(vec![Type::Any, Type::Any]))))));
-        dict_schema.add_method("get".to_string(), Type::Function(vec![Type::Any], Box::new(Type::Any)));
-        dict_schema.add_method("pop".to_string(), Type::Function(vec![Type::Any], Box::new(Type::Any)));
-        dict_schema.add_method("clear".to_string(), Type::Function(vec![], Box::new(Type::None)));
-        dict_schema.add_method("update".to_string(), Type::Function(vec![Type::Dict(Box::new(Type::Any), Box::new(Type::Any))], Box::new(Type::None)));
-        self.classes.insert("dict".to_string(), dict_schema);
+        let dict_schema = ClassSchema::new("dict".to_string()).with_type_params(&["K", "V"]);
+        dict_schema.add_method("keys".to_string(), fun(vec![], list_of(&k)));
+        dict_schema.add_method("values".to_string(), fun(vec![], list_of(&v)));
+        dict_schema.add_method("items".to_string(), fun(vec![], list_of(&Type::Tuple(vec![k.clone(), v.clone()]))));
+        dict_schema.add_method("get".to_string(), fun(vec![k.clone()], Type::Union(vec![v.clone(), Type::None])));
+        dict_schema.add_method("pop".to_string(), fun(vec![k.clone()], v.clone()));
+        dict_schema.add_method("clear".to_string(), fun(vec![], Type::None));
+        dict_schema.add_method("update".to_string(), fun(vec![Type::Dict(Box::new(k.clone()), Box::new(v.clone()))], Type::None));
+        self.classes.insert("dict".to_string(), Arc::new(dict_schema));
 
         // set methods
-        let set_schema = ClassSchema::new("set".to_string());
-        set_schema.add_method("add".to_string(), Type::Function(vec![Type::Any], Box::new(Type::None)));
-        set_schema.add_method("remove".to_string(), Type::Function(vec![Type::Any], Box::new(Type::None)));
-        set_schema.add_method("discard".to_string(), Type::Function(vec![Type::Any], Box::new(Type::None)));
-        set_schema.add_method("clear".to_string(), Type::Function(vec![], Box::new(Type::None)));
-        set_schema.add_method("union".to_string(), Type::Function(vec![Type::Set(Box::new(Type::Any))], Box::new(Type::Set(Box::new(Type::Any)))));
-        set_schema.add_method("intersection".to_string(), Type::Function(vec![Type::Set(Box::new(Type::Any))], Box::new(Type::Set(Box::new(Type::Any)))));
-        self.classes.insert("set".to_string(), set_schema);
+        let set_schema = ClassSchema::new("set".to_string()).with_type_params(&["T"]);
+        set_schema.add_method("add".to_string(), fun(vec![t.clone()], Type::None));
+        set_schema.add_method("remove".to_string(), fun(vec![t.clone()], Type::None));
+        set_schema.add_method("discard".to_string(), fun(vec![t.clone()], Type::None));
+        set_schema.add_method("clear".to_string(), fun(vec![], Type::None));
+        set_schema.add_method("union".to_string(), fun(vec![set_of(&t)], set_of(&t)));
+        set_schema.add_method("intersection".to_string(), fun(vec![set_of(&t)], set_of(&t)));
+        self.classes.insert("set".to_string(), Arc::new(set_schema));
+
+        // Built-in exceptions, each after its base
+        let base_exception = ClassSchema::new("BaseException".to_string());
+        base_exception.add_property("args".to_string(), Type::VarTuple(Box::new(Type::Any)));
+        base_exception.add_method("with_traceback".to_string(), Type::Function(vec![Type::Any], Box::new(Type::Class("BaseException".to_string()))));
+        base_exception.add_method("add_note".to_string(), Type::Function(vec![Type::Str], Box::new(Type::None)));
+        self.classes.insert("BaseException".to_string(), Arc::new(base_exception));
+        for (name, base) in BUILTIN_EXCEPTIONS {
+            let schema = ClassSchema::new(name.to_string()).with_bases(vec![base.to_string()]);
+            self.classes.insert(name.to_string(), Arc::new(schema));
+        }
+
+        self.init_builtin_dunders();
+    }
+
+    /// Dunder methods of the builtin types, which structural protocols such
+    /// as `Sized`, `Hashable` and `Comparable` are checked against
+    fn init_builtin_dunders(&self) {
+        let fun = |params: Vec<Type>, ret: Type| Type::Function(params, Box::new(ret));
+        let (t, k, v) = (Type::param("T"), Type::param("K"), Type::param("V"));
+        let iterator_of = |elem: &Type| Type::Generic("Iterator".to_string(), vec![elem.clone()]);
+        let schema = |name: &str, params: &[&str], bases: &[&str]| {
+            let schema = self.get_class(name).unwrap_or_else(|| {
+                let bases = bases.iter().map(|base| base.to_string()).collect();
+                let schema = Arc::new(ClassSchema::new(name.to_string()).with_type_params(params).with_bases(bases));
+                self.classes.insert(name.to_string(), Arc::clone(&schema));
+                schema
+            });
+            // Every object compares for equality and converts to a string
+            schema.add_method("__eq__".to_string(), fun(vec![Type::Any], Type::Bool));
+            schema.add_method("__ne__".to_string(), fun(vec![Type::Any], Type::Bool));
+            schema.add_method("__str__".to_string(), fun(vec![], Type::Str));
+            schema.add_method("__repr__".to_string(), fun(vec![], Type::Str));
+            schema
+        };
+        let add = |schema: &ClassSchema, names: &[&str], ty: Type| {
+            for name in names {
+                schema.add_method(name.to_string(), ty.clone());
+            }
+        };
+        let ordering = ["__lt__", "__le__", "__gt__", "__ge__"];
+
+        // A value known only to be an `object` has nothing beyond these
+        let object = schema("object", &[], &[]);
+        add(&object, &["__hash__"], fun(vec![], Type::Int));
+
+        for (name, ty) in [("int", Type::Int), ("float", Type::Float)] {
+            let number = schema(name, &[], &[]);
+            add(&number, &ordering, fun(vec![ty.clone()], Type::Bool));
+            add(&number, &["__add__", "__sub__", "__mul__", "__floordiv__", "__mod__"], fun(vec![ty.clone()], ty.clone()));
+            add(&number, &["__truediv__"], fun(vec![ty.clone()], Type::Float));
+            add(&number, &["__hash__", "__int__"], fun(vec![], Type::Int));
+            add(&number, &["__float__"], fun(vec![], Type::Float));
+            add(&number, &["__bool__"], fun(vec![], Type::Bool));
+        }
+        schema("bool", &[], &["int"]);
+
+        for (name, ty) in [("str", Type::Str), ("bytes", Type::Bytes)] {
+            let text = schema(name, &[], &[]);
+            add(&text, &ordering, fun(vec![ty.clone()], Type::Bool));
+            add(&text, &["__add__"], fun(vec![ty.clone()], ty.clone()));
+            add(&text, &["__mul__"], fun(vec![Type::Int], ty.clone()));
+            add(&text, &["__hash__", "__len__"], fun(vec![], Type::Int));
+            add(&text, &["__contains__"], fun(vec![ty.clone()], Type::Bool));
+        }
+        let str_schema = schema("str", &[], &[]);
+        add(&str_schema, &["__getitem__"], fun(vec![Type::Int], Type::Str));
+        add(&str_schema, &["__iter__"], fun(vec![], iterator_of(&Type::Str)));
+
+        let list = schema("list", &["T"], &[]);
+        let list_of = Type::List(Box::new(t.clone()));
+        add(&list, &ordering, fun(vec![list_of.clone()], Type::Bool));
+        add(&list, &["__add__"], fun(vec![list_of.clone()], list_of));
+
+        let tuple = schema("tuple", &["T"], &[]);
+        let tuple_of = Type::VarTuple(Box::new(t.clone()));
+        add(&tuple, &ordering, fun(vec![tuple_of.clone()], Type::Bool));
+        add(&tuple, &["__hash__"], fun(vec![], Type::Int));
+        add(&tuple, &["count", "index"], fun(vec![Type::Any], Type::Int));
+
+        for sequence in [&list, &tuple] {
+            add(sequence, &["__len__"], fun(vec![], Type::Int));
+            add(sequence, &["__getitem__"], fun(vec![Type::Int], t.clone()));
+            add(sequence, &["__iter__"], fun(vec![], iterator_of(&t)));
+            add(sequence, &["__contains__"], fun(vec![Type::Any], Type::Bool));
+        }
+
+        let set = schema("set", &["T"], &[]);
+        add(&set, &["__len__"], fun(vec![], Type::Int));
+        add(&set, &["__iter__"], fun(vec![], iterator_of(&t)));
+        add(&set, &["__contains__"], fun(vec![Type::Any], Type::Bool));
+
+        let dict = schema("dict", &["K", "V"], &[]);
+        add(&dict, &["__len__"], fun(vec![], Type::Int));
+        add(&dict, &["__iter__"], fun(vec![], iterator_of(&k)));
+        add(&dict, &["__contains__"], fun(vec![Type::Any], Type::Bool));
+        add(&dict, &["__getitem__"], fun(vec![k.clone()], v.clone()));
+        add(&dict, &["__setitem__"], fun(vec![k.clone(), v.clone()], Type::None));
+        add(&dict, &["__delitem__"], fun(vec![k], Type::None));
     }
 
     pub fn fresh_var(&self) -> Type {
@@ -768,30 +1426,218 @@ impl TypeContext {
         Type::Var(id)
     }
 
+    /// Id the next `fresh_var` will get
+    pub fn next_var_id(&self) -> u64 {
+        self.next_var.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    pub fn set_next_var_id(&self, id: u64) {
+        self.next_var.store(id, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    /// Copy of every name binding, for `restore_bindings`
+    pub fn bindings(&self) -> HashMap<String, Type> {
+        self.types.iter().map(|r| (r.key().clone(), self.table.resolve(*r.value()))).collect()
+    }
+
+    /// Replace every name binding with `bindings`
+    pub fn restore_bindings(&self, bindings: &HashMap<String, Type>) {
+        self.types.clear();
+        for (name, ty) in bindings {
+            self.types.insert(name.clone(), self.table.intern_ref(ty));
+        }
+    }
+
+    /// Every name binding as interned handles, for `restore_binding_refs`;
+    /// cheaper than `bindings` since no type is copied
+    pub fn binding_refs(&self) -> HashMap<String, TypeRef> {
+        self.types.iter().map(|r| (r.key().clone(), *r.value())).collect()
+    }
+
+    /// Replace every name binding with handles from `binding_refs`
+    pub fn restore_binding_refs(&self, bindings: &HashMap<String, TypeRef>) {
+        self.types.clear();
+        for (name, handle) in bindings {
+            self.types.insert(name.clone(), *handle);
+        }
+    }
+
     pub fn set_type(&self, name: String, ty: Type) {
-        self.types.insert(name, ty);
+        self.types.insert(name, self.table.intern(ty));
     }
 
     pub fn get_type(&self, name: &str) -> Option<Type> {
-        self.types.get(name).map(|r| r.value().clone())
+        self.types.get(name).map(|r| self.table.resolve(*r.value()))
+    }
+
+    pub fn remove_type(&self, name: &str) -> Option<Type> {
+        self.types.remove(name).map(|(_, handle)| self.table.resolve(handle))
+    }
+
+    pub fn has_type(&self, name: &str) -> bool {
+        self.types.contains_key(name)
+    }
+
+    /// Handle to the type bound to `name`, for putting it back with
+    /// `set_type_ref` without copying it
+    pub fn type_ref(&self, name: &str) -> Option<TypeRef> {
+        self.types.get(name).map(|r| *r.value())
+    }
+
+    pub fn set_type_ref(&self, name: String, handle: TypeRef) {
+        self.types.insert(name, handle);
+    }
+
+    /// The type a handle from `type_ref` stands for
+    pub fn resolve_ref(&self, handle: TypeRef) -> Type {
+        self.table.resolve(handle)
+    }
+
+    /// Record (or with `None`, forget) the docstring of a binding
+    pub fn set_doc(&self, name: String, doc: Option<Docstring>) {
+        match doc {
+            Some(doc) => self.docs.insert(name, doc),
+            None => self.docs.remove(&name).map(|(_, doc)| doc),
+        };
+    }
+
+    pub fn get_doc(&self, name: &str) -> Option<Docstring> {
+        self.docs.get(name).map(|r| r.value().clone())
+    }
+
+    /// Record (or with `None`, forget) the parameter list of a binding
+    pub fn set_signature(&self, name: String, sig: Option<FunctionSig>) {
+        match sig {
+            Some(sig) => self.signatures.insert(name, sig),
+            None => self.signatures.remove(&name).map(|(_, sig)| sig),
+        };
+    }
+
+    pub fn get_signature(&self, name: &str) -> Option<FunctionSig> {
+        self.signatures.get(name).map(|r| r.value().clone())
+    }
+
+    /// Copy of every signature, for `restore_signatures`
+    pub fn signatures(&self) -> HashMap<String, FunctionSig> {
+        self.signatures.iter().map(|r| (r.key().clone(), r.value().clone())).collect()
+    }
+
+    pub fn restore_signatures(&self, signatures: &HashMap<String, FunctionSig>) {
+        self.signatures.clear();
+        for (name, sig) in signatures {
+            self.signatures.insert(name.clone(), sig.clone());
+        }
     }
 
     pub fn register_class(&self, schema: ClassSchema) {
-        self.classes.insert(schema.name.clone(), schema);
+        self.classes.insert(schema.name.clone(), Arc::new(schema));
+    }
+
+    pub fn get_class(&self, name: &str) -> Option<Arc<ClassSchema>> {
+        self.classes.get(name).map(|r| Arc::clone(r.value()))
+    }
+
+    /// Keys of the `TypedDict` class `name`
+    pub fn typed_dict(&self, name: &str) -> Option<TypedDictKeys> {
+        self.get_class(name)?.typed_dict.clone()
+    }
+
+    /// Members the protocol class `name` requires, its own and those of the
+    /// protocols it extends, sorted by name; `None` when it isn't a protocol
+    pub fn protocol_members(&self, name: &str) -> Option<Vec<(String, Type)>> {
+        if !self.get_class(name)?.protocol {
+            return None;
+        }
+        let mut members: Vec<(String, Type)> = Vec::new();
+        for schema in self.class_chain(name).iter().filter_map(|class| self.get_class(class)) {
+            if !schema.protocol {
+                continue;
+            }
+            for member in schema.members.iter() {
+                if !members.iter().any(|(existing, _)| existing == member.key()) {
+                    members.push((member.key().clone(), schema.get_member(member.key()).unwrap_or(Type::Any)));
+                }
+            }
+        }
+        members.sort_by(|a, b| a.0.cmp(&b.0));
+        Some(members)
+    }
+
+    /// Whether instances of `class_name` have every member of the protocol
+    /// `protocol` with a fitting type. Member types are compared nominally,
+    /// so protocols mentioning themselves don't recurse
+    pub fn satisfies_protocol(&self, class_name: &str, protocol: &str) -> bool {
+        let Some(members) = self.protocol_members(protocol) else { return false };
+        members.iter().all(|(name, expected)| {
+            self.lookup_class_attribute(class_name, name, &[]).is_some_and(|actual| {
+                strip_effects(&actual).is_subtype_with(strip_effects(expected), &|sub: &str, sup: &str| self.is_subclass(sub, sup))
+            })
+        })
+    }
+
+    pub fn overrides(&self) -> &Arc<SignatureOverrides> {
+        &self.overrides
+    }
+
+    /// Shadow `class.member` with `ty` for this session
+    pub fn override_member(&self, class: &str, member: &str, ty: Type) {
+        self.overrides.set(class, member, ty, OverrideOrigin::Session);
+    }
+
+    /// The override that `ty.attr` resolves to, if any
+    pub fn member_override(&self, ty: &Type, attr: &str) -> Option<SignatureOverride> {
+        if self.overrides.is_empty() {
+            return None;
+        }
+        match ty {
+            Type::Class(name) | Type::ClassObject(name) => self.class_member_override(name, attr),
+            Type::Str => self.class_member_override("str", attr),
+            Type::List(_) => self.class_member_override("list", attr),
+            Type::Dict(_, _) => self.class_member_override("dict", attr),
+            Type::Set(_) => self.class_member_override("set", attr),
+            Type::Refinement(inner, _) | Type::Effect(inner, _) | Type::Dependent(inner, _) | Type::Nominal(_, inner) => {
+                self.member_override(inner, attr)
+            }
+            _ => None,
+        }
     }
 
-    pub fn get_class(&self, name: &str) -> Option<ClassSchema> {
-        self.classes.get(name).map(|r| r.value().clone())
+    fn class_member_override(&self, class_name: &str, attr: &str) -> Option<SignatureOverride> {
+        self.class_member_override_in(class_name, attr, &mut Vec::new())
+    }
+
+    fn class_member_override_in(&self, class_name: &str, attr: &str, seen: &mut Vec<String>) -> Option<SignatureOverride> {
+        if seen.iter().any(|name| name == class_name) {
+            return None;
+        }
+        seen.push(class_name.to_string());
+        if let Some(found) = self.overrides.get(class_name, attr) {
+            return Some(found);
+        }
+        // A member defined on the class itself hides overrides on its bases
+        let schema = self.get_class(class_name)?;
+        if schema.has_member(attr) {
+            return None;
+        }
+        schema.bases.iter().find_map(|base| self.class_member_override_in(base, attr, seen))
     }
 
     /// Check if a type has an attribute and return its type
     pub fn has_attribute(&self, ty: &Type, attr: &str) -> Option<Type> {
         match ty {
-            Type::Class(name) => self.lookup_class_attribute(name, attr),
-            Type::Str => self.lookup_class_attribute("str", attr),
-            Type::List(_) => self.lookup_class_attribute("list", attr),
-            Type::Dict(_, _) => self.lookup_class_attribute("dict", attr),
-            Type::Set(_) => self.lookup_class_attribute("set", attr),
+            Type::Class(name) => self.lookup_class_attribute(name, attr, &[]),
+            Type::ClassObject(name) => self.lookup_class_object_attribute(name, attr),
+            Type::Str => self.lookup_class_attribute("str", attr, &[]),
+            Type::Int => self.lookup_class_attribute("int", attr, &[]),
+            Type::Float => self.lookup_class_attribute("float", attr, &[]),
+            Type::Bool => self.lookup_class_attribute("bool", attr, &[]),
+            Type::Bytes => self.lookup_class_attribute("bytes", attr, &[]),
+            Type::Tuple(elems) => self.lookup_class_attribute("tuple", attr, &[Type::union(elems.clone())]),
+            Type::VarTuple(elem) => self.lookup_class_attribute("tuple", attr, &[(**elem).clone()]),
+            Type::List(elem) => self.lookup_class_attribute("list", attr, &[(**elem).clone()]),
+            Type::Dict(key, value) => self.lookup_class_attribute("dict", attr, &[(**key).clone(), (**value).clone()]),
+            Type::Set(elem) => self.lookup_class_attribute("set", attr, &[(**elem).clone()]),
+            Type::Generic(name, args) => self.lookup_class_attribute(name, attr, args),
             Type::Union(types) => {
                 // Union: attribute must exist in all variants
                 let mut attr_ty = None;
@@ -825,26 +1671,122 @@ impl TypeContext {
         }
     }
 
-    fn lookup_class_attribute(&self, class_name: &str, attr: &str) -> Option<Type> {
-        if let Some(schema) = self.classes.get(class_name) {
-            if let Some(ty) = schema.get_member(attr) {
-                return Some(ty);
-            }
-            // Check base classes
-            for base in &schema.bases {
-                if let Some(ty) = self.lookup_class_attribute(base, attr) {
-                    return Some(ty);
+    /// `attr` on instances of `class_name`, with the class's type parameters
+    /// replaced by `args` (`Any` when not given)
+    fn lookup_class_attribute(&self, class_name: &str, attr: &str, args: &[Type]) -> Option<Type> {
+        self.class_chain(class_name).iter().find_map(|name| {
+            let schema = self.classes.get(name);
+            let found = match self.overrides.get(name, attr) {
+                Some(found) => found.ty,
+                None => schema.as_ref()?.get_member(attr)?,
+            };
+            match schema {
+                // Conditionals over the parameters are decided by the arguments
+                Some(schema) if !schema.type_params.is_empty() => {
+                    Some(found.substitute_params(&schema.type_params, args).reduce_conditionals(self))
                 }
+                _ => Some(found),
+            }
+        })
+    }
+
+    /// Attribute of the class object itself: class variables and static
+    /// methods as declared, instance methods unbound so the instance is
+    /// passed first
+    fn lookup_class_object_attribute(&self, class_name: &str, attr: &str) -> Option<Type> {
+        self.class_chain(class_name).iter().find_map(|name| {
+            let type_params = self.get_class(name).map(|schema| schema.type_params.clone()).unwrap_or_default();
+            let overridden = self.overrides.get(name, attr).map(|found| found.ty.substitute_params(&type_params, &[]));
+            match self.class_member(name, attr).map(|member| member.substitute_params(&type_params, &[])) {
+                Some(MemberKind::Method(ty)) => Some(unbind(overridden.unwrap_or(ty), name)),
+                Some(MemberKind::Property(_)) => Some(Type::Class("property".to_string())),
+                Some(MemberKind::StaticMethod(ty) | MemberKind::ClassVar(ty)) => Some(overridden.unwrap_or(ty)),
+                None => overridden,
+            }
+        })
+    }
+
+    fn class_member(&self, class_name: &str, attr: &str) -> Option<MemberKind> {
+        self.get_class(class_name)?.members.get(attr).map(|member| member.value().clone())
+    }
+
+    /// Docstring of `attr` on `class_name` or the nearest base defining it
+    pub fn member_doc(&self, class_name: &str, attr: &str) -> Option<Docstring> {
+        self.class_chain(class_name).iter().find_map(|name| self.get_class(name)?.member_doc(attr))
+    }
+
+    /// Signature of method `attr` on `class_name` or the nearest base
+    /// defining it, stored under `Class.attr`
+    pub fn member_signature(&self, class_name: &str, attr: &str) -> Option<FunctionSig> {
+        self.class_chain(class_name).iter().find_map(|name| self.get_signature(&format!("{}.{}", name, attr)))
+    }
+
+    /// Signature of `attr` looked up on the class object: instance methods
+    /// take the instance as their first argument
+    pub fn class_object_signature(&self, class_name: &str, attr: &str) -> Option<FunctionSig> {
+        let owner = self.class_chain(class_name).into_iter().find(|name| self.class_member(name, attr).is_some())?;
+        let sig = self.get_signature(&format!("{}.{}", owner, attr))?;
+        match self.class_member(&owner, attr)? {
+            MemberKind::Method(_) => Some(sig.with_receiver()),
+            _ => Some(sig),
+        }
+    }
+
+    /// `class_name` followed by its bases, depth-first, each visited once so
+    /// cyclic hierarchies (`class A(A)`, `A(B)` with `B(A)`) terminate
+    fn class_chain(&self, class_name: &str) -> Vec<String> {
+        let mut chain: Vec<String> = Vec::new();
+        let mut pending = vec![class_name.to_string()];
+        while let Some(name) = pending.pop() {
+            if chain.contains(&name) {
+                continue;
             }
+            if let Some(schema) = self.get_class(&name) {
+                pending.extend(schema.bases.iter().rev().cloned());
+            }
+            chain.push(name);
         }
-        None
+        chain
+    }
+
+    /// `sub <: sup`, with classes related through their declared bases or,
+    /// for protocols, through their members
+    pub fn is_subtype(&self, sub: &Type, sup: &Type) -> bool {
+        sub.is_subtype_with(sup, self)
+    }
+
+    /// How the generic `name` varies in its parameter at `index`: as its
+    /// class declares, or as the builtin generic of that name does
+    pub fn variance(&self, name: &str, index: usize) -> Variance {
+        match self.get_class(name) {
+            Some(schema) if !schema.type_params.is_empty() => {
+                schema.variances.get(index).copied().unwrap_or(Variance::Invariant)
+            }
+            _ => VarianceAnalyzer::builtin_variance(name).unwrap_or(Variance::Covariant),
+        }
+    }
+
+    /// Whether every class `class_name` inherits from is registered, so a
+    /// member missing from its schemas is really missing
+    pub fn knows_hierarchy(&self, class_name: &str) -> bool {
+        self.class_chain(class_name).iter().all(|name| name == "object" || self.classes.contains_key(name))
+    }
+
+    /// Check whether `class_name` is `base` or inherits from it
+    pub fn is_subclass(&self, class_name: &str, base: &str) -> bool {
+        self.class_chain(class_name).iter().any(|name| name == base)
     }
 
     /// Get all available attributes for a type (for suggestions)
     pub fn get_attributes(&self, ty: &Type) -> Vec<String> {
         match ty {
-            Type::Class(name) => self.get_class_attributes(name),
+            Type::Class(name) | Type::ClassObject(name) | Type::Generic(name, _) => self.get_class_attributes(name),
             Type::Str => self.get_class_attributes("str"),
+            Type::Int => self.get_class_attributes("int"),
+            Type::Float => self.get_class_attributes("float"),
+            Type::Bool => self.get_class_attributes("bool"),
+            Type::Bytes => self.get_class_attributes("bytes"),
+            Type::Tuple(_) | Type::VarTuple(_) => self.get_class_attributes("tuple"),
             Type::List(_) => self.get_class_attributes("list"),
             Type::Dict(_, _) => self.get_class_attributes("dict"),
             Type::Set(_) => self.get_class_attributes("set"),
@@ -852,15 +1794,82 @@ impl TypeContext {
         }
     }
 
-    fn get_class_attributes(&self, class_name: &str) -> Vec<String> {
-        let mut attrs = Vec::new();
-        if let Some(schema) = self.classes.get(class_name) {
-            attrs.extend(schema.members.iter().map(|r| r.key().clone()));
-            for base in &schema.bases {
-                attrs.extend(self.get_class_attributes(base));
+    /// Members of `ty` with what kind each is, as the nearest class in its
+    /// hierarchy defines them, sorted by name. Member types have the type
+    /// arguments of `ty` filled in
+    pub fn get_members(&self, ty: &Type) -> Vec<(String, MemberKind)> {
+        let class_name = match strip_effects(ty) {
+            Type::Class(name) | Type::ClassObject(name) | Type::Generic(name, _) => name.as_str(),
+            Type::Str => "str",
+            Type::Int => "int",
+            Type::Float => "float",
+            Type::Bool => "bool",
+            Type::Bytes => "bytes",
+            Type::Tuple(_) | Type::VarTuple(_) => "tuple",
+            Type::List(_) => "list",
+            Type::Dict(_, _) => "dict",
+            Type::Set(_) => "set",
+            _ => return Vec::new(),
+        };
+        let mut members: Vec<(String, MemberKind)> = Vec::new();
+        for name in self.get_attributes(strip_effects(ty)) {
+            if members.iter().any(|(seen, _)| *seen == name) {
+                continue;
             }
+            let Some(kind) = self.class_chain(class_name).iter().find_map(|class| self.class_member(class, &name)) else { continue };
+            let kind = match (kind, self.has_attribute(strip_effects(ty), &name)) {
+                (MemberKind::Method(_), Some(ty)) => MemberKind::Method(ty),
+                (MemberKind::StaticMethod(_), Some(ty)) => MemberKind::StaticMethod(ty),
+                (MemberKind::Property(_), Some(ty)) => MemberKind::Property(ty),
+                (MemberKind::ClassVar(_), Some(ty)) => MemberKind::ClassVar(ty),
+                (kind, None) => kind,
+            };
+            members.push((name, kind));
+        }
+        members.sort_by(|a, b| a.0.cmp(&b.0));
+        members
+    }
+
+    fn get_class_attributes(&self, class_name: &str) -> Vec<String> {
+        self.class_chain(class_name).iter()
+            .filter_map(|name| self.get_class(name))
+            .flat_map(|schema| schema.members.iter().map(|r| r.key().clone()).collect::<Vec<_>>())
+            .collect()
+    }
+}
+
+/// Declared bases, protocols and the variances of generic classes
+impl Hierarchy for TypeContext {
+    fn is_subclass(&self, class_name: &str, base: &str) -> bool {
+        TypeContext::is_subclass(self, class_name, base) || self.satisfies_protocol(class_name, base)
+    }
+
+    fn variance(&self, name: &str, index: usize) -> Variance {
+        TypeContext::variance(self, name, index)
+    }
+
+    fn has_member(&self, ty: &Type, name: &str) -> Option<bool> {
+        Some(self.has_attribute(ty, name).is_some())
+    }
+}
+
+/// `ty` without the effects tracked on it
+fn strip_effects(ty: &Type) -> &Type {
+    match ty {
+        Type::Effect(inner, _) => strip_effects(inner),
+        other => other,
+    }
+}
+
+/// Method type taking an instance of `class_name` ahead of its parameters
+fn unbind(ty: Type, class_name: &str) -> Type {
+    match ty {
+        Type::Function(params, ret) => {
+            let params = std::iter::once(Type::Class(class_name.to_string())).chain(params).collect();
+            Type::Function(params, ret)
         }
-        attrs
+        Type::Effect(inner, effects) => Type::Effect(Box::new(unbind(*inner, class_name)), effects),
+        other => other,
     }
 }
 
@@ -0,0 +1,44 @@
+//! Embedding the checker through `typthon::api`
+//!
+//! Checks a source string (or the files given as arguments) and prints each
+//! diagnostic, then all of them as JSON:
+//!
+//! ```sh
+//! cargo run --example embed
+//! cargo run --example embed -- path/to/module.py
+//! ```
+
+use std::path::Path;
+use typthon::api::{Diagnostic, Session, Severity};
+
+const SOURCE: &str = "\
+def area(width: int, height: int) -> int:
+    return width * height
+
+size: int = area(2, 3)
+label: str = area(2, 3)
+";
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let files: Vec<String> = std::env::args().skip(1).collect();
+    let mut session = Session::discover(Path::new("."))?;
+
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    if files.is_empty() {
+        diagnostics.extend(session.check_source("example.py", SOURCE));
+        if let Some(ty) = session.type_of("size") {
+            println!("size: {}", ty);
+        }
+    }
+    for file in &files {
+        diagnostics.extend(session.check_file(Path::new(file))?);
+    }
+
+    for diagnostic in &diagnostics {
+        println!("{}", diagnostic);
+    }
+    println!("{}", serde_json::to_string_pretty(&diagnostics)?);
+
+    let errors = diagnostics.iter().filter(|d| d.severity == Severity::Error).count();
+    std::process::exit(if errors > 0 { 1 } else { 0 });
+}
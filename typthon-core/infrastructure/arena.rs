@@ -1,10 +1,17 @@
-//! Memory pool allocation for AST nodes
+//! Memory pool allocation for AST nodes and checker scratch space
 //!
-//! Arena allocator for efficient batch allocation/deallocation.
+//! Arena allocator for efficient batch allocation/deallocation, and a pool
+//! of the collections a checker fills per file, so checking many files
+//! reuses the space the first ones grew instead of growing it again.
 
 use typed_arena::Arena;
+use std::collections::HashMap;
+use std::ops::Range;
 use std::sync::Arc;
 use parking_lot::Mutex;
+use crate::compiler::analysis::checker::TypeError;
+use crate::compiler::analysis::constraints::Constraint;
+use crate::compiler::types::Type;
 
 /// Arena allocator for AST nodes
 pub struct AstArena {
@@ -141,6 +148,73 @@ impl Default for ArenaPool {
     }
 }
 
+/// Collections a `TypeChecker` fills while checking one file, kept empty
+/// between files with the capacity they grew to
+#[derive(Debug, Default)]
+pub struct CheckerScratch {
+    pub errors: Vec<TypeError>,
+    pub constraints: Vec<Constraint>,
+    pub name_types: HashMap<Range<usize>, Type>,
+    pub operand_types: HashMap<Range<usize>, Type>,
+    pub inferred_returns: HashMap<usize, Type>,
+    pub class_attributes: HashMap<String, HashMap<String, Type>>,
+}
+
+impl CheckerScratch {
+    /// Empty every collection, keeping its capacity
+    pub fn clear(&mut self) {
+        self.errors.clear();
+        self.constraints.clear();
+        self.name_types.clear();
+        self.operand_types.clear();
+        self.inferred_returns.clear();
+        self.class_attributes.clear();
+    }
+}
+
+/// Thread-safe pool of checker scratch space
+pub struct ScratchPool {
+    scratch: Mutex<Vec<CheckerScratch>>,
+
+    /// Maximum pool size
+    max_size: usize,
+}
+
+impl ScratchPool {
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            scratch: Mutex::new(Vec::new()),
+            max_size,
+        }
+    }
+
+    /// Scratch space from the pool, or new when it's empty
+    pub fn acquire(&self) -> CheckerScratch {
+        self.scratch.lock().pop().unwrap_or_default()
+    }
+
+    /// Return scratch space to the pool, emptied
+    pub fn release(&self, mut scratch: CheckerScratch) {
+        scratch.clear();
+        let mut pooled = self.scratch.lock();
+        if pooled.len() < self.max_size {
+            pooled.push(scratch);
+        }
+    }
+
+    /// Get pool size
+    pub fn size(&self) -> usize {
+        self.scratch.lock().len()
+    }
+}
+
+impl Default for ScratchPool {
+    fn default() -> Self {
+        // One per worker thread
+        Self::new(rayon::current_num_threads())
+    }
+}
+
 /// Statistics for arena allocation
 #[derive(Debug, Default, Clone)]
 pub struct ArenaStats {
@@ -219,6 +293,23 @@ mod tests {
         assert_eq!(pool.size(), 2);
     }
 
+    #[test]
+    fn test_scratch_pool_returns_emptied_space() {
+        let pool = ScratchPool::new(1);
+        let mut scratch = pool.acquire();
+        scratch.errors.push(TypeError::new("unused"));
+        scratch.inferred_returns.insert(0, Type::Int);
+        let capacity = scratch.errors.capacity();
+
+        pool.release(scratch);
+        pool.release(CheckerScratch::default());
+        assert_eq!(pool.size(), 1);
+
+        let reused = pool.acquire();
+        assert!(reused.errors.is_empty() && reused.inferred_returns.is_empty());
+        assert_eq!(reused.errors.capacity(), capacity);
+    }
+
     #[test]
     fn test_arena_stats() {
         let mut stats = ArenaStats::default();
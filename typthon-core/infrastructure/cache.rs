@@ -3,8 +3,11 @@
 //! Content-addressed storage with compression and LRU eviction.
 
 use crate::compiler::types::Type;
-use crate::compiler::errors::TypeError;
+use crate::compiler::errors::{codes, Severity, TypeError};
+use crate::compiler::analysis::checker::TypeError as CheckerError;
+use crate::compiler::analysis::lints::{self, Fix, LintDiagnostic, LintLevel};
 use crate::infrastructure::incremental::{ModuleId, ContentHash};
+use crate::infrastructure::paths::ModulePath;
 use dashmap::DashMap;
 use lru::LruCache;
 use parking_lot::RwLock;
@@ -12,7 +15,8 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::io::{self, Read, Write};
 use std::num::NonZeroUsize;
-use std::path::PathBuf;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 /// Cache key for lookup
@@ -27,6 +31,11 @@ impl CacheKey {
         Self { module, hash }
     }
 
+    /// Key for `content` of the module at `path`
+    pub fn for_path(path: &ModulePath, hash: ContentHash) -> Self {
+        Self::new(ModuleId::from_module_path(path), hash)
+    }
+
     /// Generate filename for this cache entry
     fn filename(&self) -> String {
         let hash_str = hex::encode(&self.hash.as_bytes()[..16]);
@@ -49,6 +58,16 @@ pub struct CacheEntry {
     /// Type errors found
     pub errors: Vec<CachedError>,
 
+    /// Lints reported
+    pub lints: Vec<CachedLint>,
+
+    /// Why the module couldn't be parsed
+    pub parse_error: Option<String>,
+
+    /// Project files the module imports, so a later run can order and
+    /// invalidate modules without parsing them
+    pub imports: Vec<PathBuf>,
+
     /// Timestamp when cached
     pub timestamp: u64,
 
@@ -63,6 +82,29 @@ pub struct CachedError {
     pub line: usize,
     pub col: usize,
     pub file: String,
+    pub code: Option<String>,
+    /// Entries written before severities existed were all errors
+    #[serde(default)]
+    pub severity: Severity,
+    pub range: Option<Range<usize>>,
+    pub suggestions: Vec<String>,
+}
+
+impl CachedError {
+    /// The error as the checker reported it
+    pub fn to_checker_error(&self) -> CheckerError {
+        CheckerError {
+            line: self.line,
+            col: self.col,
+            code: self.code.as_deref().and_then(|code| {
+                codes::ALL.iter().map(|(c, _)| *c).find(|c| *c == code)
+            }),
+            severity: self.severity,
+            range: self.range.clone(),
+            suggestions: self.suggestions.clone(),
+            ..CheckerError::new(self.message.clone())
+        }
+    }
 }
 
 impl From<&TypeError> for CachedError {
@@ -72,17 +114,64 @@ impl From<&TypeError> for CachedError {
             line: error.location.line,
             col: error.location.col,
             file: error.file.clone(),
+            code: Some(error.kind.code().to_string()),
+            severity: error.severity,
+            range: None,
+            suggestions: error.suggestions.clone(),
         }
     }
 }
 
-impl From<&crate::compiler::analysis::checker::TypeError> for CachedError {
-    fn from(error: &crate::compiler::analysis::checker::TypeError) -> Self {
+impl From<&CheckerError> for CachedError {
+    fn from(error: &CheckerError) -> Self {
         Self {
             message: error.message.clone(),
             line: error.line,
             col: error.col,
             file: String::new(),
+            code: error.code.map(str::to_string),
+            severity: error.severity,
+            range: error.range.clone(),
+            suggestions: error.suggestions.clone(),
+        }
+    }
+}
+
+/// Cached lint (serializable version of LintDiagnostic)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedLint {
+    pub code: String,
+    pub level: LintLevel,
+    pub message: String,
+    pub range: Range<usize>,
+    pub fix: Option<Fix>,
+}
+
+impl CachedLint {
+    /// The lint as reported, or `None` for a code this version doesn't have
+    pub fn to_lint(&self) -> Option<LintDiagnostic> {
+        // The checker reports warnings such as unreachable code as lints too
+        let code = lints::CODES.iter().copied()
+            .chain(codes::ALL.iter().map(|(code, _)| *code))
+            .find(|code| *code == self.code)?;
+        Some(LintDiagnostic {
+            code,
+            level: self.level,
+            message: self.message.clone(),
+            range: self.range.clone(),
+            fix: self.fix.clone(),
+        })
+    }
+}
+
+impl From<&LintDiagnostic> for CachedLint {
+    fn from(lint: &LintDiagnostic) -> Self {
+        Self {
+            code: lint.code.to_string(),
+            level: lint.level,
+            message: lint.message.clone(),
+            range: lint.range.clone(),
+            fix: lint.fix.clone(),
         }
     }
 }
@@ -276,6 +365,23 @@ impl CacheStats {
 }
 
 impl ResultCache {
+    /// Open the cache at `cache_dir` for results of checker `version`;
+    /// entries written by any other version are deleted first
+    pub fn for_version(cache_dir: PathBuf, max_size_mb: usize, version: &str) -> io::Result<Self> {
+        let cache = Self::new(cache_dir, max_size_mb)?;
+        let stamp = cache.disk.root.join("VERSION");
+        if fs::read_to_string(&stamp).ok().as_deref() != Some(version) {
+            cache.disk.clear()?;
+            fs::write(stamp, version)?;
+        }
+        Ok(cache)
+    }
+
+    /// Directory the entries are written to
+    pub fn dir(&self) -> &Path {
+        &self.disk.root
+    }
+
     pub fn new(cache_dir: PathBuf, max_size_mb: usize) -> io::Result<Self> {
         let disk = Arc::new(DiskCache::new(cache_dir)?);
         let max_size = max_size_mb * 1024 * 1024;
@@ -408,6 +514,9 @@ mod tests {
             hash: ContentHash::new([0u8; 32]),
             types: vec![("x".to_string(), Type::Int)],
             errors: vec![],
+            lints: vec![],
+            parse_error: None,
+            imports: vec![],
             timestamp: 0,
             size_bytes: 100,
         };
@@ -442,6 +551,34 @@ mod tests {
         assert_eq!(evicted[0], key1);
     }
 
+    #[test]
+    fn test_differently_cased_paths_share_an_entry() {
+        use crate::infrastructure::paths::{CaseSensitivity, PathNormalizer};
+
+        let temp = TempDir::new().unwrap();
+        std::fs::write(temp.path().join("Foo.py"), "x = 1").unwrap();
+        let cache = ResultCache::new(temp.path().join("cache"), 100).unwrap();
+        let paths = PathNormalizer::native().with_case_sensitivity(CaseSensitivity::Insensitive);
+        let hash = ContentHash::from_str("x = 1");
+
+        let key = CacheKey::for_path(&paths.normalize(&temp.path().join("Foo.py")), hash);
+        let entry = CacheEntry {
+            module: key.module,
+            hash,
+            types: vec![],
+            errors: vec![],
+            lints: vec![],
+            parse_error: None,
+            imports: vec![],
+            timestamp: 0,
+            size_bytes: 100,
+        };
+        cache.set(key, entry).unwrap();
+
+        assert!(cache.get(&CacheKey::for_path(&paths.normalize(&temp.path().join("foo.PY")), hash)).is_some());
+        assert_eq!(cache.stats().hits, 1);
+    }
+
     #[test]
     fn test_cache_stats() {
         let temp = TempDir::new().unwrap();
@@ -462,6 +599,9 @@ mod tests {
             hash: ContentHash::new([0u8; 32]),
             types: vec![],
             errors: vec![],
+            lints: vec![],
+            parse_error: None,
+            imports: vec![],
             timestamp: 0,
             size_bytes: 100,
         };
@@ -5,6 +5,7 @@
 
 use std::path::{Path, PathBuf};
 use std::io;
+use std::time::SystemTime;
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
 use dashmap::DashMap;
@@ -133,9 +134,33 @@ impl BatchFileReader {
     }
 }
 
+/// Last-modified time and length of a watched file; `None` while it
+/// doesn't exist
+type Stamp = Option<(SystemTime, u64)>;
+
+/// How a watched file changed between polls
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FileChange {
+    Created(PathBuf),
+    Modified(PathBuf),
+    Removed(PathBuf),
+}
+
+impl FileChange {
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Created(path) | Self::Modified(path) | Self::Removed(path) => path,
+        }
+    }
+}
+
 /// Async file watcher for incremental compilation
+///
+/// Polls modification times, so it works the same on every platform and
+/// filesystem; a file counts as changed when its modification time or
+/// length differs from when it was last seen.
 pub struct FileWatcher {
-    watched: Arc<DashMap<PathBuf, tokio::time::Instant>>,
+    watched: Arc<DashMap<PathBuf, Stamp>>,
 }
 
 impl FileWatcher {
@@ -145,46 +170,67 @@ impl FileWatcher {
         }
     }
 
-    /// Watch file for changes
+    /// Watch file for changes, as it is now; a file that doesn't exist yet
+    /// is reported as created once it does
     pub fn watch(&self, path: impl AsRef<Path>) {
         let path = path.as_ref().to_path_buf();
-        self.watched.insert(path, tokio::time::Instant::now());
+        let stamp = std::fs::metadata(&path).ok().and_then(|metadata| stamp(&metadata));
+        self.watched.insert(path, stamp);
+    }
+
+    /// Whether `path` is watched
+    pub fn is_watched(&self, path: impl AsRef<Path>) -> bool {
+        self.watched.contains_key(path.as_ref())
+    }
+
+    /// Watched files, in no particular order
+    pub fn watched(&self) -> Vec<PathBuf> {
+        self.watched.iter().map(|entry| entry.key().clone()).collect()
     }
 
     /// Check if file has changed since last watch
     pub async fn has_changed(&self, path: impl AsRef<Path>) -> io::Result<bool> {
         let path = path.as_ref();
+        let Some(seen) = self.watched.get(path).map(|entry| *entry.value()) else {
+            return Ok(false);
+        };
+        Ok(current_stamp(path).await? != seen)
+    }
 
-        let watched_time = self.watched.get(path)
-            .map(|entry| *entry.value());
-
-        if let Some(watched) = watched_time {
-            let metadata = fs::metadata(path).await?;
-            if let Ok(modified) = metadata.modified() {
-                let elapsed = modified.duration_since(std::time::UNIX_EPOCH).ok()
-                    .and_then(|_d| {
-                        let watched_unix = std::time::UNIX_EPOCH + watched.elapsed();
-                        modified.duration_since(watched_unix).ok()
-                    });
+    /// Get all watched files that have changed
+    pub async fn get_changed(&self) -> io::Result<Vec<PathBuf>> {
+        let mut changed = Vec::new();
 
-                return Ok(elapsed.is_some());
+        for path in self.watched() {
+            if self.has_changed(&path).await? {
+                changed.push(path);
             }
         }
 
-        Ok(false)
+        Ok(changed)
     }
 
-    /// Get all watched files that have changed
-    pub async fn get_changed(&self) -> io::Result<Vec<PathBuf>> {
-        let mut changed = Vec::new();
+    /// Changes to watched files since the last poll (or since they were
+    /// watched), sorted by path; each file is seen as it is now afterwards
+    pub async fn poll(&self) -> io::Result<Vec<FileChange>> {
+        let mut changes = Vec::new();
 
-        for entry in self.watched.iter() {
-            if self.has_changed(entry.key()).await? {
-                changed.push(entry.key().clone());
+        for path in self.watched() {
+            let Some(seen) = self.watched.get(&path).map(|entry| *entry.value()) else { continue };
+            let now = current_stamp(&path).await?;
+            if now == seen {
+                continue;
             }
+            self.watched.insert(path.clone(), now);
+            changes.push(match (seen, now) {
+                (None, _) => FileChange::Created(path),
+                (_, None) => FileChange::Removed(path),
+                _ => FileChange::Modified(path),
+            });
         }
 
-        Ok(changed)
+        changes.sort_by(|a, b| a.path().cmp(b.path()));
+        Ok(changes)
     }
 
     /// Unwatch file
@@ -198,6 +244,19 @@ impl FileWatcher {
     }
 }
 
+fn stamp(metadata: &std::fs::Metadata) -> Stamp {
+    metadata.modified().ok().map(|modified| (modified, metadata.len()))
+}
+
+/// Stamp of `path` now; missing files are `None` rather than an error
+async fn current_stamp(path: &Path) -> io::Result<Stamp> {
+    match fs::metadata(path).await {
+        Ok(metadata) => Ok(stamp(&metadata)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
 impl Default for FileWatcher {
     fn default() -> Self {
         Self::new()
@@ -285,6 +344,32 @@ mod tests {
         assert_eq!(files.len(), 5);
     }
 
+    #[tokio::test]
+    async fn test_file_watcher_reports_each_change_once() {
+        let temp = TempDir::new().unwrap();
+        let (kept, removed, created) = (temp.path().join("a.py"), temp.path().join("b.py"), temp.path().join("c.py"));
+        fs::write(&kept, "x = 1").await.unwrap();
+        fs::write(&removed, "y = 1").await.unwrap();
+
+        let watcher = FileWatcher::new();
+        for path in [&kept, &removed, &created] {
+            watcher.watch(path);
+        }
+        assert!(watcher.poll().await.unwrap().is_empty());
+
+        fs::write(&kept, "x = 10").await.unwrap();
+        fs::remove_file(&removed).await.unwrap();
+        fs::write(&created, "z = 1").await.unwrap();
+        assert!(watcher.has_changed(&kept).await.unwrap());
+        assert_eq!(watcher.poll().await.unwrap(), vec![
+            FileChange::Modified(kept.clone()),
+            FileChange::Removed(removed),
+            FileChange::Created(created),
+        ]);
+        assert!(!watcher.has_changed(&kept).await.unwrap());
+        assert!(watcher.poll().await.unwrap().is_empty());
+    }
+
     #[tokio::test]
     async fn test_buffered_writer() {
         let temp = TempDir::new().unwrap();
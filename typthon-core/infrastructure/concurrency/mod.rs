@@ -29,7 +29,7 @@ pub use structured::{
     TaskScope, Nursery, CancellationToken, scoped, scoped_with_limit,
 };
 pub use async_io::{
-    FileCache, BatchFileReader, FileWatcher, BufferedWriter,
+    FileCache, BatchFileReader, FileChange, FileWatcher, BufferedWriter,
 };
 pub use query::{
     TypeCheckingDatabase, CompilerDatabase, QueryCoordinator,
@@ -94,14 +94,22 @@ fn check_module(db: &dyn TypeCheckingDatabase, module: ModuleId) -> Arc<Vec<Type
     Arc::new(errors)
 }
 
-/// Get inferred types implementation
+/// Get inferred types implementation: every top-level and function-local
+/// binding with its type, as `TypeChecker::inferred_bindings` records them
 fn inferred_types(db: &dyn TypeCheckingDatabase, module: ModuleId) -> Arc<Vec<(String, Type)>> {
-    // Ensure module is checked first
-    let _errors = db.check_module(module);
+    use crate::compiler::analysis::TypeChecker;
+    use crate::compiler::types::TypeContext;
+
+    let source = db.source_text(module);
+    let ast = match crate::compiler::frontend::parse_module(&source) {
+        Ok(ast) => ast,
+        Err(_) => return Arc::new(vec![]),
+    };
+
+    let mut checker = TypeChecker::with_context(Arc::new(TypeContext::new()));
+    checker.check(&ast);
 
-    // Extract inferred types from checking
-    // In a full implementation, this would be stored in the context during checking
-    Arc::new(vec![])
+    Arc::new(checker.inferred_bindings().into_iter().map(|(name, ty, _)| (name, ty)).collect())
 }
 
 /// Get module dependencies implementation
@@ -367,6 +375,21 @@ mod tests {
         assert!(!Arc::ptr_eq(&errors1, &errors2));
     }
 
+    #[test]
+    fn test_inferred_types() {
+        let mut db = CompilerDatabase::new();
+        let module = ModuleId::new(1);
+
+        db.set_source_text(module, Arc::new("x = 1\ndef f(s: str) -> str:\n    y = s\n    return y\n".to_string()));
+        db.set_module_path(module, PathBuf::from("test.py"));
+
+        let types = db.inferred_types(module);
+        let find = |name: &str| types.iter().find(|(n, _)| n == name).map(|(_, ty)| ty.to_string());
+        assert_eq!(find("x").as_deref(), Some("int"));
+        assert_eq!(find("f.y").as_deref(), Some("str"));
+        assert!(Arc::ptr_eq(&types, &db.inferred_types(module)));
+    }
+
     #[tokio::test]
     async fn test_parallel_queries() {
         let coordinator = QueryCoordinator::new();
@@ -7,10 +7,12 @@
 
 use blake3::Hasher;
 use dashmap::{DashMap, DashSet};
-use std::collections::HashSet;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use serde::{Deserialize, Serialize};
+use super::paths::ModulePath;
 
 /// Unique identifier for a module
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -21,9 +23,15 @@ impl ModuleId {
         Self(id)
     }
 
+    /// Id of the module at `path`, normalized so every spelling of one file
+    /// gets the same id
     pub fn from_path(path: &Path) -> Self {
+        Self::from_module_path(&ModulePath::new(path))
+    }
+
+    pub fn from_module_path(path: &ModulePath) -> Self {
         let mut hasher = Hasher::new();
-        hasher.update(path.to_string_lossy().as_bytes());
+        hasher.update(path.key().as_bytes());
         let hash = hasher.finalize();
         Self(u64::from_le_bytes(hash.as_bytes()[..8].try_into().unwrap()))
     }
@@ -51,6 +59,19 @@ impl ContentHash {
         Self::from_content(content.as_bytes())
     }
 
+    /// Hash of `content` checked under settings summarized by `fingerprint`
+    /// (e.g. signature overrides); an empty fingerprint hashes like `from_str`
+    pub fn with_fingerprint(content: &str, fingerprint: &str) -> Self {
+        if fingerprint.is_empty() {
+            return Self::from_str(content);
+        }
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(content.as_bytes());
+        hasher.update(&[0]);
+        hasher.update(fingerprint.as_bytes());
+        Self(*hasher.finalize().as_bytes())
+    }
+
     pub fn as_bytes(&self) -> &[u8; 32] {
         &self.0
     }
@@ -84,6 +105,9 @@ pub struct DependencyGraph {
 
     /// Module -> metadata
     metadata: DashMap<ModuleId, ModuleMetadata>,
+
+    /// Normalized path -> module
+    paths: DashMap<ModulePath, ModuleId>,
 }
 
 impl DependencyGraph {
@@ -93,13 +117,16 @@ impl DependencyGraph {
             dependents: DashMap::new(),
             hashes: DashMap::new(),
             metadata: DashMap::new(),
+            paths: DashMap::new(),
         }
     }
 
-    /// Add a module to the graph
+    /// Add a module to the graph, replacing the imports it was added with
+    /// before
     pub fn add_module(&self, meta: ModuleMetadata) {
         let id = meta.id;
         let hash = meta.hash;
+        self.remove_imports(id);
 
         // Add dependencies
         let imports = meta.imports.clone();
@@ -113,9 +140,35 @@ impl DependencyGraph {
         }
 
         self.hashes.insert(id, hash);
+        self.paths.insert(ModulePath::new(&meta.path), id);
         self.metadata.insert(id, meta);
     }
 
+    /// Drop a module and its imports; modules importing it keep their
+    /// edges to it until they're added again
+    pub fn remove_module(&self, id: ModuleId) {
+        self.remove_imports(id);
+        self.hashes.remove(&id);
+        if let Some((_, meta)) = self.metadata.remove(&id) {
+            self.paths.remove(&ModulePath::new(&meta.path));
+        }
+    }
+
+    fn remove_imports(&self, id: ModuleId) {
+        if let Some((_, imports)) = self.dependencies.remove(&id) {
+            for import in imports {
+                if let Some(mut dependents) = self.dependents.get_mut(&import) {
+                    dependents.remove(&id);
+                }
+            }
+        }
+    }
+
+    /// Module registered for `path`, however the path is spelled
+    pub fn module_for_path(&self, path: &Path) -> Option<ModuleId> {
+        self.paths.get(&ModulePath::new(path)).map(|id| *id)
+    }
+
     /// Check if a module has changed
     pub fn has_changed(&self, id: ModuleId, new_hash: ContentHash) -> bool {
         self.hashes.get(&id).map_or(true, |h| *h != new_hash)
@@ -178,6 +231,140 @@ impl DependencyGraph {
         layers
     }
 
+    /// Content hashes of every module `id` imports, directly or through
+    /// other modules, in a stable order; changes whenever one of them does
+    pub fn dependency_fingerprint(&self, id: ModuleId) -> String {
+        let mut seen: HashSet<ModuleId> = HashSet::new();
+        let mut stack = vec![id];
+        while let Some(module) = stack.pop() {
+            if let Some(deps) = self.dependencies.get(&module) {
+                stack.extend(deps.iter().filter(|dep| **dep != id && seen.insert(**dep)));
+            }
+        }
+
+        let mut deps: Vec<ModuleId> = seen.into_iter().collect();
+        deps.sort_by_key(ModuleId::as_u64);
+        deps.iter()
+            .map(|dep| {
+                let hash = self.hashes.get(dep).map_or_else(String::new, |hash| {
+                    hash.as_bytes().iter().map(|b| format!("{:02x}", b)).collect()
+                });
+                format!("{} {}\n", dep.as_str(), hash)
+            })
+            .collect()
+    }
+
+    /// Groups of modules that each import every other of their group,
+    /// directly or through the rest: the strongly connected components of
+    /// the modules `id` reaches that hold more than one module or a module
+    /// importing itself, found with Tarjan's algorithm
+    pub fn import_cycles(&self, id: ModuleId) -> Vec<Vec<ModuleId>> {
+        let imports = |module: &ModuleId| -> Vec<ModuleId> {
+            let mut imports: Vec<ModuleId> = self.dependencies.get(module)
+                .map(|deps| deps.iter().copied().collect())
+                .unwrap_or_default();
+            imports.sort_by_key(ModuleId::as_u64);
+            imports
+        };
+
+        // Order each module was reached in, and the earliest reached module
+        // on the stack it leads back to
+        let mut index: HashMap<ModuleId, usize> = HashMap::new();
+        let mut low: HashMap<ModuleId, usize> = HashMap::new();
+        let mut stack: Vec<ModuleId> = Vec::new();
+        let mut on_stack: HashSet<ModuleId> = HashSet::new();
+        // Modules being visited, with their imports and how many were followed
+        let mut visiting: Vec<(ModuleId, Vec<ModuleId>, usize)> = Vec::new();
+        let mut components = Vec::new();
+
+        index.insert(id, 0);
+        low.insert(id, 0);
+        stack.push(id);
+        on_stack.insert(id);
+        visiting.push((id, imports(&id), 0));
+        while let Some((module, deps, followed)) = visiting.last_mut() {
+            let module = *module;
+            if let Some(&dep) = deps.get(*followed) {
+                *followed += 1;
+                match index.get(&dep) {
+                    Some(&reached) if on_stack.contains(&dep) => {
+                        let lowest = low[&module].min(reached);
+                        low.insert(module, lowest);
+                    }
+                    Some(_) => {}
+                    None => {
+                        index.insert(dep, index.len());
+                        low.insert(dep, index[&dep]);
+                        stack.push(dep);
+                        on_stack.insert(dep);
+                        visiting.push((dep, imports(&dep), 0));
+                    }
+                }
+                continue;
+            }
+
+            visiting.pop();
+            if let Some((parent, ..)) = visiting.last() {
+                let lowest = low[parent].min(low[&module]);
+                low.insert(*parent, lowest);
+            }
+            if low[&module] != index[&module] {
+                continue;
+            }
+            let mut component = Vec::new();
+            while let Some(member) = stack.pop() {
+                on_stack.remove(&member);
+                component.push(member);
+                if member == module {
+                    break;
+                }
+            }
+            let imports_itself = || self.dependencies.get(&module).is_some_and(|deps| deps.contains(&module));
+            if component.len() > 1 || imports_itself() {
+                components.push(component);
+            }
+        }
+        components
+    }
+
+    /// An import cycle through `id`, as the modules along it: from the
+    /// member of `id`'s cycle group with the least path, the fewest imports
+    /// back to it, taking modules by path where there's a choice. The
+    /// chain ends with the module whose import closes the cycle
+    pub fn cycle_through(&self, id: ModuleId) -> Option<Vec<ModuleId>> {
+        let group: HashSet<ModuleId> = self.import_cycles(id).into_iter()
+            .find(|component| component.contains(&id))?
+            .into_iter()
+            .collect();
+        let key = |module: &ModuleId| (self.metadata.get(module).map(|meta| meta.path.clone()), module.as_u64());
+        let start = *group.iter().min_by_key(|module| key(module))?;
+
+        // Breadth first from `start`, within the group, until an import leads back
+        let mut previous: HashMap<ModuleId, ModuleId> = HashMap::new();
+        let mut queue = VecDeque::from([start]);
+        while let Some(module) = queue.pop_front() {
+            let mut deps: Vec<ModuleId> = self.dependencies.get(&module)
+                .map(|deps| deps.iter().copied().filter(|dep| group.contains(dep)).collect())
+                .unwrap_or_default();
+            deps.sort_by_key(key);
+            for dep in deps {
+                if dep == start {
+                    let mut chain = vec![module];
+                    while let Some(before) = previous.get(chain.last()?) {
+                        chain.push(*before);
+                    }
+                    chain.reverse();
+                    return Some(chain);
+                }
+                if let Entry::Vacant(entry) = previous.entry(dep) {
+                    entry.insert(module);
+                    queue.push_back(dep);
+                }
+            }
+        }
+        None
+    }
+
     /// Get module metadata
     pub fn get_metadata(&self, id: ModuleId) -> Option<ModuleMetadata> {
         self.metadata.get(&id).map(|m| m.clone())
@@ -287,6 +474,11 @@ impl IncrementalEngine {
         self.graph.dependency_layers()
     }
 
+    /// See `DependencyGraph::dependency_fingerprint`
+    pub fn dependency_fingerprint(&self, id: ModuleId) -> String {
+        self.graph.dependency_fingerprint(id)
+    }
+
     /// Enable/disable incremental checking
     pub fn set_enabled(&mut self, enabled: bool) {
         self.enabled = enabled;
@@ -352,6 +544,99 @@ mod tests {
         assert!(invalid.contains(&id_c));
     }
 
+    #[test]
+    fn test_dependency_fingerprint_follows_transitive_imports() {
+        let graph = DependencyGraph::new();
+        let module = |id: u64, content: &str, imports: Vec<ModuleId>| ModuleMetadata {
+            id: ModuleId(id),
+            path: PathBuf::from(format!("{}.py", id)),
+            hash: ContentHash::from_str(content),
+            timestamp: 0,
+            imports,
+        };
+        graph.add_module(module(1, "a", vec![]));
+        graph.add_module(module(2, "b", vec![ModuleId(1)]));
+        graph.add_module(module(3, "c", vec![ModuleId(2)]));
+
+        let before = graph.dependency_fingerprint(ModuleId(3));
+        assert_eq!(before.lines().count(), 2);
+        assert_eq!(graph.dependency_fingerprint(ModuleId(1)), "");
+
+        graph.update_hash(ModuleId(1), ContentHash::from_str("a2"));
+        assert_ne!(graph.dependency_fingerprint(ModuleId(3)), before);
+    }
+
+    #[test]
+    fn test_readding_a_module_replaces_its_imports() {
+        let graph = DependencyGraph::new();
+        let module = |id: u64, imports: Vec<ModuleId>| ModuleMetadata {
+            id: ModuleId(id),
+            path: PathBuf::from(format!("{}.py", id)),
+            hash: ContentHash::from_str(""),
+            timestamp: 0,
+            imports,
+        };
+        graph.add_module(module(1, vec![]));
+        graph.add_module(module(2, vec![ModuleId(1)]));
+        graph.add_module(module(2, vec![]));
+        assert_eq!(graph.invalidate(&[ModuleId(1)]).len(), 1);
+
+        graph.add_module(module(2, vec![ModuleId(1)]));
+        graph.remove_module(ModuleId(2));
+        assert_eq!(graph.invalidate(&[ModuleId(1)]).len(), 1);
+        assert_eq!(graph.module_for_path(Path::new("2.py")), None);
+    }
+
+    #[test]
+    fn test_cycle_through_starts_at_the_least_path() {
+        let graph = DependencyGraph::new();
+        let module = |id: u64, imports: Vec<ModuleId>| ModuleMetadata {
+            id: ModuleId(id),
+            path: PathBuf::from(format!("{}.py", id)),
+            hash: ContentHash::from_str(""),
+            timestamp: 0,
+            imports,
+        };
+        // 1 -> 2 -> 3 -> 1, 3 -> 4 -> 4, and 5 importing into the cycle
+        graph.add_module(module(1, vec![ModuleId(2)]));
+        graph.add_module(module(2, vec![ModuleId(3)]));
+        graph.add_module(module(3, vec![ModuleId(1), ModuleId(4)]));
+        graph.add_module(module(4, vec![ModuleId(4)]));
+        graph.add_module(module(5, vec![ModuleId(2)]));
+
+        let mut cycles: Vec<Vec<u64>> = graph.import_cycles(ModuleId(5)).into_iter()
+            .map(|cycle| {
+                let mut ids: Vec<u64> = cycle.iter().map(ModuleId::as_u64).collect();
+                ids.sort_unstable();
+                ids
+            })
+            .collect();
+        cycles.sort();
+        assert_eq!(cycles, vec![vec![1, 2, 3], vec![4]]);
+
+        let chain = |id: u64| graph.cycle_through(ModuleId(id)).map(|chain| chain.iter().map(ModuleId::as_u64).collect::<Vec<_>>());
+        assert_eq!(chain(2), Some(vec![1, 2, 3]));
+        assert_eq!(chain(4), Some(vec![4]));
+        assert_eq!(chain(5), None);
+    }
+
+    #[test]
+    fn test_module_lookup_ignores_path_spelling() {
+        let graph = DependencyGraph::new();
+        let id = ModuleId::from_path(Path::new("pkg/a.py"));
+        assert_eq!(id, ModuleId::from_path(Path::new("./pkg/../pkg/a.py")));
+
+        graph.add_module(ModuleMetadata {
+            id,
+            path: PathBuf::from("pkg/a.py"),
+            hash: ContentHash::from_str("a"),
+            timestamp: 0,
+            imports: vec![],
+        });
+        assert_eq!(graph.module_for_path(Path::new("./pkg/a.py")), Some(id));
+        assert_eq!(graph.module_for_path(Path::new("pkg/b.py")), None);
+    }
+
     #[test]
     fn test_dependency_layers() {
         let graph = DependencyGraph::new();
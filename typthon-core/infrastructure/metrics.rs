@@ -1,7 +1,8 @@
 //! Performance metrics and monitoring
 
 use parking_lot::RwLock;
-use std::collections::HashMap;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
@@ -13,6 +14,12 @@ pub struct PerformanceMetrics {
     /// Counter metrics
     counters: RwLock<HashMap<String, u64>>,
 
+    /// Time attributed to a phase (and optionally one rule), per file
+    phases: RwLock<HashMap<String, HashMap<PhaseKey, PhaseTotal>>>,
+
+    /// Largest value seen for each high-water mark
+    peaks: RwLock<HashMap<String, u64>>,
+
     /// Start time for uptime
     start_time: Instant,
 }
@@ -22,6 +29,8 @@ impl PerformanceMetrics {
         Self {
             timings: RwLock::new(HashMap::new()),
             counters: RwLock::new(HashMap::new()),
+            phases: RwLock::new(HashMap::new()),
+            peaks: RwLock::new(HashMap::new()),
             start_time: Instant::now(),
         }
     }
@@ -44,6 +53,126 @@ impl PerformanceMetrics {
         *counters.entry(name.into()).or_default() += value;
     }
 
+    /// Raise a high-water mark to `value`, if it's higher
+    pub fn record_peak(&self, name: impl Into<String>, value: u64) {
+        let mut peaks = self.peaks.write();
+        let peak = peaks.entry(name.into()).or_default();
+        *peak = (*peak).max(value);
+    }
+
+    /// Count a lookup in the cache `cache`
+    pub fn record_cache_lookup(&self, cache: &str, hit: bool) {
+        let outcome = if hit { "hits" } else { "misses" };
+        self.increment(format!("cache.{}.{}", cache, outcome));
+    }
+
+    /// Get the high-water mark `name`
+    pub fn get_peak(&self, name: &str) -> u64 {
+        self.peaks.read().get(name).copied().unwrap_or(0)
+    }
+
+    /// Fold `other` in: timings and phases are added to, counters summed and
+    /// peaks kept at the larger. Lets workers record into metrics of their
+    /// own and combine them afterwards
+    pub fn merge(&self, other: &PerformanceMetrics) {
+        if std::ptr::eq(self, other) {
+            return;
+        }
+
+        let mut timings = self.timings.write();
+        for (name, durations) in other.timings.read().iter() {
+            timings.entry(name.clone()).or_default().extend(durations);
+        }
+        drop(timings);
+
+        let mut counters = self.counters.write();
+        for (name, value) in other.counters.read().iter() {
+            *counters.entry(name.clone()).or_default() += value;
+        }
+        drop(counters);
+
+        let mut phases = self.phases.write();
+        for (file, totals) in other.phases.read().iter() {
+            let merged = phases.entry(file.clone()).or_default();
+            for (key, t) in totals {
+                let total = merged.entry(key.clone()).or_default();
+                total.calls += t.calls;
+                total.total += t.total;
+            }
+        }
+        drop(phases);
+
+        let mut peaks = self.peaks.write();
+        for (name, value) in other.peaks.read().iter() {
+            let peak = peaks.entry(name.clone()).or_default();
+            *peak = (*peak).max(*value);
+        }
+    }
+
+    /// Attribute time spent checking `file` to a phase, optionally narrowed to a rule
+    pub fn record_phase(&self, file: &str, phase: &str, rule: Option<&str>, duration: Duration) {
+        let key = PhaseKey {
+            phase: phase.to_string(),
+            rule: rule.map(str::to_string),
+        };
+        let mut phases = self.phases.write();
+        let total = phases.entry(file.to_string()).or_default().entry(key).or_default();
+        total.calls += 1;
+        total.total += duration;
+    }
+
+    /// Aggregate phase timings into a per-phase and per-file breakdown
+    pub fn phase_breakdown(&self) -> PhaseBreakdown {
+        PhaseBreakdown::from_files(&self.phases.read())
+    }
+
+    /// Phase totals, file counts, cache hit rates and peaks of a run
+    pub fn profile(&self) -> Profile {
+        let counters = self.counters.read();
+        let counter = |name: &str| counters.get(name).copied().unwrap_or(0);
+
+        let phases = self.phase_breakdown().phases.into_iter()
+            .filter(|row| row.key.rule.is_none())
+            .map(|row| ProfilePhase { phase: row.key.phase, calls: row.calls, total: row.total, percent: row.percent })
+            .collect();
+
+        let mut caches: BTreeMap<String, CacheRate> = BTreeMap::new();
+        for (name, value) in counters.iter() {
+            let Some(rest) = name.strip_prefix("cache.") else { continue };
+            let Some((cache, outcome)) = rest.rsplit_once('.') else { continue };
+            let rate = caches.entry(cache.to_string()).or_insert_with(|| CacheRate {
+                cache: cache.to_string(),
+                hits: 0,
+                misses: 0,
+                hit_rate: 0.0,
+            });
+            match outcome {
+                "hits" => rate.hits += value,
+                "misses" => rate.misses += value,
+                _ => {}
+            }
+        }
+        let caches = caches.into_values()
+            .map(|mut rate| {
+                let lookups = rate.hits + rate.misses;
+                rate.hit_rate = if lookups == 0 { 0.0 } else { rate.hits as f64 / lookups as f64 };
+                rate
+            })
+            .collect();
+
+        Profile {
+            wall: self.uptime(),
+            files: FileCounts {
+                checked: counter(counters::FILES_CHECKED),
+                cached: counter(counters::FILES_CACHED),
+                parse_errors: counter(counters::PARSE_ERRORS),
+            },
+            phases,
+            caches,
+            peaks: self.peaks.read().iter().map(|(name, value)| (name.clone(), *value)).collect(),
+        }
+    }
+
     /// Get statistics for a timing metric
     pub fn get_timing_stats(&self, name: &str) -> Option<TimingStats> {
         let timings = self.timings.read();
@@ -76,6 +205,8 @@ impl PerformanceMetrics {
     pub fn reset(&self) {
         self.timings.write().clear();
         self.counters.write().clear();
+        self.phases.write().clear();
+        self.peaks.write().clear();
     }
 
     /// Generate summary report
@@ -199,6 +330,286 @@ impl MetricsSummary {
     }
 }
 
+/// Names of the counters and peaks the checking pipeline records
+pub mod counters {
+    /// Files parsed, and checked when they parsed
+    pub const FILES_CHECKED: &str = "files.checked";
+    /// Files whose results came from the result cache
+    pub const FILES_CACHED: &str = "files.cached";
+    /// Files that didn't parse
+    pub const PARSE_ERRORS: &str = "files.parse_errors";
+    /// The result cache, as named in `record_cache_lookup`
+    pub const RESULT_CACHE: &str = "results";
+    /// Peak: constraints pending when a module's constraints are solved
+    pub const CONSTRAINTS: &str = "constraints";
+}
+
+/// How many files a run checked, and how
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct FileCounts {
+    pub checked: u64,
+    pub cached: u64,
+    pub parse_errors: u64,
+}
+
+impl FileCounts {
+    /// Files checked or answered from the cache
+    pub fn total(&self) -> u64 {
+        self.checked + self.cached
+    }
+}
+
+/// Time spent in one phase across all files
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfilePhase {
+    pub phase: String,
+    pub calls: usize,
+    #[serde(serialize_with = "serialize_nanos", rename = "total_ns")]
+    pub total: Duration,
+    pub percent: f64,
+}
+
+/// Lookups in one cache
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheRate {
+    pub cache: String,
+    pub hits: u64,
+    pub misses: u64,
+    pub hit_rate: f64,
+}
+
+/// What `--profile` reports: where a run's time went, how many files it
+/// checked, how often its caches answered and how large its work got
+#[derive(Debug, Clone, Serialize)]
+pub struct Profile {
+    #[serde(serialize_with = "serialize_nanos", rename = "wall_ns")]
+    pub wall: Duration,
+    pub files: FileCounts,
+    pub phases: Vec<ProfilePhase>,
+    pub caches: Vec<CacheRate>,
+    pub peaks: BTreeMap<String, u64>,
+}
+
+impl Profile {
+    /// Time recorded for `phase`, zero when it never ran
+    pub fn phase_time(&self, phase: &str) -> Duration {
+        self.phases.iter().find(|row| row.phase == phase).map_or(Duration::ZERO, |row| row.total)
+    }
+
+    /// Format as tables of phases, files, caches and peaks
+    pub fn report(&self) -> String {
+        let mut lines = vec![
+            "=== Profile ===".to_string(),
+            format!("{:<24} {:>8} {:>12} {:>7}", "phase", "calls", "time", "%"),
+        ];
+        for row in &self.phases {
+            lines.push(format!(
+                "{:<24} {:>8} {:>12} {:>6.1}%",
+                row.phase,
+                row.calls,
+                format!("{:.2?}", row.total),
+                row.percent
+            ));
+        }
+        lines.push(format!("{:<24} {:>8} {:>12}", "wall", "", format!("{:.2?}", self.wall)));
+
+        lines.push(String::new());
+        lines.push(format!(
+            "files: {} ({} checked, {} cached, {} with parse errors)",
+            self.files.total(),
+            self.files.checked,
+            self.files.cached,
+            self.files.parse_errors
+        ));
+        for rate in &self.caches {
+            lines.push(format!(
+                "cache {}: {} hits, {} misses ({:.1}% hit rate)",
+                rate.cache,
+                rate.hits,
+                rate.misses,
+                rate.hit_rate * 100.0
+            ));
+        }
+        for (name, value) in &self.peaks {
+            lines.push(format!("peak {}: {}", name, value));
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// Where check time went: a phase, optionally narrowed to one rule or plugin
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub struct PhaseKey {
+    pub phase: String,
+    pub rule: Option<String>,
+}
+
+impl std::fmt::Display for PhaseKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.rule {
+            Some(rule) => write!(f, "{}/{}", self.phase, rule),
+            None => write!(f, "{}", self.phase),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct PhaseTotal {
+    calls: usize,
+    total: Duration,
+}
+
+/// One row of the breakdown table
+#[derive(Debug, Clone, Serialize)]
+pub struct PhaseRow {
+    #[serde(flatten)]
+    pub key: PhaseKey,
+    pub calls: usize,
+    #[serde(serialize_with = "serialize_nanos", rename = "total_ns")]
+    pub total: Duration,
+    /// Share of all phase-level time (rule rows overlap their phase)
+    pub percent: f64,
+}
+
+/// Time spent on one file, with its most expensive phases and rules
+#[derive(Debug, Clone, Serialize)]
+pub struct FileBreakdown {
+    pub file: String,
+    #[serde(serialize_with = "serialize_nanos", rename = "total_ns")]
+    pub total: Duration,
+    pub top: Vec<PhaseRow>,
+}
+
+/// Phase × time × share table plus per-file top offenders
+///
+/// Phase-level rows partition the check time; rule rows (collected under
+/// `--stats-detailed`, or for plugins) are nested inside their phase.
+#[derive(Debug, Clone, Serialize)]
+pub struct PhaseBreakdown {
+    #[serde(serialize_with = "serialize_nanos", rename = "total_ns")]
+    pub total: Duration,
+    pub phases: Vec<PhaseRow>,
+    pub files: Vec<FileBreakdown>,
+}
+
+fn serialize_nanos<S: serde::Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_nanos() as u64)
+}
+
+impl PhaseBreakdown {
+    fn from_files(files: &HashMap<String, HashMap<PhaseKey, PhaseTotal>>) -> Self {
+        let phase_time = |totals: &HashMap<PhaseKey, PhaseTotal>| -> Duration {
+            totals.iter().filter(|(key, _)| key.rule.is_none()).map(|(_, t)| t.total).sum()
+        };
+        let total: Duration = files.values().map(phase_time).sum();
+        let percent = |d: Duration| {
+            if total.is_zero() { 0.0 } else { d.as_secs_f64() * 100.0 / total.as_secs_f64() }
+        };
+        let row = |key: &PhaseKey, t: &PhaseTotal| PhaseRow {
+            key: key.clone(),
+            calls: t.calls,
+            total: t.total,
+            percent: percent(t.total),
+        };
+
+        // Merge across files
+        let mut merged: HashMap<PhaseKey, PhaseTotal> = HashMap::new();
+        for totals in files.values() {
+            for (key, t) in totals {
+                let entry = merged.entry(key.clone()).or_default();
+                entry.calls += t.calls;
+                entry.total += t.total;
+            }
+        }
+
+        // Slowest phases first, each followed by its slowest rules
+        let mut phase_rows: Vec<PhaseRow> = merged.iter()
+            .filter(|(key, _)| key.rule.is_none())
+            .map(|(key, t)| row(key, t))
+            .collect();
+        phase_rows.sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.key.cmp(&b.key)));
+
+        // Rules may be recorded without a matching phase row (e.g. plugins)
+        let mut orphan_phases: Vec<String> = merged.keys()
+            .filter(|key| key.rule.is_some())
+            .map(|key| key.phase.clone())
+            .filter(|phase| !phase_rows.iter().any(|row| &row.key.phase == phase))
+            .collect();
+        orphan_phases.sort();
+        orphan_phases.dedup();
+
+        let mut phases = Vec::new();
+        let groups = phase_rows.into_iter().map(|r| (r.key.phase.clone(), Some(r)))
+            .chain(orphan_phases.into_iter().map(|p| (p, None)));
+        for (phase, phase_row) in groups {
+            phases.extend(phase_row);
+            let mut rules: Vec<PhaseRow> = merged.iter()
+                .filter(|(key, _)| key.phase == phase && key.rule.is_some())
+                .map(|(key, t)| row(key, t))
+                .collect();
+            rules.sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.key.cmp(&b.key)));
+            phases.extend(rules);
+        }
+
+        let mut file_rows: Vec<FileBreakdown> = files.iter()
+            .map(|(file, totals)| {
+                let mut top: Vec<PhaseRow> = totals.iter().map(|(key, t)| row(key, t)).collect();
+                top.sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.key.cmp(&b.key)));
+                FileBreakdown { file: file.clone(), total: phase_time(totals), top }
+            })
+            .collect();
+        file_rows.sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.file.cmp(&b.file)));
+
+        Self { total, phases, files: file_rows }
+    }
+
+    /// Phase-level row with the most time, if anything was recorded
+    pub fn dominant_phase(&self) -> Option<&PhaseRow> {
+        self.phases.iter()
+            .filter(|row| row.key.rule.is_none())
+            .max_by_key(|row| row.total)
+    }
+
+    /// Format as a table, listing up to `top` offenders for each of the `top` slowest files
+    pub fn report(&self, top: usize) -> String {
+        let mut lines = vec![
+            "=== Check time by phase ===".to_string(),
+            format!("{:<32} {:>12} {:>7}", "phase", "time", "%"),
+        ];
+
+        for row in &self.phases {
+            let label = match &row.key.rule {
+                Some(rule) => format!("  {}", rule),
+                None => row.key.phase.clone(),
+            };
+            lines.push(format!(
+                "{:<32} {:>12} {:>6.1}%",
+                label,
+                format!("{:.2?}", row.total),
+                row.percent
+            ));
+        }
+        lines.push(format!("{:<32} {:>12}", "total", format!("{:.2?}", self.total)));
+
+        lines.push(String::new());
+        lines.push("=== Slowest files ===".to_string());
+        for file in self.files.iter().take(top) {
+            lines.push(format!("{} ({:.2?})", file.file, file.total));
+            for row in file.top.iter().take(top) {
+                lines.push(format!(
+                    "  {:<30} {:>12} {:>6.1}%",
+                    row.key.to_string(),
+                    format!("{:.2?}", row.total),
+                    row.percent
+                ));
+            }
+        }
+
+        lines.join("\n")
+    }
+}
+
 /// RAII timer for automatic timing measurement
 pub struct Timer<'a> {
     metrics: &'a PerformanceMetrics,
@@ -277,6 +688,75 @@ mod tests {
         assert!(stats.total >= Duration::from_millis(10));
     }
 
+    #[test]
+    fn test_phase_breakdown() {
+        let metrics = PerformanceMetrics::new();
+
+        metrics.record_phase("a.py", "statements", None, Duration::from_millis(30));
+        metrics.record_phase("a.py", "statements", Some("attributes"), Duration::from_millis(20));
+        metrics.record_phase("a.py", "constraints", None, Duration::from_millis(10));
+        metrics.record_phase("b.py", "statements", None, Duration::from_millis(60));
+        metrics.record_phase("b.py", "plugins", Some("slow-plugin"), Duration::from_millis(5));
+
+        let breakdown = metrics.phase_breakdown();
+        assert_eq!(breakdown.total, Duration::from_millis(100));
+
+        // Phases sorted by time, rules nested after their phase
+        let labels: Vec<String> = breakdown.phases.iter().map(|r| r.key.to_string()).collect();
+        assert_eq!(labels, ["statements", "statements/attributes", "constraints", "plugins/slow-plugin"]);
+        assert_eq!(breakdown.dominant_phase().unwrap().key.phase, "statements");
+        assert!((breakdown.phases[0].percent - 90.0).abs() < 1e-9);
+
+        assert_eq!(breakdown.files[0].file, "b.py");
+        assert_eq!(breakdown.files[1].top[0].key.to_string(), "statements");
+
+        let report = breakdown.report(5);
+        assert!(report.contains("  attributes"));
+        assert!(report.contains("=== Slowest files ==="));
+
+        let json = serde_json::to_value(&breakdown).unwrap();
+        assert_eq!(json["total_ns"], 100_000_000);
+        assert_eq!(json["phases"][1]["rule"], "attributes");
+    }
+
+    #[test]
+    fn test_merge_combines_worker_metrics() {
+        let total = PerformanceMetrics::new();
+        let workers: Vec<PerformanceMetrics> = (0..2).map(|_| PerformanceMetrics::new()).collect();
+
+        workers[0].record_phase("a.py", "parse", None, Duration::from_millis(4));
+        workers[0].increment(counters::FILES_CHECKED);
+        workers[0].record_peak(counters::CONSTRAINTS, 7);
+        workers[1].record_phase("b.py", "parse", None, Duration::from_millis(6));
+        workers[1].increment(counters::FILES_CHECKED);
+        workers[1].record_peak(counters::CONSTRAINTS, 3);
+        workers[1].record_timing("solve", Duration::from_millis(1));
+        for worker in &workers {
+            total.merge(worker);
+        }
+        total.merge(&total);
+        total.record_cache_lookup(counters::RESULT_CACHE, true);
+        total.record_cache_lookup(counters::RESULT_CACHE, false);
+        total.record_cache_lookup(counters::RESULT_CACHE, false);
+        total.record_cache_lookup(counters::RESULT_CACHE, true);
+
+        assert_eq!(total.get_counter(counters::FILES_CHECKED), 2);
+        assert_eq!(total.get_peak(counters::CONSTRAINTS), 7);
+        assert_eq!(total.get_timing_stats("solve").unwrap().count, 1);
+
+        let profile = total.profile();
+        assert_eq!(profile.phase_time("parse"), Duration::from_millis(10));
+        assert_eq!(profile.phases[0].calls, 2);
+        assert_eq!(profile.files, FileCounts { checked: 2, cached: 0, parse_errors: 0 });
+        assert_eq!(profile.caches[0].cache, "results");
+        assert!((profile.caches[0].hit_rate - 0.5).abs() < 1e-9);
+        assert!(profile.report().contains("cache results: 2 hits, 2 misses (50.0% hit rate)"));
+
+        let json = serde_json::to_value(&profile).unwrap();
+        assert_eq!(json["phases"][0]["total_ns"], 10_000_000);
+        assert_eq!(json["peaks"]["constraints"], 7);
+    }
+
     #[test]
     fn test_summary() {
         let metrics = PerformanceMetrics::new();
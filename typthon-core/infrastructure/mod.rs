@@ -5,6 +5,8 @@ pub mod incremental;
 pub mod logging;
 pub mod metrics;
 pub mod parallel;
+pub mod paths;
+pub mod watch;
 
 // Concurrency patterns
 pub mod concurrency;
@@ -16,4 +18,6 @@ pub use incremental::*;
 pub use logging::*;
 pub use metrics::*;
 pub use parallel::*;
+pub use paths::*;
+pub use watch::WatchSession;
 pub use concurrency::*;
@@ -6,17 +6,21 @@
 //! - Query system for memoized, incremental type checking
 //! - Structured concurrency for proper resource management
 
-use crate::compiler::analysis::TypeChecker;
-use crate::compiler::types::TypeContext;
+use crate::compiler::analysis::{LintDiagnostic, TypeChecker};
+use crate::compiler::analysis::checker::{phases, TypeError as CheckerError};
+use crate::compiler::types::{Type, TypeContext};
 use crate::compiler::errors::TypeError;
 use crate::compiler::frontend::parse_module;
-use crate::infrastructure::incremental::{IncrementalEngine, ModuleId};
-use crate::infrastructure::cache::{ResultCache, CacheKey, CacheEntry, CachedError};
+use crate::infrastructure::incremental::{ContentHash, IncrementalEngine, ModuleId};
+use crate::infrastructure::arena::{CheckerScratch, ScratchPool};
+use crate::infrastructure::cache::{ResultCache, CacheKey, CacheEntry, CachedError, CachedLint};
+use crate::infrastructure::metrics::{counters, PerformanceMetrics};
 use crate::infrastructure::concurrency::{
     QueryCoordinator, BatchFileReader, CompilerPipeline, QueryModuleId,
 };
 use dashmap::DashMap;
 use rayon::prelude::*;
+use rustpython_parser::ast::{Expr, Mod, ModModule, Stmt};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Instant;
@@ -34,15 +38,29 @@ pub struct AnalysisTask {
 pub struct AnalysisResult {
     pub id: ModuleId,
     pub errors: Vec<TypeError>,
+    /// The checker's own errors, with codes, ranges and suggestions
+    pub diagnostics: Vec<CheckerError>,
+    /// Lints at the levels the checker's options set
+    pub lints: Vec<LintDiagnostic>,
+    /// Why the module couldn't be parsed; it is then not checked
+    pub parse_error: Option<String>,
     pub duration_ms: u64,
 }
 
+/// Builds the checker for the module at a path, so every module is checked
+/// with the options and rules configured for it but its own bindings
+pub type CheckerFactory = Arc<dyn Fn(&Path) -> TypeChecker + Send + Sync>;
+
+/// Part of every cache key, so results cached by another version of the
+/// checker are never reused
+pub const CACHE_VERSION: &str = concat!("typthon ", env!("CARGO_PKG_VERSION"));
+
 /// Parallel analyzer
 pub struct ParallelAnalyzer {
     /// Type context (shared across threads)
     context: Arc<TypeContext>,
-    /// Result cache
-    cache: Arc<ResultCache>,
+    /// Result cache; without one every module is checked
+    cache: Option<Arc<ResultCache>>,
     /// Incremental engine
     incremental: Arc<IncrementalEngine>,
     /// Query coordinator for incremental computation
@@ -55,6 +73,17 @@ pub struct ParallelAnalyzer {
     workers: usize,
     /// Analysis results
     results: DashMap<ModuleId, AnalysisResult>,
+    /// Checkers for `analyze_modules`; without one they share `context`
+    checker_factory: Option<CheckerFactory>,
+    /// Identifies the configuration `checker_factory` builds checkers with
+    factory_fingerprint: String,
+    /// Scratch space of finished checkers, for the next ones to reuse
+    scratch: ScratchPool,
+    /// Whether checkers take their scratch space from `scratch`
+    pooling: bool,
+    /// Where each module's metrics are merged once it's checked, with the
+    /// analyzer's own cache lookups and file counts
+    metrics: Arc<PerformanceMetrics>,
 }
 
 impl ParallelAnalyzer {
@@ -63,6 +92,21 @@ impl ParallelAnalyzer {
         cache: Arc<ResultCache>,
         incremental: Arc<IncrementalEngine>,
         workers: usize,
+    ) -> Self {
+        Self::with_cache(context, Some(cache), incremental, workers)
+    }
+
+    /// Analyzer that checks every module, neither reading nor keeping
+    /// cached results
+    pub fn uncached(context: Arc<TypeContext>, incremental: Arc<IncrementalEngine>, workers: usize) -> Self {
+        Self::with_cache(context, None, incremental, workers)
+    }
+
+    fn with_cache(
+        context: Arc<TypeContext>,
+        cache: Option<Arc<ResultCache>>,
+        incremental: Arc<IncrementalEngine>,
+        workers: usize,
     ) -> Self {
         // Configure rayon thread pool
         if workers > 0 {
@@ -81,9 +125,41 @@ impl ParallelAnalyzer {
             pipeline: CompilerPipeline::check_only(),
             workers: if workers == 0 { num_cpus::get() } else { workers },
             results: DashMap::new(),
+            checker_factory: None,
+            factory_fingerprint: String::new(),
+            scratch: ScratchPool::default(),
+            pooling: true,
+            metrics: Arc::new(PerformanceMetrics::new()),
         }
     }
 
+    /// Collect metrics into `metrics`; the metrics of each module's checker
+    /// are merged into it after the module is checked
+    pub fn with_metrics(mut self, metrics: Arc<PerformanceMetrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    pub fn metrics(&self) -> &Arc<PerformanceMetrics> {
+        &self.metrics
+    }
+
+    /// Check each module with a checker from `factory`. `fingerprint`
+    /// identifies the configuration of those checkers, so results cached
+    /// under another configuration aren't reused
+    pub fn with_checker_factory(mut self, factory: CheckerFactory, fingerprint: impl Into<String>) -> Self {
+        self.checker_factory = Some(factory);
+        self.factory_fingerprint = fingerprint.into();
+        self
+    }
+
+    /// Have each checker reuse the scratch space of one that finished (the
+    /// default), or grow its own
+    pub fn with_pooling(mut self, pooling: bool) -> Self {
+        self.pooling = pooling;
+        self
+    }
+
     /// Create analyzer with custom pipeline
     pub fn with_pipeline(mut self, pipeline: CompilerPipeline) -> Self {
         self.pipeline = pipeline;
@@ -115,13 +191,18 @@ impl ParallelAnalyzer {
         query_results.into_iter().map(|(qid, errors)| {
             AnalysisResult {
                 id: ModuleId::new(qid.as_u64()),
+                diagnostics: errors.iter().map(Self::located_error).collect(),
                 errors: (*errors).clone(),
+                lints: Vec::new(),
+                parse_error: None,
                 duration_ms: 0,
             }
         }).collect()
     }
 
-    /// Analyze modules in parallel with dependency ordering
+    /// Analyze modules in parallel with dependency ordering; results come
+    /// back in the order of `modules`. Modules outside every dependency
+    /// layer, such as those in an import cycle, are checked last
     pub fn analyze_modules(&self, modules: Vec<AnalysisTask>) -> Vec<AnalysisResult> {
         self.results.clear();
 
@@ -157,47 +238,71 @@ impl ParallelAnalyzer {
                     self.results.insert(result.id, result);
                 }
             }
+
+            let remaining: Vec<_> = modules.iter()
+                .filter(|task| !self.results.contains_key(&task.id))
+                .collect();
+            let remaining_results: Vec<_> = remaining
+                .par_iter()
+                .map(|task| self.analyze_task(task))
+                .collect();
+            for result in remaining_results {
+                self.results.insert(result.id, result);
+            }
         }
 
-        self.results.iter().map(|e| e.value().clone()).collect()
+        modules.iter()
+            .filter_map(|task| self.results.get(&task.id).map(|result| result.clone()))
+            .collect()
     }
 
     /// Analyze a single task with caching
     fn analyze_task(&self, task: &AnalysisTask) -> AnalysisResult {
+        if let Some(factory) = &self.checker_factory {
+            return self.analyze_with_factory(task, factory);
+        }
         let start = Instant::now();
 
-        // Check cache
+        // Check cache; results under different signature overrides are kept apart
         let cache_key = CacheKey {
             module: task.id,
-            hash: crate::infrastructure::incremental::ContentHash::from_str(&task.content),
+            hash: crate::infrastructure::incremental::ContentHash::with_fingerprint(
+                &task.content,
+                &self.context.overrides().fingerprint(),
+            ),
         };
 
-        if let Some(cached) = self.cache.get(&cache_key) {
+        if let Some(cached) = self.cached(&task.path, &cache_key) {
             let errors = cached.errors.iter()
                 .map(|e| self.cached_error_to_type_error(e))
                 .collect();
 
             return AnalysisResult {
                 id: task.id,
+                diagnostics: cached.errors.iter().map(CachedError::to_checker_error).collect(),
                 errors,
+                lints: Vec::new(),
+                parse_error: None,
                 duration_ms: start.elapsed().as_millis() as u64,
             };
         }
 
         // Cache miss - perform analysis
+        self.metrics.increment(counters::FILES_CHECKED);
+        let mut parse_error = None;
         let (errors, inferred_types) = match parse_module(&task.content) {
             Ok(ast) => {
-                let mut checker = TypeChecker::with_context(self.context.clone());
+                let mut checker = self.with_scratch(TypeChecker::with_context(self.context.clone()));
+                checker.set_source(&task.content);
                 let check_errors = checker.check(&ast);
                 let types = self.extract_types_from_context(&task.id);
+                self.release_scratch(checker.into_scratch());
                 (check_errors, types)
             }
             Err(e) => {
-                (vec![crate::compiler::analysis::checker::TypeError {
-                    message: format!("parse error: {}", e),
-                    line: 0,
-                    col: 0,
-                }], vec![])
+                self.metrics.increment(counters::PARSE_ERRORS);
+                parse_error = Some(e.to_string());
+                (vec![CheckerError::new(format!("parse error: {}", e))], vec![])
             }
         };
 
@@ -205,10 +310,8 @@ impl ParallelAnalyzer {
 
         // Cache result
         let cached_errors: Vec<CachedError> = errors.iter().map(|e| CachedError {
-            message: e.message.clone(),
-            line: e.line,
-            col: e.col,
             file: task.path.to_string_lossy().to_string(),
+            ..CachedError::from(e)
         }).collect();
 
         let cache_entry = CacheEntry {
@@ -216,14 +319,16 @@ impl ParallelAnalyzer {
             hash: cache_key.hash,
             types: inferred_types,
             errors: cached_errors,
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            lints: Vec::new(),
+            parse_error: parse_error.clone(),
+            imports: Vec::new(),
+            timestamp: Self::now(),
             size_bytes: task.content.len(),
         };
 
-        let _ = self.cache.set(cache_key, cache_entry);
+        if let Some(cache) = &self.cache {
+            let _ = cache.set(cache_key, cache_entry);
+        }
 
         // Convert to result
         let result_errors: Vec<crate::compiler::errors::TypeError> = errors.iter().map(|e| {
@@ -233,16 +338,189 @@ impl ParallelAnalyzer {
                     found: e.message.clone(),
                 },
                 crate::compiler::errors::SourceLocation::new(e.line, e.col, e.line, e.col),
-            )
+            ).with_severity(e.severity)
         }).collect();
 
         AnalysisResult {
             id: task.id,
             errors: result_errors,
+            diagnostics: errors,
+            lints: Vec::new(),
+            parse_error,
             duration_ms: duration,
         }
     }
 
+    /// Check `task` with a checker from `factory`, unless the cache has a
+    /// result for the same content, configuration and imported modules
+    fn analyze_with_factory(&self, task: &AnalysisTask, factory: &CheckerFactory) -> AnalysisResult {
+        let start = Instant::now();
+        let fingerprint = format!(
+            "{}\n{}\n{}",
+            CACHE_VERSION,
+            self.factory_fingerprint,
+            self.incremental.dependency_fingerprint(task.id),
+        );
+        let key = CacheKey::new(task.id, ContentHash::with_fingerprint(&task.content, &fingerprint));
+
+        if let Some(cached) = self.cached(&task.path, &key) {
+            let diagnostics: Vec<CheckerError> = cached.errors.iter().map(CachedError::to_checker_error).collect();
+            return AnalysisResult {
+                id: task.id,
+                errors: Self::legacy_errors(&diagnostics, &task.path),
+                diagnostics,
+                lints: cached.lints.iter().filter_map(CachedLint::to_lint).collect(),
+                parse_error: cached.parse_error.clone(),
+                duration_ms: start.elapsed().as_millis() as u64,
+            };
+        }
+
+        let checker = self.with_scratch(factory(&task.path));
+        let metrics = checker.metrics().clone();
+        let (result, types, scratch) = Self::run_checker(task, checker);
+        self.release_scratch(scratch);
+        self.metrics.merge(&metrics);
+        if let Some(cache) = &self.cache {
+            let entry = CacheEntry {
+                module: task.id,
+                hash: key.hash,
+                types,
+                errors: result.diagnostics.iter().map(CachedError::from).collect(),
+                lints: result.lints.iter().map(CachedLint::from).collect(),
+                parse_error: result.parse_error.clone(),
+                imports: Vec::new(),
+                timestamp: Self::now(),
+                size_bytes: task.content.len(),
+            };
+            let _ = cache.set(key, entry);
+        }
+        result
+    }
+
+    /// Result cached under `key` for the module at `path`, when caching; the
+    /// lookup is timed and counted, and a hit counts the file as cached
+    fn cached(&self, path: &Path, key: &CacheKey) -> Option<Arc<CacheEntry>> {
+        let cache = self.cache.as_ref()?;
+        let start = Instant::now();
+        let cached = cache.get(key);
+        self.metrics.record_phase(&path.display().to_string(), phases::CACHE, None, start.elapsed());
+        self.metrics.record_cache_lookup(counters::RESULT_CACHE, cached.is_some());
+        if cached.is_some() {
+            self.metrics.increment(counters::FILES_CACHED);
+        }
+        cached
+    }
+
+    /// Parse, check and lint `task` with `checker`, uncached; what
+    /// `analyze_modules` does per module with a checker factory
+    pub fn check_task(task: &AnalysisTask, checker: TypeChecker) -> AnalysisResult {
+        Self::run_checker(task, checker).0
+    }
+
+    /// `checker` with pooled scratch space, when pooling
+    fn with_scratch(&self, checker: TypeChecker) -> TypeChecker {
+        if self.pooling {
+            checker.with_scratch(self.scratch.acquire())
+        } else {
+            checker
+        }
+    }
+
+    fn release_scratch(&self, scratch: CheckerScratch) {
+        if self.pooling {
+            self.scratch.release(scratch);
+        }
+    }
+
+    /// `check_task`, also returning the types of the module's public names
+    /// and the checker's scratch space
+    fn run_checker(task: &AnalysisTask, mut checker: TypeChecker) -> (AnalysisResult, Vec<(String, Type)>, CheckerScratch) {
+        let start = Instant::now();
+        let name = task.path.display().to_string();
+        checker.set_source_name(name.as_str());
+        checker.set_source_file(&task.path, &task.content);
+
+        let parsed = parse_module(&task.content);
+        checker.metrics().record_phase(&name, phases::PARSE, None, start.elapsed());
+        checker.metrics().increment(counters::FILES_CHECKED);
+        if parsed.is_err() {
+            checker.metrics().increment(counters::PARSE_ERRORS);
+        }
+
+        let (diagnostics, lints, parse_error, types) = match parsed {
+            Ok(ast) => {
+                let diagnostics = checker.check(&ast);
+                let lints = checker.lint(&ast);
+                let types = Self::public_types(&ast, &checker);
+                (diagnostics, lints, None, types)
+            }
+            Err(e) => (Vec::new(), Vec::new(), Some(e.to_string()), Vec::new()),
+        };
+
+        let result = AnalysisResult {
+            id: task.id,
+            errors: Self::legacy_errors(&diagnostics, &task.path),
+            diagnostics,
+            lints,
+            parse_error,
+            duration_ms: start.elapsed().as_millis() as u64,
+        };
+        (result, types, checker.into_scratch())
+    }
+
+    /// Types of the names a module defines at top level without a leading underscore
+    fn public_types(ast: &Mod, checker: &TypeChecker) -> Vec<(String, Type)> {
+        let Mod::Module(ModModule { body, .. }) = ast else { return Vec::new() };
+        let target_names = |targets: &[Expr]| -> Vec<String> {
+            targets.iter().filter_map(|target| target.as_name_expr()).map(|name| name.id.to_string()).collect()
+        };
+
+        body.iter()
+            .flat_map(|stmt| match stmt {
+                Stmt::FunctionDef(f) => vec![f.name.to_string()],
+                Stmt::AsyncFunctionDef(f) => vec![f.name.to_string()],
+                Stmt::ClassDef(c) => vec![c.name.to_string()],
+                Stmt::Assign(assign) => target_names(&assign.targets),
+                Stmt::AnnAssign(assign) => target_names(std::slice::from_ref(&*assign.target)),
+                _ => Vec::new(),
+            })
+            .filter(|name| !name.starts_with('_'))
+            .filter_map(|name| checker.get_type(&name).map(|ty| (name, ty)))
+            .collect()
+    }
+
+    /// Checker errors in the `errors::TypeError` form `AnalysisResult::errors` holds
+    fn legacy_errors(diagnostics: &[CheckerError], path: &Path) -> Vec<TypeError> {
+        diagnostics.iter().map(|e| {
+            TypeError::new(
+                crate::compiler::errors::ErrorKind::TypeMismatch {
+                    expected: "".to_string(),
+                    found: e.message.clone(),
+                },
+                crate::compiler::errors::SourceLocation::new(e.line, e.col, e.line, e.col),
+            ).with_file(path.display().to_string()).with_severity(e.severity)
+        }).collect()
+    }
+
+    fn now() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    /// A located error as the checker reports it
+    fn located_error(error: &TypeError) -> CheckerError {
+        CheckerError {
+            line: error.location.line,
+            col: error.location.col,
+            code: Some(error.kind.code()),
+            severity: error.severity,
+            suggestions: error.suggestions.clone(),
+            ..CheckerError::new(error.kind.to_string())
+        }
+    }
+
     fn cached_error_to_type_error(&self, cached: &CachedError) -> TypeError {
         TypeError::new(
             crate::compiler::errors::ErrorKind::TypeMismatch {
@@ -250,7 +528,7 @@ impl ParallelAnalyzer {
                 found: cached.message.clone(),
             },
             crate::compiler::errors::SourceLocation::new(cached.line, cached.col, cached.line, cached.col),
-        ).with_file(cached.file.clone())
+        ).with_file(cached.file.clone()).with_severity(cached.severity)
     }
 
     /// Analyze project directory with async I/O
@@ -372,6 +650,58 @@ mod tests {
         assert_eq!(results.len(), 2);
     }
 
+    #[test]
+    fn test_import_cycle_is_still_analyzed() {
+        let context = Arc::new(TypeContext::new());
+        let temp = TempDir::new().unwrap();
+        let cache = Arc::new(ResultCache::new(temp.path().to_path_buf(), 100).unwrap());
+        let incremental = Arc::new(IncrementalEngine::new(Arc::new(DependencyGraph::new())));
+        incremental.register_module(PathBuf::from("/p/a.py"), "import b", vec![PathBuf::from("/p/b.py")]);
+        incremental.register_module(PathBuf::from("/p/b.py"), "import a", vec![PathBuf::from("/p/a.py")]);
+
+        let factory: CheckerFactory = Arc::new(|_: &Path| TypeChecker::new());
+        let analyzer = ParallelAnalyzer::new(context, cache, incremental, 2).with_checker_factory(factory, "");
+        let tasks: Vec<_> = ["/p/b.py", "/p/a.py", "/p/c.py"].into_iter()
+            .map(|path| AnalysisTask {
+                id: ModuleId::from_path(Path::new(path)),
+                path: PathBuf::from(path),
+                content: "x: int = 'one'".to_string(),
+            })
+            .collect();
+
+        let results = analyzer.analyze_modules(tasks.clone());
+        let ids: Vec<_> = results.iter().map(|result| result.id).collect();
+        assert_eq!(ids, tasks.iter().map(|task| task.id).collect::<Vec<_>>());
+        assert!(results.iter().all(|result| !result.diagnostics.is_empty()));
+    }
+
+    #[test]
+    fn test_pooled_scratch_leaves_diagnostics_unchanged() {
+        let analyzer = |pooling: bool| {
+            let incremental = Arc::new(IncrementalEngine::new(Arc::new(DependencyGraph::new())));
+            let factory: CheckerFactory = Arc::new(|_: &Path| TypeChecker::new());
+            ParallelAnalyzer::uncached(Arc::new(TypeContext::new()), incremental, 2)
+                .with_checker_factory(factory, "")
+                .with_pooling(pooling)
+        };
+        let tasks: Vec<_> = (0..8)
+            .map(|i| AnalysisTask {
+                id: ModuleId::new(i),
+                path: PathBuf::from(format!("m{}.py", i)),
+                content: format!("class C{i}:\n    def size(self) -> int:\n        return '{i}'\n\nx: str = C{i}().size()\n"),
+            })
+            .collect();
+
+        let messages = |results: Vec<AnalysisResult>| -> Vec<Vec<String>> {
+            results.iter().map(|result| result.diagnostics.iter().map(ToString::to_string).collect()).collect()
+        };
+        let pooled = analyzer(true);
+        let with_pool = messages(pooled.analyze_modules(tasks.clone()));
+        assert_eq!(with_pool, messages(analyzer(false).analyze_modules(tasks)));
+        assert!(with_pool.iter().all(|messages| messages.len() == 2));
+        assert!(pooled.scratch.size() > 0);
+    }
+
     #[tokio::test]
     async fn test_incremental_analysis() {
         let context = Arc::new(TypeContext::new());
@@ -0,0 +1,442 @@
+//! Path normalization for module identity
+//!
+//! Every place that compares or hashes source paths (module ids, the
+//! dependency graph, cache keys, LSP documents) goes through `ModulePath`, so
+//! `Foo.py`, `./foo.py` and `file:///.../f%6Fo.py` are one module wherever the
+//! filesystem says they are one file.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Component, Path, PathBuf};
+
+/// Whether the filesystem distinguishes `Foo.py` from `foo.py`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaseSensitivity {
+    Sensitive,
+    Insensitive,
+}
+
+impl CaseSensitivity {
+    /// The default for the host platform: insensitive on Windows and macOS
+    pub fn native() -> Self {
+        if cfg!(any(windows, target_os = "macos")) {
+            CaseSensitivity::Insensitive
+        } else {
+            CaseSensitivity::Sensitive
+        }
+    }
+}
+
+/// Turns paths and `file:` URIs into `ModulePath`s
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathNormalizer {
+    case: CaseSensitivity,
+    resolve_symlinks: bool,
+}
+
+impl Default for PathNormalizer {
+    fn default() -> Self {
+        Self::native()
+    }
+}
+
+impl PathNormalizer {
+    pub fn native() -> Self {
+        Self { case: CaseSensitivity::native(), resolve_symlinks: false }
+    }
+
+    /// Treat the filesystem as (in)sensitive regardless of the platform
+    pub fn with_case_sensitivity(mut self, case: CaseSensitivity) -> Self {
+        self.case = case;
+        self
+    }
+
+    /// Resolve symlinks, so a linked file is the same module as its target
+    pub fn with_symlinks_resolved(mut self, resolve: bool) -> Self {
+        self.resolve_symlinks = resolve;
+        self
+    }
+
+    pub fn case_sensitivity(&self) -> CaseSensitivity {
+        self.case
+    }
+
+    /// Absolute, lexically clean path; on case-insensitive filesystems each
+    /// existing component takes its on-disk casing
+    pub fn normalize(&self, path: &Path) -> ModulePath {
+        let mut path = lexical(path);
+        if self.resolve_symlinks {
+            if let Ok(resolved) = fs::canonicalize(&path) {
+                path = strip_verbatim(resolved);
+            }
+        }
+        if self.case == CaseSensitivity::Insensitive {
+            path = canonical_case(&path);
+        }
+
+        let mut key = path.to_string_lossy().replace('\\', "/");
+        if self.case == CaseSensitivity::Insensitive {
+            key = key.to_lowercase();
+        }
+        ModulePath { path, key }
+    }
+
+    /// `None` for URIs that don't name a local file (`untitled:`, `https:`)
+    pub fn normalize_uri(&self, uri: &str) -> Option<ModulePath> {
+        uri_to_path(uri).map(|path| self.normalize(&path))
+    }
+}
+
+/// A source path in canonical form; equality and hashing use the normalized
+/// key, so differently spelled paths to one file compare equal
+#[derive(Debug, Clone)]
+pub struct ModulePath {
+    path: PathBuf,
+    key: String,
+}
+
+impl ModulePath {
+    /// Normalize with the platform's defaults
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        PathNormalizer::native().normalize(path.as_ref())
+    }
+
+    pub fn from_uri(uri: &str) -> Option<Self> {
+        PathNormalizer::native().normalize_uri(uri)
+    }
+
+    /// The path with on-disk casing where it could be found
+    pub fn as_path(&self) -> &Path {
+        &self.path
+    }
+
+    /// What equality and hashing compare
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn to_uri(&self) -> String {
+        path_to_uri(&self.path)
+    }
+}
+
+impl PartialEq for ModulePath {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl Eq for ModulePath {}
+
+impl Hash for ModulePath {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key.hash(state);
+    }
+}
+
+impl PartialOrd for ModulePath {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ModulePath {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+impl fmt::Display for ModulePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path.display())
+    }
+}
+
+/// Pairs of paths that differ only by case; such a project can't be checked
+/// out on a case-insensitive filesystem
+pub fn find_case_conflicts(paths: &[PathBuf]) -> Vec<(PathBuf, PathBuf)> {
+    let mut seen: HashMap<String, &PathBuf> = HashMap::new();
+    let mut conflicts = Vec::new();
+    for path in paths {
+        let folded = lexical(path).to_string_lossy().to_lowercase();
+        match seen.get(&folded) {
+            Some(first) if lexical(first) != lexical(path) => conflicts.push(((*first).clone(), path.clone())),
+            Some(_) => {}
+            None => {
+                seen.insert(folded, path);
+            }
+        }
+    }
+    conflicts.sort();
+    conflicts
+}
+
+/// Local path named by a `file:` URI, decoding percent-escapes, drive letters
+/// (`file:///c%3A/x`) and UNC hosts (`file://server/share/x`)
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    uri_to_path_string(uri, cfg!(windows)).map(PathBuf::from)
+}
+
+/// `file:` URI for a path; the inverse of `uri_to_path`
+pub fn path_to_uri(path: &Path) -> String {
+    path_string_to_uri(&path.to_string_lossy(), cfg!(windows))
+}
+
+fn uri_to_path_string(uri: &str, windows: bool) -> Option<String> {
+    let (scheme, rest) = uri.split_once("://")?;
+    if !scheme.eq_ignore_ascii_case("file") {
+        return None;
+    }
+    let rest = &rest[..rest.find(['?', '#']).unwrap_or(rest.len())];
+    let (authority, path) = rest.find('/').map_or((rest, ""), |slash| rest.split_at(slash));
+    let path = percent_decode(path);
+    let host = !authority.is_empty() && !authority.eq_ignore_ascii_case("localhost");
+
+    if !windows {
+        let path = if path.is_empty() { "/".to_string() } else { path };
+        return Some(if host { format!("//{}{}", authority, path) } else { path });
+    }
+    if host {
+        return Some(format!(r"\\{}{}", authority, path.replace('/', r"\")));
+    }
+    let bytes = path.as_bytes();
+    let path = if bytes.len() >= 3 && bytes[0] == b'/' && bytes[1].is_ascii_alphabetic() && matches!(bytes[2], b':' | b'|') {
+        let rest = if path.len() == 3 { "/" } else { &path[3..] };
+        format!("{}:{}", (bytes[1] as char).to_ascii_uppercase(), rest)
+    } else {
+        path
+    };
+    Some(path.replace('/', r"\"))
+}
+
+fn path_string_to_uri(path: &str, windows: bool) -> String {
+    if !windows {
+        return format!("file://{}", percent_encode(path));
+    }
+    let path = path.replace('\\', "/");
+    if let Some(unc) = path.strip_prefix("//") {
+        let (host, rest) = unc.find('/').map_or((unc, ""), |slash| unc.split_at(slash));
+        return format!("file://{}{}", host, percent_encode(rest));
+    }
+    format!("file:///{}", percent_encode(path.trim_start_matches('/')))
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%' && i + 2 < bytes.len())
+            .then(|| std::str::from_utf8(&bytes[i + 1..i + 3]).ok())
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' | b'@'
+            | b'!' | b'$' | b'&' | b'\'' | b'(' | b')' | b'*' | b'+' | b',' | b';' | b'=' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Absolute path with `.` and `..` resolved without touching the filesystem;
+/// drive letters are upper-cased
+fn lexical(path: &Path) -> PathBuf {
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().map(|cwd| cwd.join(path)).unwrap_or_else(|_| path.to_path_buf())
+    };
+
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Prefix(prefix) => {
+                let text = prefix.as_os_str().to_string_lossy();
+                match text.as_bytes() {
+                    [letter, b':'] => out.push(format!("{}:", (*letter as char).to_ascii_uppercase())),
+                    _ => out.push(prefix.as_os_str()),
+                }
+            }
+            Component::RootDir => out.push(component.as_os_str()),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            Component::Normal(part) => out.push(part),
+        }
+    }
+    out
+}
+
+/// `path` with each existing component spelled as it is on disk
+fn canonical_case(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    let mut components = path.components();
+    for component in components.by_ref() {
+        let Component::Normal(part) = component else {
+            out.push(component.as_os_str());
+            continue;
+        };
+        let wanted = part.to_string_lossy();
+        let on_disk = fs::read_dir(if out.as_os_str().is_empty() { Path::new(".") } else { &out })
+            .ok()
+            .and_then(|entries| {
+                let names: Vec<_> = entries.flatten().map(|entry| entry.file_name()).collect();
+                names.iter().find(|name| **name == *part)
+                    .or_else(|| names.iter().find(|name| name.to_string_lossy().to_lowercase() == wanted.to_lowercase()))
+                    .cloned()
+            });
+        match on_disk {
+            Some(name) => out.push(name),
+            None => {
+                // Nothing below a missing component exists either
+                out.push(part);
+                break;
+            }
+        }
+    }
+    out.extend(components);
+    out
+}
+
+/// Drop the `\\?\` prefix Windows adds to canonicalized paths
+fn strip_verbatim(path: PathBuf) -> PathBuf {
+    let text = path.to_string_lossy();
+    if let Some(unc) = text.strip_prefix(r"\\?\UNC\") {
+        PathBuf::from(format!(r"\\{}", unc))
+    } else if let Some(local) = text.strip_prefix(r"\\?\") {
+        PathBuf::from(local)
+    } else {
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn insensitive() -> PathNormalizer {
+        PathNormalizer::native().with_case_sensitivity(CaseSensitivity::Insensitive)
+    }
+
+    #[test]
+    fn test_lexical_normalization() {
+        let root = if cfg!(windows) { PathBuf::from(r"C:\work") } else { PathBuf::from("/work") };
+        let a = PathNormalizer::native().normalize(&root.join("src/./pkg/../mod.py"));
+        let b = PathNormalizer::native().normalize(&root.join("src/mod.py"));
+        assert_eq!(a, b);
+        assert_eq!(a.as_path(), root.join("src").join("mod.py"));
+    }
+
+    #[test]
+    fn test_insensitive_lookup_uses_on_disk_case() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir(temp.path().join("Pkg")).unwrap();
+        fs::write(temp.path().join("Pkg/Foo.py"), "").unwrap();
+
+        let lower = insensitive().normalize(&temp.path().join("pkg/foo.py"));
+        let upper = insensitive().normalize(&temp.path().join("PKG/FOO.py"));
+        assert_eq!(lower, upper);
+        assert_eq!(lower.as_path(), temp.path().join("Pkg").join("Foo.py"));
+
+        // A file that doesn't exist yet still matches by folded key
+        assert_eq!(insensitive().normalize(&temp.path().join("New.py")), insensitive().normalize(&temp.path().join("new.py")));
+
+        let sensitive = PathNormalizer::native().with_case_sensitivity(CaseSensitivity::Sensitive);
+        assert_ne!(sensitive.normalize(&temp.path().join("Pkg/Foo.py")), sensitive.normalize(&temp.path().join("pkg/foo.py")));
+    }
+
+    #[test]
+    fn test_uri_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        let file = temp.path().join("my module.py");
+        fs::write(&file, "").unwrap();
+
+        let path = ModulePath::new(&file);
+        assert!(path.to_uri().contains("my%20module.py"));
+        assert_eq!(ModulePath::from_uri(&path.to_uri()), Some(path.clone()));
+
+        // Escaping ordinary characters doesn't change the path
+        let escaped = path.to_uri().replace("module", "m%6Fdule");
+        assert_eq!(ModulePath::from_uri(&escaped), Some(path));
+        assert_eq!(ModulePath::from_uri("untitled:Untitled-1"), None);
+    }
+
+    #[test]
+    fn test_windows_uri_forms() {
+        assert_eq!(uri_to_path_string("file:///c%3A/src/Foo.py", true).as_deref(), Some(r"C:\src\Foo.py"));
+        assert_eq!(uri_to_path_string("file:///C:/src/Foo.py", true).as_deref(), Some(r"C:\src\Foo.py"));
+        assert_eq!(uri_to_path_string("file:///d|/x.py", true).as_deref(), Some(r"D:\x.py"));
+        assert_eq!(uri_to_path_string("file://server/share/a%20b.py", true).as_deref(), Some(r"\\server\share\a b.py"));
+        assert_eq!(uri_to_path_string("file://localhost/C:/x.py", true).as_deref(), Some(r"C:\x.py"));
+
+        assert_eq!(path_string_to_uri(r"C:\src\a b.py", true), "file:///C:/src/a%20b.py");
+        assert_eq!(path_string_to_uri(r"\\server\share\x.py", true), "file://server/share/x.py");
+    }
+
+    #[test]
+    fn test_find_case_conflicts() {
+        let paths = vec![
+            PathBuf::from("/p/app/Models.py"),
+            PathBuf::from("/p/app/views.py"),
+            PathBuf::from("/p/app/models.py"),
+            PathBuf::from("/p/app/./views.py"),
+        ];
+        assert_eq!(find_case_conflicts(&paths), vec![
+            (PathBuf::from("/p/app/Models.py"), PathBuf::from("/p/app/models.py")),
+        ]);
+    }
+}
+
+#[cfg(all(test, windows))]
+mod windows_tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_native_normalization_folds_case_and_drive() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("Foo.py"), "").unwrap();
+        let spelled = temp.path().to_string_lossy().to_lowercase();
+
+        let path = ModulePath::new(Path::new(&spelled).join("FOO.PY"));
+        assert_eq!(path, ModulePath::new(temp.path().join("Foo.py")));
+        assert!(path.as_path().ends_with("Foo.py"));
+    }
+
+    #[test]
+    fn test_native_uri_conversion() {
+        let temp = TempDir::new().unwrap();
+        let file = temp.path().join("Foo.py");
+        fs::write(&file, "").unwrap();
+
+        // VS Code lower-cases the drive and escapes its colon
+        let uri = path_to_uri(&file);
+        let (drive, rest) = uri["file:///".len()..].split_at(1);
+        let vscode_style = format!("file:///{}%3A{}", drive.to_lowercase(), &rest[1..]);
+        assert_eq!(ModulePath::from_uri(&vscode_style), Some(ModulePath::new(&file)));
+        assert_eq!(uri_to_path("file://server/share/x.py"), Some(PathBuf::from(r"\\server\share\x.py")));
+    }
+}
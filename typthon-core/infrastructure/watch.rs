@@ -0,0 +1,161 @@
+//! Watch mode
+//!
+//! Tracks the checked files and what each imports, and turns file changes
+//! into the set of files to check again: the changed files and every file
+//! importing one of them, directly or not. Paths are compared as given, so
+//! callers should spell them one way throughout, e.g. absolute.
+
+use crate::infrastructure::concurrency::{FileChange, FileWatcher};
+use crate::infrastructure::incremental::{ContentHash, DependencyGraph, ModuleId, ModuleMetadata};
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Quiet time after a change before checking, so a burst of saves is
+/// checked once
+pub const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// How often watched files are polled
+pub const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Checked files, what they import and whether they've changed
+pub struct WatchSession {
+    watcher: FileWatcher,
+    graph: DependencyGraph,
+    debounce: Duration,
+    poll_interval: Duration,
+}
+
+impl WatchSession {
+    pub fn new() -> Self {
+        Self {
+            watcher: FileWatcher::new(),
+            graph: DependencyGraph::new(),
+            debounce: DEBOUNCE,
+            poll_interval: POLL_INTERVAL,
+        }
+    }
+
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Watch `path`, checked with `content`, which imports the project files
+    /// `imports`; tracking it again replaces its imports
+    pub fn track(&self, path: &Path, content: &str, imports: &[PathBuf]) {
+        self.watcher.watch(path);
+        self.graph.add_module(ModuleMetadata {
+            id: ModuleId::from_path(path),
+            path: path.to_path_buf(),
+            hash: ContentHash::from_str(content),
+            timestamp: 0,
+            imports: imports.iter().map(|import| ModuleId::from_path(import)).collect(),
+        });
+    }
+
+    /// Stop watching `path`, e.g. once it's been removed
+    pub fn untrack(&self, path: &Path) {
+        self.watcher.unwatch(path);
+        self.graph.remove_module(ModuleId::from_path(path));
+    }
+
+    pub fn is_tracked(&self, path: &Path) -> bool {
+        self.watcher.is_watched(path)
+    }
+
+    /// Changes since the last poll, with files in `discovered` that aren't
+    /// tracked yet reported as created
+    pub async fn poll(&self, discovered: &[PathBuf]) -> io::Result<Vec<FileChange>> {
+        let mut changes = self.watcher.poll().await?;
+        for path in discovered {
+            if !self.watcher.is_watched(path) && !changes.iter().any(|change| change.path() == path) {
+                changes.push(FileChange::Created(path.clone()));
+            }
+        }
+        Ok(changes)
+    }
+
+    /// Wait for changes, then keep collecting them until none arrive for the
+    /// debounce interval. `discover` lists the files that should be
+    /// checked, to notice new ones
+    pub async fn next_changes(&self, mut discover: impl FnMut() -> Vec<PathBuf>) -> io::Result<Vec<FileChange>> {
+        let mut changes: Vec<FileChange> = Vec::new();
+        let mut quiet = Duration::ZERO;
+        loop {
+            tokio::time::sleep(self.poll_interval).await;
+            let mut batch = self.poll(&discover()).await?;
+            // Files created in this batch are watched from now on, so they
+            // aren't reported again
+            for change in &batch {
+                if let FileChange::Created(path) = change {
+                    self.watcher.watch(path);
+                }
+            }
+            if batch.is_empty() {
+                quiet += self.poll_interval;
+                if !changes.is_empty() && quiet >= self.debounce {
+                    return Ok(merge(changes));
+                }
+            } else {
+                quiet = Duration::ZERO;
+                changes.append(&mut batch);
+            }
+        }
+    }
+
+    /// Files to check again after `changes`: the created and modified files
+    /// and every tracked file importing a changed or removed one, directly
+    /// or not. Removed files aren't included; sorted
+    pub fn recheck_set(&self, changes: &[FileChange]) -> Vec<PathBuf> {
+        let removed: HashSet<&Path> = changes.iter()
+            .filter(|change| matches!(change, FileChange::Removed(_)))
+            .map(FileChange::path)
+            .collect();
+        let changed: Vec<ModuleId> = changes.iter().map(|change| ModuleId::from_path(change.path())).collect();
+
+        let mut files: Vec<PathBuf> = self.graph.invalidate(&changed).into_iter()
+            .filter_map(|id| self.graph.get_metadata(id).map(|meta| meta.path))
+            .chain(changes.iter().filter(|change| matches!(change, FileChange::Created(_))).map(|change| change.path().to_path_buf()))
+            .filter(|path| !removed.contains(path.as_path()))
+            .collect();
+        files.sort();
+        files.dedup();
+        files
+    }
+}
+
+impl Default for WatchSession {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One change per file for a run of polls: created then removed cancels
+/// out, removed then created is a modification
+fn merge(changes: Vec<FileChange>) -> Vec<FileChange> {
+    let mut merged: Vec<FileChange> = Vec::new();
+    for change in changes {
+        let Some(i) = merged.iter().position(|seen| seen.path() == change.path()) else {
+            merged.push(change);
+            continue;
+        };
+        let path = change.path().to_path_buf();
+        match (&merged[i], &change) {
+            (FileChange::Created(_), FileChange::Removed(_)) => {
+                merged.remove(i);
+            }
+            (FileChange::Created(_), _) => {}
+            (FileChange::Removed(_), FileChange::Created(_)) => merged[i] = FileChange::Modified(path),
+            _ => merged[i] = change,
+        }
+    }
+    merged.sort_by(|a, b| a.path().cmp(b.path()));
+    merged
+}
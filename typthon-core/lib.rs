@@ -1,8 +1,15 @@
 //! Typthon Core - A high-performance gradual type system for Python
 //!
 //! This is the core library organized semantically by functionality rather than implementation language.
+//!
+//! Embedders should use [`api`]; the other modules are internal and hidden
+//! from the docs.
+
+// Supported public surface
+pub mod api;
 
 // Compiler modules
+#[doc(hidden)]
 pub mod compiler {
     pub mod frontend;
     pub mod ast;
@@ -12,19 +19,23 @@ pub mod compiler {
 }
 
 // Runtime support
+#[doc(hidden)]
 pub mod runtime {
     // Runtime components are language-specific but organized by target
 }
 
 // FFI and bindings layer
+#[doc(hidden)]
 pub mod bindings;
 
 // Infrastructure (performance, caching, etc.)
+#[doc(hidden)]
 pub mod infrastructure;
 
 // CLI (standalone binary, not a module)
 
-// Re-export commonly used items for convenience
+// Re-export commonly used items for convenience (internal, like their modules)
+#[doc(hidden)]
 pub use compiler::{
     types::{Type, TypeContext},
     analysis::{TypeChecker, InferenceEngine, BiInfer, ConstraintSolver},
@@ -33,6 +44,7 @@ pub use compiler::{
     frontend::{parse_module, Config},
 };
 
+#[doc(hidden)]
 pub use infrastructure::{
     // Core infrastructure
     IncrementalEngine, DependencyGraph, ResultCache,
@@ -4,8 +4,12 @@
 try:
     from typthon.typthon import (
         TypeValidator,
+        Diagnostic,
         check_file_py,
+        check_file_detailed,
+        check_source_detailed,
         infer_types_py,
+        infer_module,
         analyze_effects_py,
         validate_refinement_py,
         init_runtime_py,
@@ -13,11 +17,20 @@ try:
         force_gc_py,
         clear_cache_py,
         get_metrics_py,
+        configure,
+        get_configuration,
+        patch_signature,
+        list_signature_patches,
+        clear_signature_patches,
     )
 except ImportError:
     TypeValidator = None
+    Diagnostic = None
     check_file_py = None
+    check_file_detailed = None
+    check_source_detailed = None
     infer_types_py = None
+    infer_module = None
     analyze_effects_py = None
     validate_refinement_py = None
     init_runtime_py = None
@@ -25,6 +38,11 @@ except ImportError:
     force_gc_py = None
     clear_cache_py = None
     get_metrics_py = None
+    configure = None
+    get_configuration = None
+    patch_signature = None
+    list_signature_patches = None
+    clear_signature_patches = None
 
 from typthon.decorators import type, infer
 from typthon.checker import check
@@ -55,9 +73,13 @@ __all__ = [
     # Runtime
     "Runtime",
     "TypeValidator",
+    "Diagnostic",
     # Rust functions
     "check_file_py",
+    "check_file_detailed",
+    "check_source_detailed",
     "infer_types_py",
+    "infer_module",
     "analyze_effects_py",
     "validate_refinement_py",
     "init_runtime_py",
@@ -65,6 +87,12 @@ __all__ = [
     "force_gc_py",
     "clear_cache_py",
     "get_metrics_py",
+    # Configuration
+    "configure",
+    "get_configuration",
+    "patch_signature",
+    "list_signature_patches",
+    "clear_signature_patches",
     # Type variables
     "T", "U", "V",
     "TypeVar",
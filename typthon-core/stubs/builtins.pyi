@@ -0,0 +1,34 @@
+# Bundled stub: the builtin functions typthon knows the signatures of
+
+from typing import Any
+
+def print(*values: Any, sep: str = " ", end: str = "\n", file: Any = None, flush: bool = False) -> None: ...
+def input(prompt: str = "", /) -> str: ...
+def len(obj: Any, /) -> int: ...
+def abs(x: float, /) -> float: ...
+def round(number: float, ndigits: int = ..., /) -> float: ...
+def divmod(a: int, b: int, /) -> tuple[int, int]: ...
+def pow(base: float, exp: float, mod: int = ..., /) -> float: ...
+def sum(iterable: Any, /, start: float = 0) -> float: ...
+def all(iterable: Any, /) -> bool: ...
+def any(iterable: Any, /) -> bool: ...
+def sorted(iterable: Any, /, *, key: Any = None, reverse: bool = False) -> list[Any]: ...
+def repr(obj: Any, /) -> str: ...
+def ascii(obj: Any, /) -> str: ...
+def chr(i: int, /) -> str: ...
+def ord(c: str, /) -> int: ...
+def bin(number: int, /) -> str: ...
+def oct(number: int, /) -> str: ...
+def hex(number: int, /) -> str: ...
+def hash(obj: Any, /) -> int: ...
+def id(obj: Any, /) -> int: ...
+def callable(obj: Any, /) -> bool: ...
+def isinstance(obj: Any, class_or_tuple: Any, /) -> bool: ...
+def issubclass(cls: Any, class_or_tuple: Any, /) -> bool: ...
+def hasattr(obj: Any, name: str, /) -> bool: ...
+def getattr(obj: Any, name: str, default: Any = ..., /) -> Any: ...
+def setattr(obj: Any, name: str, value: Any, /) -> None: ...
+def delattr(obj: Any, name: str, /) -> None: ...
+def iter(obj: Any, /) -> Any: ...
+def next(iterator: Any, default: Any = ..., /) -> Any: ...
+def open(file: str, mode: str = "r", buffering: int = -1, encoding: str | None = None, errors: str | None = None, newline: str | None = None) -> Any: ...
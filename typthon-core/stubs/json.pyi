@@ -0,0 +1,39 @@
+# Bundled stub: the parts of `json` typthon knows the types of
+
+from typing import Any
+
+def dumps(
+    obj: Any,
+    *,
+    skipkeys: bool = False,
+    ensure_ascii: bool = True,
+    check_circular: bool = True,
+    allow_nan: bool = True,
+    cls: Any = None,
+    indent: int | str | None = None,
+    separators: tuple[str, str] | None = None,
+    default: Any = None,
+    sort_keys: bool = False,
+) -> str: ...
+def dump(
+    obj: Any,
+    fp: Any,
+    *,
+    skipkeys: bool = False,
+    ensure_ascii: bool = True,
+    check_circular: bool = True,
+    allow_nan: bool = True,
+    cls: Any = None,
+    indent: int | str | None = None,
+    separators: tuple[str, str] | None = None,
+    default: Any = None,
+    sort_keys: bool = False,
+) -> None: ...
+def loads(s: str | bytes, *, cls: Any = None, object_hook: Any = None, parse_float: Any = None, parse_int: Any = None) -> Any: ...
+def load(fp: Any, *, cls: Any = None, object_hook: Any = None, parse_float: Any = None, parse_int: Any = None) -> Any: ...
+
+class JSONDecodeError(ValueError):
+    msg: str
+    pos: int
+    lineno: int
+    colno: int
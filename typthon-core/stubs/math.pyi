@@ -0,0 +1,35 @@
+# Bundled stub: the parts of `math` typthon knows the types of
+
+pi: float
+e: float
+tau: float
+inf: float
+nan: float
+
+def sqrt(x: float, /) -> float: ...
+def pow(x: float, y: float, /) -> float: ...
+def exp(x: float, /) -> float: ...
+def log(x: float, base: float = ..., /) -> float: ...
+def log2(x: float, /) -> float: ...
+def log10(x: float, /) -> float: ...
+def sin(x: float, /) -> float: ...
+def cos(x: float, /) -> float: ...
+def tan(x: float, /) -> float: ...
+def asin(x: float, /) -> float: ...
+def acos(x: float, /) -> float: ...
+def atan(x: float, /) -> float: ...
+def atan2(y: float, x: float, /) -> float: ...
+def degrees(x: float, /) -> float: ...
+def radians(x: float, /) -> float: ...
+def fabs(x: float, /) -> float: ...
+def fmod(x: float, y: float, /) -> float: ...
+def copysign(x: float, y: float, /) -> float: ...
+def floor(x: float, /) -> int: ...
+def ceil(x: float, /) -> int: ...
+def trunc(x: float, /) -> int: ...
+def factorial(x: int, /) -> int: ...
+def gcd(*integers: int) -> int: ...
+def isnan(x: float, /) -> bool: ...
+def isinf(x: float, /) -> bool: ...
+def isfinite(x: float, /) -> bool: ...
+def isclose(a: float, b: float, *, rel_tol: float = ..., abs_tol: float = ...) -> bool: ...
@@ -0,0 +1,21 @@
+# Bundled stub: the parts of `os` typthon knows the types of
+
+from os import path as path
+
+sep: str
+linesep: str
+name: str
+curdir: str
+pardir: str
+environ: dict[str, str]
+
+def getcwd() -> str: ...
+def getenv(key: str, default: str | None = None) -> str | None: ...
+def listdir(path: str = ...) -> list[str]: ...
+def mkdir(path: str, mode: int = ...) -> None: ...
+def makedirs(name: str, mode: int = ..., exist_ok: bool = ...) -> None: ...
+def remove(path: str) -> None: ...
+def rename(src: str, dst: str) -> None: ...
+def rmdir(path: str) -> None: ...
+def getpid() -> int: ...
+def cpu_count() -> int | None: ...
@@ -0,0 +1,18 @@
+# Bundled stub: the parts of `os.path` typthon knows the types of
+
+sep: str
+
+def join(a: str, *paths: str) -> str: ...
+def exists(path: str) -> bool: ...
+def isfile(path: str) -> bool: ...
+def isdir(path: str) -> bool: ...
+def isabs(path: str) -> bool: ...
+def abspath(path: str) -> str: ...
+def realpath(path: str) -> str: ...
+def normpath(path: str) -> str: ...
+def expanduser(path: str) -> str: ...
+def basename(p: str) -> str: ...
+def dirname(p: str) -> str: ...
+def split(p: str) -> tuple[str, str]: ...
+def splitext(p: str) -> tuple[str, str]: ...
+def getsize(filename: str) -> int: ...
@@ -0,0 +1,16 @@
+# Bundled stub: the parts of `sys` typthon knows the types of
+
+from typing import Any, NoReturn
+
+argv: list[str]
+path: list[str]
+platform: str
+version: str
+executable: str
+maxsize: int
+
+def exit(status: Any = ..., /) -> NoReturn: ...
+def getrecursionlimit() -> int: ...
+def setrecursionlimit(limit: int, /) -> None: ...
+def getsizeof(obj: Any, default: int = ..., /) -> int: ...
+def intern(string: str, /) -> str: ...
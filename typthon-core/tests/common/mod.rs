@@ -0,0 +1,18 @@
+//! Helpers shared by the integration tests
+
+use typthon::compiler::analysis::checker::TypeError;
+use typthon::{parse_module, TypeChecker};
+
+/// Checker that has checked `prelude` followed by `source`, with its
+/// errors, their lines counted within `source`
+pub fn check_with_prelude(prelude: &str, source: &str) -> (TypeChecker, Vec<TypeError>) {
+    let full = format!("{}{}", prelude, source);
+    let offset = prelude.lines().count();
+    let mut checker = TypeChecker::new();
+    checker.set_source(&full);
+    let mut errors = checker.check(&parse_module(&full).unwrap());
+    for error in &mut errors {
+        error.line = error.line.saturating_sub(offset);
+    }
+    (checker, errors)
+}
@@ -0,0 +1,6 @@
+def add(x: int, y: int) -> int:
+    return x + y
+
+
+total: int = add(1, 2)
+names: list[str] = ["a", "b"]
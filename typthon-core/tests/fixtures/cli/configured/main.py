@@ -0,0 +1,2 @@
+ratio: float = 1
+label: str = 2
@@ -0,0 +1,7 @@
+def greet(name: str) -> str:
+    return "hello " + name
+
+
+count: int = "three"
+greet(1)
+"text".uper()
@@ -0,0 +1,9 @@
+from cycle_a import ping
+
+
+def pong(n: int) -> int:
+    return n
+
+
+def loop() -> int:
+    return ping("again")
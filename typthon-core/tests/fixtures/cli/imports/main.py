@@ -0,0 +1,11 @@
+import pkg.shapes as shapes
+from pkg import area
+from util import Counter, scale as resize
+
+total: int = resize(2, 3)
+bad = resize("2", 3)
+side: float = area(1.5)
+wrong = shapes.area("big")
+
+counter = Counter(0)
+counter.bump("one")
@@ -0,0 +1 @@
+from .shapes import area
@@ -0,0 +1,9 @@
+from . import units
+
+
+def area(side: float) -> float:
+    return units.square(side)
+
+
+def describe(side: float) -> str:
+    return units.square("wide")
@@ -0,0 +1,10 @@
+def scale(value: int, factor: int) -> int:
+    return value * factor
+
+
+class Counter:
+    def __init__(self, start: int) -> None:
+        self.start = start
+
+    def bump(self, by: int) -> int:
+        return by + 1
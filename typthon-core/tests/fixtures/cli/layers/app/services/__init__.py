@@ -0,0 +1 @@
+from app.db.models import User as Customer
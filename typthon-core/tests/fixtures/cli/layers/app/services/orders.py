@@ -0,0 +1,5 @@
+from ..db.models import User
+
+
+def describe(user: User) -> User:
+    return user
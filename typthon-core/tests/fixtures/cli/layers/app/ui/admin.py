@@ -0,0 +1,5 @@
+from app.services import Customer
+
+
+def welcome(customer: Customer) -> Customer:
+    return customer
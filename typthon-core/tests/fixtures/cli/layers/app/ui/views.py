@@ -0,0 +1,6 @@
+from app.services.orders import describe
+from app.db.models import User
+
+
+def render(user: User) -> User:
+    return describe(user)
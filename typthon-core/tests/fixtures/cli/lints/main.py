@@ -0,0 +1,8 @@
+def resize(image: str, keepdim: bool, inplace: bool) -> None:
+    pass
+
+resize("img", True, False)
+
+
+def same_area(a: float, b: float) -> bool:
+    return a == b
@@ -0,0 +1,11 @@
+def area(width: int, height: int) -> int:
+    return width * height
+
+
+result = area(
+    3,
+    4,
+    5,
+)
+café: int = "crème"
+"text".uper()
@@ -0,0 +1,9 @@
+cache = {}
+
+
+def scale(value, factor: int):
+    return value * factor
+
+
+def total(prices: list[int]) -> int:
+    return sum(prices)
@@ -0,0 +1,5 @@
+seen = {}
+
+
+def check_scale(value):
+    return value * 2
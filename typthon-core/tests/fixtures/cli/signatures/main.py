@@ -0,0 +1,6 @@
+def first_dot(s: str) -> int:
+    return s.find(".")
+
+
+def shout(s: str) -> str:
+    return s.upper()
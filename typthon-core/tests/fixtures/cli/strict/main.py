@@ -0,0 +1,15 @@
+registry = {}
+
+
+def scale(value, factor: int):
+    return value * factor
+
+
+def total(prices: list[int]) -> int:
+    result = 0
+    for price in prices:
+        result += price
+    return result
+
+
+doubled = scale(2, 2)
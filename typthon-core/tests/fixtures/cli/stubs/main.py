@@ -0,0 +1,7 @@
+import math
+import vendor
+
+area: float = math.pi * math.pow(2, 2)
+math.sqrt("four")
+body: bytes = vendor.fetch("https://example.com")
+vendor.fetch("https://example.com", retries="3")
@@ -0,0 +1 @@
+def fetch(url: str, retries: int = ...) -> bytes: ...
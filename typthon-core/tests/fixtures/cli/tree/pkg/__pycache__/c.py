@@ -0,0 +1 @@
+broken: int = "cached"
@@ -0,0 +1 @@
+total: int = "none"
@@ -0,0 +1 @@
+expected: str = 1
@@ -0,0 +1 @@
+vendored: int = "venv"
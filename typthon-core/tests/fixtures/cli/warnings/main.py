@@ -0,0 +1,7 @@
+import os
+
+def first(items: list[int]) -> int:
+    for item in items:
+        return item
+    return 0
+    print("unreachable")
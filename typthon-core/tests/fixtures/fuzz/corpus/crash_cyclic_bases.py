@@ -0,0 +1,10 @@
+# stack overflow: attribute lookup on a class that inherits from itself
+class Point(Point):
+    pass
+Point.append
+class A(B):
+    pass
+class B(A):
+    pass
+x = A().y
+A().upper()
@@ -0,0 +1,10 @@
+from typing import Callable, Dict, Final, List, Literal, Optional, Union
+KB: Final = 1024
+MASK: Final = -(2 ** 63)
+size: Bounded(0, 2 * KB) = 1500
+huge: Bounded(0, 9223372036854775807 * 2) = 1
+neg: Bounded(MASK // -1, 0) = 0
+shifted: Bounded(0, 1 << 100) = 1
+status: Literal['a' * 5000] = 'a'
+nested: Dict[str, List[Optional[Union[int, Callable[[int], str]]]]] = {}
+pipe: int | str | None = None
@@ -0,0 +1,21 @@
+from enum import Enum
+from typing import Generic, TypeVar
+T = TypeVar('T')
+class Color(Enum):
+    RED = 1
+    GREEN = RED + 1
+class Node(Generic[T]):
+    value: T
+    next: 'Node[T]'
+    @property
+    def head(self) -> 'Node':
+        return self.next.next.head
+class Loop(Loop):
+    pass
+class A(B):
+    pass
+class B(A):
+    pass
+x = Color.RED.value.real
+y = (lambda *a: a)(1)(2)(3)
+z = {**{}, 'a': 1}[::-1]
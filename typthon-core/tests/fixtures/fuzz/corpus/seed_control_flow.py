@@ -0,0 +1,23 @@
+def gen(p: int = 1, *args, **kwargs) -> int:
+    while (n := p):
+        try:
+            yield n
+        except (ValueError, KeyError) as err:
+            break
+        finally:
+            pass
+    return [x for x in range(p) if x for y in x]
+
+async def fetch(url: str):
+    async with open(url) as f:
+        return await f.read()
+
+match gen(1):
+    case [first, *rest]:
+        pass
+    case {'k': int() | str(), **others}:
+        pass
+    case Point(x=0, y=second) if second:
+        pass
+    case _:
+        pass
@@ -0,0 +1,2 @@
+tags = ["math", "art"]
+index = {("alice", tags): 1}
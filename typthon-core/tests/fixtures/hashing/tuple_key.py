@@ -0,0 +1,3 @@
+scores = {("alice", "math"): 90}
+scores[("bob", "art")] = 75
+best = scores[("alice", "math")]
@@ -0,0 +1,108 @@
+"""Every statement and expression kind, for IR lowering coverage."""
+import os
+import os.path as osp
+from collections import OrderedDict as OD, deque
+from . import sibling
+from ..pkg.mod import *
+
+type Pair = tuple[int, int]
+total: int = 0
+counter = 0
+counter += 1
+a = b = [1, 2.5, 3j, "s", b"b", True, None, ...]
+x, *rest = (1, 2, 3)
+mapping = {"k": 1, **{"j": 2}}
+unique = {1, 2}
+del mapping["k"]
+global_name = lambda value, /, other=1, *args, key, flag=True, **kwargs: value + other
+
+
+@decorator(arg)
+def pure(x: int, y: int = 1) -> int:
+    """Docstring."""
+    return x + y if x > y else -y
+
+
+async def fetch(url: str) -> str:
+    async with session(url) as response:
+        async for chunk in response:
+            await consume(chunk)
+    return await response.text()
+
+
+def generator(n):
+    yield n
+    yield from range(n)
+    z = yield
+
+
+def noisy(items):
+    global counter
+    print(items)
+    for item in items:
+        if item and not item or item is None:
+            continue
+        elif item in items:
+            break
+        else:
+            pass
+    else:
+        counter = 1
+    while counter < 10:
+        counter *= 2
+    else:
+        pass
+    with open("f") as handle, open("g"):
+        handle.write(f"{counter!r:>{width}} and {items}")
+    try:
+        raise ValueError("bad") from None
+    except (ValueError, TypeError) as error:
+        assert error, "message"
+    except Exception:
+        raise
+    else:
+        pass
+    finally:
+        del items
+    try:
+        pass
+    except* OSError as group:
+        pass
+    squares = [i * i for i in range(10) if i % 2]
+    evens = {i for i in range(10)}
+    table = {k: v for k, v in zip("ab", "cd")}
+    lazy = (i async for i in aiter())
+    sliced = items[1:2:3][::-1][a, b:c]
+    if (n := len(items)) > 3 and items.count(1) <= 2 < n != 4:
+        return ~n << 2 >> 1 | 1 & 3 ^ 4 // 2 ** 3 @ m
+    return sorted(items, key=lambda i: -i)[0]
+
+
+def outer():
+    value = 1
+
+    def inner():
+        nonlocal value
+        value += 1
+        return value
+
+    return inner
+
+
+class Shape(Base, metaclass=Meta):
+    sides: int = 0
+
+    def area(self) -> float:
+        match self.sides:
+            case 0 | 1:
+                return 0.0
+            case [first, *others] if first:
+                return first
+            case {"key": value, **extra}:
+                return value
+            case Point(x=0, y=yy) as point:
+                return yy
+            case str() | None:
+                return 1.0
+            case _:
+                return float(self.sides)
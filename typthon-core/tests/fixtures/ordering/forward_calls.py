@@ -0,0 +1,26 @@
+def main() -> None:
+    total = helper(3, "four")
+    config = Config("prod")
+    print(total + 1, config.describe(), counted())
+
+
+def helper(count: int, step: int) -> int:
+    return count * step
+
+
+def counted():
+    return 1
+
+
+class Config:
+    def __init__(self, name: str) -> None:
+        self.name = name
+
+    def describe(self) -> str:
+        return self.label()
+
+    def label(self) -> str:
+        return self.name
+
+
+main()
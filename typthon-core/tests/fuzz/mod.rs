@@ -0,0 +1,684 @@
+//! Checker fuzzing support: a grammar-based Python generator, a bounded
+//! runner that turns panics and hangs into outcomes, and a block-aware
+//! minimizer for failing inputs
+
+use std::cell::{Cell, RefCell};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc;
+use std::sync::Once;
+use std::thread;
+use std::time::Duration;
+use typthon::compiler::analysis::lints::{ADJACENT_BOOL_PARAMS, BOOLEAN_POSITIONAL, FLOAT_EQUALITY};
+use typthon::compiler::analysis::{CheckerOptions, LintLevel};
+use typthon::infrastructure::CancellationToken;
+use typthon::{parse_module, TypeChecker};
+
+/// How long a cancelled check may take to wind down before it counts as stuck
+const GRACE: Duration = Duration::from_secs(2);
+
+/// Deterministic splitmix64, so a seed reproduces a run exactly
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n.max(1) as u64) as usize
+    }
+
+    pub fn chance(&mut self, percent: usize) -> bool {
+        self.below(100) < percent
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+}
+
+const NAMES: &[&str] = &["a", "b", "x", "y", "items", "data", "result", "value", "Color", "Point", "f", "g"];
+const ATTRS: &[&str] = &["value", "append", "upper", "x", "items", "RED", "get", "real"];
+const CLASSES: &[&str] = &["Point", "Color", "Node", "Base", "Shape"];
+const FUNCTIONS: &[&str] = &["f", "g", "helper", "compute", "area"];
+const BINOPS: &[&str] = &["+", "-", "*", "/", "//", "%", "**", "<<", ">>", "|", "&", "^", "@"];
+const CMPOPS: &[&str] = &["==", "!=", "<", "<=", ">", ">=", "in", "not in", "is", "is not"];
+const AUGOPS: &[&str] = &["+=", "-=", "*=", "//=", "|=", "&="];
+
+/// Generates syntactically valid Python modules from a small grammar
+pub struct Generator {
+    rng: Rng,
+    out: String,
+    in_function: bool,
+    in_loop: bool,
+}
+
+impl Generator {
+    pub fn new(seed: u64) -> Self {
+        Self { rng: Rng::new(seed), out: String::new(), in_function: false, in_loop: false }
+    }
+
+    pub fn module(&mut self) -> String {
+        self.out.clear();
+        if self.rng.chance(60) {
+            self.line(0, "from typing import Any, Callable, Dict, Final, List, Literal, Optional, Union");
+        }
+        if self.rng.chance(30) {
+            self.line(0, "from enum import Enum");
+        }
+        let count = 1 + self.rng.below(8);
+        for _ in 0..count {
+            self.stmt(0, 0);
+        }
+        std::mem::take(&mut self.out)
+    }
+
+    fn line(&mut self, indent: usize, text: &str) {
+        self.out.push_str(&"    ".repeat(indent));
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+
+    fn block(&mut self, indent: usize, depth: usize) {
+        let count = 1 + self.rng.below(3);
+        for _ in 0..count {
+            self.stmt(indent, depth + 1);
+        }
+    }
+
+    fn stmt(&mut self, indent: usize, depth: usize) {
+        // Compound statements thin out as blocks nest
+        let compound = depth < 3 && self.rng.chance(45);
+        if compound {
+            match self.rng.below(10) {
+                0 | 1 => self.function(indent, depth),
+                2 => self.class(indent, depth),
+                3 => self.if_stmt(indent, depth),
+                4 => self.loop_stmt(indent, depth),
+                5 => self.match_stmt(indent, depth),
+                6 => self.try_stmt(indent, depth),
+                7 => {
+                    let header = format!("with {} as {}:", self.expr(2), self.name());
+                    self.line(indent, &header);
+                    self.block(indent + 1, depth);
+                }
+                8 => self.enum_class(indent),
+                _ => {
+                    let header = format!("if ({} := {}):", self.name(), self.expr(2));
+                    self.line(indent, &header);
+                    self.block(indent + 1, depth);
+                }
+            }
+            return;
+        }
+
+        let text = match self.rng.below(16) {
+            0..=2 => format!("{} = {}", self.target(), self.expr(3)),
+            3 | 4 => format!("{}: {} = {}", self.name(), self.annotation(2), self.expr(3)),
+            5 => format!("{}: {}", self.name(), self.annotation(2)),
+            6 => format!("{} {} {}", self.name(), self.rng.pick(AUGOPS), self.expr(2)),
+            7 => format!("{}: Final = {}", self.name().to_uppercase(), self.const_expr(2)),
+            8 => format!("assert {}, {}", self.expr(2), self.string()),
+            9 => format!("del {}", self.name()),
+            10 if self.in_function => format!("return {}", self.expr(3)),
+            11 if self.in_function => format!("x = yield {}", self.expr(2)),
+            12 if self.in_loop => self.rng.pick(&["break", "continue"]).to_string(),
+            13 => format!("raise {}({})", self.rng.pick(&["ValueError", "TypeError", "KeyError"]), self.string()),
+            14 => self.rng.pick(&["import os", "import os.path as osp", "from collections import defaultdict", "pass"]).to_string(),
+            _ => self.expr(3),
+        };
+        self.line(indent, &text);
+    }
+
+    fn function(&mut self, indent: usize, depth: usize) {
+        for _ in 0..self.rng.below(3) {
+            let decorator = match self.rng.below(5) {
+                0 => "@staticmethod".to_string(),
+                1 => "@functools.lru_cache(maxsize=None)".to_string(),
+                2 => format!("@{}", self.rng.pick(FUNCTIONS)),
+                3 => format!("@{}({})", self.rng.pick(FUNCTIONS), self.expr(1)),
+                _ => "@property".to_string(),
+            };
+            self.line(indent, &decorator);
+        }
+
+        let mut params = Vec::new();
+        if self.rng.chance(30) {
+            params.push("self".to_string());
+        }
+        for (i, name) in ["p", "q", "r"].iter().enumerate().take(self.rng.below(4)) {
+            let mut param = name.to_string();
+            if self.rng.chance(60) {
+                param = format!("{}: {}", param, self.annotation(2));
+            }
+            // Defaults may only follow defaults
+            if i > 0 && params.last().is_some_and(|p: &String| p.contains('=')) || self.rng.chance(25) {
+                param = format!("{} = {}", param, self.const_expr(1));
+            }
+            params.push(param);
+        }
+        if self.rng.chance(20) {
+            params.push("*args".to_string());
+        }
+        if self.rng.chance(15) {
+            params.push("**kwargs".to_string());
+        }
+        let returns = if self.rng.chance(50) { format!(" -> {}", self.annotation(2)) } else { String::new() };
+        let keyword = if self.rng.chance(15) { "async def" } else { "def" };
+        let header = format!("{} {}({}){}:", keyword, self.rng.pick(FUNCTIONS), params.join(", "), returns);
+        self.line(indent, &header);
+
+        let (outer_function, outer_loop) = (self.in_function, self.in_loop);
+        self.in_function = true;
+        self.in_loop = false;
+        self.block(indent + 1, depth);
+        self.in_function = outer_function;
+        self.in_loop = outer_loop;
+    }
+
+    fn class(&mut self, indent: usize, depth: usize) {
+        let bases = match self.rng.below(4) {
+            0 => String::new(),
+            1 => format!("({})", self.rng.pick(CLASSES)),
+            2 => format!("({}, metaclass=type)", self.rng.pick(CLASSES)),
+            _ => "(Generic[T])".to_string(),
+        };
+        let header = format!("class {}{}:", self.rng.pick(CLASSES), bases);
+        self.line(indent, &header);
+        if self.rng.chance(50) {
+            let field = format!("{}: {}", self.rng.pick(ATTRS), self.annotation(2));
+            self.line(indent + 1, &field);
+        }
+        let (outer_function, outer_loop) = (self.in_function, self.in_loop);
+        self.in_function = false;
+        self.in_loop = false;
+        self.block(indent + 1, depth);
+        self.in_function = outer_function;
+        self.in_loop = outer_loop;
+    }
+
+    fn enum_class(&mut self, indent: usize) {
+        self.line(indent, "class Color(Enum):");
+        for member in ["RED", "GREEN", "BLUE"].iter().take(1 + self.rng.below(3)) {
+            let value = self.const_expr(1);
+            self.line(indent + 1, &format!("{} = {}", member, value));
+        }
+    }
+
+    fn if_stmt(&mut self, indent: usize, depth: usize) {
+        let header = format!("if {}:", self.condition());
+        self.line(indent, &header);
+        self.block(indent + 1, depth);
+        if self.rng.chance(40) {
+            let header = format!("elif {}:", self.condition());
+            self.line(indent, &header);
+            self.block(indent + 1, depth);
+        }
+        if self.rng.chance(40) {
+            self.line(indent, "else:");
+            self.block(indent + 1, depth);
+        }
+    }
+
+    fn loop_stmt(&mut self, indent: usize, depth: usize) {
+        let header = if self.rng.chance(60) {
+            format!("for {} in {}:", self.loop_target(), self.expr(2))
+        } else {
+            format!("while {}:", self.condition())
+        };
+        self.line(indent, &header);
+        let outer = std::mem::replace(&mut self.in_loop, true);
+        self.block(indent + 1, depth);
+        self.in_loop = outer;
+        if self.rng.chance(20) {
+            self.line(indent, "else:");
+            self.block(indent + 1, depth);
+        }
+    }
+
+    fn try_stmt(&mut self, indent: usize, depth: usize) {
+        self.line(indent, "try:");
+        self.block(indent + 1, depth);
+        let handler = match self.rng.below(3) {
+            0 => "except:".to_string(),
+            1 => "except (ValueError, KeyError) as err:".to_string(),
+            _ => format!("except {}:", self.rng.pick(&["Exception", "TypeError"])),
+        };
+        self.line(indent, &handler);
+        self.block(indent + 1, depth);
+        if self.rng.chance(30) {
+            self.line(indent, "finally:");
+            self.block(indent + 1, depth);
+        }
+    }
+
+    fn match_stmt(&mut self, indent: usize, depth: usize) {
+        let header = format!("match {}:", self.expr(2));
+        self.line(indent, &header);
+        let cases = 1 + self.rng.below(3);
+        for i in 0..cases {
+            let pattern = self.pattern(2);
+            let guard = if self.rng.chance(25) { format!(" if {}", self.condition()) } else { String::new() };
+            self.line(indent + 1, &format!("case {}{}:", pattern, guard));
+            self.block(indent + 2, depth);
+            // An irrefutable case has to come last
+            if i + 1 == cases && self.rng.chance(40) {
+                self.line(indent + 1, "case _:");
+                self.block(indent + 2, depth);
+            }
+        }
+    }
+
+    fn pattern(&mut self, depth: usize) -> String {
+        let leaf = depth == 0 || self.rng.chance(40);
+        if leaf {
+            return match self.rng.below(4) {
+                0 => self.int(),
+                1 => self.string(),
+                2 => "None".to_string(),
+                _ => format!("{}.{}", self.rng.pick(CLASSES), self.rng.pick(ATTRS)),
+            };
+        }
+        match self.rng.below(5) {
+            0 => format!("[{}, {}, *rest]", self.capture(), self.pattern(depth - 1)),
+            1 => format!("{{{}: {}, **others}}", self.string(), self.pattern(depth - 1)),
+            2 => format!("{}(x={}, y={})", self.rng.pick(CLASSES), self.pattern(depth - 1), self.capture()),
+            3 => format!("{}() | {}()", self.rng.pick(&["int", "str"]), self.rng.pick(&["float", "bytes"])),
+            _ => format!("({} as {})", self.pattern(depth - 1), self.capture()),
+        }
+    }
+
+    fn capture(&mut self) -> String {
+        self.rng.pick(&["first", "second", "head", "tail"]).to_string()
+    }
+
+    fn annotation(&mut self, depth: usize) -> String {
+        let leaf = depth == 0 || self.rng.chance(35);
+        if leaf {
+            return self.rng.pick(&["int", "str", "float", "bool", "bytes", "None", "Any", "Point", "'Node'", "T"]).to_string();
+        }
+        let d = depth - 1;
+        match self.rng.below(11) {
+            0 => format!("List[{}]", self.annotation(d)),
+            1 => format!("Dict[{}, {}]", self.annotation(d), self.annotation(d)),
+            2 => format!("Optional[{}]", self.annotation(d)),
+            3 => format!("Union[{}, {}]", self.annotation(d), self.annotation(d)),
+            4 => format!("Callable[[{}], {}]", self.annotation(d), self.annotation(d)),
+            5 => format!("tuple[{}, ...]", self.annotation(d)),
+            6 => format!("{} | {}", self.annotation(d), self.annotation(d)),
+            7 => format!("Literal[{}, {}]", self.const_expr(1), self.const_expr(1)),
+            8 => format!("Bounded({}, {})", self.const_expr(1), self.const_expr(1)),
+            9 => format!("Final[{}]", self.annotation(d)),
+            _ => format!("set[{}]", self.annotation(d)),
+        }
+    }
+
+    fn condition(&mut self) -> String {
+        match self.rng.below(4) {
+            0 => format!("isinstance({}, {})", self.name(), self.rng.pick(&["int", "str", "(int, str)", "Point"])),
+            1 => format!("len({}) {} {}", self.name(), self.rng.pick(CMPOPS), self.int()),
+            2 => format!("{} is not None", self.name()),
+            _ => self.expr(2),
+        }
+    }
+
+    fn expr(&mut self, depth: usize) -> String {
+        if depth == 0 || self.rng.chance(30) {
+            return self.atom();
+        }
+        let d = depth - 1;
+        match self.rng.below(16) {
+            0 | 1 => format!("({} {} {})", self.expr(d), self.rng.pick(BINOPS), self.expr(d)),
+            2 => format!("({} {} {})", self.expr(d), self.rng.pick(CMPOPS), self.expr(d)),
+            3 => format!("({} {} {})", self.expr(d), self.rng.pick(&["and", "or"]), self.expr(d)),
+            4 => format!("({}{})", self.rng.pick(&["-", "+", "~", "not "]), self.expr(d)),
+            5 => self.call(d),
+            6 => format!("({}).{}", self.atom(), self.rng.pick(ATTRS)),
+            7 => format!("{}[{}]", self.name(), self.subscript(d)),
+            8 => format!("({} if {} else {})", self.expr(d), self.expr(d), self.expr(d)),
+            9 => format!("(lambda {}: {})", self.rng.pick(&["", "v", "v, w=1", "*a"]), self.expr(d)),
+            10 => format!("[{}, {}, *{}]", self.expr(d), self.expr(d), self.name()),
+            11 => format!("{{{}: {}, **{}}}", self.expr(d), self.expr(d), self.name()),
+            12 => format!("{{{}, {}}}", self.expr(d), self.expr(d)),
+            13 => self.comprehension(d),
+            14 => format!("({} := {})", self.name(), self.expr(d)),
+            _ => format!("({}, {})", self.expr(d), self.expr(d)),
+        }
+    }
+
+    fn subscript(&mut self, depth: usize) -> String {
+        match self.rng.below(4) {
+            0 => format!("{}:{}", self.int(), self.int()),
+            1 => format!("::{}", self.rng.pick(&["-1", "2", "-2"])),
+            2 => format!("{}, {}", self.expr(depth), self.expr(depth)),
+            _ => self.expr(depth),
+        }
+    }
+
+    fn call(&mut self, depth: usize) -> String {
+        let callee = match self.rng.below(4) {
+            0 => self.rng.pick(FUNCTIONS).to_string(),
+            1 => self.rng.pick(&["len", "isinstance", "print", "sorted", "partial", "int", "str", "getattr"]).to_string(),
+            2 => format!("{}.{}", self.name(), self.rng.pick(ATTRS)),
+            _ => self.rng.pick(CLASSES).to_string(),
+        };
+        let mut args = Vec::new();
+        for _ in 0..self.rng.below(4) {
+            args.push(self.expr(depth));
+        }
+        if self.rng.chance(20) {
+            args.push(format!("key={}", self.expr(depth)));
+        }
+        if self.rng.chance(10) {
+            args.push(format!("*{}", self.name()));
+        }
+        if self.rng.chance(10) {
+            args.push(format!("**{}", self.name()));
+        }
+        format!("{}({})", callee, args.join(", "))
+    }
+
+    fn comprehension(&mut self, depth: usize) -> String {
+        let mut clauses = format!("for {} in {}", self.loop_target(), self.expr(depth));
+        if self.rng.chance(40) {
+            clauses = format!("{} if {}", clauses, self.expr(depth));
+        }
+        if self.rng.chance(25) {
+            clauses = format!("{} for {} in {}", clauses, self.rng.pick(&["k", "m"]), self.expr(depth));
+        }
+        match self.rng.below(4) {
+            0 => format!("[{} {}]", self.expr(depth), clauses),
+            1 => format!("{{{} {}}}", self.expr(depth), clauses),
+            2 => format!("{{{}: {} {}}}", self.expr(depth), self.expr(depth), clauses),
+            _ => format!("({} {})", self.expr(depth), clauses),
+        }
+    }
+
+    fn atom(&mut self) -> String {
+        match self.rng.below(9) {
+            0..=2 => self.name(),
+            3 => self.int(),
+            4 => self.string(),
+            5 => self.rng.pick(&["True", "False", "None", "...", "1.5", "-0.0", "1e308", "3j", "b'raw'"]).to_string(),
+            6 => format!("f\"{{{}}}!\"", self.name()),
+            7 => self.rng.pick(&["[]", "{}", "()", "set()"]).to_string(),
+            _ => format!("{}.{}", self.rng.pick(CLASSES), self.rng.pick(ATTRS)),
+        }
+    }
+
+    /// Expressions the constant folder can evaluate, plus some it can't
+    fn const_expr(&mut self, depth: usize) -> String {
+        if depth == 0 || self.rng.chance(50) {
+            return match self.rng.below(4) {
+                0 => self.string(),
+                1 => self.rng.pick(&["KB", "LIMIT", "Color.RED", "True", "None"]).to_string(),
+                _ => self.int(),
+            };
+        }
+        format!("({} {} {})", self.const_expr(depth - 1), self.rng.pick(BINOPS), self.const_expr(depth - 1))
+    }
+
+    fn target(&mut self) -> String {
+        match self.rng.below(5) {
+            0 => format!("{}, {}", self.name(), self.name()),
+            1 => format!("{}.{}", self.name(), self.rng.pick(ATTRS)),
+            2 => format!("{}[{}]", self.name(), self.int()),
+            3 => format!("[{}, *{}]", self.name(), self.name()),
+            _ => self.name(),
+        }
+    }
+
+    fn loop_target(&mut self) -> String {
+        match self.rng.below(3) {
+            0 => format!("{}, {}", self.name(), self.name()),
+            _ => self.name(),
+        }
+    }
+
+    fn name(&mut self) -> String {
+        self.rng.pick(NAMES).to_string()
+    }
+
+    fn int(&mut self) -> String {
+        match self.rng.below(6) {
+            0 => "0".to_string(),
+            1 => "-1".to_string(),
+            2 => "9223372036854775808".to_string(),
+            _ => self.rng.below(2048).to_string(),
+        }
+    }
+
+    fn string(&mut self) -> String {
+        self.rng.pick(&["'ok'", "\"\"", "'a' 'b'", "'\\n'", "'é'"]).to_string()
+    }
+}
+
+/// What running the checker on one input did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Ok,
+    /// The input didn't parse; a generator bug rather than a checker one
+    Invalid(String),
+    /// Panic location and message
+    Panic(String),
+    /// Still running when its budget ran out
+    Hang,
+}
+
+impl Outcome {
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Outcome::Panic(_) | Outcome::Hang)
+    }
+
+    /// What has to stay the same while minimizing
+    pub fn signature(&self) -> String {
+        match self {
+            // The location identifies the bug; messages often embed input details
+            Outcome::Panic(panic) => panic.split(": ").next().unwrap_or(panic).to_string(),
+            other => format!("{:?}", other),
+        }
+    }
+}
+
+thread_local! {
+    static FUZZING: Cell<bool> = const { Cell::new(false) };
+    static LAST_PANIC: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Record panics on fuzz workers instead of printing them; other threads
+/// keep the default hook
+fn install_panic_hook() {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let default = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !FUZZING.with(Cell::get) {
+                return default(info);
+            }
+            let location = info.location().map_or_else(|| "<unknown>".to_string(), |l| format!("{}:{}", l.file(), l.line()));
+            let message = info.payload().downcast_ref::<&str>().map(|s| s.to_string())
+                .or_else(|| info.payload().downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            LAST_PANIC.with(|last| *last.borrow_mut() = Some(format!("{}: {}", location, message)));
+        }));
+    });
+}
+
+/// Parse, check and lint `source` on a worker thread with `budget` of wall
+/// clock; past the budget the check is cancelled and counts as a hang
+pub fn run_input(source: &str, budget: Duration) -> Outcome {
+    install_panic_hook();
+    let source = source.to_string();
+    let token = CancellationToken::new();
+    let worker_token = token.clone();
+    let (sender, receiver) = mpsc::channel();
+
+    let spawned = thread::Builder::new().stack_size(64 * 1024 * 1024).spawn(move || {
+        FUZZING.with(|fuzzing| fuzzing.set(true));
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let module = parse_module(&source).map_err(|e| e.to_string())?;
+            // Every lint on, so the type-aware ones read what the check recorded
+            let lints = [BOOLEAN_POSITIONAL, ADJACENT_BOOL_PARAMS, FLOAT_EQUALITY].iter()
+                .map(|code| (code.to_string(), LintLevel::Warn))
+                .collect();
+            let options = CheckerOptions { lints, ..CheckerOptions::default() };
+            let mut checker = TypeChecker::new().with_options(options).with_cancellation(worker_token);
+            checker.check(&module);
+            checker.lint(&module);
+            Ok::<_, String>(())
+        }));
+        let outcome = match result {
+            Ok(Ok(())) => Outcome::Ok,
+            Ok(Err(error)) => Outcome::Invalid(error),
+            Err(_) => Outcome::Panic(LAST_PANIC.with(|last| last.borrow_mut().take()).unwrap_or_default()),
+        };
+        let _ = sender.send(outcome);
+    });
+    if let Err(error) = spawned {
+        return Outcome::Panic(format!("<spawn>: {}", error));
+    }
+
+    match receiver.recv_timeout(budget) {
+        Ok(outcome) => outcome,
+        Err(_) => {
+            token.cancel();
+            // Whether or not it stops now, it overran
+            let _ = receiver.recv_timeout(GRACE);
+            Outcome::Hang
+        }
+    }
+}
+
+/// A line and the indented block under it
+#[derive(Debug, Clone)]
+struct Node {
+    line: String,
+    children: Vec<Node>,
+}
+
+fn parse_tree(source: &str) -> Vec<Node> {
+    fn build(lines: &[(usize, String)], pos: &mut usize, indent: usize) -> Vec<Node> {
+        let mut nodes = Vec::new();
+        while *pos < lines.len() && lines[*pos].0 == indent {
+            let line = lines[*pos].1.clone();
+            *pos += 1;
+            let children = match lines.get(*pos) {
+                Some((next, _)) if *next > indent => build(lines, pos, *next),
+                _ => Vec::new(),
+            };
+            nodes.push(Node { line, children });
+        }
+        nodes
+    }
+
+    let lines: Vec<(usize, String)> = source.lines()
+        .filter(|line| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|line| (line.len() - line.trim_start().len(), line.trim_start().to_string()))
+        .collect();
+    let mut pos = 0;
+    let mut nodes = Vec::new();
+    // Tolerate stray dedents by restarting at each level-0 line
+    while pos < lines.len() {
+        let indent = lines[pos].0;
+        nodes.extend(build(&lines, &mut pos, indent));
+    }
+    nodes
+}
+
+fn render(nodes: &[Node]) -> String {
+    fn write(nodes: &[Node], indent: usize, out: &mut String) {
+        for node in nodes {
+            out.push_str(&"    ".repeat(indent));
+            out.push_str(&node.line);
+            out.push('\n');
+            if node.line.ends_with(':') {
+                if node.children.is_empty() {
+                    out.push_str(&"    ".repeat(indent + 1));
+                    out.push_str("pass\n");
+                } else {
+                    write(&node.children, indent + 1, out);
+                }
+            }
+        }
+    }
+    let mut out = String::new();
+    write(nodes, 0, &mut out);
+    out
+}
+
+/// Shrink a failing input while `fails` still holds: drop statements (with
+/// their blocks) in halving chunks, hoist block bodies over their headers,
+/// then recurse into the blocks that remain
+pub fn minimize(source: &str, fails: &dyn Fn(&str) -> bool) -> String {
+    let mut tree = parse_tree(source);
+    if !fails(&render(&tree)) {
+        return source.to_string();
+    }
+    loop {
+        let before = render(&tree);
+        shrink(&mut tree, &mut Vec::new(), fails);
+        if render(&tree) == before {
+            return before;
+        }
+    }
+}
+
+/// Shrink the sibling list at `path` inside `tree`
+fn shrink(tree: &mut Vec<Node>, path: &mut Vec<usize>, fails: &dyn Fn(&str) -> bool) {
+    fn siblings<'a>(tree: &'a mut Vec<Node>, path: &[usize]) -> &'a mut Vec<Node> {
+        path.iter().fold(tree, |nodes, &i| &mut nodes[i].children)
+    }
+
+    // Remove chunks, halving their size down to single statements
+    let mut chunk = siblings(tree, path).len().div_ceil(2).max(1);
+    loop {
+        let mut start = 0;
+        while start < siblings(tree, path).len() {
+            let mut candidate = tree.clone();
+            let nodes = siblings(&mut candidate, path);
+            let end = (start + chunk).min(nodes.len());
+            nodes.drain(start..end);
+            if fails(&render(&candidate)) {
+                *tree = candidate;
+            } else {
+                start += chunk;
+            }
+        }
+        if chunk == 1 {
+            break;
+        }
+        chunk = chunk.div_ceil(2);
+    }
+
+    // Replace a compound statement by its body
+    let mut i = 0;
+    while i < siblings(tree, path).len() {
+        if !siblings(tree, path)[i].children.is_empty() {
+            let mut candidate = tree.clone();
+            let nodes = siblings(&mut candidate, path);
+            let body = std::mem::take(&mut nodes[i].children);
+            nodes.splice(i..=i, body);
+            if fails(&render(&candidate)) {
+                *tree = candidate;
+                continue;
+            }
+        }
+        i += 1;
+    }
+
+    for i in 0..siblings(tree, path).len() {
+        if !siblings(tree, path)[i].children.is_empty() {
+            path.push(i);
+            shrink(tree, path, fails);
+            path.pop();
+        }
+    }
+}
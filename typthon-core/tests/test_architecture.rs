@@ -0,0 +1,58 @@
+//! Layering rules over the `fixtures/cli/layers` project
+
+use std::path::{Path, PathBuf};
+use typthon::compiler::analysis::ArchitectureRules;
+use typthon::compiler::frontend::config::{ArchitectureConfig, Config, UnknownLayerPolicy};
+use typthon::parse_module;
+
+fn project() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("typthon-core/tests/fixtures/cli/layers")
+}
+
+fn fixture_config() -> ArchitectureConfig {
+    Config::load(&project().join(".typyrc")).unwrap().architecture
+}
+
+fn violations(rules: &ArchitectureRules, file: &str) -> Vec<String> {
+    let source = std::fs::read_to_string(project().join(file)).unwrap();
+    rules.check_module(Path::new(file), &parse_module(&source).unwrap())
+        .into_iter()
+        .map(|violation| violation.message)
+        .collect()
+}
+
+#[test]
+fn test_only_disallowed_imports_are_reported() {
+    let rules = ArchitectureRules::new(&fixture_config(), project()).unwrap();
+
+    // `app.services.orders` is allowed, `app.db.models` is not
+    assert_eq!(violations(&rules, "app/ui/views.py"), vec![
+        "import of 'app.db.models' from layer 'ui' violates architecture rule ui → db",
+    ]);
+    assert!(violations(&rules, "app/services/orders.py").is_empty());
+}
+
+#[test]
+fn test_reexports_count_as_the_defining_layer() {
+    let rules = ArchitectureRules::new(&fixture_config(), project()).unwrap();
+    assert_eq!(violations(&rules, "app/ui/admin.py"), vec![
+        "import of 'app.db.models' (re-exported by 'app.services') from layer 'ui' violates architecture rule ui → db",
+    ]);
+}
+
+#[test]
+fn test_unknown_files_policy() {
+    // Without a `db` layer, `app/db` is outside every layer
+    let mut config = fixture_config();
+    config.layers.retain(|layer| layer.name != "db");
+    config.allow.remove("services");
+
+    let rules = ArchitectureRules::new(&config, project()).unwrap();
+    assert!(violations(&rules, "app/ui/views.py").is_empty());
+
+    config.unknown = UnknownLayerPolicy::Forbidden;
+    let rules = ArchitectureRules::new(&config, project()).unwrap();
+    assert_eq!(violations(&rules, "app/services/orders.py"), vec![
+        "import of 'app.db.models' from layer 'services' violates architecture rule services → unlayered",
+    ]);
+}
@@ -0,0 +1,48 @@
+//! `async def` and `await`: coroutine functions return awaitables
+
+use typthon::{parse_module, Type, TypeChecker};
+
+const FETCH: &str = "\
+async def fetch(url: str) -> int:
+    return len(url)
+";
+
+fn check(source: &str) -> (TypeChecker, Vec<String>) {
+    let mut checker = TypeChecker::new();
+    let errors = checker.check(&parse_module(source).unwrap()).into_iter().map(|e| e.message).collect();
+    (checker, errors)
+}
+
+#[test]
+fn test_await_gives_the_declared_return_type() {
+    let (checker, errors) = check(&format!(
+        "{}async def main():\n    size = await fetch('a')\n    pending = fetch('b')\n",
+        FETCH
+    ));
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(checker.local_binding("main", "size"), Some(Type::Int));
+    assert_eq!(checker.local_binding("main", "pending"), Some(Type::awaitable(Type::Int)));
+}
+
+#[test]
+fn test_awaiting_a_plain_value_is_reported() {
+    let (_, errors) = check("async def main():\n    count = 3\n    await count\n");
+
+    assert_eq!(errors, vec!["Type 'int' is not awaitable (missing __await__)".to_string()]);
+}
+
+#[test]
+fn test_coroutine_bodies_are_checked() {
+    let (_, errors) = check("\
+async def load() -> str:
+    return 1
+
+async def save(path: str) -> None:
+    await load()
+    size: int = path
+");
+
+    assert!(errors.iter().any(|e| e.starts_with("Return type mismatch")), "{:?}", errors);
+    assert!(errors.iter().any(|e| e.contains("variable 'size'")), "{:?}", errors);
+}
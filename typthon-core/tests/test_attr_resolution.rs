@@ -1,5 +1,5 @@
-use typthon::{Type, TypeContext};
-use typthon::compiler::types::ClassSchema;
+use typthon::{parse_module, Type, TypeChecker, TypeContext};
+use typthon::compiler::types::{ClassSchema, Effect, MemberKind};
 use std::sync::Arc;
 
 #[test]
@@ -240,3 +240,91 @@ fn test_no_attributes_on_primitives() {
     assert!(ctx.has_attribute(&Type::Bool, "invalid").is_none());
 }
 
+
+#[test]
+fn test_get_class_shares_schema() {
+    let ctx = Arc::new(TypeContext::new());
+    ctx.register_class(ClassSchema::new("Config".to_string()));
+
+    // Lookups hand out the same schema rather than copies
+    let first = ctx.get_class("Config").unwrap();
+    let second = ctx.get_class("Config").unwrap();
+    assert!(Arc::ptr_eq(&first, &second));
+
+    // Members added after registration are visible to attribute resolution
+    first.add_property("debug".to_string(), Type::Bool);
+    assert_eq!(ctx.has_attribute(&Type::Class("Config".to_string()), "debug"), Some(Type::Bool));
+}
+
+#[test]
+fn test_class_body_methods_are_registered_on_the_schema() {
+    let ctx = Arc::new(TypeContext::new());
+    let source = "class Base:\n    def greet(self, name: str) -> str:\n        return name\n\n\
+                  class Child(Base):\n    def shout(self, times: int) -> str:\n        return 'x'\n    \
+                  @staticmethod\n    def make(size: int) -> int:\n        return size\n\n\
+                  c = Child()\na = c.shout(2)\nb = c.greet('x')\nc.greet(1)\nc.greet()\nc.shout(times='no')\nChild.make(3)\n";
+    let mut checker = TypeChecker::with_context(ctx.clone());
+    let errors: Vec<String> = checker.check(&parse_module(source).unwrap()).into_iter().map(|e| e.message).collect();
+
+    let child = ctx.get_class("Child").unwrap();
+    // Checked methods carry their inferred effects
+    let pure = |ty: Type| Some(ty.with_effect(Effect::Pure));
+    assert_eq!(child.get_member("shout"), pure(Type::Function(vec![Type::Int], Box::new(Type::Str))));
+    assert!(!child.has_member("greet"));
+    assert_eq!(child.get_member("make"), pure(Type::Function(vec![Type::Int], Box::new(Type::Int))));
+
+    // Inherited methods resolve through the base, with the receiver bound
+    assert_eq!(checker.get_type("a"), Some(Type::Str));
+    assert_eq!(checker.get_type("b"), Some(Type::Str));
+    assert_eq!(errors, vec![
+        "Argument 0 type mismatch: expected str, got int",
+        "Function call argument count mismatch: expected 1, got 0",
+        "Keyword argument 'times' type mismatch: expected int, got str",
+    ]);
+}
+
+#[test]
+fn test_class_objects_are_distinct_from_instances() {
+    let source = "class Counter:\n    step: int = 1\n    \
+                  def __init__(self, start: int):\n        self.value = start\n    \
+                  def bump(self, by: int) -> int:\n        return by\n    \
+                  @classmethod\n    def zero(cls, label: str) -> str:\n        return label\n\n\
+                  c = Counter(0)\nstep = Counter.step\nunbound = Counter.bump\nbumped = Counter.bump(c, 2)\n\
+                  label = Counter.zero('z')\ninstance_step = c.step\nCounter.bump(2)\n";
+    let mut checker = TypeChecker::new();
+    let errors: Vec<String> = checker.check(&parse_module(source).unwrap()).into_iter().map(|e| e.message).collect();
+
+    let counter = Type::Class("Counter".to_string());
+    assert_eq!(checker.get_type("Counter"), Some(Type::ClassObject("Counter".to_string())));
+    assert_eq!(checker.get_type("c"), Some(counter.clone()));
+    assert_eq!(checker.get_type("step"), Some(Type::Int));
+    assert_eq!(checker.get_type("instance_step"), Some(Type::Int));
+    // Methods reached through the class take the instance first
+    let unbound = Type::Function(vec![counter, Type::Int], Box::new(Type::Int));
+    assert_eq!(checker.get_type("unbound"), Some(unbound.with_effect(Effect::Pure)));
+    assert_eq!(checker.get_type("bumped"), Some(Type::Int));
+    assert_eq!(checker.get_type("label"), Some(Type::Str));
+    assert_eq!(errors, vec![
+        "Function call argument count mismatch: expected 2, got 1",
+        "Argument 0 type mismatch: expected Counter, got int",
+    ]);
+}
+
+#[test]
+fn test_members_for_completion() {
+    let ctx = TypeContext::new();
+    let members = ctx.get_members(&Type::List(Box::new(Type::Str)));
+    let append = members.iter().find(|(name, _)| name == "append").map(|(_, kind)| kind.clone());
+    assert_eq!(append, Some(MemberKind::Method(Type::Function(vec![Type::Str], Box::new(Type::None)))));
+    assert!(members.windows(2).all(|pair| pair[0].0 < pair[1].0));
+
+    let source = "class Base:\n    def __init__(self) -> None:\n        self.id = 0\n\n\
+                  class User(Base):\n    kind: str = 'user'\n    def __init__(self, name: str) -> None:\n        self.name = name\n";
+    let mut checker = TypeChecker::new();
+    checker.check(&parse_module(source).unwrap());
+    let names: Vec<String> = checker.members(&Type::Class("User".to_string())).into_iter()
+        .filter(|(name, _)| !name.starts_with("__"))
+        .map(|(name, _)| name)
+        .collect();
+    assert_eq!(names, vec!["id", "kind", "name"]);
+}
@@ -0,0 +1,45 @@
+//! Augmented assignments (`x += value`) follow the binary operator rules and
+//! have to fit the variable's type
+
+use typthon::{parse_module, Type, TypeChecker};
+
+fn check(source: &str) -> (TypeChecker, Vec<String>) {
+    let mut checker = TypeChecker::new();
+    let errors = checker.check(&parse_module(source).unwrap()).into_iter().map(|e| e.message).collect();
+    (checker, errors)
+}
+
+#[test]
+fn test_augmented_assignment_results() {
+    let (checker, errors) = check(
+        "greeting = 'hello'\ngreeting += ' world'\n\
+         banner = '-'\nbanner *= 20\n\
+         halves = 9\nhalves //= 2\n\
+         total = 1.5\ntotal -= 1\n\
+         names = []\nnames += ['ann']\n",
+    );
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(checker.get_type("greeting"), Some(Type::Str));
+    assert_eq!(checker.get_type("banner"), Some(Type::Str));
+    assert_eq!(checker.get_type("halves"), Some(Type::Int));
+    assert_eq!(checker.get_type("total"), Some(Type::Float));
+    // The empty list learns its element type from what's added
+    assert_eq!(checker.get_type("names"), Some(Type::List(Box::new(Type::Str))));
+}
+
+#[test]
+fn test_augmented_assignment_errors() {
+    let (checker, errors) = check(
+        "count: int = 1\ncount += 'foo'\ncount += [1]\ncount += 0.5\n\
+         ids: list[int] = [1]\nids += ['a']\n",
+    );
+
+    assert_eq!(errors, vec![
+        "Unsupported operand types for +=: int and str",
+        "Unsupported operand types for +=: int and list[int]",
+        "Type mismatch: cannot assign float to variable 'count' of type int",
+        "Type mismatch: cannot assign list[int | str] to variable 'ids' of type list[int]",
+    ]);
+    assert_eq!(checker.get_type("count"), Some(Type::Int));
+}
@@ -0,0 +1,134 @@
+//! Matching call arguments to parameters: keywords, defaults, keyword-only
+//! parameters and typed `*args`/`**kwargs`
+
+use std::sync::Arc;
+use typthon::compiler::errors::ErrorKind;
+use typthon::compiler::types::KeywordMismatch;
+use typthon::{parse_module, BiInfer, Type, TypeChecker, TypeContext};
+
+const GREET: &str = "\
+def greet(name: str, greeting: str = 'hello', *, loud: bool = False) -> str:
+    return greeting + name
+";
+
+fn check(source: &str) -> Vec<String> {
+    let module = parse_module(source).unwrap();
+    TypeChecker::new().check(&module).into_iter().map(|e| e.message).collect()
+}
+
+#[test]
+fn test_mixed_positional_and_keyword_calls() {
+    let errors = check(&format!(
+        "{}a = greet('ann')\nb = greet('bob', greeting='hi')\nc = greet(greeting='hey', name='cy', loud=True)\nd = greet('dee', 'yo', loud=False)\n",
+        GREET
+    ));
+
+    assert!(errors.is_empty(), "{:?}", errors);
+}
+
+#[test]
+fn test_bad_keywords_are_reported() {
+    let errors = check(&format!(
+        "{}greet('ann', name='bob')\ngreet('ann', shout=True)\ngreet(greeting='hi')\ngreet('ann', loud='yes')\ngreet()\n",
+        GREET
+    ));
+
+    assert_eq!(errors, vec![
+        "greet() got multiple values for argument 'name'",
+        "greet() got an unexpected keyword argument 'shout'",
+        "greet() missing required argument 'name'",
+        "Keyword argument 'loud' type mismatch: expected bool, got str",
+        "Function call argument count mismatch: expected at least 1, got 0",
+    ]);
+}
+
+#[test]
+fn test_positional_only_and_keyword_only_parameters() {
+    let errors = check(
+        "def clamp(value: int, /, low: int, *, high: int) -> int:\n    return value\n\
+         clamp(1, 0, high=2)\nclamp(value=1, low=0, high=2)\nclamp(1, 0)\nclamp(1, 0, 2)\n",
+    );
+
+    assert_eq!(errors, vec![
+        "clamp() missing required argument 'value'",
+        "clamp() got positional-only argument 'value' passed as keyword",
+        "clamp() missing required argument 'high'",
+        "Function call argument count mismatch: expected 2, got 3",
+        "clamp() missing required argument 'high'",
+    ]);
+}
+
+#[test]
+fn test_star_args_and_kwargs_pass_through() {
+    let errors = check(
+        "def area(width: int, height: int = 1) -> int:\n    return width * height\n\
+         def forward(*args, **kwargs) -> int:\n    return area(*args, **kwargs)\n\
+         def log(message: str, **fields) -> None:\n    pass\n\
+         forward(1, 2, 3, size=4)\narea(2, **{'height': 3})\nlog('saved', user='ann', count=2)\n",
+    );
+
+    assert!(errors.is_empty(), "{:?}", errors);
+}
+
+#[test]
+fn test_bidirectional_synthesis_matches_keywords() {
+    let ctx = Arc::new(TypeContext::new());
+    let mut checker = TypeChecker::with_context(ctx.clone());
+    checker.check(&parse_module(GREET).unwrap());
+
+    let mut infer = BiInfer::new(ctx);
+    let module = parse_module("greet('ann', greeting='hi')\ngreet(greeting='hi', colour='red')\n").unwrap();
+    let rustpython_parser::ast::Mod::Module(ast) = module else { unreachable!() };
+    for stmt in &ast.body {
+        let rustpython_parser::ast::Stmt::Expr(expr) = stmt else { unreachable!() };
+        infer.synthesize(&expr.value);
+    }
+
+    let kinds: Vec<ErrorKind> = infer.into_errors().into_iter().map(|e| e.kind).collect();
+    assert_eq!(kinds, vec![
+        ErrorKind::MissingArgument { name: "name".to_string() },
+        ErrorKind::InvalidKeyword { name: "colour".to_string(), reason: KeywordMismatch::Unexpected },
+    ]);
+}
+
+#[test]
+fn test_varargs_take_any_number_of_typed_arguments() {
+    let source = "def total(*xs: int) -> int:\n    return len(xs)\n\
+                  total()\ntotal(1)\ntotal(1, 2, 3, 4, 5)\n";
+    assert!(check(source).is_empty(), "{:?}", check(source));
+
+    let errors = check(&format!("{}total(1, 'two')\n", source));
+    assert_eq!(errors, vec!["Argument 1 type mismatch: expected int, got str"]);
+}
+
+#[test]
+fn test_variadic_parameters_inside_the_body() {
+    let module = parse_module(
+        "def label(*parts: int, **fields: str) -> None:\n    p = parts\n    f = fields\n    first = parts[0]\n\
+         label(1, 2, name='x')\nlabel(name=3)\n",
+    ).unwrap();
+    let mut checker = TypeChecker::new();
+    let errors: Vec<String> = checker.check(&module).into_iter().map(|e| e.message).collect();
+
+    assert_eq!(errors, vec!["Keyword argument 'name' type mismatch: expected str, got int"]);
+    assert_eq!(checker.local_binding("label", "p"), Some(Type::VarTuple(Box::new(Type::Int))));
+    assert_eq!(checker.local_binding("label", "f"), Some(Type::Dict(Box::new(Type::Str), Box::new(Type::Str))));
+    assert_eq!(checker.local_binding("label", "first"), Some(Type::Int));
+    assert_eq!(Type::VarTuple(Box::new(Type::Int)).to_string(), "tuple[int, ...]");
+}
+
+#[test]
+fn test_constructor_calls_match_init() {
+    let errors = check(
+        "class Point:\n    def __init__(self, x: int, y: int = 0):\n        self.x = x\n\n\
+         class Point3(Point):\n    pass\n\n\
+         a = Point(1, 2)\nb = Point(x=1)\nPoint('a', 'b')\nPoint()\nPoint3(1, z=3)\n",
+    );
+
+    assert_eq!(errors, vec![
+        "Argument 0 type mismatch: expected int, got str",
+        "Argument 1 type mismatch: expected int, got str",
+        "Function call argument count mismatch: expected at least 1, got 0",
+        "Point3() got an unexpected keyword argument 'z'",
+    ]);
+}
@@ -0,0 +1,74 @@
+//! `Callable[[P1, P2], R]`, `Callable[..., R]` and bare `Callable`
+//! annotations, and the functions and lambdas passed where they're expected
+
+mod common;
+
+use common::check_with_prelude;
+
+const APPLY: &str = "\
+from typing import Callable
+
+def apply(f: Callable[[int], int], x: int) -> int:
+    return f(x)
+
+def add(a: int, b: int) -> int:
+    return a + b
+";
+
+fn check(source: &str) -> Vec<(usize, String)> {
+    let (_, errors) = check_with_prelude(APPLY, source);
+    errors.into_iter().map(|e| (e.line, e.message)).collect()
+}
+
+#[test]
+fn test_lambda_takes_the_expected_parameter_types() {
+    let errors = check("\
+apply(lambda x: x + 1, 1)
+apply(lambda x, y=0: x, 1)
+to_str: Callable[[int], str] = lambda x: str(x)
+");
+    assert!(errors.is_empty(), "{:?}", errors);
+}
+
+#[test]
+fn test_wrong_arity_is_reported() {
+    let errors = check("\
+apply(add, 1)
+apply(lambda x, y: x, 1)
+");
+    assert_eq!(errors.len(), 2, "{:?}", errors);
+    assert_eq!(errors[0].0, 1);
+    assert!(errors[0].1.ends_with("; the callable takes 2 arguments, expected 1 argument"), "{}", errors[0].1);
+    assert_eq!(errors[1].0, 2);
+    assert!(errors[1].1.contains("the callable takes 2 arguments"), "{}", errors[1].1);
+}
+
+#[test]
+fn test_parameters_are_contravariant() {
+    let errors = check("\
+def widen(x: float) -> int:
+    return 1
+def text(s: str) -> int:
+    return 1
+apply(widen, 1)
+apply(text, 1)
+");
+    let lines: Vec<usize> = errors.iter().map(|(line, _)| *line).collect();
+    assert_eq!(lines, vec![6], "{:?}", errors);
+}
+
+#[test]
+fn test_any_arity_callables() {
+    let errors = check("\
+def run(f: Callable[..., int], g: Callable) -> None:
+    f(1, 2, key=3)
+    g()
+run(add, add)
+run(lambda: 0, len)
+def shout() -> str:
+    return ''
+run(shout, shout)
+");
+    let lines: Vec<usize> = errors.iter().map(|(line, _)| *line).collect();
+    assert_eq!(lines, vec![8], "{:?}", errors);
+}
@@ -0,0 +1,89 @@
+//! Results cached between CLI runs: reuse, invalidation and `--no-cache`
+
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+/// `a` imports `b`; `c` stands alone
+fn project() -> TempDir {
+    let temp = TempDir::new().unwrap();
+    fs::write(temp.path().join("a.py"), "import b\nx: int = \"s\"\n").unwrap();
+    fs::write(temp.path().join("b.py"), "def f() -> int:\n    return 1\n").unwrap();
+    fs::write(temp.path().join("c.py"), "y: str = 1\n").unwrap();
+    temp
+}
+
+/// Run over the project with `--stats`; returns the diagnostics and the
+/// number of files parsed
+fn run(root: &Path, extra: &[&str]) -> (Value, u64) {
+    let output = Command::new(env!("CARGO_BIN_EXE_typthon"))
+        .args(["--stats", "--output", "json"])
+        .args(extra)
+        .arg(".")
+        .current_dir(root)
+        .env_remove("TYPTHON_CACHE_DIR")
+        .output()
+        .expect("failed to run typthon binary");
+    let document: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let parses = document["stats"]["phases"].as_array().unwrap().iter()
+        .find(|phase| phase["phase"] == "parse" && phase["rule"].is_null())
+        .map_or(0, |phase| phase["calls"].as_u64().unwrap());
+    (document["diagnostics"].clone(), parses)
+}
+
+#[test]
+fn test_unchanged_tree_is_not_parsed_again() {
+    let root = project();
+    let (first, parses) = run(root.path(), &[]);
+    assert_eq!(parses, 3);
+    assert!(root.path().join(".typthon_cache/VERSION").exists());
+
+    let (second, parses) = run(root.path(), &[]);
+    assert_eq!(parses, 0);
+    assert_eq!(second, first);
+}
+
+#[test]
+fn test_changed_module_and_its_importers_are_checked_again() {
+    let root = project();
+    run(root.path(), &[]);
+
+    fs::write(root.path().join("b.py"), "def f() -> str:\n    return 'one'\n").unwrap();
+    let (_, parses) = run(root.path(), &[]);
+    assert_eq!(parses, 2);
+
+    // Different options are a different configuration
+    let (_, parses) = run(root.path(), &["--disable", "TYP001"]);
+    assert_eq!(parses, 3);
+}
+
+#[test]
+fn test_no_cache_and_cache_dir() {
+    let root = project();
+    let (_, parses) = run(root.path(), &["--no-cache"]);
+    assert_eq!(parses, 3);
+    let (_, parses) = run(root.path(), &["--no-cache"]);
+    assert_eq!(parses, 3);
+    assert!(!root.path().join(".typthon_cache").exists());
+
+    let elsewhere = TempDir::new().unwrap();
+    let dir = elsewhere.path().to_str().unwrap();
+    run(root.path(), &["--cache-dir", dir]);
+    let (_, parses) = run(root.path(), &["--cache-dir", dir]);
+    assert_eq!(parses, 0);
+    assert!(!root.path().join(".typthon_cache").exists());
+}
+
+#[test]
+fn test_other_version_invalidates_the_cache() {
+    let root = project();
+    run(root.path(), &[]);
+
+    let stamp = root.path().join(".typthon_cache/VERSION");
+    fs::write(&stamp, "typthon 0.0.0").unwrap();
+    let (_, parses) = run(root.path(), &[]);
+    assert_eq!(parses, 3);
+    assert_ne!(fs::read_to_string(&stamp).unwrap(), "typthon 0.0.0");
+}
@@ -0,0 +1,272 @@
+//! Golden-output tests for the `typthon` binary
+//!
+//! Each scenario runs the real CLI inside a fixture project and compares exit
+//! code, stdout and stderr against `tests/golden/cli/<name>.txt`. Set
+//! `UPDATE_GOLDEN=1` to rewrite the goldens after an intentional change.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("typthon-core/tests/fixtures/cli")
+}
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("typthon-core/tests/golden/cli")
+}
+
+/// Replace durations like `12ms` or `1.50s` with a stable placeholder
+fn mask_durations(text: &str) -> String {
+    const UNITS: [&str; 4] = ["ns", "µs", "ms", "s"];
+
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find(|c: char| c.is_ascii_digit()) {
+        // Digits glued to an identifier (e.g. `utf8s`) aren't durations
+        let glued = rest[..start].chars().next_back().is_some_and(|c| c.is_alphanumeric() || c == '_');
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let number_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let after = &rest[number_len..];
+        let unit = UNITS.iter().find(|unit| {
+            after.starts_with(*unit)
+                && !after[unit.len()..].chars().next().is_some_and(|c| c.is_alphanumeric())
+        });
+
+        match unit {
+            Some(unit) if !glued => {
+                out.push_str("<duration>");
+                rest = &after[unit.len()..];
+            }
+            _ => {
+                out.push_str(&rest[..number_len]);
+                rest = after;
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Strip volatile content so goldens are stable across machines and runs
+fn normalize(text: &str) -> String {
+    let text = text
+        .replace(env!("CARGO_BIN_EXE_typthon"), "typthon")
+        .replace(&fixtures_dir().display().to_string(), "<fixtures>")
+        .replace('\x1b', "\\x1b");
+    mask_durations(&text)
+}
+
+/// Run the CLI in `fixtures/cli/<project>` and compare against `golden/cli/<name>.txt`
+fn golden(name: &str, project: &str, args: &[&str]) {
+    // A fresh cache, so every scenario is checked from scratch
+    let cache = tempfile::TempDir::new().unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_typthon"))
+        .args(args)
+        .current_dir(fixtures_dir().join(project))
+        .env("TYPTHON_CACHE_DIR", cache.path())
+        .env_remove("NO_COLOR")
+        .env_remove("RUST_LOG")
+        .output()
+        .expect("failed to run typthon binary");
+
+    let actual = format!(
+        "$ typthon {}\nexit: {}\n--- stdout\n{}--- stderr\n{}",
+        args.join(" "),
+        output.status.code().map_or("signal".to_string(), |c| c.to_string()),
+        normalize(&String::from_utf8_lossy(&output.stdout)),
+        normalize(&String::from_utf8_lossy(&output.stderr)),
+    );
+
+    let path = golden_dir().join(format!("{}.txt", name));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::create_dir_all(golden_dir()).unwrap();
+        fs::write(&path, &actual).unwrap();
+        return;
+    }
+
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("missing golden {}; run with UPDATE_GOLDEN=1", path.display()));
+    assert_eq!(
+        expected, actual,
+        "CLI output for '{}' changed; rerun with UPDATE_GOLDEN=1 if intended",
+        name
+    );
+}
+
+#[test]
+fn test_clean_project() {
+    golden("clean", "clean", &["--no-color", "main.py"]);
+}
+
+#[test]
+fn test_clean_project_forced_color() {
+    golden("clean_color", "clean", &["--color=always", "main.py"]);
+}
+
+#[test]
+fn test_errors() {
+    golden("errors", "errors", &["--no-color", "main.py"]);
+}
+
+#[test]
+fn test_errors_forced_color() {
+    golden("errors_color", "errors", &["--color", "always", "main.py"]);
+}
+
+#[test]
+fn test_pretty_excerpts() {
+    golden("pretty", "pretty", &["--no-color", "--output=pretty", "main.py"]);
+}
+
+#[test]
+fn test_pretty_excerpts_forced_color() {
+    golden("pretty_color", "errors", &["--color=always", "--output", "pretty", "main.py"]);
+}
+
+#[test]
+fn test_pretty_lints_with_fixes() {
+    golden("lints_pretty", "lints", &["--no-color", "--output=pretty", "main.py"]);
+}
+
+#[test]
+fn test_errors_json() {
+    golden("errors_json", "errors", &["--output-format", "json", "main.py"]);
+}
+
+#[test]
+fn test_errors_sarif() {
+    golden("errors_sarif", "errors", &["--output=sarif", "main.py"]);
+}
+
+#[test]
+fn test_lints_json() {
+    golden("lints_json", "lints", &["--output=json", "main.py"]);
+}
+
+#[test]
+fn test_disabled_codes_leave_the_output_and_the_count() {
+    golden("errors_disabled", "errors", &["--no-color", "--disable=TYP001,TYP005", "--disable", "typ012", "main.py"]);
+}
+
+#[test]
+fn test_enable_only_keeps_the_listed_codes() {
+    golden("errors_enable_only", "errors", &["--no-color", "--enable-only=TYP012", "main.py"]);
+}
+
+#[test]
+fn test_project_config() {
+    golden("project_config", "configured", &["main.py"]);
+}
+
+#[test]
+fn test_project_config_from_pyproject() {
+    golden("project_config_pyproject", "configured_pyproject", &["main.py"]);
+}
+
+#[test]
+fn test_pyproject_config() {
+    golden("pyproject", "pyproject", &["--no-color"]);
+}
+
+#[test]
+fn test_flags_override_pyproject_config() {
+    golden("pyproject_flags", "pyproject", &["--no-color", "--strict", "tests"]);
+}
+
+#[test]
+fn test_directory_discovery() {
+    golden("tree", "tree", &["--no-color", "."]);
+}
+
+#[test]
+fn test_imported_names_are_checked() {
+    golden("imports", "imports", &["--no-color", "main.py"]);
+}
+
+#[test]
+fn test_stubs_from_stubs_path_and_bundled() {
+    golden("stubs", "stubs", &["--no-color", "--stubs-path", "typings", "main.py"]);
+}
+
+#[test]
+fn test_imports_across_a_tree() {
+    golden("imports_tree", "imports", &["--no-color", "."]);
+}
+
+#[test]
+fn test_directory_include_exclude() {
+    golden("tree_filtered", "tree", &["--no-color", "--exclude", "tests", "--include=pkg/*.py", "."]);
+}
+
+#[test]
+fn test_lint_levels_from_config() {
+    golden("lints", "lints", &["--no-color", "main.py"]);
+}
+
+#[test]
+fn test_warnings_alone_pass_the_run() {
+    golden("warnings", "warnings", &["--no-color", "main.py"]);
+}
+
+#[test]
+fn test_warnings_in_yellow() {
+    golden("warnings_color", "warnings", &["--color=always", "main.py"]);
+}
+
+#[test]
+fn test_warnings_as_errors_fail_the_run() {
+    golden("warnings_as_errors", "warnings", &["--no-color", "--warnings-as-errors", "main.py"]);
+}
+
+#[test]
+fn test_strict_only_errors_fail_the_run() {
+    golden("strict", "strict", &["--no-color", "--strict", "main.py"]);
+}
+
+#[test]
+fn test_non_strict_run_of_strict_fixture_passes() {
+    golden("strict_off", "strict", &["--no-color", "main.py"]);
+}
+
+#[test]
+fn test_signature_override_from_config() {
+    golden("signatures", "signatures", &["--no-color", "main.py"]);
+}
+
+#[test]
+fn test_layer_violation_at_import() {
+    golden("layers", "layers", &["--no-color", "app/ui/views.py"]);
+}
+
+#[test]
+fn test_deps_graph() {
+    golden("deps", "layers", &["deps"]);
+}
+
+#[test]
+fn test_deps_check_rules() {
+    golden("deps_check_rules", "layers", &["deps", "--check-rules", "--no-color"]);
+}
+
+#[test]
+fn test_parse_error() {
+    golden("parse_error", "errors", &["--no-color", "broken.py"]);
+}
+
+#[test]
+fn test_unknown_option() {
+    golden("unknown_option", "clean", &["--bogus", "main.py"]);
+}
+
+#[test]
+fn test_mask_durations() {
+    assert_eq!(mask_durations("took 12ms (1.5s total)"), "took <duration> (<duration> total)");
+    assert_eq!(mask_durations("Line 3, Col 4: utf8s 10 items"), "Line 3, Col 4: utf8s 10 items");
+}
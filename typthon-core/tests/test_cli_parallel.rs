@@ -0,0 +1,74 @@
+//! `--jobs`: checking many files in parallel matches checking them in turn
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+const MODULES: usize = 50;
+
+/// A project where every third module has an error and modules import the
+/// one before them, with `mod_0` and `mod_1` importing each other
+fn generate(root: &Path) {
+    for i in 0..MODULES {
+        let import = match i {
+            0 => "import mod_1\n".to_string(),
+            _ => format!("import mod_{}\n", i - 1),
+        };
+        let body = if i % 3 == 0 {
+            format!("value_{}: int = \"not an int\"\n", i)
+        } else {
+            format!("def double_{}(x: int) -> int:\n    return x * 2\n", i)
+        };
+        fs::write(root.join(format!("mod_{}.py", i)), import + &body).unwrap();
+    }
+}
+
+fn run(root: &Path, jobs: &str) -> (Option<i32>, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_typthon"))
+        .args(["--no-color", "--no-cache", "--jobs", jobs, "."])
+        .current_dir(root)
+        .output()
+        .expect("failed to run typthon binary");
+    (output.status.code(), String::from_utf8_lossy(&output.stderr).into_owned())
+}
+
+#[test]
+fn test_parallel_matches_sequential() {
+    let temp = TempDir::new().unwrap();
+    generate(temp.path());
+
+    let (sequential_status, sequential) = run(temp.path(), "1");
+    let (parallel_status, parallel) = run(temp.path(), "4");
+
+    assert_eq!(sequential_status, Some(1));
+    assert_eq!(parallel_status, sequential_status);
+    assert_eq!(parallel, sequential);
+
+    // Every erroneous module is reported, in file order
+    let reported: Vec<&str> = parallel.lines()
+        .filter(|line| line.contains("TYP001"))
+        .map(|line| line.split(':').next().unwrap())
+        .collect();
+    let mut expected: Vec<String> = (0..MODULES).step_by(3).map(|i| format!("mod_{}.py", i)).collect();
+    expected.sort();
+    assert_eq!(reported, expected);
+}
+
+#[test]
+fn test_parallel_output_is_stable() {
+    let temp = TempDir::new().unwrap();
+    generate(temp.path());
+
+    let first = run(temp.path(), "0");
+    for _ in 0..3 {
+        assert_eq!(run(temp.path(), "0"), first);
+    }
+}
+
+#[test]
+fn test_invalid_jobs() {
+    let (status, stderr) = run(Path::new("."), "many");
+    assert_eq!(status, Some(1));
+    assert!(stderr.starts_with("Invalid value for --jobs: many"), "{}", stderr);
+}
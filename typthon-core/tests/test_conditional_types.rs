@@ -0,0 +1,89 @@
+//! `Conditional[T, U, Then, Else]`: `Then` where `T` extends `U` and `Else`
+//! elsewhere, decided as soon as both types are known
+
+use typthon::compiler::errors::codes;
+use typthon::compiler::types::TypeCondition;
+use typthon::{parse_module, Type, TypeChecker};
+
+fn check(source: &str) -> (TypeChecker, Vec<(usize, &'static str, String)>) {
+    let module = parse_module(source).unwrap();
+    let mut checker = TypeChecker::new();
+    checker.set_source(source);
+    let errors = checker.check(&module).iter()
+        .map(|e| (e.line, e.code.unwrap_or(""), e.message.clone()))
+        .collect();
+    (checker, errors)
+}
+
+#[test]
+fn test_int_extends_float_is_reduced() {
+    let (checker, errors) = check(
+        "label: Conditional[int, float, str, bytes] = 'numeric'\n\
+         raw: Conditional[str, float, str, bytes] = b'raw'\n\
+         wrong: Conditional[int, float, str, bytes] = b'raw'\n",
+    );
+
+    assert_eq!(checker.get_type("label"), Some(Type::Str));
+    assert_eq!(checker.get_type("raw"), Some(Type::Bytes));
+    assert_eq!(errors, vec![
+        (3, codes::TYPE_MISMATCH, "Type mismatch: cannot assign bytes to variable 'wrong' of type str".to_string()),
+    ]);
+}
+
+#[test]
+fn test_condition_follows_declared_bases() {
+    let (checker, errors) = check(
+        "class Animal: pass\n\
+         class Dog(Animal): pass\n\
+         class Car: pass\n\
+         pet: Conditional[Dog, Animal, int, str] = 1\n\
+         ride: Conditional[Car, Animal, int, str] = 'car'\n",
+    );
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(checker.get_type("pet"), Some(Type::Int));
+    assert_eq!(checker.get_type("ride"), Some(Type::Str));
+}
+
+#[test]
+fn test_type_var_condition_stays_symbolic() {
+    let (checker, errors) = check(
+        "from typing import Generic, TypeVar\n\
+         T = TypeVar('T')\n\
+         class Box(Generic[T]):\n\
+         \x20   def __init__(self, item: T) -> None:\n\
+         \x20       self.item = item\n\
+         \x20   def describe(self) -> Conditional[T, int, str, bytes]:\n\
+         \x20       raise NotImplementedError\n\
+         ints: Box[int] = Box(1)\n\
+         strs: Box[str] = Box('a')\n\
+         a = ints.describe()\n\
+         b = strs.describe()\n",
+    );
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(checker.get_type("a"), Some(Type::Str));
+    assert_eq!(checker.get_type("b"), Some(Type::Bytes));
+
+    let symbolic = Type::Conditional {
+        condition: Box::new(TypeCondition::Extends(Type::param("T"), Type::Int)),
+        then_type: Box::new(Type::Str),
+        else_type: Box::new(Type::Bytes),
+    };
+    assert_eq!(symbolic.reduce_conditionals(&|sub: &str, sup: &str| sub == sup), symbolic);
+    assert_eq!(symbolic.to_string(), "~T extends int ? str : bytes");
+    // Undecided, it fits only where both branches do
+    assert!(symbolic.is_subtype(&Type::union(vec![Type::Str, Type::Bytes])));
+    assert!(!symbolic.is_subtype(&Type::Str));
+}
+
+#[test]
+fn test_wrong_arity_is_reported() {
+    let (_, errors) = check("x: Conditional[int, float] = 1\n");
+
+    assert_eq!(errors, vec![(
+        1,
+        codes::INVALID_ANNOTATION,
+        "Conditional[...] takes a type, the type it must extend, and the types when it does and doesn't; got 2 arguments".to_string(),
+    )]);
+}
@@ -0,0 +1,83 @@
+//! Constant folding in annotations: Final constants, enum members and
+//! arithmetic inside Bounded(...) and Literal[...]
+
+use typthon::compiler::analysis::RefinementAnalyzer;
+use typthon::compiler::types::DependentConstraint;
+use typthon::{parse_module, Type, TypeChecker};
+
+fn check(source: &str) -> (TypeChecker, Vec<String>) {
+    let module = parse_module(source).unwrap();
+    let mut checker = TypeChecker::new();
+    let errors = checker.check(&module).iter().map(|e| e.message.clone()).collect();
+    (checker, errors)
+}
+
+fn literal(base: Type, value: &str) -> Type {
+    Type::Dependent(Box::new(base), DependentConstraint::ValueEq(value.to_string()))
+}
+
+#[test]
+fn test_bounded_folds_final_arithmetic() {
+    let (checker, errors) = check(
+        "from typing import Final\n\
+         KB: Final = 1024\n\
+         size: Bounded(0, 2 * KB) = 1500\n\
+         big: Bounded(0, 2 * KB) = 4096\n",
+    );
+
+    assert_eq!(checker.get_type("KB"), Some(Type::Int));
+    assert_eq!(checker.get_type("size"), Some(RefinementAnalyzer::bounded_int(0, 2048)));
+    assert_eq!(errors, vec![
+        "Type mismatch: cannot assign 4096 to variable 'big' of type int[(value >= 0 && value <= 2048)]".to_string(),
+    ]);
+}
+
+#[test]
+fn test_literal_of_str_constant() {
+    let (checker, errors) = check(
+        "from typing import Final, Literal\n\
+         STATUS_OK: Final[str] = 'o' + 'k'\n\
+         status: Literal[STATUS_OK, 'error'] = 'ok'\n\
+         other: Literal[STATUS_OK] = 'okay'\n",
+    );
+
+    assert_eq!(checker.get_type("status"), Some(Type::Union(vec![
+        literal(Type::Str, "'ok'"),
+        literal(Type::Str, "'error'"),
+    ])));
+    assert_eq!(errors, vec![
+        "Type mismatch: cannot assign 'okay' to variable 'other' of type Literal['ok']".to_string(),
+    ]);
+}
+
+#[test]
+fn test_enum_member_in_annotation() {
+    let (checker, errors) = check(
+        "from enum import Enum\n\
+         from typing import Literal\n\
+         class Color(Enum):\n    RED = 1\n    GREEN = 2\n\
+         def paint(c: Literal[Color.RED]) -> None:\n    pass\n\
+         favourite: Literal[Color.RED] = Color.RED\n\
+         wrong: Literal[Color.RED] = Color.GREEN\n",
+    );
+
+    assert_eq!(checker.get_type("favourite"), Some(literal(Type::Class("Color".to_string()), "Color.RED")));
+    assert_eq!(errors, vec![
+        "Type mismatch: cannot assign Color.GREEN to variable 'wrong' of type Literal[Color.RED]".to_string(),
+    ]);
+}
+
+#[test]
+fn test_non_constant_bound_is_reported() {
+    let (checker, errors) = check(
+        "limit = 10\n\
+         count: Bounded(0, limit)\n\
+         other: Bounded(0, len('abc'))\n",
+    );
+
+    assert_eq!(checker.get_type("count"), Some(Type::Int));
+    assert_eq!(errors, vec![
+        "Bounded() upper bound must be a constant expression: 'limit' is not a Final or enum constant".to_string(),
+        "Bounded() upper bound must be a constant expression: calls are not constant expressions".to_string(),
+    ]);
+}
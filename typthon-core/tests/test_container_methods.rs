@@ -0,0 +1,52 @@
+//! Methods of `list`, `dict` and `set` typed by the container's element types
+
+use typthon::compiler::analysis::TypeChecker;
+use typthon::{parse_module, Type};
+
+/// Checker that has checked `source`, with the lines of its errors
+fn check(source: &str) -> (TypeChecker, Vec<usize>) {
+    let mut checker = TypeChecker::new();
+    checker.set_source(source);
+    let lines = checker.check(&parse_module(source).unwrap()).into_iter().map(|e| e.line).collect();
+    (checker, lines)
+}
+
+#[test]
+fn test_dict_items_loop_elements() {
+    let source = "\
+d: dict[str, int] = {'a': 1}
+for pair in d.items():
+    first = pair
+for key, value in d.items():
+    k = key
+    v = value
+";
+    let (checker, lines) = check(source);
+    assert!(lines.is_empty(), "{:?}", lines);
+    assert_eq!(checker.get_type("first"), Some(Type::Tuple(vec![Type::Str, Type::Int])));
+    assert_eq!(checker.get_type("k"), Some(Type::Str));
+    assert_eq!(checker.get_type("v"), Some(Type::Int));
+}
+
+#[test]
+fn test_list_append_checks_element_type() {
+    let source = "lst: list[int] = [1]\nlst.append(2)\nlst.append('x')\nlast = lst.pop()\n";
+    let (checker, lines) = check(source);
+    assert_eq!(lines, vec![3]);
+    assert_eq!(checker.get_type("last"), Some(Type::Int));
+}
+
+#[test]
+fn test_dict_and_set_methods() {
+    let source = "\
+d: dict[str, float] = {}
+keys = d.keys()
+found = d.get('a')
+s: set[str] = set()
+s.add(1)
+";
+    let (checker, lines) = check(source);
+    assert_eq!(lines, vec![5]);
+    assert_eq!(checker.get_type("keys"), Some(Type::List(Box::new(Type::Str))));
+    assert_eq!(checker.get_type("found"), Some(Type::Union(vec![Type::Float, Type::None])));
+}
@@ -0,0 +1,61 @@
+//! `with` statements: the context manager protocol and the `as` target
+
+use typthon::{parse_module, Type, TypeChecker};
+
+const RESOURCE: &str = "\
+class Resource:
+    def __enter__(self) -> str:
+        return 'handle'
+
+    def __exit__(self, *exc) -> None:
+        pass
+
+class Session:
+    async def __aenter__(self):
+        return self
+
+    async def __aexit__(self, *exc):
+        return None
+";
+
+fn check(source: &str) -> (TypeChecker, Vec<String>) {
+    let mut checker = TypeChecker::new();
+    let errors = checker.check(&parse_module(source).unwrap()).into_iter().map(|e| e.message).collect();
+    (checker, errors)
+}
+
+#[test]
+fn test_as_target_takes_the_enter_type() {
+    let (checker, errors) = check(&format!(
+        "{}with Resource() as handle, Resource():\n    shout = handle.upper()\n",
+        RESOURCE
+    ));
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(checker.get_type("handle"), Some(Type::Str));
+    assert_eq!(checker.get_type("shout"), Some(Type::Str));
+}
+
+#[test]
+fn test_values_without_the_protocol_are_reported() {
+    let (_, errors) = check(&format!(
+        "{}class Half:\n    def __enter__(self) -> int:\n        return 1\n\n\
+         with 5 as n:\n    pass\nwith Half():\n    pass\n\
+         async with Session() as s:\n    pass\nasync with Resource():\n    pass\n",
+        RESOURCE
+    ));
+
+    assert_eq!(errors, vec![
+        "Type 'int' does not support the context manager protocol (missing __enter__, __exit__)",
+        "Type 'Half' does not support the context manager protocol (missing __exit__)",
+        "Type 'Resource' does not support the asynchronous context manager protocol (missing __aenter__, __aexit__)",
+    ]);
+}
+
+#[test]
+fn test_targets_of_unknown_managers_are_any() {
+    let (checker, errors) = check("def load(path: str) -> None:\n    with open(path) as fh:\n        data = fh.read()\n\nwith open('log') as log:\n    pass\n");
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(checker.get_type("log"), Some(Type::Any));
+}
@@ -0,0 +1,85 @@
+//! `@dataclass` classes: the synthesized `__init__`, field attributes and
+//! frozen fields
+
+mod common;
+
+use typthon::compiler::analysis::TypeChecker;
+use typthon::Type;
+use common::check_with_prelude;
+
+const POINT: &str = "\
+from dataclasses import dataclass, field
+
+@dataclass
+class Point:
+    x: int
+    y: int = 0
+    tags: list[str] = field(default_factory=list)
+";
+
+fn check(source: &str) -> (TypeChecker, Vec<(usize, String)>) {
+    let (checker, errors) = check_with_prelude(POINT, source);
+    (checker, errors.into_iter().map(|e| (e.line, e.message)).collect())
+}
+
+#[test]
+fn test_init_takes_the_fields() {
+    let (checker, errors) = check("\
+p = Point(1, 2)
+q = Point(1, tags=['a'])
+x = p.x
+tags = q.tags
+");
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(checker.get_type("p"), Some(Type::Class("Point".to_string())));
+    assert_eq!(checker.get_type("x"), Some(Type::Int));
+    assert_eq!(checker.get_type("tags"), Some(Type::List(Box::new(Type::Str))));
+}
+
+#[test]
+fn test_missing_and_mistyped_arguments() {
+    let (_, errors) = check("\
+Point()
+Point('a')
+Point(1, 2, [], 3)
+Point(1, z=2)
+");
+    assert_eq!(errors, vec![
+        (1, "Function call argument count mismatch: expected at least 1, got 0".to_string()),
+        (2, "Argument 0 type mismatch: expected int, got str".to_string()),
+        (3, "Function call argument count mismatch: expected at most 3, got 4".to_string()),
+        (4, "Point() got an unexpected keyword argument 'z'".to_string()),
+    ]);
+}
+
+#[test]
+fn test_fields_are_inherited_and_init_false_is_skipped() {
+    let (_, errors) = check("\
+@dataclass
+class Point3(Point):
+    z: int = 0
+    label: str = field(init=False, default='')
+Point3(1, 2, [], 3)
+Point3(1, 2, [], 3, 'a')
+");
+    let lines: Vec<usize> = errors.iter().map(|(line, _)| *line).collect();
+    assert_eq!(lines, vec![6], "{:?}", errors);
+}
+
+#[test]
+fn test_frozen_fields_cannot_be_assigned() {
+    let (_, errors) = check("\
+@dataclass(frozen=True)
+class Frozen:
+    a: int
+f = Frozen(1)
+f.a = 2
+f.a += 1
+p = Point(1)
+p.x = 2
+");
+    assert_eq!(errors, vec![
+        (5, "Cannot assign to field 'a' of frozen dataclass 'Frozen'".to_string()),
+        (6, "Cannot assign to field 'a' of frozen dataclass 'Frozen'".to_string()),
+    ]);
+}
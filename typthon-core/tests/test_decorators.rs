@@ -0,0 +1,101 @@
+//! Decorated functions: properties read as values of their getter's return
+//! type, static and class methods bind no instance, and decorators that
+//! return the function they wrap keep its signature
+
+use typthon::compiler::analysis::checker::TypeError;
+use typthon::compiler::analysis::{CheckerOptions, TypeChecker};
+use typthon::compiler::errors::codes;
+use typthon::parse_module;
+
+const ACCOUNT: &str = "\
+import functools
+
+class Account:
+    def __init__(self, balance: int) -> None:
+        self._balance = balance
+
+    @property
+    def balance(self) -> int:
+        return self._balance
+
+    @balance.setter
+    def balance(self, value: int) -> None:
+        self._balance = value
+
+    @functools.cached_property
+    def owner(self) -> str:
+        return \"me\"
+
+    @staticmethod
+    def fee(amount: int, rate: int) -> int:
+        return amount * rate
+
+account = Account(10)
+";
+
+fn check_with(options: CheckerOptions, source: &str) -> Vec<TypeError> {
+    let mut checker = TypeChecker::new_with_options(options);
+    checker.set_source(source);
+    checker.check(&parse_module(source).unwrap())
+}
+
+fn check(source: &str) -> Vec<TypeError> {
+    check_with(CheckerOptions::default(), source)
+}
+
+#[test]
+fn test_property_reads_as_its_value() {
+    let source = format!("{}total: int = account.balance + 1\nname: str = account.owner\n", ACCOUNT);
+    let errors = check(&source);
+    assert!(errors.is_empty(), "{:?}", errors);
+}
+
+#[test]
+fn test_calling_a_property_value_is_an_error() {
+    let source = format!("{}account.balance()\n", ACCOUNT);
+    let errors = check(&source);
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(errors[0].code, Some(codes::NOT_CALLABLE));
+    assert!(errors[0].message.contains("int"), "{}", errors[0].message);
+}
+
+#[test]
+fn test_static_method_called_on_the_class() {
+    let ok = format!("{}charge: int = Account.fee(100, 2)\n", ACCOUNT);
+    assert!(check(&ok).is_empty(), "{:?}", check(&ok));
+
+    let wrong = format!("{}Account.fee(\"100\", 2)\n", ACCOUNT);
+    let errors = check(&wrong);
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(errors[0].code, Some(codes::ARG_TYPE));
+}
+
+#[test]
+fn test_unknown_decorators_keep_the_signature_unless_erased() {
+    let source = "\
+import functools
+
+def traced(f):
+    return f
+
+@traced
+def double(x: int) -> int:
+    return x * 2
+
+@functools.lru_cache(maxsize=None)
+def square(x: int) -> int:
+    return x * x
+
+double(\"a\")
+square(\"a\")
+";
+    let lines = |errors: Vec<TypeError>| errors.iter().map(|e| e.line).collect::<Vec<_>>();
+    assert_eq!(lines(check(source)), vec![14, 15]);
+
+    // Strict mode also wants annotations on `traced`; only argument errors matter here
+    let erasing = CheckerOptions { strict: true, erase_unknown_decorators: true, ..CheckerOptions::default() };
+    let errors: Vec<TypeError> = check_with(erasing, source).into_iter()
+        .filter(|e| e.code == Some(codes::ARG_TYPE))
+        .collect();
+    assert_eq!(lines(errors), vec![15]);
+}
@@ -0,0 +1,46 @@
+//! `check_source_detailed` and `check_file_detailed`: diagnostics with their
+//! full extent, code, severity and suggestions, as the Python bindings see them
+
+use typthon::compiler::errors::codes;
+use typthon::typthon::{check_file_detailed, check_source_detailed, Diagnostic};
+
+const SOURCE: &str = "\
+def greet(name: str) -> str:
+    return name
+
+count: int = \"three\"
+greet(\"x\").uper()
+";
+
+#[test]
+fn test_fields_of_a_known_error() {
+    let diagnostics = check_source_detailed(SOURCE, None).unwrap();
+    let mismatch = diagnostics.iter().find(|d| d.line == 4).expect("no error on line 4");
+    assert_eq!(mismatch.code.as_deref(), Some(codes::TYPE_MISMATCH));
+    assert_eq!(mismatch.severity, "error");
+    assert_eq!((mismatch.col, mismatch.end_line, mismatch.end_col), (13, 4, 20), "{:?}", mismatch);
+    assert!(mismatch.message.contains("str"), "{}", mismatch.message);
+}
+
+#[test]
+fn test_suggestions_are_carried() {
+    let diagnostics = check_source_detailed(SOURCE, Some("example.py")).unwrap();
+    let misspelled: &Diagnostic = diagnostics.iter().find(|d| d.line == 5).expect("no error on line 5");
+    assert_eq!(misspelled.code.as_deref(), Some(codes::INVALID_ATTRIBUTE));
+    assert_eq!((misspelled.col, misspelled.end_line, misspelled.end_col), (0, 5, 15), "{:?}", misspelled);
+    assert_eq!(misspelled.suggestions, vec!["upper".to_string()]);
+}
+
+#[test]
+fn test_file_matches_source() {
+    let path = std::env::temp_dir().join(format!("typthon_detailed_{}.py", std::process::id()));
+    std::fs::write(&path, SOURCE).unwrap();
+    let from_file = check_file_detailed(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(from_file.unwrap(), check_source_detailed(SOURCE, Some(&path.display().to_string())).unwrap());
+}
+
+#[test]
+fn test_syntax_error_is_an_error() {
+    assert!(check_source_detailed("def broken(:\n", None).is_err());
+}
@@ -0,0 +1,57 @@
+//! Docstrings captured during checking: bindings, class members and the
+//! notes attached to bad calls
+
+use std::sync::Arc;
+use typthon::compiler::ast::Docstring;
+use typthon::{parse_module, TypeChecker, TypeContext};
+
+const SOURCE: &str = r#""""Geometry helpers."""
+
+def area(width: int, height: int) -> int:
+    """Area of a rectangle.
+
+    Both sides must be non-negative.
+    """
+    return width * height
+
+class Shape:
+    r'Base of all shapes, e.g. \d-gons.'
+
+    def scale(self, factor: float) -> float:
+        "Grow the shape " "by `factor`."
+        return factor
+
+def undocumented(x: int) -> int:
+    return x
+"#;
+
+#[test]
+fn test_docstrings_reach_bindings_and_class_members() {
+    let ctx = Arc::new(TypeContext::new());
+    let mut checker = TypeChecker::with_context(ctx.clone());
+    assert!(checker.check(&parse_module(SOURCE).unwrap()).is_empty());
+
+    assert_eq!(checker.module_docstring().map(|doc| doc.summary.as_str()), Some("Geometry helpers."));
+    assert_eq!(checker.get_docstring("area"), Docstring::new("Area of a rectangle.\n\nBoth sides must be non-negative."));
+    assert_eq!(checker.get_docstring("undocumented"), None);
+
+    let shape = ctx.get_class("Shape").unwrap();
+    assert_eq!(shape.doc.as_ref().unwrap().summary, "Base of all shapes, e.g. \\d-gons.");
+    assert_eq!(shape.member_doc("scale").unwrap().text, "Grow the shape by `factor`.");
+}
+
+#[test]
+fn test_bad_call_notes_documented_contract() {
+    let source = format!("{}\narea(1)\narea('wide', 2)\nshape = Shape()\nshape.scale('big')\nundocumented('x')\n", SOURCE);
+    let errors: Vec<String> = TypeChecker::new().check(&parse_module(&source).unwrap())
+        .into_iter()
+        .map(|error| error.message)
+        .collect();
+
+    assert_eq!(errors, vec![
+        "Function call argument count mismatch: expected 2, got 1; note: area(): Area of a rectangle.",
+        "Argument 0 type mismatch: expected int, got str; note: area(): Area of a rectangle.",
+        "Argument 0 type mismatch: expected float, got str; note: Shape.scale(): Grow the shape by `factor`.",
+        "Argument 0 type mismatch: expected int, got str",
+    ]);
+}
@@ -0,0 +1,122 @@
+//! Declared effects: `@effects(...)` and `@pure` bound what a function's
+//! body may do, and `EffectType[...]` what a callback passed in may do
+
+use typthon::compiler::analysis::checker::TypeError;
+use typthon::compiler::analysis::TypeChecker;
+use typthon::compiler::errors::codes;
+use typthon::parse_module;
+
+fn check(source: &str) -> Vec<TypeError> {
+    let mut checker = TypeChecker::new();
+    checker.set_source(source);
+    checker.check(&parse_module(source).unwrap())
+}
+
+#[test]
+fn test_printing_in_a_pure_function() {
+    let source = "\
+@effects(\"pure\")
+def greet(name: str) -> str:
+    message = \"hello \" + name
+    print(message)
+    return message
+";
+    let errors = check(source);
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(errors[0].code, Some(codes::EFFECT_VIOLATION));
+    assert_eq!(errors[0].line, 4);
+    assert!(errors[0].message.contains("IO"), "{}", errors[0].message);
+}
+
+#[test]
+fn test_calling_an_impure_function_is_pinpointed() {
+    let source = "\
+def log(text: str) -> None:
+    print(text)
+
+@pure
+def double(x: int) -> int:
+    log(str(x))
+    return x * 2
+";
+    let errors = check(source);
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(errors[0].code, Some(codes::EFFECT_VIOLATION));
+    assert_eq!((errors[0].line, errors[0].col), (6, 4));
+    assert!(errors[0].message.starts_with("'double'"), "{}", errors[0].message);
+}
+
+#[test]
+fn test_declared_effects_allow_what_they_name() {
+    let source = "\
+from random import random
+
+@effects(\"io\", \"network\")
+def report(value: int) -> None:
+    print(value)
+
+@effects(\"io\")
+def roll() -> int:
+    print(\"rolling\")
+    return random()
+";
+    let errors = check(source);
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(errors[0].line, 10);
+    assert!(errors[0].message.contains("Random"), "{}", errors[0].message);
+}
+
+#[test]
+fn test_raise_is_reported_at_the_definition() {
+    let source = "\
+@pure
+def check(x: int) -> int:
+    if x < 0:
+        raise ValueError(\"negative\")
+    return x
+";
+    let errors = check(source);
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(errors[0].line, 2);
+    assert!(errors[0].message.contains("Exception"), "{}", errors[0].message);
+}
+
+const CALLBACKS: &str = "\
+from typing import Callable
+
+def shout(x: int) -> int:
+    print(x)
+    return x
+
+def double(x: int) -> int:
+    return x * 2
+
+def apply_pure(f: EffectType[Callable[[int], int], \"pure\"], x: int) -> int:
+    return f(x)
+
+def apply_io(f: EffectType[Callable[[int], int], \"io\"], x: int) -> int:
+    return f(x)
+
+def apply(f: Callable[[int], int], x: int) -> int:
+    return f(x)
+";
+
+#[test]
+fn test_impure_callback_where_a_pure_one_is_expected() {
+    let source = format!("{}apply_pure(shout, 1)\napply_pure(f=shout, x=1)\n", CALLBACKS);
+    let errors = check(&source);
+    assert_eq!(errors.len(), 2, "{:?}", errors);
+    assert_eq!(errors[0].code, Some(codes::ARG_TYPE));
+    assert_eq!(errors[0].line, 18);
+    // Function types read as written; only effects beyond pure are shown
+    assert_eq!(errors[0].message, "Argument 0 type mismatch: expected (int) -> int, got (int) -> int ! {IO}");
+    assert_eq!(errors[1].message, "Keyword argument 'f' type mismatch: expected (int) -> int, got (int) -> int ! {IO}");
+}
+
+#[test]
+fn test_callbacks_within_the_allowed_effects() {
+    // Pure callbacks fit anywhere, and a parameter without effects accepts any
+    let source = format!("{}apply_io(shout, 1)\napply_io(double, 1)\napply_pure(double, 1)\napply(shout, 1)\n", CALLBACKS);
+    let errors = check(&source);
+    assert!(errors.is_empty(), "{:?}", errors);
+}
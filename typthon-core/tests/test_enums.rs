@@ -0,0 +1,98 @@
+//! `enum.Enum` subclasses: member types, `name` / `value`, iteration and
+//! narrowing against members
+
+mod common;
+
+use typthon::compiler::analysis::TypeChecker;
+use typthon::compiler::types::DependentConstraint;
+use typthon::Type;
+use common::check_with_prelude;
+
+const COLOR: &str = "\
+from enum import Enum
+
+class Color(Enum):
+    RED = 1
+    GREEN = 'g'
+";
+
+fn check(source: &str) -> (TypeChecker, Vec<(usize, String)>) {
+    let (checker, errors) = check_with_prelude(COLOR, source);
+    (checker, errors.into_iter().map(|e| (e.line, e.message)).collect())
+}
+
+fn color() -> Type {
+    Type::Class("Color".to_string())
+}
+
+fn member(name: &str) -> Type {
+    Type::Dependent(Box::new(color()), DependentConstraint::ValueEq(format!("Color.{}", name)))
+}
+
+#[test]
+fn test_members_name_and_value() {
+    let (checker, errors) = check("\
+red = Color.RED
+red_value = Color.RED.value
+green_value = Color.GREEN.value
+name = Color.RED.name
+def value_of(c: Color) -> int:
+    return c.value
+");
+    assert_eq!(errors, vec![
+        (6, "Return type mismatch: expected Int, got Union([Int, Str])".to_string()),
+    ]);
+    assert_eq!(checker.get_type("red"), Some(color()));
+    assert_eq!(checker.get_type("red_value"), Some(Type::Int));
+    assert_eq!(checker.get_type("green_value"), Some(Type::Str));
+    assert_eq!(checker.get_type("name"), Some(Type::Str));
+}
+
+#[test]
+fn test_unknown_member_suggests_the_closest() {
+    let (_, errors) = check("x = Color.REDD\n");
+    assert_eq!(errors, vec![
+        (1, "Type 'type[Color]' has no attribute 'REDD'. Did you mean: RED?".to_string()),
+    ]);
+}
+
+#[test]
+fn test_parameters_reject_plain_values() {
+    let (_, errors) = check("\
+def paint(c: Color) -> None:
+    pass
+paint(Color.GREEN)
+paint(1)
+");
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(errors[0].0, 4);
+    assert!(errors[0].1.starts_with("Argument 0 type mismatch"), "{:?}", errors);
+}
+
+#[test]
+fn test_iterating_the_class_gives_members() {
+    let (checker, errors) = check("\
+for c in Color:
+    last = c
+");
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(checker.get_type("last"), Some(color()));
+}
+
+#[test]
+fn test_comparison_narrows_to_members() {
+    let (_, errors) = check("\
+from typing import Literal
+def f(c: Color) -> None:
+    if c is Color.RED:
+        red: Literal[Color.RED] = c
+        number: int = c.value
+    else:
+        green: Literal[Color.GREEN] = c
+    if c != Color.GREEN:
+        wrong: Literal[Color.GREEN] = c
+");
+    let lines: Vec<usize> = errors.iter().map(|(line, _)| *line).collect();
+    assert_eq!(lines, vec![9], "{:?}", errors);
+    assert!(errors[0].1.contains(&member("RED").to_string()), "{:?}", errors);
+}
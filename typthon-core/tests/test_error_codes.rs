@@ -0,0 +1,105 @@
+//! Stable error codes and filtering diagnostics by code
+
+use std::collections::HashSet;
+use typthon::compiler::analysis::CheckerOptions;
+use typthon::compiler::errors::{codes, ErrorCollector, ErrorKind, Severity, SourceLocation, TypeError};
+use typthon::{parse_module, TypeChecker};
+
+const SOURCE: &str = "\
+def greet(name: str) -> str:
+    return name
+count: int = 'three'
+greet(3)
+greet(name='a', loud=True)
+";
+
+fn check(options: CheckerOptions) -> Vec<(usize, Option<&'static str>)> {
+    let mut checker = TypeChecker::new_with_options(options);
+    checker.set_source(SOURCE);
+    checker.check(&parse_module(SOURCE).unwrap()).into_iter()
+        .map(|e| (e.line, e.code))
+        .collect()
+}
+
+#[test]
+fn test_error_kinds_have_stable_codes() {
+    let mismatch = ErrorKind::TypeMismatch { expected: "int".to_string(), found: "str".to_string() };
+    assert_eq!(mismatch.code(), "TYP001");
+    assert_eq!(ErrorKind::UndefinedVariable { name: "x".to_string() }.code(), "TYP002");
+    assert_eq!(ErrorKind::InfiniteType { var: "T".to_string(), ty: "list[T]".to_string() }.code(), "TYP016");
+    assert_eq!(codes::name("TYP001"), Some("type-mismatch"));
+    assert_eq!(codes::name("S001"), None);
+    assert!(codes::ALL.windows(2).all(|pair| pair[0].0 < pair[1].0));
+
+    let error = TypeError::new(mismatch, SourceLocation::new(3, 4, 3, 9));
+    assert_eq!(error.to_string(), "3:4: error[TYP001]: Type mismatch: expected int, found str");
+}
+
+#[test]
+fn test_checker_errors_carry_codes() {
+    assert_eq!(check(CheckerOptions::default()), vec![
+        (3, Some(codes::TYPE_MISMATCH)),
+        (4, Some(codes::ARG_TYPE)),
+        (5, Some(codes::INVALID_KEYWORD)),
+    ]);
+
+    let mut checker = TypeChecker::new();
+    checker.set_source(SOURCE);
+    let first = checker.check(&parse_module(SOURCE).unwrap()).remove(0);
+    assert_eq!(first.to_string(), "Line 3, Col 13: error[TYP001]: Type mismatch: cannot assign str to variable 'count' of type int");
+}
+
+#[test]
+fn test_disabled_and_enabled_codes() {
+    let disabled = CheckerOptions {
+        disabled_codes: [codes::ARG_TYPE.to_string()].into_iter().collect(),
+        ..CheckerOptions::default()
+    };
+    assert_eq!(check(disabled).iter().map(|(line, _)| *line).collect::<Vec<_>>(), vec![3, 5]);
+
+    let only = CheckerOptions {
+        enabled_codes: Some([codes::INVALID_KEYWORD.to_string()].into_iter().collect()),
+        ..CheckerOptions::default()
+    };
+    assert_eq!(check(only), vec![(5, Some(codes::INVALID_KEYWORD))]);
+}
+
+#[test]
+fn test_collector_drops_disabled_codes() {
+    let disabled: HashSet<String> = [codes::UNDEFINED_VARIABLE.to_string()].into_iter().collect();
+    let mut collector = ErrorCollector::new().with_disabled_codes(disabled);
+    collector.add(TypeError::undefined_variable("x".to_string(), SourceLocation::default(), Vec::new()));
+    collector.add(TypeError::invalid_arg_count(1, 2, SourceLocation::default()));
+
+    assert_eq!(collector.error_count(), 1);
+    assert_eq!(collector.errors()[0].kind.code(), codes::ARG_COUNT);
+}
+
+#[test]
+fn test_collector_counts_each_severity() {
+    let mut collector = ErrorCollector::new();
+    collector.add(TypeError::invalid_arg_count(1, 2, SourceLocation::default()));
+    collector.add(TypeError::new(ErrorKind::UnreachableCode { after: "'return'".to_string() }, SourceLocation::default()));
+    collector.add(TypeError::invalid_arg_count(1, 3, SourceLocation::default()).with_severity(Severity::Note));
+
+    let counts = collector.counts();
+    assert_eq!((counts.errors, counts.warnings, counts.notes), (1, 1, 1));
+    assert_eq!(collector.error_count(), 1);
+    assert_eq!(collector.warning_count(), 1);
+    assert!(collector.errors()[1].to_string().contains("warning[TYP024]"));
+
+    let mut warnings_only = ErrorCollector::new();
+    warnings_only.add(TypeError::new(ErrorKind::UnreachableCode { after: "'raise'".to_string() }, SourceLocation::default()));
+    assert!(!warnings_only.has_errors());
+}
+
+#[test]
+fn test_coded_ignore_matches_error_codes() {
+    let source = "a: int = 'x'  # type: ignore[TYP005]\nb: int = 'y'  # type: ignore[TYP001]\n";
+    let mut checker = TypeChecker::new();
+    checker.set_source(source);
+    let lines: Vec<usize> = checker.check(&parse_module(source).unwrap()).iter()
+        .map(|e| e.line)
+        .collect();
+    assert_eq!(lines, vec![1]);
+}
@@ -0,0 +1,51 @@
+//! Source locations on checker errors
+//!
+//! Lines are 1-based and columns 0-based, matching lint locations; `range`
+//! covers the offending node so editors can underline it.
+
+use typthon::{parse_module, TypeChecker};
+
+/// `(line, col, text under range, message)` for each error in `source`
+fn locate(source: &str) -> Vec<(usize, usize, String, String)> {
+    let mut checker = TypeChecker::new();
+    checker.set_source(source);
+    checker.check(&parse_module(source).unwrap()).into_iter()
+        .map(|e| {
+            let range = e.range.unwrap_or_else(|| panic!("no range: {}", e.message));
+            (e.line, e.col, source[range].to_string(), e.message)
+        })
+        .collect()
+}
+
+#[test]
+fn test_assignment_and_call_errors_point_at_the_value() {
+    let errors = locate(
+        "def greet(name: str) -> str:\n    return name\n\n\
+         count: int = \"three\"\n\
+         greet(1)\n\
+         greet()\n",
+    );
+
+    let spans: Vec<_> = errors.iter().map(|(line, col, text, _)| (*line, *col, text.as_str())).collect();
+    assert_eq!(spans, [(4, 13, "\"three\""), (5, 6, "1"), (6, 0, "greet()")], "{:?}", errors);
+}
+
+#[test]
+fn test_return_and_attribute_errors_inside_functions() {
+    let errors = locate(
+        "def size(text: str) -> int:\n    return text\n\n\
+         def shout(text: str) -> str:\n    return text.uper()\n",
+    );
+
+    assert_eq!((errors[0].0, errors[0].1, errors[0].2.as_str()), (2, 11, "text"), "{:?}", errors);
+    assert_eq!((errors[1].0, errors[1].1, errors[1].2.as_str()), (5, 11, "text.uper"), "{:?}", errors);
+}
+
+#[test]
+fn test_without_source_errors_keep_ranges_but_no_position() {
+    let source = "count: int = 'three'\n";
+    let errors = TypeChecker::new().check(&parse_module(source).unwrap());
+
+    assert_eq!((errors[0].line, errors[0].col), (0, 0));
+    assert_eq!(errors[0].range.clone().map(|range| &source[range]), Some("'three'"));
+}
@@ -0,0 +1,60 @@
+//! `try` statements: every block is checked and handlers bind what they catch
+
+use typthon::{parse_module, Type, TypeChecker};
+
+fn check(source: &str) -> (TypeChecker, Vec<String>) {
+    let mut checker = TypeChecker::new();
+    let errors = checker.check(&parse_module(source).unwrap()).into_iter().map(|e| e.message).collect();
+    (checker, errors)
+}
+
+#[test]
+fn test_handler_binds_the_caught_exception() {
+    let (checker, errors) = check("\
+try:
+    value = int('3')
+except ValueError as e:
+    details = e.args
+    count: int = 'none'
+");
+
+    assert!(errors.iter().any(|e| e.contains("cannot assign str to variable 'count' of type int")), "{:?}", errors);
+    assert_eq!(checker.get_type("e"), Some(Type::Class("ValueError".to_string())));
+    assert_eq!(checker.get_type("details"), Some(Type::VarTuple(Box::new(Type::Any))));
+}
+
+#[test]
+fn test_tuples_and_user_exceptions() {
+    let (checker, errors) = check("\
+class ParseError(ValueError):
+    pass
+
+try:
+    pass
+except (ParseError, KeyError) as e:
+    details = e.args
+");
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(checker.get_type("e"), Some(Type::union(vec![
+        Type::Class("ParseError".to_string()),
+        Type::Class("KeyError".to_string()),
+    ])));
+}
+
+#[test]
+fn test_else_and_finally_are_checked() {
+    let (_, errors) = check("\
+try:
+    pass
+except Exception:
+    pass
+else:
+    first: int = 'a'
+finally:
+    second: str = 1
+");
+
+    assert!(errors.iter().any(|e| e.contains("variable 'first'")), "{:?}", errors);
+    assert!(errors.iter().any(|e| e.contains("variable 'second'")), "{:?}", errors);
+}
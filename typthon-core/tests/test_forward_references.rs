@@ -0,0 +1,38 @@
+//! Functions and classes can be used before the module defines them, as a
+//! `main()` at the top of a module does
+
+use typthon::compiler::errors::codes;
+use typthon::{parse_module, Type, TypeChecker};
+
+const FORWARD_CALLS: &str = include_str!("fixtures/ordering/forward_calls.py");
+
+fn check(source: &str) -> Vec<(usize, Option<&'static str>, String)> {
+    let mut checker = TypeChecker::new();
+    checker.set_source(source);
+    checker.check(&parse_module(source).unwrap()).into_iter().map(|e| (e.line, e.code, e.message)).collect()
+}
+
+#[test]
+fn test_calls_before_definitions_are_checked() {
+    assert_eq!(check(FORWARD_CALLS), vec![
+        (2, Some(codes::ARG_TYPE), "Argument 1 type mismatch: expected int, got str".to_string()),
+    ]);
+}
+
+#[test]
+fn test_unannotated_returns_are_solved_for_earlier_calls() {
+    let source = "\
+def main() -> None:
+    size = count()
+    print(size)
+
+def count():
+    return 1
+";
+    let mut checker = TypeChecker::new();
+    let errors = checker.check(&parse_module(source).unwrap());
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    let read = source.find("print(size)").unwrap() + "print(".len();
+    assert_eq!(checker.type_at(read).map(|(_, ty)| ty), Some(Type::Int));
+}
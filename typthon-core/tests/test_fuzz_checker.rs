@@ -0,0 +1,113 @@
+//! Checker resilience: generated programs and the crash corpus must check
+//! without panicking or hanging
+//!
+//! `fuzz_checker_long` is the fuzzer proper. It's ignored by default; run it
+//! with `cargo test --test test_fuzz_checker -- --ignored --nocapture`,
+//! optionally setting `TYPTHON_FUZZ_SECONDS` (default 600) and
+//! `TYPTHON_FUZZ_SEED`. Minimized failures land in the corpus directory and
+//! are replayed by `test_crash_corpus_replays` from then on.
+
+mod fuzz;
+
+use fuzz::{minimize, run_input, Generator, Outcome};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use typthon::infrastructure::CancellationToken;
+use typthon::{parse_module, TypeChecker};
+
+const CORPUS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/typthon-core/tests/fixtures/fuzz/corpus");
+
+/// Per-input wall clock; generated inputs are tiny, so this is generous
+const BUDGET: Duration = Duration::from_secs(5);
+
+fn corpus_files() -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(CORPUS).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "py"))
+        .collect();
+    files.sort();
+    files
+}
+
+fn record_crash(source: &str, outcome: &Outcome) -> PathBuf {
+    let minimized = minimize(source, &|candidate| run_input(candidate, BUDGET).signature() == outcome.signature());
+    let hash = blake3::hash(minimized.as_bytes()).to_hex();
+    let path = Path::new(CORPUS).join(format!("crash_{}.py", &hash[..12]));
+    fs::write(&path, format!("# {}\n{}", outcome.signature(), minimized)).unwrap();
+    path
+}
+
+#[test]
+fn test_crash_corpus_replays() {
+    let files = corpus_files();
+    assert!(!files.is_empty(), "empty corpus at {}", CORPUS);
+
+    for path in files {
+        let source = fs::read_to_string(&path).unwrap();
+        assert_eq!(run_input(&source, BUDGET), Outcome::Ok, "{}", path.display());
+    }
+}
+
+#[test]
+fn test_fuzz_smoke() {
+    for seed in 0..300 {
+        let source = Generator::new(seed).module();
+        let outcome = run_input(&source, BUDGET);
+        assert_eq!(outcome, Outcome::Ok, "seed {}:\n{}", seed, source);
+    }
+}
+
+#[test]
+fn test_minimizer_keeps_the_failure() {
+    let source = "import os\nclass A:\n    x: int = 1\n    def f(self):\n        if x:\n            boom()\n        return 2\ny = 3\n";
+    let minimized = minimize(source, &|candidate| candidate.contains("boom()"));
+
+    assert_eq!(minimized, "boom()\n");
+}
+
+#[test]
+fn test_cancelled_check_stops() {
+    let token = CancellationToken::new();
+    token.cancel();
+    let module = parse_module("x: int = 'a'\ny: str = 1\n").unwrap();
+    let errors = TypeChecker::new().with_cancellation(token).check(&module);
+
+    let messages: Vec<_> = errors.iter().map(|e| e.message.as_str()).collect();
+    assert_eq!(messages, vec!["Type checking cancelled before it completed"]);
+}
+
+#[test]
+#[ignore = "long-running fuzzer; run explicitly"]
+fn fuzz_checker_long() {
+    let seconds = std::env::var("TYPTHON_FUZZ_SECONDS").ok().and_then(|s| s.parse().ok()).unwrap_or(600);
+    let seed = std::env::var("TYPTHON_FUZZ_SEED").ok().and_then(|s| s.parse().ok())
+        .unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs());
+    let deadline = Instant::now() + Duration::from_secs(seconds);
+    let in_flight = std::env::temp_dir().join("typthon-fuzz-input.py");
+    println!("fuzzing for {}s from seed {}; current input in {}", seconds, seed, in_flight.display());
+
+    let mut runs = 0u64;
+    let mut invalid = 0u64;
+    let mut crashes = Vec::new();
+    while Instant::now() < deadline {
+        let source = Generator::new(seed.wrapping_add(runs)).module();
+        runs += 1;
+        // A stack overflow aborts the process rather than unwinding; keep
+        // the input that did it
+        fs::write(&in_flight, &source).unwrap();
+        let outcome = run_input(&source, BUDGET);
+        if outcome.is_failure() {
+            let path = record_crash(&source, &outcome);
+            println!("{} -> {}", outcome.signature(), path.display());
+            crashes.push(path);
+        } else if outcome != Outcome::Ok {
+            invalid += 1;
+        }
+    }
+
+    let _ = fs::remove_file(&in_flight);
+    println!("{} runs, {} unparseable, {} failures", runs, invalid, crashes.len());
+    assert!(crashes.is_empty(), "minimized failures written to {:?}", crashes);
+}
+
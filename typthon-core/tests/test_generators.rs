@@ -0,0 +1,60 @@
+//! Generator functions: their calls give generators, iterated for what they yield
+
+use typthon::{parse_module, Type, TypeChecker};
+
+fn check(source: &str) -> (TypeChecker, Vec<String>) {
+    let mut checker = TypeChecker::new();
+    let errors = checker.check(&parse_module(source).unwrap()).into_iter().map(|e| e.message).collect();
+    (checker, errors)
+}
+
+#[test]
+fn test_iterating_a_generator_gives_its_yield_type() {
+    let (checker, errors) = check("\
+def names(prefix: str):
+    yield prefix
+    yield prefix + '!'
+
+for name in names('a'):
+    loud = name.upper()
+");
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(checker.get_type("loud"), Some(Type::Str));
+    let names = match checker.get_type("names") {
+        Some(Type::Effect(inner, _)) => *inner,
+        other => other.unwrap(),
+    };
+    let Type::Function(_, ret) = names else { panic!("names is not a function") };
+    assert!(matches!(&*ret, Type::Generic(name, args) if name == "Generator" && args[0] == Type::Str), "{:?}", ret);
+}
+
+#[test]
+fn test_yields_are_checked_against_the_annotation() {
+    let (checker, errors) = check("\
+from typing import Generator, Iterator
+
+def counts() -> Iterator[int]:
+    yield 1
+    yield 'two'
+
+def totals() -> Generator[int, None, str]:
+    yield 1
+    return 'done'
+
+for total in totals():
+    pass
+");
+
+    assert_eq!(errors, vec!["Yield type mismatch: expected int, got str".to_string()]);
+    assert_eq!(checker.get_type("total"), Some(Type::Int));
+}
+
+#[test]
+fn test_generator_annotation_must_be_an_iterator() {
+    let (_, errors) = check("def numbers() -> int:\n    yield 1\n");
+
+    assert_eq!(errors, vec![
+        "Generator function 'numbers' must be annotated with Generator, Iterator or Iterable, not int".to_string(),
+    ]);
+}
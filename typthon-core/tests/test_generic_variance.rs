@@ -0,0 +1,86 @@
+//! Generic classes over `TypeVar`s: arguments are checked according to the
+//! declared variance of each type parameter
+
+mod common;
+
+use common::check_with_prelude;
+
+const CLASSES: &str = "\
+from typing import Generic, Sequence, TypeVar
+
+T = TypeVar('T')
+T_co = TypeVar('T_co', covariant=True)
+T_contra = TypeVar('T_contra', contravariant=True)
+
+class Animal: pass
+class Dog(Animal): pass
+";
+
+fn check(source: &str) -> Vec<(usize, String)> {
+    let (_, errors) = check_with_prelude(CLASSES, source);
+    errors.into_iter().map(|e| (e.line, e.message)).collect()
+}
+
+#[test]
+fn test_covariant_sequence_and_invariant_list() {
+    let errors = check("\
+def f(dogs: Sequence[Dog], dog_list: list[Dog]) -> None:
+    animals: Sequence[Animal] = dogs
+    animal_list: list[Animal] = dog_list
+    numbers: list[float] = [1, 2]
+");
+    assert_eq!(errors, vec![
+        (3, "Type mismatch: cannot assign list[Dog] to variable 'animal_list' of type list[Animal]".to_string()),
+    ]);
+}
+
+#[test]
+fn test_declared_variance_of_user_generics() {
+    let errors = check("\
+class Box(Generic[T]):
+    def get(self) -> T: ...
+class Reader(Generic[T_co]):
+    def read(self) -> T_co: ...
+class Sink(Generic[T_contra]):
+    def put(self, item: T_contra) -> None: ...
+
+def f(box: Box[Dog], reader: Reader[Dog], sink: Sink[Animal], dog_sink: Sink[Dog]) -> None:
+    a: Box[Animal] = box
+    b: Reader[Animal] = reader
+    c: Sink[Dog] = sink
+    d: Sink[Animal] = dog_sink
+");
+    let lines: Vec<usize> = errors.iter().map(|(line, _)| *line).collect();
+    assert_eq!(lines, vec![9, 12], "{:?}", errors);
+}
+
+#[test]
+fn test_type_parameters_are_substituted_in_members() {
+    let errors = check("\
+class Box(Generic[T]):
+    def __init__(self, item: T) -> None: ...
+    def get(self) -> T: ...
+
+b: Box[int] = Box(1)
+s: str = b.get()
+");
+    assert_eq!(errors, vec![
+        (6, "Type mismatch: cannot assign int to variable 's' of type str".to_string()),
+    ]);
+}
+
+#[test]
+fn test_covariant_parameter_taken_as_argument() {
+    let errors = check("\
+class Reader(Generic[T_co]):
+    def __init__(self, item: T_co) -> None: ...
+    def read(self) -> T_co: ...
+    def write(self, item: T_co) -> None: ...
+class Sink(Generic[T_contra]):
+    def get(self) -> T_contra: ...
+");
+    assert_eq!(errors, vec![
+        (4, "Variance error: covariant type parameter 'T_co' of Reader used in a contravariant position in 'write'".to_string()),
+        (6, "Variance error: contravariant type parameter 'T_contra' of Sink used in a covariant position in 'get'".to_string()),
+    ]);
+}
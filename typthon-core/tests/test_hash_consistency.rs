@@ -0,0 +1,102 @@
+//! Hashability agrees between the checker and the runtime
+//!
+//! Each fixture is type-checked, then replayed against the runtime's C ABI the
+//! way generated code would run it. A key the checker rejects must fail at
+//! runtime with the same `unhashable type` message, and vice versa.
+
+use std::fs;
+use std::path::Path;
+use typthon::{parse_module, TypeChecker};
+use typthon_runtime::PyObject;
+
+// The runtime ABI that compiled programs link against
+extern "C" {
+    fn typthon_string_new(ptr: *const u8, len: usize) -> PyObject;
+    fn typthon_list_new() -> PyObject;
+    fn typthon_list_append(list: PyObject, value: PyObject);
+    fn typthon_tuple_new(items: *const PyObject, len: usize) -> PyObject;
+    fn typthon_dict_new() -> PyObject;
+    fn typthon_dict_set(dict: PyObject, key: PyObject, value: PyObject);
+    fn typthon_dict_get(dict: PyObject, key: PyObject) -> PyObject;
+    fn typthon_dict_len(dict: PyObject) -> usize;
+    fn typthon_last_error() -> i32;
+    fn typthon_last_error_message() -> PyObject;
+    fn typthon_clear_error();
+    fn py_object_hash(obj: PyObject) -> i64;
+}
+
+fn check_fixture(name: &str) -> Vec<String> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("typthon-core/tests/fixtures/hashing")
+        .join(name);
+    let source = fs::read_to_string(&path).unwrap();
+    let module = parse_module(&source).unwrap();
+    TypeChecker::new().check(&module).iter().map(|e| e.message.clone()).collect()
+}
+
+fn init_runtime() {
+    typthon_runtime::allocator::init();
+    typthon_runtime::gc::init();
+    unsafe { typthon_clear_error() };
+}
+
+fn str_obj(s: &str) -> PyObject {
+    unsafe { typthon_string_new(s.as_ptr(), s.len()) }
+}
+
+fn tuple(items: &[PyObject]) -> PyObject {
+    unsafe { typthon_tuple_new(items.as_ptr(), items.len()) }
+}
+
+/// Pending runtime error message, if any
+fn runtime_error() -> Option<String> {
+    unsafe {
+        if typthon_last_error() == 0 {
+            return None;
+        }
+        Some(typthon_last_error_message().to_string())
+    }
+}
+
+#[test]
+fn test_tuple_of_str_key() {
+    let errors = check_fixture("tuple_key.py");
+    assert!(errors.is_empty(), "{:?}", errors);
+
+    init_runtime();
+    unsafe {
+        // scores = {("alice", "math"): 90}
+        let scores = typthon_dict_new();
+        typthon_dict_set(scores, tuple(&[str_obj("alice"), str_obj("math")]), PyObject::from_int(90));
+        // scores[("bob", "art")] = 75
+        typthon_dict_set(scores, tuple(&[str_obj("bob"), str_obj("art")]), PyObject::from_int(75));
+        // best = scores[("alice", "math")] -- a fresh but equal tuple
+        let best = typthon_dict_get(scores, tuple(&[str_obj("alice"), str_obj("math")]));
+
+        assert_eq!(runtime_error(), None);
+        assert_eq!(typthon_dict_len(scores), 2);
+        assert_eq!(best.as_int(), 90);
+    }
+}
+
+#[test]
+fn test_tuple_containing_list_key() {
+    let errors = check_fixture("list_key.py");
+    assert_eq!(errors, vec!["unhashable type: 'list'".to_string()]);
+
+    init_runtime();
+    unsafe {
+        // tags = ["math", "art"]
+        let tags = typthon_list_new();
+        typthon_list_append(tags, str_obj("math"));
+        typthon_list_append(tags, str_obj("art"));
+        // index = {("alice", tags): 1}
+        let index = typthon_dict_new();
+        let key = tuple(&[str_obj("alice"), tags]);
+        assert_eq!(py_object_hash(key), -1);
+        typthon_dict_set(index, key, PyObject::from_int(1));
+
+        assert_eq!(typthon_dict_len(index), 0);
+        assert_eq!(runtime_error().as_deref(), Some(errors[0].as_str()));
+    }
+}
@@ -0,0 +1,130 @@
+//! `TypeChecker::inferred_bindings` and `infer_module`: the final type of
+//! every top-level and function-local binding, and `type_at`
+
+use std::collections::BTreeMap;
+use typthon::compiler::analysis::TypeChecker;
+use typthon::parse_module;
+use typthon::typthon::infer_module;
+
+/// Displayed types of the bindings in `source`, by qualified name
+fn types(source: &str) -> BTreeMap<String, String> {
+    infer_module(source).unwrap().into_iter().map(|(name, ty, _)| (name, ty.to_string())).collect()
+}
+
+#[test]
+fn test_module_bindings() {
+    let types = types("\
+x = 1
+
+def greet(name: str) -> str:
+    return \"Hello, \" + name
+
+def shout(name: str) -> str:
+    loud = name.upper()
+    return loud
+");
+    assert_eq!(types.get("x").map(String::as_str), Some("int"));
+    assert_eq!(types.get("greet").map(String::as_str), Some("(str) -> str"));
+    assert_eq!(types.get("greet.name").map(String::as_str), Some("str"));
+    assert_eq!(types.get("shout.loud").map(String::as_str), Some("str"));
+}
+
+#[test]
+fn test_bindings_inside_blocks() {
+    let types = types("\
+total = 0
+for i in [1.0, 2.0]:
+    if i > 1.0:
+        last, pair = i, (1, \"a\")
+");
+    assert_eq!(types.get("i").map(String::as_str), Some("float"));
+    assert_eq!(types.get("last").map(String::as_str), Some("float"));
+    assert_eq!(types.get("pair").map(String::as_str), Some("(int, str)"));
+}
+
+#[test]
+fn test_method_and_nested_function_locals() {
+    let types = types("\
+class Box:
+    size = 3
+
+    def put(self, item: str) -> None:
+        stored = item
+
+def outer() -> None:
+    def inner() -> None:
+        ratio = 2.5
+");
+    assert_eq!(types.get("Box.put.self").map(String::as_str), Some("Box"));
+    assert_eq!(types.get("Box.put.stored").map(String::as_str), Some("str"));
+    assert_eq!(types.get("outer.inner").map(String::as_str).map(|ty| ty.starts_with("() -> None")), Some(true));
+    assert_eq!(types.get("outer.inner.ratio").map(String::as_str), Some("float"));
+    // Class attributes aren't function-local
+    assert!(!types.contains_key("size") && !types.contains_key("Box.size"), "{:?}", types);
+}
+
+#[test]
+fn test_bindings_are_located_where_first_bound() {
+    let source = "\
+def zero() -> int:
+    return 0
+count = zero()
+count += 1
+";
+    let mut checker = TypeChecker::new();
+    checker.set_source(source);
+    checker.check(&parse_module(source).unwrap());
+    let bindings = checker.inferred_bindings();
+    let names: Vec<&str> = bindings.iter().map(|(name, _, _)| name.as_str()).collect();
+    assert_eq!(names, vec!["zero", "count"]);
+    let count = &bindings[1].2;
+    assert_eq!((count.line, count.col, count.end_line, count.end_col), (3, 0, 3, 5));
+}
+
+#[test]
+fn test_type_at_follows_narrowing() {
+    let source = "\
+def size(text: str | None) -> int:
+    if text is None:
+        return 0
+    return len(text)
+";
+    let mut checker = TypeChecker::new();
+    checker.set_source(source);
+    checker.check(&parse_module(source).unwrap());
+    let type_at = |needle: &str, nth: usize| {
+        let offset = source.match_indices(needle).nth(nth).unwrap().0;
+        checker.type_at(offset).map(|(range, ty)| (&source[range], ty.to_string()))
+    };
+    assert_eq!(type_at("text", 0), Some(("text", "str | None".to_string())));
+    assert_eq!(type_at("text", 2), Some(("text", "str".to_string())));
+    assert_eq!(type_at("return", 0), None);
+}
+
+#[test]
+fn test_unannotated_functions_return_what_they_return() {
+    let source = "\
+def double(x: int):
+    return x * 2
+
+def countdown(n: int):
+    if n <= 0:
+        return 0
+    return countdown(n - 1)
+
+def pick(flag: bool):
+    if flag:
+        return \"yes\"
+
+doubled = double(3)
+answer: str | None = pick(True)
+";
+    let mut checker = TypeChecker::new();
+    assert!(checker.check(&parse_module(source).unwrap()).is_empty());
+    // What `TypeValidator.get_function_type` shows
+    let shown = |name: &str| checker.get_type(name).map(|ty| ty.to_string());
+    assert_eq!(shown("double").as_deref(), Some("(int) -> int"));
+    assert_eq!(shown("countdown").as_deref(), Some("(int) -> int"));
+    assert_eq!(shown("pick").as_deref(), Some("(bool) -> str | None"));
+    assert_eq!(shown("doubled").as_deref(), Some("int"));
+}
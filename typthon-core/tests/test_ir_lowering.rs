@@ -0,0 +1,74 @@
+//! IR lowering: coverage of every node kind and parity of ported analyses
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use typthon::compiler::analysis::EffectAnalyzer;
+use typthon::compiler::ast::ir::{NodeRef, StmtKind};
+use typthon::compiler::ast::{lower_module, LineIndex};
+use typthon::{TypeContext, parse_module};
+
+const STRESS: &str = include_str!("fixtures/ir/stress.py");
+
+const STMT_KINDS: &[&str] = &[
+    "FunctionDef", "AsyncFunctionDef", "ClassDef", "Return", "Delete", "Assign", "TypeAlias",
+    "AugAssign", "AnnAssign", "For", "AsyncFor", "While", "If", "With", "AsyncWith", "Match",
+    "Raise", "Try", "TryStar", "Assert", "Import", "ImportFrom", "Global", "Nonlocal", "Expr",
+    "Pass", "Break", "Continue",
+];
+
+const EXPR_KINDS: &[&str] = &[
+    "BoolOp", "NamedExpr", "BinOp", "UnaryOp", "Lambda", "IfExp", "Dict", "Set", "ListComp",
+    "SetComp", "DictComp", "GeneratorExp", "Await", "Yield", "YieldFrom", "Compare", "Call",
+    "FormattedValue", "JoinedStr", "Constant", "Attribute", "Subscript", "Starred", "Name",
+    "List", "Tuple", "Slice",
+];
+
+#[test]
+fn test_every_node_kind_lowers() {
+    let module = parse_module(STRESS).unwrap();
+    let index = LineIndex::new(STRESS);
+    let lowered = lower_module(&module, Some(&index));
+
+    let mut stmts = BTreeSet::new();
+    let mut exprs = BTreeSet::new();
+    lowered.walk(&mut |node| match node {
+        NodeRef::Stmt(stmt) => {
+            assert!(stmt.span.location.line >= 1, "{} has no position", stmt.kind_name());
+            stmts.insert(stmt.kind_name());
+        }
+        NodeRef::Expr(expr) => {
+            assert!(expr.span.location.line >= 1, "{} has no position", expr.kind_name());
+            exprs.insert(expr.kind_name());
+        }
+    });
+
+    assert_eq!(stmts, STMT_KINDS.iter().copied().collect());
+    assert_eq!(exprs, EXPR_KINDS.iter().copied().collect());
+
+    // The side-pointer reaches the original node's exact range
+    let last = lowered.body.last().unwrap();
+    assert!(matches!(last.kind, StmtKind::ClassDef(_)));
+    assert!(STRESS[last.span.range.clone()].starts_with("class Shape(Base, metaclass=Meta):"));
+}
+
+#[test]
+fn test_effect_analysis_unchanged_on_ir() {
+    let module = parse_module(STRESS).unwrap();
+    let mut analyzer = EffectAnalyzer::new(Arc::new(TypeContext::new()));
+    let effects = analyzer.analyze_module(&module);
+
+    let mut summary: Vec<_> = effects.iter()
+        .map(|(name, set)| format!("{} {:?}", name, set))
+        .collect();
+    summary.sort();
+    // Methods and nested functions are analyzed too
+    assert_eq!(summary, vec![
+        "Shape.area EffectSet { effects: [Pure] }",
+        "fetch EffectSet { effects: [Async] }",
+        "generator EffectSet { effects: [Async, Mutation] }",
+        "inner EffectSet { effects: [Mutation] }",
+        "noisy EffectSet { effects: [IO, Mutation, Exception] }",
+        "outer EffectSet { effects: [Mutation] }",
+        "pure EffectSet { effects: [Pure] }",
+    ]);
+}
@@ -0,0 +1,116 @@
+use typthon::{parse_module, Type, TypeChecker};
+
+fn check(source: &str) -> (TypeChecker, Vec<String>) {
+    let module = parse_module(source).unwrap();
+    let mut checker = TypeChecker::new();
+    let errors = checker.check(&module).iter().map(|e| e.message.clone()).collect();
+    (checker, errors)
+}
+
+#[test]
+fn test_guarded_unpack_passes() {
+    let (checker, errors) = check(
+        "from typing import List\n\
+         xs: List[int] = []\n\
+         if len(xs) == 2:\n    a, b = xs\n    first = xs[0]\n    last = xs[-1]\n\
+         if len(xs) >= 3:\n    c, d = xs[:2]\n",
+    );
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(checker.get_type("a"), Some(Type::Int));
+    assert_eq!(checker.get_type("d"), Some(Type::Int));
+}
+
+#[test]
+fn test_index_out_of_range_after_guard() {
+    let (_, errors) = check(
+        "from typing import List\n\
+         xs: List[int] = []\n\
+         if len(xs) == 1:\n    y = xs[1]\n",
+    );
+
+    assert_eq!(errors, vec!["list index 1 out of range (length 1)".to_string()]);
+}
+
+#[test]
+fn test_unpack_arity_uses_narrowed_length() {
+    let (_, errors) = check(
+        "from typing import List\n\
+         xs: List[int] = []\n\
+         if len(xs) < 2:\n    a, b = xs\n\
+         else:\n    c, d = xs\n",
+    );
+
+    // The else branch only knows len(xs) >= 2, which may still be fine
+    assert_eq!(errors, vec!["not enough values to unpack (expected 2, got at most 1)".to_string()]);
+}
+
+#[test]
+fn test_negative_slice_of_known_length_literal() {
+    let (checker, errors) = check(
+        "a, b = [1, 2, 3][-2:]\n\
+         t = (1, 'x', 2.0)[-2:]\n\
+         c, d, e = [1, 2, 3, 4][::-1][1:]\n",
+    );
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(checker.get_type("a"), Some(Type::Int));
+    assert_eq!(checker.get_type("t"), Some(Type::Tuple(vec![Type::Str, Type::Float])));
+
+    let (_, errors) = check("a, b = [1, 2, 3][-1:]\n");
+    assert_eq!(errors, vec!["not enough values to unpack (expected 2, got 1)".to_string()]);
+}
+
+#[test]
+fn test_unknown_lengths_stay_silent() {
+    let (_, errors) = check(
+        "from typing import List\n\
+         xs: List[int] = []\n\
+         n = 3\n\
+         a, b = xs\n\
+         y = xs[5]\n\
+         c, d = xs[1:n]\n\
+         if len(xs) == 1:\n    xs.append(2)\n    e, f = xs\n",
+    );
+
+    assert!(errors.is_empty(), "{:?}", errors);
+}
+
+#[test]
+fn test_slices_keep_the_sequence_type() {
+    let (checker, errors) = check(
+        "xs: list[int] = []\n\
+         s = 'hello'\n\
+         t: tuple[int, str, float] = (1, 'a', 2.0)\n\
+         n = int('2')\n\
+         tail = xs[1:]\n\
+         every_other = s[::2]\n\
+         head = t[0:2]\n\
+         rest = t[n:]\n\
+         last = t[-1]\n",
+    );
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(checker.get_type("tail"), Some(Type::List(Box::new(Type::Int))));
+    assert_eq!(checker.get_type("every_other"), Some(Type::Str));
+    assert_eq!(checker.get_type("head"), Some(Type::Tuple(vec![Type::Int, Type::Str])));
+    assert_eq!(checker.get_type("rest"), Some(Type::VarTuple(Box::new(Type::union(vec![Type::Int, Type::Str, Type::Float])))));
+    assert_eq!(checker.get_type("last"), Some(Type::Float));
+}
+
+#[test]
+fn test_slice_bounds_must_be_indices() {
+    let (_, errors) = check(
+        "xs: list[int] = [1, 2, 3]\n\
+         t: tuple[int, str, float] = (1, 'a', 2.0)\n\
+         a = xs['a':]\n\
+         b = xs[:1.5:None]\n\
+         c = t[5]\n",
+    );
+
+    assert_eq!(errors, vec![
+        "slice indices must be integers or None, not str".to_string(),
+        "slice indices must be integers or None, not float".to_string(),
+        "tuple index 5 out of range (length 3)".to_string(),
+    ]);
+}
@@ -0,0 +1,153 @@
+use typthon::{parse_module, TypeChecker};
+use typthon::compiler::analysis::CheckerOptions;
+use typthon::compiler::analysis::lints::{ADJACENT_BOOL_PARAMS, BOOLEAN_POSITIONAL, FLOAT_EQUALITY};
+use typthon::compiler::frontend::config::Config;
+
+fn lint(source: &str, config: &str) -> Vec<typthon::compiler::analysis::LintDiagnostic> {
+    let config = Config::parse(config).unwrap();
+    let options = CheckerOptions { lints: config.lints, ..CheckerOptions::default() };
+    TypeChecker::new().with_options(options).lint(&parse_module(source).unwrap())
+}
+
+/// Type-aware lints need the checker to have run first
+fn check_and_lint(source: &str) -> Vec<typthon::compiler::analysis::LintDiagnostic> {
+    let config = Config::parse("[lints]\nC001 = \"warn\"\n").unwrap();
+    let options = CheckerOptions { lints: config.lints, ..CheckerOptions::default() };
+    let module = parse_module(source).unwrap();
+    let mut checker = TypeChecker::new().with_options(options);
+    checker.check(&module);
+    checker.lint(&module)
+}
+
+#[test]
+fn test_boolean_positional_warning_and_fix() {
+    let source = "def resize(image, keepdim, inplace=False):\n    pass\n\nresize(img, True, flag)\n";
+    let lints = lint(source, "[lints]\nS001 = \"warn\"\n");
+
+    assert_eq!(lints.len(), 1);
+    assert_eq!(lints[0].code, BOOLEAN_POSITIONAL);
+    assert_eq!(lints[0].message, "boolean positional argument; use keepdim=True for clarity");
+    assert_eq!(&source[lints[0].range.clone()], "True");
+
+    // Later positionals have to become keywords as well
+    let fix = lints[0].fix.as_ref().unwrap();
+    assert_eq!(
+        fix.apply(source),
+        "def resize(image, keepdim, inplace=False):\n    pass\n\nresize(img, keepdim=True, inplace=flag)\n"
+    );
+}
+
+#[test]
+fn test_adjacent_boolean_parameters_suggest_keyword_only() {
+    let source = "def resize(image, keepdim: bool, inplace=False):\n    pass\n";
+    let lints = lint(source, "[lints]\nS002 = \"warn\"\n");
+
+    assert_eq!(lints.len(), 1);
+    assert_eq!(lints[0].code, ADJACENT_BOOL_PARAMS);
+    assert_eq!(
+        lints[0].message,
+        "boolean parameters 'keepdim' and 'inplace' are adjacent; make them keyword-only"
+    );
+    assert_eq!(
+        lints[0].fix.as_ref().unwrap().apply(source),
+        "def resize(image, *, keepdim: bool, inplace=False):\n    pass\n"
+    );
+}
+
+#[test]
+fn test_lints_silent_without_signature_or_literal() {
+    let source = "import lib\n\
+                  def resize(image, keepdim):\n    pass\n\
+                  lib.resize(img, True)\n\
+                  resize(img, not done)\n\
+                  resize(img, keepdim=True)\n";
+    assert!(lint(source, "[lints]\nS001 = \"warn\"\n").is_empty());
+}
+
+#[test]
+fn test_per_code_config_suppression() {
+    let source = "def f(a: bool, b: bool):\n    pass\nf(True, False)\n";
+
+    let all = lint(source, "[lints]\nS001 = \"warn\"\nS002 = \"error\"\n");
+    assert_eq!(all.len(), 3);
+
+    let only_definitions = lint(source, "[lints]\nS001 = \"off\"\nS002 = \"warn\"\n");
+    let codes: Vec<_> = only_definitions.iter().map(|lint| lint.code).collect();
+    assert_eq!(codes, vec![ADJACENT_BOOL_PARAMS]);
+
+    // Style lints are opt-in
+    assert!(lint(source, "").is_empty());
+}
+
+#[test]
+fn test_float_equality_warning_and_fix_adds_import() {
+    let source = "\"\"\"Geometry.\"\"\"\nfrom __future__ import annotations\n\n\
+                  def same(a: float, b: float) -> bool:\n    return a == b\n";
+    let lints = check_and_lint(source);
+
+    assert_eq!(lints.len(), 1);
+    assert_eq!(lints[0].code, FLOAT_EQUALITY);
+    assert_eq!(
+        lints[0].message,
+        "float equality comparison; use math.isclose(a, b) or compare against an explicit tolerance"
+    );
+    assert_eq!(&source[lints[0].range.clone()], "a == b");
+    assert_eq!(
+        lints[0].fix.as_ref().unwrap().apply(source),
+        "\"\"\"Geometry.\"\"\"\nfrom __future__ import annotations\n\nimport math\n\
+         def same(a: float, b: float) -> bool:\n    return math.isclose(a, b)\n"
+    );
+}
+
+#[test]
+fn test_float_inequality_reuses_existing_import() {
+    let source = "from math import isclose as close\n\
+                  def differ(a: float, b: float | int) -> bool:\n    return a != b\n";
+    let lints = check_and_lint(source);
+
+    assert_eq!(lints.len(), 1);
+    assert_eq!(
+        lints[0].fix.as_ref().unwrap().apply(source),
+        "from math import isclose as close\n\
+         def differ(a: float, b: float | int) -> bool:\n    return not close(a, b)\n"
+    );
+}
+
+#[test]
+fn test_float_equality_exemptions() {
+    let source = "import math\n\
+                  def f(x: float, y: float, n: int, m: int) -> bool:\n\
+                  \x20   assert x == y\n\
+                  \x20   assert n == m\n\
+                  \x20   assert x == 0.0\n\
+                  \x20   assert x != float(\"inf\")\n\
+                  \x20   assert -math.inf == x\n\
+                  \x20   assert x == float(\"nan\")\n\
+                  \x20   return x == n\n";
+    // Only the genuine float comparison is reported; int == int never is
+    let lints = check_and_lint(source);
+    let flagged: Vec<_> = lints.iter().map(|lint| &source[lint.range.clone()]).collect();
+    assert_eq!(flagged, vec!["x == y"]);
+
+    // Style-only configs leave the lint off
+    assert!(lint("def f(a: float, b: float) -> bool:\n    return a == b\n", "[lints]\nS001 = \"warn\"\n").is_empty());
+}
+
+#[test]
+fn test_float_equality_suppressed_with_pytest_approx() {
+    let source = "import pytest\n\
+                  def test_area(a: float, b: float) -> None:\n\
+                  \x20   assert a == pytest.approx(b)\n\
+                  \x20   assert a == b\n";
+    assert!(check_and_lint(source).is_empty());
+}
+
+#[test]
+fn test_float_equality_without_safe_rewrite_has_no_fix() {
+    let source = "def f(a: float, b: float, c: float) -> bool:\n    return (a + b) == c\n";
+    let lints = check_and_lint(source);
+
+    assert_eq!(lints.len(), 1);
+    assert_eq!(&source[lints[0].range.clone()], "(a + b) == c");
+    assert!(lints[0].fix.is_none());
+}
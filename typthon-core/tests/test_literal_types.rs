@@ -0,0 +1,84 @@
+//! `Literal[...]` parameters: constant arguments, widening in unions and
+//! narrowing on `==` / `!=`
+
+mod common;
+
+use typthon::compiler::types::DependentConstraint;
+use typthon::Type;
+use common::check_with_prelude;
+
+const OPEN_MODE: &str = "\
+from typing import Literal
+
+def open_mode(m: Literal['r', 'w']) -> None:
+    pass
+";
+
+fn check(source: &str) -> Vec<(usize, String)> {
+    let (_, errors) = check_with_prelude(OPEN_MODE, source);
+    errors.into_iter().map(|e| (e.line, e.message)).collect()
+}
+
+fn literal(base: Type, value: &str) -> Type {
+    Type::Dependent(Box::new(base), DependentConstraint::ValueEq(value.to_string()))
+}
+
+#[test]
+fn test_constant_arguments_are_checked_exactly() {
+    let errors = check("open_mode('r')\nopen_mode(m='w')\nopen_mode('x')\nopen_mode(m='a')\n");
+    assert_eq!(errors, vec![
+        (3, "Argument 0 type mismatch: expected Literal['r', 'w'], got 'x'".to_string()),
+        (4, "Keyword argument 'm' type mismatch: expected Literal['r', 'w'], got 'a'".to_string()),
+    ]);
+}
+
+#[test]
+fn test_equality_narrows_to_the_literal() {
+    let source = "\
+def f(s: str) -> None:
+    if s == 'r':
+        open_mode(s)
+    if 'w' != s:
+        return
+    open_mode(s)
+
+def g(s: str) -> None:
+    open_mode(s)
+";
+    let errors: Vec<usize> = check(source).into_iter().map(|(line, _)| line).collect();
+    assert_eq!(errors, vec![9]);
+}
+
+#[test]
+fn test_inequality_drops_a_literal_from_a_union() {
+    let source = "\
+def f(m: Literal['r', 'w']) -> None:
+    if m != 'r':
+        only_w: Literal['w'] = m
+    else:
+        only_r: Literal['r'] = m
+        wrong: Literal['w'] = m
+";
+    let errors: Vec<usize> = check(source).into_iter().map(|(line, _)| line).collect();
+    assert_eq!(errors, vec![6]);
+}
+
+#[test]
+fn test_literals_widen_to_their_base_type() {
+    let r = literal(Type::Str, "'r'");
+    assert!(r.is_subtype(&Type::Str));
+    assert!(!Type::Str.is_subtype(&r));
+    assert_eq!(Type::union(vec![r.clone(), Type::Str]), Type::Str);
+    assert_eq!(Type::union(vec![Type::Str, r]), Type::Str);
+    assert_eq!(
+        Type::union(vec![literal(Type::Int, "1"), literal(Type::Int, "2")]),
+        Type::Union(vec![literal(Type::Int, "1"), literal(Type::Int, "2")]),
+    );
+}
+
+#[test]
+fn test_literal_types_display_as_written() {
+    let modes = Type::Union(vec![literal(Type::Str, "'r'"), Type::None, literal(Type::Str, "'w'")]);
+    assert_eq!(modes.to_string(), "Literal['r', 'w'] | None");
+    assert_eq!(literal(Type::Int, "3").to_string(), "Literal[3]");
+}
@@ -0,0 +1,42 @@
+//! Annotated functions that can end without returning a value
+
+use typthon::{parse_module, TypeChecker};
+
+fn check(source: &str) -> Vec<(usize, String)> {
+    let mut checker = TypeChecker::new();
+    checker.set_source(source);
+    checker.check(&parse_module(source).unwrap()).into_iter().map(|e| (e.line, e.message)).collect()
+}
+
+#[test]
+fn test_paths_that_fall_off_the_end_are_reported() {
+    let errors = check(
+        "def one_branch(x: int) -> int:\n    if x:\n        return 1\n\n\
+         def both_branches(x: int) -> int:\n    if x:\n        return 1\n    elif x > 2:\n        raise ValueError()\n    else:\n        return 2\n\n\
+         def forever() -> int:\n    while True:\n        if forever():\n            return 1\n\n\
+         def escapes() -> int:\n    while True:\n        break\n\n\
+         def loop_only(xs: list[int]) -> int:\n    for x in xs:\n        return x\n",
+    );
+
+    assert_eq!(errors, vec![
+        (1, "Missing return: 'one_branch' is declared to return int but can end without returning".to_string()),
+        (18, "Missing return: 'escapes' is declared to return int but can end without returning".to_string()),
+        (22, "Missing return: 'loop_only' is declared to return int but can end without returning".to_string()),
+    ]);
+}
+
+#[test]
+fn test_functions_allowed_to_end_without_returning() {
+    let errors = check(
+        "from typing import Iterator, Optional, overload\n\
+         def untyped(x):\n    x = 1\n\n\
+         def nothing() -> None:\n    x = 1\n\n\
+         def maybe(x: int) -> Optional[int]:\n    if x:\n        return x\n\n\
+         def stub() -> int:\n    '''Implemented elsewhere.'''\n    ...\n\n\
+         def numbers() -> Iterator[int]:\n    yield 1\n\n\
+         @overload\n\
+         def pick(x: int) -> int:\n    pass\n",
+    );
+
+    assert!(errors.is_empty(), "{:?}", errors);
+}
@@ -0,0 +1,139 @@
+//! Names imported from other project modules carry their types
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::TempDir;
+use typthon::compiler::analysis::{ModuleResolver, TypeChecker};
+use typthon::parse_module;
+
+fn project(files: &[(&str, &str)]) -> TempDir {
+    let dir = TempDir::new().unwrap();
+    for (path, source) in files {
+        let path = dir.path().join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, source).unwrap();
+    }
+    dir
+}
+
+/// Lines of the errors `file` gets, checked with `resolver`
+fn error_lines(resolver: &Arc<ModuleResolver>, root: &Path, file: &str) -> Vec<usize> {
+    let path = root.join(file);
+    let source = fs::read_to_string(&path).unwrap();
+    let mut checker = TypeChecker::new().with_modules(resolver.clone());
+    checker.set_source_file(&path, &source);
+    checker.check(&parse_module(&source).unwrap()).into_iter().map(|e| e.line).collect()
+}
+
+#[test]
+fn test_resolves_modules_and_packages() {
+    let dir = project(&[
+        ("app/__init__.py", ""),
+        ("app/db.py", ""),
+        ("app/api/__init__.py", ""),
+        ("app/api/views.py", ""),
+    ]);
+    let root = dir.path();
+    let resolver = ModuleResolver::new([root.to_path_buf()]);
+    let views = root.join("app/api/views.py");
+
+    assert_eq!(resolver.resolve(&views, 0, "app.db"), Some(root.join("app/db.py")));
+    assert_eq!(resolver.resolve(&views, 0, "app.api"), Some(root.join("app/api/__init__.py")));
+    assert_eq!(resolver.resolve(&views, 1, ""), Some(root.join("app/api/__init__.py")));
+    assert_eq!(resolver.resolve(&views, 2, "db"), Some(root.join("app/db.py")));
+    assert_eq!(resolver.resolve(&views, 0, "json"), None);
+}
+
+#[test]
+fn test_wrong_argument_to_imported_function() {
+    let dir = project(&[
+        ("util.py", "def scale(value: int, factor: int) -> int:\n    return value * factor\n"),
+        ("main.py", "from util import scale\nfrom util import scale as resize\nscale(1, 2)\nresize('a', 2)\n"),
+    ]);
+    let resolver = Arc::new(ModuleResolver::new([dir.path().to_path_buf()]));
+    assert_eq!(error_lines(&resolver, dir.path(), "main.py"), vec![4]);
+    // Checking main.py checked util.py first and kept what it exports
+    assert!(resolver.get(&dir.path().join("util.py")).unwrap().types.contains_key("scale"));
+}
+
+#[test]
+fn test_relative_and_aliased_module_imports() {
+    let dir = project(&[
+        ("pkg/__init__.py", "from .shapes import area\n"),
+        ("pkg/shapes.py", "from .units import square\n\ndef area(side: float) -> float:\n    return square(side)\n"),
+        ("pkg/units.py", "def square(x: float) -> float:\n    return x * x\n"),
+        ("main.py", "import pkg.shapes as shapes\nimport pkg.units\nfrom pkg import area\n\
+shapes.area('a')\npkg.units.square('b')\narea('c')\nx: float = area(2.0)\n"),
+    ]);
+    let resolver = Arc::new(ModuleResolver::new([dir.path().to_path_buf()]));
+    assert_eq!(error_lines(&resolver, dir.path(), "main.py"), vec![4, 5, 6]);
+}
+
+#[test]
+fn test_imported_class_methods_are_checked() {
+    let dir = project(&[
+        ("models.py", "class Counter:\n    def bump(self, by: int) -> int:\n        return by\n"),
+        ("main.py", "from models import *\nc = Counter()\nc.bump('x')\n"),
+    ]);
+    let resolver = Arc::new(ModuleResolver::new([dir.path().to_path_buf()]));
+    assert_eq!(error_lines(&resolver, dir.path(), "main.py"), vec![3]);
+}
+
+#[test]
+fn test_import_cycles_terminate_and_are_not_recorded() {
+    let dir = project(&[
+        ("a.py", "from b import pong\n\ndef ping(n: int) -> int:\n    return n\n"),
+        ("b.py", "from a import ping\n\ndef pong(n: int) -> int:\n    return ping('x')\n"),
+    ]);
+    let resolver = Arc::new(ModuleResolver::new([dir.path().to_path_buf()]));
+    // b.py's import closes the cycle a -> b -> a
+    assert_eq!(error_lines(&resolver, dir.path(), "b.py"), vec![4, 1]);
+    assert!(error_lines(&resolver, dir.path(), "a.py").is_empty());
+    assert!(resolver.get(&dir.path().join("a.py")).is_none());
+    assert!(resolver.get(&dir.path().join("b.py")).is_none());
+}
+
+/// Cycle diagnostics of each of `files`, as file, line and message
+fn cycle_errors(resolver: &Arc<ModuleResolver>, root: &Path, files: &[&str]) -> Vec<(String, usize, String)> {
+    let mut errors = Vec::new();
+    for file in files {
+        let path = root.join(file);
+        let source = fs::read_to_string(&path).unwrap();
+        let mut checker = TypeChecker::new().with_modules(resolver.clone());
+        checker.set_source_file(&path, &source);
+        for error in checker.check(&parse_module(&source).unwrap()) {
+            if error.code == Some("TYP013") {
+                errors.push((file.to_string(), error.line, error.message));
+            }
+        }
+    }
+    errors
+}
+
+#[test]
+fn test_import_cycle_is_reported_once_with_its_chain() {
+    let dir = project(&[
+        ("a.py", "import b\n\nVALUE = 1\n"),
+        ("b.py", "from c import run\n"),
+        ("c.py", "import os\nfrom a import VALUE\n\ndef run() -> int:\n    return VALUE\n"),
+    ]);
+    let resolver = Arc::new(ModuleResolver::new([dir.path().to_path_buf()]));
+    // Whichever module is checked first
+    for order in [["a.py", "b.py", "c.py"], ["c.py", "b.py", "a.py"]] {
+        assert_eq!(cycle_errors(&resolver, dir.path(), &order), vec![
+            ("c.py".to_string(), 2, "Circular dependency: a -> b -> c -> a".to_string()),
+        ]);
+    }
+}
+
+#[test]
+fn test_late_imports_do_not_close_cycles() {
+    let dir = project(&[
+        ("app.py", "from jobs import enqueue\n"),
+        ("jobs.py", "import hints\n\ndef enqueue() -> None:\n    import app\n"),
+        ("hints.py", "from typing import TYPE_CHECKING\nif TYPE_CHECKING:\n    import jobs\n"),
+    ]);
+    let resolver = Arc::new(ModuleResolver::new([dir.path().to_path_buf()]));
+    assert!(cycle_errors(&resolver, dir.path(), &["app.py", "jobs.py", "hints.py"]).is_empty());
+}
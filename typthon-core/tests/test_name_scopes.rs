@@ -0,0 +1,130 @@
+//! Names resolve by Python's scoping rules: what a function binds is local
+//! to it, comprehension targets don't leak, and `global`/`nonlocal` bind
+//! in the scope they name
+
+use typthon::compiler::errors::codes;
+use typthon::{parse_module, Type, TypeChecker};
+
+fn check(source: &str) -> (TypeChecker, Vec<(usize, Option<&'static str>, String)>) {
+    let mut checker = TypeChecker::new();
+    checker.set_source(source);
+    let errors = checker.check(&parse_module(source).unwrap()).into_iter().map(|e| (e.line, e.code, e.message)).collect();
+    (checker, errors)
+}
+
+#[test]
+fn test_functions_keep_their_locals_to_themselves() {
+    let (checker, errors) = check("\
+def first() -> int:
+    tmp = 1
+    return tmp
+
+def second() -> str:
+    tmp = 'a'
+    return tmp
+
+tmp: bytes = b''
+");
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(checker.get_type("tmp"), Some(Type::Bytes));
+}
+
+#[test]
+fn test_local_read_before_assignment_is_reported() {
+    let (_, errors) = check("\
+count = 0
+
+def bump() -> int:
+    total = count + 1
+    count = total
+    return count
+");
+
+    assert_eq!(errors, vec![
+        (4, Some(codes::UNBOUND_LOCAL), "Local variable 'count' is read before it's assigned".to_string()),
+    ]);
+}
+
+#[test]
+fn test_closures_read_enclosing_variables() {
+    let (checker, errors) = check("\
+def outer() -> int:
+    base = 10
+    def inner(step: int) -> int:
+        return base + step
+    return inner(1)
+
+def later() -> str:
+    def inner() -> str:
+        return label
+    label = 'x'
+    return inner()
+");
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(checker.get_type("base"), None);
+}
+
+#[test]
+fn test_comprehension_targets_do_not_leak() {
+    let (checker, errors) = check("\
+item = 'kept'
+squares = [item * item for item in [1, 2, 3]]
+");
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(checker.get_type("item"), Some(Type::Str));
+    assert_eq!(checker.get_type("squares"), Some(Type::List(Box::new(Type::Int))));
+}
+
+#[test]
+fn test_global_and_nonlocal_bind_where_they_name() {
+    let (checker, errors) = check("\
+setting = 'off'
+
+def configure() -> None:
+    global setting
+    setting = 'on'
+
+def reset() -> None:
+    setting = 0
+
+def counter() -> int:
+    total = 0
+    def add() -> None:
+        nonlocal total
+        total = total + 1
+    add()
+    return total
+");
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(checker.get_type("setting"), Some(Type::Str));
+    assert_eq!(checker.get_type("total"), None);
+
+    let (_, errors) = check("\
+setting = 'off'
+
+def configure() -> None:
+    global setting
+    setting = 1
+");
+    assert_eq!(errors.first().map(|(line, code, _)| (*line, *code)), Some((5, Some(codes::TYPE_MISMATCH))), "{:?}", errors);
+}
+
+#[test]
+fn test_methods_do_not_see_class_body_names() {
+    let (checker, errors) = check("\
+limit = 'module'
+
+class Config:
+    limit = 3
+
+    def describe(self) -> str:
+        return limit
+");
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(checker.get_type("limit"), Some(Type::Str));
+}
@@ -0,0 +1,231 @@
+use typthon::{parse_module, Type, TypeChecker};
+
+fn check(source: &str) -> (TypeChecker, Vec<String>) {
+    let module = parse_module(source).unwrap();
+    let mut checker = TypeChecker::new();
+    let errors = checker.check(&module).iter().map(|e| e.message.clone()).collect();
+    (checker, errors)
+}
+
+fn union(types: &[Type]) -> Type {
+    Type::Union(types.to_vec())
+}
+
+#[test]
+fn test_isinstance_tuple_in_if() {
+    let (checker, errors) = check(
+        "from typing import Union\n\
+         x: Union[int, str, bytes] = 1\n\
+         if isinstance(x, (str, bytes)):\n    a = x\n\
+         else:\n    b = x\n\
+         c = x\n",
+    );
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(checker.get_type("a"), Some(union(&[Type::Str, Type::Bytes])));
+    assert_eq!(checker.get_type("b"), Some(Type::Int));
+    // Narrowing ends with the branch
+    assert_eq!(checker.get_type("c"), Some(union(&[Type::Int, Type::Str, Type::Bytes])));
+}
+
+#[test]
+fn test_isinstance_tuple_in_assert() {
+    let (checker, errors) = check(
+        "from typing import Union\n\
+         x: Union[int, str, bytes, None] = None\n\
+         assert isinstance(x, (str, (bytes, int)))\n\
+         a = x\n",
+    );
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(checker.get_type("a"), Some(union(&[Type::Int, Type::Str, Type::Bytes])));
+}
+
+#[test]
+fn test_exact_type_keeps_subclass_in_else() {
+    let (checker, errors) = check(
+        "from typing import Union\n\
+         class Animal:\n    pass\n\
+         class Dog(Animal):\n    pass\n\
+         class Cat:\n    pass\n\
+         x: Union[Animal, Dog, Cat] = Cat()\n\
+         if type(x) is Animal:\n    a = x\n\
+         else:\n    b = x\n\
+         if isinstance(x, Animal):\n    c = x\n\
+         else:\n    d = x\n",
+    );
+
+    let class = |name: &str| Type::Class(name.to_string());
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(checker.get_type("a"), Some(class("Animal")));
+    assert_eq!(checker.get_type("b"), Some(union(&[class("Dog"), class("Cat")])));
+    assert_eq!(checker.get_type("c"), Some(union(&[class("Animal"), class("Dog")])));
+    assert_eq!(checker.get_type("d"), Some(class("Cat")));
+}
+
+#[test]
+fn test_narrowing_composes_with_boolean_operators() {
+    let (checker, errors) = check(
+        "from typing import Union\n\
+         x: Union[int, str, bytes] = 1\n\
+         if not isinstance(x, (str, bytes)):\n    a = x\n\
+         if isinstance(x, str) or isinstance(x, bytes):\n    b = x\n\
+         else:\n    c = x\n\
+         if isinstance(x, (int, str)) and type(x) != int:\n    d = x\n",
+    );
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(checker.get_type("a"), Some(Type::Int));
+    assert_eq!(checker.get_type("b"), Some(union(&[Type::Str, Type::Bytes])));
+    assert_eq!(checker.get_type("c"), Some(Type::Int));
+    assert_eq!(checker.get_type("d"), Some(Type::Str));
+}
+
+#[test]
+fn test_isinstance_non_class_diagnostic() {
+    let (_, errors) = check(
+        "x: int = 1\n\
+         if isinstance(x, (str, 42)):\n    pass\n",
+    );
+
+    assert_eq!(errors, vec!["isinstance() expects a class or tuple of classes, got 42".to_string()]);
+}
+
+#[test]
+fn test_union_parameter_narrowed_in_both_branches() {
+    let (_, errors) = check(
+        "from typing import Union\n\
+         def shout(x: Union[int, str]) -> None:\n\
+         \x20   if isinstance(x, str):\n        loud = x.upper()\n\
+         \x20   else:\n        quiet = x.upper()\n\
+         \x20   after = x.upper()\n",
+    );
+
+    // Only the str branch may call .upper(); the union is back after the if
+    assert_eq!(errors, vec![
+        "Type 'int' has no attribute 'upper'".to_string(),
+        "Type 'int | str' has no attribute 'upper'".to_string(),
+    ]);
+}
+
+#[test]
+fn test_nested_narrowing_composes() {
+    let (checker, errors) = check(
+        "from typing import Union\n\
+         def split(x: Union[int, str, bytes]) -> None:\n\
+         \x20   if not isinstance(x, int):\n\
+         \x20       if type(x) is str:\n            a = x\n\
+         \x20       else:\n            b = x\n\
+         \x20   elif isinstance(x, (int, str)):\n        c = x\n",
+    );
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(checker.local_binding("split", "a"), Some(Type::Str));
+    assert_eq!(checker.local_binding("split", "b"), Some(Type::Bytes));
+    assert_eq!(checker.local_binding("split", "c"), Some(Type::Int));
+}
+
+#[test]
+fn test_type_guard_narrows_in_true_branch() {
+    let (_, errors) = check(
+        "from typing import TypeGuard\n\
+         def is_str_list(val: list[object]) -> TypeGuard[list[str]]:\n\
+         \x20   return all(isinstance(x, str) for x in val)\n\
+         def shout(xs: list[object]) -> None:\n\
+         \x20   before = xs[0].upper()\n\
+         \x20   if is_str_list(xs):\n        inside = xs[0].upper()\n\
+         \x20   checked: bool = is_str_list(xs)\n",
+    );
+
+    // Only the call before the guard is flagged; the guard itself returns a bool
+    assert_eq!(errors, vec!["Type 'object' has no attribute 'upper'".to_string()]);
+}
+
+#[test]
+fn test_type_is_narrows_both_branches() {
+    let (checker, errors) = check(
+        "from typing import TypeIs, Union\n\
+         def is_int(val: Union[int, str]) -> TypeIs[int]:\n\
+         \x20   return isinstance(val, int)\n\
+         x: Union[int, str] = 1\n\
+         if is_int(x):\n    a = x\n\
+         else:\n    b = x\n",
+    );
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(checker.get_type("a"), Some(Type::Int));
+    assert_eq!(checker.get_type("b"), Some(Type::Str));
+}
+
+#[test]
+fn test_assert_narrows_rest_of_block() {
+    let (_, errors) = check(
+        "from typing import Optional\n\
+         def describe(value: object, count: Optional[int]) -> None:\n\
+         \x20   assert isinstance(value, str)\n\
+         \x20   assert count is not None\n\
+         \x20   label = value.upper()\n\
+         \x20   total = count + 1\n",
+    );
+
+    assert!(errors.is_empty(), "{:?}", errors);
+}
+
+#[test]
+fn test_attributes_narrow_until_assigned() {
+    let (_, errors) = check(
+        "from typing import Optional\n\
+         class Counter:\n\
+         \x20   def __init__(self) -> None:\n\
+         \x20       self.x: Optional[int] = None\n\
+         \x20   def get(self) -> int:\n\
+         \x20       if self.x is not None:\n\
+         \x20           return self.x\n\
+         \x20       return 0\n\
+         \x20   def unchecked(self) -> int:\n\
+         \x20       return self.x\n\
+         def size(counter: Counter) -> int:\n\
+         \x20   if counter.x:\n\
+         \x20       return counter.x\n\
+         \x20   return 0\n\
+         class Slot:\n\
+         \x20   def __init__(self) -> None:\n\
+         \x20       self.value: Optional[int] = None\n\
+         \x20   def reset(self) -> int:\n\
+         \x20       if self.value is not None:\n\
+         \x20           self.value = None\n\
+         \x20           return self.value\n\
+         \x20       return 0\n",
+    );
+
+    // Narrowing ends with the method, and where the attribute is assigned
+    assert_eq!(errors, vec![
+        "Return type mismatch: expected Int, got Union([Int, None])".to_string(),
+        "Return type mismatch: expected Int, got None".to_string(),
+    ]);
+}
+
+#[test]
+fn test_branches_keep_what_they_reassign() {
+    let (checker, errors) = check(
+        "from typing import Union\n\
+         x: Union[int, str, None] = None\n\
+         if isinstance(x, int):\n    x = 's'\n\
+         a = x\n\
+         y: Union[int, None] = None\n\
+         if y is None:\n    y = 0\n\
+         b = y\n\
+         z: Union[int, str] = 1\n\
+         if isinstance(z, int):\n    c = z\n\
+         else:\n    z = 2\n\
+         d = z\n\
+         x = 3\n",
+    );
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    // Joined with what the branch that wasn't taken left
+    assert_eq!(checker.get_type("a"), Some(union(&[Type::Str, Type::None])));
+    assert_eq!(checker.get_type("b"), Some(Type::Int));
+    assert_eq!(checker.get_type("c"), Some(Type::Int));
+    assert_eq!(checker.get_type("d"), Some(Type::Int));
+}
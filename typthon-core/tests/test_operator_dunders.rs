@@ -0,0 +1,119 @@
+//! Operators, `len()`, subscripts, `in` and calls on class instances,
+//! resolved through the class's dunder methods
+
+mod common;
+
+use typthon::compiler::errors::codes;
+use typthon::{Type, TypeChecker};
+use common::check_with_prelude;
+
+const VECTOR: &str = "\
+class Vector:
+    def __init__(self, x: int) -> None:
+        self.x = x
+    def __add__(self, other: 'Vector') -> 'Vector':
+        return Vector(self.x + other.x)
+    def __rmul__(self, factor: int) -> 'Vector':
+        return Vector(self.x * factor)
+    def __lt__(self, other: 'Vector') -> bool:
+        return self.x < other.x
+    def __len__(self) -> int:
+        return 1
+    def __getitem__(self, index: int) -> float:
+        return 1.0
+    def __contains__(self, value: int) -> bool:
+        return True
+    def __call__(self, scale: int) -> str:
+        return ''
+
+class Plain:
+    pass
+
+v1 = Vector(1)
+v2 = Vector(2)
+plain = Plain()
+";
+
+fn check(source: &str) -> (TypeChecker, Vec<(usize, &'static str, String)>) {
+    let (checker, errors) = check_with_prelude(VECTOR, source);
+    (checker, errors.into_iter().map(|e| (e.line, e.code.unwrap_or_default(), e.message)).collect())
+}
+
+fn vector() -> Type {
+    Type::Class("Vector".to_string())
+}
+
+#[test]
+fn test_binary_operators_use_the_operand_methods() {
+    let (checker, errors) = check("total = v1 + v2\nscaled = 3 * v1\nbad = v1 + 3\n");
+    assert_eq!(checker.get_type("total"), Some(vector()));
+    assert_eq!(checker.get_type("scaled"), Some(vector()));
+    assert_eq!(errors, vec![(
+        3,
+        codes::UNSUPPORTED_OPERAND,
+        "Unsupported operand types for +: Vector and int (Vector.__add__ expects Vector)".to_string(),
+    )]);
+}
+
+#[test]
+fn test_operators_missing_from_the_class_are_reported() {
+    let (_, errors) = check("a = plain + 1\nb = v1 - v2\nc = plain < plain\nd = 3 < v1\n");
+    let lines: Vec<_> = errors.iter().map(|(line, code, _)| (*line, *code)).collect();
+    assert_eq!(lines, vec![
+        (1, codes::UNSUPPORTED_OPERAND),
+        (2, codes::UNSUPPORTED_OPERAND),
+        (3, codes::UNSUPPORTED_OPERAND),
+        (4, codes::UNSUPPORTED_OPERAND),
+    ], "{:?}", errors);
+    assert_eq!(errors[2].2, "Unsupported operand types for <: Plain and Plain");
+}
+
+#[test]
+fn test_len_subscripts_membership_and_calls() {
+    let source = "\
+size = len(v1)
+item = v1[0]
+found = 3 in v1
+label = v1(2)
+ordered = v1 < v2
+";
+    let (checker, errors) = check(source);
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(checker.get_type("size"), Some(Type::Int));
+    assert_eq!(checker.get_type("item"), Some(Type::Float));
+    assert_eq!(checker.get_type("found"), Some(Type::Bool));
+    assert_eq!(checker.get_type("label"), Some(Type::Str));
+    assert_eq!(checker.get_type("ordered"), Some(Type::Bool));
+}
+
+#[test]
+fn test_dunder_misuse_is_reported() {
+    let source = "\
+a = len(plain)
+b = v1['first']
+c = plain[0]
+d = 'x' in v1
+e = 1 in plain
+f = v1('big')
+g = plain()
+";
+    let (_, errors) = check(source);
+    let lines: Vec<_> = errors.iter().map(|(line, code, _)| (*line, *code)).collect();
+    assert_eq!(lines, vec![
+        (1, codes::ARG_TYPE),
+        (2, codes::INVALID_SUBSCRIPT),
+        (3, codes::INVALID_SUBSCRIPT),
+        (4, codes::UNSUPPORTED_OPERAND),
+        (5, codes::UNSUPPORTED_OPERAND),
+        (6, codes::ARG_TYPE),
+        (7, codes::NOT_CALLABLE),
+    ], "{:?}", errors);
+    assert_eq!(errors[0].2, "Invalid type for parameter 'obj': expected Sized, found Plain");
+    assert_eq!(errors[6].2, "Type Plain is not callable");
+}
+
+#[test]
+fn test_classes_with_unknown_bases_stay_silent() {
+    let (_, errors) = check("from somewhere import Base\n\nclass Open(Base):\n    pass\n\nopen_ = Open()\nh = open_ + 1\ni = len(open_)\nj = open_[0]\nk = open_()\n");
+    assert!(errors.is_empty(), "{:?}", errors);
+}
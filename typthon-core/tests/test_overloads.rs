@@ -0,0 +1,89 @@
+//! `@overload` signatures: calls resolve to the first overload their
+//! arguments fit, and the implementation has to accept what each one does
+
+mod common;
+
+use typthon::compiler::errors::codes;
+use common::check_with_prelude;
+
+const OVERLOADS: &str = "\
+from typing import overload
+
+@overload
+def f(x: int) -> int: ...
+@overload
+def f(x: str) -> str: ...
+def f(x):
+    return x
+";
+
+fn check(source: &str) -> Vec<(usize, Option<&'static str>, String)> {
+    let (_, errors) = check_with_prelude(OVERLOADS, source);
+    errors.into_iter().map(|e| (e.line, e.code, e.message)).collect()
+}
+
+#[test]
+fn test_call_takes_the_matching_overloads_return_type() {
+    let errors = check("\
+a: int = f(1)
+b: str = f(\"a\")
+c: str = f(1)
+");
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(errors[0].0, 3);
+    assert_eq!(errors[0].1, Some(codes::TYPE_MISMATCH));
+}
+
+#[test]
+fn test_no_matching_overload_lists_every_candidate() {
+    let errors = check("f(1.5)\n");
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(errors[0].1, Some(codes::NO_MATCHING_OVERLOAD));
+    assert_eq!(
+        errors[0].2,
+        "No overload of 'f' matches argument types (float); tried f(x: int) -> int, f(x: str) -> str",
+    );
+}
+
+#[test]
+fn test_method_overloads() {
+    let errors = check("\
+class C:
+    @overload
+    def m(self, x: int) -> int: ...
+    @overload
+    def m(self, x: str, y: int = 0) -> str: ...
+    def m(self, x, y=0):
+        return x
+
+o = C()
+s: str = o.m(\"s\", y=2)
+o.m(b\"x\")
+");
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(errors[0].0, 11);
+    assert!(errors[0].2.ends_with("tried C.m(x: int) -> int, C.m(x: str, y: int = ...) -> str"), "{}", errors[0].2);
+}
+
+#[test]
+fn test_implementation_must_accept_each_overload() {
+    let errors = check("\
+@overload
+def g(x: int) -> int: ...
+@overload
+def g(x: str) -> str: ...
+def g(x: int) -> int:
+    return x
+
+@overload
+def h(x: int) -> int: ...
+@overload
+def h(x: int, y: int) -> int: ...
+def h(x: int, y: int = 0) -> float:
+    return x + y
+");
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(errors[0].0, 5);
+    assert_eq!(errors[0].1, Some(codes::OVERLOAD_IMPLEMENTATION));
+    assert_eq!(errors[0].2, "Implementation of 'g' is incompatible with overload 2: parameter 'x' doesn't accept str");
+}
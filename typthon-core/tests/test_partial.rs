@@ -0,0 +1,97 @@
+use typthon::compiler::types::Effect;
+use typthon::{parse_module, Type, TypeChecker};
+
+fn check(source: &str) -> (TypeChecker, Vec<String>) {
+    let module = parse_module(source).unwrap();
+    let mut checker = TypeChecker::new();
+    let errors = checker.check(&module).iter().map(|e| e.message.clone()).collect();
+    (checker, errors)
+}
+
+#[test]
+fn test_partial_rejects_bad_bound_argument() {
+    let (_, errors) = check(
+        "from functools import partial\n\
+         def f(x: int, y: str) -> int:\n    return x\n\
+         p = partial(f, \"wrong\")\n",
+    );
+
+    assert_eq!(errors, vec!["partial() argument 0 type mismatch: expected int, got str".to_string()]);
+}
+
+#[test]
+fn test_partial_residual_signature() {
+    let (checker, errors) = check(
+        "from functools import partial\n\
+         def f(x: int, y: str) -> int:\n    return x\n\
+         p = partial(f, 1)\n\
+         r = p(\"ok\")\n",
+    );
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    // The residual function keeps the effects of the one it binds
+    assert_eq!(checker.get_type("p"), Some(Type::Function(vec![Type::Str], Box::new(Type::Int)).with_effect(Effect::Pure)));
+    assert_eq!(checker.get_type("r"), Some(Type::Int));
+
+    let (_, errors) = check(
+        "from functools import partial\n\
+         def f(x: int, y: str) -> int:\n    return x\n\
+         p = partial(f, 1)\n\
+         r = p(2)\n",
+    );
+    assert_eq!(errors.len(), 1);
+}
+
+#[test]
+fn test_partial_against_callable() {
+    let (_, errors) = check(
+        "from functools import partial\n\
+         from typing import Callable\n\
+         def f(x: int, y: str) -> int:\n    return x\n\
+         def g(x: int, y: int) -> int:\n    return x\n\
+         def apply(cb: Callable[[str], int]) -> int:\n    return cb(\"a\")\n\
+         ok = apply(partial(f, 1))\n\
+         bad = apply(partial(g, 1))\n",
+    );
+
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+}
+
+#[test]
+fn test_partial_keywords_and_nesting() {
+    let (checker, errors) = check(
+        "import functools\n\
+         def f(x: int, y: str, z: float) -> int:\n    return x\n\
+         p = functools.partial(f, y=\"a\")\n\
+         q = functools.partial(p, 1)\n\
+         bad = functools.partial(f, w=1)\n\
+         worse = functools.partial(f, z=\"s\")\n",
+    );
+
+    assert_eq!(errors, vec![
+        "partial() got an unexpected keyword argument 'w'".to_string(),
+        "partial() keyword argument 'z' type mismatch: expected float, got str".to_string(),
+    ]);
+    assert_eq!(
+        checker.get_type("p"),
+        Some(Type::Function(vec![Type::Int, Type::Float], Box::new(Type::Int)).with_effect(Effect::Pure))
+    );
+    assert_eq!(checker.get_type("q"), Some(Type::Function(vec![Type::Float], Box::new(Type::Int)).with_effect(Effect::Pure)));
+}
+
+#[test]
+fn test_partial_methods() {
+    let (checker, errors) = check(
+        "from functools import partial, partialmethod\n\
+         class C:\n    def scale(self, factor: int, label: str) -> str:\n        return label\n    \
+         double = partialmethod(scale, 2)\n\
+         c: C = C()\n\
+         p = partial(c.scale, 3)\n\
+         a = p(\"x\")\n\
+         b = c.double(\"y\")\n",
+    );
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(checker.get_type("p"), Some(Type::Function(vec![Type::Str], Box::new(Type::Str)).with_effect(Effect::Pure)));
+    assert_eq!(checker.get_type("b"), Some(Type::Str));
+}
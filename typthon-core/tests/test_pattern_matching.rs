@@ -0,0 +1,88 @@
+//! `match` statements: capture patterns, narrowing and exhaustiveness
+
+use typthon::{parse_module, Type, TypeChecker};
+
+const POINT: &str = "\
+class Point:
+    def __init__(self, x: int, y: int) -> None:
+        self.x = x
+        self.y = y
+
+    @property
+    def norm(self) -> float:
+        return 0.0
+";
+
+fn check(source: &str) -> (TypeChecker, Vec<String>) {
+    let mut checker = TypeChecker::new();
+    let errors = checker.check(&parse_module(source).unwrap()).into_iter().map(|e| e.message).collect();
+    (checker, errors)
+}
+
+#[test]
+fn test_captures_are_bound_in_case_bodies() {
+    let (checker, errors) = check(&format!("{}\
+def describe(value: Point | str | list[int] | dict[str, float]) -> None:
+    match value:
+        case Point(norm=size):
+            scaled = size * 2.0
+        case str() as text:
+            loud = text.upper()
+        case [first, *rest]:
+            head = first
+        case {{'key': found, **others}}:
+            entry = found
+",
+        POINT
+    ));
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(checker.local_binding("describe", "size"), Some(Type::Float));
+    assert_eq!(checker.local_binding("describe", "loud"), Some(Type::Str));
+    assert_eq!(checker.local_binding("describe", "head"), Some(Type::Int));
+    assert_eq!(checker.local_binding("describe", "rest"), Some(Type::List(Box::new(Type::Int))));
+    assert_eq!(checker.local_binding("describe", "entry"), Some(Type::Float));
+    assert_eq!(checker.local_binding("describe", "others"), Some(Type::Dict(Box::new(Type::Str), Box::new(Type::Float))));
+}
+
+#[test]
+fn test_class_pattern_that_cannot_match_is_reported() {
+    let (_, errors) = check(&format!(
+        "{}count: int = 3\nmatch count:\n    case Point():\n        pass\n    case int(n) if n > 0:\n        doubled: str = n\n",
+        POINT
+    ));
+
+    assert_eq!(errors[..2], [
+        "Class pattern 'Point' can never match a subject of type int".to_string(),
+        "Type mismatch: cannot assign int to variable 'doubled' of type str".to_string(),
+    ]);
+}
+
+#[test]
+fn test_exhaustive_literal_cases_end_the_function() {
+    let (_, errors) = check("\
+from typing import Literal
+
+def code(mode: Literal['r', 'w'], flag: bool) -> int:
+    match mode:
+        case 'r':
+            return 0
+        case 'w':
+            return 1
+
+def sign(flag: bool) -> int:
+    match flag:
+        case True:
+            return 1
+        case False:
+            return -1
+
+def partial(mode: Literal['r', 'w']) -> int:
+    match mode:
+        case 'r':
+            return 0
+");
+
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert!(errors[0].contains("'partial'"), "{:?}", errors);
+}
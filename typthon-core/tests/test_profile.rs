@@ -0,0 +1,54 @@
+//! `--profile-json` reports where a run's time went, per phase, with file
+//! counts and cache hit rates consistent with what was checked
+
+use std::path::Path;
+use std::process::Command;
+
+/// Profile of checking the `tree` fixture with results kept in `cache`
+fn profile(cache: &Path, out: &Path) -> serde_json::Value {
+    let project = Path::new(env!("CARGO_MANIFEST_DIR")).join("typthon-core/tests/fixtures/cli/tree");
+    let output = Command::new(env!("CARGO_BIN_EXE_typthon"))
+        .args(["--no-color", "--profile-json"])
+        .arg(out)
+        .arg(".")
+        .current_dir(project)
+        .env("TYPTHON_CACHE_DIR", cache)
+        .output()
+        .expect("failed to run typthon binary");
+    // The fixture has type errors; the run still finishes and writes the profile
+    assert_eq!(output.status.code(), Some(1), "{}", String::from_utf8_lossy(&output.stderr));
+    serde_json::from_str(&std::fs::read_to_string(out).unwrap()).unwrap()
+}
+
+fn phase_ns(profile: &serde_json::Value, phase: &str) -> u64 {
+    profile["phases"].as_array().unwrap().iter()
+        .find(|row| row["phase"] == phase)
+        .and_then(|row| row["total_ns"].as_u64())
+        .unwrap_or(0)
+}
+
+#[test]
+fn test_profile_json_counts_files_and_phases() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let cache = dir.path().join("cache");
+
+    let first = profile(&cache, &dir.path().join("first.json"));
+    // pkg/a.py, pkg/b.py and tests/test_x.py; hidden, virtualenv and
+    // __pycache__ files aren't collected
+    assert_eq!(first["files"]["checked"], 3, "{}", first);
+    assert_eq!(first["files"]["cached"], 0);
+    assert!(phase_ns(&first, "parse") > 0, "{}", first);
+    assert!(phase_ns(&first, "statements") > 0, "{}", first);
+    assert!(first["wall_ns"].as_u64().unwrap() > 0);
+    assert_eq!(first["caches"][0]["cache"], "results");
+    assert_eq!(first["caches"][0]["misses"], 3);
+    assert!(first["peaks"]["constraints"].is_u64());
+
+    // Unchanged files come from the cache the second time
+    let second = profile(&cache, &dir.path().join("second.json"));
+    assert_eq!(second["files"]["checked"], 0, "{}", second);
+    assert_eq!(second["files"]["cached"], 3);
+    assert_eq!(second["caches"][0]["hits"], 3);
+    assert_eq!(second["caches"][0]["hit_rate"], 1.0);
+    assert_eq!(phase_ns(&second, "parse"), 0);
+}
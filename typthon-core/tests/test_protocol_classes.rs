@@ -0,0 +1,81 @@
+//! User-defined `Protocol` classes: any class with the protocol's members
+//! is accepted where the protocol is expected
+
+mod common;
+
+use common::check_with_prelude;
+
+const READER: &str = "\
+from typing import Protocol, runtime_checkable
+
+@runtime_checkable
+class Reader(Protocol):
+    def read(self, n: int) -> bytes: ...
+
+def consume(r: Reader) -> None:
+    pass
+";
+
+fn check(source: &str) -> Vec<(usize, String)> {
+    let (_, errors) = check_with_prelude(READER, source);
+    errors.into_iter().map(|e| (e.line, e.message)).collect()
+}
+
+#[test]
+fn test_conforming_class_is_accepted() {
+    let errors = check("\
+class File:
+    def read(self, n: int) -> bytes:
+        return b''
+consume(File())
+r: Reader = File()
+");
+    assert!(errors.is_empty(), "{:?}", errors);
+}
+
+#[test]
+fn test_incompatible_member_is_explained() {
+    let errors = check("\
+class Text:
+    def read(self) -> str:
+        return ''
+consume(Text())
+r: Reader = Text()
+");
+    assert_eq!(errors, vec![
+        (4, "Argument 0 type mismatch: expected Reader, got Text; \
+             'Text' does not satisfy protocol 'Reader': 'read' is () -> str, expected (int) -> bytes".to_string()),
+        (5, "Type mismatch: cannot assign Text to variable 'r' of type Reader; \
+             'Text' does not satisfy protocol 'Reader': 'read' is () -> str, expected (int) -> bytes".to_string()),
+    ]);
+}
+
+#[test]
+fn test_missing_members_are_listed() {
+    let errors = check("\
+class Named(Reader, Protocol):
+    name: str
+
+class Empty:
+    pass
+
+def label(n: Named) -> None:
+    pass
+label(Empty())
+");
+    assert_eq!(errors, vec![
+        (9, "Argument 0 type mismatch: expected Named, got Empty; \
+             'Empty' does not satisfy protocol 'Named': missing 'name', missing 'read'".to_string()),
+    ]);
+}
+
+#[test]
+fn test_subclassing_a_protocol_is_nominal() {
+    let errors = check("\
+class Explicit(Reader):
+    def read(self, n: int) -> bytes:
+        return b''
+consume(Explicit())
+");
+    assert!(errors.is_empty(), "{:?}", errors);
+}
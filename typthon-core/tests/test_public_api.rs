@@ -0,0 +1,85 @@
+//! The supported embedding API in `typthon::api`
+//!
+//! `test_public_api_is_unchanged` compares the items `api.rs` exports with
+//! `tests/golden/public_api.txt`, so a change to the surface has to update the
+//! snapshot in the same commit. Set `UPDATE_GOLDEN=1` to rewrite it after an
+//! intentional change, and bump the version accordingly.
+
+use std::fs;
+use std::path::Path;
+use typthon::api::{Config, Diagnostic, Session, Severity, Type};
+
+fn golden_path() -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("typthon-core/tests/golden/public_api.txt")
+}
+
+/// Top-level `pub` items and trait impls, with their `pub` members (and enum
+/// variants) indented below them
+fn exported_items(source: &str) -> String {
+    let mut items = String::new();
+    let mut in_enum = false;
+    for line in source.lines() {
+        let signature = line.trim_end().trim_end_matches('{').trim_end();
+        if !line.starts_with(' ') {
+            in_enum = line.starts_with("pub enum");
+            if line.starts_with("pub ") || line.starts_with("impl") {
+                items.push_str(&format!("{}\n", signature));
+            }
+        } else if line.starts_with("    ") && !line.starts_with("     ") {
+            let member = signature.trim_start();
+            let variant = in_enum && !member.is_empty() && !member.starts_with("//") && !member.starts_with('#');
+            if member.starts_with("pub ") || variant {
+                items.push_str(&format!("    {}\n", member));
+            }
+        }
+    }
+    items
+}
+
+#[test]
+fn test_public_api_is_unchanged() {
+    let source = fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("typthon-core/api.rs")).unwrap();
+    let actual = exported_items(&source);
+
+    let path = golden_path();
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, &actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("missing snapshot {}; run with UPDATE_GOLDEN=1", path.display()));
+    assert_eq!(expected, actual, "typthon::api changed; rerun with UPDATE_GOLDEN=1 if intended");
+}
+
+#[test]
+fn test_session_reports_errors_and_lints() {
+    let config = Config::parse("[lints]\nC001 = \"warn\"\n").unwrap();
+    let mut session = Session::new(config, ".").unwrap();
+    let diagnostics = session.check_source("shapes.py", "ratio: float = 0.1 + 0.2\nok = ratio == 0.3\nname: str = 'a'\ncount: int = name\n");
+
+    assert_eq!(diagnostics[0], Diagnostic {
+        file: "shapes.py".to_string(),
+        line: 4,
+        col: 13,
+        severity: Severity::Error,
+        code: Some("TYP001".to_string()),
+        message: "Type mismatch: cannot assign str to variable 'count' of type int".to_string(),
+    });
+    let lint = diagnostics.last().unwrap();
+    assert_eq!((lint.line, lint.col, lint.severity), (2, 5, Severity::Warning));
+    assert_eq!(lint.code.as_deref(), Some("C001"));
+    assert!(lint.to_string().starts_with("shapes.py:2:5: warning[C001]: "), "{}", lint);
+
+    assert_eq!(session.type_of("ratio"), Some(Type::Float));
+    assert_eq!(session.parse_type("list[int]"), Ok(Type::List(Box::new(Type::Int))));
+}
+
+#[test]
+fn test_unparsable_source_is_one_diagnostic() {
+    let mut session = Session::new(Config::default(), ".").unwrap();
+    let diagnostics = session.check_source("broken.py", "def f(:\n");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+    assert!(diagnostics[0].message.starts_with("Parse error"), "{}", diagnostics[0]);
+}
@@ -0,0 +1,73 @@
+//! Re-checking one edited function against the last full check of a module
+//!
+//! Whatever `recheck_function` reports must match checking the edited module
+//! from scratch; when it can't guarantee that, it declines.
+
+use std::sync::Arc;
+use rustpython_parser::ast::Mod;
+use typthon::compiler::analysis::checker::{phases, TypeError};
+use typthon::compiler::analysis::{diff_items, fingerprint_items, ItemChange, ItemFingerprint};
+use typthon::{parse_module, PerformanceMetrics, TypeChecker};
+
+const BEFORE: &str = "\
+def label(count: int) -> str:
+    text = 'items'
+    return text
+
+def total(values: list[int]) -> int:
+    return len(values)
+
+name: str = label(1)
+size: int = total([1, 2])
+wrong: int = label(2)
+";
+
+fn messages(errors: Vec<TypeError>) -> Vec<String> {
+    errors.into_iter().map(|e| e.message).collect()
+}
+
+fn items(source: &str, module: &Mod) -> Vec<ItemFingerprint> {
+    let Mod::Module(ast) = module else { unreachable!() };
+    fingerprint_items(source, &ast.body)
+}
+
+/// Check `BEFORE`, then re-check `after`'s only edited function
+fn recheck(after: &str) -> (Option<Vec<String>>, Arc<PerformanceMetrics>) {
+    let (old, new) = (parse_module(BEFORE).unwrap(), parse_module(after).unwrap());
+    let ItemChange::FunctionBody(index) = diff_items(&items(BEFORE, &old), &items(after, &new)) else {
+        panic!("expected a body-only edit:\n{}", after);
+    };
+    let mut checker = TypeChecker::new().with_item_records();
+    checker.check(&old);
+    let errors = checker.recheck_function(&new, index).map(messages);
+    (errors, checker.metrics().clone())
+}
+
+#[test]
+fn test_recheck_matches_full_check() {
+    for after in [
+        BEFORE.replace("'items'", "'a much longer label'"),
+        BEFORE.replace("return text", "return len(text)"),
+        BEFORE.replace("return len(values)", "if values:\n        return values[0]\n    return 0"),
+        // Locals are the function's own, so retyping one changes nothing outside it
+        BEFORE.replace("    text = 'items'\n", "    text: int = 'items'\n"),
+    ] {
+        let (rechecked, metrics) = recheck(&after);
+        let fresh = messages(TypeChecker::new().check(&parse_module(&after).unwrap()));
+
+        assert_eq!(rechecked, Some(fresh), "{}", after);
+        let timed: Vec<_> = metrics.phase_breakdown().phases.into_iter().map(|row| row.key.phase).collect();
+        assert!(timed.iter().any(|phase| phase == phases::RECHECK), "{:?}", timed);
+    }
+}
+
+#[test]
+fn test_recheck_declines_edits_that_leave_the_function() {
+    // Writes a module binding, and gains an effect callers inherit
+    for after in [
+        BEFORE.replace("    text = 'items'\n", "    global size\n    size = 'x'\n    text = 'items'\n"),
+        BEFORE.replace("    text = 'items'\n", "    text = 'items'\n    print(text)\n"),
+    ] {
+        assert_eq!(recheck(&after).0, None, "{}", after);
+    }
+}
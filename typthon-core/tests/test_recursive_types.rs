@@ -0,0 +1,100 @@
+//! Recursive types: equi-recursive subtyping, which unfolds them as far as
+//! needed and doesn't care what their binders are called
+
+use typthon::compiler::analysis::TypeChecker;
+use typthon::compiler::errors::codes;
+use typthon::compiler::types::Type;
+use typthon::parse_module;
+
+/// Lines, codes and messages of the errors from checking `source`
+fn check(source: &str) -> Vec<(usize, Option<&'static str>, String)> {
+    let mut checker = TypeChecker::new();
+    checker.set_source(source);
+    checker.check(&parse_module(source).unwrap()).into_iter()
+        .map(|e| (e.line, e.code, e.message))
+        .collect()
+}
+
+/// `rec name. body`
+fn rec(name: &str, body: Type) -> Type {
+    Type::Recursive(name.to_string(), Box::new(body))
+}
+
+fn var(name: &str) -> Type {
+    Type::Class(name.to_string())
+}
+
+fn list(elem: Type) -> Type {
+    Type::List(Box::new(elem))
+}
+
+#[test]
+fn test_json_accepts_nested_literals() {
+    let errors = check("\
+JSON = None | bool | int | str | list[\"JSON\"] | dict[str, \"JSON\"]
+
+def dump(doc: JSON) -> str:
+    return \"\"
+
+dump({\"name\": \"x\", \"tags\": [\"a\", \"b\"], \"meta\": {\"depth\": [1, [2, None]], \"ok\": True}})
+doc: JSON = [1, {\"a\": [True, None, \"s\"]}]
+dump(doc)
+");
+    assert!(errors.is_empty(), "{:?}", errors);
+}
+
+#[test]
+fn test_json_rejects_a_set_inside() {
+    let errors = check("\
+JSON = None | bool | int | str | list[\"JSON\"] | dict[str, \"JSON\"]
+
+def dump(doc: JSON) -> str:
+    return \"\"
+
+dump({\"name\": \"x\", \"tags\": {1, 2}})
+doc: JSON = [1, [{\"a\": {3}}]]
+");
+    let lines: Vec<usize> = errors.iter().map(|e| e.0).collect();
+    assert_eq!(lines, vec![6, 7], "{:?}", errors);
+    assert_eq!(errors[0].1, Some(codes::ARG_TYPE));
+}
+
+#[test]
+fn test_binder_names_dont_matter() {
+    let x = rec("X", list(var("X")));
+    let y = rec("Y", list(var("Y")));
+    assert!(x.is_subtype(&y));
+    assert!(y.is_subtype(&x));
+
+    let ints = rec("X", Type::Union(vec![Type::Int, list(var("X"))]));
+    let more = rec("Y", Type::Union(vec![Type::Int, Type::Str, list(var("Y"))]));
+    assert!(!ints.is_subtype(&more), "list elements are invariant");
+    let tuples = rec("X", Type::Union(vec![Type::Int, Type::VarTuple(Box::new(var("X")))]));
+    let wider = rec("Y", Type::Union(vec![Type::Int, Type::Str, Type::VarTuple(Box::new(var("Y")))]));
+    assert!(tuples.is_subtype(&wider));
+    assert!(!wider.is_subtype(&tuples));
+}
+
+#[test]
+fn test_unfolding_matches_concrete_structures() {
+    let tree = rec("T", Type::Union(vec![Type::Int, list(var("T"))]));
+    // One level unfolded by hand is the same type
+    let unfolded = Type::Union(vec![Type::Int, list(tree.clone())]);
+    assert!(unfolded.is_subtype(&tree));
+    assert!(tree.is_subtype(&unfolded));
+    assert!(list(tree.clone()).is_subtype(&tree));
+    assert!(!list(Type::Str).is_subtype(&tree));
+}
+
+#[test]
+fn test_aliases_with_different_names_are_compatible() {
+    let errors = check("\
+Tree = int | list[\"Tree\"]
+Forest = int | list[\"Forest\"]
+
+def grow(t: Tree) -> Forest:
+    return t
+");
+    assert!(errors.is_empty(), "{:?}", errors);
+}
+
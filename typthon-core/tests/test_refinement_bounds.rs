@@ -0,0 +1,95 @@
+//! Bounds of refined ints: values are checked against `Positive`, `Negative`
+//! and `Bounded(...)` as they're assigned or passed, arithmetic tracks the
+//! interval a result may fall in, and comparisons narrow it
+
+use typthon::compiler::analysis::RefinementAnalyzer;
+use typthon::compiler::errors::codes;
+use typthon::{parse_module, Type, TypeChecker};
+
+fn check(source: &str) -> (TypeChecker, Vec<(usize, &'static str, String)>) {
+    let module = parse_module(source).unwrap();
+    let mut checker = TypeChecker::new();
+    checker.set_source(source);
+    let errors = checker.check(&module).iter()
+        .map(|e| (e.line, e.code.unwrap_or(""), e.message.clone()))
+        .collect();
+    (checker, errors)
+}
+
+#[test]
+fn test_negative_literal_passed_as_positive() {
+    let (_, errors) = check(
+        "def scale(factor: Positive) -> int:\n    return factor\n\
+         scale(2)\n\
+         scale(-1)\n\
+         scale(factor=0)\n",
+    );
+
+    assert_eq!(errors, vec![
+        (4, codes::CONSTRAINT_VIOLATION, "Constraint 'value > 0' violated by value: -1".to_string()),
+        (5, codes::CONSTRAINT_VIOLATION, "Constraint 'value > 0' violated by value: 0".to_string()),
+    ]);
+}
+
+#[test]
+fn test_arithmetic_shifts_bounds() {
+    let (checker, errors) = check(
+        "level: Bounded(0, 10) = 3\n\
+         shifted = level + 5\n\
+         scaled = level * -2\n\
+         fits: Bounded(0, 20) = level + 5\n\
+         overflows: Bounded(0, 10) = level + 5\n\
+         level = 11\n",
+    );
+
+    assert_eq!(checker.get_type("shifted"), Some(RefinementAnalyzer::bounded_int(5, 15)));
+    assert_eq!(checker.get_type("scaled"), Some(RefinementAnalyzer::bounded_int(-20, 0)));
+    assert_eq!(checker.get_type("fits"), Some(RefinementAnalyzer::bounded_int(0, 20)));
+    assert_eq!(errors, vec![
+        (5, codes::CONSTRAINT_VIOLATION, "Constraint '(value >= 0 && value <= 10)' violated by value: [5, 15]".to_string()),
+        (6, codes::CONSTRAINT_VIOLATION, "Constraint '(value >= 0 && value <= 10)' violated by value: 11".to_string()),
+    ]);
+}
+
+#[test]
+fn test_bounded_counter_in_loop() {
+    let (_, errors) = check(
+        "count: Bounded(0, 100) = 0\n\
+         for _ in range(200):\n\
+         \x20   if count < 100:\n\
+         \x20       count += 1\n\
+         \x20   count += 1\n",
+    );
+
+    // Below 100 the increment stays in bounds; unguarded it may reach 101
+    assert_eq!(errors, vec![
+        (5, codes::CONSTRAINT_VIOLATION, "Constraint '(value >= 0 && value <= 100)' violated by value: [1, 101]".to_string()),
+    ]);
+}
+
+#[test]
+fn test_comparison_narrows_each_branch() {
+    let (checker, errors) = check(
+        "def clamp(x: Bounded(0, 100)) -> int:\n\
+         \x20   if x < 50:\n\
+         \x20       low = x\n\
+         \x20   else:\n\
+         \x20       high = x\n\
+         \x20   return x\n",
+    );
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(checker.local_binding("clamp", "low"), Some(RefinementAnalyzer::bounded_int(0, 49)));
+    assert_eq!(checker.local_binding("clamp", "high"), Some(RefinementAnalyzer::bounded_int(50, 100)));
+}
+
+#[test]
+fn test_plain_ints_are_unchecked() {
+    let (checker, errors) = check(
+        "def scale(factor: Positive) -> int:\n    return factor\n\
+         def run(n: int) -> int:\n    return scale(n) + n\n",
+    );
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert!(matches!(checker.get_type("run"), Some(Type::Effect(..) | Type::Function(..))));
+}
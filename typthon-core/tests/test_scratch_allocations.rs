@@ -0,0 +1,97 @@
+//! Checkers reusing pooled scratch space allocate less and report the same
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use typthon::compiler::analysis::TypeChecker;
+use typthon::parse_module;
+
+/// Counts the allocations of the thread that's counting
+struct Counting;
+
+thread_local! {
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    static BYTES: Cell<usize> = const { Cell::new(0) };
+}
+
+fn record(size: usize) {
+    if COUNTING.try_with(Cell::get).unwrap_or(false) {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        let _ = BYTES.try_with(|bytes| bytes.set(bytes.get() + size));
+    }
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// `f`, with how many allocations it made and how many bytes they took
+fn counted<T>(f: impl FnOnce() -> T) -> (T, (usize, usize)) {
+    ALLOCATIONS.with(|count| count.set(0));
+    BYTES.with(|bytes| bytes.set(0));
+    COUNTING.with(|counting| counting.set(true));
+    let value = f();
+    COUNTING.with(|counting| counting.set(false));
+    (value, (ALLOCATIONS.with(Cell::get), BYTES.with(Cell::get)))
+}
+
+/// About 10k lines of classes, functions and module-level calls, with an
+/// error every hundred blocks
+fn synthetic_module() -> String {
+    let mut source = String::new();
+    for i in 0..910 {
+        let target = if i % 100 == 0 { "str" } else { "int" };
+        source.push_str(&format!("\
+class Shape{i}:
+    def __init__(self, side: int) -> None:
+        self.side = side
+
+    def area(self) -> int:
+        return self.side * self.side
+
+
+def grow{i}(shape: Shape{i}, by: int) -> int:
+    return shape.area() + by
+total{i}: {target} = grow{i}(Shape{i}({i}), 2)
+"));
+    }
+    source
+}
+
+#[test]
+fn test_pooled_scratch_allocates_less() {
+    let source = synthetic_module();
+    assert!(source.lines().count() >= 10_000);
+    let module = parse_module(&source).unwrap();
+    let check = |checker: TypeChecker| {
+        let mut checker = checker;
+        checker.set_source(&source);
+        let messages: Vec<String> = checker.check(&module).iter().map(ToString::to_string).collect();
+        (messages, checker)
+    };
+
+    let ((fresh, first), grown) = counted(|| check(TypeChecker::new()));
+    let scratch = first.into_scratch();
+    let ((reused, _), pooled) = counted(|| check(TypeChecker::new().with_scratch(scratch)));
+
+    assert_eq!(reused, fresh);
+    assert_eq!(fresh.iter().filter(|message| message.contains("cannot assign")).count(), 10);
+    // Fewer allocations, and less space for them as the collections don't grow
+    let message = format!("{:?} allocations and bytes with pooled scratch, {:?} without", pooled, grown);
+    assert!(pooled.0 < grown.0 && pooled.1 < grown.1, "{}", message);
+}
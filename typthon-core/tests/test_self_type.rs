@@ -0,0 +1,93 @@
+//! The receiver of a method: `self` is an instance of the enclosing class,
+//! `cls` its class object, and attributes reached through `self` come from
+//! the whole class, whichever method declares them
+
+use typthon::compiler::analysis::checker::TypeError;
+use typthon::compiler::analysis::TypeChecker;
+use typthon::compiler::errors::codes;
+use typthon::parse_module;
+
+fn check(source: &str) -> Vec<TypeError> {
+    let mut checker = TypeChecker::new();
+    checker.set_source(source);
+    checker.check(&parse_module(source).unwrap()).into_iter().collect()
+}
+
+#[test]
+fn test_method_calls_through_self_are_checked() {
+    let errors = check("\
+class Counter:
+    def __init__(self, start: int) -> None:
+        self.count = start
+
+    def bump(self, by: int) -> int:
+        return self.scaled(\"x\") + self.scaled(by)
+
+    def scaled(self, factor: int) -> int:
+        return self.count * factor
+");
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!((errors[0].line, errors[0].code), (6, Some(codes::ARG_TYPE)));
+}
+
+#[test]
+fn test_undefined_attribute_on_self() {
+    let errors = check("\
+class Counter:
+    label: str
+
+    def total(self) -> int:
+        return self.cont + len(self.label) + self.offset
+
+    def __init__(self, start: int) -> None:
+        self.count = start
+
+    def reset(self) -> None:
+        self.offset = 0
+");
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!((errors[0].line, errors[0].code), (5, Some(codes::INVALID_ATTRIBUTE)));
+    assert_eq!(errors[0].message, "Type 'Counter' has no attribute 'cont'. Did you mean: count?");
+    assert_eq!(errors[0].suggestions, vec!["count"]);
+}
+
+#[test]
+fn test_receivers_of_class_and_static_methods() {
+    let errors = check("\
+class Counter:
+    def __init__(self, start: int) -> None:
+        self.count = start
+
+    @classmethod
+    def zero(cls) -> \"Counter\":
+        return cls(0)
+
+    @staticmethod
+    def double(x: int) -> int:
+        return x * 2
+
+c: Counter = Counter.zero()
+n: int = Counter.double(c.count)
+");
+    assert!(errors.is_empty(), "{:?}", errors);
+}
+
+#[test]
+fn test_inherited_and_unknown_attributes() {
+    let errors = check("\
+from somewhere import Base
+
+class Shape:
+    def __init__(self, name: str) -> None:
+        self.name = name
+
+class Square(Shape):
+    def describe(self) -> str:
+        return self.name.upper()
+
+class Plugin(Base):
+    def run(self) -> None:
+        self.configure()
+");
+    assert!(errors.is_empty(), "{:?}", errors);
+}
@@ -0,0 +1,63 @@
+//! Session signature overrides: diagnostics, provenance notes and caching
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use tempfile::TempDir;
+use typthon::compiler::types::{OverrideOrigin, Type};
+use typthon::infrastructure::incremental::ModuleId;
+use typthon::infrastructure::parallel::{AnalysisTask, ParallelAnalyzer};
+use typthon::{DependencyGraph, IncrementalEngine, ResultCache, TypeChecker, TypeContext, parse_module};
+
+const SOURCE: &str = "def first_dot(s: str) -> int:\n    return s.find(\".\")\n";
+
+fn messages(analyzer: &ParallelAnalyzer) -> Vec<String> {
+    let task = AnalysisTask {
+        id: ModuleId::new(1),
+        path: PathBuf::from("main.py"),
+        content: SOURCE.to_string(),
+    };
+    analyzer.analyze_modules(vec![task]).into_iter()
+        .flat_map(|result| result.errors)
+        .map(|error| error.kind.to_string())
+        .collect()
+}
+
+#[test]
+fn test_override_changes_diagnostics_and_cache_key() {
+    let temp = TempDir::new().unwrap();
+    let ctx = Arc::new(TypeContext::new());
+    let cache = Arc::new(ResultCache::new(temp.path().to_path_buf(), 100).unwrap());
+    let incremental = Arc::new(IncrementalEngine::new(Arc::new(DependencyGraph::new())));
+    let analyzer = ParallelAnalyzer::new(ctx.clone(), cache.clone(), incremental, 1);
+
+    assert!(messages(&analyzer).is_empty());
+    assert_eq!(cache.stats().misses, 1);
+
+    // str.find now returns Optional[int]
+    let optional_int = Type::Union(vec![Type::Int, Type::None]);
+    ctx.override_member("str", "find", Type::Function(vec![Type::Str], Box::new(optional_int)));
+
+    let overridden = messages(&analyzer);
+    assert_eq!(overridden.len(), 1, "{:?}", overridden);
+    assert!(overridden[0].contains("Return type mismatch"), "{}", overridden[0]);
+    assert!(overridden[0].contains("note: signature overridden for this session (str.find)"), "{}", overridden[0]);
+    // A different override set is a different cache entry, not a stale hit
+    assert_eq!(cache.stats().misses, 2);
+
+    ctx.overrides().clear();
+    assert!(messages(&analyzer).is_empty());
+    assert_eq!(cache.stats().hits, 1);
+}
+
+#[test]
+fn test_override_from_annotation_string() {
+    let module = parse_module(SOURCE).unwrap();
+    let mut checker = TypeChecker::new();
+    checker.override_signature("builtins.str.find", "Callable[[str], Optional[int]]", OverrideOrigin::Config).unwrap();
+
+    let errors = checker.check(&module);
+    assert!(errors.iter().any(|e| e.message.ends_with("note: signature overridden by project configuration (str.find)")));
+
+    assert!(checker.override_signature("find", "int", OverrideOrigin::Config).is_err());
+    assert!(checker.override_signature("str.find", "Callable[[", OverrideOrigin::Config).is_err());
+}
@@ -0,0 +1,115 @@
+//! Timing attribution behind `--stats`
+//!
+//! A slow phase or plugin is simulated with the injectable delay hook and must
+//! dominate the reported breakdown.
+
+use std::process::Command;
+use std::sync::Arc;
+use std::time::Duration;
+use rustpython_parser::ast::Mod;
+use typthon::compiler::analysis::checker::{phases, TypeError};
+use typthon::compiler::analysis::CheckerPlugin;
+use typthon::{parse_module, PerformanceMetrics, TypeChecker, TypeContext};
+
+const SOURCE: &str = "\
+class Point:\n    def __init__(self, x: int):\n        self.x = x\n\
+p = Point(1)\n\
+name = 'typthon'.upper()\n\
+if isinstance(p, Point):\n    y = p.x\n";
+
+const DELAY: Duration = Duration::from_millis(50);
+
+fn check(mut checker: TypeChecker) -> Arc<PerformanceMetrics> {
+    checker.set_source_name("slow.py");
+    let module = parse_module(SOURCE).unwrap();
+    checker.check(&module);
+    checker.metrics().clone()
+}
+
+/// Plugin that sleeps for `DELAY` and reports nothing
+struct SlowPlugin;
+
+impl CheckerPlugin for SlowPlugin {
+    fn name(&self) -> &str {
+        "slow-plugin"
+    }
+
+    fn check_module(&mut self, _module: &Mod, _ctx: &TypeContext) -> Vec<TypeError> {
+        std::thread::sleep(DELAY);
+        Vec::new()
+    }
+}
+
+#[test]
+fn test_slow_phase_dominates_breakdown() {
+    let mut checker = TypeChecker::new();
+    checker.inject_phase_delay(phases::CONSTRAINTS, DELAY);
+    let breakdown = check(checker).phase_breakdown();
+
+    let dominant = breakdown.dominant_phase().unwrap();
+    assert_eq!(dominant.key.phase, phases::CONSTRAINTS);
+    assert!(dominant.total >= DELAY);
+    assert!(dominant.percent > 50.0, "{}", breakdown.report(5));
+
+    // Phase level is always collected, rules only in detailed mode
+    for phase in [phases::EFFECTS, phases::STATEMENTS] {
+        assert!(breakdown.phases.iter().any(|row| row.key.phase == phase && row.key.rule.is_none()));
+    }
+    assert!(breakdown.phases.iter().all(|row| row.key.rule.is_none()));
+
+    assert_eq!(breakdown.files.len(), 1);
+    assert_eq!(breakdown.files[0].file, "slow.py");
+    assert_eq!(breakdown.files[0].top[0].key.phase, phases::CONSTRAINTS);
+}
+
+#[test]
+fn test_detailed_stats_time_rules() {
+    let metrics = Arc::new(PerformanceMetrics::new());
+    let checker = TypeChecker::new().with_metrics(metrics.clone(), true);
+    check(checker);
+
+    let rules: Vec<String> = metrics.phase_breakdown().phases.iter()
+        .filter_map(|row| row.key.rule.clone())
+        .collect();
+    assert!(rules.contains(&"attributes".to_string()), "{:?}", rules);
+    assert!(rules.contains(&"narrowing".to_string()), "{:?}", rules);
+}
+
+#[test]
+fn test_plugin_hooks_timed_under_plugin_name() {
+    let mut checker = TypeChecker::new();
+    checker.register_plugin(Box::new(SlowPlugin));
+    let breakdown = check(checker).phase_breakdown();
+
+    assert_eq!(breakdown.dominant_phase().unwrap().key.phase, phases::PLUGINS);
+    let plugin = breakdown.phases.iter()
+        .find(|row| row.key.rule.as_deref() == Some("slow-plugin"))
+        .expect("plugin row is recorded without --stats-detailed");
+    assert_eq!(plugin.key.phase, phases::PLUGINS);
+    assert!(plugin.total >= DELAY);
+}
+
+#[test]
+fn test_cli_json_stats() {
+    let fixture = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("typthon-core/tests/fixtures/cli/clean");
+    let output = Command::new(env!("CARGO_BIN_EXE_typthon"))
+        .args(["--stats-detailed", "--no-cache", "--output-format", "json", "main.py"])
+        .current_dir(fixture)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(document["diagnostics"], serde_json::json!([]));
+
+    let stats = &document["stats"];
+    assert!(stats["total_ns"].as_u64().unwrap() > 0);
+    let phases: Vec<&str> = stats["phases"].as_array().unwrap().iter()
+        .filter(|row| row["rule"].is_null())
+        .map(|row| row["phase"].as_str().unwrap())
+        .collect();
+    for phase in ["parse", "effects", "statements", "constraints"] {
+        assert!(phases.contains(&phase), "{:?}", phases);
+    }
+    assert_eq!(stats["files"][0]["file"], "main.py");
+}
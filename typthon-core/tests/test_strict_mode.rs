@@ -0,0 +1,73 @@
+//! Strict mode and the individual `disallow_*` checks it turns on
+
+use typthon::compiler::analysis::{CheckerOptions, LintLevel};
+use typthon::{parse_module, TypeChecker};
+
+const SOURCE: &str = "\
+from typing import Any
+cache = []
+seen = set()
+def load(path):
+    return path
+def parse(text: str) -> int:
+    raw = load(text)
+    return raw
+def explicit(value: Any) -> Any:
+    copy = value
+    return copy
+";
+
+fn check(options: CheckerOptions) -> Vec<(usize, String)> {
+    let mut checker = TypeChecker::new_with_options(options);
+    checker.set_source(SOURCE);
+    checker.check(&parse_module(SOURCE).unwrap()).into_iter().map(|e| (e.line, e.message)).collect()
+}
+
+fn lint_codes(options: CheckerOptions) -> Vec<(&'static str, LintLevel)> {
+    let module = parse_module(SOURCE).unwrap();
+    let mut checker = TypeChecker::new_with_options(options);
+    checker.check(&module);
+    checker.lint(&module).into_iter().map(|lint| (lint.code, lint.level)).collect()
+}
+
+#[test]
+fn test_default_options_report_nothing() {
+    assert!(check(CheckerOptions::default()).is_empty());
+    assert!(lint_codes(CheckerOptions::default()).is_empty());
+}
+
+#[test]
+fn test_strict_reports_untyped_defs_and_implicit_any() {
+    let errors = check(CheckerOptions { strict: true, ..CheckerOptions::default() });
+    assert_eq!(errors, vec![
+        (4, "Function 'load' is missing a type annotation for parameter 'path'".to_string()),
+        (4, "Function 'load' is missing a return type annotation".to_string()),
+        (7, "Variable 'raw' has an implicit Any type; annotate it or the value it comes from".to_string()),
+        (8, "Returning an implicit Any from a function declared to return int".to_string()),
+    ]);
+}
+
+#[test]
+fn test_individual_checks_without_strict() {
+    let untyped = check(CheckerOptions { disallow_untyped_defs: true, ..CheckerOptions::default() });
+    assert_eq!(untyped.iter().map(|(line, _)| *line).collect::<Vec<_>>(), vec![4, 4]);
+
+    let any = check(CheckerOptions { disallow_any: true, ..CheckerOptions::default() });
+    assert_eq!(any.iter().map(|(line, _)| *line).collect::<Vec<_>>(), vec![7, 8]);
+
+    let exempt = check(CheckerOptions { strict: true, allow_untyped_defs: true, ..CheckerOptions::default() });
+    assert_eq!(exempt.iter().map(|(line, _)| *line).collect::<Vec<_>>(), vec![7, 8]);
+}
+
+#[test]
+fn test_strict_warns_about_empty_module_containers() {
+    let strict = CheckerOptions { strict: true, ..CheckerOptions::default() };
+    assert_eq!(lint_codes(strict.clone()), vec![("S003", LintLevel::Warn), ("S003", LintLevel::Warn)]);
+
+    // Configured levels win over the strict default
+    let silenced = CheckerOptions {
+        lints: [("S003".to_string(), LintLevel::Off)].into_iter().collect(),
+        ..strict
+    };
+    assert!(lint_codes(silenced).is_empty());
+}
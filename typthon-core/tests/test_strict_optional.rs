@@ -0,0 +1,77 @@
+//! `strict_optional`: operations on values that may be `None`
+
+use typthon::compiler::analysis::CheckerOptions;
+use typthon::compiler::errors::codes;
+use typthon::{parse_module, TypeChecker};
+
+/// Lines and messages of the possibly-None errors in `source`
+fn possibly_none(source: &str, options: CheckerOptions) -> Vec<(usize, String)> {
+    let mut checker = TypeChecker::new_with_options(options);
+    checker.set_source(source);
+    checker.check(&parse_module(source).unwrap()).into_iter()
+        .filter(|e| e.code == Some(codes::POSSIBLY_NONE))
+        .map(|e| (e.line, e.message))
+        .collect()
+}
+
+fn strict_optional() -> CheckerOptions {
+    CheckerOptions { strict_optional: true, ..CheckerOptions::default() }
+}
+
+#[test]
+fn test_operations_on_optional_values() {
+    let source = "\
+from typing import Optional
+def f(x: Optional[int], s: str | None, cb: Optional[int]) -> None:
+    y = x + 1
+    s.upper()
+    s[0]
+    cb()
+";
+    let errors = possibly_none(source, strict_optional());
+    assert_eq!(errors, vec![
+        (3, "'x' is possibly None: operator '+' is not supported on None".to_string()),
+        (4, "'s' is possibly None: attribute 'upper' is not supported on None".to_string()),
+        (5, "'s' is possibly None: subscripting is not supported on None".to_string()),
+        (6, "'cb' is possibly None: calling is not supported on None".to_string()),
+    ]);
+    assert!(possibly_none(source, CheckerOptions::default()).is_empty());
+    assert_eq!(possibly_none(source, CheckerOptions { strict: true, ..CheckerOptions::default() }).len(), 4);
+}
+
+#[test]
+fn test_narrowing_by_condition_and_early_return() {
+    let source = "\
+def f(x: int | None, s: str | None) -> None:
+    if x is not None:
+        y = x + 1
+    if s:
+        s.upper()
+    if x is None:
+        return
+    z = x + 1
+";
+    assert!(possibly_none(source, strict_optional()).is_empty());
+}
+
+#[test]
+fn test_narrowing_by_assert() {
+    let source = "\
+def f(x: int | None) -> int:
+    assert x is not None
+    return x + 1
+";
+    assert!(possibly_none(source, strict_optional()).is_empty());
+}
+
+#[test]
+fn test_narrowing_by_walrus() {
+    let source = "\
+def f(d: dict[str, int]) -> None:
+    if (found := d.get('a')) is not None:
+        y = found + 1
+    z = found + 1
+";
+    let lines: Vec<usize> = possibly_none(source, strict_optional()).into_iter().map(|(line, _)| line).collect();
+    assert_eq!(lines, vec![4]);
+}
@@ -0,0 +1,84 @@
+//! f-strings, `str.format` and `%` formatting: placeholders against the
+//! arguments that fill them
+
+use typthon::compiler::analysis::TypeChecker;
+use typthon::compiler::errors::codes;
+use typthon::parse_module;
+
+/// Line, code and message of each error from checking `source`
+fn check(source: &str) -> Vec<(usize, &'static str, String)> {
+    let mut checker = TypeChecker::new();
+    checker.set_source(source);
+    checker.check(&parse_module(source).unwrap()).into_iter()
+        .map(|e| (e.line, e.code.unwrap_or_default(), e.message))
+        .collect()
+}
+
+#[test]
+fn test_fstring_values_are_checked() {
+    let errors = check("name = 'x'\ngreeting = f'hello {nme}'\n");
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!((errors[0].0, errors[0].1), (2, codes::UNDEFINED_VARIABLE));
+}
+
+#[test]
+fn test_fstring_specs_fit_their_values() {
+    let source = "\
+name = 'x'
+count = 3
+a = f'{count:>5d} {3.5:.2f} {name:>10}'
+b = f'{name:d}'
+c = f'{count!r:d}'
+d = f'{count:{name}}'
+";
+    let errors = check(source);
+    let lines: Vec<_> = errors.iter().map(|(line, code, _)| (*line, *code)).collect();
+    assert_eq!(lines, vec![(4, codes::STRING_FORMAT), (5, codes::STRING_FORMAT)], "{:?}", errors);
+    assert_eq!(errors[0].2, "Format code 'd' is not valid for a value of type str");
+}
+
+#[test]
+fn test_format_calls_match_placeholder_counts() {
+    let source = "\
+a = '{} {}'.format(1)
+b = '{0} {1} {0}'.format(1, 2)
+c = '{name}: {}'.format(1, name='x')
+d = '{} {}'.format(*[1])
+e = '{greeting}'.format(name='x')
+f = '{} {0}'.format(1)
+";
+    let errors = check(source);
+    let lines: Vec<_> = errors.iter().map(|(line, code, _)| (*line, *code)).collect();
+    assert_eq!(lines, vec![(1, codes::STRING_FORMAT), (5, codes::STRING_FORMAT), (6, codes::STRING_FORMAT)], "{:?}", errors);
+    assert_eq!(errors[0].2, "Format string expects 2 positional argument(s), found 1");
+    assert_eq!(errors[1].2, "Format string refers to missing keyword argument 'greeting'");
+}
+
+#[test]
+fn test_format_call_specs_fit_their_arguments() {
+    let errors = check("a = '{:d} {:x}'.format('three', 3)\nb: str = '{}'.format(1)\n");
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(errors[0].2, "Format code 'd' is not valid for a value of type str");
+}
+
+#[test]
+fn test_percent_formatting_matches_conversions() {
+    let source = "\
+a = '%d' % 'three'
+b = '%s and %s' % (1,)
+c = '%s' % (1, 2)
+d = '%5.2f%% of %*d' % (1.0, 3, 4)
+e = '%(name)s' % {'nam': 1}
+f = '%x' % 2.5
+g = 'done' % {}
+";
+    let errors = check(source);
+    let messages: Vec<_> = errors.iter().map(|(line, _, message)| (*line, message.as_str())).collect();
+    assert_eq!(messages, vec![
+        (1, "%d format: a real number is required, not str"),
+        (2, "Not enough arguments for format string: expects 2, found 1"),
+        (3, "Not all arguments converted during string formatting: expects 1, found 2"),
+        (5, "Format string refers to missing key 'name'"),
+        (6, "%x format: an integer is required, not float"),
+    ]);
+}
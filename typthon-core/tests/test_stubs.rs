@@ -0,0 +1,90 @@
+//! `.pyi` stubs for modules outside the project, bundled or found on disk
+
+use std::fs;
+use std::sync::Arc;
+use tempfile::TempDir;
+use typthon::compiler::analysis::{CheckerOptions, ModuleResolver, TypeChecker};
+use typthon::{parse_module, Type};
+
+/// Checker that has checked `source`, with the lines of its errors
+fn check(mut checker: TypeChecker, source: &str) -> (TypeChecker, Vec<usize>) {
+    checker.set_source(source);
+    let lines = checker.check(&parse_module(source).unwrap()).into_iter().map(|e| e.line).collect();
+    (checker, lines)
+}
+
+#[test]
+fn test_bundled_math_stub() {
+    let source = "import math\nfrom math import floor\nroot = math.sqrt(2)\nmath.sqrt('x')\nlow = floor(2.5)\n";
+    let (checker, lines) = check(TypeChecker::new(), source);
+    assert_eq!(lines, vec![4]);
+    assert_eq!(checker.get_type("root"), Some(Type::Float));
+    assert_eq!(checker.get_type("low"), Some(Type::Int));
+}
+
+#[test]
+fn test_bundled_builtins_stub() {
+    let source = "from builtins import len, sorted\nsize = len([1, 2])\nitems = sorted([2, 1], reverse=True)\nsorted([2, 1], True)\n";
+    let (checker, lines) = check(TypeChecker::new(), source);
+    assert_eq!(lines, vec![4]);
+    assert_eq!(checker.get_type("size"), Some(Type::Int));
+}
+
+#[test]
+fn test_bundled_submodules_and_keywords() {
+    let source = "\
+import os
+import json
+joined = os.path.join('a', 'b')
+text = json.dumps({}, indent=2)
+json.dumps({}, sort_keys=2.5)
+cwd: int = os.getcwd()
+";
+    let (checker, lines) = check(TypeChecker::new(), source);
+    assert_eq!(checker.get_type("joined"), Some(Type::Str));
+    assert_eq!(checker.get_type("text"), Some(Type::Str));
+    assert!(lines.contains(&5) && lines.contains(&6), "{:?}", lines);
+}
+
+#[test]
+fn test_stub_paths_and_overloads() {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("requests")).unwrap();
+    fs::write(dir.path().join("requests/__init__.pyi"), "\
+from typing import overload
+
+def get(url: str, timeout: float = ...) -> int:
+    return 'bodies are not checked'
+
+@overload
+def pick(x: int) -> int: ...
+@overload
+def pick(x: str) -> str: ...
+").unwrap();
+
+    let options = CheckerOptions { stub_paths: vec![dir.path().to_path_buf()], ..CheckerOptions::default() };
+    let source = "import requests\nstatus = requests.get('u')\nrequests.get(1)\nchoice = requests.pick(1)\n";
+    let (checker, lines) = check(TypeChecker::new_with_options(options), source);
+    assert_eq!(lines, vec![3]);
+    assert_eq!(checker.get_type("status"), Some(Type::Int));
+    // Calls to overloaded functions aren't resolved yet
+    assert!(matches!(checker.get_type("choice"), Some(Type::Var(_))));
+}
+
+#[test]
+fn test_stub_beside_module_wins() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("lib.py"), "def size(x):\n    return len(x)\n").unwrap();
+    fs::write(dir.path().join("lib.pyi"), "def size(x: str) -> int: ...\n").unwrap();
+    let main = dir.path().join("main.py");
+    let source = "from lib import size\nn = size('abc')\nsize(3)\n";
+    fs::write(&main, source).unwrap();
+
+    let resolver = Arc::new(ModuleResolver::new([dir.path().to_path_buf()]));
+    assert_eq!(resolver.resolve(&main, 0, "lib"), Some(dir.path().join("lib.pyi")));
+    let mut checker = TypeChecker::new().with_modules(resolver);
+    checker.set_source_file(&main, source);
+    let lines: Vec<usize> = checker.check(&parse_module(source).unwrap()).into_iter().map(|e| e.line).collect();
+    assert_eq!(lines, vec![3]);
+    assert_eq!(checker.get_type("n"), Some(Type::Int));
+}
@@ -0,0 +1,55 @@
+//! Nominal subtyping: classes fit wherever one of their declared bases is
+//! expected
+
+use std::sync::Arc;
+use typthon::compiler::types::ClassSchema;
+use typthon::{parse_module, Type, TypeChecker, TypeContext};
+
+fn class(name: &str) -> Type {
+    Type::Class(name.to_string())
+}
+
+fn register(ctx: &TypeContext, name: &str, bases: &[&str]) {
+    let bases = bases.iter().map(|base| base.to_string()).collect();
+    ctx.register_class(ClassSchema::new(name.to_string()).with_bases(bases));
+}
+
+#[test]
+fn test_subclasses_are_subtypes_through_the_mro() {
+    let ctx = TypeContext::new();
+    // Diamond: Dog(Pet, Working), both deriving from Animal
+    register(&ctx, "Animal", &[]);
+    register(&ctx, "Pet", &["Animal"]);
+    register(&ctx, "Working", &["Animal"]);
+    register(&ctx, "Dog", &["Pet", "Working"]);
+    register(&ctx, "Loop", &["Knot"]);
+    register(&ctx, "Knot", &["Loop"]);
+
+    assert!(ctx.is_subtype(&class("Dog"), &class("Animal")));
+    assert!(ctx.is_subtype(&class("Dog"), &class("Working")));
+    // Lists are invariant in their elements, read-only sequences covariant
+    assert!(!ctx.is_subtype(&Type::List(Box::new(class("Dog"))), &Type::List(Box::new(class("Animal")))));
+    let sequence = |elem: &str| Type::Generic("Sequence".to_string(), vec![class(elem)]);
+    assert!(ctx.is_subtype(&sequence("Dog"), &sequence("Animal")));
+    assert!(!ctx.is_subtype(&class("Animal"), &class("Dog")));
+    assert!(!ctx.is_subtype(&class("Pet"), &class("Working")));
+    // Cyclic bases terminate
+    assert!(!ctx.is_subtype(&class("Loop"), &class("Animal")));
+
+    // Without a context classes only match themselves
+    assert!(!class("Dog").is_subtype(&class("Animal")));
+}
+
+#[test]
+fn test_subclass_instances_are_accepted_for_their_bases() {
+    let source = "class Animal:\n    pass\n\nclass Dog(Animal):\n    pass\n\nclass Rock:\n    pass\n\n\
+                  def feed(a: Animal) -> Animal:\n    return a\n\n\
+                  def fetch() -> Animal:\n    return Dog()\n\n\
+                  a: Animal = Dog()\nfeed(Dog())\nfeed(a=Dog())\nr: Animal = Rock()\nfeed(Rock())\n";
+    let mut checker = TypeChecker::with_context(Arc::new(TypeContext::new()));
+    let errors: Vec<String> = checker.check(&parse_module(source).unwrap()).into_iter().map(|e| e.message).collect();
+
+    assert_eq!(errors.len(), 2, "{:?}", errors);
+    assert_eq!(errors[0], "Type mismatch: cannot assign Rock to variable 'r' of type Animal");
+    assert_eq!(errors[1], "Argument 0 type mismatch: expected Animal, got Rock");
+}
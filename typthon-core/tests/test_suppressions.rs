@@ -0,0 +1,66 @@
+//! `# type: ignore` comments silencing errors and lints on their line
+
+use typthon::compiler::analysis::lints::{FLOAT_EQUALITY, UNUSED_IGNORE};
+use typthon::compiler::analysis::{CheckerOptions, LintDiagnostic, LintLevel};
+use typthon::{parse_module, TypeChecker};
+
+fn check(source: &str, options: CheckerOptions) -> (Vec<usize>, Vec<LintDiagnostic>) {
+    let module = parse_module(source).unwrap();
+    let mut checker = TypeChecker::new_with_options(options);
+    checker.set_source(source);
+    let errors = checker.check(&module).into_iter().map(|e| e.line).collect();
+    (errors, checker.lint(&module))
+}
+
+fn float_lints() -> CheckerOptions {
+    CheckerOptions {
+        lints: [(FLOAT_EQUALITY.to_string(), LintLevel::Warn)].into_iter().collect(),
+        ..CheckerOptions::default()
+    }
+}
+
+#[test]
+fn test_bare_ignore_silences_errors_on_its_line_only() {
+    let source = "\
+x: int = 'a'  # type: ignore
+y: int = 'b'
+z: str = 1  # type: ignore # stub is wrong
+s = '# type: ignore'; w: int = 'c'
+";
+    let (errors, _) = check(source, CheckerOptions::default());
+    assert_eq!(errors, vec![2, 4]);
+}
+
+#[test]
+fn test_coded_ignore_matches_lint_codes() {
+    let source = "\
+a: float = 0.1
+b = a == 0.3  # type: ignore[C001]
+c = a == 0.2  # type: ignore[S001]
+d = a == 0.4  # type: ignore
+";
+    let (_, lints) = check(source, float_lints());
+    let lines: Vec<&str> = lints.iter().map(|lint| &source[lint.range.clone()]).collect();
+    assert_eq!(lines, vec!["a == 0.2"]);
+}
+
+#[test]
+fn test_unused_ignores_are_reported_only_when_enabled() {
+    let source = "\
+x: int = 'a'  # type: ignore
+y: int = 1  # type: ignore
+r: float = 0.5
+ok = r == 0.5  # type: ignore[C001]
+";
+    let (_, lints) = check(source, float_lints());
+    assert!(lints.is_empty());
+
+    let (errors, lints) = check(source, CheckerOptions { warn_unused_ignores: true, ..float_lints() });
+    assert!(errors.is_empty());
+    assert_eq!(lints.len(), 1);
+    assert_eq!(lints[0].code, UNUSED_IGNORE);
+    assert_eq!(lints[0].level, LintLevel::Warn);
+    assert_eq!(&source[lints[0].range.clone()], "# type: ignore");
+    let fixed = lints[0].fix.as_ref().unwrap().apply(source);
+    assert!(fixed.contains("\ny: int = 1\n"));
+}
@@ -0,0 +1,101 @@
+//! Type aliases, `type X = ...` statements and quoted forward references
+
+use typthon::compiler::analysis::TypeChecker;
+use typthon::compiler::errors::codes;
+use typthon::parse_module;
+
+/// Lines, codes and messages of the errors from checking `source`
+fn check(source: &str) -> Vec<(usize, Option<&'static str>, String)> {
+    let mut checker = TypeChecker::new();
+    checker.set_source(source);
+    checker.check(&parse_module(source).unwrap()).into_iter()
+        .map(|e| (e.line, e.code, e.message))
+        .collect()
+}
+
+#[test]
+fn test_alias_stands_for_its_value() {
+    let errors = check("\
+Vector = list[float]
+
+def norm(v: Vector) -> float:
+    return v[0]
+
+norm([1.0, 2.0])
+norm([\"a\"])
+");
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(errors[0].0, 7);
+    assert_eq!(errors[0].1, Some(codes::ARG_TYPE));
+}
+
+#[test]
+fn test_type_statement_and_type_alias_annotation() {
+    let errors = check("\
+from typing import TypeAlias
+
+type Matrix = list[Row]
+Pair: TypeAlias = \"tuple[int, int]\"
+Row = list[float]
+
+m: Matrix = [[1.0, 2.0]]
+p: Pair = (1, 2)
+q: Pair = (1, \"a\")
+");
+    assert_eq!(errors.iter().map(|e| e.0).collect::<Vec<_>>(), vec![9], "{:?}", errors);
+}
+
+#[test]
+fn test_recursive_alias() {
+    let errors = check("\
+Tree = list[\"Tree\"] | int
+type Loop = Loop | int
+
+def size(t: Tree) -> int:
+    return 0
+
+size(1)
+size([1, [2, [3]]])
+size(\"x\")
+t: Tree = [1, [\"x\"]]
+");
+    let lines: Vec<usize> = errors.iter().map(|e| e.0).collect();
+    assert_eq!(lines, vec![2, 9, 10], "{:?}", errors);
+    assert_eq!(errors[0].1, Some(codes::INVALID_ANNOTATION));
+    assert_eq!(errors[0].2, "Type alias 'Loop' refers to itself outside of a container");
+    assert!(errors[2].2.ends_with("of type rec Tree. list[Tree] | int"), "{}", errors[2].2);
+}
+
+#[test]
+fn test_classes_referring_to_each_other() {
+    let errors = check("\
+class Node:
+    def __init__(self, value: int) -> None:
+        self.value = value
+
+    def link(self, target: \"Node\") -> \"Edge\":
+        return Edge(self, target)
+
+
+class Edge:
+    def __init__(self, source: Node, target: \"Node\") -> None:
+        self.source = source
+        self.target = target
+
+
+def follow(edge: \"Edge\") -> \"Node\":
+    return edge.target
+
+def weight(edge: \"Edge\") -> int:
+    return edge.target.value - edge.source.value
+
+edge = Node(1).link(Node(2))
+node: Node = follow(edge)
+w: int = weight(edge)
+");
+    assert!(errors.is_empty(), "{:?}", errors);
+
+    let errors = check("x: \"list[\" = []\n");
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(errors[0].2, "Invalid forward reference 'list['");
+}
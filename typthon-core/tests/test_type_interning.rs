@@ -0,0 +1,82 @@
+use proptest::prelude::*;
+use std::collections::HashMap;
+use typthon::compiler::analysis::ConstraintSolver;
+use typthon::compiler::types::{Type, TypeContext, TypeTable};
+
+fn arb_type() -> impl Strategy<Value = Type> {
+    let leaf = prop_oneof![
+        Just(Type::Any),
+        Just(Type::Never),
+        Just(Type::None),
+        Just(Type::Bool),
+        Just(Type::Int),
+        Just(Type::Float),
+        Just(Type::Str),
+        Just(Type::Bytes),
+        "[A-Z][a-z]{0,6}".prop_map(Type::Class),
+        "[A-Z][a-z]{0,6}".prop_map(Type::ClassObject),
+        (0u64..8).prop_map(Type::Var),
+    ];
+    leaf.prop_recursive(4, 48, 4, |inner| {
+        prop_oneof![
+            inner.clone().prop_map(|elem| Type::List(Box::new(elem))),
+            inner.clone().prop_map(|elem| Type::Set(Box::new(elem))),
+            inner.clone().prop_map(|elem| Type::VarTuple(Box::new(elem))),
+            prop::collection::vec(inner.clone(), 0..4).prop_map(Type::Tuple),
+            (inner.clone(), inner.clone()).prop_map(|(key, value)| Type::Dict(Box::new(key), Box::new(value))),
+            (prop::collection::vec(inner.clone(), 0..3), inner.clone())
+                .prop_map(|(params, ret)| Type::Function(params, Box::new(ret))),
+            prop::collection::vec(inner.clone(), 2..4).prop_map(Type::Union),
+            ("[A-Z][a-z]{0,6}", prop::collection::vec(inner.clone(), 1..3))
+                .prop_map(|(name, args)| Type::Generic(name, args)),
+            ("[A-Z][a-z]{0,6}", inner).prop_map(|(name, ty)| Type::Nominal(name, Box::new(ty))),
+        ]
+    })
+}
+
+proptest! {
+    #[test]
+    fn test_resolve_preserves_equality_and_display(ty in arb_type()) {
+        let table = TypeTable::new();
+        let handle = table.intern(ty.clone());
+        let resolved = table.resolve(handle);
+        prop_assert_eq!(&resolved, &ty);
+        prop_assert_eq!(resolved.to_string(), ty.to_string());
+    }
+
+    #[test]
+    fn test_handles_are_equal_exactly_when_types_are(a in arb_type(), b in arb_type()) {
+        let table = TypeTable::new();
+        let (first, second) = (table.intern(a.clone()), table.intern_ref(&b));
+        prop_assert_eq!(first == second, a == b);
+        prop_assert_eq!(table.intern_ref(&a), first);
+        prop_assert_eq!(table.len(), if a == b { 1 } else { 2 });
+    }
+
+    #[test]
+    fn test_context_bindings_round_trip(types in prop::collection::vec(arb_type(), 1..8)) {
+        let ctx = TypeContext::new();
+        let bindings: HashMap<String, Type> = types.iter().enumerate()
+            .map(|(i, ty)| (format!("v{}", i), ty.clone()))
+            .collect();
+        ctx.restore_bindings(&bindings);
+        let snapshot = ctx.binding_refs();
+        prop_assert_eq!(ctx.bindings(), bindings.clone());
+
+        ctx.set_type("v0".to_string(), Type::Int);
+        ctx.remove_type("v1");
+        ctx.restore_binding_refs(&snapshot);
+        for (name, ty) in &bindings {
+            prop_assert_eq!(ctx.get_type(name), Some(ty.clone()));
+        }
+    }
+
+    #[test]
+    fn test_solver_bounds_round_trip(ty in arb_type(), other in arb_type()) {
+        let mut solver = ConstraintSolver::new();
+        solver.add_bound(0, ty.clone());
+        solver.add_bound(0, other);
+        prop_assert_eq!(solver.get_bound(0), Some(ty));
+        prop_assert_eq!(solver.get_bound(1), None);
+    }
+}
@@ -0,0 +1,89 @@
+//! `TypedDict` classes, declared with a class body or functionally
+
+mod common;
+
+use typthon::compiler::analysis::TypeChecker;
+use typthon::Type;
+use common::check_with_prelude;
+
+const MOVIE: &str = "\
+from typing import TypedDict, NotRequired
+
+class Movie(TypedDict):
+    title: str
+    year: int
+";
+
+fn check(source: &str) -> (TypeChecker, Vec<(usize, String)>) {
+    let (checker, errors) = check_with_prelude(MOVIE, source);
+    (checker, errors.into_iter().map(|e| (e.line, e.message)).collect())
+}
+
+#[test]
+fn test_literal_keys_are_checked() {
+    let source = "\
+m: Movie = {'title': 'Heat', 'year': '1995'}
+extra: Movie = {'title': 'Heat', 'year': 1995, 'rating': 8}
+short: Movie = {'title': 'Heat'}
+ok: Movie = {'title': 'Heat', 'year': 1995}
+";
+    let (_, errors) = check(source);
+    assert_eq!(errors, vec![
+        (1, "Type mismatch for key 'year' of TypedDict 'Movie': expected int, got str".to_string()),
+        (2, "TypedDict 'Movie' has no key 'rating'".to_string()),
+        (3, "Missing key 'year' for TypedDict 'Movie'".to_string()),
+    ]);
+}
+
+#[test]
+fn test_subscripts_have_field_types() {
+    let source = "\
+m: Movie = {'title': 'Heat', 'year': 1995}
+loud = m['title'].upper()
+year = m['year']
+m['director']
+";
+    let (checker, errors) = check(source);
+    assert_eq!(errors, vec![(4, "TypedDict 'Movie' has no key 'director'".to_string())]);
+    assert_eq!(checker.get_type("loud"), Some(Type::Str));
+    assert_eq!(checker.get_type("year"), Some(Type::Int));
+}
+
+#[test]
+fn test_optional_keys_and_inheritance() {
+    let source = "\
+class Rated(Movie, total=False):
+    rating: float
+
+class Review(TypedDict):
+    text: str
+    stars: NotRequired[int]
+
+a: Rated = {'title': 'Alien', 'year': 1979}
+b: Rated = {'title': 'Alien'}
+c: Review = {'text': 'Tense'}
+";
+    let (_, errors) = check(source);
+    assert_eq!(errors, vec![(9, "Missing key 'year' for TypedDict 'Rated'".to_string())]);
+}
+
+#[test]
+fn test_functional_syntax_arguments_and_returns() {
+    let source = "\
+Point = TypedDict('Point', {'x': int, 'y': int})
+
+def origin() -> Point:
+    return {'x': 0, 'y': '0'}
+
+def title(movie: Movie) -> str:
+    return movie['title']
+
+title({'title': 'Heat', 'year': 1995})
+title({'title': 'Heat'})
+";
+    let (_, errors) = check(source);
+    assert_eq!(errors, vec![
+        (4, "Type mismatch for key 'y' of TypedDict 'Point': expected int, got str".to_string()),
+        (10, "Missing key 'year' for TypedDict 'Movie'".to_string()),
+    ]);
+}
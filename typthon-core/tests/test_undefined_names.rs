@@ -0,0 +1,59 @@
+//! Names nothing binds are reported where they're read, with the bound
+//! names and builtins they're likely misspellings of
+
+use typthon::compiler::errors::codes;
+use typthon::{parse_module, TypeChecker};
+
+fn check(source: &str) -> Vec<(usize, Option<&'static str>, String, Vec<String>)> {
+    let mut checker = TypeChecker::new();
+    checker.set_source(source);
+    checker.check(&parse_module(source).unwrap()).into_iter().map(|e| (e.line, e.code, e.message, e.suggestions)).collect()
+}
+
+#[test]
+fn test_misspelled_names_get_suggestions() {
+    let errors = check("\
+def area(width: int, height: int) -> int:
+    return width * hieght
+
+count = 3
+print(cuont, lenn([1]))
+");
+
+    assert_eq!(errors, vec![
+        (2, Some(codes::UNDEFINED_VARIABLE), "Undefined variable: hieght. Did you mean: height?".to_string(), vec!["height".to_string()]),
+        (5, Some(codes::UNDEFINED_VARIABLE), "Undefined variable: cuont. Did you mean: count?".to_string(), vec!["count".to_string()]),
+        (5, Some(codes::UNDEFINED_FUNCTION), "Undefined function: lenn. Did you mean: len?".to_string(), vec!["len".to_string()]),
+    ]);
+}
+
+#[test]
+fn test_later_definitions_and_builtins_are_not_undefined() {
+    let errors = check("\
+def main() -> None:
+    print(len(helper()), range(3), isinstance(Config(), Config))
+
+def helper() -> list[int]:
+    return [1]
+
+class Config:
+    pass
+");
+
+    assert!(errors.is_empty(), "{:?}", errors);
+}
+
+#[test]
+fn test_names_bound_only_in_a_sibling_function_are_undefined() {
+    let errors = check("\
+def f() -> None:
+    yy = 1
+
+def g() -> int:
+    return yy
+");
+
+    assert_eq!(errors, vec![
+        (5, Some(codes::UNDEFINED_VARIABLE), "Undefined variable: yy".to_string(), Vec::new()),
+    ]);
+}
@@ -0,0 +1,104 @@
+//! Unreachable statements and functions that never return
+
+use typthon::compiler::analysis::{LintLevel, TypeChecker};
+use typthon::compiler::errors::codes;
+use typthon::parse_module;
+
+/// Line and message of each unreachable-code warning, and the lines of the errors
+fn check(source: &str) -> (Vec<(usize, String)>, Vec<usize>) {
+    let module = parse_module(source).unwrap();
+    let mut checker = TypeChecker::new();
+    checker.set_source(source);
+    let errors = checker.check(&module).into_iter().map(|e| e.line).collect();
+    let warnings = checker.lint(&module).into_iter()
+        .filter(|lint| lint.code == codes::UNREACHABLE_CODE)
+        .map(|lint| {
+            assert_eq!(lint.level, LintLevel::Warn);
+            let line = source[..lint.range.start].matches('\n').count() + 1;
+            (line, lint.message)
+        })
+        .collect();
+    (warnings, errors)
+}
+
+#[test]
+fn test_code_after_return_and_raise() {
+    let source = "\
+def f(x: int) -> int:
+    return x
+    print('dead')
+    print('also dead')
+
+def g() -> None:
+    raise ValueError()
+    y = 1
+";
+    let (warnings, _) = check(source);
+    assert_eq!(warnings, vec![
+        (3, "Unreachable code after 'return'".to_string()),
+        (8, "Unreachable code after 'raise'".to_string()),
+    ]);
+}
+
+#[test]
+fn test_code_after_if_where_every_branch_returns() {
+    let source = "\
+def f(x: int) -> int:
+    if x:
+        return 1
+    else:
+        return 2
+    z = 3
+
+def g(x: int) -> int:
+    if x:
+        return 1
+    return 2
+";
+    let (warnings, _) = check(source);
+    assert_eq!(warnings, vec![(6, "Unreachable code after an 'if' whose every branch exits".to_string())]);
+}
+
+#[test]
+fn test_never_returning_calls_end_flow() {
+    let source = "\
+import sys
+from typing import NoReturn
+
+def fail(message: str) -> NoReturn:
+    print(message)
+    sys.exit(1)
+
+def first(items: list[int] | None) -> int:
+    if items is None:
+        fail('no items')
+    return items[0]
+
+def main() -> int:
+    fail('always')
+    return 0
+";
+    let (warnings, errors) = check(source);
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(warnings, vec![(15, "Unreachable code after a call that never returns".to_string())]);
+}
+
+#[test]
+fn test_guarded_branches_stay_live() {
+    let source = "\
+import sys
+from typing import TYPE_CHECKING
+
+if TYPE_CHECKING:
+    from os import path
+else:
+    path = None
+
+def separator() -> str:
+    if sys.platform == 'win32':
+        return '\\\\'
+    return '/'
+";
+    let (warnings, _) = check(source);
+    assert!(warnings.is_empty(), "{:?}", warnings);
+}
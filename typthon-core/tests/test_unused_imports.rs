@@ -0,0 +1,68 @@
+//! Imports whose names nothing uses, and repeated imports
+
+use typthon::compiler::analysis::{LintLevel, TypeChecker};
+use typthon::compiler::errors::codes;
+use typthon::parse_module;
+
+/// Line and message of each unused-import warning, and `source` with the
+/// fix of each applied on its own
+fn check(source: &str) -> Vec<(usize, String, String)> {
+    let module = parse_module(source).unwrap();
+    let mut checker = TypeChecker::new();
+    checker.set_source(source);
+    checker.check(&module);
+    checker.lint(&module).into_iter()
+        .filter(|lint| lint.code == codes::UNUSED_IMPORT)
+        .map(|lint| {
+            assert_eq!(lint.level, LintLevel::Warn);
+            let line = source[..lint.range.start].matches('\n').count() + 1;
+            (line, lint.message, lint.fix.unwrap().apply(source))
+        })
+        .collect()
+}
+
+#[test]
+fn test_unused_module_import() {
+    let source = "import os\nimport sys\n\nprint(sys.argv)\n";
+    assert_eq!(check(source), vec![
+        (1, "'os' is imported but never used".to_string(), "import sys\n\nprint(sys.argv)\n".to_string()),
+    ]);
+}
+
+#[test]
+fn test_unused_name_of_a_from_import() {
+    let source = "from math import floor, ceil\n\nprint(floor(2.5))\n";
+    assert_eq!(check(source), vec![
+        (1, "'ceil' is imported but never used".to_string(), "from math import floor\n\nprint(floor(2.5))\n".to_string()),
+    ]);
+}
+
+#[test]
+fn test_names_in_all_are_exported() {
+    let source = "from math import floor, ceil\nimport os\n\n__all__ = [\"floor\", \"ceil\"]\n";
+    let warnings: Vec<String> = check(source).into_iter().map(|(_, message, _)| message).collect();
+    assert_eq!(warnings, vec!["'os' is imported but never used".to_string()]);
+}
+
+#[test]
+fn test_names_in_quoted_annotations_are_used() {
+    let source = "\
+from typing import TYPE_CHECKING
+if TYPE_CHECKING:
+    from pathlib import Path
+
+def open_all(paths: \"list[Path]\") -> None:
+    pass
+";
+    assert!(check(source).is_empty());
+}
+
+#[test]
+fn test_repeated_import() {
+    let source = "import os\nimport os\n\nprint(os.sep)\n";
+    assert_eq!(check(source), vec![(
+        2,
+        "'os' is already imported on line 1".to_string(),
+        "import os\n\nprint(os.sep)\n".to_string(),
+    )]);
+}
@@ -0,0 +1,114 @@
+//! `warn_unused_variables`: local variables and parameters nothing reads
+
+use typthon::compiler::analysis::{CheckerOptions, LintLevel, TypeChecker};
+use typthon::compiler::errors::codes;
+use typthon::parse_module;
+
+/// Code, line and message of each unused-variable or unused-argument warning
+fn unused_with(source: &str, options: CheckerOptions) -> Vec<(&'static str, usize, String)> {
+    let module = parse_module(source).unwrap();
+    let mut checker = TypeChecker::new().with_options(options);
+    checker.set_source(source);
+    checker.check(&module);
+    checker.lint(&module).into_iter()
+        .filter(|lint| lint.code == codes::UNUSED_VARIABLE || lint.code == codes::UNUSED_ARGUMENT)
+        .map(|lint| {
+            assert_eq!(lint.level, LintLevel::Warn);
+            let line = source[..lint.range.start].matches('\n').count() + 1;
+            (lint.code, line, lint.message)
+        })
+        .collect()
+}
+
+fn unused(source: &str) -> Vec<(&'static str, usize, String)> {
+    unused_with(source, CheckerOptions { warn_unused_variables: true, ..CheckerOptions::default() })
+}
+
+#[test]
+fn test_unused_local_and_parameter() {
+    let source = "\
+def total(items: list[int], scale: int) -> int:
+    count = len(items)
+    result = 0
+    for item in items:
+        result = result + item
+    return result
+";
+    assert_eq!(unused(source), vec![
+        (codes::UNUSED_ARGUMENT, 1, "Parameter 'scale' is never used".to_string()),
+        (codes::UNUSED_VARIABLE, 2, "Local variable 'count' is assigned but never used".to_string()),
+    ]);
+}
+
+#[test]
+fn test_underscore_names_are_exempt() {
+    let source = "\
+def ticks(_unused: int, steps: int) -> int:
+    total = 0
+    for _ in range(steps):
+        total += 1
+    for step in range(steps):
+        pass
+    _ignored = steps
+    return total
+";
+    assert_eq!(unused(source), vec![
+        (codes::UNUSED_VARIABLE, 5, "Local variable 'step' is assigned but never used".to_string()),
+    ]);
+}
+
+#[test]
+fn test_augmented_assignment_and_del_are_uses() {
+    let source = "\
+def shrink(values: list[int]) -> None:
+    count = 0
+    count += 1
+    scratch = values
+    del scratch
+";
+    assert!(unused(source).is_empty(), "{:?}", unused(source));
+}
+
+#[test]
+fn test_variables_captured_by_closures_are_used() {
+    let source = "\
+def counter(start: int):
+    count = start
+    label = 'count'
+
+    def increment() -> int:
+        nonlocal count
+        count += 1
+        return count
+
+    def describe() -> str:
+        return label
+
+    return increment, describe
+";
+    assert!(unused(source).is_empty(), "{:?}", unused(source));
+}
+
+#[test]
+fn test_overrides_and_placeholders_keep_their_parameters() {
+    let source = "\
+class Handler:
+    def handle(self, event: str) -> None:
+        raise NotImplementedError()
+
+class Printer(Handler):
+    def handle(self, event: str) -> None:
+        print(self)
+
+    def __exit__(self, kind, value, traceback) -> None:
+        print(self)
+";
+    assert!(unused(source).is_empty(), "{:?}", unused(source));
+}
+
+#[test]
+fn test_off_by_default() {
+    let source = "def f(x: int) -> None:\n    y = 1\n";
+    assert!(unused_with(source, CheckerOptions::default()).is_empty());
+    assert_eq!(unused(source).len(), 2);
+}
@@ -0,0 +1,99 @@
+//! Watch mode: which files a change sends back to the checker
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use tempfile::TempDir;
+use typthon::infrastructure::concurrency::FileChange;
+use typthon::infrastructure::WatchSession;
+
+/// `main` imports `shapes`, which imports `units`; `other` stands alone.
+/// Returns the project and the session tracking it
+fn project() -> (TempDir, WatchSession) {
+    let temp = TempDir::new().unwrap();
+    let session = WatchSession::new()
+        .with_debounce(Duration::from_millis(100))
+        .with_poll_interval(Duration::from_millis(10));
+    let files: [(&str, &str, &[&str]); 4] = [
+        ("units.py", "SCALE = 1\n", &[]),
+        ("shapes.py", "from units import SCALE\n", &["units.py"]),
+        ("main.py", "from shapes import SCALE\n", &["shapes.py"]),
+        ("other.py", "y = 1\n", &[]),
+    ];
+    for (name, content, imports) in files {
+        let path = temp.path().join(name);
+        fs::write(&path, content).unwrap();
+        let imports: Vec<PathBuf> = imports.iter().map(|import| temp.path().join(import)).collect();
+        session.track(&path, content, &imports);
+    }
+    (temp, session)
+}
+
+fn names(temp: &TempDir, paths: &[PathBuf]) -> Vec<String> {
+    paths.iter().map(|path| path.strip_prefix(temp.path()).unwrap().display().to_string()).collect()
+}
+
+#[tokio::test]
+async fn test_change_rechecks_the_file_and_its_importers() {
+    let (temp, session) = project();
+    assert!(session.poll(&[]).await.unwrap().is_empty());
+
+    fs::write(temp.path().join("units.py"), "SCALE = 100\n").unwrap();
+    let changes = session.poll(&[]).await.unwrap();
+    assert_eq!(changes, vec![FileChange::Modified(temp.path().join("units.py"))]);
+    assert_eq!(names(&temp, &session.recheck_set(&changes)), vec!["main.py", "shapes.py", "units.py"]);
+
+    fs::write(temp.path().join("other.py"), "y = 22\n").unwrap();
+    let changes = session.poll(&[]).await.unwrap();
+    assert_eq!(names(&temp, &session.recheck_set(&changes)), vec!["other.py"]);
+}
+
+#[tokio::test]
+async fn test_retracking_replaces_imports() {
+    let (temp, session) = project();
+    let main = temp.path().join("main.py");
+    session.track(&main, "x = 1\n", &[]);
+
+    fs::write(temp.path().join("shapes.py"), "SCALE = 2\n").unwrap();
+    let changes = session.poll(&[]).await.unwrap();
+    assert_eq!(names(&temp, &session.recheck_set(&changes)), vec!["shapes.py"]);
+}
+
+#[tokio::test]
+async fn test_created_and_removed_files() {
+    let (temp, session) = project();
+    let created = temp.path().join("new.py");
+    fs::write(&created, "z = 1\n").unwrap();
+    fs::remove_file(temp.path().join("shapes.py")).unwrap();
+
+    let changes = session.poll(std::slice::from_ref(&created)).await.unwrap();
+    assert_eq!(changes, vec![
+        FileChange::Removed(temp.path().join("shapes.py")),
+        FileChange::Created(created.clone()),
+    ]);
+    // Importers of a removed file are checked again; the file itself isn't
+    assert_eq!(names(&temp, &session.recheck_set(&changes)), vec!["main.py", "new.py"]);
+
+    session.untrack(&temp.path().join("shapes.py"));
+    assert!(!session.is_tracked(&temp.path().join("shapes.py")));
+}
+
+#[tokio::test]
+async fn test_rapid_saves_are_debounced_into_one_batch() {
+    let (temp, session) = project();
+    let units = temp.path().join("units.py");
+    let writer = {
+        let units = units.clone();
+        tokio::spawn(async move {
+            for scale in 2..5 {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                fs::write(&units, format!("SCALE = {}{}\n", scale, "0".repeat(scale))).unwrap();
+            }
+        })
+    };
+
+    let changes = session.next_changes(Vec::new).await.unwrap();
+    writer.await.unwrap();
+    assert_eq!(changes, vec![FileChange::Modified(units)]);
+    assert!(session.poll(&[]).await.unwrap().is_empty());
+}
@@ -4,15 +4,53 @@ Document analyzer for LSP features.
 Provides type checking, completion, and navigation features.
 */
 
-use rustpython_parser::{ast, parse, Mode};
-use tower_lsp::lsp_types::CompletionItemKind;
+use rustpython_parser::ast::{Arguments, ExceptHandler, Mod, Ranged, Stmt};
+use rustpython_parser::lexer::lex;
+use rustpython_parser::{parse, Mode, Tok};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tower_lsp::lsp_types::{
+    CompletionItemKind, DocumentSymbol, FoldingRange, FoldingRangeKind, InlayHint, InlayHintKind, InlayHintLabel, Position,
+    Range, SignatureHelp, SignatureInformation, SymbolKind as LspSymbolKind, TextEdit,
+};
+use crate::completion;
+use crate::organize;
+use crate::semantic_tokens::{self, Token};
+use crate::signature::{self, CallSite, SignatureParam};
+use crate::settings::InlayHintSettings;
+use typthon::compiler::analysis::{bindings, imports};
+use typthon::compiler::analysis::lints::{LintDiagnostic, LintLevel};
+use typthon::compiler::analysis::undefined::BUILTINS;
+use typthon::compiler::analysis::unused_imports::unused_imports;
+use typthon::compiler::analysis::{
+    diff_items, fingerprint_items, CheckerOptions, ItemChange, ItemFingerprint, ModuleResolver, ScopeKind, Scopes, Symbol, TypeChecker,
+};
+use typthon::compiler::ast::ir::{self, ExprKind, NodeRef, ParamKind, StmtKind};
+use typthon::compiler::ast::{lower_module, Docstring};
+use typthon::compiler::errors::Severity;
+use typthon::compiler::types::{MemberKind, Type};
+
+/// Name a value is assigned to, to infer its type: the receiver before a
+/// dot, or a parameter's default
+const RECEIVER_PROBE: &str = "__receiver__";
 
 /// Simple type error for diagnostics
 #[derive(Debug, Clone)]
 pub struct TypeError {
-    pub line: usize,
-    pub col: usize,
+    /// Byte range of the offending code; `None` for errors about the whole document
+    pub range: Option<std::ops::Range<usize>>,
     pub message: String,
+    /// Stable code such as `TYP001`; `None` for syntax errors
+    pub code: Option<&'static str>,
+    pub severity: Severity,
+}
+
+impl From<typthon::compiler::analysis::checker::TypeError> for TypeError {
+    fn from(error: typthon::compiler::analysis::checker::TypeError) -> Self {
+        Self { range: error.range, message: error.message, code: error.code, severity: error.severity }
+    }
 }
 
 /// Completion suggestion
@@ -22,6 +60,8 @@ pub struct CompletionSuggestion {
     pub kind: CompletionItemKind,
     pub detail: String,
     pub documentation: Option<String>,
+    /// Documentation is left out and fetched on `completionItem/resolve`
+    pub lazy_documentation: bool,
 }
 
 /// Definition location
@@ -40,6 +80,9 @@ pub struct SymbolInfo {
     pub line: usize,
     pub col: usize,
     pub length: usize,
+    /// Byte offset the definition ends at: the end of a function's or
+    /// class's body, or of the name for variables and parameters
+    pub end: usize,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -52,53 +95,554 @@ pub enum SymbolKind {
     Property,
 }
 
+/// The names and attributes of a document, each with the symbol it
+/// refers to when that's known
+struct ResolvedNames {
+    scopes: Scopes,
+    /// By index of the occurrence
+    symbols: Vec<Option<Symbol>>,
+}
+
+impl ResolvedNames {
+    fn new(content: &str, module: &Mod, checker: &TypeChecker) -> Self {
+        let scopes = Scopes::resolve(module, content);
+        // Attributes of anything but a method's receiver resolve through
+        // the inferred type of the name they're looked up on
+        let symbols = scopes.occurrences().iter()
+            .map(|occurrence| occurrence.symbol.clone().or_else(|| {
+                let receiver = occurrence.receiver.as_ref()?;
+                let (_, ty) = checker.type_at(receiver.start).filter(|(range, _)| range == receiver)?;
+                let class = match ty {
+                    Type::Class(class) | Type::ClassObject(class) => scopes.class_named(&class)?,
+                    _ => return None,
+                };
+                Some(scopes.member(class, &occurrence.name))
+            }))
+            .collect();
+        Self { scopes, symbols }
+    }
+
+    fn symbol_at(&self, offset: usize) -> Option<Symbol> {
+        let index = self.scopes.occurrences().iter()
+            .position(|occurrence| occurrence.range.start <= offset && offset <= occurrence.range.end)?;
+        self.symbols[index].clone()
+    }
+
+    /// Byte ranges of the occurrences of `symbol`, in order
+    fn references(&self, symbol: &Symbol) -> Vec<std::ops::Range<usize>> {
+        let mut ranges: Vec<_> = self.scopes.occurrences().iter().zip(&self.symbols)
+            .filter(|(_, found)| found.as_ref() == Some(symbol))
+            .map(|(occurrence, _)| occurrence.range.clone())
+            .collect();
+        ranges.sort_by_key(|range| range.start);
+        ranges
+    }
+}
+
+/// Signature and docstring of a function or class defined in the document
+#[derive(Debug, Clone)]
+pub struct DefinitionDoc {
+    pub signature: String,
+    pub doc: Option<Docstring>,
+}
+
+impl DefinitionDoc {
+    /// The signature as a code block, then the full docstring
+    pub fn hover_markdown(&self) -> String {
+        let mut markdown = format!("```python\n{}\n```", self.signature);
+        if let Some(doc) = &self.doc {
+            markdown.push_str("\n\n");
+            markdown.push_str(&doc.text);
+        }
+        markdown
+    }
+}
+
+/// Quick-fix or refactoring, with edits in editor coordinates
+#[derive(Debug, Clone)]
+pub struct QuickFix {
+    pub title: String,
+    pub edits: Vec<TextEdit>,
+}
+
+/// A type-checked document, kept so the next edit can re-check less
+pub struct CheckedDocument {
+    items: Vec<ItemFingerprint>,
+    checker: TypeChecker,
+    pub errors: Vec<TypeError>,
+    pub lints: Vec<LintDiagnostic>,
+}
+
+/// How `check_document` arrived at its result
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckPath {
+    /// Reused the previous check, re-checking at most one function body
+    Incremental,
+    Full,
+}
+
 /// Document analyzer for type checking and code intelligence
-pub struct DocumentAnalyzer;
+pub struct DocumentAnalyzer {
+    /// Lint levels by code, from the project config
+    lints: BTreeMap<String, LintLevel>,
+    /// Check in strict mode (editor setting or project config)
+    strict: bool,
+    /// Test hook passed on to every checker
+    phase_delay: Option<(&'static str, Duration)>,
+    /// Where imports of other workspace files resolve, shared by every check
+    modules: Option<Arc<ModuleResolver>>,
+}
 
 impl DocumentAnalyzer {
     pub fn new() -> Self {
-        Self
+        Self { lints: BTreeMap::new(), strict: false, phase_delay: None, modules: None }
     }
 
-    /// Analyze document and return diagnostics
-    pub fn analyze(&self, content: &str) -> Vec<TypeError> {
-        let mut errors = Vec::new();
+    /// Enable lints at the given levels
+    pub fn with_lints(mut self, lints: BTreeMap<String, LintLevel>) -> Self {
+        self.lints = lints;
+        self
+    }
 
-        // Parse the Python code
-        match parse(content, Mode::Module, "<string>") {
-            Ok(_ast) => {
-                // TODO: Integrate with typthon-core type checker
-                // For now, just validate syntax
-            }
-            Err(err) => {
-                errors.push(TypeError {
-                    line: 0, // Error location not available in this API
-                    col: 0,
-                    message: format!("Syntax error: {}", err.error),
-                });
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Check imports of workspace files against what those files define
+    pub fn with_modules(mut self, modules: Arc<ModuleResolver>) -> Self {
+        self.modules = Some(modules);
+        self
+    }
+
+    /// Test hook: slow down `phase` of every check
+    #[cfg(test)]
+    pub fn with_phase_delay(mut self, phase: &'static str, delay: Duration) -> Self {
+        self.phase_delay = Some((phase, delay));
+        self
+    }
+
+    /// Run enabled lints (nothing is reported for unparsable documents)
+    ///
+    /// The checker runs first so type-aware lints see inferred types.
+    pub fn lint(&self, content: &str) -> Vec<LintDiagnostic> {
+        match self.check(content) {
+            Some((module, checker)) => checker.lint(&module),
+            None => Vec::new(),
+        }
+    }
+
+    /// Type check `content`, keeping the module and the checker's bindings
+    fn check(&self, content: &str) -> Option<(Mod, TypeChecker)> {
+        let module = parse(content, Mode::Module, "<string>").ok()?;
+        let mut checker = self.checker();
+        checker.set_source(content);
+        checker.check(&module);
+        Some((module, checker))
+    }
+
+    fn checker(&self) -> TypeChecker {
+        let options = CheckerOptions {
+            lints: self.lints.clone(),
+            strict: self.strict,
+            ..CheckerOptions::default()
+        };
+        let mut checker = TypeChecker::new().with_options(options);
+        if let Some(modules) = &self.modules {
+            checker = checker.with_modules(modules.clone());
+        }
+        if let Some((phase, delay)) = self.phase_delay {
+            checker.inject_phase_delay(phase, delay);
+        }
+        checker
+    }
+
+    /// Type check and lint `content`, starting from the `previous` check of
+    /// the same document when there is one
+    ///
+    /// When the only change is inside one function body, that function alone
+    /// is re-checked and its errors replace the ones it had; anything else
+    /// (signatures, classes, imports, added or moved items) checks the whole
+    /// document again. `None` for unparsable documents.
+    ///
+    /// With the document's `path`, its imports resolve relative to it and a
+    /// full check records what it exports for the files importing it.
+    pub fn check_document(
+        &self,
+        content: &str,
+        path: Option<&Path>,
+        previous: Option<CheckedDocument>,
+    ) -> Option<(CheckedDocument, CheckPath)> {
+        let module = parse(content, Mode::Module, "<string>").ok()?;
+        let Mod::Module(ast) = &module else { return None };
+        let items = fingerprint_items(content, &ast.body);
+
+        if let Some(mut previous) = previous {
+            let reused = match diff_items(&previous.items, &items) {
+                ItemChange::Unchanged => true,
+                ItemChange::FunctionBody(index) => {
+                    previous.checker.set_source(content);
+                    previous.checker.recheck_function(&module, index).map(|errors| {
+                        previous.errors = errors.into_iter().map(TypeError::from).collect();
+                        previous.lints = previous.checker.lint(&module);
+                    }).is_some()
+                }
+                ItemChange::Structural => false,
+            };
+            if reused {
+                previous.items = items;
+                return Some((previous, CheckPath::Incremental));
             }
         }
 
-        errors
+        let mut checker = self.checker().with_item_records();
+        match path {
+            Some(path) => checker.set_source_file(path, content),
+            None => checker.set_source(content),
+        }
+        let errors = checker.check(&module).into_iter().map(TypeError::from).collect();
+        let lints = checker.lint(&module);
+        Some((CheckedDocument { items, checker, errors, lints }, CheckPath::Full))
     }
 
-    /// Get hover information at position
-    pub fn get_hover_info(&self, content: &str, line: usize, col: usize) -> Option<String> {
-        // TODO: Integrate with typthon-core to get actual type information
-        // For now, provide basic Python information
+    /// Signature and docstring of the function or class named `name`
+    pub fn definition_doc(&self, content: &str, name: &str) -> Option<DefinitionDoc> {
+        let signature = self.definitions(content).into_iter().find(|(found, _)| found == name)?.1;
+        let (_, checker) = self.check(content)?;
+        Some(DefinitionDoc { signature, doc: checker.get_docstring(name) })
+    }
 
-        let lines: Vec<&str> = content.lines().collect();
-        if line >= lines.len() {
-            return None;
+    /// First paragraph of `name`'s docstring, for a resolved completion item
+    pub fn completion_documentation(&self, content: &str, name: &str) -> Option<String> {
+        let doc = self.definition_doc(content, name)?.doc?;
+        Some(doc.first_paragraph().to_string())
+    }
+
+    /// Functions and classes with their signatures, including methods
+    fn definitions(&self, content: &str) -> Vec<(String, String)> {
+        let Ok(ast) = parse(content, Mode::Module, "<string>") else { return Vec::new() };
+        let module = lower_module(&ast, None);
+        let mut definitions = Vec::new();
+        for stmt in &module.body {
+            if let Some(signature) = signature(content, stmt) {
+                definitions.push(signature);
+            }
+            if let StmtKind::ClassDef(class) = &stmt.kind {
+                definitions.extend(class.body.iter().filter_map(|member| signature(content, member)));
+            }
         }
+        definitions
+    }
 
-        let line_content = lines[line];
-        if col >= line_content.len() {
+    /// Convert a byte range to an editor range
+    pub fn lsp_range(&self, content: &str, range: &std::ops::Range<usize>) -> Range {
+        let position = |offset| {
+            let (line, col) = self.offset_to_position(content, offset);
+            Position { line: line as u32, character: col as u32 }
+        };
+        Range { start: position(range.start), end: position(range.end) }
+    }
+
+    /// Fixes for lints touching `range`
+    pub fn lint_fixes(&self, content: &str, range: Range) -> Vec<QuickFix> {
+        self.lint(content)
+            .into_iter()
+            .filter(|lint| {
+                let lint_range = self.lsp_range(content, &lint.range);
+                lint_range.start <= range.end && range.start <= lint_range.end
+            })
+            .filter_map(|lint| lint.fix)
+            .map(|fix| QuickFix {
+                title: fix.title,
+                edits: fix.edits.iter()
+                    .map(|edit| TextEdit {
+                        range: self.lsp_range(content, &edit.range),
+                        new_text: edit.new_text.clone(),
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// "Add type annotation" for the unannotated assignment target or
+    /// parameter at `line`/`col`, giving it the type the checker inferred
+    /// there. `None` when the type isn't one an annotation can spell, such
+    /// as a function or a type still unknown
+    pub fn annotation_fix(&self, content: &str, line: usize, col: usize) -> Option<QuickFix> {
+        let (module, checker) = self.check(content)?;
+        let offset = position_to_offset(content, line, col)
+            .or_else(|| position_to_offset(content, line, col.checked_sub(1)?).map(|offset| offset + 1))?;
+        let lowered = lower_module(&module, None);
+        let defined: Vec<String> = self.definitions(content).into_iter().map(|(name, _)| name).collect();
+
+        // Where the name is, and the parameter's default if it has one
+        let mut site: Option<(std::ops::Range<usize>, Option<std::ops::Range<usize>>)> = None;
+        lowered.walk(&mut |node| {
+            let ir::NodeRef::Stmt(stmt) = node else { return };
+            match &stmt.kind {
+                StmtKind::Assign { targets, .. } if targets.len() == 1 => {
+                    if let ExprKind::Name(_) = &targets[0].kind {
+                        let name = targets[0].span.range.clone();
+                        if name.start <= offset && offset <= name.end {
+                            site = Some((name, None));
+                        }
+                    }
+                }
+                StmtKind::FunctionDef(func) => {
+                    for param in &func.params {
+                        let start = param.node.range.start().to_usize();
+                        let name = start..start + param.name.len();
+                        let plain = matches!(param.kind, ParamKind::PositionalOnly | ParamKind::Positional | ParamKind::KeywordOnly);
+                        if param.annotation.is_some() || !plain || matches!(&*param.name, "self" | "cls")
+                            || !(name.start <= offset && offset <= name.end)
+                        {
+                            continue;
+                        }
+                        site = Some((name, param.default.as_ref().map(|default| default.span.range.clone())));
+                    }
+                }
+                _ => {}
+            }
+        });
+
+        let (name, default) = site?;
+        let (_, ty) = checker.type_at(name.start)?;
+        let annotation = match (annotation_text(&ty, &defined), &default) {
+            (Some(annotation), _) => annotation,
+            // A parameter only its calls constrain: the type of its default
+            (None, Some(default)) => {
+                let probed = format!("{}\n{} = {}\n", content.trim_end(), RECEIVER_PROBE, &content[default.clone()]);
+                let (_, checker) = self.check(&probed)?;
+                let (_, ty) = checker.type_at(content.trim_end().len() + 1)?;
+                annotation_text(&ty, &defined)?
+            }
+            (None, None) => return None,
+        };
+        // `x=1` becomes `x: int = 1`
+        let (replaced, new_text) = match default {
+            Some(default) => (name.end..default.start, format!(": {} = ", annotation)),
+            None => (name.end..name.end, format!(": {}", annotation)),
+        };
+        Some(QuickFix {
+            title: format!("Add type annotation `{}`", annotation),
+            edits: vec![TextEdit { range: self.lsp_range(content, &replaced), new_text }],
+        })
+    }
+
+    /// "Add missing import" fixes for the undefined `name`: `import name`
+    /// when it's a module typthon has stubs for, and `from m import name`
+    /// for each such module `m` defining it. The import goes after the
+    /// document's leading imports, or its docstring
+    pub fn import_fixes(&self, content: &str, name: &str) -> Vec<QuickFix> {
+        let Ok(Mod::Module(module)) = parse(content, Mode::Module, "<string>") else { return Vec::new() };
+        let mut imports = Vec::new();
+        for (module_name, stub) in imports::bundled_stubs() {
+            if *module_name == name {
+                imports.push(format!("import {}", name));
+            }
+            let Ok(Mod::Module(stub)) = parse(stub, Mode::Module, "<stub>") else { continue };
+            if stub_definitions(&stub.body).any(|defined| defined == name) {
+                imports.push(format!("from {} import {}", module_name, name));
+            }
+        }
+
+        let (offset, prefix) = import_insert_offset(content, &module.body);
+        imports.into_iter()
+            .map(|import| QuickFix {
+                title: format!("Add `{}`", import),
+                edits: vec![TextEdit { range: self.lsp_range(content, &(offset..offset)), new_text: format!("{}{}\n", prefix, import) }],
+            })
+            .collect()
+    }
+
+    /// "Organize imports": the imports the document starts with grouped and
+    /// sorted, and unused names taken out of every import. Modules under the
+    /// workspace roots are local. `None` when the imports are organized
+    /// already or the document doesn't parse
+    pub fn organize_imports(&self, content: &str) -> Option<QuickFix> {
+        let module = parse(content, Mode::Module, "<string>").ok()?;
+        let Mod::Module(body) = &module else { return None };
+        let unused = unused_imports(&module, content);
+        let roots = self.modules.as_ref().map(|modules| modules.roots().to_vec()).unwrap_or_default();
+        let is_local = |top: &str| roots.iter().any(|root| root.join(top).is_dir() || root.join(format!("{}.py", top)).is_file());
+        let edits = organize::organize_imports(content, &body.body, &unused, is_local);
+        if edits.is_empty() {
             return None;
         }
+        Some(QuickFix {
+            title: "Organize imports".to_string(),
+            edits: edits.into_iter()
+                .map(|(range, new_text)| TextEdit { range: self.lsp_range(content, &range), new_text })
+                .collect(),
+        })
+    }
 
-        // Simple word extraction
-        let word = extract_word_at_position(line_content, col);
+    /// Inlay hints for the lines `range` covers: the inferred types of
+    /// unannotated assignment targets and, after the `)`, of unannotated
+    /// functions' returns, each with the edit writing it out; and parameter
+    /// names before the positional arguments of calls. Types an annotation
+    /// can't spell get no hint
+    pub fn inlay_hints(&self, content: &str, range: Range, settings: &InlayHintSettings) -> Vec<InlayHint> {
+        let Some((module, checker)) = self.check(content) else { return Vec::new() };
+        let lowered = lower_module(&module, None);
+        let line_start = |line: u32| -> usize { content.split_inclusive('\n').take(line as usize).map(str::len).sum() };
+        let visible = line_start(range.start.line)..line_start(range.end.line + 1);
+        let shown = |offset: usize| visible.start <= offset && (offset < visible.end || offset == content.len());
+        let defined: Vec<String> = self.definitions(content).into_iter().map(|(name, _)| name).collect();
+
+        let mut hints = Vec::new();
+        let type_hint = |offset: usize, text: String| {
+            let position = self.lsp_range(content, &(offset..offset)).start;
+            InlayHint {
+                position,
+                label: InlayHintLabel::String(text.clone()),
+                kind: Some(InlayHintKind::TYPE),
+                text_edits: Some(vec![TextEdit { range: Range { start: position, end: position }, new_text: text }]),
+                tooltip: None,
+                padding_left: None,
+                padding_right: None,
+                data: None,
+            }
+        };
+        let statements = lowered.body.iter()
+            .filter(|stmt| stmt.span.range.start < visible.end && visible.start <= stmt.span.range.end);
+        for stmt in statements {
+            stmt.walk(&mut |node| match node {
+                ir::NodeRef::Stmt(stmt) => match &stmt.kind {
+                    StmtKind::Assign { targets, value, .. } if settings.variable_types && targets.len() == 1 => {
+                        let ExprKind::Name(_) = &targets[0].kind else { return };
+                        let name = targets[0].span.range.clone();
+                        if !shown(name.end) || (settings.skip_literals && is_literal(value)) {
+                            return;
+                        }
+                        let annotation = checker.type_at(name.start).and_then(|(_, ty)| annotation_text(&ty, &defined));
+                        if let Some(annotation) = annotation {
+                            hints.push(type_hint(name.end, format!(": {}", annotation)));
+                        }
+                    }
+                    StmtKind::FunctionDef(func) if settings.return_types && func.returns.is_none() => {
+                        let start = stmt.span.range.start;
+                        let Some(end) = params_end(content, start).filter(|end| shown(*end)) else { return };
+                        let annotation = checker.inferred_return(start).and_then(|returns| annotation_text(&returns, &defined));
+                        if let Some(annotation) = annotation {
+                            hints.push(type_hint(end, format!(" -> {}", annotation)));
+                        }
+                    }
+                    _ => {}
+                },
+                ir::NodeRef::Expr(expr) => {
+                    let ExprKind::Call { func, args, .. } = &expr.kind else { return };
+                    if !settings.parameter_names || !shown(expr.span.range.start) {
+                        return;
+                    }
+                    let Some(signature) = checker.call_signature(func.node) else { return };
+                    for (arg, param) in args.iter().zip(&signature.params) {
+                        // Past a `*args` the positions are unknown; an
+                        // argument named like its parameter says it already
+                        match &arg.kind {
+                            ExprKind::Starred(_) => break,
+                            ExprKind::Name(name) if **name == param.name => continue,
+                            _ => {}
+                        }
+                        hints.push(InlayHint {
+                            position: self.lsp_range(content, &arg.span.range).start,
+                            label: InlayHintLabel::String(format!("{}:", param.name)),
+                            kind: Some(InlayHintKind::PARAMETER),
+                            text_edits: None,
+                            tooltip: None,
+                            padding_left: None,
+                            padding_right: Some(true),
+                            data: None,
+                        });
+                    }
+                }
+            });
+        }
+        hints.sort_by_key(|hint| hint.position);
+        hints
+    }
+
+    /// Signature help for the call around the cursor: the signature of the
+    /// innermost call still open, with the parameter the argument being
+    /// typed goes to
+    ///
+    /// Functions and classes come from their definitions in the document,
+    /// methods from the class of the receiver's type, and imported and
+    /// builtin functions from the bundled stubs, each parameter with its
+    /// annotation and default as written. Anything else the checker can
+    /// type shows with the parameter types it knows.
+    pub fn signature_help(&self, content: &str, line: usize, col: usize) -> Option<SignatureHelp> {
+        let site = signature::call_site(content, cursor_offset(content, line, col))?;
+        let information = self.callee_signature(content, line, &site)?;
+        let active_parameter = information.active_parameter;
+        Some(SignatureHelp { signatures: vec![information], active_signature: Some(0), active_parameter })
+    }
+
+    /// Signature of what `site`, whose call is open on line `line`, calls
+    ///
+    /// The call being typed rarely parses, so the document is checked with
+    /// the lines from the callee to the cursor replaced by an assignment of
+    /// the receiver (or the callee itself) to a probe name. When that
+    /// doesn't parse either, the document is checked as it is.
+    fn callee_signature(&self, content: &str, line: usize, site: &CallSite) -> Option<SignatureInformation> {
+        let (receiver, attr) = site.split();
+        let value = receiver.unwrap_or(&site.callee);
+        let first = content[..site.start].matches('\n').count();
+        let mut probed = String::new();
+        let mut probe = 0;
+        for (i, text) in content.split_inclusive('\n').enumerate() {
+            if i == first {
+                let indent = &text[..text.len() - text.trim_start().len()];
+                probe = probed.len() + indent.len();
+                probed.push_str(&format!("{}{} = {}\n", indent, RECEIVER_PROBE, value));
+            } else if i < first || i > line {
+                probed.push_str(text);
+            }
+        }
+        let (source, probe) = match parse(&probed, Mode::Module, "<string>") {
+            Ok(_) => (probed, Some(probe)),
+            Err(_) => (content.to_string(), Some(site.start).filter(|_| !value.contains('.'))),
+        };
+        let (ast, checker) = self.check(&source)?;
+        let module = lower_module(&ast, None);
+        let ty = probe.and_then(|probe| checker.type_at(probe)).map(|(_, ty)| ty);
+
+        let found = match receiver {
+            None => definition_signature(&source, &module.body, attr, site)
+                .or_else(|| imported_name(&module.body, attr).and_then(|(from, name)| stub_signature(&from, &name, site)))
+                .or_else(|| stub_signature("builtins", attr, site)),
+            Some(receiver) => match imported_module(&module.body, receiver) {
+                Some(from) => stub_signature(&from, attr, site),
+                None => match &ty {
+                    Some(Type::Class(class)) => method_signature(&source, &module.body, class, attr, true, site),
+                    Some(Type::ClassObject(class)) => method_signature(&source, &module.body, class, attr, false, site),
+                    _ => None,
+                },
+            },
+        };
+        found.or_else(|| inferred_signature(&checker, ty, site))
+    }
+
+    /// Type errors in `content`, or the syntax error when it doesn't parse
+    pub fn analyze(&self, content: &str) -> Vec<TypeError> {
+        if let Some((checked, _)) = self.check_document(content, None, None) {
+            return checked.errors;
+        }
+        match parse(content, Mode::Module, "<string>") {
+            Ok(_) => Vec::new(),
+            Err(err) => vec![syntax_error(&err)],
+        }
+    }
+
+    /// Hover text at a position: the inferred type of the name there, with
+    /// the effects and docstring of functions and the docstring of classes.
+    /// `None` inside strings and comments
+    pub fn get_hover_info(&self, content: &str, line: usize, col: usize) -> Option<String> {
+        let offset = position_to_offset(content, line, col)?;
+        if in_string_or_comment(content, offset) {
+            return None;
+        }
+        let word = extract_word_at_position(content.lines().nth(line)?, col);
         if word.is_empty() {
             return None;
         }
@@ -117,88 +661,126 @@ impl DocumentAnalyzer {
             "def" => "Function definition keyword",
             "class" => "Class definition keyword",
             "return" => "Return statement keyword",
-            _ => return Some(format!("Identifier: {}", word)),
+            _ => {
+                return Some(self.inferred_hover(content, offset, &word)
+                    .or_else(|| self.definition_doc(content, &word).map(|definition| definition.hover_markdown()))
+                    .unwrap_or_else(|| format!("Identifier: {}", word)));
+            }
         };
 
         Some(hover_text.to_string())
     }
 
-    /// Get completions at position
+    /// The type the checker inferred for `word` at `offset`, or where a
+    /// function or class is defined, its module-level type, as markdown
+    fn inferred_hover(&self, content: &str, offset: usize, word: &str) -> Option<String> {
+        let (_, checker) = self.check(content)?;
+        let ty = match checker.type_at(offset) {
+            Some((_, ty)) => ty,
+            None if self.definitions(content).iter().any(|(name, _)| name == word) => checker.get_type(word)?,
+            None => return None,
+        };
+
+        // Function types show their effects, when not pure
+        let mut markdown = format!("```python\n{}: {}\n```", word, ty);
+        if matches!(ty, Type::Function(..) | Type::Effect(..) | Type::ClassObject(_)) {
+            if let Some(doc) = checker.get_docstring(word) {
+                markdown.push_str("\n\n");
+                markdown.push_str(&doc.text);
+            }
+        }
+        Some(markdown)
+    }
+
+    /// Get completions at position: after a dot, the members of the value
+    /// before it; otherwise keywords, builtin types, the document's
+    /// definitions and the names in scope
     pub fn get_completions(&self, content: &str, line: usize, col: usize) -> Vec<CompletionSuggestion> {
         let mut completions = Vec::new();
 
-        // Check if we're after a dot (attribute access)
-        let lines: Vec<&str> = content.lines().collect();
-        if line >= lines.len() {
-            return completions;
+        let Some(line_content) = content.lines().nth(line) else { return completions };
+        let before: String = line_content.chars().take(col).collect();
+        let stem = before.trim_end_matches(|ch: char| ch.is_alphanumeric() || ch == '_');
+        if let Some(before_dot) = stem.strip_suffix('.') {
+            return self.member_completions(content, line, before_dot);
         }
 
-        let line_content = lines[line];
-        if col > 0 && col <= line_content.len() && line_content.chars().nth(col - 1) == Some('.') {
-            // Provide attribute completions
-            // TODO: Context-aware completions based on type
-            completions.extend(vec![
-                CompletionSuggestion {
-                    label: "append".to_string(),
-                    kind: CompletionItemKind::METHOD,
-                    detail: "list.append(item)".to_string(),
-                    documentation: Some("Append an item to the list".to_string()),
-                },
-                CompletionSuggestion {
-                    label: "extend".to_string(),
-                    kind: CompletionItemKind::METHOD,
-                    detail: "list.extend(items)".to_string(),
-                    documentation: Some("Extend the list with multiple items".to_string()),
-                },
-                CompletionSuggestion {
-                    label: "pop".to_string(),
-                    kind: CompletionItemKind::METHOD,
-                    detail: "list.pop() -> T".to_string(),
-                    documentation: Some("Remove and return the last item".to_string()),
-                },
-            ]);
-        } else {
-            // Provide keyword completions
-            completions.extend(vec![
-                CompletionSuggestion {
-                    label: "def".to_string(),
-                    kind: CompletionItemKind::KEYWORD,
-                    detail: "Function definition".to_string(),
-                    documentation: Some("Define a function".to_string()),
-                },
-                CompletionSuggestion {
-                    label: "class".to_string(),
-                    kind: CompletionItemKind::KEYWORD,
-                    detail: "Class definition".to_string(),
-                    documentation: Some("Define a class".to_string()),
-                },
-                CompletionSuggestion {
-                    label: "if".to_string(),
-                    kind: CompletionItemKind::KEYWORD,
-                    detail: "Conditional statement".to_string(),
-                    documentation: Some("Conditional if statement".to_string()),
-                },
-                CompletionSuggestion {
-                    label: "for".to_string(),
-                    kind: CompletionItemKind::KEYWORD,
-                    detail: "For loop".to_string(),
-                    documentation: Some("For loop iteration".to_string()),
-                },
-                CompletionSuggestion {
-                    label: "return".to_string(),
-                    kind: CompletionItemKind::KEYWORD,
-                    detail: "Return statement".to_string(),
-                    documentation: Some("Return a value from function".to_string()),
-                },
-            ]);
+        // Provide keyword completions
+        completions.extend(vec![
+            CompletionSuggestion {
+                label: "def".to_string(),
+                kind: CompletionItemKind::KEYWORD,
+                detail: "Function definition".to_string(),
+                documentation: Some("Define a function".to_string()),
+                lazy_documentation: false,
+            },
+            CompletionSuggestion {
+                label: "class".to_string(),
+                kind: CompletionItemKind::KEYWORD,
+                detail: "Class definition".to_string(),
+                documentation: Some("Define a class".to_string()),
+                lazy_documentation: false,
+            },
+            CompletionSuggestion {
+                label: "if".to_string(),
+                kind: CompletionItemKind::KEYWORD,
+                detail: "Conditional statement".to_string(),
+                documentation: Some("Conditional if statement".to_string()),
+                lazy_documentation: false,
+            },
+            CompletionSuggestion {
+                label: "for".to_string(),
+                kind: CompletionItemKind::KEYWORD,
+                detail: "For loop".to_string(),
+                documentation: Some("For loop iteration".to_string()),
+                lazy_documentation: false,
+            },
+            CompletionSuggestion {
+                label: "return".to_string(),
+                kind: CompletionItemKind::KEYWORD,
+                detail: "Return statement".to_string(),
+                documentation: Some("Return a value from function".to_string()),
+                lazy_documentation: false,
+            },
+        ]);
+
+        // Add type keywords
+        for type_name in &["int", "str", "float", "bool", "list", "dict", "tuple", "set"] {
+            completions.push(CompletionSuggestion {
+                label: type_name.to_string(),
+                kind: CompletionItemKind::CLASS,
+                detail: format!("Built-in type: {}", type_name),
+                documentation: None,
+                lazy_documentation: false,
+            });
+        }
+
+        // Document definitions; docstrings are resolved on demand
+        for (name, signature) in self.definitions(content) {
+            let kind = if signature.starts_with("class ") {
+                CompletionItemKind::CLASS
+            } else {
+                CompletionItemKind::FUNCTION
+            };
+            completions.push(CompletionSuggestion {
+                label: name,
+                kind,
+                detail: signature,
+                documentation: None,
+                lazy_documentation: true,
+            });
+        }
 
-            // Add type keywords
-            for type_name in &["int", "str", "float", "bool", "list", "dict", "tuple", "set"] {
+        // Names in scope where the cursor is
+        let line_start: usize = content.split_inclusive('\n').take(line).map(str::len).sum();
+        for (name, detail) in self.names_in_scope(content, line_start + before.len()) {
+            if !completions.iter().any(|completion| completion.label == name) {
                 completions.push(CompletionSuggestion {
-                    label: type_name.to_string(),
-                    kind: CompletionItemKind::CLASS,
-                    detail: format!("Built-in type: {}", type_name),
+                    label: name,
+                    kind: CompletionItemKind::VARIABLE,
+                    detail,
                     documentation: None,
+                    lazy_documentation: false,
                 });
             }
         }
@@ -206,6 +788,59 @@ impl DocumentAnalyzer {
         completions
     }
 
+    /// Members of the value `before_dot` ends with
+    ///
+    /// The line being typed rarely parses, so the document is checked with
+    /// it replaced by an assignment of that value to a probe name, at the
+    /// same indentation, and the members are those of the probe's type.
+    fn member_completions(&self, content: &str, line: usize, before_dot: &str) -> Vec<CompletionSuggestion> {
+        let Some(receiver) = completion::receiver_expression(before_dot) else { return Vec::new() };
+        let indent = &before_dot[..before_dot.len() - before_dot.trim_start().len()];
+        let mut probed = String::new();
+        let mut probe = 0;
+        for (i, text) in content.split_inclusive('\n').enumerate() {
+            if i == line {
+                probe = probed.len() + indent.len();
+                probed.push_str(&format!("{}{} = {}\n", indent, RECEIVER_PROBE, receiver));
+            } else {
+                probed.push_str(text);
+            }
+        }
+        let Some((_, checker)) = self.check(&probed) else { return Vec::new() };
+        let Some((_, ty)) = checker.type_at(probe) else { return Vec::new() };
+
+        checker.members(&ty).into_iter()
+            .filter(|(name, _)| !name.starts_with("__"))
+            .map(|(name, member)| {
+                let (kind, detail) = match member {
+                    MemberKind::Method(ty) | MemberKind::StaticMethod(ty) => match ty {
+                        Type::Function(..) | Type::Effect(..) => (CompletionItemKind::METHOD, format!("{}{}", name, ty)),
+                        ty => (CompletionItemKind::METHOD, format!("{}: {}", name, ty)),
+                    },
+                    MemberKind::Property(ty) | MemberKind::ClassVar(ty) => (CompletionItemKind::PROPERTY, format!("{}: {}", name, ty)),
+                };
+                CompletionSuggestion { label: name, kind, detail, documentation: None, lazy_documentation: false }
+            })
+            .collect()
+    }
+
+    /// Names bound in the scopes around byte `offset`, with their inferred
+    /// types, or `variable` when there's none
+    fn names_in_scope(&self, content: &str, offset: usize) -> Vec<(String, String)> {
+        let Some((Mod::Module(module), checker)) = self.check(content) else { return Vec::new() };
+        let types: HashMap<String, Type> = checker.inferred_bindings().into_iter()
+            .map(|(qualified, ty, _)| (qualified, ty))
+            .collect();
+        let mut names = Vec::new();
+        scope_names(&module.body, offset, None, &mut names);
+        names.into_iter()
+            .map(|(qualified, name)| {
+                let detail = types.get(&qualified).map_or_else(|| "variable".to_string(), Type::to_string);
+                (name, detail)
+            })
+            .collect()
+    }
+
     /// Get definition location
     pub fn get_definition(&self, content: &str, line: usize, col: usize) -> Option<DefinitionLocation> {
         let word = self.get_word_at_position(content, line, col)?;
@@ -221,60 +856,334 @@ impl DocumentAnalyzer {
             })
     }
 
-    /// Find all references to a symbol
+    /// Occurrences of the symbol at the cursor, its definition included:
+    /// the variable, parameter, function or class a name resolves to in
+    /// its scope, or the member an attribute resolves to in its class
     pub fn find_references(&self, content: &str, line: usize, col: usize) -> Vec<DefinitionLocation> {
-        let word = match self.get_word_at_position(content, line, col) {
-            Some(w) => w,
-            None => return Vec::new(),
-        };
+        let Some(names) = self.resolve_names(content) else { return Vec::new() };
+        let Some(symbol) = names.symbol_at(cursor_offset(content, line, col)) else { return Vec::new() };
+        names.references(&symbol).into_iter().map(|range| self.location(content, range)).collect()
+    }
 
-        let mut references = Vec::new();
-        let lines: Vec<&str> = content.lines().collect();
+    /// Edits renaming the symbol at the cursor to `new_name`, or why it
+    /// can't be: `new_name` isn't an identifier, there's no symbol there,
+    /// it's a builtin, or `new_name` is already bound where it's used
+    pub fn rename(&self, content: &str, line: usize, col: usize, new_name: &str) -> Result<Vec<TextEdit>, String> {
+        if !is_identifier(new_name) {
+            return Err(format!("`{}` is not a valid name", new_name));
+        }
+        let word = self.get_word_at_position(content, line, col).unwrap_or_default();
+        if completion::PYTHON_KEYWORDS.contains(&word.as_str()) {
+            return Err(format!("`{}` is a keyword", word));
+        }
+        let names = self.resolve_names(content).ok_or("The document doesn't parse")?;
+        let symbol = names.symbol_at(cursor_offset(content, line, col))
+            .ok_or_else(|| format!("Can't tell what `{}` refers to", word))?;
+        if symbol.scope == 0 && !names.scopes.binds(&symbol) {
+            return Err(match BUILTINS.contains(&symbol.name.as_str()) {
+                true => format!("`{}` is a builtin", symbol.name),
+                false => format!("`{}` isn't defined in this document", symbol.name),
+            });
+        }
+        if new_name != symbol.name && names.scopes.conflicts(&symbol, new_name) {
+            return Err(format!("`{}` is already bound where `{}` is used", new_name, symbol.name));
+        }
+        Ok(names.references(&symbol).into_iter()
+            .map(|range| TextEdit { range: self.lsp_range(content, &range), new_text: new_name.to_string() })
+            .collect())
+    }
 
-        for (idx, line_text) in lines.iter().enumerate() {
-            let mut start = 0;
-            while let Some(pos) = line_text[start..].find(&word) {
-                let actual_pos = start + pos;
-
-                // Check if this is a complete word (not part of another identifier)
-                let is_word_start = actual_pos == 0 ||
-                    !line_text.chars().nth(actual_pos - 1).map_or(false, |c| c.is_alphanumeric() || c == '_');
-                let is_word_end = actual_pos + word.len() >= line_text.len() ||
-                    !line_text.chars().nth(actual_pos + word.len()).map_or(false, |c| c.is_alphanumeric() || c == '_');
-
-                if is_word_start && is_word_end {
-                    references.push(DefinitionLocation {
-                        line: idx,
-                        col: actual_pos,
-                        length: word.len(),
-                    });
+    /// The document's functions and classes, with how many times each is
+    /// used besides its definition
+    pub fn definition_uses(&self, content: &str) -> Vec<(SymbolInfo, usize)> {
+        let names = self.resolve_names(content);
+        self.extract_symbols(content)
+            .into_iter()
+            .filter(|symbol| matches!(symbol.kind, SymbolKind::Function | SymbolKind::Class))
+            .map(|symbol| {
+                let uses = names.as_ref()
+                    .and_then(|names| {
+                        let start = position_to_offset(content, symbol.line, symbol.col)?;
+                        names.symbol_at(name_offset(content, start, &symbol.name))
+                            .map(|found| names.references(&found).len().saturating_sub(1))
+                    })
+                    .unwrap_or(0);
+                (symbol, uses)
+            })
+            .collect()
+    }
+
+    /// Every name and attribute in `content` with what it refers to
+    fn resolve_names(&self, content: &str) -> Option<ResolvedNames> {
+        let (module, checker) = self.check(content)?;
+        Some(ResolvedNames::new(content, &module, &checker))
+    }
+
+    /// Semantic tokens of `content`: names coloured by what they refer to,
+    /// with a method's receiver apart from other parameters, attributes of
+    /// the document's classes, decorators, the names in type annotations,
+    /// and string and number literals. Bindings carry the definition
+    /// modifier, and every use of a `Final` or all-caps module-level name
+    /// the readonly one. Only literals when the document doesn't parse
+    pub fn semantic_tokens(&self, content: &str) -> Vec<Token> {
+        let lines = LineStarts::new(content);
+        let token = |range: std::ops::Range<usize>, token_type: u32, modifiers: u32| {
+            let (line, start) = lines.position(content, range.start);
+            Token { line, start, length: content[range].encode_utf16().count() as u32, token_type, modifiers }
+        };
+
+        let mut tokens = Vec::new();
+        for (tok, range) in lex(content, Mode::Module).map_while(Result::ok) {
+            let range = range.start().to_usize()..range.end().to_usize();
+            match tok {
+                Tok::Int { .. } | Tok::Float { .. } | Tok::Complex { .. } => tokens.push(token(range, semantic_tokens::NUMBER, 0)),
+                // One token per line; editors don't take tokens spanning lines
+                Tok::String { .. } => {
+                    let mut start = range.start;
+                    for line in content[range.clone()].split_inclusive('\n') {
+                        let end = start + line.trim_end_matches(['\n', '\r']).len();
+                        tokens.push(token(start..end, semantic_tokens::STRING, 0));
+                        start += line.len();
+                    }
                 }
+                _ => {}
+            }
+        }
+        let Some((module, checker)) = self.check(content) else { return tokens };
+        let names = ResolvedNames::new(content, &module, &checker);
+        let lowered = lower_module(&module, None);
+
+        // What each definition defines, by the offset of its name, and the
+        // names in decorators and annotations
+        let mut defined: HashMap<usize, u32> = HashMap::new();
+        let mut finals: Vec<usize> = Vec::new();
+        let mut decorators: Vec<std::ops::Range<usize>> = Vec::new();
+        let mut annotations: Vec<std::ops::Range<usize>> = Vec::new();
+        let annotate = |expr: &ir::Expr, annotations: &mut Vec<std::ops::Range<usize>>| {
+            expr.walk(&mut |node| {
+                if let NodeRef::Expr(expr) = node {
+                    match &expr.kind {
+                        ExprKind::Name(_) => annotations.push(expr.span.range.clone()),
+                        ExprKind::Attribute { attr, .. } => annotations.push(expr.span.range.end - attr.len()..expr.span.range.end),
+                        _ => {}
+                    }
+                }
+            });
+        };
+        let params = |params: &[ir::Param], defined: &mut HashMap<usize, u32>, annotations: &mut Vec<_>| {
+            for param in params {
+                defined.insert(param.span.range.start, semantic_tokens::PARAMETER);
+                if let Some(annotation) = &param.annotation {
+                    annotate(annotation, annotations);
+                }
+            }
+        };
+        lowered.walk(&mut |node| match node {
+            NodeRef::Stmt(stmt) => match &stmt.kind {
+                StmtKind::FunctionDef(func) => {
+                    defined.insert(name_offset(content, stmt.span.range.start, &func.name), semantic_tokens::FUNCTION);
+                    decorators.extend(func.decorators.iter().filter_map(decorator_name));
+                    params(&func.params, &mut defined, &mut annotations);
+                    if let Some(returns) = &func.returns {
+                        annotate(returns, &mut annotations);
+                    }
+                }
+                StmtKind::ClassDef(class) => {
+                    defined.insert(name_offset(content, stmt.span.range.start, &class.name), semantic_tokens::CLASS);
+                    decorators.extend(class.decorators.iter().filter_map(decorator_name));
+                }
+                StmtKind::AnnAssign { target, annotation, .. } => {
+                    annotate(annotation, &mut annotations);
+                    let final_ = match &annotation.kind {
+                        ExprKind::Subscript { value, .. } => &**value,
+                        _ => annotation,
+                    };
+                    if matches!(&final_.kind, ExprKind::Name(name) if &**name == "Final") {
+                        finals.push(target.span.range.start);
+                    }
+                }
+                StmtKind::Import(aliases) => {
+                    for alias in aliases.iter().filter(|alias| alias.asname.is_none()) {
+                        defined.insert(alias.span.range.start, semantic_tokens::NAMESPACE);
+                    }
+                    for alias in aliases.iter().filter_map(|alias| alias.asname.as_ref().map(|asname| (alias, asname))) {
+                        defined.insert(alias.0.span.range.end - alias.1.len(), semantic_tokens::NAMESPACE);
+                    }
+                }
+                _ => {}
+            },
+            NodeRef::Expr(expr) => {
+                if let ExprKind::Lambda { params: lambda, .. } = &expr.kind {
+                    params(lambda, &mut defined, &mut annotations);
+                }
+            }
+        });
+
+        // What each symbol is, from the definition binding it, or failing
+        // that from its type
+        let occurrences = names.scopes.occurrences();
+        let mut kinds: HashMap<&Symbol, u32> = HashMap::new();
+        let mut readonly: Vec<&Symbol> = Vec::new();
+        for (occurrence, symbol) in occurrences.iter().zip(&names.symbols) {
+            let Some(symbol) = symbol else { continue };
+            if let Some(&kind) = defined.get(&occurrence.range.start).filter(|_| occurrence.binding && occurrence.receiver.is_none()) {
+                let in_class = names.scopes.kind(occurrence.scope) == ScopeKind::Class;
+                let kind = if kind == semantic_tokens::FUNCTION && in_class { semantic_tokens::METHOD } else { kind };
+                kinds.entry(symbol).or_insert(kind);
+            }
+            let constant = symbol.scope == 0 && symbol.name.chars().any(char::is_alphabetic)
+                && !symbol.name.chars().any(char::is_lowercase);
+            if occurrence.binding && (finals.contains(&occurrence.range.start) || constant) {
+                readonly.push(symbol);
+            }
+        }
 
-                start = actual_pos + 1;
+        for (occurrence, symbol) in occurrences.iter().zip(&names.symbols) {
+            let Some(symbol) = symbol else { continue };
+            let range = occurrence.range.clone();
+            let token_type = if decorators.contains(&range) {
+                semantic_tokens::DECORATOR
+            } else if annotations.contains(&range) {
+                semantic_tokens::TYPE
+            } else if names.scopes.is_receiver(symbol) {
+                semantic_tokens::SELF_PARAMETER
+            } else if let Some(&kind) = kinds.get(symbol) {
+                kind
+            } else if occurrence.receiver.is_some() || names.scopes.kind(symbol.scope) == ScopeKind::Class {
+                semantic_tokens::PROPERTY
+            } else {
+                match checker.get_type(&symbol.name).filter(|_| symbol.scope == 0) {
+                    Some(Type::ClassObject(_)) => semantic_tokens::CLASS,
+                    Some(Type::Function(..) | Type::Effect(..)) => semantic_tokens::FUNCTION,
+                    _ => semantic_tokens::VARIABLE,
+                }
+            };
+            let mut modifiers = 0;
+            if occurrence.binding {
+                modifiers |= semantic_tokens::DEFINITION;
             }
+            if readonly.contains(&symbol) {
+                modifiers |= semantic_tokens::READONLY;
+            }
+            tokens.push(token(range, token_type, modifiers));
         }
+        tokens
+    }
 
-        references
+    fn location(&self, content: &str, range: std::ops::Range<usize>) -> DefinitionLocation {
+        let (line, col) = self.offset_to_position(content, range.start);
+        DefinitionLocation { line, col, length: content[range].chars().count() }
     }
 
     /// Extract all symbols from document
     pub fn extract_symbols(&self, content: &str) -> Vec<SymbolInfo> {
         let mut symbols = Vec::new();
 
-        match parse(content, Mode::Module, "<string>") {
-            Ok(ast) => {
-                if let ast::Mod::Module(module) = ast {
-                    self.visit_module(&module.body, content, &mut symbols);
-                }
-            }
-            Err(_) => {}
+        if let Ok(ast) = parse(content, Mode::Module, "<string>") {
+            let module = lower_module(&ast, None);
+            self.visit_module(&module.body, content, &mut symbols);
         }
 
         symbols
     }
 
-    /// Visit AST module and extract symbols
-    fn visit_module(&self, stmts: &[ast::Stmt], content: &str, symbols: &mut Vec<SymbolInfo>) {
+    /// Outline of the document: classes holding their methods and
+    /// attributes, functions their nested functions and locals. Each spans
+    /// its whole definition, with its name as the selection
+    pub fn document_symbols(&self, content: &str) -> Vec<DocumentSymbol> {
+        let mut roots: Vec<DocumentSymbol> = Vec::new();
+        // Definitions whose bodies may hold the next symbol, innermost last
+        let mut open: Vec<(DocumentSymbol, usize)> = Vec::new();
+        let close = |open: &mut Vec<(DocumentSymbol, usize)>, roots: &mut Vec<DocumentSymbol>| {
+            let (symbol, _) = open.pop().expect("an open symbol");
+            match open.last_mut() {
+                Some((parent, _)) => parent.children.get_or_insert_with(Vec::new).push(symbol),
+                None => roots.push(symbol),
+            }
+        };
+
+        for symbol in self.extract_symbols(content).into_iter().filter(|symbol| symbol.kind != SymbolKind::Parameter) {
+            let Some(start) = position_to_offset(content, symbol.line, symbol.col) else { continue };
+            while open.last().is_some_and(|(_, end)| *end <= start) {
+                close(&mut open, &mut roots);
+            }
+            let in_class = open.last().is_some_and(|(parent, _)| parent.kind == LspSymbolKind::CLASS);
+            let (kind, name_start) = match symbol.kind {
+                SymbolKind::Function if in_class => (LspSymbolKind::METHOD, name_offset(content, start, &symbol.name)),
+                SymbolKind::Function => (LspSymbolKind::FUNCTION, name_offset(content, start, &symbol.name)),
+                SymbolKind::Class => (LspSymbolKind::CLASS, name_offset(content, start, &symbol.name)),
+                _ => (LspSymbolKind::VARIABLE, start),
+            };
+            #[allow(deprecated)]
+            let outlined = DocumentSymbol {
+                name: symbol.name.clone(),
+                detail: None,
+                kind,
+                tags: None,
+                deprecated: None,
+                range: self.lsp_range(content, &(start..symbol.end)),
+                selection_range: self.lsp_range(content, &(name_start..name_start + symbol.name.len())),
+                children: None,
+            };
+            open.push((outlined, symbol.end));
+        }
+        while !open.is_empty() {
+            close(&mut open, &mut roots);
+        }
+        roots
+    }
+
+    /// Regions an editor can fold: the bodies of functions and classes,
+    /// literals and strings spanning several lines, and runs of imports.
+    /// A body's region starts on the line its header ends, which stays
+    /// visible when folded
+    pub fn folding_ranges(&self, content: &str) -> Vec<FoldingRange> {
+        let Ok(ast) = parse(content, Mode::Module, "<string>") else { return Vec::new() };
+        let module = lower_module(&ast, None);
+        let line = |offset: usize| self.lsp_range(content, &(offset..offset)).start.line;
+        let mut ranges = Vec::new();
+        let mut fold = |start: usize, end: usize, kind: Option<FoldingRangeKind>| {
+            let (start_line, end_line) = (line(start), line(end));
+            if start_line < end_line {
+                ranges.push(FoldingRange { start_line, start_character: None, end_line, end_character: None, kind, collapsed_text: None });
+            }
+        };
+
+        let mut bodies: Vec<&[ir::Stmt]> = vec![&module.body];
+        module.walk(&mut |node| match node {
+            ir::NodeRef::Stmt(stmt) => match &stmt.kind {
+                StmtKind::FunctionDef(ir::FunctionDef { body, .. }) | StmtKind::ClassDef(ir::ClassDef { body, .. }) => {
+                    if let Some(header) = header_end(content, stmt.span.range.start) {
+                        fold(header, stmt.span.range.end, None);
+                    }
+                    bodies.push(body);
+                }
+                _ => {}
+            },
+            ir::NodeRef::Expr(expr) => {
+                let literal = matches!(
+                    expr.kind,
+                    ExprKind::List(_) | ExprKind::Tuple(_) | ExprKind::Set(_) | ExprKind::Dict { .. } | ExprKind::Constant(ir::Literal::Str(_))
+                );
+                if literal {
+                    fold(expr.span.range.start, expr.span.range.end, None);
+                }
+            }
+        });
+        for body in bodies {
+            let mut rest = body;
+            while let Some(first) = rest.iter().position(|stmt| matches!(stmt.kind, StmtKind::Import(_) | StmtKind::ImportFrom { .. })) {
+                let run = rest[first..].iter().take_while(|stmt| matches!(stmt.kind, StmtKind::Import(_) | StmtKind::ImportFrom { .. })).count();
+                fold(rest[first].span.range.start, rest[first + run - 1].span.range.end, Some(FoldingRangeKind::Imports));
+                rest = &rest[first + run..];
+            }
+        }
+        ranges.sort_by_key(|range| (range.start_line, range.end_line));
+        ranges
+    }
+
+    /// Visit lowered module and extract symbols
+    fn visit_module(&self, stmts: &[ir::Stmt], content: &str, symbols: &mut Vec<SymbolInfo>) {
         for stmt in stmts {
             self.visit_stmt(stmt, content, symbols);
         }
@@ -304,12 +1213,11 @@ impl DocumentAnalyzer {
         (line, col)
     }
 
-    /// Visit AST statement and extract symbols
-    fn visit_stmt(&self, stmt: &ast::Stmt, content: &str, symbols: &mut Vec<SymbolInfo>) {
-        match stmt {
-            ast::Stmt::FunctionDef(func) => {
-                let offset = func.range.start().to_usize();
-                let (line, col) = self.offset_to_position(content, offset);
+    /// Visit lowered statement and extract symbols
+    fn visit_stmt(&self, stmt: &ir::Stmt, content: &str, symbols: &mut Vec<SymbolInfo>) {
+        match &stmt.kind {
+            StmtKind::FunctionDef(func) => {
+                let (line, col) = self.offset_to_position(content, stmt.span.range.start);
 
                 symbols.push(SymbolInfo {
                     name: func.name.to_string(),
@@ -317,30 +1225,28 @@ impl DocumentAnalyzer {
                     line,
                     col,
                     length: func.name.len(),
+                    end: stmt.span.range.end,
                 });
 
                 // Visit parameters
-                for arg in &func.args.args {
-                    let param_offset = arg.def.range.start().to_usize();
-                    let (param_line, param_col) = self.offset_to_position(content, param_offset);
+                for param in func.params.iter().filter(|p| p.kind == ParamKind::Positional) {
+                    let (param_line, param_col) = self.offset_to_position(content, param.span.range.start);
 
                     symbols.push(SymbolInfo {
-                        name: arg.def.arg.to_string(),
+                        name: param.name.to_string(),
                         kind: SymbolKind::Parameter,
                         line: param_line,
                         col: param_col,
-                        length: arg.def.arg.len(),
+                        length: param.name.len(),
+                        end: param.span.range.start + param.name.len(),
                     });
                 }
 
                 // Visit body
-                for stmt in &func.body {
-                    self.visit_stmt(stmt, content, symbols);
-                }
+                self.visit_module(&func.body, content, symbols);
             }
-            ast::Stmt::ClassDef(class) => {
-                let offset = class.range.start().to_usize();
-                let (line, col) = self.offset_to_position(content, offset);
+            StmtKind::ClassDef(class) => {
+                let (line, col) = self.offset_to_position(content, stmt.span.range.start);
 
                 symbols.push(SymbolInfo {
                     name: class.name.to_string(),
@@ -348,25 +1254,24 @@ impl DocumentAnalyzer {
                     line,
                     col,
                     length: class.name.len(),
+                    end: stmt.span.range.end,
                 });
 
                 // Visit body
-                for stmt in &class.body {
-                    self.visit_stmt(stmt, content, symbols);
-                }
+                self.visit_module(&class.body, content, symbols);
             }
-            ast::Stmt::Assign(assign) => {
-                for target in &assign.targets {
-                    if let ast::Expr::Name(name) = target {
-                        let offset = name.range.start().to_usize();
-                        let (line, col) = self.offset_to_position(content, offset);
+            StmtKind::Assign { targets, .. } => {
+                for target in targets {
+                    if let ExprKind::Name(name) = &target.kind {
+                        let (line, col) = self.offset_to_position(content, target.span.range.start);
 
                         symbols.push(SymbolInfo {
-                            name: name.id.to_string(),
+                            name: name.to_string(),
                             kind: SymbolKind::Variable,
                             line,
                             col,
-                            length: name.id.len(),
+                            length: name.len(),
+                            end: target.span.range.end,
                         });
                     }
                 }
@@ -376,7 +1281,7 @@ impl DocumentAnalyzer {
     }
 
     /// Get word at position
-    fn get_word_at_position(&self, content: &str, line: usize, col: usize) -> Option<String> {
+    pub fn get_word_at_position(&self, content: &str, line: usize, col: usize) -> Option<String> {
         let lines: Vec<&str> = content.lines().collect();
         if line >= lines.len() {
             return None;
@@ -392,6 +1297,470 @@ impl DocumentAnalyzer {
     }
 }
 
+/// Name and header (`def f(x: int) -> str`, `class C(Base)`) of a definition
+fn signature(content: &str, stmt: &ir::Stmt) -> Option<(String, String)> {
+    let source = |expr: &ir::Expr| content.get(expr.span.range.clone()).unwrap_or("...").to_string();
+    match &stmt.kind {
+        StmtKind::FunctionDef(func) => {
+            let mut params = Vec::new();
+            let mut starred = false;
+            for (i, param) in func.params.iter().enumerate() {
+                let prefix = match param.kind {
+                    ParamKind::VarArgs => {
+                        starred = true;
+                        "*"
+                    }
+                    ParamKind::VarKeywords => "**",
+                    ParamKind::KeywordOnly if !starred => {
+                        starred = true;
+                        params.push("*".to_string());
+                        ""
+                    }
+                    _ => "",
+                };
+                let mut text = format!("{}{}", prefix, param.name);
+                if let Some(annotation) = &param.annotation {
+                    text = format!("{}: {}", text, source(annotation));
+                }
+                if let Some(default) = &param.default {
+                    let separator = if param.annotation.is_some() { " = " } else { "=" };
+                    text = format!("{}{}{}", text, separator, source(default));
+                }
+                params.push(text);
+                let next = func.params.get(i + 1).map(|p| p.kind);
+                if param.kind == ParamKind::PositionalOnly && next != Some(ParamKind::PositionalOnly) {
+                    params.push("/".to_string());
+                }
+            }
+            let keyword = if func.is_async { "async def" } else { "def" };
+            let returns = func.returns.as_ref().map(|r| format!(" -> {}", source(r))).unwrap_or_default();
+            Some((func.name.to_string(), format!("{} {}({}){}", keyword, func.name, params.join(", "), returns)))
+        }
+        StmtKind::ClassDef(class) => {
+            let bases: Vec<String> = class.bases.iter().map(source).collect();
+            let bases = if bases.is_empty() { String::new() } else { format!("({})", bases.join(", ")) };
+            Some((class.name.to_string(), format!("class {}{}", class.name, bases)))
+        }
+        _ => None,
+    }
+}
+
+/// Signature of the function or class named `name` defined in `body`, at
+/// the top level or failing that nested anywhere. Classes show the
+/// parameters of their `__init__`
+fn definition_signature(content: &str, body: &[ir::Stmt], name: &str, site: &CallSite) -> Option<SignatureInformation> {
+    let stmt = definition(body, name)?;
+    match &stmt.kind {
+        StmtKind::FunctionDef(_) => function_information(content, stmt, name, false, site),
+        StmtKind::ClassDef(_) => match method(body, name, "__init__", &mut Vec::new()) {
+            Some(init) => function_information(content, init, name, true, site),
+            None => Some(signature::signature_information(name, &[], None, docstring(stmt), site)),
+        },
+        _ => None,
+    }
+}
+
+/// Signature of the method `attr` of the document's class `class` or the
+/// classes it derives from, called on an instance when `on_instance` and
+/// otherwise on the class itself
+fn method_signature(content: &str, body: &[ir::Stmt], class: &str, attr: &str, on_instance: bool, site: &CallSite) -> Option<SignatureInformation> {
+    let stmt = method(body, class, attr, &mut Vec::new())?;
+    let StmtKind::FunctionDef(func) = &stmt.kind else { return None };
+    let decorated = |decorator: &str| func.decorators.iter().any(|expr| matches!(&expr.kind, ExprKind::Name(name) if &**name == decorator));
+    let bound = !decorated("staticmethod") && (on_instance || decorated("classmethod"));
+    function_information(content, stmt, attr, bound, site)
+}
+
+/// Signature of `name` as the bundled stub for module `from` defines it
+fn stub_signature(from: &str, name: &str, site: &CallSite) -> Option<SignatureInformation> {
+    let (_, stub) = imports::bundled_stubs().iter().find(|(module, _)| *module == from)?;
+    let ast = parse(stub, Mode::Module, "<stub>").ok()?;
+    let module = lower_module(&ast, None);
+    definition_signature(stub, &module.body, name, site)
+}
+
+/// Signature of the callee of `site` from the types the checker inferred,
+/// with `ty` the type of the receiver, or of the callee when it has none:
+/// parameter names from its signature, their types from its function type.
+/// Without a signature, such as for methods of builtin types, the
+/// parameters are their types alone, passed by position
+fn inferred_signature(checker: &TypeChecker, ty: Option<Type>, site: &CallSite) -> Option<SignatureInformation> {
+    let Ok(Mod::Expression(callee)) = parse(&site.callee, Mode::Expression, "<callee>") else { return None };
+    let sig = checker.call_signature(&callee.body);
+    let (receiver, attr) = site.split();
+    let ty = match receiver {
+        Some(_) => checker.members(&ty?).into_iter().find(|(name, _)| name == attr).and_then(|(_, member)| match member {
+            MemberKind::Method(ty) | MemberKind::StaticMethod(ty) => Some(ty),
+            _ => None,
+        }),
+        None => ty,
+    };
+    let (types, returns) = match ty.map(|ty| match ty {
+        Type::Effect(inner, _) => *inner,
+        ty => ty,
+    }) {
+        Some(Type::Function(params, returns)) => (params, Some(returns.to_string())),
+        _ => (Vec::new(), None),
+    };
+
+    let Some(sig) = sig else {
+        returns.as_ref()?;
+        let params: Vec<SignatureParam> = types.iter()
+            .map(|ty| SignatureParam { name: String::new(), kind: ParamKind::PositionalOnly, label: ty.to_string() })
+            .collect();
+        return Some(signature::signature_information(attr, &params, returns.as_deref(), None, site));
+    };
+    let typed = |name: &str, ty: Option<&Type>| match ty {
+        Some(ty) => format!("{}: {}", name, ty),
+        None => name.to_string(),
+    };
+    let mut params = Vec::new();
+    for (i, param) in sig.params.iter().enumerate() {
+        let kind = if i < sig.posonly { ParamKind::PositionalOnly } else { ParamKind::Positional };
+        let default = if param.has_default { " = ..." } else { "" };
+        params.push(SignatureParam { name: param.name.clone(), kind, label: format!("{}{}", typed(&param.name, types.get(i)), default) });
+    }
+    if let Some(ty) = &sig.varargs {
+        params.push(SignatureParam { name: "args".to_string(), kind: ParamKind::VarArgs, label: typed("*args", Some(ty)) });
+    }
+    for (param, ty) in &sig.kwonly {
+        let default = if param.has_default { " = ..." } else { "" };
+        params.push(SignatureParam { name: param.name.clone(), kind: ParamKind::KeywordOnly, label: format!("{}{}", typed(&param.name, Some(ty)), default) });
+    }
+    if let Some(ty) = &sig.kwargs {
+        params.push(SignatureParam { name: "kwargs".to_string(), kind: ParamKind::VarKeywords, label: typed("**kwargs", Some(ty)) });
+    }
+    Some(signature::signature_information(attr, &params, returns.as_deref(), None, site))
+}
+
+/// Signature of the function defined by `stmt`, shown as `name`, with its
+/// parameters' annotations and defaults as written in `content`. A `bound`
+/// function leaves out its first parameter, the receiver, and shows no
+/// return when it's an `__init__`
+fn function_information(content: &str, stmt: &ir::Stmt, name: &str, bound: bool, site: &CallSite) -> Option<SignatureInformation> {
+    let StmtKind::FunctionDef(func) = &stmt.kind else { return None };
+    let source = |expr: &ir::Expr| content.get(expr.span.range.clone()).unwrap_or("...").to_string();
+    let params: Vec<SignatureParam> = func.params.iter()
+        .skip(usize::from(bound))
+        .map(|param| {
+            let prefix = match param.kind {
+                ParamKind::VarArgs => "*",
+                ParamKind::VarKeywords => "**",
+                _ => "",
+            };
+            let mut label = format!("{}{}", prefix, param.name);
+            if let Some(annotation) = &param.annotation {
+                label = format!("{}: {}", label, source(annotation));
+            }
+            if let Some(default) = &param.default {
+                let separator = if param.annotation.is_some() { " = " } else { "=" };
+                label = format!("{}{}{}", label, separator, source(default));
+            }
+            SignatureParam { name: param.name.to_string(), kind: param.kind, label }
+        })
+        .collect();
+    let returns = func.returns.as_ref().filter(|_| &*func.name != "__init__").map(source);
+    Some(signature::signature_information(name, &params, returns.as_deref(), docstring(stmt), site))
+}
+
+/// The function or class named `name` defined in `body`, preferring the
+/// top level
+fn definition<'r, 'a>(body: &'r [ir::Stmt<'a>], name: &str) -> Option<&'r ir::Stmt<'a>> {
+    let defines = |stmt: &ir::Stmt| match &stmt.kind {
+        StmtKind::FunctionDef(func) => &*func.name == name,
+        StmtKind::ClassDef(class) => &*class.name == name,
+        _ => false,
+    };
+    if let Some(stmt) = body.iter().find(|stmt| defines(stmt)) {
+        return Some(stmt);
+    }
+    let mut found = None;
+    for stmt in body {
+        stmt.walk(&mut |node| {
+            if let NodeRef::Stmt(stmt) = node {
+                if found.is_none() && defines(stmt) {
+                    found = Some(stmt);
+                }
+            }
+        });
+    }
+    found
+}
+
+/// The method `attr` of the class `class` defined in `body`, or of the
+/// first of its bases defining it; `seen` guards against cyclic bases
+fn method<'r, 'a>(body: &'r [ir::Stmt<'a>], class: &str, attr: &str, seen: &mut Vec<String>) -> Option<&'r ir::Stmt<'a>> {
+    if seen.iter().any(|name| name == class) {
+        return None;
+    }
+    seen.push(class.to_string());
+    let StmtKind::ClassDef(def) = &definition(body, class)?.kind else { return None };
+    def.body.iter()
+        .find(|stmt| matches!(&stmt.kind, StmtKind::FunctionDef(func) if &*func.name == attr))
+        .or_else(|| def.bases.iter().find_map(|base| match &base.kind {
+            ExprKind::Name(base) => method(body, base, attr, seen),
+            _ => None,
+        }))
+}
+
+/// Module and name behind `name` when `body` imports it with
+/// `from module import name`, perhaps under an alias
+fn imported_name(body: &[ir::Stmt], name: &str) -> Option<(String, String)> {
+    body.iter().find_map(|stmt| match &stmt.kind {
+        StmtKind::ImportFrom { module: Some(module), names, level: 0 } => names.iter()
+            .find(|alias| &**alias.asname.as_ref().unwrap_or(&alias.name) == name)
+            .map(|alias| (module.to_string(), alias.name.to_string())),
+        _ => None,
+    })
+}
+
+/// Module `receiver` names when `body` imports it: `import os.path`,
+/// `import numpy as np`, `from os import path`
+fn imported_module(body: &[ir::Stmt], receiver: &str) -> Option<String> {
+    body.iter().find_map(|stmt| match &stmt.kind {
+        StmtKind::Import(aliases) => aliases.iter()
+            .find(|alias| &**alias.asname.as_ref().unwrap_or(&alias.name) == receiver)
+            .map(|alias| alias.name.to_string()),
+        StmtKind::ImportFrom { module: Some(module), names, level: 0 } => names.iter()
+            .find(|alias| &**alias.asname.as_ref().unwrap_or(&alias.name) == receiver)
+            .map(|alias| format!("{}.{}", module, alias.name))
+            .filter(|submodule| imports::bundled_stubs().iter().any(|(name, _)| name == submodule)),
+        _ => None,
+    })
+}
+
+/// First paragraph of the docstring of the function or class `stmt` defines
+fn docstring(stmt: &ir::Stmt) -> Option<String> {
+    let body = match stmt.node {
+        Stmt::FunctionDef(def) => &def.body,
+        Stmt::AsyncFunctionDef(def) => &def.body,
+        Stmt::ClassDef(class) => &class.body,
+        _ => return None,
+    };
+    Docstring::of(body).map(|doc| doc.first_paragraph().to_string())
+}
+
+/// `ty` as an annotation, when it can be written as one: builtin types,
+/// containers and unions of them, and the document's own `classes`.
+/// Refinements annotate as the type they refine
+fn annotation_text(ty: &Type, classes: &[String]) -> Option<String> {
+    let all = |types: &[Type]| types.iter().map(|ty| annotation_text(ty, classes)).collect::<Option<Vec<_>>>();
+    Some(match ty {
+        Type::None | Type::Bool | Type::Int | Type::Float | Type::Str | Type::Bytes => ty.to_string(),
+        Type::List(elem) => format!("list[{}]", annotation_text(elem, classes)?),
+        Type::Set(elem) => format!("set[{}]", annotation_text(elem, classes)?),
+        Type::VarTuple(elem) => format!("tuple[{}, ...]", annotation_text(elem, classes)?),
+        Type::Dict(key, value) => format!("dict[{}, {}]", annotation_text(key, classes)?, annotation_text(value, classes)?),
+        Type::Tuple(elems) if elems.is_empty() => "tuple[()]".to_string(),
+        Type::Tuple(elems) => format!("tuple[{}]", all(elems)?.join(", ")),
+        Type::Union(members) => all(members)?.join(" | "),
+        Type::Class(name) if classes.contains(name) => name.clone(),
+        Type::Refinement(base, _) | Type::Dependent(base, _) => annotation_text(base, classes)?,
+        _ => return None,
+    })
+}
+
+/// Whether `expr` is a literal such as `1`, `-2.5` or `"text"`, whose type
+/// needs no hint
+fn is_literal(expr: &ir::Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Constant(_) => true,
+        ExprKind::UnaryOp { operand, .. } => matches!(operand.kind, ExprKind::Constant(_)),
+        _ => false,
+    }
+}
+
+/// Byte offset of the name of the function or class defined at byte
+/// `start`, after any decorators; `start` if it can't be found
+fn name_offset(content: &str, start: usize, name: &str) -> usize {
+    lex(&content[start..], Mode::Module).flatten()
+        .skip_while(|(token, _)| !matches!(token, Tok::Def | Tok::Class))
+        .find(|(token, _)| matches!(token, Tok::Name { name: found } if found.as_str() == name))
+        .map_or(start, |(_, range)| start + range.start().to_usize())
+}
+
+/// Byte offset of the `:` ending the header of the function or class
+/// defined at byte `start`
+fn header_end(content: &str, start: usize) -> Option<usize> {
+    let mut depth = 0;
+    let tokens = lex(&content[start..], Mode::Module).flatten().skip_while(|(token, _)| !matches!(token, Tok::Def | Tok::Class));
+    for (token, range) in tokens {
+        match token {
+            Tok::Lpar | Tok::Lsqb | Tok::Lbrace => depth += 1,
+            Tok::Rpar | Tok::Rsqb | Tok::Rbrace => depth -= 1,
+            Tok::Colon if depth == 0 => return Some(start + range.start().to_usize()),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Byte offset just past the `)` closing the parameters of the function
+/// defined at byte `start`, after any decorators
+fn params_end(content: &str, start: usize) -> Option<usize> {
+    let mut depth = 0;
+    let tokens = lex(&content[start..], Mode::Module).flatten().skip_while(|(token, _)| *token != Tok::Def);
+    for (token, range) in tokens {
+        match token {
+            Tok::Lpar => depth += 1,
+            Tok::Rpar if depth == 1 => return Some(start + range.end().to_usize()),
+            Tok::Rpar => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Names a stub defines itself, not the ones it imports
+fn stub_definitions(body: &[Stmt]) -> impl Iterator<Item = &str> {
+    body.iter().filter_map(|stmt| match stmt {
+        Stmt::FunctionDef(def) => Some(def.name.as_str()),
+        Stmt::ClassDef(class) => Some(class.name.as_str()),
+        Stmt::AnnAssign(assign) => assign.target.as_name_expr().map(|name| name.id.as_str()),
+        Stmt::Assign(assign) => assign.targets.first().and_then(|target| target.as_name_expr()).map(|name| name.id.as_str()),
+        _ => None,
+    })
+}
+
+/// Byte offset a new import goes at: the start of the line after the
+/// imports at the top of `body`, after its docstring and, failing those,
+/// the start of the document. With a prefix when the document's last line
+/// has no newline to end it
+fn import_insert_offset(content: &str, body: &[Stmt]) -> (usize, &'static str) {
+    let docstring = body.first().is_some_and(|stmt| stmt.as_expr_stmt().is_some_and(|expr| expr.value.is_constant_expr()));
+    let rest = &body[usize::from(docstring)..];
+    let imports = rest.iter().take_while(|stmt| matches!(stmt, Stmt::Import(_) | Stmt::ImportFrom(_))).last();
+    let Some(last) = imports.or(body.first().filter(|_| docstring)) else { return (0, "") };
+    let end = last.end().to_usize();
+    match content[end..].find('\n') {
+        Some(newline) => (end + newline + 1, ""),
+        None => (content.len(), "\n"),
+    }
+}
+
+/// The diagnostic for a parse failure, placed where the parser stopped
+fn syntax_error(err: &rustpython_parser::ParseError) -> TypeError {
+    let offset = err.offset.to_usize();
+    TypeError {
+        range: Some(offset..offset),
+        message: format!("Syntax error: {}", err.error),
+        code: None,
+        severity: Severity::Error,
+    }
+}
+
+/// Names bound in `body` and, for the function or class around byte
+/// `offset`, in its own scope, as (qualified name, name); qualified names
+/// follow the checker's, such as `f.x` and `C.m.x`
+fn scope_names(body: &[Stmt], offset: usize, scope: Option<&str>, names: &mut Vec<(String, String)>) {
+    let qualify = |name: &str| scope.map_or_else(|| name.to_string(), |scope| format!("{}.{}", scope, name));
+    names.extend(bindings::binding_sites(body).into_iter().map(|(name, _)| (qualify(&name), name)));
+    match enclosing(body, offset) {
+        Some(Stmt::FunctionDef(def)) => function_scope_names(&qualify(&def.name), &def.args, &def.body, offset, names),
+        Some(Stmt::AsyncFunctionDef(def)) => function_scope_names(&qualify(&def.name), &def.args, &def.body, offset, names),
+        // Class-level names are in scope in the class body, not its methods
+        Some(Stmt::ClassDef(class)) => {
+            let class_scope = qualify(&class.name);
+            match enclosing(&class.body, offset) {
+                Some(Stmt::FunctionDef(def)) => {
+                    function_scope_names(&format!("{}.{}", class_scope, def.name), &def.args, &def.body, offset, names)
+                }
+                Some(Stmt::AsyncFunctionDef(def)) => {
+                    function_scope_names(&format!("{}.{}", class_scope, def.name), &def.args, &def.body, offset, names)
+                }
+                _ => scope_names(&class.body, offset, Some(&class_scope), names),
+            }
+        }
+        _ => {}
+    }
+}
+
+fn function_scope_names(scope: &str, args: &Arguments, body: &[Stmt], offset: usize, names: &mut Vec<(String, String)>) {
+    names.extend(bindings::parameter_sites(args).into_iter().map(|(name, _)| (format!("{}.{}", scope, name), name)));
+    scope_names(body, offset, Some(scope), names);
+}
+
+/// The function or class definition in `body` around byte `offset`,
+/// looking inside compound statements
+fn enclosing(body: &[Stmt], offset: usize) -> Option<&Stmt> {
+    let stmt = body.iter().find(|stmt| stmt.start().to_usize() <= offset && offset <= stmt.end().to_usize())?;
+    let blocks: Vec<&[Stmt]> = match stmt {
+        Stmt::FunctionDef(_) | Stmt::AsyncFunctionDef(_) | Stmt::ClassDef(_) => return Some(stmt),
+        Stmt::For(node) => vec![&node.body, &node.orelse],
+        Stmt::AsyncFor(node) => vec![&node.body, &node.orelse],
+        Stmt::While(node) => vec![&node.body, &node.orelse],
+        Stmt::If(node) => vec![&node.body, &node.orelse],
+        Stmt::With(node) => vec![&node.body],
+        Stmt::AsyncWith(node) => vec![&node.body],
+        Stmt::Try(node) => {
+            let handlers = node.handlers.iter().map(|ExceptHandler::ExceptHandler(handler)| handler.body.as_slice());
+            [node.body.as_slice()].into_iter().chain(handlers).chain([node.orelse.as_slice(), node.finalbody.as_slice()]).collect()
+        }
+        Stmt::Match(node) => node.cases.iter().map(|case| case.body.as_slice()).collect(),
+        _ => return None,
+    };
+    blocks.into_iter().find_map(|block| enclosing(block, offset))
+}
+
+/// Byte offset of the `col`th character of line `line`
+fn position_to_offset(content: &str, line: usize, col: usize) -> Option<usize> {
+    let start: usize = content.split_inclusive('\n').take(line).map(str::len).sum();
+    let text = content.split_inclusive('\n').nth(line)?;
+    let (index, _) = text.char_indices().nth(col).filter(|(_, ch)| *ch != '\n' && *ch != '\r')?;
+    Some(start + index)
+}
+
+/// Byte offset of the cursor at character `col` of line `line`, or of the
+/// end of the line when it's past it
+fn cursor_offset(content: &str, line: usize, col: usize) -> usize {
+    let start: usize = content.split_inclusive('\n').take(line).map(str::len).sum();
+    let text = content.split_inclusive('\n').nth(line).unwrap_or("").trim_end_matches(['\n', '\r']);
+    start + text.char_indices().nth(col).map_or(text.len(), |(index, _)| index)
+}
+
+/// Byte offsets each line starts at, to place semantic tokens
+struct LineStarts(Vec<usize>);
+
+impl LineStarts {
+    fn new(content: &str) -> Self {
+        Self(std::iter::once(0).chain(content.match_indices('\n').map(|(i, _)| i + 1)).collect())
+    }
+
+    /// Line and UTF-16 column of byte `offset`
+    fn position(&self, content: &str, offset: usize) -> (u32, u32) {
+        let line = self.0.partition_point(|&start| start <= offset) - 1;
+        (line as u32, content[self.0[line]..offset].encode_utf16().count() as u32)
+    }
+}
+
+/// The name a decorator is written with: `property`, the `setter` of
+/// `@value.setter`, the `wraps` of `@functools.wraps(f)`
+fn decorator_name(decorator: &ir::Expr) -> Option<std::ops::Range<usize>> {
+    match &decorator.kind {
+        ExprKind::Name(_) => Some(decorator.span.range.clone()),
+        ExprKind::Attribute { attr, .. } => Some(decorator.span.range.end - attr.len()..decorator.span.range.end),
+        ExprKind::Call { func, .. } => decorator_name(func),
+        _ => None,
+    }
+}
+
+/// Whether `name` can name a variable: an identifier that isn't a keyword
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|first| first.is_alphabetic() || first == '_')
+        && chars.all(|ch| ch.is_alphanumeric() || ch == '_')
+        && !completion::PYTHON_KEYWORDS.contains(&name)
+}
+
+/// Whether byte `offset` is inside a string literal or a comment
+fn in_string_or_comment(content: &str, offset: usize) -> bool {
+    lex(content, Mode::Module).flatten().any(|(token, range)| {
+        matches!(token, Tok::String { .. } | Tok::Comment(_))
+            && range.start().to_usize() <= offset
+            && offset < range.end().to_usize()
+    })
+}
+
 /// Extract word at position
 fn extract_word_at_position(line: &str, col: usize) -> String {
     let chars: Vec<char> = line.chars().collect();
@@ -441,6 +1810,48 @@ mod tests {
         assert!(errors.len() > 0);
     }
 
+    #[test]
+    fn test_checker_errors_span_the_offending_token() {
+        let analyzer = DocumentAnalyzer::new();
+        let code = "def size(text: str) -> int:\n    return text\n";
+        let (checked, _) = analyzer.check_document(code, None, None).unwrap();
+
+        let range = checked.errors[0].range.clone().unwrap();
+        assert_eq!(&code[range.clone()], "text");
+        assert_eq!(analyzer.lsp_range(code, &range), Range {
+            start: Position { line: 1, character: 11 },
+            end: Position { line: 1, character: 15 },
+        });
+    }
+
+    #[test]
+    fn test_analyze_reports_type_errors() {
+        let analyzer = DocumentAnalyzer::new();
+        let code = "x: int = \"s\"\n";
+        let errors = analyzer.analyze(code);
+
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert_eq!(errors[0].code, Some("TYP001"));
+        assert_eq!(errors[0].severity, Severity::Error);
+        let range = errors[0].range.clone().unwrap();
+        assert_eq!(&code[range.clone()], "\"s\"");
+        assert_eq!(analyzer.lsp_range(code, &range), Range {
+            start: Position { line: 0, character: 9 },
+            end: Position { line: 0, character: 12 },
+        });
+    }
+
+    #[test]
+    fn test_syntax_errors_keep_their_location() {
+        let analyzer = DocumentAnalyzer::new();
+        let code = "x = 1\ny = (\n";
+        let errors = analyzer.analyze(code);
+
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.starts_with("Syntax error"));
+        assert!(analyzer.lsp_range(code, errors[0].range.as_ref().unwrap()).start.line > 0);
+    }
+
     #[test]
     fn test_extract_symbols_functions() {
         let analyzer = DocumentAnalyzer::new();
@@ -517,6 +1928,36 @@ mod tests {
         assert_eq!(def.line, 0); // Definition is on first line
     }
 
+    #[test]
+    fn test_hover_shows_inferred_types() {
+        let analyzer = DocumentAnalyzer::new();
+        let code = "count: int = 3\nnames = [\"a\", \"b\"]\ntotal = count + len(names)\n";
+
+        assert_eq!(analyzer.get_hover_info(code, 0, 2).unwrap(), "```python\ncount: int\n```");
+        assert_eq!(analyzer.get_hover_info(code, 1, 0).unwrap(), "```python\nnames: list[str]\n```");
+        assert_eq!(analyzer.get_hover_info(code, 2, 22).unwrap(), "```python\nnames: list[str]\n```");
+    }
+
+    #[test]
+    fn test_hover_shows_function_signature_and_effects() {
+        let analyzer = DocumentAnalyzer::new();
+        let code = "def shout(text: str, times: int) -> bool:\n    \"\"\"Print loudly.\"\"\"\n    print(text * times)\n    return True\n\nshout(\"hi\", 2)  # shout twice\n";
+
+        let expected = "```python\nshout: (str, int) -> bool ! {IO}\n```\n\nPrint loudly.";
+        assert_eq!(analyzer.get_hover_info(code, 0, 5).unwrap(), expected);
+        assert_eq!(analyzer.get_hover_info(code, 5, 1).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_no_hover_in_strings_or_comments() {
+        let analyzer = DocumentAnalyzer::new();
+        let code = "greeting = \"count\"  # count\n";
+
+        assert_eq!(analyzer.get_hover_info(code, 0, 13), None);
+        assert_eq!(analyzer.get_hover_info(code, 0, 24), None);
+        assert!(analyzer.get_hover_info(code, 0, 2).is_some());
+    }
+
     #[test]
     fn test_hover_builtin_types() {
         let analyzer = DocumentAnalyzer::new();
@@ -538,6 +1979,72 @@ mod tests {
         assert!(completions.iter().any(|c| c.label == "append"));
     }
 
+    #[test]
+    fn test_completions_follow_the_receiver_type() {
+        let analyzer = DocumentAnalyzer::new();
+        let code = "greeting = \"abc\".\n";
+        let labels: Vec<String> = analyzer.get_completions(code, 0, 17).into_iter().map(|c| c.label).collect();
+
+        assert!(labels.contains(&"upper".to_string()) && labels.contains(&"split".to_string()), "{:?}", labels);
+        assert!(!labels.contains(&"append".to_string()));
+
+        let split = analyzer.get_completions(code, 0, 17).into_iter().find(|c| c.label == "split").unwrap();
+        assert_eq!(split.kind, CompletionItemKind::METHOD);
+        assert_eq!(split.detail, "split(str) -> list[str]");
+    }
+
+    #[test]
+    fn test_completions_for_user_classes() {
+        let analyzer = DocumentAnalyzer::new();
+        let code = "\
+class Point:
+    def __init__(self, x: int, label: str):
+        self.x = x
+        self.label = label
+
+    def norm(self) -> int:
+        return self.x
+
+origin = Point(0, \"o\")
+origin.la
+";
+        let completions = analyzer.get_completions(code, 9, 9);
+        let summary: Vec<(String, CompletionItemKind, String)> = completions.into_iter().map(|c| (c.label, c.kind, c.detail)).collect();
+        assert_eq!(summary, vec![
+            ("label".to_string(), CompletionItemKind::PROPERTY, "label: str".to_string()),
+            ("norm".to_string(), CompletionItemKind::METHOD, "norm() -> int".to_string()),
+            ("x".to_string(), CompletionItemKind::PROPERTY, "x: int".to_string()),
+        ]);
+
+        let in_method = code.replace("self.x\n", "self.\n");
+        let labels: Vec<String> = analyzer.get_completions(&in_method, 6, 20).into_iter().map(|c| c.label).collect();
+        assert_eq!(labels, vec!["label", "norm", "x"]);
+    }
+
+    #[test]
+    fn test_completions_include_names_in_scope() {
+        let analyzer = DocumentAnalyzer::new();
+        let code = "\
+limit = 10
+
+def scale(factor: float) -> float:
+    step = factor * 2
+    return s
+
+def other() -> None:
+    pass
+";
+        let completions = analyzer.get_completions(code, 4, 12);
+        let detail = |label: &str| completions.iter().find(|c| c.label == label).map(|c| c.detail.clone());
+        assert_eq!(detail("limit").as_deref(), Some("int"));
+        assert_eq!(detail("factor").as_deref(), Some("float"));
+        assert_eq!(detail("step").as_deref(), Some("float"));
+
+        let outside = analyzer.get_completions(code, 7, 4);
+        assert!(outside.iter().any(|c| c.label == "limit"));
+        assert!(!outside.iter().any(|c| c.label == "step" || c.label == "factor"));
+    }
+
     #[test]
     fn test_completions_keywords() {
         let analyzer = DocumentAnalyzer::new();
@@ -565,5 +2072,142 @@ mod tests {
         // Test middle of second line
         assert_eq!(analyzer.offset_to_position(code, 9), (1, 3));
     }
-}
 
+    #[test]
+    fn test_boolean_positional_quick_fix() {
+        let lints = [("S001".to_string(), LintLevel::Warn)].into_iter().collect();
+        let analyzer = DocumentAnalyzer::new().with_lints(lints);
+        let code = "def resize(image, keepdim, inplace):\n    pass\nresize(img, True, False)\n";
+
+        let line = Range { start: Position { line: 2, character: 0 }, end: Position { line: 2, character: 24 } };
+        let fixes = analyzer.lint_fixes(code, line);
+        assert_eq!(fixes.len(), 2);
+        assert_eq!(fixes[0].title, "Pass as keyword argument keepdim=True");
+
+        let inserted: Vec<_> = fixes[0].edits.iter().map(|e| (e.range.start, e.new_text.as_str())).collect();
+        assert_eq!(inserted, vec![
+            (Position { line: 2, character: 12 }, "keepdim="),
+            (Position { line: 2, character: 18 }, "inplace="),
+        ]);
+    }
+
+    #[test]
+    fn test_extract_symbols_stress_fixture() {
+        let analyzer = DocumentAnalyzer::new();
+        let code = include_str!("../../typthon-core/tests/fixtures/ir/stress.py");
+        let symbols: Vec<_> = analyzer.extract_symbols(code).into_iter()
+            .map(|s| format!("{:?} {} {}:{}", s.kind, s.name, s.line, s.col))
+            .collect();
+
+        assert_eq!(symbols, vec![
+            "Variable counter 9:0", "Variable a 11:0", "Variable b 11:4", "Variable mapping 13:0",
+            "Variable unique 14:0", "Variable global_name 16:0",
+            "Function pure 20:0", "Parameter x 20:9", "Parameter y 20:17",
+            "Function fetch 25:0", "Parameter url 25:16",
+            "Function generator 32:0", "Parameter n 32:14", "Variable z 35:4",
+            "Function noisy 38:0", "Parameter items 38:10", "Variable squares 70:4",
+            "Variable evens 71:4", "Variable table 72:4", "Variable lazy 73:4", "Variable sliced 74:4",
+            "Function outer 80:0", "Variable value 81:4", "Function inner 83:4",
+            "Class Shape 91:0", "Function area 94:4", "Parameter self 94:13",
+        ]);
+    }
+
+    #[test]
+    fn test_float_equality_quick_fix_adds_import() {
+        let lints = [("C001".to_string(), LintLevel::Warn)].into_iter().collect();
+        let analyzer = DocumentAnalyzer::new().with_lints(lints);
+        let code = "def same(a: float, b: float) -> bool:\n    return a == b\n";
+
+        let line = Range { start: Position { line: 1, character: 0 }, end: Position { line: 1, character: 17 } };
+        let fixes = analyzer.lint_fixes(code, line);
+        assert_eq!(fixes.len(), 1);
+        assert_eq!(fixes[0].title, "Use math.isclose(a, b)");
+
+        let edits: Vec<_> = fixes[0].edits.iter().map(|e| (e.range.start, e.new_text.as_str())).collect();
+        assert_eq!(edits, vec![
+            (Position { line: 1, character: 11 }, "math.isclose("),
+            (Position { line: 1, character: 12 }, ", "),
+            (Position { line: 1, character: 17 }, ")"),
+            (Position { line: 0, character: 0 }, "import math\n"),
+        ]);
+    }
+
+    #[test]
+    fn test_import_fixes_name_every_stub_defining_the_name() {
+        let analyzer = DocumentAnalyzer::new();
+        let fix = |code: &str, name: &str| -> Vec<(String, Position, String)> {
+            analyzer.import_fixes(code, name).into_iter()
+                .map(|fix| (fix.title, fix.edits[0].range.start, fix.edits[0].new_text.clone()))
+                .collect()
+        };
+        let top = Position { line: 0, character: 0 };
+
+        assert_eq!(fix("print(json.dumps([]))\n", "json"), vec![("Add `import json`".to_string(), top, "import json\n".to_string())]);
+        let titles: Vec<String> = fix("print(sep)\n", "sep").into_iter().map(|(title, ..)| title).collect();
+        assert_eq!(titles, vec!["Add `from os import sep`", "Add `from os.path import sep`"]);
+        assert!(fix("print(unknown)\n", "unknown").is_empty());
+
+        // After the docstring, which may end the document
+        let after = Position { line: 0, character: 10 };
+        assert_eq!(fix("\"\"\"Doc.\"\"\"", "sqrt"), vec![("Add `from math import sqrt`".to_string(), after, "\nfrom math import sqrt\n".to_string())]);
+    }
+
+    #[test]
+    fn test_document_symbols_nest_methods_under_classes() {
+        let analyzer = DocumentAnalyzer::new();
+        let code = "\
+import os
+import sys
+
+LIMIT = 3
+
+class Shape:
+    sides = 0
+
+    @property
+    def area(self) -> float:
+        def helper() -> float:
+            return 1.0
+        return helper()
+
+def build(
+    name: str,
+) -> Shape:
+    return Shape()
+";
+        fn outline(symbols: &[DocumentSymbol], depth: usize, out: &mut Vec<String>) {
+            for symbol in symbols {
+                let (range, name) = (symbol.range, symbol.selection_range);
+                out.push(format!(
+                    "{}{:?} {} {}:{}-{}:{} name {}:{}",
+                    "  ".repeat(depth), symbol.kind, symbol.name,
+                    range.start.line, range.start.character, range.end.line, range.end.character,
+                    name.start.line, name.start.character,
+                ));
+                outline(symbol.children.as_deref().unwrap_or_default(), depth + 1, out);
+            }
+        }
+        let mut lines = Vec::new();
+        outline(&analyzer.document_symbols(code), 0, &mut lines);
+        assert_eq!(lines, vec![
+            "Variable LIMIT 3:0-3:5 name 3:0",
+            "Class Shape 5:0-12:23 name 5:6",
+            "  Variable sides 6:4-6:9 name 6:4",
+            "  Method area 9:4-12:23 name 9:8",
+            "    Function helper 10:8-11:22 name 10:12",
+            "Function build 14:0-17:18 name 14:4",
+        ]);
+
+        // Bodies fold from the line their header ends on; imports together
+        let folds: Vec<(u32, u32, Option<FoldingRangeKind>)> = analyzer.folding_ranges(code).into_iter()
+            .map(|range| (range.start_line, range.end_line, range.kind))
+            .collect();
+        assert_eq!(folds, vec![
+            (0, 1, Some(FoldingRangeKind::Imports)),
+            (5, 12, None),
+            (9, 12, None),
+            (10, 11, None),
+            (16, 17, None),
+        ]);
+    }
+}
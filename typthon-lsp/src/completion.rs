@@ -51,3 +51,56 @@ pub fn get_completion_kind(name: &str) -> CompletionItemKind {
     }
 }
 
+
+/// The expression `line` ends with, for completing its members after a
+/// dot: names, attribute chains, calls, subscripts and string literals,
+/// e.g. `items[0].name` in `print(items[0].name`. `None` when `line` doesn't
+/// end with one
+pub fn receiver_expression(line: &str) -> Option<&str> {
+    let chars: Vec<(usize, char)> = line.char_indices().collect();
+    let mut start = line.len();
+    let mut depth = 0usize;
+    let mut i = chars.len();
+    while i > 0 {
+        let (offset, ch) = chars[i - 1];
+        match ch {
+            ')' | ']' | '}' => depth += 1,
+            '(' | '[' | '{' if depth > 0 => depth -= 1,
+            '"' | '\'' => {
+                // Back to the opening quote
+                let close = i - 1;
+                i = close;
+                while i > 0 && !(chars[i - 1].1 == ch && (i < 2 || chars[i - 2].1 != '\\')) {
+                    i -= 1;
+                }
+                if i == 0 {
+                    return None;
+                }
+                i -= 1;
+                start = chars[i].0;
+                continue;
+            }
+            _ if depth > 0 => {}
+            ch if ch.is_alphanumeric() || ch == '_' || ch == '.' => {}
+            _ => break,
+        }
+        start = offset;
+        i -= 1;
+    }
+    let receiver = line[start..].trim();
+    (depth == 0 && !receiver.is_empty() && !receiver.starts_with('.')).then_some(receiver)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_receiver_expression() {
+        assert_eq!(receiver_expression("x = \"abc\""), Some("\"abc\""));
+        assert_eq!(receiver_expression("print(items[0].name"), Some("items[0].name"));
+        assert_eq!(receiver_expression("    self.load(path, mode='r')"), Some("self.load(path, mode='r')"));
+        assert_eq!(receiver_expression("total = count + "), None);
+        assert_eq!(receiver_expression("f(a, b"), Some("b"));
+    }
+}
@@ -0,0 +1,167 @@
+/*!
+Open documents, edited in place by incremental changes.
+
+Editor positions count characters in UTF-16 code units, the protocol's
+default encoding, so a character outside the Basic Multilingual Plane (most
+emoji) is two units wide.
+*/
+
+use std::ops::Deref;
+use tower_lsp::lsp_types::{Position, TextDocumentContentChangeEvent};
+
+/// Text of an open document with where each line starts, so positions
+/// resolve without rescanning the text before them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Document {
+    text: String,
+    /// Byte offset each line starts at; the first is 0
+    line_starts: Vec<usize>,
+}
+
+impl Document {
+    pub fn new(text: String) -> Self {
+        let mut document = Self { text, line_starts: vec![0] };
+        document.index_lines_from(0);
+        document
+    }
+
+    /// Apply `changes` in order, each to the text the one before left, as
+    /// the protocol orders them
+    pub fn apply_changes(&mut self, changes: &[TextDocumentContentChangeEvent]) {
+        for change in changes {
+            self.apply(change);
+        }
+    }
+
+    /// Replace the change's range, or the whole text when it has none
+    pub fn apply(&mut self, change: &TextDocumentContentChangeEvent) {
+        let Some(range) = change.range else {
+            *self = Self::new(change.text.clone());
+            return;
+        };
+        let start = self.offset(range.start);
+        let end = self.offset(range.end).max(start);
+        self.text.replace_range(start..end, &change.text);
+        let line = range.start.line as usize;
+        self.index_lines_from(line.min(self.line_starts.len() - 1));
+    }
+
+    /// Byte offset of `position`. Characters past the end of a line clamp to
+    /// it, lines past the last to the end of the text, and a position inside
+    /// a surrogate pair to the character after it
+    pub fn offset(&self, position: Position) -> usize {
+        let Some(&start) = self.line_starts.get(position.line as usize) else { return self.text.len() };
+        let end = self.line_starts.get(position.line as usize + 1).copied().unwrap_or(self.text.len());
+        let line = self.text[start..end].trim_end_matches(['\n', '\r']);
+        let mut units = 0;
+        for (i, ch) in line.char_indices() {
+            if units >= position.character as usize {
+                return start + i;
+            }
+            units += ch.len_utf16();
+        }
+        start + line.len()
+    }
+
+    /// Recompute where lines start from line `line` on, whose start is
+    /// still right
+    fn index_lines_from(&mut self, line: usize) {
+        let start = self.line_starts[line];
+        self.line_starts.truncate(line + 1);
+        let newlines = self.text[start..].match_indices('\n').map(|(i, _)| start + i + 1);
+        self.line_starts.extend(newlines);
+    }
+}
+
+impl Deref for Document {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp::lsp_types::Range;
+
+    fn edit(start: (u32, u32), end: (u32, u32), text: &str) -> TextDocumentContentChangeEvent {
+        TextDocumentContentChangeEvent {
+            range: Some(Range { start: Position::new(start.0, start.1), end: Position::new(end.0, end.1) }),
+            range_length: None,
+            text: text.to_string(),
+        }
+    }
+
+    /// Apply `change` the slow way: walk the whole text counting UTF-16 units
+    fn reference(text: &str, change: &TextDocumentContentChangeEvent) -> String {
+        let Some(range) = change.range else { return change.text.clone() };
+        let offset = |position: Position| {
+            let (mut line, mut units) = (0, 0);
+            for (i, ch) in text.char_indices() {
+                if line == position.line && (units >= position.character || ch == '\n' || ch == '\r') {
+                    return i;
+                }
+                if ch == '\n' {
+                    if line == position.line {
+                        return i;
+                    }
+                    line += 1;
+                    units = 0;
+                } else if line == position.line {
+                    units += ch.len_utf16() as u32;
+                }
+            }
+            text.len()
+        };
+        let (start, end) = (offset(range.start), offset(range.end));
+        format!("{}{}{}", &text[..start], change.text, &text[end.max(start)..])
+    }
+
+    fn check(text: &str, changes: &[TextDocumentContentChangeEvent]) -> String {
+        let mut document = Document::new(text.to_string());
+        document.apply_changes(changes);
+        let expected = changes.iter().fold(text.to_string(), |text, change| reference(&text, change));
+        assert_eq!(&*document, expected);
+        assert_eq!(document, Document::new(expected.clone()), "line index out of date");
+        expected
+    }
+
+    #[test]
+    fn test_out_of_order_edits_apply_in_sequence() {
+        let text = "def f(x):\n    return x\n\nprint(f(1))\n";
+        let result = check(text, &[
+            edit((3, 8), (3, 9), "2"),
+            edit((0, 6), (0, 7), "value: int"),
+            edit((1, 11), (1, 12), "value * 2"),
+            edit((2, 0), (2, 0), "# doubled\n"),
+        ]);
+        assert_eq!(result, "def f(value: int):\n    return value * 2\n# doubled\n\nprint(f(2))\n");
+    }
+
+    #[test]
+    fn test_positions_count_utf16_units() {
+        let text = "label = \"🎉 party 🎉\"\nx = 1\n";
+        // The emoji are two units each: "party" starts at unit 12
+        let result = check(text, &[edit((0, 12), (0, 17), "fête"), edit((0, 17), (0, 19), "✨")]);
+        assert_eq!(result, "label = \"🎉 fête ✨\"\nx = 1\n");
+    }
+
+    #[test]
+    fn test_edits_across_lines_and_past_the_end() {
+        let text = "a = 1\r\nb = 2\nc = 3";
+        check(text, &[
+            edit((0, 4), (1, 3), "10\nb ="),
+            edit((2, 2), (2, 99), "= 30"),
+            edit((9, 0), (9, 0), "\nd = 4"),
+            edit((1, 0), (2, 0), ""),
+        ]);
+    }
+
+    #[test]
+    fn test_full_changes_replace_the_text() {
+        let full = TextDocumentContentChangeEvent { range: None, range_length: None, text: "y = 2\n".to_string() };
+        assert_eq!(check("x = 1\n", &[edit((0, 0), (0, 1), "z"), full, edit((0, 4), (0, 5), "3")]), "y = 3\n");
+    }
+}
@@ -7,90 +7,510 @@ Provides editor integration for:
 - Go to definition
 - Hover information
 - Diagnostics
+
+Editor settings live under the `typthon` section and are re-read on
+`workspace/didChangeConfiguration`; see `settings.rs` for the keys.
 */
 
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer, LspService, Server};
 use dashmap::DashMap;
-use std::sync::Arc;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use typthon::compiler::analysis::{LintDiagnostic, ModuleResolver};
+use typthon::compiler::errors::codes::{UNDEFINED_FUNCTION, UNDEFINED_VARIABLE};
+use typthon::compiler::errors::Severity;
+use typthon::compiler::frontend::config::Config as ProjectConfig;
+use typthon::infrastructure::PathNormalizer;
 
 mod analyzer;
 mod diagnostics;
+mod document;
 mod completion;
+mod organize;
+mod semantic_tokens;
+mod settings;
+mod signature;
+mod workspace;
+
+use analyzer::{CheckPath, CheckedDocument, DocumentAnalyzer};
+use document::Document;
+use settings::{EditorSettings, ServerSettings, Strictness, TraceLevel, CONFIG_FILES};
+use workspace::{workspace_files, WorkspaceIndex};
 
-use analyzer::DocumentAnalyzer;
+const CONFIGURATION_REGISTRATION: &str = "typthon-configuration";
+const WATCHER_REGISTRATION: &str = "typthon-watchers";
 
 /// The Typthon Language Server
 pub struct TypthonLanguageServer {
     client: Client,
-    documents: Arc<DashMap<String, String>>,
-    analyzer: Arc<DocumentAnalyzer>,
+    /// Open documents by `document_key`, so differently spelled URIs for
+    /// one file share an entry
+    documents: Arc<DashMap<String, Document>>,
+    /// URI each document was last opened or edited under, for publishing
+    uris: DashMap<String, Url>,
+    paths: PathNormalizer,
+    analyzer: RwLock<Arc<DocumentAnalyzer>>,
+    /// Python files of the workspace folders, for cross-file navigation and
+    /// the files to re-check after one changes
+    workspace: RwLock<Arc<WorkspaceIndex>>,
+    /// Whether the workspace folders have been indexed yet
+    indexed: AtomicBool,
+    /// Workspace root, where the project config is discovered
+    root: RwLock<PathBuf>,
+    project: RwLock<ProjectConfig>,
+    /// Last valid values from the editor's `typthon` section
+    editor: RwLock<EditorSettings>,
+    settings: RwLock<ServerSettings>,
+    /// Edit counter per document, so debounced analyses of stale text are skipped
+    generations: DashMap<String, u64>,
+    /// Last type check per document, reused when an edit stays inside one function
+    checked: DashMap<String, CheckedDocument>,
+    /// Semantic tokens last sent per document and their result id, which
+    /// delta requests send edits against
+    semantic_tokens: DashMap<String, (String, Vec<SemanticToken>)>,
+    semantic_results: AtomicU64,
+    /// Analyses that reused the previous check, and ones that checked from scratch
+    incremental_hits: AtomicU64,
+    full_reanalyses: AtomicU64,
+    dynamic_configuration: AtomicBool,
+    dynamic_watchers: AtomicBool,
 }
 
 impl TypthonLanguageServer {
     pub fn new(client: Client) -> Self {
+        let (project, root) = ProjectConfig::discover_with_root();
+        let settings = ServerSettings::resolve(&EditorSettings::default(), &project);
+        let workspace = Arc::new(WorkspaceIndex::new([root.clone()]));
         Self {
             client,
             documents: Arc::new(DashMap::new()),
-            analyzer: Arc::new(DocumentAnalyzer::new()),
+            uris: DashMap::new(),
+            paths: PathNormalizer::native(),
+            analyzer: RwLock::new(Arc::new(build_analyzer(&settings, workspace.modules()))),
+            workspace: RwLock::new(workspace),
+            indexed: AtomicBool::new(false),
+            root: RwLock::new(root),
+            project: RwLock::new(project),
+            editor: RwLock::new(EditorSettings::default()),
+            settings: RwLock::new(settings),
+            generations: DashMap::new(),
+            checked: DashMap::new(),
+            semantic_tokens: DashMap::new(),
+            semantic_results: AtomicU64::new(0),
+            incremental_hits: AtomicU64::new(0),
+            full_reanalyses: AtomicU64::new(0),
+            dynamic_configuration: AtomicBool::new(false),
+            dynamic_watchers: AtomicBool::new(false),
         }
     }
 
-    async fn analyze_document(&self, uri: &str) {
-        if let Some(content) = self.documents.get(uri) {
-            let diagnostics = self.analyzer.analyze(content.value());
+    /// Treat file URIs with `paths` instead of the platform's rules
+    pub fn with_paths(mut self, paths: PathNormalizer) -> Self {
+        self.paths = paths;
+        self
+    }
 
-            let lsp_diagnostics: Vec<Diagnostic> = diagnostics
-                .into_iter()
-                .map(|d| Diagnostic {
-                    range: Range {
-                        start: Position {
-                            line: d.line as u32,
-                            character: d.col as u32,
-                        },
-                        end: Position {
-                            line: d.line as u32,
-                            character: (d.col + 10) as u32, // Approximate end
-                        },
-                    },
-                    severity: Some(DiagnosticSeverity::ERROR),
-                    code: None,
-                    source: Some("typthon".to_string()),
-                    message: d.message,
-                    related_information: None,
-                    tags: None,
-                    code_description: None,
-                    data: None,
-                })
-                .collect();
+    /// Key for the `documents` map; non-file URIs are used as-is
+    fn document_key(&self, uri: &Url) -> String {
+        self.paths.normalize_uri(uri.as_str())
+            .map_or_else(|| uri.to_string(), |path| path.key().to_string())
+    }
+
+    fn analyzer(&self) -> Arc<DocumentAnalyzer> {
+        self.analyzer.read().unwrap().clone()
+    }
+
+    /// Semantic tokens of the open document under `key`, encoded
+    fn encode_semantic_tokens(&self, key: &str) -> Option<Vec<SemanticToken>> {
+        let content = self.documents.get(key)?.value().clone();
+        Some(semantic_tokens::encode(self.analyzer().semantic_tokens(&content)))
+    }
+
+    /// Remember `data` as the tokens last sent for `key`, under a new result id
+    fn cache_semantic_tokens(&self, key: String, data: Vec<SemanticToken>) -> String {
+        let result_id = self.semantic_results.fetch_add(1, Ordering::Relaxed).to_string();
+        self.semantic_tokens.insert(key, (result_id.clone(), data));
+        result_id
+    }
+
+    fn workspace(&self) -> Arc<WorkspaceIndex> {
+        self.workspace.read().unwrap().clone()
+    }
+
+    fn settings(&self) -> ServerSettings {
+        self.settings.read().unwrap().clone()
+    }
+
+    /// Echo `message` to the client's log when the trace level allows it
+    async fn trace(&self, level: TraceLevel, message: String) {
+        if self.settings().trace >= level {
+            self.client.log_message(MessageType::LOG, message).await;
+        }
+    }
+
+    /// Pull the `typthon` section from the client and apply it
+    async fn pull_configuration(&self) {
+        let items = vec![ConfigurationItem { scope_uri: None, section: Some("typthon".to_string()) }];
+        match self.client.configuration(items).await {
+            Ok(mut values) => self.apply_editor_settings(&values.pop().unwrap_or(Value::Null)).await,
+            Err(err) => tracing::debug!("Client did not provide configuration: {}", err),
+        }
+    }
+
+    async fn apply_editor_settings(&self, section: &Value) {
+        let (editor, warnings) = EditorSettings::parse(section);
+        for warning in warnings {
+            self.client.show_message(MessageType::WARNING, warning).await;
+        }
+        *self.editor.write().unwrap() = editor;
+        self.refresh_settings().await;
+    }
+
+    /// Reload the project config after one of its files changed
+    async fn reload_project(&self) {
+        let root = self.root.read().unwrap().clone();
+        *self.project.write().unwrap() = ProjectConfig::discover_from(&root).0;
+        self.refresh_settings().await;
+    }
+
+    /// Re-resolve settings and apply what changed to open documents and watchers
+    async fn refresh_settings(&self) {
+        let new = ServerSettings::resolve(&self.editor.read().unwrap(), &self.project.read().unwrap());
+        let old = std::mem::replace(&mut *self.settings.write().unwrap(), new.clone());
+        if old == new {
+            return;
+        }
+        self.trace(TraceLevel::Messages, format!("Settings updated: {}", json!(new))).await;
+
+        if old.strictness != new.strictness || old.lints != new.lints {
+            *self.analyzer.write().unwrap() = Arc::new(build_analyzer(&new, self.workspace().modules()));
+            self.checked.clear();
+            let keys: Vec<String> = self.documents.iter().map(|entry| entry.key().clone()).collect();
+            for key in keys {
+                self.analyze_document(&key).await;
+            }
+        }
+        if old.include != new.include {
+            self.register_watchers(&new, true).await;
+        }
+        if old.workspace != new.workspace && self.indexed.load(Ordering::Relaxed) {
+            self.index_workspace().await;
+        }
+        if old.inlay_hints != new.inlay_hints {
+            let _ = self.client.inlay_hint_refresh().await;
+        }
+        if old.code_lens != new.code_lens {
+            let _ = self.client.code_lens_refresh().await;
+        }
+    }
+
+    /// Watch the source globs and project config files, replacing earlier watchers
+    async fn register_watchers(&self, settings: &ServerSettings, replace: bool) {
+        if !self.dynamic_watchers.load(Ordering::Relaxed) {
+            return;
+        }
+        if replace {
+            let unregistration = Unregistration {
+                id: WATCHER_REGISTRATION.to_string(),
+                method: "workspace/didChangeWatchedFiles".to_string(),
+            };
+            let _ = self.client.unregister_capability(vec![unregistration]).await;
+        }
+        let watchers = settings.watch_globs().into_iter()
+            .map(|glob| FileSystemWatcher { glob_pattern: GlobPattern::String(glob), kind: None })
+            .collect();
+        let registration = Registration {
+            id: WATCHER_REGISTRATION.to_string(),
+            method: "workspace/didChangeWatchedFiles".to_string(),
+            register_options: serde_json::to_value(DidChangeWatchedFilesRegistrationOptions { watchers }).ok(),
+        };
+        if let Err(err) = self.client.register_capability(vec![registration]).await {
+            tracing::warn!("Failed to register file watchers: {}", err);
+        }
+    }
+
+    /// Effective settings and where they came from, for `typthon/serverStatus`
+    async fn server_status(&self) -> Result<Value> {
+        Ok(json!({
+            "settings": self.settings(),
+            "projectRoot": self.root.read().unwrap().display().to_string(),
+            "openDocuments": self.documents.len(),
+            "indexedFiles": self.workspace().len(),
+            "metrics": {
+                "incrementalHits": self.incremental_hits.load(Ordering::Relaxed),
+                "fullReanalyses": self.full_reanalyses.load(Ordering::Relaxed),
+            },
+        }))
+    }
+
+    /// Index the workspace folders, then check every file found and publish
+    /// its diagnostics
+    async fn index_workspace(&self) {
+        let settings = self.settings();
+        let workspace = self.workspace();
+        let analyzer = self.analyzer();
+        let project = self.project.read().unwrap().clone();
+        let limit = settings.workspace.max_files;
+        let (files, truncated) = workspace_files(workspace.roots(), &project, limit);
+        if truncated {
+            self.client.show_message(MessageType::WARNING, format!(
+                "Indexed the first {} Python files of the workspace; raise typthon.workspace.maxFiles to index more",
+                limit,
+            )).await;
+        }
+
+        for path in &files {
+            if let Some(content) = self.file_text(path) {
+                workspace.update(path, &content, &analyzer);
+            }
+        }
+        self.indexed.store(true, Ordering::Relaxed);
+        self.trace(TraceLevel::Messages, format!("Indexed {} file(s)", files.len())).await;
+        for path in &files {
+            self.publish_file(path).await;
+        }
+    }
+
+    /// Text of `path`: the open document, or else the file on disk
+    fn file_text(&self, path: &Path) -> Option<String> {
+        let uri = Url::from_file_path(path).ok()?;
+        match self.documents.get(&self.document_key(&uri)) {
+            Some(document) => Some(document.to_string()),
+            None => fs::read_to_string(path).ok(),
+        }
+    }
+
+    /// Check an open document and publish its diagnostics, then re-check the
+    /// files importing it when what it exports may have changed
+    async fn analyze_document(&self, key: &str) {
+        if let Some((path, CheckPath::Full)) = self.publish_document(key).await {
+            self.recheck_importers(&path).await;
+        }
+    }
+
+    /// Check an open document and publish its diagnostics; the file it is
+    /// and how it was checked, when it's a file that parses
+    async fn publish_document(&self, key: &str) -> Option<(PathBuf, CheckPath)> {
+        let analyzer = self.analyzer();
+        let uri = self.uris.get(key).map(|uri| uri.clone())?;
+        let content = self.documents.get(key).map(|document| document.to_string())?;
+        let path = uri.to_file_path().ok();
+        let previous = self.checked.remove(key).map(|(_, checked)| checked);
+        let (diagnostics, lints, check) = match analyzer.check_document(&content, path.as_deref(), previous) {
+            Some((checked, check)) => {
+                let counter = match check {
+                    CheckPath::Incremental => &self.incremental_hits,
+                    CheckPath::Full => &self.full_reanalyses,
+                };
+                counter.fetch_add(1, Ordering::Relaxed);
+                let found = (checked.errors.clone(), checked.lints.clone());
+                self.checked.insert(key.to_string(), checked);
+                (found.0, found.1, Some(check))
+            }
+            // Unparsable: just the syntax error
+            None => (analyzer.analyze(&content), Vec::new(), None),
+        };
+
+        self.publish(uri, lsp_diagnostics(&analyzer, &content, diagnostics, lints)).await;
+        let path = path?;
+        self.workspace().update(&path, &content, &analyzer);
+        Some((path, check?))
+    }
+
+    /// Check `path` from scratch, from its open document or else from disk,
+    /// and publish its diagnostics
+    async fn publish_file(&self, path: &Path) {
+        let Ok(uri) = Url::from_file_path(path) else { return };
+        let key = self.document_key(&uri);
+        if self.documents.contains_key(&key) {
+            self.checked.remove(&key);
+            self.publish_document(&key).await;
+            return;
+        }
+        let Ok(content) = fs::read_to_string(path) else { return };
+        let analyzer = self.analyzer();
+        let (diagnostics, lints) = match analyzer.check_document(&content, Some(path), None) {
+            Some((checked, _)) => (checked.errors, checked.lints),
+            None => (analyzer.analyze(&content), Vec::new()),
+        };
+        self.publish(uri, lsp_diagnostics(&analyzer, &content, diagnostics, lints)).await;
+    }
+
+    /// Re-check the files importing `path`, directly or not, after it changed
+    async fn recheck_importers(&self, path: &Path) {
+        let workspace = self.workspace();
+        let importers = workspace.importers(path);
+        // Nearest first, each checked against the new exports of the ones before
+        for importer in &importers {
+            workspace.modules().forget(importer);
+        }
+        for importer in &importers {
+            self.publish_file(importer).await;
+        }
+    }
 
-            self.client
-                .publish_diagnostics(uri.parse().unwrap(), lsp_diagnostics, None)
-                .await;
+    /// Take in a change to `path` on disk. Open documents are left alone;
+    /// the editor's text is what they're checked with
+    async fn file_changed(&self, path: &Path, deleted: bool) {
+        if Url::from_file_path(path).is_ok_and(|uri| self.documents.contains_key(&self.document_key(&uri))) {
+            return;
+        }
+        let workspace = self.workspace();
+        workspace.modules().forget(path);
+        if deleted {
+            workspace.remove(path);
+            if let Ok(uri) = Url::from_file_path(path) {
+                self.publish(uri, Vec::new()).await;
+            }
+        } else if let Ok(content) = fs::read_to_string(path) {
+            workspace.update(path, &content, &self.analyzer());
+            self.publish_file(path).await;
         }
+        self.recheck_importers(path).await;
+    }
+
+    async fn publish(&self, uri: Url, diagnostics: Vec<Diagnostic>) {
+        let count = diagnostics.len();
+        self.client.publish_diagnostics(uri.clone(), diagnostics, None).await;
+        self.trace(TraceLevel::Verbose, format!("Published {} diagnostic(s) for {}", count, uri)).await;
+    }
+}
+
+/// Type errors and lints of `content` as editor diagnostics
+fn lsp_diagnostics(
+    analyzer: &DocumentAnalyzer,
+    content: &str,
+    errors: Vec<analyzer::TypeError>,
+    lints: Vec<LintDiagnostic>,
+) -> Vec<Diagnostic> {
+    let lint_diagnostics = lints.into_iter().map(|lint| Diagnostic {
+        range: analyzer.lsp_range(content, &lint.range),
+        severity: Some(diagnostic_severity(lint.severity())),
+        code: Some(NumberOrString::String(lint.code.to_string())),
+        source: Some("typthon".to_string()),
+        message: lint.message,
+        ..Default::default()
+    });
+
+    errors
+        .into_iter()
+        .map(|d| Diagnostic {
+            range: d.range.map_or_else(Range::default, |range| analyzer.lsp_range(content, &range)),
+            severity: Some(diagnostic_severity(d.severity)),
+            code: d.code.map(|code| NumberOrString::String(code.to_string())),
+            source: Some("typthon".to_string()),
+            message: d.message,
+            related_information: None,
+            tags: None,
+            code_description: None,
+            data: None,
+        })
+        .chain(lint_diagnostics)
+        .collect()
+}
+
+/// Editor range of a symbol `length` characters long
+fn symbol_range(line: usize, col: usize, length: usize) -> Range {
+    Range {
+        start: Position { line: line as u32, character: col as u32 },
+        end: Position { line: line as u32, character: (col + length) as u32 },
+    }
+}
+
+fn symbol_kind(kind: &analyzer::SymbolKind) -> SymbolKind {
+    match kind {
+        analyzer::SymbolKind::Function => SymbolKind::FUNCTION,
+        analyzer::SymbolKind::Class => SymbolKind::CLASS,
+        analyzer::SymbolKind::Variable => SymbolKind::VARIABLE,
+        analyzer::SymbolKind::Parameter => SymbolKind::VARIABLE,
+        analyzer::SymbolKind::Method => SymbolKind::METHOD,
+        analyzer::SymbolKind::Property => SymbolKind::PROPERTY,
+    }
+}
+
+/// Notes show as information, the least severe level editors underline
+fn diagnostic_severity(severity: Severity) -> DiagnosticSeverity {
+    match severity {
+        Severity::Error => DiagnosticSeverity::ERROR,
+        Severity::Warning => DiagnosticSeverity::WARNING,
+        Severity::Note => DiagnosticSeverity::INFORMATION,
     }
 }
 
+fn build_analyzer(settings: &ServerSettings, modules: Arc<ModuleResolver>) -> DocumentAnalyzer {
+    DocumentAnalyzer::new()
+        .with_lints(settings.lints.clone())
+        .with_strict(settings.strictness == Strictness::Strict)
+        .with_modules(modules)
+}
+
 #[tower_lsp::async_trait]
 impl LanguageServer for TypthonLanguageServer {
-    async fn initialize(&self, _: InitializeParams) -> Result<InitializeResult> {
+    async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
         tracing::info!("Typthon LSP server initializing");
 
+        let workspace = params.capabilities.workspace.as_ref();
+        let dynamic = |registration: Option<Option<bool>>| registration.flatten().unwrap_or(false);
+        self.dynamic_configuration.store(
+            dynamic(workspace.map(|w| w.did_change_configuration.and_then(|c| c.dynamic_registration))),
+            Ordering::Relaxed,
+        );
+        self.dynamic_watchers.store(
+            dynamic(workspace.map(|w| w.did_change_watched_files.and_then(|c| c.dynamic_registration))),
+            Ordering::Relaxed,
+        );
+
+        let folders: Vec<PathBuf> = params.workspace_folders.iter().flatten()
+            .filter_map(|folder| folder.uri.to_file_path().ok())
+            .collect();
+        #[allow(deprecated)]
+        let root = params.workspace_folders.as_ref()
+            .and_then(|folders| folders.first().map(|folder| folder.uri.clone()))
+            .or(params.root_uri)
+            .and_then(|uri| uri.to_file_path().ok());
+        if let Some(root) = root {
+            let (project, _) = ProjectConfig::discover_from(&root);
+            let workspace = Arc::new(WorkspaceIndex::new(std::iter::once(root.clone()).chain(folders)));
+            *self.root.write().unwrap() = root;
+            *self.project.write().unwrap() = project;
+            *self.workspace.write().unwrap() = workspace.clone();
+            self.refresh_settings().await;
+            *self.analyzer.write().unwrap() = Arc::new(build_analyzer(&self.settings(), workspace.modules()));
+        }
+
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
                 text_document_sync: Some(TextDocumentSyncCapability::Kind(
-                    TextDocumentSyncKind::FULL,
+                    TextDocumentSyncKind::INCREMENTAL,
                 )),
                 hover_provider: Some(HoverProviderCapability::Simple(true)),
                 completion_provider: Some(CompletionOptions {
                     trigger_characters: Some(vec![".".to_string(), ":".to_string()]),
+                    resolve_provider: Some(true),
                     ..Default::default()
                 }),
                 definition_provider: Some(OneOf::Left(true)),
+                workspace_symbol_provider: Some(OneOf::Left(true)),
+                document_symbol_provider: Some(OneOf::Left(true)),
+                folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
                 references_provider: Some(OneOf::Left(true)),
                 rename_provider: Some(OneOf::Left(true)),
-                code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+                code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
+                    code_action_kinds: Some(vec![
+                        CodeActionKind::QUICKFIX,
+                        CodeActionKind::REFACTOR_REWRITE,
+                        CodeActionKind::SOURCE_ORGANIZE_IMPORTS,
+                    ]),
+                    work_done_progress_options: WorkDoneProgressOptions::default(),
+                    resolve_provider: None,
+                })),
                 signature_help_provider: Some(SignatureHelpOptions {
                     trigger_characters: Some(vec!["(".to_string(), ",".to_string()]),
                     retrigger_characters: None,
@@ -99,30 +519,15 @@ impl LanguageServer for TypthonLanguageServer {
                 semantic_tokens_provider: Some(
                     SemanticTokensServerCapabilities::SemanticTokensOptions(
                         SemanticTokensOptions {
-                            legend: SemanticTokensLegend {
-                                token_types: vec![
-                                    SemanticTokenType::NAMESPACE,
-                                    SemanticTokenType::CLASS,
-                                    SemanticTokenType::FUNCTION,
-                                    SemanticTokenType::VARIABLE,
-                                    SemanticTokenType::PARAMETER,
-                                    SemanticTokenType::PROPERTY,
-                                    SemanticTokenType::METHOD,
-                                    SemanticTokenType::KEYWORD,
-                                    SemanticTokenType::TYPE,
-                                ],
-                                token_modifiers: vec![
-                                    SemanticTokenModifier::DEFINITION,
-                                    SemanticTokenModifier::READONLY,
-                                ],
-                            },
+                            legend: semantic_tokens::legend(),
                             range: Some(true),
-                            full: Some(SemanticTokensFullOptions::Bool(true)),
+                            full: Some(SemanticTokensFullOptions::Delta { delta: Some(true) }),
                             work_done_progress_options: WorkDoneProgressOptions::default(),
                         }
                     )
                 ),
                 inlay_hint_provider: Some(OneOf::Left(true)),
+                code_lens_provider: Some(CodeLensOptions { resolve_provider: Some(false) }),
                 diagnostic_provider: Some(DiagnosticServerCapabilities::Options(
                     DiagnosticOptions {
                         identifier: Some("typthon".to_string()),
@@ -145,6 +550,45 @@ impl LanguageServer for TypthonLanguageServer {
         self.client
             .log_message(MessageType::INFO, "Typthon LSP server started")
             .await;
+
+        if self.dynamic_configuration.load(Ordering::Relaxed) {
+            let registration = Registration {
+                id: CONFIGURATION_REGISTRATION.to_string(),
+                method: "workspace/didChangeConfiguration".to_string(),
+                register_options: None,
+            };
+            if let Err(err) = self.client.register_capability(vec![registration]).await {
+                tracing::warn!("Failed to register for configuration changes: {}", err);
+            }
+        }
+        self.register_watchers(&self.settings(), false).await;
+        self.pull_configuration().await;
+        self.index_workspace().await;
+    }
+
+    async fn did_change_configuration(&self, params: DidChangeConfigurationParams) {
+        match params.settings.get("typthon") {
+            Some(section) => self.apply_editor_settings(section).await,
+            // Pull-model clients send an empty notification
+            None => self.pull_configuration().await,
+        }
+    }
+
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        let config_changed = params.changes.iter().any(|change| {
+            change.uri.path_segments()
+                .and_then(|mut segments| segments.next_back())
+                .is_some_and(|name| CONFIG_FILES.contains(&name))
+        });
+        if config_changed {
+            self.reload_project().await;
+        }
+        for change in &params.changes {
+            let Ok(path) = change.uri.to_file_path() else { continue };
+            if path.extension().is_some_and(|ext| ext == "py") {
+                self.file_changed(&path, change.typ == FileChangeType::DELETED).await;
+            }
+        }
     }
 
     async fn shutdown(&self) -> Result<()> {
@@ -153,34 +597,63 @@ impl LanguageServer for TypthonLanguageServer {
     }
 
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
-        let uri = params.text_document.uri.to_string();
+        let uri = params.text_document.uri;
+        let key = self.document_key(&uri);
         let text = params.text_document.text;
 
         tracing::info!("Document opened: {}", uri);
-        self.documents.insert(uri.clone(), text);
-        self.analyze_document(&uri).await;
+        self.documents.insert(key.clone(), Document::new(text));
+        self.uris.insert(key.clone(), uri);
+        self.analyze_document(&key).await;
     }
 
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
-        let uri = params.text_document.uri.to_string();
-
-        if let Some(change) = params.content_changes.first() {
-            tracing::debug!("Document changed: {}", uri);
-            self.documents.insert(uri.clone(), change.text.clone());
-            self.analyze_document(&uri).await;
+        let uri = params.text_document.uri;
+        let key = self.document_key(&uri);
+
+        if !params.content_changes.is_empty() {
+            tracing::debug!("Document changed: {} ({} change(s))", uri, params.content_changes.len());
+            self.documents.entry(key.clone())
+                .or_insert_with(|| Document::new(String::new()))
+                .apply_changes(&params.content_changes);
+            self.uris.insert(key.clone(), uri);
+
+            let generation = {
+                let mut generation = self.generations.entry(key.clone()).or_insert(0);
+                *generation += 1;
+                *generation
+            };
+            let debounce = self.settings().diagnostics.debounce_ms;
+            if debounce > 0 {
+                tokio::time::sleep(Duration::from_millis(debounce)).await;
+                // A newer edit publishes instead
+                if self.generations.get(&key).map(|current| *current) != Some(generation) {
+                    return;
+                }
+            }
+            self.analyze_document(&key).await;
         }
     }
 
     async fn did_save(&self, params: DidSaveTextDocumentParams) {
-        let uri = params.text_document.uri.to_string();
+        let uri = params.text_document.uri;
         tracing::info!("Document saved: {}", uri);
-        self.analyze_document(&uri).await;
+        self.analyze_document(&self.document_key(&uri)).await;
     }
 
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
-        let uri = params.text_document.uri.to_string();
+        let uri = params.text_document.uri;
+        let key = self.document_key(&uri);
         tracing::info!("Document closed: {}", uri);
-        self.documents.remove(&uri);
+        self.documents.remove(&key);
+        self.uris.remove(&key);
+        self.generations.remove(&key);
+        self.checked.remove(&key);
+        self.semantic_tokens.remove(&key);
+        // Unsaved edits are gone: importers go back to the file on disk
+        if let Ok(path) = uri.to_file_path() {
+            self.file_changed(&path, !path.exists()).await;
+        }
     }
 
     async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
@@ -189,8 +662,8 @@ impl LanguageServer for TypthonLanguageServer {
 
         tracing::debug!("Hover request at {}:{}", position.line, position.character);
 
-        if let Some(content) = self.documents.get(&uri.to_string()) {
-            let info = self.analyzer.get_hover_info(
+        if let Some(content) = self.documents.get(&self.document_key(&uri)) {
+            let info = self.analyzer().get_hover_info(
                 content.value(),
                 position.line as usize,
                 position.character as usize,
@@ -198,7 +671,7 @@ impl LanguageServer for TypthonLanguageServer {
 
             if let Some(text) = info {
                 return Ok(Some(Hover {
-                    contents: HoverContents::Scalar(MarkedString::String(text)),
+                    contents: HoverContents::Markup(MarkupContent { kind: MarkupKind::Markdown, value: text }),
                     range: None,
                 }));
             }
@@ -213,8 +686,8 @@ impl LanguageServer for TypthonLanguageServer {
 
         tracing::debug!("Completion request at {}:{}", position.line, position.character);
 
-        if let Some(content) = self.documents.get(&uri.to_string()) {
-            let completions = self.analyzer.get_completions(
+        if let Some(content) = self.documents.get(&self.document_key(&uri)) {
+            let completions = self.analyzer().get_completions(
                 content.value(),
                 position.line as usize,
                 position.character as usize,
@@ -223,6 +696,7 @@ impl LanguageServer for TypthonLanguageServer {
             let items: Vec<CompletionItem> = completions
                 .into_iter()
                 .map(|c| CompletionItem {
+                    data: c.lazy_documentation.then(|| json!({ "uri": uri, "name": c.label })),
                     label: c.label,
                     kind: Some(c.kind),
                     detail: Some(c.detail),
@@ -239,6 +713,20 @@ impl LanguageServer for TypthonLanguageServer {
         Ok(None)
     }
 
+    async fn completion_resolve(&self, mut item: CompletionItem) -> Result<CompletionItem> {
+        let target = item.data.as_ref().and_then(|data| {
+            let uri = Url::parse(data.get("uri")?.as_str()?).ok()?;
+            Some((uri, data.get("name")?.as_str()?.to_string()))
+        });
+        if let Some((uri, name)) = target {
+            if let Some(content) = self.documents.get(&self.document_key(&uri)) {
+                item.documentation = self.analyzer().completion_documentation(content.value(), &name)
+                    .map(|value| Documentation::MarkupContent(MarkupContent { kind: MarkupKind::Markdown, value }));
+            }
+        }
+        Ok(item)
+    }
+
     async fn goto_definition(
         &self,
         params: GotoDefinitionParams,
@@ -248,29 +736,60 @@ impl LanguageServer for TypthonLanguageServer {
 
         tracing::debug!("Go to definition at {}:{}", position.line, position.character);
 
-        if let Some(content) = self.documents.get(&uri.to_string()) {
-            if let Some(location) = self.analyzer.get_definition(
-                content.value(),
-                position.line as usize,
-                position.character as usize,
-            ) {
-                return Ok(Some(GotoDefinitionResponse::Scalar(Location {
-                    uri: uri.clone(),
-                    range: Range {
-                        start: Position {
-                            line: location.line as u32,
-                            character: location.col as u32,
-                        },
-                        end: Position {
-                            line: location.line as u32,
-                            character: (location.col + location.length) as u32,
-                        },
-                    },
-                })));
-            }
+        let Some(content) = self.documents.get(&self.document_key(&uri)).map(|document| document.to_string()) else {
+            return Ok(None);
+        };
+        let analyzer = self.analyzer();
+        let (line, col) = (position.line as usize, position.character as usize);
+        if let Some(location) = analyzer.get_definition(&content, line, col) {
+            return Ok(Some(GotoDefinitionResponse::Scalar(Location {
+                uri: uri.clone(),
+                range: symbol_range(location.line, location.col, location.length),
+            })));
         }
 
-        Ok(None)
+        // Not defined here: look in the files this one imports
+        let (Ok(path), Some(word)) = (uri.to_file_path(), analyzer.get_word_at_position(&content, line, col)) else {
+            return Ok(None);
+        };
+        Ok(self.workspace().imported_definition(&path, &word).and_then(|(file, symbol)| {
+            Some(GotoDefinitionResponse::Scalar(Location {
+                uri: Url::from_file_path(file).ok()?,
+                range: symbol_range(symbol.line, symbol.col, symbol.length),
+            }))
+        }))
+    }
+
+    async fn symbol(&self, params: WorkspaceSymbolParams) -> Result<Option<Vec<SymbolInformation>>> {
+        let symbols = self.workspace().search(&params.query).into_iter()
+            .filter_map(|(file, symbol)| {
+                #[allow(deprecated)]
+                Some(SymbolInformation {
+                    kind: symbol_kind(&symbol.kind),
+                    location: Location {
+                        uri: Url::from_file_path(file).ok()?,
+                        range: symbol_range(symbol.line, symbol.col, symbol.length),
+                    },
+                    name: symbol.name,
+                    tags: None,
+                    deprecated: None,
+                    container_name: None,
+                })
+            })
+            .collect();
+        Ok(Some(symbols))
+    }
+
+    async fn document_symbol(&self, params: DocumentSymbolParams) -> Result<Option<DocumentSymbolResponse>> {
+        let uri = params.text_document.uri;
+        let Some(content) = self.documents.get(&self.document_key(&uri)) else { return Ok(None) };
+        Ok(Some(DocumentSymbolResponse::Nested(self.analyzer().document_symbols(content.value()))))
+    }
+
+    async fn folding_range(&self, params: FoldingRangeParams) -> Result<Option<Vec<FoldingRange>>> {
+        let uri = params.text_document.uri;
+        let Some(content) = self.documents.get(&self.document_key(&uri)) else { return Ok(None) };
+        Ok(Some(self.analyzer().folding_ranges(content.value())))
     }
 
     async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
@@ -279,8 +798,8 @@ impl LanguageServer for TypthonLanguageServer {
 
         tracing::debug!("Find references at {}:{}", position.line, position.character);
 
-        if let Some(content) = self.documents.get(&uri.to_string()) {
-            let references = self.analyzer.find_references(
+        if let Some(content) = self.documents.get(&self.document_key(&uri)) {
+            let references = self.analyzer().find_references(
                 content.value(),
                 position.line as usize,
                 position.character as usize,
@@ -316,29 +835,12 @@ impl LanguageServer for TypthonLanguageServer {
 
         tracing::debug!("Rename at {}:{} to {}", position.line, position.character, new_name);
 
-        if let Some(content) = self.documents.get(&uri.to_string()) {
-            let references = self.analyzer.find_references(
-                content.value(),
-                position.line as usize,
-                position.character as usize,
-            );
-
-            let edits: Vec<TextEdit> = references
-                .into_iter()
-                .map(|r| TextEdit {
-                    range: Range {
-                        start: Position {
-                            line: r.line as u32,
-                            character: r.col as u32,
-                        },
-                        end: Position {
-                            line: r.line as u32,
-                            character: (r.col + r.length) as u32,
-                        },
-                    },
-                    new_text: new_name.clone(),
-                })
-                .collect();
+        if let Some(content) = self.documents.get(&self.document_key(&uri)) {
+            let line = position.line as usize;
+            let col = position.character as usize;
+            let edits = self.analyzer()
+                .rename(content.value(), line, col, &new_name)
+                .map_err(tower_lsp::jsonrpc::Error::invalid_params)?;
 
             let mut changes = std::collections::HashMap::new();
             changes.insert(uri, edits);
@@ -355,36 +857,81 @@ impl LanguageServer for TypthonLanguageServer {
 
     async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
         let uri = params.text_document.uri;
-        let _range = params.range;
+        let range = params.range;
 
         tracing::debug!("Code action request");
 
-        if let Some(_content) = self.documents.get(&uri.to_string()) {
+        if let Some(content) = self.documents.get(&self.document_key(&uri)) {
             let mut actions = Vec::new();
 
-            // Add import statement action
-            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
-                title: "Add missing import".to_string(),
-                kind: Some(CodeActionKind::QUICKFIX),
-                diagnostics: None,
-                edit: None,
-                command: None,
-                is_preferred: Some(true),
-                disabled: None,
-                data: None,
-            }));
+            // Lint quick-fixes
+            for fix in self.analyzer().lint_fixes(content.value(), range) {
+                actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                    title: fix.title,
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(HashMap::from([(uri.clone(), fix.edits)])),
+                        ..Default::default()
+                    }),
+                    is_preferred: Some(true),
+                    ..Default::default()
+                }));
+            }
+
+            // Imports for the names diagnostics report undefined
+            let undefined = params.context.diagnostics.iter().filter(|diagnostic| matches!(
+                &diagnostic.code,
+                Some(NumberOrString::String(code)) if code == UNDEFINED_VARIABLE || code == UNDEFINED_FUNCTION
+            ));
+            for diagnostic in undefined {
+                let start = diagnostic.range.start;
+                let Some(name) = self.analyzer().get_word_at_position(content.value(), start.line as usize, start.character as usize) else { continue };
+                let fixes = self.analyzer().import_fixes(content.value(), &name);
+                let preferred = fixes.len() == 1;
+                for fix in fixes {
+                    actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                        title: fix.title,
+                        kind: Some(CodeActionKind::QUICKFIX),
+                        diagnostics: Some(vec![diagnostic.clone()]),
+                        edit: Some(WorkspaceEdit {
+                            changes: Some(HashMap::from([(uri.clone(), fix.edits)])),
+                            ..Default::default()
+                        }),
+                        is_preferred: Some(preferred),
+                        ..Default::default()
+                    }));
+                }
+            }
 
-            // Add type annotation action
-            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
-                title: "Add type annotation".to_string(),
-                kind: Some(CodeActionKind::REFACTOR),
-                diagnostics: None,
-                edit: None,
-                command: None,
-                is_preferred: Some(false),
-                disabled: None,
-                data: None,
+            // Annotate the assignment target or parameter under the cursor
+            let start = range.start;
+            if let Some(fix) = self.analyzer().annotation_fix(content.value(), start.line as usize, start.character as usize) {
+                actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                    title: fix.title,
+                    kind: Some(CodeActionKind::REFACTOR_REWRITE),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(HashMap::from([(uri.clone(), fix.edits)])),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }));
+            }
+
+            // Only when the editor asks for source actions, or for no kind in particular
+            let organize = params.context.only.as_ref().is_none_or(|only| only.iter().any(|kind| {
+                CodeActionKind::SOURCE_ORGANIZE_IMPORTS.as_str().starts_with(kind.as_str())
             }));
+            if let Some(fix) = self.analyzer().organize_imports(content.value()).filter(|_| organize) {
+                actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                    title: fix.title,
+                    kind: Some(CodeActionKind::SOURCE_ORGANIZE_IMPORTS),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(HashMap::from([(uri.clone(), fix.edits)])),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }));
+            }
 
             return Ok(Some(actions));
         }
@@ -398,63 +945,9 @@ impl LanguageServer for TypthonLanguageServer {
 
         tracing::debug!("Signature help at {}:{}", position.line, position.character);
 
-        if let Some(content) = self.documents.get(&uri.to_string()) {
-            // Extract function name at cursor
-            let lines: Vec<&str> = content.lines().collect();
-            if position.line as usize >= lines.len() {
-                return Ok(None);
-            }
-
-            let line = lines[position.line as usize];
-            let before_cursor = &line[..position.character.min(line.len() as u32) as usize];
-
-            // Simple pattern to find function call
-            if let Some(open_paren) = before_cursor.rfind('(') {
-                let func_start = before_cursor[..open_paren]
-                    .rfind(|c: char| !c.is_alphanumeric() && c != '_')
-                    .map(|i| i + 1)
-                    .unwrap_or(0);
-                let func_name = &before_cursor[func_start..open_paren];
-
-                // Provide signature for known functions
-                let signature_info = match func_name {
-                    "print" => Some(SignatureInformation {
-                        label: "print(*args, sep=' ', end='\\n', file=None, flush=False)".to_string(),
-                        documentation: Some(Documentation::String(
-                            "Print values to a stream, or to sys.stdout by default.".to_string()
-                        )),
-                        parameters: Some(vec![
-                            ParameterInformation {
-                                label: ParameterLabel::Simple("*args".to_string()),
-                                documentation: Some(Documentation::String("Values to print".to_string())),
-                            },
-                        ]),
-                        active_parameter: None,
-                    }),
-                    "len" => Some(SignatureInformation {
-                        label: "len(obj)".to_string(),
-                        documentation: Some(Documentation::String(
-                            "Return the length of an object.".to_string()
-                        )),
-                        parameters: Some(vec![
-                            ParameterInformation {
-                                label: ParameterLabel::Simple("obj".to_string()),
-                                documentation: Some(Documentation::String("Object to measure".to_string())),
-                            },
-                        ]),
-                        active_parameter: None,
-                    }),
-                    _ => None,
-                };
-
-                if let Some(sig) = signature_info {
-                    return Ok(Some(SignatureHelp {
-                        signatures: vec![sig],
-                        active_signature: Some(0),
-                        active_parameter: Some(0),
-                    }));
-                }
-            }
+        if let Some(content) = self.documents.get(&self.document_key(&uri)) {
+            let help = self.analyzer().signature_help(content.value(), position.line as usize, position.character as usize);
+            return Ok(help);
         }
 
         Ok(None)
@@ -468,86 +961,105 @@ impl LanguageServer for TypthonLanguageServer {
 
         tracing::debug!("Semantic tokens request");
 
-        if let Some(content) = self.documents.get(&uri.to_string()) {
-            let symbols = self.analyzer.extract_symbols(content.value());
-
-            let mut data = Vec::new();
-            let mut prev_line = 0u32;
-            let mut prev_char = 0u32;
-
-            for symbol in symbols {
-                let line = symbol.line as u32;
-                let char = symbol.col as u32;
-                let length = symbol.length as u32;
-
-                let token_type = match symbol.kind {
-                    analyzer::SymbolKind::Class => 1,      // CLASS
-                    analyzer::SymbolKind::Function => 2,   // FUNCTION
-                    analyzer::SymbolKind::Variable => 3,   // VARIABLE
-                    analyzer::SymbolKind::Parameter => 4,  // PARAMETER
-                    analyzer::SymbolKind::Property => 5,   // PROPERTY
-                    analyzer::SymbolKind::Method => 6,     // METHOD
-                };
+        let key = self.document_key(&uri);
+        let Some(data) = self.encode_semantic_tokens(&key) else { return Ok(None) };
+        let result_id = self.cache_semantic_tokens(key, data.clone());
+        Ok(Some(SemanticTokensResult::Tokens(SemanticTokens { result_id: Some(result_id), data })))
+    }
 
-                let delta_line = if line >= prev_line { line - prev_line } else { 0 };
-                let delta_char = if delta_line == 0 && char >= prev_char {
-                    char - prev_char
-                } else {
-                    char
-                };
+    async fn semantic_tokens_full_delta(
+        &self,
+        params: SemanticTokensDeltaParams,
+    ) -> Result<Option<SemanticTokensFullDeltaResult>> {
+        let uri = params.text_document.uri;
 
-                data.push(SemanticToken {
-                    delta_line,
-                    delta_start: delta_char,
-                    length,
-                    token_type,
-                    token_modifiers_bitset: 0,
-                });
+        tracing::debug!("Semantic tokens delta request");
+
+        let key = self.document_key(&uri);
+        let Some(data) = self.encode_semantic_tokens(&key) else { return Ok(None) };
+        // Edits only make sense against the tokens the editor still has
+        let edits = self.semantic_tokens.get(&key)
+            .filter(|cached| cached.0 == params.previous_result_id)
+            .map(|cached| semantic_tokens::edits(&cached.1, &data));
+        let result_id = self.cache_semantic_tokens(key, data.clone());
+        Ok(Some(match edits {
+            Some(edits) => SemanticTokensFullDeltaResult::TokensDelta(SemanticTokensDelta {
+                result_id: Some(result_id),
+                edits,
+            }),
+            None => SemanticTokensFullDeltaResult::Tokens(SemanticTokens { result_id: Some(result_id), data }),
+        }))
+    }
 
-                prev_line = line;
-                prev_char = char;
-            }
+    async fn semantic_tokens_range(
+        &self,
+        params: SemanticTokensRangeParams,
+    ) -> Result<Option<SemanticTokensRangeResult>> {
+        let uri = params.text_document.uri;
+        let range = params.range;
+
+        tracing::debug!("Semantic tokens request for lines {}-{}", range.start.line, range.end.line);
+
+        let Some(content) = self.documents.get(&self.document_key(&uri)) else { return Ok(None) };
+        let tokens = self.analyzer().semantic_tokens(content.value()).into_iter()
+            .filter(|token| {
+                let start = Position::new(token.line, token.start);
+                let end = Position::new(token.line, token.start + token.length);
+                end > range.start && start < range.end
+            })
+            .collect();
+        Ok(Some(SemanticTokensRangeResult::Tokens(SemanticTokens {
+            result_id: None,
+            data: semantic_tokens::encode(tokens),
+        })))
+    }
 
-            return Ok(Some(SemanticTokensResult::Tokens(SemanticTokens {
-                result_id: None,
-                data,
-            })));
+    async fn inlay_hint(&self, params: InlayHintParams) -> Result<Option<Vec<InlayHint>>> {
+        let uri = params.text_document.uri;
+
+        tracing::debug!("Inlay hint request");
+
+        let inlay_hints = self.settings().inlay_hints;
+        if !inlay_hints.enabled {
+            return Ok(None);
+        }
+
+        if let Some(content) = self.documents.get(&self.document_key(&uri)) {
+            let hints = self.analyzer().inlay_hints(content.value(), params.range, &inlay_hints);
+            return Ok(Some(hints));
         }
 
         Ok(None)
     }
 
-    async fn inlay_hint(&self, params: InlayHintParams) -> Result<Option<Vec<InlayHint>>> {
+    async fn code_lens(&self, params: CodeLensParams) -> Result<Option<Vec<CodeLens>>> {
         let uri = params.text_document.uri;
 
-        tracing::debug!("Inlay hint request");
+        tracing::debug!("Code lens request");
 
-        if let Some(content) = self.documents.get(&uri.to_string()) {
-            let mut hints = Vec::new();
-            let symbols = self.analyzer.extract_symbols(content.value());
-
-            // Add type hints for variables without annotations
-            for symbol in symbols {
-                if matches!(symbol.kind, analyzer::SymbolKind::Variable) {
-                    hints.push(InlayHint {
-                        position: Position {
-                            line: symbol.line as u32,
-                            character: (symbol.col + symbol.length) as u32,
-                        },
-                        label: InlayHintLabel::String(": Unknown".to_string()),
-                        kind: Some(InlayHintKind::TYPE),
-                        text_edits: None,
-                        tooltip: Some(InlayHintTooltip::String(
-                            "Type could not be inferred".to_string()
-                        )),
-                        padding_left: None,
-                        padding_right: None,
+        if !self.settings().code_lens.enabled {
+            return Ok(None);
+        }
+
+        if let Some(content) = self.documents.get(&self.document_key(&uri)) {
+            let analyzer = self.analyzer();
+            let lenses = analyzer.definition_uses(content.value())
+                .into_iter()
+                .map(|(symbol, uses)| {
+                    let start = Position { line: symbol.line as u32, character: symbol.col as u32 };
+                    CodeLens {
+                        range: Range { start, end: start },
+                        command: Some(Command {
+                            title: format!("{} reference{}", uses, if uses == 1 { "" } else { "s" }),
+                            command: String::new(),
+                            arguments: None,
+                        }),
                         data: None,
-                    });
-                }
-            }
+                    }
+                })
+                .collect();
 
-            return Ok(Some(hints));
+            return Ok(Some(lenses));
         }
 
         Ok(None)
@@ -569,7 +1081,674 @@ async fn main() {
     let stdin = tokio::io::stdin();
     let stdout = tokio::io::stdout();
 
-    let (service, socket) = LspService::new(|client| TypthonLanguageServer::new(client));
+    let (service, socket) = LspService::build(TypthonLanguageServer::new)
+        .custom_method("typthon/serverStatus", TypthonLanguageServer::server_status)
+        .finish();
     Server::new(stdin, stdout, socket).serve(service).await;
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_configuration_changes_apply_without_reinitializing() {
+        let (service, _socket) = LspService::new(TypthonLanguageServer::new);
+        let server = service.inner();
+        let uri = Url::parse("file:///workspace/example.py").unwrap();
+        let text = "def area(r):\n    return r\n\nsize = area(2)\n";
+        server.did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(uri.clone(), "python".to_string(), 1, text.to_string()),
+        }).await;
+
+        let hints = || server.inlay_hint(InlayHintParams {
+            text_document: TextDocumentIdentifier::new(uri.clone()),
+            range: Range::new(Position::new(0, 0), Position::new(4, 0)),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        });
+        let lenses = || server.code_lens(CodeLensParams {
+            text_document: TextDocumentIdentifier::new(uri.clone()),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        });
+        assert_eq!(hints().await.unwrap().map(|h| h.len()), Some(1));
+
+        server.did_change_configuration(DidChangeConfigurationParams {
+            settings: json!({ "typthon": { "inlayHints": { "enabled": false }, "strictness": "strict" } }),
+        }).await;
+        assert!(hints().await.unwrap().is_none());
+        let lens = lenses().await.unwrap().unwrap();
+        assert_eq!(lens[0].command.as_ref().unwrap().title, "1 reference");
+
+        let status = server.server_status().await.unwrap();
+        assert_eq!(status["settings"]["strictness"], "strict");
+        assert_eq!(status["settings"]["sources"]["strictness"], "editor");
+        assert_eq!(status["settings"]["sources"]["inlayHints.enabled"], "editor");
+
+        // A second payload replaces the first rather than merging with it
+        server.did_change_configuration(DidChangeConfigurationParams {
+            settings: json!({ "typthon": { "codeLens": { "enabled": false } } }),
+        }).await;
+        assert_eq!(hints().await.unwrap().map(|h| h.len()), Some(1));
+        assert_eq!(lenses().await.unwrap(), None);
+        assert_eq!(server.server_status().await.unwrap()["settings"]["strictness"], "basic");
+    }
+
+    #[tokio::test]
+    async fn test_differently_cased_uris_share_one_document() {
+        use typthon::infrastructure::CaseSensitivity;
+
+        let insensitive = PathNormalizer::native().with_case_sensitivity(CaseSensitivity::Insensitive);
+        let (service, _socket) = LspService::new(|client| TypthonLanguageServer::new(client).with_paths(insensitive));
+        let server = service.inner();
+        let opened = Url::parse("file:///workspace/Pkg/Example.py").unwrap();
+        let edited = Url::parse("file:///workspace/pkg/ex%61mple.PY").unwrap();
+
+        server.did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(opened.clone(), "python".to_string(), 1, "x = 1\n".to_string()),
+        }).await;
+        server.did_change(DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier::new(edited.clone(), 2),
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: "def area(r):\n    return r\n".to_string(),
+            }],
+        }).await;
+        assert_eq!(server.server_status().await.unwrap()["openDocuments"], 1);
+
+        let lenses = server.code_lens(CodeLensParams {
+            text_document: TextDocumentIdentifier::new(opened.clone()),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        }).await.unwrap().unwrap();
+        assert_eq!(lenses.len(), 1);
+
+        server.did_close(DidCloseTextDocumentParams { text_document: TextDocumentIdentifier::new(edited) }).await;
+        assert_eq!(server.server_status().await.unwrap()["openDocuments"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_hover_and_completion_show_docstrings() {
+        let (service, _socket) = LspService::new(TypthonLanguageServer::new);
+        let server = service.inner();
+        let uri = Url::parse("file:///workspace/geometry.py").unwrap();
+        let text = "def area(width: int, height: int = 1) -> int:\n    \"\"\"Area of a rectangle.\n\n    Sides must be non-negative.\n    \"\"\"\n    return width * height\n\narea(2)\n";
+        server.did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(uri.clone(), "python".to_string(), 1, text.to_string()),
+        }).await;
+        let position = |line, character| TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier::new(uri.clone()),
+            position: Position { line, character },
+        };
+
+        let hover = server.hover(HoverParams {
+            text_document_position_params: position(7, 1),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        }).await.unwrap().unwrap();
+        assert_eq!(hover.contents, HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: "```python\narea: (int, int) -> int\n```\n\n\
+                    Area of a rectangle.\n\nSides must be non-negative.".to_string(),
+        }));
+
+        let completions = server.completion(CompletionParams {
+            text_document_position: position(7, 0),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+            context: None,
+        }).await.unwrap();
+        let Some(CompletionResponse::Array(items)) = completions else { panic!("expected completion items") };
+        let item = items.into_iter().find(|item| item.label == "area").unwrap();
+        assert_eq!(item.detail.as_deref(), Some("def area(width: int, height: int = 1) -> int"));
+        assert_eq!(item.documentation, None);
+
+        let resolved = server.completion_resolve(item).await.unwrap();
+        assert_eq!(resolved.documentation, Some(Documentation::MarkupContent(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: "Area of a rectangle.".to_string(),
+        })));
+    }
+
+    #[tokio::test]
+    async fn test_body_edits_recheck_one_function() {
+        use std::time::Instant;
+        use typthon::compiler::analysis::checker::phases;
+
+        // Only a full check goes through the statements phase
+        const DELAY: Duration = Duration::from_millis(300);
+        let (service, _socket) = LspService::new(TypthonLanguageServer::new);
+        let server = service.inner();
+        *server.analyzer.write().unwrap() =
+            Arc::new(DocumentAnalyzer::new().with_phase_delay(phases::STATEMENTS, DELAY));
+        let uri = Url::parse("file:///workspace/scaling.py").unwrap();
+        let key = server.document_key(&uri);
+        let source = |label: &str, param: &str| format!(
+            "from functools import partial\n\n\
+             def scale(value: int, {param}: int) -> int:\n    label = '{label}'\n    return value * {param}\n\n\
+             def name() -> str:\n    return 'scale'\n\n\
+             double = partial(scale, factor=2)\n"
+        );
+        let change = |version, text: String| server.did_change(DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier::new(uri.clone(), version),
+            content_changes: vec![TextDocumentContentChangeEvent { range: None, range_length: None, text }],
+        });
+        let errors = || -> Vec<String> {
+            server.checked.get(&key).unwrap().errors.iter().map(|e| e.message.clone()).collect()
+        };
+        let metrics = || async { server.server_status().await.unwrap()["metrics"].clone() };
+
+        server.did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(uri.clone(), "python".to_string(), 1, source("a", "factor")),
+        }).await;
+        assert!(errors().is_empty(), "{:?}", errors());
+
+        let started = Instant::now();
+        change(2, source("a longer label", "factor")).await;
+        assert!(started.elapsed() < DELAY, "{:?}", started.elapsed());
+        assert_eq!(metrics().await, json!({ "incrementalHits": 1, "fullReanalyses": 1 }));
+
+        let started = Instant::now();
+        change(3, source("a longer label", "ratio")).await;
+        assert!(started.elapsed() >= DELAY);
+        assert_eq!(errors(), vec!["partial() got an unexpected keyword argument 'factor'"]);
+        assert_eq!(metrics().await, json!({ "incrementalHits": 1, "fullReanalyses": 2 }));
+    }
+
+    #[tokio::test]
+    async fn test_incremental_changes_all_apply() {
+        let (service, _socket) = LspService::new(TypthonLanguageServer::new);
+        let server = service.inner();
+        let uri = Url::parse("file:///workspace/party.py").unwrap();
+        let key = server.document_key(&uri);
+        server.did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(uri.clone(), "python".to_string(), 1, "tag = \"🎉\"\ncount: int = 1\n".to_string()),
+        }).await;
+
+        let edit = |line, start, end, text: &str| TextDocumentContentChangeEvent {
+            range: Some(Range { start: Position::new(line, start), end: Position::new(line, end) }),
+            range_length: None,
+            text: text.to_string(),
+        };
+        server.did_change(DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier::new(uri.clone(), 2),
+            // A later line first, then after the emoji, two UTF-16 units wide
+            content_changes: vec![edit(1, 13, 14, "\"one\""), edit(0, 9, 9, "!")],
+        }).await;
+
+        assert_eq!(server.documents.get(&key).unwrap().to_string(), "tag = \"🎉!\"\ncount: int = \"one\"\n");
+        let errors: Vec<String> = server.checked.get(&key).unwrap().errors.iter().map(|e| e.message.clone()).collect();
+        assert_eq!(errors, vec!["Type mismatch: cannot assign str to variable 'count' of type int"]);
+    }
+
+    #[tokio::test]
+    async fn test_errors_and_definitions_cross_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let util = dir.path().join("util.py");
+        let main = dir.path().join("main.py");
+        fs::write(&util, "def scale(value: int, factor: int) -> int:\n    return value * factor\n").unwrap();
+        fs::write(dir.path().join(".gitignore"), "build/\n").unwrap();
+        fs::create_dir(dir.path().join("build")).unwrap();
+        fs::write(dir.path().join("build/copy.py"), "def scale() -> None:\n    pass\n").unwrap();
+
+        let (service, _socket) = LspService::new(TypthonLanguageServer::new);
+        let server = service.inner();
+        let folder = Url::from_directory_path(dir.path()).unwrap();
+        server.initialize(InitializeParams {
+            workspace_folders: Some(vec![WorkspaceFolder { uri: folder, name: "fixture".to_string() }]),
+            ..InitializeParams::default()
+        }).await.unwrap();
+        server.index_workspace().await;
+        assert_eq!(server.server_status().await.unwrap()["indexedFiles"], 1);
+
+        let main_uri = Url::from_file_path(&main).unwrap();
+        let util_uri = Url::from_file_path(&util).unwrap();
+        let text = "from util import scale\n\nsize: int = scale(\"2\", 3)\n";
+        server.did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(main_uri.clone(), "python".to_string(), 1, text.to_string()),
+        }).await;
+        let errors = || -> Vec<String> {
+            let checked = server.checked.get(&server.document_key(&main_uri)).unwrap();
+            checked.errors.iter().map(|e| e.message.clone()).collect()
+        };
+        assert_eq!(errors(), vec!["Argument 0 type mismatch: expected int, got str"]);
+
+        let definition = server.goto_definition(GotoDefinitionParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier::new(main_uri.clone()),
+                position: Position::new(2, 14),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        }).await.unwrap();
+        assert_eq!(definition, Some(GotoDefinitionResponse::Scalar(Location {
+            uri: util_uri.clone(),
+            range: Range { start: Position::new(0, 0), end: Position::new(0, 5) },
+        })));
+
+        // Editing util.py re-checks main.py against the new signature
+        server.did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(util_uri.clone(), "python".to_string(), 1, fs::read_to_string(&util).unwrap()),
+        }).await;
+        server.did_change(DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier::new(util_uri.clone(), 2),
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: Some(Range { start: Position::new(0, 17), end: Position::new(0, 20) }),
+                range_length: None,
+                text: "str".to_string(),
+            }],
+        }).await;
+        assert!(errors().is_empty(), "{:?}", errors());
+
+        let symbols = server.symbol(WorkspaceSymbolParams {
+            query: "SCA".to_string(),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        }).await.unwrap().unwrap();
+        let found: Vec<(String, &Url)> = symbols.iter().map(|symbol| (symbol.name.clone(), &symbol.location.uri)).collect();
+        assert_eq!(found, vec![("scale".to_string(), &util_uri)]);
+    }
+
+    #[tokio::test]
+    async fn test_code_actions_add_imports_and_annotations() {
+        let (service, _socket) = LspService::new(TypthonLanguageServer::new);
+        let server = service.inner();
+        let uri = &Url::parse("file:///workspace/circle.py").unwrap();
+        let text = "\"\"\"Circles.\"\"\"\nimport os\n\ndef area(r: float, scale=2) -> float:\n    return pi * r * r * scale\n\nroot = sqrt(area(2.0))\nlabel = os.sep\n";
+        server.did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(uri.clone(), "python".to_string(), 1, String::new()),
+        }).await;
+
+        // The actions for `range` once the document reads `text`, with its
+        // diagnostics as context
+        let version = &std::cell::Cell::new(1);
+        let actions = |text: String, range: Range| async move {
+            version.set(version.get() + 1);
+            server.did_change(DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier::new(uri.clone(), version.get()),
+                content_changes: vec![TextDocumentContentChangeEvent { range: None, range_length: None, text: text.clone() }],
+            }).await;
+            let diagnostics = {
+                let checked = server.checked.get(&server.document_key(uri)).unwrap();
+                lsp_diagnostics(&server.analyzer(), &text, checked.errors.clone(), checked.lints.clone())
+            };
+            let actions = server.code_action(CodeActionParams {
+                text_document: TextDocumentIdentifier::new(uri.clone()),
+                range,
+                context: CodeActionContext { diagnostics, ..CodeActionContext::default() },
+                work_done_progress_params: WorkDoneProgressParams::default(),
+                partial_result_params: PartialResultParams::default(),
+            }).await.unwrap().unwrap_or_default();
+            actions.into_iter().filter_map(|action| match action {
+                CodeActionOrCommand::CodeAction(action) => Some(action),
+                _ => None,
+            }).collect::<Vec<_>>()
+        };
+        // `text` with the edits of the action titled `title`
+        let apply = |text: &str, actions: Vec<CodeAction>, title: &str| {
+            let titles: Vec<String> = actions.iter().map(|action| action.title.clone()).collect();
+            let action = actions.into_iter().find(|action| action.title == title).unwrap_or_else(|| panic!("{:?}", titles));
+            let mut edits = action.edit.unwrap().changes.unwrap().remove(uri).unwrap();
+            edits.sort_by_key(|edit| std::cmp::Reverse(edit.range.start));
+            let mut document = Document::new(text.to_string());
+            for edit in edits {
+                document.apply(&TextDocumentContentChangeEvent { range: Some(edit.range), range_length: None, text: edit.new_text });
+            }
+            document.to_string()
+        };
+        let at = |line, character| Range::new(Position::new(line, character), Position::new(line, character));
+
+        let everywhere = Range::new(Position::new(0, 0), Position::new(8, 0));
+        let found = actions(text.to_string(), everywhere).await;
+        let titles: Vec<&str> = found.iter().map(|action| action.title.as_str()).collect();
+        assert_eq!(titles, vec!["Add `from math import pi`", "Add `from math import sqrt`"]);
+
+        let text = apply(text, found, "Add `from math import pi`");
+        let text = apply(&text, actions(text.clone(), everywhere).await, "Add `from math import sqrt`");
+        assert!(text.starts_with("\"\"\"Circles.\"\"\"\nimport os\nfrom math import pi\nfrom math import sqrt\n\n"), "{}", text);
+
+        let text = apply(&text, actions(text.clone(), at(8, 2)).await, "Add type annotation `float`");
+        let text = apply(&text, actions(text.clone(), at(5, 23)).await, "Add type annotation `int`");
+        assert!(text.contains("def area(r: float, scale: int = 2) -> float:\n"), "{}", text);
+        assert!(text.contains("root: float = sqrt(area(2.0))\n"), "{}", text);
+        assert!(server.analyzer().analyze(&text).is_empty(), "{:?}", server.analyzer().analyze(&text));
+    }
+
+    #[tokio::test]
+    async fn test_organize_imports_is_a_source_action() {
+        let (service, _socket) = LspService::new(TypthonLanguageServer::new);
+        let server = service.inner();
+        let uri = &Url::parse("file:///workspace/paths.py").unwrap();
+        let text = "import sys\nimport os\nfrom typing import List, Any\n\nroots: List[str] = [os.sep]\n";
+        server.did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(uri.clone(), "python".to_string(), 1, text.to_string()),
+        }).await;
+
+        let actions = |only: Vec<CodeActionKind>| server.code_action(CodeActionParams {
+            text_document: TextDocumentIdentifier::new(uri.clone()),
+            range: Range::default(),
+            context: CodeActionContext { only: Some(only), ..CodeActionContext::default() },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        });
+        assert!(actions(vec![CodeActionKind::REFACTOR]).await.unwrap().unwrap().is_empty());
+
+        let found = actions(vec![CodeActionKind::SOURCE]).await.unwrap().unwrap();
+        let [CodeActionOrCommand::CodeAction(action)] = &found[..] else { panic!("{:?}", found) };
+        assert_eq!(action.kind, Some(CodeActionKind::SOURCE_ORGANIZE_IMPORTS));
+        let edits = action.edit.clone().unwrap().changes.unwrap().remove(uri).unwrap();
+        let mut document = Document::new(text.to_string());
+        for edit in edits.into_iter().rev() {
+            document.apply(&TextDocumentContentChangeEvent { range: Some(edit.range), range_length: None, text: edit.new_text });
+        }
+        assert_eq!(document.to_string(), "import os\nfrom typing import List\n\nroots: List[str] = [os.sep]\n");
+    }
+
+    #[tokio::test]
+    async fn test_inlay_hints_show_inferred_types_and_parameter_names() {
+        let (service, _socket) = LspService::new(TypthonLanguageServer::new);
+        let server = service.inner();
+        let uri = Url::parse("file:///workspace/shapes.py").unwrap();
+        let text = "\
+class Point:
+    def __init__(self, x: int, y: int) -> None:
+        self.x = x
+
+    def doubled(self):
+        return self.x * 2
+
+def describe(point: Point, prefix: str):
+    if point.x > 0:
+        return prefix
+    return None
+
+origin = Point(0, 0)
+count = 3
+label = describe(origin, \"at\")
+";
+        server.did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(uri.clone(), "python".to_string(), 1, text.to_string()),
+        }).await;
+        let hints = |range: Range| server.inlay_hint(InlayHintParams {
+            text_document: TextDocumentIdentifier::new(uri.clone()),
+            range,
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        });
+        let labels = |hints: Vec<InlayHint>| -> Vec<(u32, u32, String)> {
+            hints.into_iter()
+                .map(|hint| match hint.label {
+                    InlayHintLabel::String(label) => (hint.position.line, hint.position.character, label),
+                    InlayHintLabel::LabelParts(_) => unreachable!(),
+                })
+                .collect()
+        };
+
+        let all = hints(Range::new(Position::new(0, 0), Position::new(15, 0))).await.unwrap().unwrap();
+        assert_eq!(labels(all.clone()), vec![
+            (4, 21, " -> int".to_string()),
+            (7, 39, " -> str | None".to_string()),
+            (12, 6, ": Point".to_string()),
+            (12, 15, "x:".to_string()),
+            (12, 18, "y:".to_string()),
+            (13, 5, ": int".to_string()),
+            (14, 5, ": str | None".to_string()),
+            (14, 17, "point:".to_string()),
+            (14, 25, "prefix:".to_string()),
+        ]);
+
+        // Accepting every type hint writes out annotations that check
+        let mut document = Document::new(text.to_string());
+        let mut edits: Vec<TextEdit> = all.into_iter().flat_map(|hint| hint.text_edits.unwrap_or_default()).collect();
+        edits.sort_by_key(|edit| std::cmp::Reverse(edit.range.start));
+        for edit in edits {
+            document.apply(&TextDocumentContentChangeEvent { range: Some(edit.range), range_length: None, text: edit.new_text });
+        }
+        assert!(document.contains("    def doubled(self) -> int:\n"), "{}", &*document);
+        assert!(document.contains("origin: Point = Point(0, 0)\n"), "{}", &*document);
+        assert!(server.analyzer().analyze(&document).is_empty(), "{:?}", server.analyzer().analyze(&document));
+
+        // Only the requested lines, and no literal types when told not to
+        server.did_change_configuration(DidChangeConfigurationParams {
+            settings: json!({ "typthon": { "inlayHints": { "skipLiterals": true } } }),
+        }).await;
+        let visible = hints(Range::new(Position::new(12, 0), Position::new(13, 9))).await.unwrap().unwrap();
+        assert_eq!(labels(visible), vec![
+            (12, 6, ": Point".to_string()),
+            (12, 15, "x:".to_string()),
+            (12, 18, "y:".to_string()),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_signature_help_follows_the_arguments_typed() {
+        let (service, _socket) = LspService::new(TypthonLanguageServer::new);
+        let server = service.inner();
+        let uri = &Url::parse("file:///workspace/colors.py").unwrap();
+        let text = "\
+def mix(red: int, green: int, blue: int = 0) -> str:
+    \"Blend three channels.\"
+    return f\"{red}{green}{blue}\"
+
+class Palette:
+    def add(self, name: str, *shades: int) -> None:
+        pass
+
+palette = Palette()
+";
+        server.did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(uri.clone(), "python".to_string(), 1, text.to_string()),
+        }).await;
+
+        let version = &std::cell::Cell::new(1);
+        let typed = &std::cell::RefCell::new(String::new());
+        // Where what's been typed after the document's last line ends
+        let end = |typed: &str| {
+            let last = typed.rsplit('\n').next().unwrap_or_default();
+            Position::new(9 + typed.matches('\n').count() as u32, last.encode_utf16().count() as u32)
+        };
+        let type_text = |text: &'static str| async move {
+            let start = end(&typed.borrow());
+            typed.borrow_mut().push_str(text);
+            version.set(version.get() + 1);
+            server.did_change(DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier::new(uri.clone(), version.get()),
+                content_changes: vec![TextDocumentContentChangeEvent {
+                    range: Some(Range::new(start, start)),
+                    range_length: None,
+                    text: text.to_string(),
+                }],
+            }).await;
+            let position = end(&typed.borrow());
+            let help = server.signature_help(SignatureHelpParams {
+                context: None,
+                text_document_position_params: TextDocumentPositionParams::new(TextDocumentIdentifier::new(uri.clone()), position),
+                work_done_progress_params: WorkDoneProgressParams::default(),
+            }).await.unwrap()?;
+            let signature = help.signatures.into_iter().next()?;
+            let active = help.active_parameter.map(|index| match &signature.parameters.as_ref().unwrap()[index as usize].label {
+                ParameterLabel::LabelOffsets([start, end]) => signature.label[*start as usize..*end as usize].to_string(),
+                ParameterLabel::Simple(label) => label.clone(),
+            });
+            Some((signature.label, active))
+        };
+        let help = |label: &str, active: &str| Some((label.to_string(), Some(active.to_string())));
+        let mix = "mix(red: int, green: int, blue: int = 0) -> str";
+
+        assert_eq!(type_text("color = mix(").await, help(mix, "red: int"));
+        assert_eq!(type_text("1, ").await, help(mix, "green: int"));
+        assert_eq!(type_text("2, ").await, help(mix, "blue: int = 0"));
+        assert_eq!(type_text("3, ").await, Some((mix.to_string(), None)));
+
+        // Nested calls resolve the innermost, builtins included
+        assert_eq!(type_text("len(").await, help("len(obj: Any, /) -> int", "obj: Any"));
+        assert_eq!(type_text("[1, 2]), blue=").await, help(mix, "blue: int = 0"));
+
+        // Methods through the receiver's type, without `self`
+        let add = "add(name: str, *shades: int) -> None";
+        assert_eq!(type_text("4)\npalette.add(").await, help(add, "name: str"));
+        assert_eq!(type_text("\"sky\", 1, 2").await, help(add, "*shades: int"));
+    }
+
+    #[tokio::test]
+    async fn test_semantic_tokens_send_edits_after_a_change() {
+        let (service, _socket) = LspService::new(TypthonLanguageServer::new);
+        let server = service.inner();
+        let uri = &Url::parse("file:///workspace/shapes.py").unwrap();
+        let text = "\
+import os
+from typing import Final
+
+LIMIT: Final = 10
+
+class Shape:
+    def __init__(self, size: int) -> None:
+        self.size = size
+
+    @property
+    def area(self) -> int:
+        return self.size * LIMIT
+
+shape = Shape(2)
+label = \"area\"
+";
+        server.did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(uri.clone(), "python".to_string(), 1, text.to_string()),
+        }).await;
+
+        let document = TextDocumentIdentifier::new(uri.clone());
+        let full = server.semantic_tokens_full(SemanticTokensParams {
+            text_document: document.clone(),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        }).await.unwrap();
+        let Some(SemanticTokensResult::Tokens(full)) = full else { panic!("expected tokens") };
+        // Back to absolute positions: line, column, length, type, modifiers
+        let absolute = |data: &[SemanticToken]| {
+            let (mut line, mut start) = (0, 0);
+            data.iter().map(|token| {
+                line += token.delta_line;
+                start = if token.delta_line == 0 { start + token.delta_start } else { token.delta_start };
+                (line, start, token.length, token.token_type, token.token_modifiers_bitset)
+            }).collect::<Vec<_>>()
+        };
+        let tokens = absolute(&full.data);
+        let (definition, readonly) = (semantic_tokens::DEFINITION, semantic_tokens::READONLY);
+        for expected in [
+            (0, 7, 2, semantic_tokens::NAMESPACE, definition),
+            (3, 0, 5, semantic_tokens::VARIABLE, definition | readonly),
+            (3, 7, 5, semantic_tokens::TYPE, 0),
+            (3, 15, 2, semantic_tokens::NUMBER, 0),
+            (5, 6, 5, semantic_tokens::CLASS, definition),
+            (6, 8, 8, semantic_tokens::METHOD, definition),
+            (6, 17, 4, semantic_tokens::SELF_PARAMETER, definition),
+            (6, 23, 4, semantic_tokens::PARAMETER, definition),
+            (7, 13, 4, semantic_tokens::PROPERTY, definition),
+            (9, 5, 8, semantic_tokens::DECORATOR, 0),
+            (11, 27, 5, semantic_tokens::VARIABLE, readonly),
+            (13, 8, 5, semantic_tokens::CLASS, 0),
+            (14, 8, 6, semantic_tokens::STRING, 0),
+        ] {
+            assert!(tokens.contains(&expected), "{:?} not in {:?}", expected, tokens);
+        }
+
+        let text = text.replace("Shape(2)", "Shape(size=2)");
+        server.did_change(DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier::new(uri.clone(), 2),
+            content_changes: vec![TextDocumentContentChangeEvent { range: None, range_length: None, text }],
+        }).await;
+        let delta = |previous_result_id: &str| server.semantic_tokens_full_delta(SemanticTokensDeltaParams {
+            text_document: document.clone(),
+            previous_result_id: previous_result_id.to_string(),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        });
+        let Some(SemanticTokensFullDeltaResult::TokensDelta(changed)) = delta(full.result_id.as_deref().unwrap()).await.unwrap() else {
+            panic!("expected edits")
+        };
+        // Only the end of the document changed, and the edits bring the
+        // tokens sent first up to date
+        assert_eq!(changed.edits.len(), 1);
+        assert!(changed.edits[0].start >= 20 * 5);
+        let flat = |data: &[SemanticToken]| -> Vec<u32> {
+            data.iter()
+                .flat_map(|t| [t.delta_line, t.delta_start, t.length, t.token_type, t.token_modifiers_bitset])
+                .collect()
+        };
+        let mut patched = flat(&full.data);
+        let edit = &changed.edits[0];
+        let start = edit.start as usize;
+        patched.splice(start..start + edit.delete_count as usize, flat(edit.data.as_deref().unwrap_or_default()));
+        let Some(SemanticTokensFullDeltaResult::Tokens(current)) = delta("stale").await.unwrap() else {
+            panic!("expected all the tokens for an unknown result id")
+        };
+        assert_eq!(patched, flat(&current.data));
+        assert!(absolute(&current.data).contains(&(13, 19, 1, semantic_tokens::NUMBER, 0)));
+    }
+
+    #[tokio::test]
+    async fn test_rename_follows_scopes() {
+        let (service, _socket) = LspService::new(TypthonLanguageServer::new);
+        let server = service.inner();
+        let uri = &Url::parse("file:///workspace/stats.py").unwrap();
+        let text = "\
+x = 3
+
+def scale(x: int, factor: int) -> int:
+    \"Multiply x by factor.\"
+    # x is the value to scale
+    return max(x, 1) * factor
+
+class Counter:
+    def __init__(self) -> None:
+        self.count = x
+
+counter = Counter()
+total = scale(counter.count, x)
+";
+        server.did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(uri.clone(), "python".to_string(), 1, text.to_string()),
+        }).await;
+
+        let rename = |line, character, new_name: &str| server.rename(RenameParams {
+            text_document_position: TextDocumentPositionParams::new(TextDocumentIdentifier::new(uri.clone()), Position::new(line, character)),
+            new_name: new_name.to_string(),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        });
+        let renamed = |edit: Option<WorkspaceEdit>| {
+            let mut document = Document::new(text.to_string());
+            let mut edits = edit.unwrap().changes.unwrap().remove(uri).unwrap();
+            edits.sort_by_key(|edit| std::cmp::Reverse(edit.range.start));
+            for edit in edits {
+                document.apply(&TextDocumentContentChangeEvent { range: Some(edit.range), range_length: None, text: edit.new_text });
+            }
+            document.to_string()
+        };
+
+        // The parameter, used inside `scale` only; not the global, `max`,
+        // the docstring or the comment
+        let document = renamed(rename(5, 15, "value").await.unwrap());
+        assert!(document.contains("def scale(value: int, factor: int) -> int:\n"), "{}", document);
+        assert!(document.contains("    \"Multiply x by factor.\"\n    # x is the value to scale\n"), "{}", document);
+        assert!(document.contains("return max(value, 1) * factor\n"), "{}", document);
+        assert!(document.starts_with("x = 3\n") && document.contains("self.count = x\n"), "{}", document);
+
+        // Attributes through the receiver and through the inferred type
+        let document = renamed(rename(12, 24, "value").await.unwrap());
+        assert!(document.contains("self.value = x\n") && document.contains("scale(counter.value, x)"), "{}", document);
+
+        let references = server.references(ReferenceParams {
+            text_document_position: TextDocumentPositionParams::new(TextDocumentIdentifier::new(uri.clone()), Position::new(0, 0)),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+            context: ReferenceContext { include_declaration: true },
+        }).await.unwrap().unwrap();
+        let lines: Vec<(u32, u32)> = references.iter().map(|location| (location.range.start.line, location.range.start.character)).collect();
+        assert_eq!(lines, vec![(0, 0), (9, 21), (12, 29)]);
+
+        let refused = |line, character, new_name| async move { rename(line, character, new_name).await.unwrap_err().message };
+        assert_eq!(refused(5, 15, "factor").await, "`factor` is already bound where `x` is used");
+        assert_eq!(refused(5, 12, "largest").await, "`max` is a builtin");
+        assert_eq!(refused(5, 4, "give").await, "`return` is a keyword");
+        assert_eq!(refused(0, 0, "2x").await, "`2x` is not a valid name");
+    }
+}
@@ -0,0 +1,326 @@
+/*!
+Organize imports support for LSP.
+
+Rewrites the imports a module starts with: unused names dropped, one
+`import` per module, `from` imports of the same module merged, and the
+rest sorted into `__future__`, standard library, third-party and local
+groups. Unused names in imports further down are dropped where they are.
+*/
+
+use rustpython_parser::ast::{Alias, Ranged, Stmt};
+use std::ops::Range;
+use typthon::compiler::analysis::unused_imports::statement_lines;
+use typthon::compiler::analysis::UnusedImport;
+
+/// Top-level modules of the standard library
+const STDLIB: &[&str] = &[
+    "abc", "argparse", "array", "ast", "asyncio", "atexit", "base64", "binascii", "bisect", "builtins", "bz2",
+    "calendar", "cmath", "codecs", "collections", "colorsys", "concurrent", "configparser", "contextlib",
+    "contextvars", "copy", "copyreg", "csv", "ctypes", "dataclasses", "datetime", "decimal", "difflib", "dis",
+    "email", "enum", "errno", "faulthandler", "fcntl", "filecmp", "fileinput", "fnmatch", "fractions",
+    "ftplib", "functools", "gc", "getopt", "getpass", "gettext", "glob", "graphlib", "gzip", "hashlib", "heapq",
+    "hmac", "html", "http", "imaplib", "importlib", "inspect", "io", "ipaddress", "itertools", "json", "keyword",
+    "linecache", "locale", "logging", "lzma", "mailbox", "marshal", "math", "mimetypes", "mmap",
+    "multiprocessing", "numbers", "operator", "os", "pathlib", "pdb", "pickle", "pkgutil", "platform",
+    "plistlib", "poplib", "posixpath", "pprint", "profile", "pstats", "queue", "random", "re", "readline",
+    "reprlib", "resource", "sched", "secrets", "select", "selectors", "shelve", "shlex", "shutil", "signal",
+    "site", "smtplib", "socket", "socketserver", "sqlite3", "ssl", "stat", "statistics", "string", "struct",
+    "subprocess", "sys", "sysconfig", "tarfile", "tempfile", "textwrap", "threading", "time", "timeit",
+    "tkinter", "token", "tokenize", "tomllib", "trace", "traceback", "tracemalloc", "types", "typing",
+    "unicodedata", "unittest", "urllib", "uuid", "venv", "warnings", "weakref", "webbrowser", "wsgiref",
+    "xml", "zipfile", "zipimport", "zlib", "zoneinfo",
+];
+
+/// Longest line a `from` import is written on before it's wrapped
+const MAX_LINE: usize = 79;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Group {
+    Future,
+    Stdlib,
+    ThirdParty,
+    Local,
+}
+
+/// One import as it's written back, with the comments above it
+struct Import {
+    group: Group,
+    /// `from` imports sort after plain ones in their group
+    from: bool,
+    /// `os`, `os.path as p`, or for `from` imports `..pkg.mod`
+    module: String,
+    names: Vec<String>,
+    comments: Vec<String>,
+}
+
+impl Import {
+    fn render(&self) -> String {
+        let mut text: String = self.comments.iter().map(|comment| format!("{}\n", comment)).collect();
+        if !self.from {
+            text.push_str(&format!("import {}\n", self.module));
+            return text;
+        }
+        let line = format!("from {} import {}", self.module, self.names.join(", "));
+        if line.len() <= MAX_LINE {
+            text.push_str(&line);
+            text.push('\n');
+        } else {
+            text.push_str(&format!("from {} import (\n", self.module));
+            for name in &self.names {
+                text.push_str(&format!("    {},\n", name));
+            }
+            text.push_str(")\n");
+        }
+        text
+    }
+}
+
+/// Edits organizing the imports of `content`, whose statements are `body`:
+/// the leading imports replaced by their organized text, and each import
+/// further down with an unused name rewritten without it. Empty when
+/// there's nothing to change. `is_local` tells the workspace's own top-level
+/// modules apart from third-party ones
+pub fn organize_imports(
+    content: &str,
+    body: &[Stmt],
+    unused: &[UnusedImport],
+    is_local: impl Fn(&str) -> bool,
+) -> Vec<(Range<usize>, String)> {
+    let used = |alias: &Alias| !unused.iter().any(|import| import.range.start == alias.start().to_usize());
+    let docstring = body.first().is_some_and(|stmt| stmt.as_expr_stmt().is_some_and(|expr| expr.value.is_constant_expr()));
+    let leading = body[usize::from(docstring)..].iter()
+        .take_while(|stmt| matches!(stmt, Stmt::Import(_) | Stmt::ImportFrom(_)))
+        .count();
+    let (block, rest) = body[usize::from(docstring)..].split_at(leading);
+
+    let mut edits = Vec::new();
+    if let (Some(first), Some(last)) = (block.first(), block.last()) {
+        let start = line_start(content, first.start().to_usize());
+        let end = match content[last.end().to_usize()..].find('\n') {
+            Some(newline) => last.end().to_usize() + newline + 1,
+            None => content.len(),
+        };
+
+        let mut imports: Vec<Import> = Vec::new();
+        let mut after = start;
+        for stmt in block {
+            // Comments on the lines before the statement and after it on its own
+            let mut comments: Vec<String> = content[after..stmt.start().to_usize()].lines()
+                .map(str::trim)
+                .filter(|line| line.starts_with('#'))
+                .map(str::to_string)
+                .collect();
+            let line_end = content[stmt.end().to_usize()..].find('\n').map_or(content.len(), |i| stmt.end().to_usize() + i);
+            let trailing = content[stmt.end().to_usize()..line_end].trim();
+            if trailing.starts_with('#') {
+                comments.push(trailing.to_string());
+            }
+            after = (line_end + 1).min(content.len());
+
+            match stmt {
+                Stmt::Import(import) => {
+                    for alias in import.names.iter().filter(|alias| used(alias)) {
+                        let top = alias.name.split('.').next().unwrap_or_default();
+                        imports.push(Import {
+                            group: group(top, 0, &is_local),
+                            from: false,
+                            module: alias_text(alias),
+                            names: Vec::new(),
+                            comments: std::mem::take(&mut comments),
+                        });
+                    }
+                }
+                Stmt::ImportFrom(import) => {
+                    let level = import.level.map_or(0, |level| level.to_u32());
+                    let module = import.module.as_deref().unwrap_or_default();
+                    let names: Vec<String> = import.names.iter().filter(|alias| used(alias)).map(alias_text).collect();
+                    if names.is_empty() {
+                        continue;
+                    }
+                    let written = format!("{}{}", ".".repeat(level as usize), module);
+                    match imports.iter_mut().find(|import| import.from && import.module == written) {
+                        Some(same) => {
+                            same.names.extend(names);
+                            same.comments.extend(comments);
+                        }
+                        None => imports.push(Import {
+                            group: group(module.split('.').next().unwrap_or_default(), level, &is_local),
+                            from: true,
+                            module: written,
+                            names,
+                            comments,
+                        }),
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for import in &mut imports {
+            import.names.sort_by_key(|name| (name.to_lowercase(), name.clone()));
+            import.names.dedup();
+        }
+        // Relative imports after absolute ones
+        imports.sort_by_key(|import| (import.group, import.module.starts_with('.'), import.from, import.module.to_lowercase()));
+        imports.dedup_by(|a, b| !a.from && !b.from && a.module == b.module);
+
+        let mut organized = String::new();
+        for (i, import) in imports.iter().enumerate() {
+            if i > 0 && imports[i - 1].group != import.group {
+                organized.push('\n');
+            }
+            organized.push_str(&import.render());
+        }
+        if !content[start..end].ends_with('\n') {
+            organized.pop();
+        }
+        if organized != content[start..end] {
+            edits.push((start..end, organized));
+        }
+    }
+
+    // Imports after the leading ones, in functions and `if` blocks among them
+    let mut later = Vec::new();
+    for stmt in rest {
+        collect_imports(stmt, &mut later);
+    }
+    for stmt in later {
+        let (aliases, written) = match stmt {
+            Stmt::Import(import) => (&import.names, "import ".to_string()),
+            Stmt::ImportFrom(import) => {
+                let level = import.level.map_or(0, |level| level.to_u32());
+                let module = import.module.as_deref().unwrap_or_default();
+                (&import.names, format!("from {}{} import ", ".".repeat(level as usize), module))
+            }
+            _ => continue,
+        };
+        if aliases.iter().all(used) {
+            continue;
+        }
+        let range = stmt.start().to_usize()..stmt.end().to_usize();
+        let kept: Vec<String> = aliases.iter().filter(|alias| used(alias)).map(alias_text).collect();
+        if kept.is_empty() {
+            edits.push((statement_lines(content, range), String::new()));
+        } else {
+            edits.push((range, written + &kept.join(", ")));
+        }
+    }
+    edits
+}
+
+/// Import statements in `stmt` and the blocks nested in it
+fn collect_imports<'s>(stmt: &'s Stmt, imports: &mut Vec<&'s Stmt>) {
+    let blocks: Vec<&[Stmt]> = match stmt {
+        Stmt::Import(_) | Stmt::ImportFrom(_) => {
+            imports.push(stmt);
+            return;
+        }
+        Stmt::FunctionDef(def) => vec![&def.body],
+        Stmt::AsyncFunctionDef(def) => vec![&def.body],
+        Stmt::ClassDef(def) => vec![&def.body],
+        Stmt::If(stmt) => vec![&stmt.body, &stmt.orelse],
+        Stmt::While(stmt) => vec![&stmt.body, &stmt.orelse],
+        Stmt::For(stmt) => vec![&stmt.body, &stmt.orelse],
+        Stmt::With(stmt) => vec![&stmt.body],
+        Stmt::Try(stmt) => {
+            let handlers = stmt.handlers.iter().map(|handler| handler.as_except_handler().map_or(&[][..], |h| &h.body));
+            [&stmt.body[..], &stmt.orelse, &stmt.finalbody].into_iter().chain(handlers).collect()
+        }
+        _ => Vec::new(),
+    };
+    for block in blocks {
+        for stmt in block {
+            collect_imports(stmt, imports);
+        }
+    }
+}
+
+/// Which group an import of top-level module `top` goes in, for a relative
+/// import `level` dots up
+fn group(top: &str, level: u32, is_local: impl Fn(&str) -> bool) -> Group {
+    if level > 0 || is_local(top) {
+        Group::Local
+    } else if top == "__future__" {
+        Group::Future
+    } else if STDLIB.binary_search(&top).is_ok() {
+        Group::Stdlib
+    } else {
+        Group::ThirdParty
+    }
+}
+
+/// `name`, or `name as asname`
+fn alias_text(alias: &Alias) -> String {
+    match &alias.asname {
+        Some(asname) => format!("{} as {}", alias.name, asname),
+        None => alias.name.to_string(),
+    }
+}
+
+/// Byte offset the line containing `offset` starts at
+fn line_start(content: &str, offset: usize) -> usize {
+    content[..offset].rfind('\n').map_or(0, |i| i + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustpython_parser::ast::Mod;
+    use typthon::compiler::analysis::unused_imports::unused_imports;
+    use typthon::parse_module;
+
+    fn organized(content: &str) -> String {
+        let module = parse_module(content).unwrap();
+        let Mod::Module(body) = &module else { unreachable!() };
+        let unused = unused_imports(&module, content);
+        let mut edits = organize_imports(content, &body.body, &unused, |module| module == "shapes");
+        edits.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+        let mut content = content.to_string();
+        for (range, text) in edits {
+            content.replace_range(range, &text);
+        }
+        content
+    }
+
+    #[test]
+    fn test_stdlib_is_in_the_list() {
+        let mut sorted = STDLIB.to_vec();
+        sorted.sort_unstable();
+        assert_eq!(sorted, STDLIB);
+    }
+
+    #[test]
+    fn test_imports_are_grouped_sorted_and_pruned() {
+        let content = "\
+\"\"\"Draw shapes.\"\"\"
+import sys, os
+from shapes import Square
+import numpy as np
+# For the canvas size
+from typing import Optional, List
+from __future__ import annotations
+from typing import Any
+from .colors import RED
+
+def draw(square: Square) -> Optional[int]:
+    import json
+    print(os.sep, np.pi, RED, List)
+";
+        assert_eq!(organized(content), "\
+\"\"\"Draw shapes.\"\"\"
+from __future__ import annotations
+
+import os
+# For the canvas size
+from typing import List, Optional
+
+import numpy as np
+
+from shapes import Square
+from .colors import RED
+
+def draw(square: Square) -> Optional[int]:
+    print(os.sep, np.pi, RED, List)
+");
+    }
+}
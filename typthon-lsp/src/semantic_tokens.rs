@@ -0,0 +1,165 @@
+/*!
+Semantic token support for LSP.
+
+The legend the server announces, and the encoding of tokens as the
+relative positions the protocol sends, in full or as edits to the tokens
+sent last.
+*/
+
+use tower_lsp::lsp_types::{
+    SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokensEdit, SemanticTokensLegend,
+};
+
+/// Token types, by their index in the legend
+pub const NAMESPACE: u32 = 0;
+pub const CLASS: u32 = 1;
+pub const FUNCTION: u32 = 2;
+pub const VARIABLE: u32 = 3;
+pub const PARAMETER: u32 = 4;
+pub const PROPERTY: u32 = 5;
+pub const METHOD: u32 = 6;
+pub const TYPE: u32 = 8;
+pub const DECORATOR: u32 = 9;
+pub const STRING: u32 = 10;
+pub const NUMBER: u32 = 11;
+/// A method's `self` or `cls`
+pub const SELF_PARAMETER: u32 = 12;
+
+/// Token modifiers, as bits of the set
+pub const DEFINITION: u32 = 1 << 0;
+pub const READONLY: u32 = 1 << 1;
+
+/// Integers each token takes up in the encoded data
+const TOKEN_WIDTH: u32 = 5;
+
+pub fn legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: vec![
+            SemanticTokenType::NAMESPACE,
+            SemanticTokenType::CLASS,
+            SemanticTokenType::FUNCTION,
+            SemanticTokenType::VARIABLE,
+            SemanticTokenType::PARAMETER,
+            SemanticTokenType::PROPERTY,
+            SemanticTokenType::METHOD,
+            SemanticTokenType::KEYWORD,
+            SemanticTokenType::TYPE,
+            SemanticTokenType::DECORATOR,
+            SemanticTokenType::STRING,
+            SemanticTokenType::NUMBER,
+            SemanticTokenType::new("selfParameter"),
+        ],
+        token_modifiers: vec![
+            SemanticTokenModifier::DEFINITION,
+            SemanticTokenModifier::READONLY,
+        ],
+    }
+}
+
+/// A token at an absolute position: an editor line, and the UTF-16 column
+/// and length within it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token {
+    pub line: u32,
+    pub start: u32,
+    pub length: u32,
+    pub token_type: u32,
+    pub modifiers: u32,
+}
+
+/// `tokens` as the protocol sends them, each relative to the one before
+///
+/// The encoding only works in document order, so the tokens are sorted by
+/// position first; a token overlapping the one before it is dropped, as
+/// editors can't show both.
+pub fn encode(mut tokens: Vec<Token>) -> Vec<SemanticToken> {
+    tokens.sort_by_key(|token| (token.line, token.start));
+    let mut data = Vec::with_capacity(tokens.len());
+    let mut previous: Option<Token> = None;
+    for token in tokens {
+        if token.length == 0 {
+            continue;
+        }
+        let (delta_line, delta_start) = match previous {
+            Some(before) if before.line == token.line && token.start < before.start + before.length => continue,
+            Some(before) if before.line == token.line => (0, token.start - before.start),
+            Some(before) => (token.line - before.line, token.start),
+            None => (token.line, token.start),
+        };
+        data.push(SemanticToken {
+            delta_line,
+            delta_start,
+            length: token.length,
+            token_type: token.token_type,
+            token_modifiers_bitset: token.modifiers,
+        });
+        previous = Some(token);
+    }
+    data
+}
+
+/// Edits turning the `previous` encoded tokens into `current`: the one
+/// run in between the part they start with and the part they end with in
+/// common, or none when they're the same. Positions and counts are in the
+/// integers of the encoded data, five to a token
+pub fn edits(previous: &[SemanticToken], current: &[SemanticToken]) -> Vec<SemanticTokensEdit> {
+    let prefix = previous.iter().zip(current).take_while(|(before, after)| before == after).count();
+    if prefix == previous.len() && prefix == current.len() {
+        return Vec::new();
+    }
+    let suffix = previous[prefix..].iter().rev()
+        .zip(current[prefix..].iter().rev())
+        .take_while(|(before, after)| before == after)
+        .count();
+    let deleted = &previous[prefix..previous.len() - suffix];
+    let inserted = &current[prefix..current.len() - suffix];
+    vec![SemanticTokensEdit {
+        start: prefix as u32 * TOKEN_WIDTH,
+        delete_count: deleted.len() as u32 * TOKEN_WIDTH,
+        data: (!inserted.is_empty()).then(|| inserted.to_vec()),
+    }]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(line: u32, start: u32, length: u32, token_type: u32) -> Token {
+        Token { line, start, length, token_type, modifiers: 0 }
+    }
+
+    fn flat(tokens: &[SemanticToken]) -> Vec<u32> {
+        tokens.iter()
+            .flat_map(|t| [t.delta_line, t.delta_start, t.length, t.token_type, t.token_modifiers_bitset])
+            .collect()
+    }
+
+    #[test]
+    fn test_tokens_on_one_line_encode_in_order() {
+        // `    total = scale(value)` on line 3, then `x` on line 5, given out
+        // of order
+        let tokens = vec![
+            token(3, 18, 5, PARAMETER),
+            token(5, 0, 1, VARIABLE),
+            token(3, 4, 5, VARIABLE),
+            token(3, 12, 5, FUNCTION),
+        ];
+        assert_eq!(flat(&encode(tokens)), vec![
+            3, 4, 5, VARIABLE, 0,
+            0, 8, 5, FUNCTION, 0,
+            0, 6, 5, PARAMETER, 0,
+            2, 0, 1, VARIABLE, 0,
+        ]);
+    }
+
+    #[test]
+    fn test_edits_replace_the_changed_run() {
+        let before = encode(vec![token(0, 0, 1, VARIABLE), token(1, 0, 3, FUNCTION), token(2, 0, 1, VARIABLE)]);
+        let after = encode(vec![token(0, 0, 1, VARIABLE), token(1, 0, 5, FUNCTION), token(1, 6, 1, PARAMETER), token(2, 0, 1, VARIABLE)]);
+        let edits = edits(&before, &after);
+        assert_eq!(edits.len(), 1);
+        assert_eq!((edits[0].start, edits[0].delete_count), (5, 5));
+        assert_eq!(flat(edits[0].data.as_deref().unwrap()), vec![1, 0, 5, FUNCTION, 0, 0, 6, 1, PARAMETER, 0]);
+        assert!(self::edits(&after, &after).is_empty());
+    }
+}
@@ -0,0 +1,302 @@
+/*!
+Server settings from the editor's `typthon` configuration section.
+
+Each value resolves as editor settings > project config (`.typyrc`) >
+defaults. Invalid editor values are reported back as warnings and fall
+through to the next source instead of being silently dropped.
+*/
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use typthon::compiler::analysis::LintLevel;
+use typthon::compiler::frontend::config::Config as ProjectConfig;
+
+/// Project config files; changing one reloads the settings
+pub const CONFIG_FILES: &[&str] = &[".typyrc", ".typyrc.toml"];
+
+/// Files indexed unless `workspace.maxFiles` says otherwise
+pub const DEFAULT_MAX_FILES: usize = 5000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Strictness {
+    Basic,
+    Strict,
+}
+
+/// How much the server echoes to the client's output channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TraceLevel {
+    Off,
+    Messages,
+    Verbose,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SettingSource {
+    Editor,
+    Project,
+    Default,
+}
+
+/// Values read from the editor; `None` when unset or invalid
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EditorSettings {
+    pub debounce_ms: Option<u64>,
+    pub inlay_hints: Option<bool>,
+    pub inlay_variable_types: Option<bool>,
+    pub inlay_return_types: Option<bool>,
+    pub inlay_parameter_names: Option<bool>,
+    pub inlay_skip_literals: Option<bool>,
+    pub code_lens: Option<bool>,
+    pub strictness: Option<Strictness>,
+    pub trace: Option<TraceLevel>,
+    pub max_files: Option<u64>,
+}
+
+impl EditorSettings {
+    /// Read the `typthon` section, with one warning per invalid value
+    pub fn parse(section: &Value) -> (Self, Vec<String>) {
+        let mut warnings = Vec::new();
+        if !matches!(section, Value::Object(_) | Value::Null) {
+            warnings.push(format!("Invalid typthon settings: expected an object, got {}", section));
+            return (Self::default(), warnings);
+        }
+
+        let mut read = |path: &[&str], expected: &str, convert: &dyn Fn(&Value) -> Option<()>| {
+            let value = path.iter().try_fold(section, |value, key| value.get(key))?;
+            if value.is_null() {
+                return None;
+            }
+            if convert(value).is_none() {
+                warnings.push(format!(
+                    "Invalid value for typthon.{}: expected {}, got {}; using the project or default value",
+                    path.join("."), expected, value,
+                ));
+                return None;
+            }
+            Some(value.clone())
+        };
+
+        let bool_value = |value: &Value| value.as_bool().map(|_| ());
+        let debounce = read(&["diagnostics", "debounceMs"], "a non-negative integer", &|v| v.as_u64().map(|_| ()))
+            .or_else(|| read(&["diagnostics", "debounce_ms"], "a non-negative integer", &|v| v.as_u64().map(|_| ())));
+        let settings = Self {
+            debounce_ms: debounce.and_then(|v| v.as_u64()),
+            inlay_hints: read(&["inlayHints", "enabled"], "true or false", &bool_value).and_then(|v| v.as_bool()),
+            inlay_variable_types: read(&["inlayHints", "variableTypes"], "true or false", &bool_value)
+                .and_then(|v| v.as_bool()),
+            inlay_return_types: read(&["inlayHints", "returnTypes"], "true or false", &bool_value)
+                .and_then(|v| v.as_bool()),
+            inlay_parameter_names: read(&["inlayHints", "parameterNames"], "true or false", &bool_value)
+                .and_then(|v| v.as_bool()),
+            inlay_skip_literals: read(&["inlayHints", "skipLiterals"], "true or false", &bool_value)
+                .and_then(|v| v.as_bool()),
+            code_lens: read(&["codeLens", "enabled"], "true or false", &bool_value).and_then(|v| v.as_bool()),
+            strictness: read(&["strictness"], "\"basic\" or \"strict\"", &|v| parse_strictness(v).map(|_| ()))
+                .and_then(|v| parse_strictness(&v)),
+            trace: read(&["trace", "server"], "\"off\", \"messages\" or \"verbose\"", &|v| parse_trace(v).map(|_| ()))
+                .and_then(|v| parse_trace(&v)),
+            max_files: read(&["workspace", "maxFiles"], "a non-negative integer", &|v| v.as_u64().map(|_| ()))
+                .and_then(|v| v.as_u64()),
+        };
+        (settings, warnings)
+    }
+}
+
+fn parse_strictness(value: &Value) -> Option<Strictness> {
+    match value.as_str()? {
+        "basic" => Some(Strictness::Basic),
+        "strict" => Some(Strictness::Strict),
+        _ => None,
+    }
+}
+
+fn parse_trace(value: &Value) -> Option<TraceLevel> {
+    match value.as_str()? {
+        "off" => Some(TraceLevel::Off),
+        "messages" => Some(TraceLevel::Messages),
+        "verbose" => Some(TraceLevel::Verbose),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticsSettings {
+    /// Delay after an edit before diagnostics are recomputed
+    pub debounce_ms: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceSettings {
+    /// Most Python files indexed for cross-file navigation and diagnostics
+    pub max_files: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlayHintSettings {
+    pub enabled: bool,
+    pub variable_types: bool,
+    /// After the `)` of functions without a return annotation
+    pub return_types: bool,
+    /// Before the positional arguments of calls
+    pub parameter_names: bool,
+    /// No variable type for an assignment of a literal such as `x = 1`
+    pub skip_literals: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeLensSettings {
+    pub enabled: bool,
+}
+
+/// Effective settings after merging editor, project and defaults
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerSettings {
+    pub diagnostics: DiagnosticsSettings,
+    pub workspace: WorkspaceSettings,
+    pub inlay_hints: InlayHintSettings,
+    pub code_lens: CodeLensSettings,
+    pub strictness: Strictness,
+    pub trace: TraceLevel,
+    /// Source globs from the project's `paths.include`
+    pub include: Vec<String>,
+    pub lints: BTreeMap<String, LintLevel>,
+    /// Where each value came from, by setting name
+    pub sources: BTreeMap<&'static str, SettingSource>,
+}
+
+impl Default for ServerSettings {
+    fn default() -> Self {
+        Self::resolve(&EditorSettings::default(), &ProjectConfig::default())
+    }
+}
+
+impl ServerSettings {
+    pub fn resolve(editor: &EditorSettings, project: &ProjectConfig) -> Self {
+        let mut sources = BTreeMap::new();
+        let mut pick = |name: &'static str, editor: Option<bool>, default: bool| {
+            sources.insert(name, if editor.is_some() { SettingSource::Editor } else { SettingSource::Default });
+            editor.unwrap_or(default)
+        };
+        let inlay_hints = InlayHintSettings {
+            enabled: pick("inlayHints.enabled", editor.inlay_hints, true),
+            variable_types: pick("inlayHints.variableTypes", editor.inlay_variable_types, true),
+            return_types: pick("inlayHints.returnTypes", editor.inlay_return_types, true),
+            parameter_names: pick("inlayHints.parameterNames", editor.inlay_parameter_names, true),
+            skip_literals: pick("inlayHints.skipLiterals", editor.inlay_skip_literals, false),
+        };
+        let code_lens = CodeLensSettings { enabled: pick("codeLens.enabled", editor.code_lens, true) };
+
+        let source = |set: bool| if set { SettingSource::Editor } else { SettingSource::Default };
+        sources.insert("diagnostics.debounceMs", source(editor.debounce_ms.is_some()));
+        sources.insert("trace.server", source(editor.trace.is_some()));
+        sources.insert("workspace.maxFiles", source(editor.max_files.is_some()));
+
+        let project_strictness = project.check.strict.then_some(Strictness::Strict);
+        let strictness = match (editor.strictness, project_strictness) {
+            (Some(strictness), _) => {
+                sources.insert("strictness", SettingSource::Editor);
+                strictness
+            }
+            (None, Some(strictness)) => {
+                sources.insert("strictness", SettingSource::Project);
+                strictness
+            }
+            (None, None) => {
+                sources.insert("strictness", SettingSource::Default);
+                Strictness::Basic
+            }
+        };
+
+        let include = if project.paths.include.is_empty() {
+            sources.insert("include", SettingSource::Default);
+            vec!["**/*.py".to_string()]
+        } else {
+            sources.insert("include", SettingSource::Project);
+            project.paths.include.clone()
+        };
+
+        Self {
+            diagnostics: DiagnosticsSettings { debounce_ms: editor.debounce_ms.unwrap_or(0) },
+            workspace: WorkspaceSettings {
+                max_files: editor.max_files.map_or(DEFAULT_MAX_FILES, |max| usize::try_from(max).unwrap_or(usize::MAX)),
+            },
+            inlay_hints,
+            code_lens,
+            strictness,
+            trace: editor.trace.unwrap_or(TraceLevel::Off),
+            include,
+            lints: project.lints.clone(),
+            sources,
+        }
+    }
+
+    /// Globs the client should watch: sources plus the project config files
+    pub fn watch_globs(&self) -> Vec<String> {
+        self.include.iter().cloned()
+            .chain(CONFIG_FILES.iter().map(|name| format!("**/{}", name)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_editor_overrides_project_overrides_default() {
+        let project = ProjectConfig::parse("[check]\nstrict = true\n[paths]\ninclude = [\"src/**/*.py\"]\n").unwrap();
+
+        let (editor, warnings) = EditorSettings::parse(&json!({ "inlayHints": { "enabled": false } }));
+        assert!(warnings.is_empty());
+        let settings = ServerSettings::resolve(&editor, &project);
+        assert!(!settings.inlay_hints.enabled);
+        assert_eq!(settings.strictness, Strictness::Strict);
+        assert_eq!(settings.sources["inlayHints.enabled"], SettingSource::Editor);
+        assert_eq!(settings.sources["strictness"], SettingSource::Project);
+        assert_eq!(settings.sources["codeLens.enabled"], SettingSource::Default);
+        assert_eq!(settings.watch_globs(), vec!["src/**/*.py", "**/.typyrc", "**/.typyrc.toml"]);
+
+        let (editor, _) = EditorSettings::parse(&json!({ "strictness": "basic" }));
+        assert_eq!(ServerSettings::resolve(&editor, &project).strictness, Strictness::Basic);
+
+        assert_eq!(settings.workspace.max_files, DEFAULT_MAX_FILES);
+        let (editor, _) = EditorSettings::parse(&json!({ "workspace": { "maxFiles": 200 } }));
+        let settings = ServerSettings::resolve(&editor, &project);
+        assert_eq!(settings.workspace.max_files, 200);
+        assert_eq!(settings.sources["workspace.maxFiles"], SettingSource::Editor);
+
+        assert!(!settings.inlay_hints.skip_literals);
+        let (editor, _) = EditorSettings::parse(&json!({ "inlayHints": { "skipLiterals": true } }));
+        let hints = ServerSettings::resolve(&editor, &project).inlay_hints;
+        assert!(hints.skip_literals && hints.return_types && hints.parameter_names);
+    }
+
+    #[test]
+    fn test_invalid_values_warn_and_fall_through() {
+        let payload = json!({
+            "diagnostics": { "debounceMs": "fast" },
+            "strictness": "paranoid",
+            "codeLens": { "enabled": false },
+        });
+        let (editor, warnings) = EditorSettings::parse(&payload);
+
+        assert_eq!(editor, EditorSettings { code_lens: Some(false), ..EditorSettings::default() });
+        assert_eq!(warnings, vec![
+            "Invalid value for typthon.diagnostics.debounceMs: expected a non-negative integer, got \"fast\"; \
+             using the project or default value",
+            "Invalid value for typthon.strictness: expected \"basic\" or \"strict\", got \"paranoid\"; \
+             using the project or default value",
+        ]);
+    }
+}
@@ -0,0 +1,209 @@
+/*!
+Signature help support for LSP.
+
+Finds the call the cursor is in and lays out a signature with the
+parameter the argument being typed goes to.
+*/
+
+use rustpython_parser::lexer::lex;
+use rustpython_parser::{Mode, Tok};
+use tower_lsp::lsp_types::{Documentation, ParameterInformation, ParameterLabel, SignatureInformation};
+use typthon::compiler::ast::ir::ParamKind;
+
+/// The innermost call around a cursor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallSite {
+    /// Source of what's called: `f`, `shape.area`, `os.path.join`
+    pub callee: String,
+    /// Byte offset the callee starts at
+    pub start: usize,
+    /// How many arguments come before the cursor's
+    pub argument: usize,
+    /// Name the cursor's argument is passed by, after `name=`
+    pub keyword: Option<String>,
+}
+
+impl CallSite {
+    /// What the called attribute is looked up on, and the attribute:
+    /// `("shape", "area")` for `shape.area`; no receiver for plain names
+    pub fn split(&self) -> (Option<&str>, &str) {
+        match self.callee.rsplit_once('.') {
+            Some((receiver, attr)) => (Some(receiver), attr),
+            None => (None, &self.callee),
+        }
+    }
+}
+
+/// A parameter as signature help shows it: `x: int = 1`, `*args`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureParam {
+    pub name: String,
+    pub kind: ParamKind,
+    pub label: String,
+}
+
+/// Open bracket before the cursor, and the call it starts if it does
+struct Frame {
+    call: Option<(usize, usize)>,
+    argument: usize,
+    keyword: Option<String>,
+}
+
+/// The innermost call whose parentheses are still open at byte `offset`:
+/// its callee, counting the commas at its depth for the argument and
+/// noting a `name=` before the cursor. Brackets of lists, dicts and tuples
+/// in between don't count as calls; neither do `def` and `class` headers
+pub fn call_site(content: &str, offset: usize) -> Option<CallSite> {
+    let mut frames: Vec<Frame> = Vec::new();
+    // The dotted name ending at the previous token, and the token before it
+    let mut chain: Option<(usize, usize)> = None;
+    let mut before_chain: Option<Tok> = None;
+    let mut previous: Option<Tok> = None;
+
+    // The lexer keeps reporting brackets left open at the end of its input
+    for (token, range) in lex(&content[..offset], Mode::Module).map_while(Result::ok) {
+        let (start, end) = (range.start().to_usize(), range.end().to_usize());
+        match &token {
+            Tok::Lpar => {
+                let call = chain.filter(|_| !matches!(before_chain, Some(Tok::Def | Tok::Class)));
+                frames.push(Frame { call, argument: 0, keyword: None });
+            }
+            Tok::Lsqb | Tok::Lbrace => frames.push(Frame { call: None, argument: 0, keyword: None }),
+            Tok::Rpar | Tok::Rsqb | Tok::Rbrace => {
+                frames.pop();
+            }
+            Tok::Comma => {
+                if let Some(frame) = frames.last_mut() {
+                    frame.argument += 1;
+                    frame.keyword = None;
+                }
+            }
+            Tok::Equal => {
+                if let (Some(frame), Some(Tok::Name { name })) = (frames.last_mut(), &previous) {
+                    if frame.call.is_some() {
+                        frame.keyword = Some(name.to_string());
+                    }
+                }
+            }
+            Tok::Newline => frames.clear(),
+            _ => {}
+        }
+
+        chain = match &token {
+            Tok::Name { .. } if previous == Some(Tok::Dot) => chain.map(|(chain_start, _)| (chain_start, end)),
+            Tok::Name { .. } => {
+                before_chain = previous.clone();
+                Some((start, end))
+            }
+            Tok::Dot if matches!(previous, Some(Tok::Name { .. })) => chain,
+            _ => None,
+        };
+        previous = Some(token);
+    }
+
+    let frame = frames.into_iter().rev().find(|frame| frame.call.is_some())?;
+    let (start, end) = frame.call?;
+    Some(CallSite { callee: content[start..end].to_string(), start, argument: frame.argument, keyword: frame.keyword })
+}
+
+/// `name(params) -> returns` with each parameter's place in the label, and
+/// the parameter the argument at `site` goes to marked active
+///
+/// `/` and `*` separate positional-only and keyword-only parameters as
+/// they do in a `def`, without being parameters themselves.
+pub fn signature_information(
+    name: &str,
+    params: &[SignatureParam],
+    returns: Option<&str>,
+    documentation: Option<String>,
+    site: &CallSite,
+) -> SignatureInformation {
+    let mut label = format!("{}(", name);
+    let mut parameters = Vec::new();
+    let mut starred = false;
+    for (i, param) in params.iter().enumerate() {
+        if i > 0 {
+            label.push_str(", ");
+        }
+        match param.kind {
+            ParamKind::VarArgs => starred = true,
+            ParamKind::KeywordOnly if !starred => {
+                starred = true;
+                label.push_str("*, ");
+            }
+            _ => {}
+        }
+        let start = utf16_len(&label);
+        label.push_str(&param.label);
+        parameters.push(ParameterInformation {
+            label: ParameterLabel::LabelOffsets([start, utf16_len(&label)]),
+            documentation: None,
+        });
+        let next = params.get(i + 1).map(|p| p.kind);
+        if param.kind == ParamKind::PositionalOnly && next != Some(ParamKind::PositionalOnly) {
+            label.push_str(", /");
+        }
+    }
+    label.push(')');
+    if let Some(returns) = returns {
+        label.push_str(&format!(" -> {}", returns));
+    }
+
+    SignatureInformation {
+        label,
+        documentation: documentation.map(Documentation::String),
+        parameters: Some(parameters),
+        active_parameter: active_parameter(params, site),
+    }
+}
+
+/// Index of the parameter the argument at `site` binds to: the one named by
+/// its keyword, or the next positional one, with `*args` and `**kwargs`
+/// taking what's left over. `None` when no parameter takes it
+fn active_parameter(params: &[SignatureParam], site: &CallSite) -> Option<u32> {
+    let position = |kind| params.iter().position(|param| param.kind == kind);
+    let index = match &site.keyword {
+        Some(keyword) => params.iter()
+            .position(|param| {
+                &param.name == keyword && matches!(param.kind, ParamKind::Positional | ParamKind::KeywordOnly)
+            })
+            .or_else(|| position(ParamKind::VarKeywords)),
+        None => params.iter()
+            .enumerate()
+            .filter(|(_, param)| matches!(param.kind, ParamKind::PositionalOnly | ParamKind::Positional))
+            .nth(site.argument)
+            .map(|(i, _)| i)
+            .or_else(|| position(ParamKind::VarArgs)),
+    };
+    index.map(|i| i as u32)
+}
+
+/// Length of `text` in the UTF-16 code units label offsets count in
+fn utf16_len(text: &str) -> u32 {
+    text.encode_utf16().count() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn site(content: &str) -> Option<CallSite> {
+        call_site(content, content.len())
+    }
+
+    #[test]
+    fn test_call_site_counts_arguments_of_the_innermost_call() {
+        let found = site("total = add(1, scale(2, [3, 4], ").unwrap();
+        assert_eq!((found.callee.as_str(), found.start, found.argument), ("scale", 15, 2));
+
+        let found = site("total = add(1, scale(2), [3, ").unwrap();
+        assert_eq!((found.callee.as_str(), found.argument), ("add", 2));
+
+        let found = site("shape.resize(width=2, height=").unwrap();
+        assert_eq!((found.callee.as_str(), found.keyword.as_deref()), ("shape.resize", Some("height")));
+
+        assert_eq!(site("def add(a, "), None);
+        assert_eq!(site("x = (1, "), None);
+        assert_eq!(site("add(1)\nx = "), None);
+    }
+}
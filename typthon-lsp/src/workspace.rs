@@ -0,0 +1,236 @@
+/*!
+Workspace index: the Python files under the workspace folders, the symbols
+each defines and the project files each imports.
+
+Files are found the way `git` would list them: `.gitignore` and `.ignore`
+files are honoured (with or without a repository), hidden directories and
+virtualenvs are skipped, and so is anything the project's `paths.exclude`
+matches. Open documents are indexed from their text in the editor.
+*/
+
+use dashmap::DashMap;
+use ignore::WalkBuilder;
+use rustpython_parser::{parse, Mode};
+use std::collections::{HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use typthon::compiler::analysis::architecture::import_statements;
+use typthon::compiler::analysis::ModuleResolver;
+use typthon::compiler::frontend::config::Config as ProjectConfig;
+
+use crate::analyzer::{DocumentAnalyzer, SymbolInfo, SymbolKind};
+
+/// Most symbols a workspace symbol search returns
+const SEARCH_LIMIT: usize = 256;
+
+/// Directories never descended into, on top of hidden ones and virtualenvs
+const SKIPPED_DIRS: &[&str] = &["__pycache__", "node_modules"];
+
+struct IndexedFile {
+    symbols: Vec<SymbolInfo>,
+    /// Project files it imports
+    imports: Vec<PathBuf>,
+}
+
+/// Symbols and imports of every indexed file, with the resolver their
+/// imports (and the checks of open documents) go through
+pub struct WorkspaceIndex {
+    modules: Arc<ModuleResolver>,
+    files: DashMap<PathBuf, IndexedFile>,
+}
+
+impl WorkspaceIndex {
+    /// Empty index over the workspace folders `roots`
+    pub fn new(roots: impl IntoIterator<Item = PathBuf>) -> Self {
+        Self { modules: Arc::new(ModuleResolver::new(roots)), files: DashMap::new() }
+    }
+
+    pub fn modules(&self) -> Arc<ModuleResolver> {
+        self.modules.clone()
+    }
+
+    pub fn roots(&self) -> &[PathBuf] {
+        self.modules.roots()
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Index `path` with the text `content`. A file that doesn't parse keeps
+    /// what it had, so navigation survives a half-typed line
+    pub fn update(&self, path: &Path, content: &str, analyzer: &DocumentAnalyzer) {
+        let Ok(ast) = parse(content, Mode::Module, "<string>") else { return };
+        let mut imports: Vec<PathBuf> = import_statements(&ast).into_iter()
+            .flat_map(|stmt| self.modules.targets(path, stmt))
+            .filter(|target| target != path)
+            .collect();
+        imports.sort();
+        imports.dedup();
+        let symbols = analyzer.extract_symbols(content);
+        self.files.insert(path.to_path_buf(), IndexedFile { symbols, imports });
+    }
+
+    /// Drop `path`, e.g. once it's been deleted
+    pub fn remove(&self, path: &Path) {
+        self.files.remove(path);
+    }
+
+    /// Files importing `path`, directly or not, nearest first
+    pub fn importers(&self, path: &Path) -> Vec<PathBuf> {
+        let mut found: Vec<PathBuf> = Vec::new();
+        let mut queue = VecDeque::from([path.to_path_buf()]);
+        while let Some(next) = queue.pop_front() {
+            let mut direct: Vec<PathBuf> = self.files.iter()
+                .filter(|entry| entry.imports.contains(&next))
+                .map(|entry| entry.key().clone())
+                .collect();
+            direct.sort();
+            for importer in direct {
+                if importer != path && !found.contains(&importer) {
+                    found.push(importer.clone());
+                    queue.push_back(importer);
+                }
+            }
+        }
+        found
+    }
+
+    /// Definition of `name` in one of the files `file` imports: a function
+    /// or class, or failing that a variable
+    pub fn imported_definition(&self, file: &Path, name: &str) -> Option<(PathBuf, SymbolInfo)> {
+        let imports = self.files.get(file)?.imports.clone();
+        let find = |wanted: &dyn Fn(&SymbolKind) -> bool| imports.iter().find_map(|import| {
+            let indexed = self.files.get(import)?;
+            let symbol = indexed.symbols.iter().find(|symbol| symbol.name == name && wanted(&symbol.kind))?;
+            Some((import.clone(), symbol.clone()))
+        });
+        find(&|kind| matches!(kind, SymbolKind::Function | SymbolKind::Class))
+            .or_else(|| find(&|kind| *kind == SymbolKind::Variable))
+    }
+
+    /// Symbols whose names contain `query`, ignoring case, by file and line;
+    /// parameters are left out
+    pub fn search(&self, query: &str) -> Vec<(PathBuf, SymbolInfo)> {
+        let query = query.to_lowercase();
+        let mut found: Vec<(PathBuf, SymbolInfo)> = self.files.iter()
+            .flat_map(|entry| {
+                let path = entry.key().clone();
+                entry.symbols.iter()
+                    .filter(|symbol| symbol.kind != SymbolKind::Parameter && symbol.name.to_lowercase().contains(&query))
+                    .map(|symbol| (path.clone(), symbol.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        found.sort_by(|(a, x), (b, y)| a.cmp(b).then(x.line.cmp(&y.line)).then(x.col.cmp(&y.col)));
+        found.truncate(SEARCH_LIMIT);
+        found
+    }
+}
+
+/// Python files under `roots` to index, sorted per root, and whether more
+/// than `limit` were found and the rest left out
+pub fn workspace_files(roots: &[PathBuf], project: &ProjectConfig, limit: usize) -> (Vec<PathBuf>, bool) {
+    let mut files = Vec::new();
+    let mut seen = HashSet::new();
+    for root in roots {
+        let walker = WalkBuilder::new(root)
+            // `.gitignore` applies outside a repository too
+            .require_git(false)
+            .filter_entry(|entry| {
+                let is_dir = entry.file_type().is_some_and(|kind| kind.is_dir());
+                !is_dir || !(SKIPPED_DIRS.iter().any(|name| entry.file_name() == *name) || entry.path().join("pyvenv.cfg").is_file())
+            })
+            .sort_by_file_name(|a, b| a.cmp(b))
+            .build();
+        for entry in walker.flatten() {
+            let path = entry.path();
+            if !entry.file_type().is_some_and(|kind| kind.is_file())
+                || path.extension().is_none_or(|ext| ext != "py")
+                || !project.should_check(path)
+                || !seen.insert(path.to_path_buf())
+            {
+                continue;
+            }
+            if files.len() == limit {
+                return (files, true);
+            }
+            files.push(path.to_path_buf());
+        }
+    }
+    (files, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn project(files: &[(&str, &str)]) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        for (path, source) in files {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, source).unwrap();
+        }
+        dir
+    }
+
+    fn relative(dir: &TempDir, files: &[PathBuf]) -> Vec<String> {
+        files.iter().map(|file| file.strip_prefix(dir.path()).unwrap().display().to_string()).collect()
+    }
+
+    #[test]
+    fn test_files_honour_ignore_files_and_the_limit() {
+        let dir = project(&[
+            (".gitignore", "build/\n*_generated.py\n"),
+            ("app/main.py", ""),
+            ("app/schema_generated.py", ""),
+            ("build/lib/app.py", ""),
+            ("env/pyvenv.cfg", ""),
+            ("env/lib/site.py", ""),
+            ("node_modules/tool.py", ""),
+            (".hidden/secret.py", ""),
+            ("notes.txt", ""),
+            ("util.py", ""),
+        ]);
+        let roots = [dir.path().to_path_buf()];
+        let (files, truncated) = workspace_files(&roots, &ProjectConfig::default(), 100);
+        assert_eq!(relative(&dir, &files), vec!["app/main.py", "util.py"]);
+        assert!(!truncated);
+
+        let (files, truncated) = workspace_files(&roots, &ProjectConfig::default(), 1);
+        assert_eq!(relative(&dir, &files), vec!["app/main.py"]);
+        assert!(truncated);
+
+        let project = ProjectConfig::parse("[paths]\nexclude = [\"**/app/**\"]\n").unwrap();
+        assert_eq!(relative(&dir, &workspace_files(&roots, &project, 100).0), vec!["util.py"]);
+    }
+
+    #[test]
+    fn test_importers_and_imported_definitions() {
+        let dir = project(&[
+            ("util.py", "LIMIT = 3\n\ndef scale(value: int) -> int:\n    return value * LIMIT\n"),
+            ("service.py", "import util\n\ndef run() -> int:\n    return util.scale(2)\n"),
+            ("main.py", "from service import run\n\nrun()\n"),
+        ]);
+        let index = WorkspaceIndex::new([dir.path().to_path_buf()]);
+        let analyzer = DocumentAnalyzer::new();
+        let (files, _) = workspace_files(index.roots(), &ProjectConfig::default(), 100);
+        for file in &files {
+            index.update(file, &fs::read_to_string(file).unwrap(), &analyzer);
+        }
+        let path = |name: &str| dir.path().join(name);
+
+        assert_eq!(index.importers(&path("util.py")), vec![path("service.py"), path("main.py")]);
+        assert!(index.importers(&path("main.py")).is_empty());
+
+        let (file, symbol) = index.imported_definition(&path("service.py"), "scale").unwrap();
+        assert_eq!((file, symbol.line), (path("util.py"), 2));
+        assert!(index.imported_definition(&path("main.py"), "scale").is_none());
+
+        let found: Vec<String> = index.search("L").into_iter().map(|(_, symbol)| symbol.name).collect();
+        assert_eq!(found, vec!["LIMIT", "scale"]);
+    }
+}
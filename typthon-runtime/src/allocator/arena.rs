@@ -1,7 +1,8 @@
 //! Arena management - OS memory acquisition
 //!
 //! Design: Lazy allocation of large blocks (64KB-4MB) for minimal syscalls.
-//! Future: Thread-local arenas for zero contention.
+//! Pools are owned by thread-local allocators; see `registry` for the
+//! process-wide view.
 
 use std::alloc::{alloc, dealloc, Layout};
 use crate::logging::{debug, warn, trace};
@@ -16,6 +17,10 @@ pub struct Arena {
     layout: Layout,
 }
 
+// Safety: An arena exclusively owns its memory block; ownership may move
+// between threads (e.g. retired to the heap registry on thread exit)
+unsafe impl Send for Arena {}
+
 impl Arena {
     /// Allocate new arena from OS
     ///
@@ -37,13 +37,38 @@ impl ObjectHeader {
         obj.sub(16) as *mut Self
     }
 
+    /// Get object data pointer from header (inverse of `from_object`)
+    ///
+    /// # Safety
+    /// `header` must point to a header prefixed to a heap object.
+    #[inline]
+    pub unsafe fn object(header: *mut Self) -> *mut u8 {
+        (header as *mut u8).add(16)
+    }
+
     /// Get type info for this object
     #[inline]
     pub fn type_info(&self) -> NonNull<TypeInfo> {
         self.type_info
     }
+
+    /// Visit the object pointers directly referenced by this object
+    ///
+    /// No-op for types without a trace function (leaf objects).
+    ///
+    /// # Safety
+    /// `header` must point to a live, initialized object.
+    #[inline]
+    pub unsafe fn trace(header: *mut Self, visit: &mut dyn FnMut(*mut u8)) {
+        if let Some(trace_fn) = (*header).type_info.as_ref().trace {
+            trace_fn(Self::object(header), visit);
+        }
+    }
 }
 
+/// Child traversal: calls `visit` with the object pointer of every heap child
+pub type TraceFn = unsafe fn(*mut u8, &mut dyn FnMut(*mut u8));
+
 /// Type metadata - immutable per-type information
 ///
 /// Shared across all instances of a type for minimal memory overhead.
@@ -53,19 +78,26 @@ pub struct TypeInfo {
     pub align: usize,
     pub type_id: u8,
     pub drop: Option<unsafe fn(*mut u8)>,
+    pub trace: Option<TraceFn>,
 }
 
 impl TypeInfo {
     /// Create type info for simple types (no drop)
     #[inline]
     pub const fn simple(size: usize, align: usize, type_id: u8) -> Self {
-        Self { size, align, type_id, drop: None }
+        Self { size, align, type_id, drop: None, trace: None }
     }
 
     /// Create type info with custom destructor
     #[inline]
     pub const fn with_drop(size: usize, align: usize, type_id: u8, drop: unsafe fn(*mut u8)) -> Self {
-        Self { size, align, type_id, drop: Some(drop) }
+        Self { size, align, type_id, drop: Some(drop), trace: None }
+    }
+
+    /// Attach child traversal for cycle detection (container types)
+    #[inline]
+    pub const fn with_trace(self, trace: TraceFn) -> Self {
+        Self { trace: Some(trace), ..self }
     }
 
     /// Get object type from type info
@@ -84,6 +116,7 @@ impl TypeInfo {
             8 => ObjectType::Function,
             9 => ObjectType::Class,
             10 => ObjectType::Instance,
+            11 => ObjectType::FrozenSet,
             _ => ObjectType::Unknown,
         }
     }
@@ -5,34 +5,65 @@
 //! 2. Arena management (slow path, amortized cost)
 //! 3. OS memory (rare, bulk acquisition)
 //!
-//! Thread-local arenas for zero-contention allocation.
+//! Thread-local arenas for zero-contention allocation. Every allocator's
+//! arenas are recorded in a process-wide registry so objects can be shared
+//! across threads: arenas outlive the thread that created them.
 
 mod header;
 mod bump;
 mod arena;
+mod registry;
+mod stats;
 
 #[cfg(test)]
 mod tests;
 
-pub use header::{ObjectHeader, TypeInfo};
+pub use header::{ObjectHeader, TraceFn, TypeInfo};
 pub use bump::BumpAllocator;
 pub use arena::{Arena, ArenaPool};
+pub use registry::{ArenaSpan, HeapRecord, owns, arenas, heaps, get_stats, allocation_info};
+pub(crate) use registry::reclaim_retired;
+pub use stats::{HeapStats, AllocationInfo};
 
 use core::ptr::NonNull;
 use core::cell::RefCell;
+use std::sync::Arc;
 use crate::logging::{log_allocation};
 use tracing::{info, debug, trace};
 
 thread_local! {
     /// Thread-local allocator for zero-contention fast path
-    static TLS_ALLOCATOR: RefCell<Option<Allocator>> = RefCell::new(None);
+    static TLS_ALLOCATOR: RefCell<ThreadAllocator> = const { RefCell::new(ThreadAllocator(None)) };
+}
+
+/// Thread-local allocator slot: retires its arenas instead of freeing them
+///
+/// Objects allocated on this thread may still be referenced from others when
+/// it exits (or when the allocator is replaced), so the memory stays mapped
+/// until runtime shutdown.
+struct ThreadAllocator(Option<Allocator>);
+
+impl ThreadAllocator {
+    fn replace(&mut self, allocator: Allocator) {
+        if let Some(old) = self.0.replace(allocator) {
+            old.retire();
+        }
+    }
+}
+
+impl Drop for ThreadAllocator {
+    fn drop(&mut self) {
+        if let Some(allocator) = self.0.take() {
+            allocator.retire();
+        }
+    }
 }
 
 /// Initialize allocator subsystem with thread-local allocators
 pub fn init() {
     info!("Allocator subsystem initializing");
     TLS_ALLOCATOR.with(|alloc| {
-        *alloc.borrow_mut() = Some(Allocator::new());
+        alloc.borrow_mut().replace(Allocator::new());
     });
     debug!("Allocator ready with thread-local bump allocation and arena management");
 }
@@ -44,7 +75,7 @@ where
 {
     TLS_ALLOCATOR.with(|alloc| {
         let mut alloc_ref = alloc.borrow_mut();
-        let allocator = alloc_ref.get_or_insert_with(Allocator::new);
+        let allocator = alloc_ref.0.get_or_insert_with(Allocator::new);
         f(allocator)
     })
 }
@@ -53,6 +84,7 @@ where
 pub struct Allocator {
     bump: BumpAllocator,
     arenas: ArenaPool,
+    heap: Option<Arc<HeapRecord>>,
 }
 
 impl Allocator {
@@ -61,6 +93,19 @@ impl Allocator {
         Self {
             bump: BumpAllocator::new(),
             arenas: ArenaPool::new(),
+            heap: Some(registry::register_heap()),
+        }
+    }
+
+    /// Registry entry for this allocator's heap
+    pub fn heap(&self) -> Option<&HeapRecord> {
+        self.heap.as_deref()
+    }
+
+    /// Give up the allocator but keep its arenas (and their objects) alive
+    pub fn retire(mut self) {
+        if let Some(heap) = self.heap.take() {
+            registry::retire_heap(heap, core::mem::take(&mut self.arenas));
         }
     }
 
@@ -69,7 +114,9 @@ impl Allocator {
         trace!(operation = "alloc_attempt", size_bytes = size, alignment = align);
 
         // Fast path: try bump allocation
+        let remaining = self.bump.remaining();
         if let Some(ptr) = self.bump.try_alloc(size, align) {
+            self.record_alloc(size, remaining);
             log_allocation(size, ptr.as_ptr());
             // Notify GC of allocation
             crate::gc::maybe_collect();
@@ -85,6 +132,11 @@ impl Allocator {
         // Acquire new arena from pool, large enough for this allocation
         let arena = self.arenas.grow_with_min(size + align)?;
         let (start, end) = arena.bounds();
+        if let Some(heap) = &self.heap {
+            registry::add_arena(heap, start, arena.size());
+            // The old arena's tail can never be used again
+            heap.counters().record_waste(self.bump.remaining());
+        }
 
         debug!(
             arena_start = ?start,
@@ -97,8 +149,10 @@ impl Allocator {
         self.bump.reset(start, end);
 
         // Retry allocation (guaranteed to succeed if arena large enough)
+        let remaining = self.bump.remaining();
         let result = self.bump.try_alloc(size, align);
         if let Some(ptr) = result {
+            self.record_alloc(size, remaining);
             log_allocation(size, ptr.as_ptr());
             // Notify GC of allocation
             crate::gc::maybe_collect();
@@ -108,6 +162,15 @@ impl Allocator {
         }
     }
 
+    /// Count an allocation against this heap (padding from the bump delta)
+    #[inline]
+    fn record_alloc(&self, size: usize, remaining_before: usize) {
+        if let Some(heap) = &self.heap {
+            let consumed = remaining_before - self.bump.remaining();
+            heap.counters().record_alloc(size, consumed - size);
+        }
+    }
+
     /// Allocate typed object with header
     pub fn alloc_object<T>(&mut self, type_info: NonNull<TypeInfo>) -> Option<NonNull<T>> {
         let total_size = core::mem::size_of::<ObjectHeader>() + core::mem::size_of::<T>();
@@ -162,7 +225,18 @@ impl Default for Allocator {
     }
 }
 
-/// Allocator statistics for monitoring and debugging
+impl Drop for Allocator {
+    fn drop(&mut self) {
+        // Arenas are freed with the pool; forget their registry spans first
+        if let Some(heap) = self.heap.take() {
+            registry::release_heap(&heap);
+        }
+    }
+}
+
+/// Per-allocator statistics for monitoring and debugging
+///
+/// See `get_stats()` for process-wide numbers across all allocators.
 #[derive(Debug, Clone, Copy)]
 pub struct AllocatorStats {
     pub total_allocated: usize,
@@ -0,0 +1,222 @@
+//! Heap registry - process-wide view of every allocator's arenas
+//!
+//! Design: Allocators stay thread-local; only the slow path (new arena) and
+//! lifecycle events touch the registry, so the bump fast path is unaffected.
+//! - Arena spans are indexed by start address for O(log n) ownership checks
+//! - A thread's arenas outlive the thread: objects may still be referenced
+//!   from other threads, so exiting threads retire their pools here. Nothing
+//!   tracks which objects in a retired pool are still live, so its arenas are
+//!   only freed at runtime shutdown (`reclaim_retired`); until then memory
+//!   grows by the arena bytes of every exited thread that allocated (at
+//!   least 64KB each, up to everything it ever allocated)
+//! - Heap statistics are aggregated here from every heap's counters
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use once_cell::sync::Lazy;
+use parking_lot::{Mutex, RwLock};
+use crate::logging::{debug, trace};
+use super::arena::ArenaPool;
+use super::stats::{self, AllocationInfo, HeapCounters, HeapStats};
+
+/// Global registry of allocator heaps
+static REGISTRY: Lazy<Registry> = Lazy::new(Registry::new);
+
+/// Next heap identifier (0 is never handed out)
+static NEXT_HEAP_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Memory region owned by one allocator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArenaSpan {
+    pub start: usize,
+    pub size: usize,
+    pub heap: u64,
+}
+
+impl ArenaSpan {
+    /// Check whether `addr` lies inside this span
+    #[inline]
+    pub fn contains(&self, addr: usize) -> bool {
+        addr >= self.start && addr < self.start + self.size
+    }
+}
+
+/// Registry entry for one allocator
+pub struct HeapRecord {
+    id: u64,
+    thread: Option<String>,
+    counters: HeapCounters,
+}
+
+impl HeapRecord {
+    /// Unique heap identifier
+    #[inline]
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Name of the thread that created the heap, if it had one
+    #[inline]
+    pub fn thread(&self) -> Option<&str> {
+        self.thread.as_deref()
+    }
+
+    /// Allocation counters (updated by the owning allocator)
+    #[inline]
+    pub(super) fn counters(&self) -> &HeapCounters {
+        &self.counters
+    }
+}
+
+struct Registry {
+    /// Arena spans keyed by start address
+    arenas: RwLock<BTreeMap<usize, ArenaSpan>>,
+    /// Heaps of live allocators and of exited threads
+    heaps: Mutex<Vec<Arc<HeapRecord>>>,
+    /// Arena pools kept alive after their thread exited, with their heaps
+    retired: Mutex<Vec<(Arc<HeapRecord>, ArenaPool)>>,
+    /// Counters of released heaps (their arenas are gone)
+    released: HeapCounters,
+    /// Bytes in use just before the largest release so far
+    peak: AtomicUsize,
+}
+
+impl Registry {
+    fn new() -> Self {
+        Self {
+            arenas: RwLock::new(BTreeMap::new()),
+            heaps: Mutex::new(Vec::new()),
+            retired: Mutex::new(Vec::new()),
+            released: HeapCounters::new(),
+            peak: AtomicUsize::new(0),
+        }
+    }
+}
+
+/// Register a new allocator heap
+pub(super) fn register_heap() -> Arc<HeapRecord> {
+    let record = Arc::new(HeapRecord {
+        id: NEXT_HEAP_ID.fetch_add(1, Ordering::Relaxed),
+        thread: std::thread::current().name().map(str::to_owned),
+        counters: HeapCounters::new(),
+    });
+
+    trace!(heap = record.id, thread = ?record.thread, "Registering allocator heap");
+    REGISTRY.heaps.lock().push(Arc::clone(&record));
+    record
+}
+
+/// Record a freshly acquired arena
+pub(super) fn add_arena(heap: &HeapRecord, start: *mut u8, size: usize) {
+    let span = ArenaSpan { start: start as usize, size, heap: heap.id };
+    REGISTRY.arenas.write().insert(span.start, span);
+}
+
+/// Forget a heap whose allocator was dropped (its arenas are freed with it)
+pub(super) fn release_heap(heap: &HeapRecord) {
+    trace!(heap = heap.id, "Releasing allocator heap");
+
+    // Usage only ever drops here, so this is where peaks are observed
+    let mut heaps = REGISTRY.heaps.lock();
+    let in_use: usize = heaps.iter().map(|record| record.counters.bytes()).sum();
+    REGISTRY.peak.fetch_max(in_use, Ordering::Relaxed);
+    REGISTRY.released.absorb(&heap.counters);
+    heaps.retain(|record| record.id != heap.id);
+    drop(heaps);
+
+    REGISTRY.arenas.write().retain(|_, span| span.heap != heap.id);
+}
+
+/// Keep a heap's arenas alive after its owning thread exits
+///
+/// A heap that never acquired an arena has nothing to keep and is released.
+pub(super) fn retire_heap(heap: Arc<HeapRecord>, pool: ArenaPool) {
+    if pool.total_allocated() == 0 {
+        release_heap(&heap);
+        return;
+    }
+    debug!(heap = heap.id, arena_bytes = pool.total_allocated(), "Retiring thread heap");
+    REGISTRY.retired.lock().push((heap, pool));
+}
+
+/// Free the arenas of every retired heap, returning the bytes released
+///
+/// # Safety
+/// No object allocated by an exited thread may be used afterwards; call only
+/// at runtime shutdown.
+pub(crate) unsafe fn reclaim_retired() -> usize {
+    let retired = core::mem::take(&mut *REGISTRY.retired.lock());
+    let mut bytes = 0;
+    for (heap, pool) in retired {
+        bytes += pool.total_allocated();
+        // Forget the spans before the pool frees the arenas
+        release_heap(&heap);
+        drop(pool);
+    }
+    debug!(arena_bytes = bytes, "Reclaimed retired thread heaps");
+    bytes
+}
+
+/// Check whether a pointer lies inside any registered arena
+///
+/// Objects outside every arena came from the system allocator.
+pub fn owns(ptr: *const u8) -> bool {
+    let addr = ptr as usize;
+    REGISTRY
+        .arenas
+        .read()
+        .range(..=addr)
+        .next_back()
+        .is_some_and(|(_, span)| span.contains(addr))
+}
+
+/// Snapshot of every registered arena, ordered by address
+pub fn arenas() -> Vec<ArenaSpan> {
+    REGISTRY.arenas.read().values().copied().collect()
+}
+
+/// Snapshot of every registered heap (live and retired)
+pub fn heaps() -> Vec<Arc<HeapRecord>> {
+    REGISTRY.heaps.lock().clone()
+}
+
+/// Aggregate heap statistics across every allocator
+pub fn get_stats() -> HeapStats {
+    let heaps = REGISTRY.heaps.lock();
+    let mut stats = HeapStats {
+        heap_count: heaps.len(),
+        ..HeapStats::default()
+    };
+    for record in heaps.iter() {
+        stats.bytes_in_use += record.counters.bytes();
+        stats.allocation_count += record.counters.allocations();
+        stats.arena_wasted += record.counters.wasted();
+    }
+    stats.total_allocated = stats.bytes_in_use + REGISTRY.released.bytes();
+    stats.allocation_count += REGISTRY.released.allocations();
+    stats.peak_usage = REGISTRY.peak.load(Ordering::Relaxed).max(stats.bytes_in_use);
+    drop(heaps);
+
+    let arenas = REGISTRY.arenas.read();
+    stats.arena_count = arenas.len();
+    stats.arena_bytes = arenas.values().map(|span| span.size).sum();
+
+    trace!(
+        bytes_in_use = stats.bytes_in_use,
+        arena_count = stats.arena_count,
+        "Heap statistics retrieved"
+    );
+
+    stats
+}
+
+/// Allocation size histogram across every allocator (non-empty classes only)
+pub fn allocation_info() -> impl Iterator<Item = AllocationInfo> {
+    let (mut counts, mut bytes) = stats::empty_histogram();
+    REGISTRY.released.add_histogram(&mut counts, &mut bytes);
+    for record in REGISTRY.heaps.lock().iter() {
+        record.counters.add_histogram(&mut counts, &mut bytes);
+    }
+    stats::histogram(counts, bytes).into_iter()
+}
@@ -0,0 +1,172 @@
+//! Heap statistics - allocation counters aggregated across all allocators
+//!
+//! Design: Each heap owns its counters and is the only writer, so the fast
+//! path does uncontended relaxed increments; readers sum every registered
+//! heap. Arena memory is reclaimed only when an allocator is released (a
+//! retired thread heap at shutdown), so bytes in use only ever drop then.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Size classes are powers of two from 16 bytes; the last one is open-ended
+const MIN_CLASS_SHIFT: u32 = 4;
+const SIZE_CLASSES: usize = 10;
+
+/// Size class index for an allocation of `size` bytes
+#[inline]
+fn size_class(size: usize) -> usize {
+    let shift = usize::BITS - size.saturating_sub(1).leading_zeros();
+    (shift.saturating_sub(MIN_CLASS_SHIFT) as usize).min(SIZE_CLASSES - 1)
+}
+
+/// Largest allocation size in a class (`usize::MAX` for the last class)
+#[inline]
+fn class_max_size(class: usize) -> usize {
+    if class == SIZE_CLASSES - 1 {
+        usize::MAX
+    } else {
+        1 << (class as u32 + MIN_CLASS_SHIFT)
+    }
+}
+
+/// Per-heap allocation counters (written by the owning thread only)
+pub(super) struct HeapCounters {
+    allocations: AtomicUsize,
+    bytes: AtomicUsize,
+    wasted: AtomicUsize,
+    class_counts: [AtomicUsize; SIZE_CLASSES],
+    class_bytes: [AtomicUsize; SIZE_CLASSES],
+}
+
+impl HeapCounters {
+    pub(super) fn new() -> Self {
+        Self {
+            allocations: AtomicUsize::new(0),
+            bytes: AtomicUsize::new(0),
+            wasted: AtomicUsize::new(0),
+            class_counts: std::array::from_fn(|_| AtomicUsize::new(0)),
+            class_bytes: std::array::from_fn(|_| AtomicUsize::new(0)),
+        }
+    }
+
+    /// Record one allocation of `size` bytes that consumed `padding` extra
+    #[inline]
+    pub(super) fn record_alloc(&self, size: usize, padding: usize) {
+        let class = size_class(size);
+        self.allocations.fetch_add(1, Ordering::Relaxed);
+        self.bytes.fetch_add(size, Ordering::Relaxed);
+        self.class_counts[class].fetch_add(1, Ordering::Relaxed);
+        self.class_bytes[class].fetch_add(size, Ordering::Relaxed);
+        if padding > 0 {
+            self.wasted.fetch_add(padding, Ordering::Relaxed);
+        }
+    }
+
+    /// Record arena bytes abandoned (e.g. the tail of a replaced arena)
+    #[inline]
+    pub(super) fn record_waste(&self, bytes: usize) {
+        self.wasted.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Bytes handed out by this heap
+    #[inline]
+    pub(super) fn bytes(&self) -> usize {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    pub(super) fn allocations(&self) -> usize {
+        self.allocations.load(Ordering::Relaxed)
+    }
+
+    pub(super) fn wasted(&self) -> usize {
+        self.wasted.load(Ordering::Relaxed)
+    }
+
+    /// Fold another heap's counters into this one (when a heap is released)
+    pub(super) fn absorb(&self, other: &HeapCounters) {
+        self.allocations.fetch_add(other.allocations(), Ordering::Relaxed);
+        self.bytes.fetch_add(other.bytes(), Ordering::Relaxed);
+        for class in 0..SIZE_CLASSES {
+            self.class_counts[class].fetch_add(other.class_counts[class].load(Ordering::Relaxed), Ordering::Relaxed);
+            self.class_bytes[class].fetch_add(other.class_bytes[class].load(Ordering::Relaxed), Ordering::Relaxed);
+        }
+    }
+
+    /// Add this heap's histogram into `counts`/`bytes`
+    pub(super) fn add_histogram(&self, counts: &mut [usize; SIZE_CLASSES], bytes: &mut [usize; SIZE_CLASSES]) {
+        for class in 0..SIZE_CLASSES {
+            counts[class] += self.class_counts[class].load(Ordering::Relaxed);
+            bytes[class] += self.class_bytes[class].load(Ordering::Relaxed);
+        }
+    }
+}
+
+/// Process-wide heap statistics
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HeapStats {
+    /// Bytes handed out by every allocator since startup
+    pub total_allocated: usize,
+    /// Bytes handed out from arenas that are still mapped
+    pub bytes_in_use: usize,
+    /// Arenas currently mapped (including those of exited threads)
+    pub arena_count: usize,
+    /// Bytes reserved from the OS for mapped arenas
+    pub arena_bytes: usize,
+    /// Mapped arena bytes lost to alignment padding and abandoned arena tails
+    pub arena_wasted: usize,
+    /// Allocations since startup
+    pub allocation_count: usize,
+    /// Highest `bytes_in_use` reached
+    pub peak_usage: usize,
+    /// Registered heaps (one per allocator, including retired ones)
+    pub heap_count: usize,
+}
+
+/// Allocations within one size class
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocationInfo {
+    /// Smallest allocation size in the class
+    pub min_size: usize,
+    /// Largest allocation size in the class (`usize::MAX` for the last class)
+    pub max_size: usize,
+    /// Allocations since startup
+    pub count: usize,
+    /// Bytes requested by those allocations
+    pub bytes: usize,
+}
+
+/// Build the per-class histogram, skipping empty classes
+pub(super) fn histogram(counts: [usize; SIZE_CLASSES], bytes: [usize; SIZE_CLASSES]) -> Vec<AllocationInfo> {
+    (0..SIZE_CLASSES)
+        .filter(|&class| counts[class] > 0)
+        .map(|class| AllocationInfo {
+            min_size: if class == 0 { 0 } else { class_max_size(class - 1) + 1 },
+            max_size: class_max_size(class),
+            count: counts[class],
+            bytes: bytes[class],
+        })
+        .collect()
+}
+
+/// Empty histogram accumulators
+pub(super) fn empty_histogram() -> ([usize; SIZE_CLASSES], [usize; SIZE_CLASSES]) {
+    ([0; SIZE_CLASSES], [0; SIZE_CLASSES])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_classes() {
+        assert_eq!(size_class(0), 0);
+        assert_eq!(size_class(16), 0);
+        assert_eq!(size_class(17), 1);
+        assert_eq!(size_class(32), 1);
+        assert_eq!(size_class(4096), 8);
+        assert_eq!(size_class(4097), 9);
+        assert_eq!(size_class(1 << 20), 9);
+        assert_eq!(class_max_size(0), 16);
+        assert_eq!(class_max_size(8), 4096);
+        assert_eq!(class_max_size(9), usize::MAX);
+    }
+}
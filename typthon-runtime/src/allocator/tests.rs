@@ -7,6 +7,7 @@
 //! - Arena: OS memory management
 //! - Object Allocation: Typed object allocation with headers
 //! - Statistics: Monitoring and metrics
+//! - Registry: Process-wide arena tracking across threads
 //! - Edge Cases: Boundary conditions and corner cases
 //!
 //! Coverage: 30+ tests validating correctness, performance, and safety
@@ -314,6 +315,129 @@ mod tests {
         assert!(stats_after.current_arena_remaining > 0);
     }
 
+    #[test]
+    fn heap_stats_count_known_allocations() {
+        // Other tests allocate concurrently: global numbers only move forward
+        let before = get_stats();
+
+        let mut allocator = Allocator::new();
+        let type_info = TypeInfo::simple(48, 8, 1);
+        let type_ptr = NonNull::new(&type_info as *const _ as *mut TypeInfo).unwrap();
+        for _ in 0..100 {
+            let _: NonNull<[u8; 48]> = allocator.alloc_object(type_ptr).expect("object alloc");
+        }
+
+        let counters = allocator.heap().expect("registered heap").counters();
+        assert_eq!(counters.allocations(), 100);
+        assert_eq!(counters.bytes(), 100 * 64);
+        assert_eq!(counters.wasted(), 0);
+
+        let after = get_stats();
+        assert!(after.allocation_count >= before.allocation_count + 100);
+        assert!(after.total_allocated >= before.total_allocated + 100 * 64);
+        assert!(after.bytes_in_use >= 100 * 64);
+        assert!(after.arena_count >= 1);
+        assert!(after.arena_bytes >= after.bytes_in_use);
+        assert!(after.peak_usage >= after.bytes_in_use);
+
+        let class = allocation_info()
+            .find(|info| info.min_size <= 64 && 64 <= info.max_size)
+            .expect("64-byte size class");
+        assert!(class.count >= 100);
+        assert!(class.bytes >= 100 * 64);
+    }
+
+    #[test]
+    fn heap_stats_count_padding_and_abandoned_tails() {
+        let mut allocator = Allocator::new();
+        allocator.alloc(1, 1).expect("byte");
+        allocator.alloc(8, 8).expect("aligned");
+
+        let counters = allocator.heap().expect("registered heap").counters();
+        assert_eq!(counters.wasted(), 7);
+
+        // Larger than the current arena: its tail is abandoned
+        let remaining = allocator.stats().current_arena_remaining;
+        allocator.alloc(remaining + 1, 8).expect("large");
+        let counters = allocator.heap().expect("registered heap").counters();
+        assert_eq!(counters.wasted(), 7 + remaining);
+    }
+
+    #[test]
+    fn heap_stats_keep_peak_after_release() {
+        let mut allocator = Allocator::new();
+        for _ in 0..10 {
+            allocator.alloc(1000, 8).expect("alloc");
+        }
+        let heap = allocator.heap().expect("registered heap").id();
+        let total_before = get_stats().total_allocated;
+        drop(allocator);
+
+        let stats = get_stats();
+        assert!(!heaps().iter().any(|record| record.id() == heap));
+        assert!(stats.peak_usage >= 10 * 1000);
+        assert!(stats.total_allocated >= total_before);
+    }
+
+    // ===== Registry Tests =====
+
+    #[test]
+    fn registry_owns_arena_pointers() {
+        let mut allocator = Allocator::new();
+        let ptr = allocator.alloc(64, 8).expect("alloc");
+        assert!(owns(ptr.as_ptr()));
+
+        let heap = allocator.heap().expect("registered heap").id();
+        assert!(arenas().iter().any(|span| span.heap == heap && span.contains(ptr.as_ptr() as usize)));
+
+        let boxed = Box::new(0u64);
+        assert!(!owns(&*boxed as *const u64 as *const u8));
+    }
+
+    #[test]
+    fn registry_forgets_dropped_allocator() {
+        let mut allocator = Allocator::new();
+        allocator.alloc(64, 8).expect("alloc");
+        let heap = allocator.heap().expect("registered heap").id();
+        assert!(arenas().iter().any(|span| span.heap == heap));
+        drop(allocator);
+
+        assert!(!arenas().iter().any(|span| span.heap == heap));
+        assert!(!heaps().iter().any(|record| record.id() == heap));
+    }
+
+    #[test]
+    fn thread_arenas_outlive_thread() {
+        let (addr, heap) = std::thread::Builder::new()
+            .name("registry-test".into())
+            .spawn(|| {
+                let ptr = with_thread_allocator(|alloc| alloc.alloc(64, 8).expect("alloc"));
+                unsafe { ptr.as_ptr().write_bytes(0xAB, 64) };
+                let heap = with_thread_allocator(|alloc| alloc.heap().expect("registered heap").id());
+                (ptr.as_ptr() as usize, heap)
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+
+        // The exited thread's arena is retired, not freed
+        assert!(owns(addr as *const u8));
+        let record = heaps().into_iter().find(|record| record.id() == heap).expect("retired heap");
+        assert_eq!(record.thread(), Some("registry-test"));
+        let bytes = unsafe { std::slice::from_raw_parts(addr as *const u8, 64) };
+        assert!(bytes.iter().all(|&b| b == 0xAB));
+    }
+
+    #[test]
+    fn thread_heap_without_arenas_is_released() {
+        let heap = std::thread::spawn(|| with_thread_allocator(|alloc| alloc.heap().expect("registered heap").id()))
+            .join()
+            .unwrap();
+
+        // Nothing was allocated, so there are no objects to keep alive
+        assert!(!heaps().iter().any(|record| record.id() == heap));
+    }
+
     // ===== Edge Cases =====
 
     #[test]
@@ -8,11 +8,11 @@
 
 use std::ptr::NonNull;
 use std::alloc::{alloc, dealloc, Layout};
-use std::hash::{Hash, Hasher};
-use std::collections::hash_map::DefaultHasher;
-use crate::objects::{PyObject, ObjectType, DictData, DictEntry};
+use crate::objects::{PyObject, ObjectType, DictData, DictEntry, trace_dict_data};
 use crate::allocator::{with_thread_allocator, TypeInfo};
 use crate::gc::maybe_collect;
+use super::hash::py_object_hash;
+use super::operations::py_eq;
 
 /// Helper: increment refcount for object if it's a heap object
 #[inline]
@@ -36,7 +36,7 @@ static DICT_TYPE: TypeInfo = TypeInfo::with_drop(
     std::mem::align_of::<DictData>(),
     ObjectType::Dict as u8,
     dict_drop,
-);
+).with_trace(dict_trace);
 
 unsafe fn dict_drop(ptr: *mut u8) {
     let data = ptr as *mut DictData;
@@ -58,21 +58,20 @@ unsafe fn dict_drop(ptr: *mut u8) {
     }
 }
 
-/// Hash a PyObject
-fn hash_object(obj: PyObject) -> u64 {
-    let mut hasher = DefaultHasher::new();
-
-    if obj.is_int() {
-        obj.as_int().hash(&mut hasher);
-    } else if obj.is_special() {
-        obj.get_type().hash(&mut hasher);
-    } else {
-        // For heap objects, use pointer address (identity hash)
-        let ptr = obj.as_ptr().as_ptr() as usize;
-        ptr.hash(&mut hasher);
-    }
+unsafe fn dict_trace(ptr: *mut u8, visit: &mut dyn FnMut(*mut u8)) {
+    trace_dict_data(ptr as *const DictData, visit);
+}
 
-    hasher.finish()
+/// Hash a PyObject for table storage (None if unhashable)
+///
+/// Slot hash 0 marks an empty entry, so a real hash of 0 is stored as 1;
+/// keys are always confirmed with an equality check anyway.
+fn hash_object(obj: PyObject) -> Option<u64> {
+    match py_object_hash(obj) {
+        -1 => None,
+        0 => Some(1),
+        hash => Some(hash as u64),
+    }
 }
 
 /// Create new empty dict
@@ -136,12 +135,16 @@ pub fn py_dict_len(obj: PyObject) -> usize {
 }
 
 /// Get value for key (panics if not found)
+///
+/// Unhashable keys return None with the error slot set.
 pub fn py_dict_get(obj: PyObject, key: PyObject) -> PyObject {
     if obj.get_type() != ObjectType::Dict {
         panic!("Expected dict object");
     }
 
-    let hash = hash_object(key);
+    let Some(hash) = hash_object(key) else {
+        return PyObject::none();
+    };
 
     unsafe {
         let heap_obj = obj.as_ptr().as_ref();
@@ -161,7 +164,7 @@ pub fn py_dict_get(obj: PyObject, key: PyObject) -> PyObject {
                 panic!("Key not found");
             }
 
-            if entry.hash == hash && objects_equal(entry.key, key) {
+            if entry.hash == hash && py_eq(entry.key, key) {
                 return entry.value;
             }
 
@@ -173,12 +176,16 @@ pub fn py_dict_get(obj: PyObject, key: PyObject) -> PyObject {
 }
 
 /// Set value for key
+///
+/// Unhashable keys leave the dict unchanged with the error slot set.
 pub fn py_dict_set(obj: PyObject, key: PyObject, value: PyObject) {
     if obj.get_type() != ObjectType::Dict {
         panic!("Expected dict object");
     }
 
-    let hash = hash_object(key);
+    let Some(hash) = hash_object(key) else {
+        return;
+    };
 
     unsafe {
         let heap_obj = obj.as_ptr().as_mut();
@@ -208,7 +215,7 @@ pub fn py_dict_set(obj: PyObject, key: PyObject, value: PyObject) {
                 return;
             }
 
-            if entry.hash == hash && objects_equal(entry.key, key) {
+            if entry.hash == hash && py_eq(entry.key, key) {
                 // Existing key - update value
                 let old_value = entry.value;
                 decref_object(old_value);
@@ -224,13 +231,15 @@ pub fn py_dict_set(obj: PyObject, key: PyObject, value: PyObject) {
     }
 }
 
-/// Check if dict contains key
+/// Check if dict contains key (false with the error slot set if unhashable)
 pub fn py_dict_contains(obj: PyObject, key: PyObject) -> bool {
     if obj.get_type() != ObjectType::Dict {
         return false;
     }
 
-    let hash = hash_object(key);
+    let Some(hash) = hash_object(key) else {
+        return false;
+    };
 
     unsafe {
         let heap_obj = obj.as_ptr().as_ref();
@@ -249,7 +258,7 @@ pub fn py_dict_contains(obj: PyObject, key: PyObject) -> bool {
                 return false;
             }
 
-            if entry.hash == hash && objects_equal(entry.key, key) {
+            if entry.hash == hash && py_eq(entry.key, key) {
                 return true;
             }
 
@@ -289,42 +298,6 @@ unsafe fn resize_dict(obj: PyObject) {
     heap_obj.data_mut().dict = *new_data;
 }
 
-/// Compare two objects for equality
-fn objects_equal(a: PyObject, b: PyObject) -> bool {
-    // Small ints - direct comparison
-    if a.is_int() && b.is_int() {
-        return a.as_int() == b.as_int();
-    }
-
-    // Special values (None, bool) - type comparison
-    if a.is_special() && b.is_special() {
-        return a.get_type() == b.get_type();
-    }
-
-    // Heap objects - type-specific equality
-    if a.is_ptr() && b.is_ptr() {
-        let a_type = a.get_type();
-        let b_type = b.get_type();
-
-        if a_type != b_type {
-            return false;
-        }
-
-        match a_type {
-            ObjectType::String => crate::builtins::string::py_string_eq(a, b),
-            ObjectType::List | ObjectType::Dict | ObjectType::Tuple |
-            ObjectType::Function | ObjectType::Class | ObjectType::Instance => {
-                // For mutable types, use identity comparison
-                a.as_ptr() == b.as_ptr()
-            }
-            _ => false,
-        }
-    } else {
-        // Mixed types (int vs heap, special vs heap, etc.) are never equal
-        false
-    }
-}
-
 /// C FFI exports
 #[no_mangle]
 pub extern "C" fn typthon_dict_new() -> PyObject {
@@ -394,5 +367,29 @@ mod tests {
         assert!(py_dict_contains(dict, key1));
         assert!(!py_dict_contains(dict, key2));
     }
-}
 
+    #[test]
+    fn test_dict_tuple_keys() {
+        use crate::builtins::{py_tuple_new, py_string_new, py_list_new};
+        use crate::ffi::{take_error, clear_error};
+
+        init_allocator();
+        init_gc();
+        clear_error();
+
+        let dict = py_dict_new();
+        let key = py_tuple_new(&[py_string_new("a"), py_string_new("b")]);
+        py_dict_set(dict, key, PyObject::from_int(1));
+
+        // An equal but distinct tuple finds the same entry
+        let lookup = py_tuple_new(&[py_string_new("a"), py_string_new("b")]);
+        assert!(py_dict_contains(dict, lookup));
+        assert_eq!(py_dict_get(dict, lookup).as_int(), 1);
+        assert!(take_error().is_none());
+
+        let bad = py_tuple_new(&[py_string_new("a"), py_list_new()]);
+        py_dict_set(dict, bad, PyObject::from_int(2));
+        assert_eq!(py_dict_len(dict), 1);
+        assert_eq!(take_error().unwrap().message, "unhashable type: 'list'");
+    }
+}
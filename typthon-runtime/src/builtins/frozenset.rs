@@ -0,0 +1,279 @@
+//! FrozenSet type - immutable hashable sets
+//!
+//! Design: Fixed-size open-addressed table stored inline:
+//! - Built once from a slice, deduplicating by hash then equality
+//! - Linear probing, capacity a power of two at most half full
+//! - Element hashes kept per slot; the set's own hash is cached on first use
+//! - Reference counted elements
+
+use std::ptr::NonNull;
+use crate::objects::{PyObject, ObjectType, FrozenSetData, SetEntry, trace_object};
+use crate::allocator::{with_thread_allocator, TypeInfo};
+use crate::gc::maybe_collect;
+use super::hash::{py_object_hash, combine_frozenset};
+
+/// Marks an empty slot (never a valid hash)
+const EMPTY: i64 = -1;
+
+/// Helper: increment refcount for object if it's a heap object
+#[inline]
+fn incref_object(obj: PyObject) {
+    if obj.is_ptr() {
+        crate::ffi::typthon_incref(obj.as_ptr().as_ptr() as *mut u8);
+    }
+}
+
+/// Helper: decrement refcount for object if it's a heap object
+#[inline]
+fn decref_object(obj: PyObject) {
+    if obj.is_ptr() {
+        crate::ffi::typthon_decref(obj.as_ptr().as_ptr() as *mut u8);
+    }
+}
+
+/// Static type info for frozensets
+static FROZENSET_TYPE: TypeInfo = TypeInfo::with_drop(
+    std::mem::size_of::<FrozenSetData>(),
+    std::mem::align_of::<FrozenSetData>(),
+    ObjectType::FrozenSet as u8,
+    frozenset_drop,
+).with_trace(frozenset_trace);
+
+unsafe fn frozenset_drop(ptr: *mut u8) {
+    let data = &*(ptr as *const FrozenSetData);
+    for entry in entries(data) {
+        if entry.hash != EMPTY {
+            decref_object(entry.key);
+        }
+    }
+}
+
+unsafe fn frozenset_trace(ptr: *mut u8, visit: &mut dyn FnMut(*mut u8)) {
+    let data = &*(ptr as *const FrozenSetData);
+    for entry in entries(data) {
+        if entry.hash != EMPTY {
+            trace_object(entry.key, visit);
+        }
+    }
+}
+
+/// View the inline slot table
+#[inline]
+unsafe fn entries(data: &FrozenSetData) -> &[SetEntry] {
+    std::slice::from_raw_parts(data.entries.as_ptr(), data.capacity)
+}
+
+#[inline]
+fn data_of(obj: PyObject) -> &'static FrozenSetData {
+    if obj.get_type() != ObjectType::FrozenSet {
+        panic!("Expected frozenset object");
+    }
+    unsafe { &obj.as_ptr().as_ref().data().frozenset }
+}
+
+/// Find the slot holding `key`, or the empty slot where it would go
+fn probe(table: &[SetEntry], key: PyObject, hash: i64) -> usize {
+    let mask = table.len() - 1;
+    let mut index = (hash as u64 as usize) & mask;
+
+    loop {
+        let entry = &table[index];
+        if entry.hash == EMPTY
+            || (entry.hash == hash && crate::builtins::operations::py_eq(entry.key, key))
+        {
+            return index;
+        }
+        index = (index + 1) & mask;
+    }
+}
+
+/// Create frozenset from slice of objects
+///
+/// Returns None with `unhashable type: '<name>'` in the error slot if any
+/// item is unhashable.
+pub fn py_frozenset_new(items: &[PyObject]) -> PyObject {
+    let mut hashes = Vec::with_capacity(items.len());
+    for &item in items {
+        let hash = py_object_hash(item);
+        if hash == -1 {
+            return PyObject::none();
+        }
+        hashes.push(hash);
+    }
+
+    let capacity = (items.len() * 2).next_power_of_two().max(8);
+    let total_size = std::mem::size_of::<crate::allocator::ObjectHeader>()
+        + std::mem::size_of::<FrozenSetData>()
+        + capacity * std::mem::size_of::<SetEntry>();
+
+    let obj = with_thread_allocator(|alloc| {
+        let type_info = NonNull::new(&FROZENSET_TYPE as *const _ as *mut _).unwrap();
+        let ptr = alloc.alloc(total_size, std::mem::align_of::<FrozenSetData>())
+            .expect("Failed to allocate frozenset");
+
+        unsafe {
+            // Initialize header
+            let header_ptr = ptr.as_ptr() as *mut crate::allocator::ObjectHeader;
+            header_ptr.write(crate::allocator::ObjectHeader::new(type_info));
+
+            // Initialize set data with every slot empty
+            let data_ptr = header_ptr.add(1) as *mut FrozenSetData;
+            (*data_ptr).len = 0;
+            (*data_ptr).capacity = capacity;
+            (*data_ptr).hash = -1;
+
+            let slots = std::slice::from_raw_parts_mut(
+                (*data_ptr).entries.as_mut_ptr(),
+                capacity,
+            );
+            for slot in slots.iter_mut() {
+                *slot = SetEntry { hash: EMPTY, key: PyObject::none() };
+            }
+
+            // Insert distinct items, incrementing refcounts
+            for (&item, &hash) in items.iter().zip(&hashes) {
+                let index = probe(slots, item, hash);
+                if slots[index].hash == EMPTY {
+                    incref_object(item);
+                    slots[index] = SetEntry { hash, key: item };
+                    (*data_ptr).len += 1;
+                }
+            }
+
+            PyObject::from_ptr(NonNull::new_unchecked(data_ptr).cast())
+        }
+    });
+
+    maybe_collect();
+    obj
+}
+
+/// Get frozenset length
+pub fn py_frozenset_len(obj: PyObject) -> usize {
+    data_of(obj).len
+}
+
+/// Check membership (false with the error slot set if `key` is unhashable)
+pub fn py_frozenset_contains(obj: PyObject, key: PyObject) -> bool {
+    let data = data_of(obj);
+
+    let hash = py_object_hash(key);
+    if hash == -1 {
+        return false;
+    }
+
+    unsafe {
+        let table = entries(data);
+        table[probe(table, key, hash)].hash != EMPTY
+    }
+}
+
+/// Frozenset equality (same members, order independent)
+pub fn py_frozenset_eq(a: PyObject, b: PyObject) -> bool {
+    if a.get_type() != ObjectType::FrozenSet || b.get_type() != ObjectType::FrozenSet {
+        return false;
+    }
+
+    if py_frozenset_len(a) != py_frozenset_len(b) {
+        return false;
+    }
+
+    py_frozenset_items(a).into_iter().all(|item| py_frozenset_contains(b, item))
+}
+
+/// Members in table order
+pub fn py_frozenset_items(obj: PyObject) -> Vec<PyObject> {
+    let data = data_of(obj);
+    unsafe {
+        entries(data).iter()
+            .filter(|entry| entry.hash != EMPTY)
+            .map(|entry| entry.key)
+            .collect()
+    }
+}
+
+/// Hash of the whole set, computed once and cached
+pub(crate) fn frozenset_hash(obj: PyObject) -> i64 {
+    unsafe {
+        let data = &mut obj.as_ptr().as_mut().data_mut().frozenset;
+        if data.hash == -1 {
+            let hashes: Vec<i64> = entries(data).iter()
+                .filter(|entry| entry.hash != EMPTY)
+                .map(|entry| entry.hash)
+                .collect();
+            data.hash = combine_frozenset(hashes.into_iter());
+        }
+        data.hash
+    }
+}
+
+/// C FFI exports
+#[no_mangle]
+pub extern "C" fn typthon_frozenset_new(items: *const PyObject, len: usize) -> PyObject {
+    let slice = if len == 0 { &[][..] } else { unsafe { std::slice::from_raw_parts(items, len) } };
+    py_frozenset_new(slice)
+}
+
+#[no_mangle]
+pub extern "C" fn typthon_frozenset_len(obj: PyObject) -> usize {
+    py_frozenset_len(obj)
+}
+
+#[no_mangle]
+pub extern "C" fn typthon_frozenset_contains(obj: PyObject, key: PyObject) -> bool {
+    py_frozenset_contains(obj, key)
+}
+
+#[no_mangle]
+pub extern "C" fn typthon_frozenset_eq(a: PyObject, b: PyObject) -> bool {
+    py_frozenset_eq(a, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::allocator::init as init_allocator;
+    use crate::gc::init as init_gc;
+    use crate::builtins::{py_list_new, py_string_new};
+    use crate::ffi::{take_error, clear_error};
+
+    #[test]
+    fn test_frozenset_dedup_and_contains() {
+        init_allocator();
+        init_gc();
+
+        let items = vec![PyObject::from_int(1), PyObject::from_int(2), PyObject::from_int(1)];
+        let set = py_frozenset_new(&items);
+
+        assert_eq!(py_frozenset_len(set), 2);
+        assert!(py_frozenset_contains(set, PyObject::from_int(2)));
+        assert!(!py_frozenset_contains(set, PyObject::from_int(3)));
+        assert!(py_frozenset_contains(py_frozenset_new(&[py_string_new("a")]), py_string_new("a")));
+    }
+
+    #[test]
+    fn test_frozenset_hash_matches_cpython() {
+        init_allocator();
+        init_gc();
+
+        // Reference values from CPython (64-bit)
+        assert_eq!(py_object_hash(py_frozenset_new(&[])), 133_146_708_735_736);
+
+        let a = py_frozenset_new(&[PyObject::from_int(1), PyObject::from_int(2), PyObject::from_int(3)]);
+        let b = py_frozenset_new(&[PyObject::from_int(3), PyObject::from_int(2), PyObject::from_int(1)]);
+        assert_eq!(py_object_hash(a), -272_375_401_224_217_160);
+        assert_eq!(py_object_hash(a), py_object_hash(b));
+        assert!(py_frozenset_eq(a, b));
+    }
+
+    #[test]
+    fn test_frozenset_rejects_unhashable() {
+        init_allocator();
+        init_gc();
+        clear_error();
+
+        let set = py_frozenset_new(&[PyObject::from_int(1), py_list_new()]);
+        assert_eq!(set.get_type(), ObjectType::None);
+        assert_eq!(take_error().unwrap().message, "unhashable type: 'list'");
+    }
+}
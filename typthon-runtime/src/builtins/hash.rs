@@ -0,0 +1,279 @@
+//! Hashing - CPython-compatible hashes for dict keys and set members
+//!
+//! Design: Mirror CPython's hash rules so programs relying on hash values
+//! behave the same within a process:
+//! - Ints and floats reduce modulo the Mersenne prime 2**61 - 1, so equal
+//!   numbers hash equal
+//! - Tuples combine element hashes with CPython's xxHash-based scheme
+//! - Frozensets use CPython's order-independent shuffle-and-xor scheme
+//! - Lists and dicts are unhashable, including when nested inside a tuple
+//!
+//! `-1` is never a valid hash: it signals an error, with the message left in
+//! the runtime error slot (`ffi::typthon_last_error`).
+
+use std::hash::{Hash, Hasher};
+use std::collections::hash_map::DefaultHasher;
+use crate::objects::{PyObject, ObjectType};
+use crate::ffi::{set_error, ErrorCode};
+
+/// Modulus for numeric hashes (2**61 - 1)
+const HASH_MODULUS: u64 = (1 << 61) - 1;
+
+/// Hash of `float('inf')`; `-inf` hashes to the negation
+const HASH_INF: i64 = 314_159;
+
+/// Hash of `None` (CPython 3.12+ uses a constant)
+const HASH_NONE: i64 = 0xFCA8_6420;
+
+// xxHash primes used by CPython's tuple hash
+const XXPRIME_1: u64 = 11_400_714_785_074_694_791;
+const XXPRIME_2: u64 = 14_029_467_366_897_019_727;
+const XXPRIME_5: u64 = 2_870_177_450_012_600_261;
+
+/// Python type name used in `unhashable type` errors
+fn type_name(ty: ObjectType) -> &'static str {
+    match ty {
+        ObjectType::List => "list",
+        ObjectType::Dict => "dict",
+        ObjectType::Tuple => "tuple",
+        ObjectType::FrozenSet => "frozenset",
+        ObjectType::Function => "function",
+        ObjectType::Class => "type",
+        ObjectType::Instance => "object",
+        ObjectType::String => "str",
+        ObjectType::Float => "float",
+        ObjectType::Int => "int",
+        ObjectType::Bool => "bool",
+        ObjectType::None => "NoneType",
+        ObjectType::Unknown => "object",
+    }
+}
+
+/// Never hand out the error sentinel as a real hash
+#[inline]
+fn fix_sentinel(hash: i64) -> i64 {
+    if hash == -1 { -2 } else { hash }
+}
+
+/// Hash an int the way CPython does (`hash(n) == n` for small n, `hash(-1) == -2`)
+#[inline]
+fn hash_int(val: i64) -> i64 {
+    let reduced = (val.unsigned_abs() % HASH_MODULUS) as i64;
+    fix_sentinel(if val < 0 { -reduced } else { reduced })
+}
+
+/// Split a finite, non-zero float into mantissa in [0.5, 1) and exponent
+fn frexp(val: f64) -> (f64, i32) {
+    let bits = val.to_bits();
+    let exp = ((bits >> 52) & 0x7ff) as i32;
+    if exp == 0 {
+        // Subnormal: scale into the normal range first
+        let (m, e) = frexp(val * f64::from_bits(0x43f0_0000_0000_0000)); // 2**64
+        return (m, e - 64);
+    }
+    let mantissa = f64::from_bits((bits & !(0x7ff << 52)) | (1022 << 52));
+    (mantissa, exp - 1022)
+}
+
+/// Hash a float with CPython's algorithm (integral values hash like the equal int)
+fn hash_float(val: f64) -> i64 {
+    const BITS: i32 = 61;
+
+    if val.is_nan() {
+        return 0;
+    }
+    if val.is_infinite() {
+        return if val > 0.0 { HASH_INF } else { -HASH_INF };
+    }
+    if val == 0.0 {
+        return 0;
+    }
+
+    let (mut m, mut e) = frexp(val.abs());
+    let mut x: u64 = 0;
+
+    // Consume the mantissa 28 bits at a time, rotating within 61 bits
+    while m != 0.0 {
+        x = ((x << 28) & HASH_MODULUS) | (x >> (BITS - 28));
+        m *= 268_435_456.0; // 2**28
+        e -= 28;
+        let y = m as u64;
+        m -= y as f64;
+        x += y;
+        if x >= HASH_MODULUS {
+            x -= HASH_MODULUS;
+        }
+    }
+
+    let e = if e >= 0 { e % BITS } else { BITS - 1 - ((-1 - e) % BITS) } as u32;
+    x = ((x << e) & HASH_MODULUS) | (x >> (BITS as u32 - e));
+
+    let signed = if val < 0.0 { -(x as i64) } else { x as i64 };
+    fix_sentinel(signed)
+}
+
+/// Hash string contents (stable within a process)
+fn hash_str(s: &str) -> i64 {
+    let mut hasher = DefaultHasher::new();
+    s.hash(&mut hasher);
+    fix_sentinel(hasher.finish() as i64)
+}
+
+/// CPython's tuple hash over precomputed element hashes
+fn combine_tuple(hashes: impl ExactSizeIterator<Item = i64>) -> i64 {
+    let len = hashes.len() as u64;
+    let mut acc = XXPRIME_5;
+
+    for lane in hashes {
+        acc = acc.wrapping_add((lane as u64).wrapping_mul(XXPRIME_2));
+        acc = acc.rotate_left(31);
+        acc = acc.wrapping_mul(XXPRIME_1);
+    }
+
+    acc = acc.wrapping_add(len ^ (XXPRIME_5 ^ 3_527_539));
+
+    if acc == u64::MAX {
+        1_546_275_796
+    } else {
+        acc as i64
+    }
+}
+
+/// CPython's frozenset hash over element hashes (order independent)
+pub(crate) fn combine_frozenset(hashes: impl ExactSizeIterator<Item = i64>) -> i64 {
+    #[inline]
+    fn shuffle_bits(h: u64) -> u64 {
+        ((h ^ 89_869_747) ^ (h << 16)).wrapping_mul(3_644_798_167)
+    }
+
+    let len = hashes.len() as u64;
+    let mut hash: u64 = 0;
+
+    for entry in hashes {
+        hash ^= shuffle_bits(entry as u64);
+    }
+
+    hash ^= (len + 1).wrapping_mul(1_927_868_237);
+    hash ^= (hash >> 11) ^ (hash >> 25);
+    hash = hash.wrapping_mul(69_069).wrapping_add(907_133_923);
+
+    if hash == u64::MAX {
+        590_923_713
+    } else {
+        hash as i64
+    }
+}
+
+/// Hash any object, returning -1 and setting the error slot if it is unhashable
+///
+/// Exported unmangled so generated code can call it directly.
+#[no_mangle]
+pub extern "C" fn py_object_hash(obj: PyObject) -> i64 {
+    if obj.is_int() {
+        return hash_int(obj.as_int());
+    }
+
+    match obj.get_type() {
+        ObjectType::None => HASH_NONE,
+        ObjectType::Bool => hash_int(obj.is_truthy() as i64),
+        ObjectType::Float => hash_float(crate::builtins::py_float_as_f64(obj)),
+        ObjectType::String => hash_str(crate::builtins::py_string_as_str(obj)),
+        ObjectType::Tuple => {
+            let len = crate::builtins::py_tuple_len(obj);
+            let mut hashes = Vec::with_capacity(len);
+            for i in 0..len {
+                let h = py_object_hash(crate::builtins::py_tuple_get(obj, i as isize));
+                if h == -1 {
+                    return -1;
+                }
+                hashes.push(h);
+            }
+            combine_tuple(hashes.into_iter())
+        }
+        ObjectType::FrozenSet => crate::builtins::frozenset::frozenset_hash(obj),
+        ObjectType::Function | ObjectType::Class | ObjectType::Instance => {
+            // Identity hash, like object.__hash__ (address rotated by 4 bits)
+            let addr = obj.as_ptr().as_ptr() as usize as u64;
+            fix_sentinel(addr.rotate_right(4) as i64)
+        }
+        // Ints are always inline, so only containers and unknowns land here
+        ty @ (ObjectType::List | ObjectType::Dict | ObjectType::Int | ObjectType::Unknown) => {
+            set_error(ErrorCode::TypeError, format!("unhashable type: '{}'", type_name(ty)));
+            -1
+        }
+    }
+}
+
+/// Check hashability without computing the hash or touching the error slot
+pub fn py_is_hashable(obj: PyObject) -> bool {
+    match obj.get_type() {
+        ObjectType::List | ObjectType::Dict | ObjectType::Unknown => false,
+        ObjectType::Tuple => (0..crate::builtins::py_tuple_len(obj))
+            .all(|i| py_is_hashable(crate::builtins::py_tuple_get(obj, i as isize))),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::allocator::init as init_allocator;
+    use crate::gc::init as init_gc;
+    use crate::builtins::{py_tuple_new, py_list_new, py_string_new, py_float_new};
+    use crate::ffi::{take_error, clear_error};
+
+    #[test]
+    fn test_int_hash_matches_cpython() {
+        assert_eq!(py_object_hash(PyObject::from_int(0)), 0);
+        assert_eq!(py_object_hash(PyObject::from_int(42)), 42);
+        assert_eq!(py_object_hash(PyObject::from_int(-1)), -2);
+        assert_eq!(py_object_hash(PyObject::from_bool(true)), 1);
+        assert_eq!(py_object_hash(PyObject::from_bool(false)), 0);
+    }
+
+    #[test]
+    fn test_tuple_hash_matches_cpython() {
+        init_allocator();
+        init_gc();
+
+        // Reference values from CPython 3.12 (64-bit)
+        let empty = py_tuple_new(&[]);
+        assert_eq!(py_object_hash(empty), 5_740_354_900_026_072_187);
+
+        let pair = py_tuple_new(&[PyObject::from_int(1), PyObject::from_int(2)]);
+        assert_eq!(py_object_hash(pair), -3_550_055_125_485_641_917);
+    }
+
+    #[test]
+    fn test_float_hash_agrees_with_int() {
+        init_allocator();
+        init_gc();
+
+        assert_eq!(py_object_hash(py_float_new(3.0)), py_object_hash(PyObject::from_int(3)));
+        assert_eq!(py_object_hash(py_float_new(-1.0)), -2);
+        assert_eq!(py_object_hash(py_float_new(3.5)), 1_152_921_504_606_846_979);
+        assert_eq!(py_object_hash(py_float_new(f64::INFINITY)), 314_159);
+    }
+
+    #[test]
+    fn test_equal_tuples_hash_equal() {
+        init_allocator();
+        init_gc();
+
+        let a = py_tuple_new(&[py_string_new("x"), py_string_new("y")]);
+        let b = py_tuple_new(&[py_string_new("x"), py_string_new("y")]);
+        assert_eq!(py_object_hash(a), py_object_hash(b));
+    }
+
+    #[test]
+    fn test_unhashable_inside_tuple() {
+        init_allocator();
+        init_gc();
+        clear_error();
+
+        let key = py_tuple_new(&[py_string_new("a"), py_list_new()]);
+        assert!(!py_is_hashable(key));
+        assert_eq!(py_object_hash(key), -1);
+        assert_eq!(take_error().unwrap().message, "unhashable type: 'list'");
+    }
+}
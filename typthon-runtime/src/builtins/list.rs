@@ -8,7 +8,7 @@
 
 use std::ptr::NonNull;
 use std::alloc::{alloc, dealloc, realloc, Layout};
-use crate::objects::{PyObject, ObjectType, ListData};
+use crate::objects::{PyObject, ObjectType, ListData, trace_object};
 use crate::allocator::{with_thread_allocator, TypeInfo};
 use crate::gc::maybe_collect;
 
@@ -34,7 +34,7 @@ static LIST_TYPE: TypeInfo = TypeInfo::with_drop(
     std::mem::align_of::<ListData>(),
     ObjectType::List as u8,
     list_drop,
-);
+).with_trace(list_trace);
 
 unsafe fn list_drop(ptr: *mut u8) {
     let data = ptr as *mut ListData;
@@ -53,6 +53,13 @@ unsafe fn list_drop(ptr: *mut u8) {
     }
 }
 
+unsafe fn list_trace(ptr: *mut u8, visit: &mut dyn FnMut(*mut u8)) {
+    let data = &*(ptr as *const ListData);
+    for i in 0..data.len {
+        trace_object(*data.ptr.add(i), visit);
+    }
+}
+
 /// Create new empty list
 pub fn py_list_new() -> PyObject {
     py_list_with_capacity(8)
@@ -11,6 +11,8 @@ mod list;
 mod dict;
 mod float;
 mod tuple;
+mod frozenset;
+mod hash;
 mod operations;
 
 #[cfg(test)]
@@ -23,7 +25,9 @@ pub use string::{py_string_new, py_string_len, py_string_concat, py_string_eq, p
 pub use list::{py_list_new, py_list_len, py_list_get, py_list_set, py_list_append, py_list_from_slice};
 pub use dict::{py_dict_new, py_dict_len, py_dict_get, py_dict_set, py_dict_contains};
 pub use float::{py_float_new, py_float_as_f64, py_float_add, py_float_sub, py_float_mul, py_float_div, py_int_to_float, py_float_to_int};
-pub use tuple::{py_tuple_new, py_tuple_len, py_tuple_get};
+pub use tuple::{py_tuple_new, py_tuple_len, py_tuple_get, py_tuple_eq};
+pub use frozenset::{py_frozenset_new, py_frozenset_len, py_frozenset_contains, py_frozenset_eq, py_frozenset_items};
+pub use hash::{py_object_hash, py_is_hashable};
 pub use operations::{py_add, py_sub, py_mul, py_div, py_eq, py_ne, py_lt, py_le, py_gt, py_ge};
 
 use crate::logging::{info, debug};
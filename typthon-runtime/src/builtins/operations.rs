@@ -212,6 +212,7 @@ pub fn py_eq(a: PyObject, b: PyObject) -> bool {
         }
         ObjectType::String => py_string_eq(a, b),
         ObjectType::Tuple => super::tuple::py_tuple_eq(a, b),
+        ObjectType::FrozenSet => super::frozenset::py_frozenset_eq(a, b),
         ObjectType::List | ObjectType::Dict | ObjectType::Function |
         ObjectType::Class | ObjectType::Instance => {
             // Identity comparison for mutable types
@@ -7,7 +7,7 @@
 //! - Reference counted elements
 
 use std::ptr::NonNull;
-use crate::objects::{PyObject, ObjectType, TupleData};
+use crate::objects::{PyObject, ObjectType, TupleData, trace_object};
 use crate::allocator::{with_thread_allocator, TypeInfo};
 use crate::gc::maybe_collect;
 
@@ -33,7 +33,7 @@ static TUPLE_TYPE: TypeInfo = TypeInfo::with_drop(
     std::mem::align_of::<TupleData>(),
     ObjectType::Tuple as u8,
     tuple_drop,
-);
+).with_trace(tuple_trace);
 
 unsafe fn tuple_drop(ptr: *mut u8) {
     let data = ptr as *mut TupleData;
@@ -47,12 +47,22 @@ unsafe fn tuple_drop(ptr: *mut u8) {
     }
 }
 
+unsafe fn tuple_trace(ptr: *mut u8, visit: &mut dyn FnMut(*mut u8)) {
+    let data = &*(ptr as *const TupleData);
+    let elements_ptr = data.elements.as_ptr();
+    for i in 0..data.len {
+        trace_object(*elements_ptr.add(i), visit);
+    }
+}
+
 /// Create new tuple from slice of objects
 pub fn py_tuple_new(items: &[PyObject]) -> PyObject {
     let len = items.len();
 
-    // Allocate tuple with inline elements
-    let total_size = std::mem::size_of::<TupleData>() + len * std::mem::size_of::<PyObject>();
+    // Allocate header plus tuple with inline elements
+    let total_size = std::mem::size_of::<crate::allocator::ObjectHeader>()
+        + std::mem::size_of::<TupleData>()
+        + len * std::mem::size_of::<PyObject>();
 
     let obj = with_thread_allocator(|alloc| {
         let type_info = NonNull::new(&TUPLE_TYPE as *const _ as *mut _).unwrap();
@@ -0,0 +1,89 @@
+//! Error slot - thread-local pending exception for generated code
+//!
+//! Builtins that can fail without aborting record an error here and return
+//! a sentinel; generated code checks `typthon_last_error` after the call.
+
+use std::cell::RefCell;
+use crate::objects::PyObject;
+use crate::logging::log_runtime_error;
+
+/// Error codes surfaced through `typthon_last_error`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum ErrorCode {
+    None = 0,
+    TypeError = 1,
+}
+
+/// Pending runtime error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<RuntimeError>> = const { RefCell::new(None) };
+}
+
+/// Record an error for the current thread, replacing any pending one
+pub fn set_error(code: ErrorCode, message: impl Into<String>) {
+    let message = message.into();
+    log_runtime_error(&message);
+    LAST_ERROR.with(|slot| *slot.borrow_mut() = Some(RuntimeError { code, message }));
+}
+
+/// Peek at the pending error without clearing it
+pub fn last_error() -> Option<RuntimeError> {
+    LAST_ERROR.with(|slot| slot.borrow().clone())
+}
+
+/// Take and clear the pending error
+pub fn take_error() -> Option<RuntimeError> {
+    LAST_ERROR.with(|slot| slot.borrow_mut().take())
+}
+
+/// Clear the pending error
+pub fn clear_error() {
+    LAST_ERROR.with(|slot| *slot.borrow_mut() = None);
+}
+
+/// Get last error code (0 when no error is pending)
+#[no_mangle]
+pub extern "C" fn typthon_last_error() -> i32 {
+    last_error().map_or(ErrorCode::None, |e| e.code) as i32
+}
+
+/// Get last error message as a string object (None when no error is pending)
+#[no_mangle]
+pub extern "C" fn typthon_last_error_message() -> PyObject {
+    match last_error() {
+        Some(e) => crate::builtins::py_string_new(&e.message),
+        None => PyObject::none(),
+    }
+}
+
+/// Clear the pending error
+#[no_mangle]
+pub extern "C" fn typthon_clear_error() {
+    clear_error();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_slot_roundtrip() {
+        clear_error();
+        assert_eq!(typthon_last_error(), 0);
+
+        set_error(ErrorCode::TypeError, "unhashable type: 'list'");
+        assert_eq!(typthon_last_error(), ErrorCode::TypeError as i32);
+        assert_eq!(last_error().unwrap().message, "unhashable type: 'list'");
+
+        let taken = take_error().unwrap();
+        assert_eq!(taken.code, ErrorCode::TypeError);
+        assert_eq!(typthon_last_error(), 0);
+    }
+}
@@ -5,12 +5,20 @@
 //! 2. Reference counting (incref, decref)
 //! 3. Type-safe conversions (Rust ↔ C)
 //! 4. Error propagation via null pointers
+//! 5. Thread attachment and safepoints (see `thread` for the threading contract)
 
 mod object;
 mod refcount;
+mod error;
+mod thread;
 
 pub use object::{typthon_object_new, typthon_object_destroy};
 pub use refcount::{typthon_incref, typthon_decref, typthon_refcount};
+pub use thread::{typthon_thread_attach, typthon_thread_detach, typthon_safepoint};
+pub use error::{
+    ErrorCode, RuntimeError, set_error, last_error, take_error, clear_error,
+    typthon_last_error, typthon_last_error_message, typthon_clear_error,
+};
 
 use core::ptr::NonNull;
 use crate::logging::{info, debug};
@@ -30,13 +38,6 @@ pub extern "C" fn typthon_ffi_cleanup() {
     // Future: Flush pending operations, validate refcounts
 }
 
-/// Get last error code (thread-local, for error propagation)
-#[no_mangle]
-pub extern "C" fn typthon_last_error() -> i32 {
-    // Future: Implement thread-local error state
-    0
-}
-
 /// Convert raw pointer to NonNull (internal helper)
 #[inline(always)]
 pub(crate) fn ptr_to_nonnull<T>(ptr: *mut T) -> Option<NonNull<T>> {
@@ -84,10 +84,10 @@ pub extern "C" fn typthon_object_to_string(obj: PyObject) -> PyObject {
     crate::builtins::py_string_new(&s)
 }
 
-/// Hash object
+/// Hash object (-1 with the error slot set if unhashable)
 #[no_mangle]
-pub extern "C" fn typthon_object_hash(obj: PyObject) -> u64 {
-    obj.hash()
+pub extern "C" fn typthon_object_hash(obj: PyObject) -> i64 {
+    crate::builtins::py_object_hash(obj)
 }
 
 /// Create PyObject from C int
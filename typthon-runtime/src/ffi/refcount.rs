@@ -1,7 +1,8 @@
 //! Reference counting - C API for refcount operations
 //!
 //! Hot path operations with minimal overhead, inlined by compiler.
-//! Thread-safe atomic operations.
+//! Thread-safe atomic operations: callable from any thread, including on
+//! objects allocated by another (possibly exited) thread.
 
 use crate::allocator::ObjectHeader;
 use std::sync::atomic::Ordering;
@@ -32,7 +33,8 @@ pub extern "C" fn typthon_incref(obj: *mut u8) {
 /// - Null-safe (no-op for null pointers)
 /// - Object must be valid heap object
 /// - Underflow checked in debug builds
-/// - Calls `typthon_object_destroy` when refcount hits zero
+/// - Destroys the object when refcount hits zero, on whichever thread
+///   released the last reference
 #[no_mangle]
 pub extern "C" fn typthon_decref(obj: *mut u8) {
     if obj.is_null() {
@@ -76,23 +78,26 @@ pub extern "C" fn typthon_refcount(obj: *const u8) -> u32 {
 #[inline(never)]
 unsafe fn destroy_object(obj: *mut u8) {
     let header = &*ObjectHeader::from_object(obj);
+    crate::gc::forget_candidate(ObjectHeader::from_object(obj));
 
     // Call type-specific destructor if present
     if let Some(drop_fn) = header.type_info.as_ref().drop {
         drop_fn(obj);
     }
 
-    // Note: In production, the allocator uses arena-based allocation
-    // where memory is freed in bulk. For FFI objects that may be allocated
-    // via system allocator (during bootstrapping or testing), we use dealloc.
-    // Arena-allocated objects will be reclaimed during arena sweeps.
+    // Arena-allocated objects are reclaimed in bulk with their arena (which
+    // may belong to another thread). Objects created via the system allocator
+    // (during bootstrapping or testing) are freed individually.
+    let header_ptr = ObjectHeader::from_object(obj) as *mut u8;
+    if crate::allocator::owns(header_ptr) {
+        return;
+    }
+
     let layout = std::alloc::Layout::from_size_align_unchecked(
         header.type_info.as_ref().size + core::mem::size_of::<ObjectHeader>(),
         8,
     );
-    // Only dealloc if this was system-allocated (not arena-allocated)
-    // For now we always dealloc; in production we'd check allocation source
-    std::alloc::dealloc(ObjectHeader::from_object(obj) as *mut u8, layout);
+    std::alloc::dealloc(header_ptr, layout);
 }
 
 /// Increment refcount and return same pointer (for chaining)
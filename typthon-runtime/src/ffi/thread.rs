@@ -0,0 +1,32 @@
+//! Thread coordination - C API for multi-threaded generated code
+//!
+//! Contract:
+//! - `typthon_incref` / `typthon_decref` may be called from any thread, on
+//!   objects allocated by any thread (refcounts are atomic; arenas outlive
+//!   the thread that created them)
+//! - Every thread that touches runtime objects calls `typthon_thread_attach`
+//!   first and `typthon_thread_detach` before exiting (exit also detaches)
+//! - Attached threads reach a safepoint regularly: every allocation is one,
+//!   long loops without allocation call `typthon_safepoint`
+//! - Before blocking indefinitely, detach or wrap the wait in `gc::blocking`
+//!   so cycle collection is not stalled
+
+use crate::gc;
+
+/// Register the calling thread as a mutator (idempotent)
+#[no_mangle]
+pub extern "C" fn typthon_thread_attach() {
+    gc::attach_thread();
+}
+
+/// Unregister the calling thread (idempotent)
+#[no_mangle]
+pub extern "C" fn typthon_thread_detach() {
+    gc::detach_thread();
+}
+
+/// Park if a cycle collection is waiting for this thread (hot path)
+#[no_mangle]
+pub extern "C" fn typthon_safepoint() {
+    gc::safepoint();
+}
@@ -1,36 +1,53 @@
-//! Cycle detection via tricolor mark-sweep
+//! Cycle detection via trial deletion (synchronous Bacon-Rajan)
 //!
 //! Rare operation for circular reference chains that refcounting can't handle.
-//! Based on Python's generational GC and Bacon's incremental cycle collector.
+//! Candidates are objects whose refcount dropped but stayed above zero. For each
+//! collection, references internal to the candidate subgraph are subtracted
+//! (mark gray); anything still holding a count must be referenced from outside
+//! and is restored together with everything it reaches (scan black). Whatever
+//! remains white is only kept alive by itself and is reclaimed.
+//!
+//! Child edges come from `TypeInfo::trace`; types without one are leaves.
 
 use crate::allocator::ObjectHeader;
 use crate::logging::{debug, trace, log_gc_start, log_gc_mark, log_gc_sweep};
 use dashmap::DashSet;
 use parking_lot::Mutex;
 use once_cell::sync::Lazy;
-use std::collections::VecDeque;
+use std::collections::HashSet;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use super::roots::get_roots;
+use super::safepoint::stop_the_world;
 
 /// Global cycle collector state (lock-free + fine-grained locking)
 static COLLECTOR: Lazy<CycleCollector> = Lazy::new(CycleCollector::new);
 
-/// Tricolor marking states for cycle detection
+/// Header flag bits 0-1 hold the color
+const COLOR_MASK: u32 = 0b11;
+
+/// Trial deletion colors (black is the resting state of every object)
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Color {
-    White,  // Unreachable (potential garbage)
-    Gray,   // Reachable, children not yet scanned
-    Black,  // Reachable, children scanned
+    Black,  // In use (or not yet examined)
+    Gray,   // Internal references subtracted, possible cycle member
+    White,  // No external references: garbage
+}
+
+impl Color {
+    const fn bits(self) -> u32 {
+        match self {
+            Color::Black => 0b00,
+            Color::Gray => 0b01,
+            Color::White => 0b10,
+        }
+    }
 }
 
-/// Cycle collector using tricolor mark-sweep with lock-free registration
+/// Cycle collector using trial deletion with lock-free registration
 struct CycleCollector {
     /// Candidate objects (lock-free concurrent set)
     candidates: DashSet<*mut ObjectHeader>,
 
-    /// Gray set for incremental marking (mutex-protected during collection only)
-    gray_set: Mutex<VecDeque<*mut ObjectHeader>>,
-
     /// Collection lock (prevents concurrent collections)
     collection_lock: Mutex<()>,
 
@@ -49,7 +66,6 @@ impl CycleCollector {
     fn new() -> Self {
         Self {
             candidates: DashSet::with_capacity(1024),
-            gray_set: Mutex::new(VecDeque::with_capacity(256)),
             collection_lock: Mutex::new(()),
             total_objects: AtomicUsize::new(0),
             reachable_objects: AtomicUsize::new(0),
@@ -58,243 +74,238 @@ impl CycleCollector {
         }
     }
 
-    /// Run full mark-sweep collection cycle (synchronized)
+    /// Run a full trial-deletion pass over the buffered candidates (synchronized)
+    ///
+    /// Mutators are stopped for the whole pass: trial deletion temporarily
+    /// lowers refcounts that other threads would otherwise observe.
     fn collect(&self) {
+        if self.candidates.is_empty() {
+            return;
+        }
+
+        let _world = stop_the_world();
+
         // Only one collection at a time
         let _guard = self.collection_lock.lock();
 
-        let candidate_count = self.candidates.len();
-        if candidate_count == 0 {
+        let candidates = self.take_candidates();
+        if candidates.is_empty() {
             return;
         }
 
-        log_gc_start(0); // candidate_count tracked separately
-        debug!(candidates = candidate_count, "Starting cycle collection");
+        log_gc_start(candidates.len());
+        debug!(
+            candidates = candidates.len(),
+            arenas = crate::allocator::arenas().len(),
+            "Starting cycle collection"
+        );
 
         self.collections_run.fetch_add(1, Ordering::Relaxed);
-        self.total_objects.store(candidate_count, Ordering::Relaxed);
-        self.reachable_objects.store(0, Ordering::Relaxed);
 
-        // Phase 1: Mark all as white (assume garbage)
-        trace!("Phase 1: Marking all candidates as white");
-        self.mark_white();
+        // Registered roots count as external references even without a refcount
+        let roots: HashSet<*mut ObjectHeader> = get_roots().into_iter().collect();
 
-        // Phase 2: Mark reachable from roots as gray
-        trace!("Phase 2: Marking from roots");
-        self.mark_from_roots();
+        // Phase 1: Subtract internal references
+        trace!("Phase 1: Marking candidate subgraphs gray");
+        let mut examined = 0;
+        for &obj in &candidates {
+            examined += unsafe { self.mark_gray(obj) };
+        }
 
-        // Phase 3: Propagate gray to black (mark children)
-        trace!("Phase 3: Propagating marks");
-        self.propagate_marks();
+        // Phase 2: Restore everything reachable from outside the subgraph
+        trace!("Phase 2: Scanning for external references");
+        for &obj in &candidates {
+            unsafe { self.scan(obj, &roots) };
+        }
 
-        let reachable = self.reachable_objects.load(Ordering::Relaxed);
+        // Phase 3: Gather what stayed white
+        trace!("Phase 3: Collecting white objects");
+        let mut garbage = Vec::new();
+        for &obj in &candidates {
+            unsafe { self.collect_white(obj, &mut garbage) };
+        }
+
+        let reachable = examined - garbage.len();
+        self.total_objects.store(examined, Ordering::Relaxed);
+        self.reachable_objects.store(reachable, Ordering::Relaxed);
         log_gc_mark(reachable);
 
-        // Phase 4: Sweep white objects (unreachable cycles)
-        trace!("Phase 4: Sweeping unreachable objects");
-        self.sweep();
+        // Phase 4: Destroy unreachable cycles
+        trace!("Phase 4: Freeing unreachable cycles");
+        let collected = garbage.len();
+        if collected > 0 {
+            debug!(cycles_freed = collected, "Freeing detected cycles");
+            unsafe { self.free_garbage(&garbage) };
+        }
 
-        let collected = self.cycles_collected.load(Ordering::Relaxed);
-        log_gc_sweep(collected); // bytes_reclaimed tracked separately
+        self.cycles_collected.fetch_add(collected, Ordering::Relaxed);
+        log_gc_sweep(collected);
 
         debug!(
             event = "gc_cycle_complete",
-            total = candidate_count,
+            total = examined,
             reachable = reachable,
             collected = collected
         );
-
-        // Clear candidates for next cycle
-        self.candidates.clear();
     }
 
-    /// Phase 1: Initialize all candidates as white
-    fn mark_white(&self) {
-        self.candidates.iter().for_each(|entry| {
-            let header_ptr = *entry.key();
-            unsafe {
-                // Use flags field bits 0-1 for color marking
-                // 00 = white, 01 = gray, 10 = black
-                (*header_ptr).flags &= !0b11;
-            }
-        });
-    }
+    /// Remove the current candidates from the buffer, dropping dead ones
+    fn take_candidates(&self) -> Vec<*mut ObjectHeader> {
+        let snapshot: Vec<*mut ObjectHeader> = self.candidates.iter().map(|entry| *entry.key()).collect();
 
-    /// Phase 2: Mark roots and objects reachable from them as gray
-    fn mark_from_roots(&self) {
-        let roots = get_roots();
+        snapshot
+            .into_iter()
+            .filter(|&obj| {
+                self.candidates.remove(&obj);
+                unsafe { (*obj).refcount.load(Ordering::Acquire) > 0 }
+            })
+            .collect()
+    }
 
-        for root_ptr in roots {
-            if self.candidates.contains(&root_ptr) {
-                self.mark_gray(root_ptr);
-            }
+    /// Color the subgraph gray, decrementing each child once per edge
+    ///
+    /// Returns the number of objects newly colored.
+    unsafe fn mark_gray(&self, start: *mut ObjectHeader) -> usize {
+        if color(start) == Color::Gray {
+            return 0;
         }
-    }
 
-    /// Phase 3: Propagate gray marks to children (mark children gray, promote to black)
-    fn propagate_marks(&self) {
-        loop {
-            let obj_ptr = {
-                let mut gray = self.gray_set.lock();
-                gray.pop_front()
-            };
-
-            match obj_ptr {
-                None => break,
-                Some(ptr) => {
-                    unsafe {
-                        // Mark this object as black
-                        self.mark_black(ptr);
-
-                        // Mark children as gray (type-specific traversal)
-                        self.mark_children(ptr);
-                    }
-
-                    self.reachable_objects.fetch_add(1, Ordering::Relaxed);
+        set_color(start, Color::Gray);
+        let mut marked = 1;
+        let mut stack = vec![start];
+
+        while let Some(obj) = stack.pop() {
+            ObjectHeader::trace(obj, &mut |child| {
+                let child = ObjectHeader::from_object(child);
+                (*child).refcount.fetch_sub(1, Ordering::Relaxed);
+                if color(child) != Color::Gray {
+                    set_color(child, Color::Gray);
+                    marked += 1;
+                    stack.push(child);
                 }
-            }
+            });
         }
-    }
 
-    /// Phase 4: Free white objects (unreachable cycles)
-    fn sweep(&self) {
-        let to_free: Vec<*mut ObjectHeader> = self.candidates
-            .iter()
-            .filter_map(|entry| {
-                let header_ptr = *entry.key();
-                unsafe {
-                    let color = self.get_color(header_ptr);
-                    let refcount = (*header_ptr).refcount.load(std::sync::atomic::Ordering::Relaxed);
-
-                    if color == Color::White && refcount > 0 {
-                        // Found a cycle: refcount > 0 but unreachable
-                        trace!(
-                            address = ?header_ptr,
-                            refcount = refcount,
-                            "Detected unreachable cycle"
-                        );
-                        Some(header_ptr)
-                    } else {
-                        None
-                    }
-                }
-            })
-            .collect();
+        marked
+    }
 
-        let count = to_free.len();
-        self.cycles_collected.fetch_add(count, Ordering::Relaxed);
+    /// Decide gray objects: externally referenced ones turn black, the rest white
+    unsafe fn scan(&self, start: *mut ObjectHeader, roots: &HashSet<*mut ObjectHeader>) {
+        let mut stack = vec![start];
 
-        if count > 0 {
-            debug!(cycles_freed = count, "Freeing detected cycles");
-        }
+        while let Some(obj) = stack.pop() {
+            if color(obj) != Color::Gray {
+                continue;
+            }
 
-        // Free collected cycles
-        for header_ptr in to_free {
-            unsafe {
-                self.free_cycle(header_ptr);
+            if (*obj).refcount.load(Ordering::Relaxed) > 0 || roots.contains(&obj) {
+                self.scan_black(obj);
+            } else {
+                set_color(obj, Color::White);
+                ObjectHeader::trace(obj, &mut |child| {
+                    stack.push(ObjectHeader::from_object(child));
+                });
             }
         }
     }
 
-    /// Mark object as gray (discovered, needs scanning)
-    fn mark_gray(&self, obj: *mut ObjectHeader) {
-        unsafe {
-            (*obj).flags = ((*obj).flags & !0b11) | 0b01;
+    /// Mark object and its subgraph black, restoring the subtracted counts
+    unsafe fn scan_black(&self, start: *mut ObjectHeader) {
+        set_color(start, Color::Black);
+        let mut stack = vec![start];
+
+        while let Some(obj) = stack.pop() {
+            ObjectHeader::trace(obj, &mut |child| {
+                let child = ObjectHeader::from_object(child);
+                (*child).refcount.fetch_add(1, Ordering::Relaxed);
+                if color(child) != Color::Black {
+                    set_color(child, Color::Black);
+                    stack.push(child);
+                }
+            });
         }
-        self.gray_set.lock().push_back(obj);
     }
 
-    /// Mark object as black (scanned)
-    fn mark_black(&self, obj: *mut ObjectHeader) {
-        unsafe {
-            (*obj).flags = ((*obj).flags & !0b11) | 0b10;
-        }
-    }
+    /// Append white objects reachable from `start` to `garbage` (each once)
+    unsafe fn collect_white(&self, start: *mut ObjectHeader, garbage: &mut Vec<*mut ObjectHeader>) {
+        let mut stack = vec![start];
 
-    /// Get current color of object
-    fn get_color(&self, obj: *mut ObjectHeader) -> Color {
-        unsafe {
-            match (*obj).flags & 0b11 {
-                0b00 => Color::White,
-                0b01 => Color::Gray,
-                0b10 => Color::Black,
-                _ => Color::White,
+        while let Some(obj) = stack.pop() {
+            if color(obj) != Color::White {
+                continue;
             }
+
+            set_color(obj, Color::Black);
+            garbage.push(obj);
+            ObjectHeader::trace(obj, &mut |child| {
+                stack.push(ObjectHeader::from_object(child));
+            });
         }
     }
 
-    /// Mark children of object as gray
-    unsafe fn mark_children(&self, obj: *mut ObjectHeader) {
-        // Type-specific child traversal for cycle detection
-        let type_info = (*obj).type_info.as_ref();
-        let obj_ptr = (obj as *mut u8).add(core::mem::size_of::<ObjectHeader>());
-
-        use crate::objects::{ObjectType, ListData, DictData};
-
-        match type_info.object_type() {
-            ObjectType::List => {
-                let list_data = &*(obj_ptr as *const ListData);
-                // Mark all list elements
-                for i in 0..list_data.len {
-                    let elem = *list_data.ptr.add(i);
-                    if elem.is_ptr() {
-                        let child_header = ObjectHeader::from_object(elem.as_ptr().as_ptr() as *mut u8);
-                        self.mark_gray(child_header);
-                    }
-                }
-            }
-            ObjectType::Dict => {
-                let dict_data = &*(obj_ptr as *const DictData);
-                // Mark all dict keys and values
-                for i in 0..dict_data.capacity {
-                    let entry = &*(dict_data.ptr.add(i));
-                    if entry.hash != 0 {
-                        if entry.key.is_ptr() {
-                            let child_header = ObjectHeader::from_object(entry.key.as_ptr().as_ptr() as *mut u8);
-                            self.mark_gray(child_header);
-                        }
-                        if entry.value.is_ptr() {
-                            let child_header = ObjectHeader::from_object(entry.value.as_ptr().as_ptr() as *mut u8);
-                            self.mark_gray(child_header);
-                        }
-                    }
-                }
-            }
-            ObjectType::Instance => {
-                // Would traverse instance attributes dict
-                // For now, conservative - no children marked
-            }
-            _ => {
-                // Other types (strings, primitives) have no heap children
+    /// Destroy garbage objects without letting member refcounts reach zero
+    ///
+    /// Internal edges are restored first so every destructor's decrements are
+    /// balanced; the extra hold keeps members from being destroyed recursively
+    /// while their peers' destructors run.
+    unsafe fn free_garbage(&self, garbage: &[*mut ObjectHeader]) {
+        for &obj in garbage {
+            trace!(address = ?obj, "Detected unreachable cycle member");
+            ObjectHeader::trace(obj, &mut |child| {
+                let child = ObjectHeader::from_object(child);
+                (*child).refcount.fetch_add(1, Ordering::Relaxed);
+            });
+            (*obj).refcount.fetch_add(1, Ordering::Relaxed);
+        }
+
+        for &obj in garbage {
+            if let Some(drop_fn) = (*obj).type_info.as_ref().drop {
+                drop_fn(ObjectHeader::object(obj));
             }
         }
-    }
 
-    /// Free cycle by forcing refcount to 0
-    unsafe fn free_cycle(&self, header: *mut ObjectHeader) {
-        // Force refcount to 0 to trigger destruction
-        (*header).refcount.store(1, std::sync::atomic::Ordering::Relaxed);
+        for &obj in garbage {
+            (*obj).refcount.store(0, Ordering::Release);
+        }
 
-        // Call destructor if present (cleans up internal resources)
-        if let Some(drop_fn) = (*header).type_info.as_ref().drop {
-            let obj_ptr = (header as *mut u8).add(core::mem::size_of::<ObjectHeader>());
-            drop_fn(obj_ptr);
+        // Arena objects stay in their arena, which is reclaimed as a whole;
+        // objects from the system allocator are freed here, as
+        // `destroy_object` frees them
+        for &obj in garbage {
+            if crate::allocator::owns(obj as *const u8) {
+                continue;
+            }
+            let layout = std::alloc::Layout::from_size_align_unchecked(
+                (*obj).type_info.as_ref().size + core::mem::size_of::<ObjectHeader>(),
+                8,
+            );
+            std::alloc::dealloc(obj as *mut u8, layout);
         }
+    }
+}
 
-        // Note: Memory remains in arena until arena sweep
-        // The allocator uses arena-based allocation where memory is freed in bulk.
-        // Individual cycle memory will be reclaimed when entire arenas are released.
-        // This provides better performance than per-object deallocation.
+/// Get current color of object
+#[inline]
+unsafe fn color(obj: *mut ObjectHeader) -> Color {
+    match (*obj).flags & COLOR_MASK {
+        0b01 => Color::Gray,
+        0b10 => Color::White,
+        _ => Color::Black,
     }
 }
 
+#[inline]
+unsafe fn set_color(obj: *mut ObjectHeader, color: Color) {
+    (*obj).flags = ((*obj).flags & !COLOR_MASK) | color.bits();
+}
+
 /// Initialize cycle collector (idempotent)
 pub(super) fn init_collector() {
     debug!("Initializing cycle collector");
     Lazy::force(&COLLECTOR);
 }
 
-/// Run mark-sweep cycle collection
+/// Run trial-deletion cycle collection
 pub fn collect_cycles() {
     COLLECTOR.collect();
 }
@@ -311,6 +322,16 @@ pub fn register_potential_cycle(header: *mut ObjectHeader) {
     COLLECTOR.candidates.insert(header);
 }
 
+/// Drop a destroyed object from the candidate buffer
+///
+/// Called by the refcount destroy paths so the collector never visits freed memory.
+#[inline]
+pub(crate) fn forget_candidate(header: *mut ObjectHeader) {
+    if !COLLECTOR.candidates.is_empty() {
+        COLLECTOR.candidates.remove(&header);
+    }
+}
+
 /// Get collector statistics (lock-free reads)
 pub(super) fn collector_stats() -> super::GcStats {
     super::GcStats {
@@ -2,19 +2,26 @@
 //!
 //! Design: Hybrid approach optimized for Python semantics:
 //! 1. Reference counting (fast path, deterministic)
-//! 2. Tricolor mark-sweep (rare, cycles only)
+//! 2. Trial-deletion cycle collection (rare, cycles only)
 //! 3. Deferred collection (amortized cost)
+//!
+//! Threading: refcounts are atomic, so objects may be shared and released on
+//! any thread. Cycle collection stops every attached mutator at a safepoint
+//! first (see `safepoint`).
 
 mod refcount;
 mod cycles;
 mod roots;
+mod safepoint;
 
 #[cfg(test)]
 mod tests;
 
 pub use refcount::RefCount;
 pub use cycles::{collect_cycles, register_potential_cycle};
+pub(crate) use cycles::forget_candidate;
 pub use roots::{register_root, unregister_root, RootGuard};
+pub use safepoint::{attach_thread, detach_thread, safepoint, blocking, attached_threads, MutatorGuard};
 
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Instant;
@@ -70,21 +77,31 @@ pub fn init() {
     );
 }
 
-/// Final cleanup and collection
-pub fn cleanup() {
+/// Final cleanup and collection, then free the arenas retired by exited
+/// threads
+///
+/// # Safety
+/// Call once, at shutdown: no object allocated by an exited thread may be
+/// used afterwards, from any thread.
+pub(crate) unsafe fn cleanup() {
     info!("GC subsystem cleanup starting");
 
     let start = Instant::now();
     collect_cycles();
     roots::clear_roots();
+    crate::allocator::reclaim_retired();
 
     let stats = stats();
     log_gc_complete(start.elapsed().as_micros() as u64, 0, stats.total_objects);
 }
 
 /// Trigger GC if threshold exceeded (called after allocations)
+///
+/// Allocation sites double as safepoints for attached mutator threads.
 #[inline]
 pub fn maybe_collect() {
+    safepoint();
+
     if GC_STATE.increment_objects() {
         debug!(
             object_count = GC_STATE.objects_since_collection.load(Ordering::Relaxed),
@@ -90,6 +90,7 @@ impl<T> RefCount<T> {
         let h = header(self.ptr.as_ptr());
 
         trace!(event = "refcount_destroy", address = ?self.ptr.as_ptr(), count = 0);
+        super::cycles::forget_candidate(ObjectHeader::from_object(self.ptr.as_ptr() as *mut u8));
 
         // Call type-specific destructor if present
         if let Some(drop_fn) = h.type_info.as_ref().drop {
@@ -0,0 +1,258 @@
+//! Stop-the-world coordination - mutator safepoints for the cycle collector
+//!
+//! Refcount operations are atomic and safe from any thread, but trial deletion
+//! temporarily rewrites refcounts across whole subgraphs, so no mutator may run
+//! while it does. Threads running compiled code attach as mutators and poll
+//! `safepoint()` (allocation does this automatically); the collector raises a
+//! stop request and waits until every attached thread is parked.
+//!
+//! Threads about to block (I/O, channel receives, joins) wrap the wait in
+//! `blocking()` so they count as parked instead of stalling the collector.
+//! Unattached threads are never waited for and must not touch shared objects
+//! while collections can run.
+
+use core::cell::Cell;
+use parking_lot::{Condvar, Mutex};
+use once_cell::sync::Lazy;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, ThreadId};
+use crate::logging::{debug, trace};
+
+/// Global world state (stop flag read lock-free on the fast path)
+static WORLD: Lazy<World> = Lazy::new(World::new);
+
+/// Set while a collector is stopping or has stopped the world
+static STOP_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// Attachment of the current thread (detached automatically on exit)
+    static MUTATOR: Mutator = const { Mutator { attached: Cell::new(false) } };
+}
+
+struct World {
+    state: Mutex<WorldState>,
+    changed: Condvar,
+}
+
+struct WorldState {
+    /// Attached mutator threads
+    attached: usize,
+    /// Attached threads parked at a safepoint or inside `blocking()`
+    parked: usize,
+    /// A collector owns the world
+    stopping: bool,
+    /// Thread that owns the world while stopped
+    owner: Option<ThreadId>,
+}
+
+impl WorldState {
+    /// The current thread stopped the world (its own safepoints must not park)
+    fn owned_by_current(&self) -> bool {
+        self.owner == Some(thread::current().id())
+    }
+}
+
+impl World {
+    fn new() -> Self {
+        Self {
+            state: Mutex::new(WorldState { attached: 0, parked: 0, stopping: false, owner: None }),
+            changed: Condvar::new(),
+        }
+    }
+}
+
+struct Mutator {
+    attached: Cell<bool>,
+}
+
+impl Drop for Mutator {
+    fn drop(&mut self) {
+        if self.attached.get() {
+            let mut state = WORLD.state.lock();
+            state.attached -= 1;
+            WORLD.changed.notify_all();
+        }
+    }
+}
+
+#[inline]
+fn is_attached() -> bool {
+    MUTATOR.try_with(|m| m.attached.get()).unwrap_or(false)
+}
+
+/// Register the current thread as a mutator (idempotent)
+pub fn attach_thread() {
+    MUTATOR.with(|m| {
+        if m.attached.get() {
+            return;
+        }
+
+        // Never join a world that is currently stopped
+        let mut state = WORLD.state.lock();
+        while state.stopping {
+            WORLD.changed.wait(&mut state);
+        }
+        state.attached += 1;
+        m.attached.set(true);
+
+        trace!(attached = state.attached, "Mutator thread attached");
+    });
+}
+
+/// Unregister the current thread (idempotent; also happens on thread exit)
+pub fn detach_thread() {
+    let _ = MUTATOR.try_with(|m| {
+        if !m.attached.replace(false) {
+            return;
+        }
+
+        let mut state = WORLD.state.lock();
+        state.attached -= 1;
+        WORLD.changed.notify_all();
+
+        trace!(attached = state.attached, "Mutator thread detached");
+    });
+}
+
+/// Park here if a collector is waiting to stop the world (hot path)
+#[inline]
+pub fn safepoint() {
+    if STOP_REQUESTED.load(Ordering::Acquire) && is_attached() {
+        park();
+    }
+}
+
+#[cold]
+fn park() {
+    let mut state = WORLD.state.lock();
+    if !state.stopping || state.owned_by_current() {
+        return;
+    }
+
+    state.parked += 1;
+    WORLD.changed.notify_all();
+    while state.stopping {
+        WORLD.changed.wait(&mut state);
+    }
+    state.parked -= 1;
+}
+
+/// Run `f` as a parked mutator (for calls that may block indefinitely)
+///
+/// `f` must not touch runtime objects: the collector may run meanwhile.
+pub fn blocking<R>(f: impl FnOnce() -> R) -> R {
+    if !is_attached() {
+        return f();
+    }
+
+    {
+        let mut state = WORLD.state.lock();
+        if state.owned_by_current() {
+            drop(state);
+            return f();
+        }
+        state.parked += 1;
+        WORLD.changed.notify_all();
+    }
+
+    let result = f();
+
+    // Resume only once the world is running again
+    let mut state = WORLD.state.lock();
+    while state.stopping {
+        WORLD.changed.wait(&mut state);
+    }
+    state.parked -= 1;
+
+    result
+}
+
+/// RAII mutator registration for the current thread
+///
+/// Usage:
+/// ```ignore
+/// std::thread::spawn(|| {
+///     let _mutator = MutatorGuard::new();
+///     // runtime objects may be used here
+/// });
+/// ```
+pub struct MutatorGuard {
+    _not_send: core::marker::PhantomData<*const ()>,
+}
+
+impl MutatorGuard {
+    #[inline]
+    pub fn new() -> Self {
+        attach_thread();
+        Self { _not_send: core::marker::PhantomData }
+    }
+}
+
+impl Default for MutatorGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for MutatorGuard {
+    #[inline]
+    fn drop(&mut self) {
+        detach_thread();
+    }
+}
+
+/// Exclusive ownership of the world; mutators resume when dropped
+pub(super) struct WorldStop {
+    _private: (),
+}
+
+/// Stop every attached mutator (other than the caller) at a safepoint
+///
+/// Concurrent collectors serialize here; a losing attached caller parks
+/// like any other mutator until the winner releases the world.
+pub(super) fn stop_the_world() -> WorldStop {
+    let attached = is_attached();
+    let mut state = WORLD.state.lock();
+
+    while state.stopping {
+        if attached {
+            state.parked += 1;
+            WORLD.changed.notify_all();
+        }
+        while state.stopping {
+            WORLD.changed.wait(&mut state);
+        }
+        if attached {
+            state.parked -= 1;
+        }
+    }
+
+    state.stopping = true;
+    state.owner = Some(thread::current().id());
+    STOP_REQUESTED.store(true, Ordering::Release);
+
+    let own = usize::from(attached);
+    while state.parked + own < state.attached {
+        WORLD.changed.wait(&mut state);
+    }
+
+    debug!(mutators = state.attached, "World stopped");
+    WorldStop { _private: () }
+}
+
+impl Drop for WorldStop {
+    fn drop(&mut self) {
+        let mut state = WORLD.state.lock();
+        state.stopping = false;
+        state.owner = None;
+        STOP_REQUESTED.store(false, Ordering::Release);
+        WORLD.changed.notify_all();
+
+        trace!("World resumed");
+    }
+}
+
+/// Number of attached mutator threads
+pub fn attached_threads() -> usize {
+    WORLD.state.lock().attached
+}
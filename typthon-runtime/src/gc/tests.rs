@@ -13,6 +13,7 @@ unsafe fn create_test_object() -> *mut ObjectHeader {
         align: 8,
         type_id: 1,
         drop: None,
+        trace: None,
     };
 
     let layout = std::alloc::Layout::from_size_align(64 + 16, 8).unwrap();
@@ -227,6 +228,9 @@ mod roots_tests {
 #[cfg(test)]
 mod cycles_tests {
     use super::*;
+    use crate::builtins::{py_list_new, py_list_append, py_list_get, py_list_len};
+    use crate::objects::PyObject;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     #[test]
     fn test_register_potential_cycle() {
@@ -276,6 +280,145 @@ mod cycles_tests {
             }
         }
     }
+
+    /// Test node: one child slot, destructor releases it and counts the call
+    #[repr(C)]
+    struct TestNode {
+        child: *mut u8,
+    }
+
+    static NODE_DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    static NODE_TYPE: TypeInfo = TypeInfo::with_drop(64, 8, 1, node_drop).with_trace(node_trace);
+
+    unsafe fn node_drop(ptr: *mut u8) {
+        NODE_DROPS.fetch_add(1, Ordering::SeqCst);
+        crate::ffi::typthon_decref((*(ptr as *mut TestNode)).child);
+    }
+
+    unsafe fn node_trace(ptr: *mut u8, visit: &mut dyn FnMut(*mut u8)) {
+        let child = (*(ptr as *const TestNode)).child;
+        if !child.is_null() {
+            visit(child);
+        }
+    }
+
+    unsafe fn create_node() -> *mut ObjectHeader {
+        let layout = std::alloc::Layout::from_size_align(64 + 16, 8).unwrap();
+        let header = std::alloc::alloc(layout) as *mut ObjectHeader;
+        (*header) = ObjectHeader::new(NonNull::from(&NODE_TYPE));
+        (*(ObjectHeader::object(header) as *mut TestNode)).child = core::ptr::null_mut();
+        header
+    }
+
+    unsafe fn link(from: *mut ObjectHeader, to: *mut ObjectHeader) {
+        let child = ObjectHeader::object(to);
+        crate::ffi::typthon_incref(child);
+        (*(ObjectHeader::object(from) as *mut TestNode)).child = child;
+    }
+
+    fn header_of(obj: PyObject) -> *mut ObjectHeader {
+        unsafe { ObjectHeader::from_object(obj.as_ptr().as_ptr() as *mut u8) }
+    }
+
+    fn refcount(obj: PyObject) -> u32 {
+        crate::ffi::typthon_refcount(obj.as_ptr().as_ptr() as *const u8)
+    }
+
+    #[test]
+    fn test_force_collect_runs_destructors_of_cycle() {
+        init();
+
+        unsafe {
+            let a = create_node();
+            let b = create_node();
+            link(a, b);
+            link(b, a);
+
+            // Drop the external references: only the cycle keeps them alive
+            crate::ffi::typthon_decref(ObjectHeader::object(a));
+            crate::ffi::typthon_decref(ObjectHeader::object(b));
+
+            let drops = NODE_DROPS.load(Ordering::SeqCst);
+            register_potential_cycle(a);
+            register_potential_cycle(b);
+            force_collect();
+
+            // Both nodes came from the system allocator, so the collector freed them
+            assert_eq!(NODE_DROPS.load(Ordering::SeqCst) - drops, 2);
+        }
+    }
+
+    #[test]
+    fn test_force_collect_reclaims_list_cycle() {
+        init();
+
+        let a = py_list_new();
+        let b = py_list_new();
+        py_list_append(a, b);
+        py_list_append(b, a);
+
+        crate::ffi::typthon_decref(a.as_ptr().as_ptr() as *mut u8);
+        crate::ffi::typthon_decref(b.as_ptr().as_ptr() as *mut u8);
+        assert_eq!(refcount(a), 1);
+        assert_eq!(refcount(b), 1);
+
+        let before = stats().cycles_collected;
+        register_potential_cycle(header_of(a));
+        register_potential_cycle(header_of(b));
+        force_collect();
+
+        assert!(stats().cycles_collected >= before + 2);
+        assert_eq!(refcount(a), 0);
+        assert_eq!(refcount(b), 0);
+    }
+
+    #[test]
+    fn test_acyclic_candidates_survive() {
+        init();
+
+        let outer = py_list_new();
+        let inner = py_list_new();
+        py_list_append(inner, PyObject::from_int(7));
+        py_list_append(outer, inner);
+
+        // inner is now only owned by outer; outer keeps its external reference
+        crate::ffi::typthon_decref(inner.as_ptr().as_ptr() as *mut u8);
+
+        register_potential_cycle(header_of(outer));
+        register_potential_cycle(header_of(inner));
+        force_collect();
+
+        assert_eq!(refcount(outer), 1);
+        assert_eq!(refcount(inner), 1);
+        assert_eq!(py_list_len(outer), 1);
+        assert_eq!(py_list_get(outer, 0).as_ptr(), inner.as_ptr());
+        assert_eq!(py_list_get(inner, 0).as_int(), 7);
+    }
+
+    #[test]
+    fn test_rooted_cycle_survives() {
+        init();
+
+        let a = py_list_new();
+        let b = py_list_new();
+        py_list_append(a, b);
+        py_list_append(b, a);
+        crate::ffi::typthon_decref(b.as_ptr().as_ptr() as *mut u8);
+
+        // a stays referenced through a root registration only
+        let _guard = RootGuard::new(a.as_ptr().as_ptr() as *mut u8);
+        crate::ffi::typthon_decref(a.as_ptr().as_ptr() as *mut u8);
+
+        register_potential_cycle(header_of(a));
+        register_potential_cycle(header_of(b));
+        force_collect();
+
+        assert_eq!(refcount(a), 1);
+        assert_eq!(refcount(b), 1);
+        assert_eq!(py_list_get(a, 0).as_ptr(), b.as_ptr());
+        assert_eq!(py_list_get(b, 0).as_ptr(), a.as_ptr());
+    }
 }
 
 #[cfg(test)]
@@ -424,3 +567,159 @@ mod integration_tests {
         }
     }
 }
+
+#[cfg(test)]
+mod threading_tests {
+    use super::*;
+    use crate::builtins::{py_list_new, py_list_append, py_list_get, py_list_len};
+    use crate::ffi::{typthon_incref, typthon_decref, typthon_object_new, typthon_refcount};
+    use crate::objects::PyObject;
+    use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+    use std::sync::{mpsc, Mutex};
+
+    const LIVE: u64 = 0x1111;
+    const DEAD: u64 = 0xDEAD;
+
+    static PAYLOAD_DROPS: AtomicUsize = AtomicUsize::new(0);
+    static DOUBLE_DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    /// Arena-allocated leaf object whose destructor detects double frees
+    static PAYLOAD_TYPE: TypeInfo = TypeInfo::with_drop(8, 8, 255, payload_drop);
+
+    unsafe fn payload_drop(ptr: *mut u8) {
+        let state = &*(ptr as *const AtomicU64);
+        if state.swap(DEAD, Ordering::SeqCst) == LIVE {
+            PAYLOAD_DROPS.fetch_add(1, Ordering::SeqCst);
+        } else {
+            DOUBLE_DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn new_payload() -> *mut u8 {
+        let obj = typthon_object_new(&PAYLOAD_TYPE, 8);
+        assert!(!obj.is_null());
+        unsafe { (obj as *mut AtomicU64).write(AtomicU64::new(LIVE)) };
+        obj
+    }
+
+    fn as_object(ptr: *mut u8) -> PyObject {
+        PyObject::from_ptr(NonNull::new(ptr).unwrap().cast())
+    }
+
+    fn raw(obj: PyObject) -> *mut u8 {
+        obj.as_ptr().as_ptr() as *mut u8
+    }
+
+    /// Turn `list` into a self-cycle, drop the caller's reference, and buffer it
+    fn abandon_as_cycle(list: PyObject) {
+        py_list_append(list, list);
+        typthon_decref(raw(list));
+        unsafe { register_potential_cycle(ObjectHeader::from_object(raw(list))) };
+    }
+
+    #[test]
+    fn test_objects_shared_across_threads_through_channel() {
+        const LISTS: usize = 64;
+        const PAYLOADS_PER_LIST: usize = 3;
+        const WORKERS: usize = 4;
+
+        init();
+
+        let shared = new_payload() as usize;
+        let drops_before = PAYLOAD_DROPS.load(Ordering::SeqCst);
+        let (tx, rx) = mpsc::channel::<PyObject>();
+
+        // Producer allocates everything on its own heap, then exits
+        thread::spawn(move || {
+            let _mutator = MutatorGuard::new();
+            for _ in 0..LISTS {
+                let list = py_list_new();
+                for _ in 0..PAYLOADS_PER_LIST {
+                    let payload = new_payload();
+                    py_list_append(list, as_object(payload));
+                    typthon_decref(payload);
+                }
+                py_list_append(list, as_object(shared as *mut u8));
+                tx.send(list).unwrap();
+            }
+        })
+        .join()
+        .unwrap();
+
+        let rx = Arc::new(Mutex::new(rx));
+        let workers: Vec<_> = (0..WORKERS)
+            .map(|_| {
+                let rx = Arc::clone(&rx);
+                thread::spawn(move || {
+                    let _mutator = MutatorGuard::new();
+                    let mut handled = 0;
+
+                    while let Ok(list) = blocking(|| rx.lock().unwrap().recv()) {
+                        // Churn refcounts of objects allocated by the exited producer
+                        for i in 0..py_list_len(list) {
+                            let item = raw(py_list_get(list, i as isize));
+                            for _ in 0..16 {
+                                typthon_incref(item);
+                                safepoint();
+                                typthon_decref(item);
+                            }
+                        }
+
+                        abandon_as_cycle(list);
+                        handled += 1;
+                        if handled % 4 == 0 {
+                            force_collect();
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        force_collect();
+
+        assert_eq!(DOUBLE_DROPS.load(Ordering::SeqCst), 0);
+        assert_eq!(PAYLOAD_DROPS.load(Ordering::SeqCst) - drops_before, LISTS * PAYLOADS_PER_LIST);
+        assert_eq!(typthon_refcount(shared as *const u8), 1);
+
+        typthon_decref(shared as *mut u8);
+        assert_eq!(PAYLOAD_DROPS.load(Ordering::SeqCst) - drops_before, LISTS * PAYLOADS_PER_LIST + 1);
+        assert_eq!(DOUBLE_DROPS.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_collection_stops_attached_threads_at_safepoints() {
+        init();
+
+        let running = Arc::new(std::sync::atomic::AtomicBool::new(true));
+        let spinner = {
+            let running = Arc::clone(&running);
+            thread::spawn(move || {
+                let _mutator = MutatorGuard::new();
+                while running.load(Ordering::SeqCst) {
+                    safepoint();
+                }
+            })
+        };
+        let (release, waiter_rx) = mpsc::channel::<()>();
+        let waiter = thread::spawn(move || {
+            let _mutator = MutatorGuard::new();
+            blocking(|| waiter_rx.recv()).unwrap();
+        });
+
+        let list = py_list_new();
+        let before = stats().cycles_collected;
+        abandon_as_cycle(list);
+        force_collect();
+
+        assert!(stats().cycles_collected > before);
+        assert_eq!(typthon_refcount(raw(list)), 0);
+
+        running.store(false, Ordering::SeqCst);
+        release.send(()).unwrap();
+        spinner.join().unwrap();
+        waiter.join().unwrap();
+    }
+}
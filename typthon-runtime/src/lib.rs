@@ -42,11 +42,16 @@ pub extern "C" fn typthon_runtime_init() {
 }
 
 /// Runtime cleanup
+///
+/// # Safety
+/// Call once, at program exit: the arenas of exited threads are freed, so
+/// no object may be used afterwards
 #[no_mangle]
 pub extern "C" fn typthon_runtime_cleanup() {
     log_runtime_shutdown();
     // Cleanup subsystems would go here
     // builtins::cleanup();
-    // gc::cleanup();
+    // Safety: the program is exiting and done with its objects
+    unsafe { gc::cleanup() };
 }
 
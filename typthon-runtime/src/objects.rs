@@ -148,6 +148,7 @@ impl PyObject {
                 ObjectType::List => crate::builtins::py_list_len(self) > 0,
                 ObjectType::Dict => crate::builtins::py_dict_len(self) > 0,
                 ObjectType::Tuple => crate::builtins::py_tuple_len(self) > 0,
+                ObjectType::FrozenSet => crate::builtins::py_frozenset_len(self) > 0,
                 _ => true,
             }
         }
@@ -207,6 +208,14 @@ impl PyObject {
                     s.push(')');
                     s
                 }
+                ObjectType::FrozenSet => {
+                    let items = crate::builtins::py_frozenset_items(self);
+                    if items.is_empty() {
+                        return "frozenset()".to_string();
+                    }
+                    let items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+                    format!("frozenset({{{}}})", items.join(", "))
+                }
                 ObjectType::Dict => format!("<dict at {:p}>", self.as_ptr()),
                 _ => format!("<{:?} at {:p}>", self.get_type(), self.as_ptr()),
             }
@@ -214,42 +223,11 @@ impl PyObject {
     }
 
     /// Hash object (for dict keys)
+    ///
+    /// See `builtins::py_object_hash`; unhashable objects yield `u64::MAX`
+    /// (the `-1` sentinel) and leave an error in the error slot.
     pub fn hash(self) -> u64 {
-        use std::hash::{Hash, Hasher};
-        use std::collections::hash_map::DefaultHasher;
-
-        let mut hasher = DefaultHasher::new();
-
-        if self.is_int() {
-            self.as_int().hash(&mut hasher);
-        } else if self.is_special() {
-            self.get_type().hash(&mut hasher);
-        } else {
-            match self.get_type() {
-                ObjectType::Float => {
-                    let val = crate::builtins::py_float_as_f64(self);
-                    val.to_bits().hash(&mut hasher);
-                }
-                ObjectType::String => {
-                    crate::builtins::py_string_as_str(self).hash(&mut hasher);
-                }
-                ObjectType::Tuple => {
-                    // Hash each element
-                    let len = crate::builtins::py_tuple_len(self);
-                    len.hash(&mut hasher);
-                    for i in 0..len {
-                        let item = crate::builtins::py_tuple_get(self, i as isize);
-                        item.hash().hash(&mut hasher);
-                    }
-                }
-                _ => {
-                    // For unhashable types, use identity
-                    (self.as_ptr().as_ptr() as usize).hash(&mut hasher);
-                }
-            }
-        }
-
-        hasher.finish()
+        crate::builtins::py_object_hash(self) as u64
     }
 
     /// Heap-allocate large integer
@@ -270,17 +248,24 @@ impl PyObject {
 }
 
 /// Heap-allocated Python object
+///
+/// Object pointers address the data; the header sits immediately before it
+/// (see `ObjectHeader::from_object`).
 #[repr(C)]
 pub struct HeapObject {
-    header: ObjectHeader,
     data: ObjectData,
 }
 
 impl HeapObject {
+    #[inline]
+    fn header(&self) -> &ObjectHeader {
+        unsafe { &*ObjectHeader::from_object(self as *const Self as *mut u8) }
+    }
+
     #[inline]
     fn type_id(&self) -> ObjectType {
         unsafe {
-            let type_info = self.header.type_info().as_ref();
+            let type_info = self.header().type_info().as_ref();
             type_info.object_type()
         }
     }
@@ -306,6 +291,7 @@ pub union ObjectData {
     pub list: ListData,
     pub dict: DictData,
     pub tuple: TupleData,
+    pub frozenset: FrozenSetData,
     pub function: FunctionData,
     pub class: ClassData,
     pub instance: InstanceData,
@@ -361,6 +347,24 @@ pub struct TupleData {
     pub elements: [PyObject; 0], // Flexible array member
 }
 
+/// Frozenset object data (immutable open-addressed table)
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct FrozenSetData {
+    pub len: usize,
+    pub capacity: usize,
+    pub hash: i64,                // Cached hash, -1 until first computed
+    pub entries: [SetEntry; 0],   // Flexible array member
+}
+
+/// Frozenset slot; `hash == -1` marks an empty slot
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct SetEntry {
+    pub hash: i64,
+    pub key: PyObject,
+}
+
 /// Function object data
 #[repr(C)]
 #[derive(Clone, Copy)]
@@ -396,6 +400,58 @@ pub struct InstanceData {
     pub attrs: *mut DictData,
 }
 
+/// Visit a child reference if it points to a heap object
+#[inline]
+pub fn trace_object(obj: PyObject, visit: &mut dyn FnMut(*mut u8)) {
+    if obj.is_ptr() {
+        visit(obj.as_ptr().as_ptr() as *mut u8);
+    }
+}
+
+/// Visit keys and values of every occupied slot in a dict table
+///
+/// # Safety
+/// `data` must be null or point to an initialized dict table.
+pub unsafe fn trace_dict_data(data: *const DictData, visit: &mut dyn FnMut(*mut u8)) {
+    if data.is_null() || (*data).ptr.is_null() {
+        return;
+    }
+
+    for i in 0..(*data).capacity {
+        let entry = &*(*data).ptr.add(i);
+        if entry.hash != 0 {
+            trace_object(entry.key, visit);
+            trace_object(entry.value, visit);
+        }
+    }
+}
+
+/// Trace function for instances: class object plus attribute values
+///
+/// # Safety
+/// `ptr` must point to initialized `InstanceData`.
+pub unsafe fn trace_instance(ptr: *mut u8, visit: &mut dyn FnMut(*mut u8)) {
+    let data = &*(ptr as *const InstanceData);
+    trace_object(data.class, visit);
+    trace_dict_data(data.attrs, visit);
+}
+
+/// Trace function for functions: captured closure variables
+///
+/// # Safety
+/// `ptr` must point to initialized `FunctionData`.
+pub unsafe fn trace_function(ptr: *mut u8, visit: &mut dyn FnMut(*mut u8)) {
+    let data = &*(ptr as *const FunctionData);
+    if data.closure.is_null() {
+        return;
+    }
+
+    let captures = (*data.closure).captures.as_ptr();
+    for i in 0..(*data.closure).len {
+        trace_object(*captures.add(i), visit);
+    }
+}
+
 /// Object types for dispatch
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(u8)]
@@ -411,6 +467,7 @@ pub enum ObjectType {
     Function = 8,
     Class = 9,
     Instance = 10,
+    FrozenSet = 11,
     Unknown = 255,
 }
 
@@ -0,0 +1,29 @@
+//! Runtime shutdown
+//!
+//! Cleanup frees the arenas of every exited thread, which no other test may
+//! still be using, so it runs in a test binary of its own.
+
+use typthon_runtime::allocator::{heaps, owns, with_thread_allocator};
+use typthon_runtime::{typthon_runtime_cleanup, typthon_runtime_init};
+
+#[test]
+fn retired_arenas_are_released_at_shutdown() {
+    typthon_runtime_init();
+
+    let (addr, heap) = std::thread::spawn(|| {
+        let ptr = with_thread_allocator(|alloc| alloc.alloc(64, 8).expect("alloc"));
+        let heap = with_thread_allocator(|alloc| alloc.heap().expect("registered heap").id());
+        (ptr.as_ptr() as usize, heap)
+    })
+    .join()
+    .unwrap();
+
+    // Retired when the thread exited, kept until shutdown
+    assert!(owns(addr as *const u8));
+    assert!(heaps().iter().any(|record| record.id() == heap));
+
+    typthon_runtime_cleanup();
+
+    assert!(!owns(addr as *const u8));
+    assert!(!heaps().iter().any(|record| record.id() == heap));
+}